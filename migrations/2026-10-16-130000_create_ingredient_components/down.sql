@@ -0,0 +1,2 @@
+DROP TABLE ingredient_components;
+DROP TABLE ingredient_compounds;
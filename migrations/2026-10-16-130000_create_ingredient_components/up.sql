@@ -0,0 +1,44 @@
+CREATE TABLE ingredient_compounds (
+    ingredient_id INTEGER PRIMARY KEY NOT NULL,
+    quantity REAL NOT NULL,
+    quantity_units TEXT CHECK ( quantity_units IN (
+        'cups',
+        'fluid_ounces',
+        'grams',
+        'kilograms',
+        'kiloliters',
+        'liters',
+        'milligrams',
+        'milliliters',
+        'ounces',
+        'pounds',
+        'quart',
+        'tablespoons',
+        'teaspoons'
+    ) ),
+    FOREIGN KEY(ingredient_id) REFERENCES ingredients(id)
+);
+
+CREATE TABLE ingredient_components (
+    id INTEGER PRIMARY KEY NOT NULL,
+    compound_id INTEGER NOT NULL,
+    component_id INTEGER NOT NULL,
+    quantity REAL NOT NULL,
+    quantity_units TEXT CHECK ( quantity_units IN (
+        'cups',
+        'fluid_ounces',
+        'grams',
+        'kilograms',
+        'kiloliters',
+        'liters',
+        'milligrams',
+        'milliliters',
+        'ounces',
+        'pounds',
+        'quart',
+        'tablespoons',
+        'teaspoons'
+    ) ),
+    FOREIGN KEY(compound_id) REFERENCES ingredient_compounds(ingredient_id),
+    FOREIGN KEY(component_id) REFERENCES ingredients(id)
+);
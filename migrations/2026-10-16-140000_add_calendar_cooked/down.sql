@@ -0,0 +1 @@
+ALTER TABLE calendar DROP COLUMN cooked;
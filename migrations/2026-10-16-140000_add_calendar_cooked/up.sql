@@ -0,0 +1 @@
+ALTER TABLE calendar ADD COLUMN cooked BOOLEAN NOT NULL DEFAULT 0;
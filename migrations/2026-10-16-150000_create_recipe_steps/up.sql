@@ -0,0 +1,7 @@
+CREATE TABLE recipe_steps (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    day INTEGER NOT NULL,
+    label VARCHAR NOT NULL,
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+);
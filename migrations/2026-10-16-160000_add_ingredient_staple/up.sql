@@ -0,0 +1 @@
+ALTER TABLE ingredients ADD COLUMN staple BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+ALTER TABLE ingredients ADD COLUMN category VARCHAR;
+
+UPDATE ingredients SET category = (
+    SELECT ingredient_categories.name FROM ingredient_categories
+    WHERE ingredient_categories.id = ingredients.category_id
+);
+
+ALTER TABLE ingredients DROP COLUMN category_id;
+
+DROP TABLE ingredient_categories;
@@ -0,0 +1,20 @@
+CREATE TABLE ingredient_categories (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name VARCHAR NOT NULL UNIQUE
+);
+
+-- Categories used to be free text, so ones which only differ by case or surrounding whitespace
+-- are merged together.
+INSERT INTO ingredient_categories (name)
+SELECT MIN(TRIM(category)) FROM ingredients
+WHERE category IS NOT NULL AND TRIM(category) != ''
+GROUP BY LOWER(TRIM(category));
+
+ALTER TABLE ingredients ADD COLUMN category_id INTEGER REFERENCES ingredient_categories(id);
+
+UPDATE ingredients SET category_id = (
+    SELECT ingredient_categories.id FROM ingredient_categories
+    WHERE LOWER(ingredient_categories.name) = LOWER(TRIM(ingredients.category))
+);
+
+ALTER TABLE ingredients DROP COLUMN category;
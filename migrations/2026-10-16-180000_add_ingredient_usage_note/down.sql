@@ -0,0 +1 @@
+ALTER TABLE ingredient_usages DROP COLUMN note;
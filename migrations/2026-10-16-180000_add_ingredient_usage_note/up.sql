@@ -0,0 +1 @@
+ALTER TABLE ingredient_usages ADD COLUMN note TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,125 @@
+CREATE TABLE ingredient_usages_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    ingredient_id INTEGER NOT NULL,
+    quantity REAL NOT NULL,
+    quantity_units TEXT CHECK ( quantity_units IN (
+        'cups',
+        'fluid_ounces',
+        'grams',
+        'kilograms',
+        'kiloliters',
+        'liters',
+        'milligrams',
+        'milliliters',
+        'ounces',
+        'pounds',
+        'quart',
+        'tablespoons',
+        'teaspoons'
+    ) ),
+    note TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id),
+    FOREIGN KEY(ingredient_id) REFERENCES ingredients(id)
+);
+INSERT INTO ingredient_usages_new SELECT * FROM ingredient_usages;
+DROP TABLE ingredient_usages;
+ALTER TABLE ingredient_usages_new RENAME TO ingredient_usages;
+
+CREATE TABLE ingredient_calories_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    ingredient_id INTEGER NOT NULL,
+    calories REAL NOT NULL,
+    quantity REAL NOT NULL,
+    quantity_units TEXT CHECK ( quantity_units IN (
+        'cups',
+        'fluid_ounces',
+        'grams',
+        'kilograms',
+        'kiloliters',
+        'liters',
+        'milligrams',
+        'milliliters',
+        'ounces',
+        'pounds',
+        'quart',
+        'tablespoons',
+        'teaspoons'
+    ) ),
+    FOREIGN KEY(ingredient_id) REFERENCES ingredients(id)
+);
+INSERT INTO ingredient_calories_new SELECT * FROM ingredient_calories;
+DROP TABLE ingredient_calories;
+ALTER TABLE ingredient_calories_new RENAME TO ingredient_calories;
+
+CREATE TABLE calendar_new (
+    day DATE PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    cooked BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+);
+INSERT INTO calendar_new SELECT * FROM calendar;
+DROP TABLE calendar;
+ALTER TABLE calendar_new RENAME TO calendar;
+
+CREATE TABLE ingredient_compounds_new (
+    ingredient_id INTEGER PRIMARY KEY NOT NULL,
+    quantity REAL NOT NULL,
+    quantity_units TEXT CHECK ( quantity_units IN (
+        'cups',
+        'fluid_ounces',
+        'grams',
+        'kilograms',
+        'kiloliters',
+        'liters',
+        'milligrams',
+        'milliliters',
+        'ounces',
+        'pounds',
+        'quart',
+        'tablespoons',
+        'teaspoons'
+    ) ),
+    FOREIGN KEY(ingredient_id) REFERENCES ingredients(id)
+);
+INSERT INTO ingredient_compounds_new SELECT * FROM ingredient_compounds;
+DROP TABLE ingredient_compounds;
+ALTER TABLE ingredient_compounds_new RENAME TO ingredient_compounds;
+
+CREATE TABLE ingredient_components_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    compound_id INTEGER NOT NULL,
+    component_id INTEGER NOT NULL,
+    quantity REAL NOT NULL,
+    quantity_units TEXT CHECK ( quantity_units IN (
+        'cups',
+        'fluid_ounces',
+        'grams',
+        'kilograms',
+        'kiloliters',
+        'liters',
+        'milligrams',
+        'milliliters',
+        'ounces',
+        'pounds',
+        'quart',
+        'tablespoons',
+        'teaspoons'
+    ) ),
+    FOREIGN KEY(compound_id) REFERENCES ingredient_compounds(ingredient_id),
+    FOREIGN KEY(component_id) REFERENCES ingredients(id)
+);
+INSERT INTO ingredient_components_new SELECT * FROM ingredient_components;
+DROP TABLE ingredient_components;
+ALTER TABLE ingredient_components_new RENAME TO ingredient_components;
+
+CREATE TABLE recipe_steps_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    day INTEGER NOT NULL,
+    label VARCHAR NOT NULL,
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+);
+INSERT INTO recipe_steps_new SELECT * FROM recipe_steps;
+DROP TABLE recipe_steps;
+ALTER TABLE recipe_steps_new RENAME TO recipe_steps;
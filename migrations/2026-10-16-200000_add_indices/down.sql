@@ -0,0 +1,6 @@
+DROP INDEX recipes_name;
+DROP INDEX recipes_category;
+DROP INDEX ingredients_name;
+DROP INDEX ingredient_usages_recipe_id;
+DROP INDEX ingredient_usages_ingredient_id;
+DROP INDEX calendar_recipe_id;
@@ -0,0 +1,7 @@
+-- For searching by name, and for the joins between recipes, their ingredients, and the calendar.
+CREATE INDEX recipes_name ON recipes (name);
+CREATE INDEX recipes_category ON recipes (category);
+CREATE INDEX ingredients_name ON ingredients (name);
+CREATE INDEX ingredient_usages_recipe_id ON ingredient_usages (recipe_id);
+CREATE INDEX ingredient_usages_ingredient_id ON ingredient_usages (ingredient_id);
+CREATE INDEX calendar_recipe_id ON calendar (recipe_id);
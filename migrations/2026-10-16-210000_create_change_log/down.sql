@@ -0,0 +1,32 @@
+DROP TRIGGER calendar_insert_log;
+DROP TRIGGER calendar_update_log;
+DROP TRIGGER calendar_delete_log;
+DROP TRIGGER ingredient_calories_insert_log;
+DROP TRIGGER ingredient_calories_update_log;
+DROP TRIGGER ingredient_calories_delete_log;
+DROP TRIGGER ingredient_categories_insert_log;
+DROP TRIGGER ingredient_categories_update_log;
+DROP TRIGGER ingredient_categories_delete_log;
+DROP TRIGGER ingredient_components_insert_log;
+DROP TRIGGER ingredient_components_update_log;
+DROP TRIGGER ingredient_components_delete_log;
+DROP TRIGGER ingredient_compounds_insert_log;
+DROP TRIGGER ingredient_compounds_update_log;
+DROP TRIGGER ingredient_compounds_delete_log;
+DROP TRIGGER ingredient_usages_insert_log;
+DROP TRIGGER ingredient_usages_update_log;
+DROP TRIGGER ingredient_usages_delete_log;
+DROP TRIGGER ingredients_insert_log;
+DROP TRIGGER ingredients_update_log;
+DROP TRIGGER ingredients_delete_log;
+DROP TRIGGER recipe_categories_insert_log;
+DROP TRIGGER recipe_categories_update_log;
+DROP TRIGGER recipe_categories_delete_log;
+DROP TRIGGER recipe_steps_insert_log;
+DROP TRIGGER recipe_steps_update_log;
+DROP TRIGGER recipe_steps_delete_log;
+DROP TRIGGER recipes_insert_log;
+DROP TRIGGER recipes_update_log;
+DROP TRIGGER recipes_delete_log;
+DROP TABLE sync_state;
+DROP TABLE change_log;
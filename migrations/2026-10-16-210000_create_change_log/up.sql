@@ -0,0 +1,139 @@
+-- Every change to the recipe book is journaled, so that when the database is synced through a
+-- shared folder, changes made on another machine can be merged with the ones made here. Only which
+-- row changed is recorded; merging copies the row as it is now. Settings are left out since they
+-- belong to each machine.
+--
+-- Dropping a table drops its triggers, so a migration which rebuilds one of these tables has to
+-- create them again.
+
+CREATE TABLE change_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    table_name TEXT NOT NULL,
+    row_key TEXT NOT NULL
+);
+
+-- For each other machine, the last of its changes which has been merged into this database.
+CREATE TABLE sync_state (
+    machine TEXT PRIMARY KEY NOT NULL,
+    last_change_id INTEGER NOT NULL
+);
+
+CREATE TRIGGER calendar_insert_log AFTER INSERT ON calendar BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('calendar', NEW.day);
+END;
+CREATE TRIGGER calendar_update_log AFTER UPDATE ON calendar BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('calendar', NEW.day);
+    INSERT INTO change_log (table_name, row_key) SELECT 'calendar', OLD.day
+        WHERE OLD.day IS NOT NEW.day;
+END;
+CREATE TRIGGER calendar_delete_log AFTER DELETE ON calendar BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('calendar', OLD.day);
+END;
+
+CREATE TRIGGER ingredient_calories_insert_log AFTER INSERT ON ingredient_calories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_calories', NEW.id);
+END;
+CREATE TRIGGER ingredient_calories_update_log AFTER UPDATE ON ingredient_calories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_calories', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'ingredient_calories', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER ingredient_calories_delete_log AFTER DELETE ON ingredient_calories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_calories', OLD.id);
+END;
+
+CREATE TRIGGER ingredient_categories_insert_log AFTER INSERT ON ingredient_categories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_categories', NEW.id);
+END;
+CREATE TRIGGER ingredient_categories_update_log AFTER UPDATE ON ingredient_categories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_categories', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'ingredient_categories', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER ingredient_categories_delete_log AFTER DELETE ON ingredient_categories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_categories', OLD.id);
+END;
+
+CREATE TRIGGER ingredient_components_insert_log AFTER INSERT ON ingredient_components BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_components', NEW.id);
+END;
+CREATE TRIGGER ingredient_components_update_log AFTER UPDATE ON ingredient_components BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_components', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'ingredient_components', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER ingredient_components_delete_log AFTER DELETE ON ingredient_components BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_components', OLD.id);
+END;
+
+CREATE TRIGGER ingredient_compounds_insert_log AFTER INSERT ON ingredient_compounds BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_compounds', NEW.ingredient_id);
+END;
+CREATE TRIGGER ingredient_compounds_update_log AFTER UPDATE ON ingredient_compounds BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_compounds', NEW.ingredient_id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'ingredient_compounds', OLD.ingredient_id
+        WHERE OLD.ingredient_id IS NOT NEW.ingredient_id;
+END;
+CREATE TRIGGER ingredient_compounds_delete_log AFTER DELETE ON ingredient_compounds BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_compounds', OLD.ingredient_id);
+END;
+
+CREATE TRIGGER ingredient_usages_insert_log AFTER INSERT ON ingredient_usages BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_usages', NEW.id);
+END;
+CREATE TRIGGER ingredient_usages_update_log AFTER UPDATE ON ingredient_usages BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_usages', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'ingredient_usages', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER ingredient_usages_delete_log AFTER DELETE ON ingredient_usages BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredient_usages', OLD.id);
+END;
+
+CREATE TRIGGER ingredients_insert_log AFTER INSERT ON ingredients BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredients', NEW.id);
+END;
+CREATE TRIGGER ingredients_update_log AFTER UPDATE ON ingredients BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredients', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'ingredients', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER ingredients_delete_log AFTER DELETE ON ingredients BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('ingredients', OLD.id);
+END;
+
+CREATE TRIGGER recipe_categories_insert_log AFTER INSERT ON recipe_categories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_categories', NEW.id);
+END;
+CREATE TRIGGER recipe_categories_update_log AFTER UPDATE ON recipe_categories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_categories', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'recipe_categories', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER recipe_categories_delete_log AFTER DELETE ON recipe_categories BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_categories', OLD.id);
+END;
+
+CREATE TRIGGER recipe_steps_insert_log AFTER INSERT ON recipe_steps BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_steps', NEW.id);
+END;
+CREATE TRIGGER recipe_steps_update_log AFTER UPDATE ON recipe_steps BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_steps', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'recipe_steps', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER recipe_steps_delete_log AFTER DELETE ON recipe_steps BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_steps', OLD.id);
+END;
+
+CREATE TRIGGER recipes_insert_log AFTER INSERT ON recipes BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipes', NEW.id);
+END;
+CREATE TRIGGER recipes_update_log AFTER UPDATE ON recipes BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipes', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'recipes', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER recipes_delete_log AFTER DELETE ON recipes BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipes', OLD.id);
+END;
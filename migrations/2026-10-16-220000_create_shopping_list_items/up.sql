@@ -0,0 +1,9 @@
+-- Which ingredients have been checked off a week's shopping list, like from a phone in the store.
+-- These aren't journaled for syncing, since they only matter for the week.
+CREATE TABLE shopping_list_items (
+    week DATE NOT NULL,
+    ingredient_id INTEGER NOT NULL,
+    checked BOOLEAN NOT NULL,
+    PRIMARY KEY (week, ingredient_id),
+    FOREIGN KEY(ingredient_id) REFERENCES ingredients(id) ON DELETE CASCADE
+);
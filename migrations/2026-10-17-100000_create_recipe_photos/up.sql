@@ -0,0 +1,22 @@
+-- Photos of recipes, like from a scanned recipe box. The image itself is kept in the database so
+-- it gets synced along with everything else.
+CREATE TABLE recipe_photos (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    file_name TEXT NOT NULL,
+    data BLOB NOT NULL,
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id) ON DELETE CASCADE
+);
+CREATE INDEX recipe_photos_recipe_id ON recipe_photos (recipe_id);
+
+CREATE TRIGGER recipe_photos_insert_log AFTER INSERT ON recipe_photos BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_photos', NEW.id);
+END;
+CREATE TRIGGER recipe_photos_update_log AFTER UPDATE ON recipe_photos BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_photos', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'recipe_photos', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER recipe_photos_delete_log AFTER DELETE ON recipe_photos BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('recipe_photos', OLD.id);
+END;
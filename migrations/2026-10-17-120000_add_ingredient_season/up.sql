@@ -0,0 +1 @@
+ALTER TABLE ingredients ADD COLUMN season INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE ingredients ADD COLUMN diet INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- The records of each file which have been imported, so an import which was cancelled can pick up
+-- where it left off. These aren't journaled for syncing, since the files are on this machine.
+CREATE TABLE imported_records (
+    source TEXT NOT NULL,
+    record_key TEXT NOT NULL,
+    PRIMARY KEY (source, record_key)
+);
@@ -0,0 +1 @@
+ALTER TABLE ingredients ADD COLUMN density REAL;
@@ -0,0 +1 @@
+ALTER TABLE recipes ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+ALTER TABLE recipes ADD COLUMN status TEXT CHECK ( status IN (
+    'tried',
+    'want_to_try'
+) ) NOT NULL DEFAULT 'tried';
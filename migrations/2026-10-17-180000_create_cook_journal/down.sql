@@ -0,0 +1 @@
+DROP TABLE cook_journal;
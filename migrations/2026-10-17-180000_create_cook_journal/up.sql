@@ -0,0 +1,22 @@
+-- How each time a recipe was cooked turned out, and what to change the next time.
+CREATE TABLE cook_journal (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    day DATE NOT NULL,
+    rating INTEGER,
+    notes TEXT NOT NULL,
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id) ON DELETE CASCADE
+);
+CREATE INDEX cook_journal_recipe_id ON cook_journal (recipe_id);
+
+CREATE TRIGGER cook_journal_insert_log AFTER INSERT ON cook_journal BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('cook_journal', NEW.id);
+END;
+CREATE TRIGGER cook_journal_update_log AFTER UPDATE ON cook_journal BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('cook_journal', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'cook_journal', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER cook_journal_delete_log AFTER DELETE ON cook_journal BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('cook_journal', OLD.id);
+END;
@@ -0,0 +1 @@
+ALTER TABLE recipe_categories DROP COLUMN default_duration;
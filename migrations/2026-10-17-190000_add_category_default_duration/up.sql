@@ -0,0 +1,7 @@
+-- What new recipes in the category start out taking, when it isn't short.
+ALTER TABLE recipe_categories ADD COLUMN default_duration TEXT CHECK ( default_duration IN (
+    'short',
+    'medium',
+    'long',
+    'really_long'
+) );
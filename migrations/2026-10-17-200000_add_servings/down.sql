@@ -0,0 +1,2 @@
+ALTER TABLE calendar DROP COLUMN servings;
+ALTER TABLE recipes DROP COLUMN servings;
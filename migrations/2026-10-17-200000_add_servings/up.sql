@@ -0,0 +1,4 @@
+-- How many people the recipe feeds as it's written.
+ALTER TABLE recipes ADD COLUMN servings INTEGER CHECK ( servings > 0 );
+-- How many people are being fed on the day, when the shopping list should be scaled for them.
+ALTER TABLE calendar ADD COLUMN servings INTEGER CHECK ( servings > 0 );
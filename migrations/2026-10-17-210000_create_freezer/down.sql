@@ -0,0 +1,2 @@
+ALTER TABLE calendar DROP COLUMN from_freezer;
+DROP TABLE freezer;
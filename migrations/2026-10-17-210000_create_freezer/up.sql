@@ -0,0 +1,24 @@
+-- Portions of recipes which were made ahead and frozen.
+CREATE TABLE freezer (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    portions INTEGER NOT NULL CHECK ( portions > 0 ),
+    frozen DATE NOT NULL,
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id) ON DELETE CASCADE
+);
+CREATE INDEX freezer_recipe_id ON freezer (recipe_id);
+
+CREATE TRIGGER freezer_insert_log AFTER INSERT ON freezer BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('freezer', NEW.id);
+END;
+CREATE TRIGGER freezer_update_log AFTER UPDATE ON freezer BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('freezer', NEW.id);
+    INSERT INTO change_log (table_name, row_key) SELECT 'freezer', OLD.id
+        WHERE OLD.id IS NOT NEW.id;
+END;
+CREATE TRIGGER freezer_delete_log AFTER DELETE ON freezer BEGIN
+    INSERT INTO change_log (table_name, row_key) VALUES ('freezer', OLD.id);
+END;
+
+-- Whether the day's recipe comes out of the freezer, so there's nothing to shop for.
+ALTER TABLE calendar ADD COLUMN from_freezer BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+ALTER TABLE ingredients DROP COLUMN price_units;
+ALTER TABLE ingredients DROP COLUMN price;
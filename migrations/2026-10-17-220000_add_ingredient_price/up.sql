@@ -0,0 +1,17 @@
+-- What one `price_units` of the ingredient costs, or one of it when there are no units.
+ALTER TABLE ingredients ADD COLUMN price REAL CHECK ( price >= 0 );
+ALTER TABLE ingredients ADD COLUMN price_units TEXT CHECK ( price_units IN (
+    'cups',
+    'fluid_ounces',
+    'grams',
+    'kilograms',
+    'kiloliters',
+    'liters',
+    'milligrams',
+    'milliliters',
+    'ounces',
+    'pounds',
+    'quart',
+    'tablespoons',
+    'teaspoons'
+) );
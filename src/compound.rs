@@ -0,0 +1,71 @@
+//! Blends, like a spice mix, which are made from other ingredients.
+
+use crate::database::models::{
+    Ingredient, IngredientComponent, IngredientCompound, IngredientMeasurement, IngredientUsage,
+};
+use crate::unit_conversion;
+
+pub struct CompoundDefinition {
+    pub compound: IngredientCompound,
+    pub components: Vec<(IngredientComponent, Ingredient)>,
+}
+
+/// How many batches of a blend which makes `yield_quantity` are needed for the given quantity.
+fn expansion_scale(
+    quantity: f32,
+    quantity_units: Option<IngredientMeasurement>,
+    yield_quantity: f32,
+    yield_units: Option<IngredientMeasurement>,
+) -> Option<f32> {
+    let factor = match (quantity_units, yield_units) {
+        (a, b) if a == b => 1.0,
+        (Some(a), Some(b)) => unit_conversion::conversion_factor(a, b).ok()?,
+        _ => return None,
+    };
+    Some(factor * quantity / yield_quantity)
+}
+
+#[test]
+fn expansion_scale_test() {
+    use IngredientMeasurement::*;
+
+    assert_eq!(
+        expansion_scale(2.0, Some(Tablespoons), 4.0, Some(Tablespoons)),
+        Some(0.5)
+    );
+    assert_eq!(
+        expansion_scale(0.5, Some(Cups), 4.0, Some(Tablespoons)),
+        Some(2.0)
+    );
+    assert_eq!(expansion_scale(3.0, None, 1.0, None), Some(3.0));
+    assert_eq!(expansion_scale(1.0, Some(Grams), 1.0, Some(Cups)), None);
+    assert_eq!(expansion_scale(1.0, None, 1.0, Some(Cups)), None);
+}
+
+impl CompoundDefinition {
+    /// Replaces a usage of the compound with usages of its components. Returns `None` if the
+    /// usage's units can't be converted to the units the compound is defined in.
+    pub fn expand(&self, usage: &IngredientUsage) -> Option<Vec<(IngredientUsage, Ingredient)>> {
+        let scale = expansion_scale(
+            usage.quantity,
+            usage.quantity_units,
+            self.compound.quantity,
+            self.compound.quantity_units,
+        )?;
+
+        Some(
+            self.components
+                .iter()
+                .map(|(component, ingredient)| {
+                    let component_usage = IngredientUsage {
+                        ingredient_id: component.component_id,
+                        quantity: component.quantity * scale,
+                        quantity_units: component.quantity_units,
+                        ..usage.clone()
+                    };
+                    (component_usage, ingredient.clone())
+                })
+                .collect(),
+        )
+    }
+}
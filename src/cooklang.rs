@@ -0,0 +1,302 @@
+//! Recipes as Cooklang text, which marks up the ingredients where the directions mention them,
+//! like `Add @flour{2%cups}`. Being plain text, recipes written this way can be kept in a git
+//! repository.
+
+use crate::database::models::Recipe;
+use crate::recipe::RecipeIngredient;
+
+/// An ingredient as it's marked up in a step.
+#[derive(Debug, Default, PartialEq)]
+pub struct CooklangIngredient {
+    pub name: String,
+    pub quantity: String,
+    pub units: String,
+    pub note: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CooklangRecipe {
+    /// The `>> key: value` lines.
+    pub metadata: Vec<(String, String)>,
+    /// The text of each step, without the markup.
+    pub steps: Vec<String>,
+    pub ingredients: Vec<CooklangIngredient>,
+}
+
+impl CooklangRecipe {
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn quantity_text(quantity: f32) -> String {
+    let text = format!("{quantity:.3}");
+    text.trim_end_matches('0').trim_end_matches('.').into()
+}
+
+fn ingredient_markup(ingredient: &RecipeIngredient, text: &str) -> String {
+    let quantity = quantity_text(ingredient.quantity);
+    let amount = match ingredient.quantity_units {
+        Some(units) => format!("{quantity}%{}", units.as_str()),
+        None => quantity,
+    };
+    let mut markup = format!("@{text}{{{amount}}}");
+    if !ingredient.note.is_empty() {
+        markup += &format!("({})", ingredient.note);
+    }
+    markup
+}
+
+/// Where `name` first appears as a whole word in `text`, ignoring case.
+fn find_word(text: &str, name: &str) -> Option<usize> {
+    let lower = text.to_lowercase();
+    // Lowercasing can change the length of some characters, which would throw off the indices.
+    if lower.len() != text.len() || name.is_empty() {
+        return None;
+    }
+    let name = name.to_lowercase();
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric());
+    lower.match_indices(&name).map(|(i, _)| i).find(|&i| {
+        !is_word(lower[..i].chars().last())
+            && !is_word(lower[i + name.len()..].chars().next())
+            && !lower[..i].ends_with('@')
+    })
+}
+
+/// Writes the recipe as Cooklang. Ingredients are marked up where the description first mentions
+/// them, and any it doesn't mention are listed in a step of their own at the start.
+pub fn recipe_to_cooklang(
+    recipe: &Recipe,
+    category: &str,
+    ingredients: &[RecipeIngredient],
+) -> String {
+    let mut steps: Vec<String> = recipe
+        .description
+        .split("\n\n")
+        .map(|s| s.trim().replace('\n', " "))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let mut unmentioned = vec![];
+    for ingredient in ingredients {
+        let name = &ingredient.ingredient.name;
+        let found = steps
+            .iter_mut()
+            .find_map(|s| find_word(s, name).map(|i| (s, i)));
+        match found {
+            Some((step, i)) => {
+                let markup = ingredient_markup(ingredient, &step[i..i + name.len()]);
+                step.replace_range(i..i + name.len(), &markup);
+            }
+            None => unmentioned.push(ingredient_markup(ingredient, name)),
+        }
+    }
+    if !unmentioned.is_empty() {
+        steps.insert(0, format!("Ingredients: {}", unmentioned.join(", ")));
+    }
+
+    let mut text = format!(">> title: {}\n", recipe.name);
+    text += &format!(">> category: {category}\n");
+    text += &format!(">> duration: {}\n", recipe.duration);
+    for step in steps {
+        text += "\n";
+        text += &step;
+        text += "\n";
+    }
+    text
+}
+
+/// The part of `s` inside the brackets it starts with, and what comes after them.
+fn bracketed(s: &str, open: char, close: char) -> Option<(&str, &str)> {
+    let inner = s.strip_prefix(open)?;
+    let end = inner.find(close)?;
+    Some((&inner[..end], &inner[end + close.len_utf8()..]))
+}
+
+/// The name after a `@`, `#` or `~`, and what comes after it. Names of more than one word end in
+/// `{}`, single words don't need to.
+fn component_name(s: &str) -> (&str, &str) {
+    let multi_word_end = s
+        .find(['{', '@', '#', '~', '\n'])
+        .filter(|&i| s[i..].starts_with('{'));
+    let end = multi_word_end.unwrap_or_else(|| {
+        s.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(s.len())
+    });
+    (&s[..end], &s[end..])
+}
+
+fn remove_comments(text: &str) -> String {
+    let mut without = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find("[-") {
+        without += &rest[..i];
+        rest = rest[i..].split_once("-]").map_or("", |(_, r)| r);
+    }
+    without += rest;
+    without
+        .lines()
+        .map(|l| l.split_once("--").map_or(l, |(l, _)| l))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse_step(step: &str, ingredients: &mut Vec<CooklangIngredient>) -> String {
+    let mut text = String::new();
+    let mut rest = step;
+    while let Some(i) = rest.find(['@', '#', '~']) {
+        text += &rest[..i];
+        let marker = rest[i..].chars().next().unwrap();
+        let (name, after) = component_name(&rest[i + 1..]);
+        let (amount, after) = bracketed(after, '{', '}').unwrap_or(("", after));
+        rest = after;
+        if name.is_empty() && amount.is_empty() {
+            text.push(marker);
+            continue;
+        }
+
+        let (quantity, units) = amount.split_once('%').unwrap_or((amount, ""));
+        match marker {
+            '@' => {
+                let (note, after) = bracketed(rest, '(', ')').unwrap_or(("", rest));
+                rest = after;
+                ingredients.push(CooklangIngredient {
+                    name: name.trim().into(),
+                    quantity: quantity.trim().into(),
+                    units: units.trim().into(),
+                    note: note.trim().into(),
+                });
+                text += name;
+            }
+            '#' => text += name,
+            '~' if amount.is_empty() => text += name,
+            // Timers read as their duration.
+            _ => text += &format!("{} {}", quantity.trim(), units.trim()),
+        }
+    }
+    text += rest;
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn parse_cooklang(text: &str) -> CooklangRecipe {
+    let mut recipe = CooklangRecipe::default();
+    let mut step_lines = vec![];
+    let mut steps = vec![];
+    for line in remove_comments(text).lines() {
+        if let Some(metadata) = line.trim().strip_prefix(">>") {
+            if let Some((key, value)) = metadata.split_once(':') {
+                recipe
+                    .metadata
+                    .push((key.trim().into(), value.trim().into()));
+            }
+        } else if line.trim().is_empty() {
+            if !step_lines.is_empty() {
+                steps.push(step_lines.join("\n"));
+                step_lines.clear();
+            }
+        } else {
+            step_lines.push(line.trim().to_owned());
+        }
+    }
+    if !step_lines.is_empty() {
+        steps.push(step_lines.join("\n"));
+    }
+    recipe.steps = steps
+        .iter()
+        .map(|s| parse_step(s, &mut recipe.ingredients))
+        .filter(|s| !s.is_empty())
+        .collect();
+    recipe
+}
+
+#[test]
+fn parse_cooklang_test() {
+    let text = "\
+>> servings: 2
+>> category: Breakfast
+
+Crack @eggs{3} into a #bowl{}, and add @ground black pepper{1%tsp}(freshly ground). -- a comment
+Whisk @salt in too.
+
+[- a block
+comment -]Cook in a #frying pan{} for ~{5%minutes}.
+";
+    let recipe = parse_cooklang(text);
+    assert_eq!(recipe.metadata("Category"), Some("Breakfast"));
+    assert_eq!(recipe.metadata("servings"), Some("2"));
+    assert_eq!(recipe.metadata("title"), None);
+    assert_eq!(
+        recipe.steps,
+        vec![
+            "Crack eggs into a bowl, and add ground black pepper. Whisk salt in too.",
+            "Cook in a frying pan for 5 minutes.",
+        ]
+    );
+    let ingredient = |name: &str, quantity: &str, units: &str, note: &str| CooklangIngredient {
+        name: name.into(),
+        quantity: quantity.into(),
+        units: units.into(),
+        note: note.into(),
+    };
+    assert_eq!(
+        recipe.ingredients,
+        vec![
+            ingredient("eggs", "3", "", ""),
+            ingredient("ground black pepper", "1", "tsp", "freshly ground"),
+            ingredient("salt", "", "", ""),
+        ]
+    );
+    assert_eq!(
+        parse_cooklang("Email me @ home").steps,
+        vec!["Email me @ home"]
+    );
+}
+
+#[test]
+fn recipe_to_cooklang_test() {
+    use crate::database::fixture::Fixture;
+    use crate::database::models::IngredientMeasurement;
+    use crate::query;
+
+    let mut f = Fixture::open();
+    query::edit_recipe_description(&mut f.conn, f.soup, "Chop the Onion and carrot.\n\nSimmer.")
+        .unwrap();
+    let (_, _, ingredients) = query::get_recipe(&mut f.conn, f.soup).unwrap();
+    let carrot = &ingredients[0];
+    query::edit_recipe_ingredient(
+        &mut f.conn,
+        carrot.id,
+        &carrot.ingredient,
+        1.5,
+        Some(IngredientMeasurement::Cups),
+        "diced",
+    )
+    .unwrap();
+    let (recipe, category, ingredients) = query::get_recipe(&mut f.conn, f.soup).unwrap();
+
+    let text = recipe_to_cooklang(&recipe, &category, &ingredients);
+    assert_eq!(
+        text,
+        "\
+>> title: Carrot Soup
+>> category: Dinner
+>> duration: short
+
+Ingredients: @salt{0.5}
+
+Chop the @Onion{1} and @carrot{1.5%cups}(diced).
+
+Simmer.
+"
+    );
+
+    let parsed = parse_cooklang(&text);
+    assert_eq!(parsed.metadata("duration"), Some("short"));
+    assert_eq!(parsed.steps[1], "Chop the Onion and carrot.");
+    assert_eq!(parsed.ingredients.len(), 3);
+    assert_eq!(parsed.ingredients[2].units, "cups");
+    assert_eq!(parsed.ingredients[2].note, "diced");
+}
@@ -0,0 +1,129 @@
+//! Passwords and tokens for the accounts the recipe book talks to. They're kept in a file of their
+//! own next to the database rather than in its settings, since copies of the database get made:
+//! the one written to the sync folder, and the backups taken before upgrades.
+
+use crate::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "credentials";
+
+/// What the file holds, by the same names the settings have.
+#[derive(Debug, Default, PartialEq)]
+pub struct Credentials {
+    values: BTreeMap<String, String>,
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+impl Credentials {
+    fn path() -> Result<PathBuf> {
+        Ok(crate::data_path()?.join(FILE_NAME))
+    }
+
+    /// Nothing is stored yet if the file doesn't exist.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let values = contents
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| (name.to_owned(), unescape(value)))
+            .collect();
+        Ok(Self { values })
+    }
+
+    /// Only readable by the user, on systems where that can be set.
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        let contents: String = self
+            .values
+            .iter()
+            .map(|(name, value)| format!("{name}={}\n", escape(value)))
+            .collect();
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        std::io::Write::write_all(&mut options.open(path)?, contents.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Setting nothing, or an empty value, removes it.
+    pub fn set(&mut self, name: &str, value: Option<&str>) {
+        match value.filter(|v| !v.is_empty()) {
+            Some(value) => self.values.insert(name.into(), value.into()),
+            None => self.values.remove(name),
+        };
+    }
+}
+
+#[test]
+fn credentials_test() {
+    let dir = std::env::temp_dir().join(format!("credentials-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(FILE_NAME);
+    assert_eq!(
+        Credentials::load_from(&path).unwrap(),
+        Credentials::default()
+    );
+
+    let mut credentials = Credentials::default();
+    credentials.set("smtp_password", Some("a=b\\n\nc"));
+    credentials.set("todoist_token", Some("0123abcd"));
+    credentials.set("bring_password", Some(""));
+    credentials.save_to(&path).unwrap();
+
+    let loaded = Credentials::load_from(&path).unwrap();
+    assert_eq!(loaded.get("smtp_password"), Some("a=b\\n\nc"));
+    assert_eq!(loaded.get("todoist_token"), Some("0123abcd"));
+    assert_eq!(loaded.get("bring_password"), None);
+    assert_eq!(loaded, credentials);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,50 @@
+//! Comma-separated values, for looking at the recipe data in a spreadsheet.
+
+use std::borrow::Cow;
+
+/// The field quoted if it has anything in it which would otherwise be read as the end of it.
+fn field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+fn write_row<'a>(csv: &mut String, fields: impl Iterator<Item = &'a str>) {
+    let fields: Vec<_> = fields.map(field).collect();
+    csv.push_str(&fields.join(","));
+    csv.push_str("\r\n");
+}
+
+/// A CSV document with the header as its first line.
+pub fn write_csv(header: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let mut csv = String::new();
+    write_row(&mut csv, header.iter().copied());
+    for row in rows {
+        write_row(&mut csv, row.iter().map(String::as_str));
+    }
+    csv
+}
+
+#[test]
+fn write_csv_test() {
+    assert_eq!(
+        write_csv(&["Recipe", "Calories"], []),
+        "Recipe,Calories\r\n"
+    );
+    assert_eq!(
+        write_csv(
+            &["Recipe", "Calories"],
+            [
+                vec!["Carrot Soup".into(), "350".into()],
+                vec!["Mac, \"Cheese\"".into(), "".into()],
+                vec!["Two\nLines".into(), "10".into()],
+            ]
+        ),
+        "Recipe,Calories\r\n\
+        Carrot Soup,350\r\n\
+        \"Mac, \"\"Cheese\"\"\",\r\n\
+        \"Two\nLines\",10\r\n"
+    );
+}
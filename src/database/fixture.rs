@@ -0,0 +1,74 @@
+//! A small recipe book in an in-memory database, for testing queries against.
+
+use super::models::{IngredientId, RecipeCategoryId, RecipeId};
+use crate::query;
+
+/// An empty database with all the migrations run.
+pub fn test_connection() -> super::Connection {
+    super::establish_connection(":memory:").unwrap()
+}
+
+/// Two categories, three recipes and the four ingredients they use. Soup is on the calendar
+/// on Monday, January 1st 2024 and the tart on the Wednesday after.
+pub struct Fixture {
+    pub conn: super::Connection,
+    pub dinner: RecipeCategoryId,
+    pub dessert: RecipeCategoryId,
+    pub carrot: IngredientId,
+    pub onion: IngredientId,
+    pub salt: IngredientId,
+    pub sugar: IngredientId,
+    pub soup: RecipeId,
+    pub tart: RecipeId,
+    pub cake: RecipeId,
+}
+
+impl Fixture {
+    pub fn open() -> Self {
+        let mut conn = test_connection();
+        let dinner = query::add_category(&mut conn, "Dinner").unwrap();
+        let dessert = query::add_category(&mut conn, "Dessert").unwrap();
+
+        let mut ingredient = |name| query::add_ingredient(&mut conn, name).unwrap().id;
+        let carrot = ingredient("carrot");
+        let onion = ingredient("onion");
+        let salt = ingredient("salt");
+        let sugar = ingredient("sugar");
+
+        let mut recipe = |name, category, ingredients: &[(IngredientId, f32)]| {
+            let id = query::add_recipe(&mut conn, name, category).unwrap();
+            for &(ingredient, quantity) in ingredients {
+                query::add_recipe_ingredient(&mut conn, id, ingredient, quantity).unwrap();
+            }
+            id
+        };
+        let soup = recipe(
+            "Carrot Soup",
+            dinner,
+            &[(carrot, 4.0), (onion, 1.0), (salt, 0.5)],
+        );
+        let tart = recipe("Onion Tart", dinner, &[(onion, 3.0), (salt, 0.25)]);
+        let cake = recipe("Carrot Cake", dessert, &[(carrot, 2.0), (sugar, 1.0)]);
+
+        query::insert_or_update_calendar_entry(&mut conn, day(1), soup).unwrap();
+        query::insert_or_update_calendar_entry(&mut conn, day(3), tart).unwrap();
+
+        Self {
+            conn,
+            dinner,
+            dessert,
+            carrot,
+            onion,
+            salt,
+            sugar,
+            soup,
+            tart,
+            cake,
+        }
+    }
+}
+
+/// A day in January 2024, which starts on a Monday.
+pub fn day(d: u32) -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+}
@@ -1,10 +1,17 @@
 // Copyright 2023 Remi Bernotavicius
 
+use diesel::connection::SimpleConnection as _;
+use diesel::migration::{Migration, MigrationSource};
 use diesel::prelude::Connection as _;
+use diesel::sqlite::Sqlite;
+use diesel::RunQueryDsl as _;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use std::collections::{HashSet, VecDeque};
 use std::error::Error;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+#[cfg(test)]
+pub mod fixture;
 pub mod models;
 pub mod schema;
 
@@ -12,14 +19,329 @@ pub type Connection = diesel::sqlite::SqliteConnection;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
+/// How many backups from before upgrades are kept. Older ones are removed as new ones are made.
+const BACKUPS_KEPT: usize = 3;
+
+/// The setting with the version of Recipe Manager which last opened the database for writing.
+const APP_VERSION: &str = "app_version";
+
+/// A database upgraded by a newer version of Recipe Manager than this one, which could fail
+/// anywhere with what it doesn't know about, so it isn't opened.
+#[derive(Debug)]
+pub struct NewerDatabase {
+    /// The version which last opened it, if it recorded one.
+    pub app_version: Option<String>,
+}
+
+impl std::fmt::Display for NewerDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let current = env!("CARGO_PKG_VERSION");
+        match &self.app_version {
+            Some(version) => write!(
+                f,
+                "the database was last used by Recipe Manager {version}, which is newer than \
+                this version ({current})"
+            ),
+            None => write!(
+                f,
+                "the database was last used by a newer version of Recipe Manager than this one \
+                ({current})"
+            ),
+        }
+    }
+}
+
+impl Error for NewerDatabase {}
+
+/// Fails with `NewerDatabase` if the database has migrations this version doesn't know about.
+fn check_not_newer(
+    connection: &mut Connection,
+) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    let known: HashSet<_> = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)?
+        .iter()
+        .map(|m| m.name().version().to_string())
+        .collect();
+    let applied = connection.applied_migrations()?;
+    if applied.iter().all(|v| known.contains(&v.to_string())) {
+        return Ok(());
+    }
+    let app_version = crate::query::get_setting(connection, APP_VERSION).unwrap_or_default();
+    Err(NewerDatabase { app_version }.into())
+}
+
+/// The version of the newest migration the database has been upgraded with.
+pub fn schema_version(
+    connection: &mut Connection,
+) -> Result<Option<String>, Box<dyn Error + Send + Sync + 'static>> {
+    Ok(connection
+        .applied_migrations()?
+        .iter()
+        .map(|v| v.to_string())
+        .max())
+}
+
 pub fn establish_connection(
     path: impl AsRef<Path>,
 ) -> Result<Connection, Box<dyn Error + Send + Sync + 'static>> {
-    let mut connection = Connection::establish(path.as_ref().to_str().unwrap())?;
-    connection.run_pending_migrations(MIGRATIONS)?;
+    Migrator::new(path)?.finish()
+}
+
+/// Upgrades a database to the current schema one migration at a time, so the progress can be
+/// shown. A database with anything in it is backed up first.
+pub struct Migrator {
+    connection: Connection,
+    pending: VecDeque<Box<dyn Migration<Sqlite>>>,
+    total: usize,
+}
+
+impl Migrator {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error + Send + Sync + 'static>> {
+        let path = path.as_ref();
+        let mut connection = Connection::establish(path.to_str().unwrap())?;
+        // Another process using the database at the same time waits for it rather than failing
+        // with "database is locked".
+        connection.batch_execute(
+            "PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 5000; PRAGMA synchronous = NORMAL;",
+        )?;
+        check_not_newer(&mut connection)?;
+        let pending: VecDeque<_> = connection.pending_migrations(MIGRATIONS)?.into();
+        if !pending.is_empty() && !connection.applied_migrations()?.is_empty() {
+            backup(&mut connection, path)?;
+        }
+        Ok(Self {
+            connection,
+            total: pending.len(),
+            pending,
+        })
+    }
+
+    /// How many migrations have run, and how many there are altogether.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.pending.len(), self.total)
+    }
+
+    /// The name of the migration which runs next.
+    pub fn next_name(&self) -> Option<String> {
+        Some(self.pending.front()?.name().to_string())
+    }
+
+    /// Runs the next migration, returning false once there are none left to run.
+    pub fn run_next(&mut self) -> Result<bool, Box<dyn Error + Send + Sync + 'static>> {
+        let Some(migration) = self.pending.front() else {
+            return Ok(false);
+        };
+        self.connection.run_migration(&**migration)?;
+        self.pending.pop_front();
+        Ok(true)
+    }
+
+    /// Runs whatever migrations are left, returning the upgraded database.
+    pub fn finish(mut self) -> Result<Connection, Box<dyn Error + Send + Sync + 'static>> {
+        while self.run_next()? {}
+        // Only after migrating, since some migrations rebuild tables other tables refer to.
+        self.connection.batch_execute("PRAGMA foreign_keys = ON")?;
+        crate::query::set_setting(
+            &mut self.connection,
+            APP_VERSION,
+            Some(env!("CARGO_PKG_VERSION")),
+        )?;
+        Ok(self.connection)
+    }
+}
+
+/// Where the backups of the database at `path` are kept.
+fn backup_dir(path: &Path) -> PathBuf {
+    path.with_file_name("backups")
+}
+
+/// Copies the database into the backups, removing the oldest ones beyond `BACKUPS_KEPT`.
+fn backup(
+    connection: &mut Connection,
+    path: &Path,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync + 'static>> {
+    let dir = backup_dir(path);
+    std::fs::create_dir_all(&dir)?;
+    let name = chrono::Local::now().format("data-%Y%m%d-%H%M%S.sqlite");
+    let backup = dir.join(name.to_string());
+    diesel::sql_query("VACUUM INTO ?")
+        .bind::<diesel::sql_types::Text, _>(backup.to_str().unwrap())
+        .execute(connection)?;
+    for old in backups(path).into_iter().skip(BACKUPS_KEPT) {
+        if let Err(error) = std::fs::remove_file(&old) {
+            log::warn!("failed to remove old backup {}: {error}", old.display());
+        }
+    }
+    Ok(backup)
+}
+
+/// The backups of the database at `path`, newest first.
+pub fn backups(path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(backup_dir(path)) else {
+        return vec![];
+    };
+    let mut backups: Vec<_> = entries
+        .filter_map(|e| Some(e.ok()?.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "sqlite"))
+        .collect();
+    // The names have the time in them, so they sort oldest first.
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+/// Puts a backup in place of the database at `path`, which mustn't be open.
+pub fn restore_backup(
+    backup: &Path,
+    path: &Path,
+) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    // What's left of the write-ahead log belongs to the database being replaced.
+    for suffix in ["-wal", "-shm"] {
+        let file = PathBuf::from(format!("{}{suffix}", path.display()));
+        if file.exists() {
+            std::fs::remove_file(file)?;
+        }
+    }
+    std::fs::copy(backup, path)?;
+    Ok(())
+}
+
+/// Opens an existing database without changing it in any way, not even to upgrade it, for when the
+/// file isn't writable or only browsing is wanted.
+pub fn establish_read_only_connection(
+    path: impl AsRef<Path>,
+) -> Result<Connection, Box<dyn Error + Send + Sync + 'static>> {
+    let mut connection = establish_outdated_connection(path)?;
+    if connection.has_pending_migration(MIGRATIONS)? {
+        return Err(
+            "the database is from an older version and needs to be opened with write \
+            access once to upgrade it"
+                .into(),
+        );
+    }
+    Ok(connection)
+}
+
+/// Opens an existing database read-only even if it hasn't been upgraded, for getting at what can
+/// still be read when upgrading it failed. Anything the upgrade would've changed may fail to load.
+pub fn establish_outdated_connection(
+    path: impl AsRef<Path>,
+) -> Result<Connection, Box<dyn Error + Send + Sync + 'static>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Err(format!("{} doesn't exist", path.display()).into());
+    }
+    let mut connection = Connection::establish(path.to_str().unwrap())?;
+    connection.batch_execute("PRAGMA busy_timeout = 5000; PRAGMA foreign_keys = ON")?;
+    check_not_newer(&mut connection)?;
+    set_read_only(&mut connection, true)?;
     Ok(connection)
 }
 
+/// Whether the database file can be written to. A file which doesn't exist yet is created, so it
+/// counts as writable.
+pub fn is_writable(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    !path.exists() || std::fs::File::options().write(true).open(path).is_ok()
+}
+
+/// Makes any change to the database fail, for when another instance of the program is already
+/// changing it.
+pub fn set_read_only(connection: &mut Connection, read_only: bool) -> diesel::QueryResult<()> {
+    connection.batch_execute(if read_only {
+        "PRAGMA query_only = ON"
+    } else {
+        "PRAGMA query_only = OFF"
+    })
+}
+
+#[test]
+fn read_only_test() {
+    let mut connection = fixture::test_connection();
+    let insert = "INSERT INTO recipe_categories (name) VALUES ('Dinner')";
+    set_read_only(&mut connection, true).unwrap();
+    assert!(diesel::sql_query(insert).execute(&mut connection).is_err());
+    set_read_only(&mut connection, false).unwrap();
+    diesel::sql_query(insert).execute(&mut connection).unwrap();
+}
+
+#[test]
+fn read_only_connection_test() {
+    let path = std::env::temp_dir().join(format!("read-only-{}.sqlite", std::process::id()));
+    assert!(establish_read_only_connection(&path).is_err());
+    assert!(is_writable(&path));
+
+    drop(establish_connection(&path).unwrap());
+    let mut connection = establish_read_only_connection(&path).unwrap();
+    let count = "SELECT COUNT(*) AS n FROM recipes";
+    diesel::sql_query(count).execute(&mut connection).unwrap();
+    let insert = "INSERT INTO recipe_categories (name) VALUES ('Dinner')";
+    assert!(diesel::sql_query(insert).execute(&mut connection).is_err());
+    drop(connection);
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+}
+
+#[test]
+fn backup_test() {
+    use diesel::QueryDsl as _;
+
+    let dir = std::env::temp_dir().join(format!("backup-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("data.sqlite");
+    let insert = "INSERT INTO recipe_categories (name) VALUES ('Dinner')";
+
+    let mut connection = establish_connection(&path).unwrap();
+    diesel::sql_query(insert).execute(&mut connection).unwrap();
+    let backup = backup(&mut connection, &path).unwrap();
+    assert_eq!(backups(&path), vec![backup.clone()]);
+    diesel::sql_query("DELETE FROM recipe_categories")
+        .execute(&mut connection)
+        .unwrap();
+    drop(connection);
+
+    restore_backup(&backup, &path).unwrap();
+    let mut connection = establish_connection(&path).unwrap();
+    let n: i64 = schema::recipe_categories::table
+        .count()
+        .get_result(&mut connection)
+        .unwrap();
+    assert_eq!(n, 1);
+    drop(connection);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn newer_database_test() {
+    let path = std::env::temp_dir().join(format!("newer-{}.sqlite", std::process::id()));
+
+    let mut connection = establish_connection(&path).unwrap();
+    assert_eq!(
+        crate::query::get_setting(&mut connection, APP_VERSION).unwrap(),
+        Some(env!("CARGO_PKG_VERSION").into())
+    );
+    assert!(schema_version(&mut connection).unwrap().is_some());
+    diesel::sql_query("INSERT INTO __diesel_schema_migrations (version) VALUES ('99990101000000')")
+        .execute(&mut connection)
+        .unwrap();
+    crate::query::set_setting(&mut connection, APP_VERSION, Some("99.0.0")).unwrap();
+    drop(connection);
+
+    for error in [
+        establish_connection(&path).err().unwrap(),
+        establish_read_only_connection(&path).err().unwrap(),
+    ] {
+        let newer = error.downcast_ref::<NewerDatabase>().unwrap();
+        assert_eq!(newer.app_version.as_deref(), Some("99.0.0"));
+    }
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+}
+
 #[test]
 fn migrations() {
     use std::process::Command;
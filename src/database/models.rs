@@ -13,22 +13,51 @@ use strum::EnumIter;
 pub struct IngredientId(i32);
 
 impl IngredientId {
-    pub const INITIAL: Self = Self(1);
+    /// For referring to an ingredient outside of the program, like in a URL.
+    pub fn from_i32(id: i32) -> Self {
+        Self(id)
+    }
 
-    pub fn next(&self) -> Self {
-        Self(self.0 + 1)
+    pub fn to_i32(self) -> i32 {
+        self.0
     }
 }
 
+#[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone, PartialOrd, Ord)]
+pub struct IngredientCategoryId(i32);
+
+#[derive(Queryable, Selectable, Identifiable, Insertable, Clone)]
+#[diesel(table_name = crate::database::schema::ingredient_categories)]
+pub struct IngredientCategory {
+    pub id: IngredientCategoryId,
+    pub name: String,
+}
+
 #[derive(Queryable, Selectable, Identifiable, Insertable, Clone)]
 #[diesel(table_name = crate::database::schema::ingredients)]
 pub struct Ingredient {
     pub id: IngredientId,
     pub name: String,
-    pub category: Option<String>,
+    /// Staples are left off shopping lists unless asked for.
+    pub staple: bool,
+    pub category_id: Option<IngredientCategoryId>,
+    /// The months it's in season, with bit 0 for January. Zero when it doesn't have a season.
+    pub season: i32,
+    /// The allergens it contains and diets it's safe for, see [`crate::diet::DietFlag`].
+    pub diet: i32,
+    /// In grams per milliliter, for converting between volume and weight.
+    pub density: Option<f32>,
+    /// What one `price_units` of it costs, or one of it when there are no units.
+    pub price: Option<f32>,
+    pub price_units: Option<IngredientMeasurement>,
 }
 
 impl Ingredient {
+    /// Whether it's in season in the month, from 1 for January to 12.
+    pub fn in_season(&self, month: u32) -> bool {
+        (1..=12).contains(&month) && self.season & (1 << (month - 1)) != 0
+    }
+
     pub fn to_handle(&self) -> IngredientHandle {
         IngredientHandle {
             id: self.id,
@@ -44,7 +73,7 @@ pub struct IngredientHandle {
     pub name: String,
 }
 
-#[derive(Debug, Display, EnumIter, Hash, Copy, Clone, PartialEq, Eq, DbEnum)]
+#[derive(Debug, Display, EnumIter, Hash, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, DbEnum)]
 pub enum RecipeDuration {
     #[display("short")]
     Short,
@@ -60,34 +89,50 @@ impl RecipeDuration {
     pub fn iter() -> impl Iterator<Item = Self> {
         <Self as strum::IntoEnumIterator>::iter()
     }
+
+    /// The longest a recipe of this duration usually takes, for planning when to start it.
+    pub fn estimated_minutes(&self) -> u32 {
+        match self {
+            Self::Short => 30,
+            Self::Medium => 60,
+            Self::Long => 180,
+            Self::ReallyLong => 300,
+        }
+    }
+}
+
+/// Whether a recipe has been made yet, or is one to try.
+#[derive(Debug, Display, EnumIter, Hash, Copy, Clone, PartialEq, Eq, DbEnum)]
+pub enum RecipeStatus {
+    #[display("tried")]
+    Tried,
+    #[display("want to try")]
+    WantToTry,
 }
 
 #[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub struct RecipeCategoryId(i32);
 
-impl RecipeCategoryId {
-    pub const INITIAL: Self = Self(1);
-
-    pub fn next(&self) -> Self {
-        Self(self.0 + 1)
-    }
-}
-
 #[derive(Queryable, Selectable, Identifiable, Insertable, Clone)]
 #[diesel(table_name = crate::database::schema::recipe_categories)]
 pub struct RecipeCategory {
     pub id: RecipeCategoryId,
     pub name: String,
+    /// What new recipes in the category start out taking, instead of short.
+    pub default_duration: Option<RecipeDuration>,
 }
 
 #[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub struct RecipeId(i32);
 
 impl RecipeId {
-    pub const INITIAL: Self = Self(1);
+    /// For referring to a recipe outside of the program, like in a URL.
+    pub fn from_i32(id: i32) -> Self {
+        Self(id)
+    }
 
-    pub fn next(&self) -> Self {
-        Self(self.0 + 1)
+    pub fn to_i32(self) -> i32 {
+        self.0
     }
 }
 
@@ -100,6 +145,22 @@ pub struct Recipe {
     pub description: String,
     pub duration: RecipeDuration,
     pub category: RecipeCategoryId,
+    /// From one to five stars, if the recipe has been rated.
+    pub rating: Option<i32>,
+    /// No longer made, so left out of the recipe lists and searches, but kept on the calendar.
+    pub archived: bool,
+    pub status: RecipeStatus,
+    /// How many people it feeds as written, if that's been filled in.
+    pub servings: Option<i32>,
+}
+
+impl Recipe {
+    pub fn to_handle(&self) -> RecipeHandle {
+        RecipeHandle {
+            id: self.id,
+            name: self.name.clone(),
+        }
+    }
 }
 
 #[derive(Queryable, Selectable, Identifiable, Insertable, Clone)]
@@ -153,14 +214,6 @@ impl IngredientMeasurement {
 #[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub struct IngredientUsageId(i32);
 
-impl IngredientUsageId {
-    pub const INITIAL: Self = Self(1);
-
-    pub fn next(&self) -> Self {
-        Self(self.0 + 1)
-    }
-}
-
 #[derive(Associations, Queryable, Selectable, Identifiable, Insertable, Clone)]
 #[diesel(belongs_to(Recipe))]
 #[diesel(belongs_to(Ingredient))]
@@ -172,6 +225,7 @@ pub struct IngredientUsage {
     pub ingredient_id: IngredientId,
     pub quantity: f32,
     pub quantity_units: Option<IngredientMeasurement>,
+    pub note: String,
 }
 
 #[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
@@ -197,6 +251,31 @@ pub struct IngredientCaloriesEntry {
     pub quantity_units: Option<IngredientMeasurement>,
 }
 
+#[derive(Associations, Queryable, Selectable, Identifiable, Insertable, Clone)]
+#[diesel(belongs_to(Ingredient))]
+#[diesel(primary_key(ingredient_id))]
+#[diesel(table_name = crate::database::schema::ingredient_compounds)]
+pub struct IngredientCompound {
+    pub ingredient_id: IngredientId,
+    pub quantity: f32,
+    pub quantity_units: Option<IngredientMeasurement>,
+}
+
+#[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct IngredientComponentId(i32);
+
+#[derive(Associations, Queryable, Selectable, Identifiable, Insertable, Clone)]
+#[diesel(belongs_to(IngredientCompound, foreign_key = compound_id))]
+#[diesel(primary_key(id))]
+#[diesel(table_name = crate::database::schema::ingredient_components)]
+pub struct IngredientComponent {
+    pub id: IngredientComponentId,
+    pub compound_id: IngredientId,
+    pub component_id: IngredientId,
+    pub quantity: f32,
+    pub quantity_units: Option<IngredientMeasurement>,
+}
+
 #[derive(Associations, Queryable, Selectable, Identifiable, Insertable, Clone)]
 #[diesel(belongs_to(RecipeCategory, foreign_key = recipe_id))]
 #[diesel(primary_key(day))]
@@ -204,4 +283,84 @@ pub struct IngredientCaloriesEntry {
 pub struct CalendarEntry {
     pub day: chrono::NaiveDate,
     pub recipe_id: RecipeId,
+    pub cooked: bool,
+    /// How many people are being fed, if it's different from what the recipe makes.
+    pub servings: Option<i32>,
+    /// Taken out of the freezer rather than cooked, so it isn't shopped for.
+    pub from_freezer: bool,
+}
+
+#[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct RecipeStepId(i32);
+
+#[derive(Associations, Queryable, Selectable, Identifiable, Insertable, Clone)]
+#[diesel(belongs_to(Recipe))]
+#[diesel(primary_key(id))]
+#[diesel(table_name = crate::database::schema::recipe_steps)]
+pub struct RecipeStep {
+    pub id: RecipeStepId,
+    pub recipe_id: RecipeId,
+    /// Which day of the recipe this step happens on, starting at 1. The recipe is scheduled on
+    /// the calendar on its last day.
+    pub day: i32,
+    pub label: String,
+}
+
+#[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct RecipePhotoId(i32);
+
+impl RecipePhotoId {
+    /// For telling photos apart in the image cache.
+    pub fn to_i32(self) -> i32 {
+        self.0
+    }
+}
+
+#[derive(Associations, Queryable, Selectable, Identifiable, Insertable, Clone)]
+#[diesel(belongs_to(Recipe))]
+#[diesel(primary_key(id))]
+#[diesel(table_name = crate::database::schema::recipe_photos)]
+pub struct RecipePhoto {
+    pub id: RecipePhotoId,
+    pub recipe_id: RecipeId,
+    /// The name of the file it came from, which tells what kind of image it is.
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct CookJournalEntryId(i32);
+
+/// How a recipe turned out one of the times it was cooked.
+#[derive(
+    Associations, Queryable, Selectable, Identifiable, Insertable, Clone, Debug, PartialEq,
+)]
+#[diesel(belongs_to(Recipe))]
+#[diesel(primary_key(id))]
+#[diesel(table_name = crate::database::schema::cook_journal)]
+pub struct CookJournalEntry {
+    pub id: CookJournalEntryId,
+    pub recipe_id: RecipeId,
+    pub day: chrono::NaiveDate,
+    /// From one to five stars, if it was rated.
+    pub rating: Option<i32>,
+    /// How it went, and what to change next time.
+    pub notes: String,
+}
+
+#[derive(DieselNewType, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct FreezerEntryId(i32);
+
+/// Portions of a recipe which were made ahead and frozen on the same day.
+#[derive(
+    Associations, Queryable, Selectable, Identifiable, Insertable, Clone, Debug, PartialEq,
+)]
+#[diesel(belongs_to(Recipe))]
+#[diesel(primary_key(id))]
+#[diesel(table_name = crate::database::schema::freezer)]
+pub struct FreezerEntry {
+    pub id: FreezerEntryId,
+    pub recipe_id: RecipeId,
+    pub portions: i32,
+    pub frozen: chrono::NaiveDate,
 }
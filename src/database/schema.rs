@@ -4,6 +4,43 @@ diesel::table! {
     calendar (day) {
         day -> Date,
         recipe_id -> Integer,
+        cooked -> Bool,
+        servings -> Nullable<Integer>,
+        from_freezer -> Bool,
+    }
+}
+
+diesel::table! {
+    change_log (id) {
+        id -> Integer,
+        table_name -> Text,
+        row_key -> Text,
+    }
+}
+
+diesel::table! {
+    cook_journal (id) {
+        id -> Integer,
+        recipe_id -> Integer,
+        day -> Date,
+        rating -> Nullable<Integer>,
+        notes -> Text,
+    }
+}
+
+diesel::table! {
+    freezer (id) {
+        id -> Integer,
+        recipe_id -> Integer,
+        portions -> Integer,
+        frozen -> Date,
+    }
+}
+
+diesel::table! {
+    imported_records (source, record_key) {
+        source -> Text,
+        record_key -> Text,
     }
 }
 
@@ -17,6 +54,31 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    ingredient_categories (id) {
+        id -> Integer,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    ingredient_components (id) {
+        id -> Integer,
+        compound_id -> Integer,
+        component_id -> Integer,
+        quantity -> Float,
+        quantity_units -> Nullable<crate::database::models::IngredientMeasurementMapping>,
+    }
+}
+
+diesel::table! {
+    ingredient_compounds (ingredient_id) {
+        ingredient_id -> Integer,
+        quantity -> Float,
+        quantity_units -> Nullable<crate::database::models::IngredientMeasurementMapping>,
+    }
+}
+
 diesel::table! {
     ingredient_usages (id) {
         id -> Integer,
@@ -24,6 +86,7 @@ diesel::table! {
         ingredient_id -> Integer,
         quantity -> Float,
         quantity_units -> Nullable<crate::database::models::IngredientMeasurementMapping>,
+        note -> Text,
     }
 }
 
@@ -31,7 +94,13 @@ diesel::table! {
     ingredients (id) {
         id -> Integer,
         name -> Text,
-        category -> Nullable<Text>,
+        staple -> Bool,
+        category_id -> Nullable<Integer>,
+        season -> Integer,
+        diet -> Integer,
+        density -> Nullable<Float>,
+        price -> Nullable<Float>,
+        price_units -> Nullable<crate::database::models::IngredientMeasurementMapping>,
     }
 }
 
@@ -39,6 +108,25 @@ diesel::table! {
     recipe_categories (id) {
         id -> Integer,
         name -> Text,
+        default_duration -> Nullable<crate::database::models::RecipeDurationMapping>,
+    }
+}
+
+diesel::table! {
+    recipe_photos (id) {
+        id -> Integer,
+        recipe_id -> Integer,
+        file_name -> Text,
+        data -> Binary,
+    }
+}
+
+diesel::table! {
+    recipe_steps (id) {
+        id -> Integer,
+        recipe_id -> Integer,
+        day -> Integer,
+        label -> Text,
     }
 }
 
@@ -49,20 +137,67 @@ diesel::table! {
         description -> Text,
         duration -> crate::database::models::RecipeDurationMapping,
         category -> Integer,
+        rating -> Nullable<Integer>,
+        archived -> Bool,
+        status -> crate::database::models::RecipeStatusMapping,
+        servings -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    settings (name) {
+        name -> Text,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    shopping_list_items (week, ingredient_id) {
+        week -> Date,
+        ingredient_id -> Integer,
+        checked -> Bool,
+    }
+}
+
+diesel::table! {
+    sync_state (machine) {
+        machine -> Text,
+        last_change_id -> Integer,
     }
 }
 
 diesel::joinable!(calendar -> recipes (recipe_id));
+diesel::joinable!(cook_journal -> recipes (recipe_id));
+diesel::joinable!(freezer -> recipes (recipe_id));
 diesel::joinable!(ingredient_calories -> ingredients (ingredient_id));
+diesel::joinable!(ingredient_components -> ingredient_compounds (compound_id));
+diesel::joinable!(ingredient_components -> ingredients (component_id));
+diesel::joinable!(ingredient_compounds -> ingredients (ingredient_id));
 diesel::joinable!(ingredient_usages -> ingredients (ingredient_id));
 diesel::joinable!(ingredient_usages -> recipes (recipe_id));
+diesel::joinable!(ingredients -> ingredient_categories (category_id));
+diesel::joinable!(recipe_photos -> recipes (recipe_id));
+diesel::joinable!(recipe_steps -> recipes (recipe_id));
 diesel::joinable!(recipes -> recipe_categories (category));
+diesel::joinable!(shopping_list_items -> ingredients (ingredient_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     calendar,
+    change_log,
+    cook_journal,
+    freezer,
+    imported_records,
     ingredient_calories,
+    ingredient_categories,
+    ingredient_components,
+    ingredient_compounds,
     ingredient_usages,
     ingredients,
     recipe_categories,
+    recipe_photos,
+    recipe_steps,
     recipes,
+    settings,
+    shopping_list_items,
+    sync_state,
 );
@@ -0,0 +1,131 @@
+//! Allergens and diets. Ingredients are marked with what they contain and which diets they're safe
+//! for, and recipes get badges from their ingredients. A household's restrictions are the same
+//! flags, meaning to avoid the allergen or stick to the diet.
+
+use derive_more::Display;
+use strum::EnumIter;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumIter)]
+pub enum DietFlag {
+    #[display("contains nuts")]
+    Nuts,
+    #[display("contains gluten")]
+    Gluten,
+    #[display("contains dairy")]
+    Dairy,
+    #[display("vegetarian")]
+    Vegetarian,
+    #[display("vegan")]
+    Vegan,
+}
+
+impl DietFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Nuts => "nuts",
+            Self::Gluten => "gluten",
+            Self::Dairy => "dairy",
+            Self::Vegetarian => "vegetarian",
+            Self::Vegan => "vegan",
+        }
+    }
+
+    /// The inverse of `as_str`, like for the settings.
+    #[expect(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|f| f.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+
+    /// Whether it's something in the food, rather than a diet the food is safe for.
+    pub fn is_allergen(&self) -> bool {
+        matches!(self, Self::Nuts | Self::Gluten | Self::Dairy)
+    }
+
+    /// How it reads as a household restriction, like "no nuts".
+    pub fn restriction(&self) -> String {
+        if self.is_allergen() {
+            format!("no {}", self.as_str())
+        } else {
+            self.as_str().into()
+        }
+    }
+
+    fn bit(&self) -> i32 {
+        1 << *self as i32
+    }
+
+    /// Whether it's set in an ingredient's flags, see [`crate::database::models::Ingredient::diet`].
+    pub fn is_set(&self, flags: i32) -> bool {
+        flags & self.bit() != 0
+    }
+
+    pub fn set(&self, flags: &mut i32, value: bool) {
+        if value {
+            *flags |= self.bit();
+        } else {
+            *flags &= !self.bit();
+        }
+    }
+}
+
+/// The badges for a recipe from its ingredients' flags: the allergens any of them contain, and the
+/// diets all of them are safe for. Anything vegan is vegetarian too.
+pub fn recipe_badges(ingredient_flags: impl IntoIterator<Item = i32>) -> Vec<DietFlag> {
+    let flags: Vec<_> = ingredient_flags.into_iter().collect();
+    let safe_for = |diet: DietFlag| {
+        !flags.is_empty()
+            && flags.iter().all(|&f| {
+                diet.is_set(f) || (diet == DietFlag::Vegetarian && DietFlag::Vegan.is_set(f))
+            })
+    };
+    DietFlag::iter()
+        .filter(|flag| {
+            if flag.is_allergen() {
+                flags.iter().any(|&f| flag.is_set(f))
+            } else {
+                safe_for(*flag)
+            }
+        })
+        .collect()
+}
+
+/// The restrictions a recipe with the badges goes against. A recipe isn't safe for a diet unless
+/// all its ingredients are known to be.
+pub fn conflicts(badges: &[DietFlag], restrictions: &[DietFlag]) -> Vec<DietFlag> {
+    restrictions
+        .iter()
+        .copied()
+        .filter(|r| badges.contains(r) == r.is_allergen())
+        .collect()
+}
+
+#[test]
+fn recipe_badges_test() {
+    use DietFlag::*;
+
+    let flags = |set: &[DietFlag]| {
+        let mut flags = 0;
+        for f in set {
+            f.set(&mut flags, true);
+        }
+        flags
+    };
+    assert_eq!(
+        recipe_badges([flags(&[Vegan]), flags(&[Vegetarian, Dairy])]),
+        [Dairy, Vegetarian]
+    );
+    assert_eq!(
+        recipe_badges([flags(&[Vegan, Nuts]), flags(&[Vegan])]),
+        [Nuts, Vegetarian, Vegan]
+    );
+    assert!(recipe_badges([flags(&[Vegan]), 0]).is_empty());
+    assert!(recipe_badges([]).is_empty());
+
+    let badges = [Dairy, Vegetarian];
+    assert!(conflicts(&badges, &[Nuts, Vegetarian]).is_empty());
+    assert_eq!(conflicts(&badges, &[Dairy, Vegan]), [Dairy, Vegan]);
+}
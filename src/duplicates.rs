@@ -0,0 +1,135 @@
+//! Finding ingredients which are probably the same thing entered more than once, like "Tomato"
+//! and "tomatoes". Imports from the previous version leave a lot of these behind, since it didn't
+//! care about the case of names.
+
+use derive_more::Display;
+
+/// How many single character edits apart two names of at least this length can be and still be
+/// thought of as spelling mistakes.
+const SPELLING_DISTANCES: [(usize, usize); 2] = [(5, 1), (9, 2)];
+
+/// The number of single character insertions, deletions and substitutions to get from `a` to `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The word with any plural ending taken off, like "berries" to "berry".
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies").filter(|s| s.len() > 1) {
+        return format!("{stem}y");
+    }
+    for ending in ["oes", "ches", "shes", "sses", "xes"] {
+        if word.ends_with(ending) {
+            return word[..word.len() - 2].into();
+        }
+    }
+    match word.strip_suffix('s') {
+        Some(stem) if stem.len() > 2 && !stem.ends_with(['s', 'u', 'i']) => stem.into(),
+        _ => word.into(),
+    }
+}
+
+#[test]
+fn singular_test() {
+    assert_eq!(singular("tomatoes"), "tomato");
+    assert_eq!(singular("berries"), "berry");
+    assert_eq!(singular("peaches"), "peach");
+    assert_eq!(singular("onions"), "onion");
+    assert_eq!(singular("onion"), "onion");
+    assert_eq!(singular("glass"), "glass");
+    assert_eq!(singular("hummus"), "hummus");
+    assert_eq!(singular("peas"), "pea");
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum DuplicateReason {
+    #[display("different capitalization")]
+    Capitalization,
+    #[display("singular and plural")]
+    Plural,
+    #[display("similar spelling")]
+    Spelling,
+}
+
+/// Why the two names are probably the same ingredient, if they are.
+fn duplicate_reason(a: &str, b: &str) -> Option<DuplicateReason> {
+    let (a, b) = (a.trim().to_lowercase(), b.trim().to_lowercase());
+    if a == b {
+        return Some(DuplicateReason::Capitalization);
+    }
+    let words = |s: &str| s.split_whitespace().map(singular).collect::<Vec<_>>();
+    let (a_words, b_words) = (words(&a), words(&b));
+    if a_words == b_words {
+        return Some(DuplicateReason::Plural);
+    }
+
+    // "1% milk" and "2% milk" are one character apart, but aren't the same.
+    let digits = |s: &str| s.chars().filter(char::is_ascii_digit).collect::<String>();
+    if digits(&a) != digits(&b) {
+        return None;
+    }
+    let (a, b) = (a_words.join(" "), b_words.join(" "));
+    let shortest = a.chars().count().min(b.chars().count());
+    let allowed = SPELLING_DISTANCES
+        .iter()
+        .filter(|(length, _)| shortest >= *length)
+        .map(|(_, distance)| *distance)
+        .max()?;
+    if a.chars().count().abs_diff(b.chars().count()) > allowed {
+        return None;
+    }
+    (edit_distance(&a, &b) <= allowed).then_some(DuplicateReason::Spelling)
+}
+
+/// The pairs of names which are probably the same ingredient, by their index in `names`.
+pub fn likely_duplicates(names: &[&str]) -> Vec<(usize, usize, DuplicateReason)> {
+    let mut pairs = vec![];
+    for (i, a) in names.iter().enumerate() {
+        for (j, b) in names.iter().enumerate().skip(i + 1) {
+            if let Some(reason) = duplicate_reason(a, b) {
+                pairs.push((i, j, reason));
+            }
+        }
+    }
+    pairs
+}
+
+#[test]
+fn likely_duplicates_test() {
+    use DuplicateReason::*;
+
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+
+    let names = [
+        "Tomato",
+        "tomatoes",
+        "salt",
+        "malt",
+        "Salt ",
+        "1% milk",
+        "2% milk",
+        "parmesan cheese",
+        "parmesean cheese",
+        "green onions",
+        "Green Onion",
+    ];
+    assert_eq!(
+        likely_duplicates(&names),
+        [
+            (0, 1, Plural),
+            (2, 4, Capitalization),
+            (7, 8, Spelling),
+            (9, 10, Plural),
+        ]
+    );
+}
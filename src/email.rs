@@ -0,0 +1,140 @@
+//! Sending email through an SMTP server, for mailing the week's menu and shopping list.
+
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport as _};
+
+/// The port for SMTP with STARTTLS, which most providers want.
+pub const DEFAULT_PORT: u16 = 587;
+
+/// The port for SMTP over TLS from the start.
+const IMPLICIT_TLS_PORT: u16 = 465;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SmtpSettings {
+    pub server: String,
+    pub port: Option<u16>,
+    pub username: String,
+    pub password: String,
+    /// The address the email comes from. The username is used if this is empty.
+    pub from: String,
+    pub recipients: Vec<String>,
+}
+
+impl SmtpSettings {
+    pub fn is_configured(&self) -> bool {
+        !self.server.is_empty() && !self.recipients.is_empty()
+    }
+
+    fn from(&self) -> &str {
+        if self.from.is_empty() {
+            &self.username
+        } else {
+            &self.from
+        }
+    }
+}
+
+/// Splits a list of addresses separated by commas, semicolons or whitespace.
+pub fn parse_recipients(s: &str) -> Vec<String> {
+    s.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|a| !a.is_empty())
+        .map(|a| a.into())
+        .collect()
+}
+
+pub struct Attachment {
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub content: Vec<u8>,
+}
+
+fn build_message(
+    settings: &SmtpSettings,
+    subject: &str,
+    body: String,
+    attachments: Vec<Attachment>,
+) -> crate::Result<Message> {
+    let from: Mailbox = settings
+        .from()
+        .parse()
+        .map_err(|e| format!("bad from address {:?}: {e}", settings.from()))?;
+    let mut builder = Message::builder().from(from).subject(subject);
+    for recipient in &settings.recipients {
+        let to: Mailbox = recipient
+            .parse()
+            .map_err(|e| format!("bad address {recipient:?}: {e}"))?;
+        builder = builder.to(to);
+    }
+
+    let mut parts = MultiPart::mixed().singlepart(SinglePart::plain(body));
+    for a in attachments {
+        parts = parts.singlepart(
+            lettre::message::Attachment::new(a.file_name)
+                .body(a.content, ContentType::parse(a.content_type)?),
+        );
+    }
+    Ok(builder.multipart(parts)?)
+}
+
+/// Sends an email with a plain text body and the given attachments to everyone in the settings.
+/// This blocks until the server has taken the message, so it belongs on its own thread.
+pub fn send(
+    settings: &SmtpSettings,
+    subject: &str,
+    body: String,
+    attachments: Vec<Attachment>,
+) -> crate::Result<()> {
+    let message = build_message(settings, subject, body, attachments)?;
+    let port = settings.port.unwrap_or(DEFAULT_PORT);
+    let transport = if port == IMPLICIT_TLS_PORT {
+        SmtpTransport::relay(&settings.server)?
+    } else {
+        SmtpTransport::starttls_relay(&settings.server)?
+    };
+    let mut transport = transport.port(port);
+    if !settings.username.is_empty() {
+        transport = transport.credentials(Credentials::new(
+            settings.username.clone(),
+            settings.password.clone(),
+        ));
+    }
+    transport.build().send(&message)?;
+    Ok(())
+}
+
+#[test]
+fn build_message_test() {
+    let settings = SmtpSettings {
+        server: "smtp.example.com".into(),
+        username: "cook@example.com".into(),
+        recipients: parse_recipients("a@example.com, b@example.com;c@example.com"),
+        ..Default::default()
+    };
+    assert_eq!(settings.recipients.len(), 3);
+    assert!(settings.is_configured());
+
+    let message = build_message(
+        &settings,
+        "Menu for the week",
+        "Sunday: Pancakes".into(),
+        vec![Attachment {
+            file_name: "menu.rtf".into(),
+            content_type: "application/rtf",
+            content: b"{\\rtf1}".to_vec(),
+        }],
+    )
+    .unwrap();
+    let formatted = String::from_utf8(message.formatted()).unwrap();
+    assert!(formatted.contains("From: cook@example.com"));
+    assert!(formatted.contains("To: a@example.com, b@example.com, c@example.com"));
+    assert!(formatted.contains("Subject: Menu for the week"));
+    assert!(formatted.contains("Sunday: Pancakes"));
+    assert!(formatted.contains("filename=\"menu.rtf\""));
+
+    let bad = SmtpSettings {
+        recipients: vec!["not an address".into()],
+        ..settings
+    };
+    assert!(build_message(&bad, "", "".into(), vec![]).is_err());
+}
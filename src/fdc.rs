@@ -0,0 +1,134 @@
+// Copyright 2023 Remi Bernotavicius
+
+//! Client for the USDA FoodData Central API. See <https://fdc.nal.usda.gov/api-guide>
+
+use serde::Deserialize;
+
+const SEARCH_URL: &str = "https://api.nal.usda.gov/fdc/v1/foods/search";
+
+/// Key which can be used without signing up, but is heavily rate-limited.
+pub const DEMO_API_KEY: &str = "DEMO_KEY";
+
+/// Nutrient numbers for energy in kcal. Foundation foods often only have the Atwater values.
+const ENERGY_NUTRIENT_IDS: [u32; 3] = [1008, 2047, 2048];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    foods: Vec<SearchResultFood>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResultFood {
+    fdc_id: u64,
+    description: String,
+    brand_owner: Option<String>,
+    #[serde(default)]
+    food_nutrients: Vec<FoodNutrient>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FoodNutrient {
+    nutrient_id: u32,
+    unit_name: String,
+    value: Option<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Food {
+    pub fdc_id: u64,
+    pub description: String,
+    pub brand: Option<String>,
+    /// Nutrient values from search results are always per 100 grams.
+    pub calories_per_100g: Option<f32>,
+}
+
+impl From<SearchResultFood> for Food {
+    fn from(food: SearchResultFood) -> Self {
+        let calories_per_100g = ENERGY_NUTRIENT_IDS.iter().find_map(|id| {
+            food.food_nutrients
+                .iter()
+                .find(|n| n.nutrient_id == *id && n.unit_name.eq_ignore_ascii_case("kcal"))
+                .and_then(|n| n.value)
+        });
+        Self {
+            fdc_id: food.fdc_id,
+            description: food.description,
+            brand: food.brand_owner,
+            calories_per_100g,
+        }
+    }
+}
+
+fn parse_search_response(body: &str) -> crate::Result<Vec<Food>> {
+    let response: SearchResponse = serde_json::from_str(body)?;
+    Ok(response.foods.into_iter().map(Food::from).collect())
+}
+
+#[test]
+fn parse_search_response_test() {
+    let body = r#"{
+        "totalHits": 2,
+        "foods": [
+            {
+                "fdcId": 171287,
+                "description": "Egg, whole, raw, fresh",
+                "dataType": "SR Legacy",
+                "foodNutrients": [
+                    {"nutrientId": 1003, "unitName": "G", "value": 12.6},
+                    {"nutrientId": 1008, "unitName": "KCAL", "value": 143.0}
+                ]
+            },
+            {
+                "fdcId": 2346404,
+                "description": "Eggs, Grade A, Large, egg whole",
+                "brandOwner": "Acme",
+                "foodNutrients": [
+                    {"nutrientId": 1008, "unitName": "kJ", "value": 615.0},
+                    {"nutrientId": 2047, "unitName": "KCAL", "value": 148.0}
+                ]
+            },
+            {
+                "fdcId": 1,
+                "description": "Water"
+            }
+        ]
+    }"#;
+    assert_eq!(
+        parse_search_response(body).unwrap(),
+        vec![
+            Food {
+                fdc_id: 171287,
+                description: "Egg, whole, raw, fresh".into(),
+                brand: None,
+                calories_per_100g: Some(143.0),
+            },
+            Food {
+                fdc_id: 2346404,
+                description: "Eggs, Grade A, Large, egg whole".into(),
+                brand: Some("Acme".into()),
+                calories_per_100g: Some(148.0),
+            },
+            Food {
+                fdc_id: 1,
+                description: "Water".into(),
+                brand: None,
+                calories_per_100g: None,
+            },
+        ]
+    );
+}
+
+/// Searches for foods by name. This blocks on the network, so it shouldn't be called from the UI
+/// thread.
+pub fn search(api_key: &str, query: &str) -> crate::Result<Vec<Food>> {
+    let body = ureq::get(SEARCH_URL)
+        .query("api_key", api_key)
+        .query("query", query)
+        .query("pageSize", "25")
+        .call()?
+        .into_string()?;
+    parse_search_response(&body)
+}
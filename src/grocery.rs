@@ -0,0 +1,232 @@
+//! Pushing the shopping list to the grocery list apps people already use on their phones.
+//!
+//! AnyList has no public API, so it isn't here. Its "Add Items" box takes a pasted list instead.
+
+use serde::Deserialize;
+
+const TODOIST_URL: &str = "https://api.todoist.com/api/v1";
+const BRING_URL: &str = "https://api.getbring.com/rest/v2";
+
+/// The key Bring's own web app sends with every request.
+const BRING_API_KEY: &str = "cof4Nc6D8saplXjE3h3HXqHH8m7VU2i1Gs0g85Sp";
+
+/// Something to buy, like "tomatoes" with the specification "2 cups (diced)".
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroceryItem {
+    pub name: String,
+    pub specification: String,
+}
+
+impl GroceryItem {
+    /// The item as one line of text, for apps which don't keep the specification separately.
+    pub fn line(&self) -> String {
+        if self.specification.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}, {}", self.name, self.specification)
+        }
+    }
+}
+
+/// A grocery list app the shopping list can be pushed to.
+pub trait GroceryExporter: Send {
+    /// Adds the items to the list. This blocks on the network, so it shouldn't be called from the
+    /// UI thread.
+    fn push(&self, items: &[GroceryItem]) -> crate::Result<()>;
+}
+
+/// The accounts for the grocery list apps, as kept in the settings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GroceryAccounts {
+    /// A Todoist API token, found under Settings > Integrations > Developer.
+    pub todoist_token: String,
+    /// The Todoist project to add the items to. They go in the inbox if this is empty.
+    pub todoist_project: String,
+    pub bring_email: String,
+    pub bring_password: String,
+}
+
+impl GroceryAccounts {
+    /// The apps which are set up, by name.
+    pub fn exporters(&self) -> Vec<(&'static str, Box<dyn GroceryExporter>)> {
+        let mut exporters: Vec<(&'static str, Box<dyn GroceryExporter>)> = vec![];
+        if !self.todoist_token.is_empty() {
+            exporters.push((
+                "Todoist",
+                Box::new(Todoist {
+                    token: self.todoist_token.clone(),
+                    project: self.todoist_project.clone(),
+                }),
+            ));
+        }
+        if !self.bring_email.is_empty() {
+            exporters.push((
+                "Bring!",
+                Box::new(Bring {
+                    email: self.bring_email.clone(),
+                    password: self.bring_password.clone(),
+                }),
+            ));
+        }
+        exporters
+    }
+}
+
+struct Todoist {
+    token: String,
+    project: String,
+}
+
+#[derive(Deserialize)]
+struct TodoistProjects {
+    results: Vec<TodoistProject>,
+}
+
+#[derive(Deserialize)]
+struct TodoistProject {
+    id: String,
+    name: String,
+}
+
+fn find_todoist_project(body: &str, name: &str) -> crate::Result<String> {
+    let projects: TodoistProjects = serde_json::from_str(body)?;
+    let project = projects
+        .results
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("no Todoist project named \"{name}\""))?;
+    Ok(project.id)
+}
+
+fn todoist_task(item: &GroceryItem, project_id: Option<&str>) -> serde_json::Value {
+    let mut task = serde_json::json!({ "content": item.line() });
+    if let Some(project_id) = project_id {
+        task["project_id"] = project_id.into();
+    }
+    task
+}
+
+impl Todoist {
+    fn authorization(&self) -> String {
+        format!("Bearer {}", self.token)
+    }
+}
+
+impl GroceryExporter for Todoist {
+    fn push(&self, items: &[GroceryItem]) -> crate::Result<()> {
+        let project_id = if self.project.is_empty() {
+            None
+        } else {
+            let body = ureq::get(&format!("{TODOIST_URL}/projects"))
+                .set("Authorization", &self.authorization())
+                .call()?
+                .into_string()?;
+            Some(find_todoist_project(&body, &self.project)?)
+        };
+        for item in items {
+            ureq::post(&format!("{TODOIST_URL}/tasks"))
+                .set("Authorization", &self.authorization())
+                .set("Content-Type", "application/json")
+                .send_string(&todoist_task(item, project_id.as_deref()).to_string())?;
+        }
+        Ok(())
+    }
+}
+
+struct Bring {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct BringLogin {
+    #[serde(rename = "bringListUUID")]
+    bring_list_uuid: String,
+    access_token: String,
+}
+
+fn parse_bring_login(body: &str) -> crate::Result<BringLogin> {
+    Ok(serde_json::from_str(body)?)
+}
+
+impl GroceryExporter for Bring {
+    // The items go on the account's default list.
+    fn push(&self, items: &[GroceryItem]) -> crate::Result<()> {
+        let body = ureq::post(&format!("{BRING_URL}/bringauth"))
+            .set("X-BRING-API-KEY", BRING_API_KEY)
+            .send_form(&[
+                ("email", self.email.as_str()),
+                ("password", self.password.as_str()),
+            ])?
+            .into_string()?;
+        let login = parse_bring_login(&body)?;
+        let authorization = format!("Bearer {}", login.access_token);
+        for item in items {
+            ureq::put(&format!("{BRING_URL}/bringlists/{}", login.bring_list_uuid))
+                .set("X-BRING-API-KEY", BRING_API_KEY)
+                .set("Authorization", &authorization)
+                .send_form(&[
+                    ("purchase", item.name.as_str()),
+                    ("specification", item.specification.as_str()),
+                ])?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn todoist_test() {
+    let body = r#"{
+        "results": [
+            {"id": "6Jf8VQXxpwv56VQ7", "name": "Inbox", "color": "grey"},
+            {"id": "6X7rM8997g3RQmvh", "name": "Groceries", "color": "green"}
+        ],
+        "next_cursor": null
+    }"#;
+    assert_eq!(
+        find_todoist_project(body, "groceries").unwrap(),
+        "6X7rM8997g3RQmvh"
+    );
+    assert!(find_todoist_project(body, "Errands").is_err());
+
+    let item = GroceryItem {
+        name: "tomatoes".into(),
+        specification: "2 cups (diced)".into(),
+    };
+    assert_eq!(
+        todoist_task(&item, Some("6X7rM8997g3RQmvh")),
+        serde_json::json!({
+            "content": "tomatoes, 2 cups (diced)",
+            "project_id": "6X7rM8997g3RQmvh",
+        })
+    );
+    let item = GroceryItem {
+        name: "bread".into(),
+        specification: "".into(),
+    };
+    assert_eq!(
+        todoist_task(&item, None),
+        serde_json::json!({ "content": "bread" })
+    );
+}
+
+#[test]
+fn parse_bring_login_test() {
+    let body = r#"{
+        "uuid": "9e4f2a1c-0000-4000-8000-000000000000",
+        "publicUuid": "c1d2e3f4-0000-4000-8000-000000000000",
+        "email": "cook@example.com",
+        "name": "Cook",
+        "bringListUUID": "b5a6c7d8-0000-4000-8000-000000000000",
+        "access_token": "abc.def",
+        "refresh_token": "ghi",
+        "token_type": "Bearer",
+        "expires_in": 604799
+    }"#;
+    let login = parse_bring_login(body).unwrap();
+    assert_eq!(
+        login.bring_list_uuid,
+        "b5a6c7d8-0000-4000-8000-000000000000"
+    );
+    assert_eq!(login.access_token, "abc.def");
+}
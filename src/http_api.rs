@@ -0,0 +1,541 @@
+//! A small HTTP server for looking at the recipe book from another device on the network, like a
+//! phone in the kitchen. The recipes, searching them, and a week's menu and shopping list are served
+//! as JSON, along with a page showing tonight's dinner and one with the shopping list. There's also
+//! a summary of today's and tomorrow's dinner for a Home Assistant dashboard. The only change which
+//! can be made is checking items off the shopping list.
+//!
+//! Only this computer can connect unless serving to the network is turned on, since there's no
+//! telling who else is on it. Changes also need the token the shopping list page is served with, so
+//! another site open in the same browser can't make them.
+
+use crate::database;
+use crate::database::models::{IngredientId, RecipeId};
+use crate::query;
+use crate::recipe::total_calories;
+use crate::Result;
+use chrono::Datelike as _;
+use serde_json::json;
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::Path;
+use std::time::Duration;
+
+/// The setting with the port to serve on. The server isn't started when it isn't set.
+pub const PORT_SETTING: &str = "http_api_port";
+
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// The setting for serving to other devices on the network, not just this computer.
+pub const LAN_SETTING: &str = "http_api_lan";
+
+const INDEX_PAGE: &str = include_str!("http_api.html");
+const SHOPPING_LIST_PAGE: &str = include_str!("http_api_shopping_list.html");
+
+/// Starts serving the database at `path` on a thread of its own with its own connection. It's
+/// served on every network interface with `lan`, otherwise only on this computer's loopback one.
+pub fn start(path: &Path, port: u16, lan: bool, read_only: bool) -> Result<()> {
+    let address = if lan {
+        Ipv4Addr::UNSPECIFIED
+    } else {
+        Ipv4Addr::LOCALHOST
+    };
+    let listener = TcpListener::bind((address, port))?;
+    let mut conn = if read_only {
+        database::establish_read_only_connection(path)?
+    } else {
+        database::establish_connection(path)?
+    };
+    log::info!("serving the recipe book on {address}:{port}");
+    let token = new_token();
+    std::thread::Builder::new()
+        .name("http api".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream
+                    .map_err(Into::into)
+                    .and_then(|stream| handle(&mut conn, stream, read_only, &token));
+                if let Err(error) = result {
+                    log::warn!("failed to answer a request: {error}");
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// A secret made up each start, which the pages served here send back with changes.
+fn new_token() -> String {
+    use std::hash::{BuildHasher as _, Hasher as _};
+
+    // Each one is seeded randomly by the OS.
+    let random = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", random(), random())
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn html(page: &str) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/html",
+            body: page.into(),
+        }
+    }
+
+    fn json(value: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message }).to_string(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+impl From<diesel::result::Error> for Response {
+    fn from(error: diesel::result::Error) -> Self {
+        match error {
+            diesel::result::Error::NotFound => Self::error(404, "not found"),
+            error => {
+                log::error!("database error: {error}");
+                Self::error(500, &error.to_string())
+            }
+        }
+    }
+}
+
+fn handle(
+    conn: &mut database::Connection,
+    mut stream: TcpStream,
+    read_only: bool,
+    token: &str,
+) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The headers aren't needed, but are read so the client isn't cut off while sending them.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let response = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        [method, target, _] => {
+            // The cache is per thread, so this only picks up changes made in the UI. The UI's
+            // cache is left as it is, but nothing changed here is kept in it.
+            query::clear_cache();
+            respond(conn, method, target, read_only, token).unwrap_or_else(Response::from)
+        }
+        _ => Response::error(400, "malformed request"),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\n\
+        Connection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    Ok(())
+}
+
+/// Decodes a query string parameter, where spaces are `+` and anything else can be `%XX`.
+fn percent_decode(s: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => match rest
+                .get(..2)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+            {
+                Some(decoded) => {
+                    bytes.push(decoded);
+                    rest = &rest[2..];
+                }
+                None => bytes.push(b),
+            },
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[test]
+fn percent_decode_test() {
+    assert_eq!(percent_decode("carrot+soup"), "carrot soup");
+    assert_eq!(
+        percent_decode("cr%C3%A8me%20br%c3%bbl%C3%A9e"),
+        "crème brûlée"
+    );
+    assert_eq!(percent_decode("100%"), "100%");
+    assert_eq!(percent_decode("%zz"), "%zz");
+}
+
+fn parameter(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|p| p.split_once('='))
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| percent_decode(v))
+}
+
+/// The week asked for with `?week=YYYY-MM-DD`, or this one. Weeks start on `start`, like in the
+/// calendar.
+fn week(query: &str, start: chrono::Weekday) -> std::result::Result<chrono::NaiveWeek, Response> {
+    let day = match parameter(query, "week") {
+        Some(day) => day
+            .parse::<chrono::NaiveDate>()
+            .map_err(|_| Response::error(400, "week must be a date like 2024-01-31"))?,
+        None => chrono::Local::now().date_naive(),
+    };
+    Ok(day.week(start))
+}
+
+fn respond(
+    conn: &mut database::Connection,
+    method: &str,
+    target: &str,
+    read_only: bool,
+    token: &str,
+) -> std::result::Result<Response, diesel::result::Error> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.trim_end_matches('/');
+    if method == "POST" && path == "/api/shopping-list/check" {
+        if parameter(query, "token").as_deref() != Some(token) {
+            return Ok(Response::error(403, "reload the page and try again"));
+        }
+        return check_shopping_list_item(conn, query, read_only);
+    } else if method != "GET" {
+        return Ok(Response::error(405, "method not allowed"));
+    }
+    Ok(match path {
+        "" => Response::html(INDEX_PAGE),
+        "/shopping-list" => Response::html(&SHOPPING_LIST_PAGE.replace("{token}", token)),
+        "/api/recipes" => {
+            let search = parameter(query, "q").unwrap_or_default();
+            let recipes = query::search_recipes(conn, &mut None, &search, false)?;
+            Response::json(
+                recipes
+                    .into_iter()
+                    .map(|(id, name)| json!({ "id": id.to_i32(), "name": name }))
+                    .collect(),
+            )
+        }
+        "/api/menu" => match week(query, query::get_week_start(conn)?) {
+            Ok(week) => menu(conn, week)?,
+            Err(response) => response,
+        },
+        "/api/today" => match parameter(query, "date") {
+            Some(date) => match date.parse() {
+                Ok(date) => today(conn, date)?,
+                Err(_) => Response::error(400, "date must be a date like 2024-01-31"),
+            },
+            None => today(conn, chrono::Local::now().date_naive())?,
+        },
+        "/api/shopping-list" => match week(query, query::get_week_start(conn)?) {
+            Ok(week) => shopping_list(
+                conn,
+                week,
+                parameter(query, "staples").is_some_and(|s| s == "true"),
+            )?,
+            Err(response) => response,
+        },
+        path => match path
+            .strip_prefix("/api/recipes/")
+            .and_then(|id| id.parse().ok())
+        {
+            Some(id) => recipe(conn, RecipeId::from_i32(id))?,
+            None => Response::error(404, "not found"),
+        },
+    })
+}
+
+fn recipe(conn: &mut database::Connection, id: RecipeId) -> diesel::QueryResult<Response> {
+    let (recipe, category, ingredients) = query::get_recipe(conn, id)?;
+    let steps = query::get_recipe_steps(conn, id)?;
+    Ok(Response::json(json!({
+        "id": recipe.id.to_i32(),
+        "name": recipe.name,
+        "category": category,
+        "description": recipe.description,
+        "duration": recipe.duration.to_string(),
+        "calories": total_calories(&ingredients).round(),
+        "ingredients": ingredients.iter().map(|i| json!({
+            "name": i.ingredient.name,
+            "quantity": i.quantity,
+            "units": i.quantity_units.map(|u| u.as_str()),
+            "note": i.note,
+        })).collect::<Vec<_>>(),
+        "steps": steps.iter().map(|s| json!({
+            "day": s.day,
+            "label": s.label,
+        })).collect::<Vec<_>>(),
+    })))
+}
+
+fn menu(conn: &mut database::Connection, week: chrono::NaiveWeek) -> diesel::QueryResult<Response> {
+    let recipes = query::get_calendar_week(conn, week)?;
+    let cooked = query::get_calendar_week_cooked(conn, week)?;
+    let days: Vec<_> = week
+        .first_day()
+        .iter_days()
+        .take(7)
+        .map(|date| {
+            let recipe = recipes.get(&date.weekday());
+            json!({
+                "date": date.to_string(),
+                "day": date.weekday().to_string(),
+                "recipe": recipe.map(|r| json!({ "id": r.id.to_i32(), "name": r.name })),
+                "cooked": cooked.contains(&date.weekday()),
+            })
+        })
+        .collect();
+    Ok(Response::json(json!({
+        "week": week.first_day().to_string(),
+        "today": chrono::Local::now().date_naive().to_string(),
+        "days": days,
+    })))
+}
+
+fn day_summary(
+    conn: &mut database::Connection,
+    date: chrono::NaiveDate,
+) -> diesel::QueryResult<serde_json::Value> {
+    let week = date.week(query::get_week_start(conn)?);
+    let recipe = query::get_calendar_week(conn, week)?.remove(&date.weekday());
+    let recipe = match recipe {
+        Some(recipe) => {
+            let (recipe, category, _) = query::get_recipe(conn, recipe.id)?;
+            json!({
+                "id": recipe.id.to_i32(),
+                "name": recipe.name,
+                "category": category,
+                "duration": recipe.duration.to_string(),
+            })
+        }
+        None => serde_json::Value::Null,
+    };
+    Ok(json!({
+        "date": date.to_string(),
+        "day": date.weekday().to_string(),
+        "recipe": recipe,
+        "cooked": query::get_calendar_week_cooked(conn, week)?.contains(&date.weekday()),
+    }))
+}
+
+/// What's for dinner today and tomorrow, shaped for a Home Assistant RESTful sensor. The `state` is
+/// the name of today's recipe, and the days can be used as attributes, like
+///
+/// ```yaml
+/// sensor:
+///   - platform: rest
+///     name: Dinner
+///     resource: http://recipes.local:8080/api/today
+///     value_template: "{{ value_json.state }}"
+///     json_attributes: [today, tomorrow]
+/// ```
+fn today(
+    conn: &mut database::Connection,
+    date: chrono::NaiveDate,
+) -> diesel::QueryResult<Response> {
+    let today = day_summary(conn, date)?;
+    let tomorrow = day_summary(conn, date + chrono::Days::new(1))?;
+    let state = today["recipe"]["name"]
+        .as_str()
+        .unwrap_or("Nothing planned")
+        .to_owned();
+    Ok(Response::json(json!({
+        "state": state,
+        "today": today,
+        "tomorrow": tomorrow,
+    })))
+}
+
+/// The ingredients of the week's recipes, scaled to how many are eating each day and added up per
+/// ingredient and units, and whether they've been checked off. Staples are left off unless asked
+/// for with `?staples=true`.
+fn shopping_list(
+    conn: &mut database::Connection,
+    week: chrono::NaiveWeek,
+    include_staples: bool,
+) -> diesel::QueryResult<Response> {
+    let categories = query::get_ingredient_category_names(conn)?;
+    let checked = query::get_checked_shopping_list_items(conn, week)?;
+    let mut items = BTreeMap::new();
+    for (usage, ingredient) in query::get_calendar_week_shopping_ingredients(conn, week)? {
+        if ingredient.staple && !include_staples {
+            continue;
+        }
+        let category = ingredient
+            .category_id
+            .and_then(|c| categories.get(&c).cloned());
+        let units = usage.quantity_units.map(|u| u.as_str());
+        let id = ingredient.id;
+        *items
+            .entry((category, ingredient.name, units, id))
+            .or_insert(0.0) += usage.quantity;
+    }
+    Ok(Response::json(
+        items
+            .into_iter()
+            .map(|((category, name, units, id), quantity)| {
+                json!({
+                    "category": category,
+                    "ingredient_id": id.to_i32(),
+                    "name": name,
+                    "quantity": quantity,
+                    "units": units,
+                    "checked": checked.contains(&id),
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// Checks an item off the shopping list, or back on with `checked=false`. The item is given by
+/// `ingredient`, its id, and `week`. Only asked for with the right `token`.
+fn check_shopping_list_item(
+    conn: &mut database::Connection,
+    query: &str,
+    read_only: bool,
+) -> diesel::QueryResult<Response> {
+    if read_only {
+        return Ok(Response::error(403, "the recipe book is open read-only"));
+    }
+    let week = match week(query, query::get_week_start(conn)?) {
+        Ok(week) => week,
+        Err(response) => return Ok(response),
+    };
+    let Some(ingredient) = parameter(query, "ingredient").and_then(|i| i.parse().ok()) else {
+        return Ok(Response::error(400, "ingredient must be an ingredient id"));
+    };
+    let checked = parameter(query, "checked").is_none_or(|c| c != "false");
+    query::set_shopping_list_item_checked(conn, week, IngredientId::from_i32(ingredient), checked)?;
+    Ok(Response::json(json!({ "checked": checked })))
+}
+
+#[test]
+fn respond_test() {
+    use crate::database::fixture::Fixture;
+
+    let mut f = Fixture::open();
+    let mut request = |method: &str, target: &str| {
+        let response =
+            respond(&mut f.conn, method, target, false, "secret").unwrap_or_else(Response::from);
+        let body = match response.content_type {
+            "application/json" => serde_json::from_str(&response.body).unwrap(),
+            _ => serde_json::Value::Null,
+        };
+        (response.status, body)
+    };
+
+    let (status, body) = request("GET", "/api/recipes?q=carrot");
+    assert_eq!(status, 200);
+    assert_eq!(body[0]["name"], "Carrot Cake");
+    assert_eq!(body[1]["name"], "Carrot Soup");
+
+    let soup = f.soup.to_i32();
+    let (_, body) = request("GET", &format!("/api/recipes/{soup}"));
+    assert_eq!(body["name"], "Carrot Soup");
+    assert_eq!(body["category"], "Dinner");
+    assert_eq!(body["ingredients"].as_array().unwrap().len(), 3);
+    assert_eq!(body["calories"], 0.0);
+
+    let (_, body) = request("GET", "/api/menu?week=2024-01-03");
+    assert_eq!(body["week"], "2023-12-31");
+    assert_eq!(body["days"][1]["recipe"]["name"], "Carrot Soup");
+    assert_eq!(body["days"][2]["recipe"], serde_json::Value::Null);
+
+    let (_, body) = request("GET", "/api/today?date=2023-12-31");
+    assert_eq!(body["state"], "Nothing planned");
+    assert_eq!(body["today"]["recipe"], serde_json::Value::Null);
+    assert_eq!(body["tomorrow"]["date"], "2024-01-01");
+    assert_eq!(body["tomorrow"]["recipe"]["name"], "Carrot Soup");
+    assert_eq!(body["tomorrow"]["recipe"]["category"], "Dinner");
+    let (_, body) = request("GET", "/api/today?date=2024-01-01");
+    assert_eq!(body["state"], "Carrot Soup");
+    assert_eq!(body["today"]["day"], "Mon");
+    assert_eq!(request("GET", "/api/today?date=soon").0, 400);
+
+    let (_, body) = request("GET", "/api/shopping-list?week=2024-01-03");
+    let names: Vec<_> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| (i["name"].as_str().unwrap(), i["quantity"].as_f64().unwrap()))
+        .collect();
+    assert_eq!(names, [("carrot", 4.0), ("onion", 4.0), ("salt", 0.75)]);
+    assert_eq!(body[0]["checked"], false);
+
+    let carrot = f.carrot.to_i32();
+    let check = format!("/api/shopping-list/check?week=2024-01-01&ingredient={carrot}");
+    assert_eq!(request("POST", &check).0, 403);
+    assert_eq!(request("POST", &format!("{check}&token=guess")).0, 403);
+    let check = format!("{check}&token=secret");
+    assert_eq!(request("POST", &check).0, 200);
+    let (_, body) = request("GET", "/api/shopping-list?week=2024-01-03");
+    assert_eq!(body[0]["checked"], true);
+    assert_eq!(body[1]["checked"], false);
+    request("POST", &format!("{check}&checked=false"));
+    let (_, body) = request("GET", "/api/shopping-list?week=2024-01-03");
+    assert_eq!(body[0]["checked"], false);
+    assert_eq!(request("POST", "/api/menu").0, 405);
+
+    assert_eq!(request("GET", "/").0, 200);
+    assert_eq!(request("GET", "/api/recipes/9999").0, 404);
+    assert_eq!(request("GET", "/api/menu?week=tuesday").0, 400);
+    assert_eq!(request("GET", "/nothing").0, 404);
+
+    let response = respond(&mut f.conn, "POST", &check, true, "secret").unwrap();
+    assert_eq!(response.status, 403);
+
+    let page = respond(&mut f.conn, "GET", "/shopping-list", false, "secret").unwrap();
+    assert!(page.body.contains("const token = \"secret\";"));
+}
+
+#[test]
+fn week_start_test() {
+    use crate::database::fixture::Fixture;
+
+    let mut f = Fixture::open();
+    query::set_setting(&mut f.conn, query::WEEK_START_SETTING, Some("monday")).unwrap();
+    let response = respond(&mut f.conn, "GET", "/api/menu?week=2024-01-03", false, "").unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(body["week"], "2024-01-01");
+    assert_eq!(body["days"][0]["recipe"]["name"], "Carrot Soup");
+
+    let response = respond(&mut f.conn, "GET", "/api/today?date=2024-01-03", false, "").unwrap();
+    let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(body["today"]["recipe"]["name"], "Onion Tart");
+}
@@ -0,0 +1,96 @@
+//! Recipes written as Cooklang `.cook` files, one recipe to a file.
+
+use super::{ForeignIngredient, ForeignRecipe};
+use crate::cooklang::{parse_cooklang, CooklangRecipe};
+use crate::database::models::RecipeDuration;
+use crate::Result;
+use std::path::Path;
+
+/// A time like "45 minutes" or "1 hour", in minutes.
+fn time_minutes(time: &str) -> Option<u32> {
+    let (number, units) = time
+        .trim()
+        .split_once(' ')
+        .unwrap_or((time.trim(), "minutes"));
+    let number: u32 = number.parse().ok()?;
+    match units.trim().to_lowercase().as_str() {
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(number),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(number * 60),
+        _ => None,
+    }
+}
+
+fn decode_duration(recipe: &CooklangRecipe) -> Option<RecipeDuration> {
+    let duration = recipe.metadata("duration").or(recipe.metadata("time"))?;
+    // Our own exports use the names of the durations.
+    RecipeDuration::iter()
+        .find(|d| d.to_string().eq_ignore_ascii_case(duration))
+        .or_else(|| time_minutes(duration).map(RecipeDuration::from_minutes))
+}
+
+pub(super) fn decode_recipe(file_name: &str, text: &str) -> ForeignRecipe {
+    let recipe = parse_cooklang(text);
+    ForeignRecipe {
+        name: recipe.metadata("title").unwrap_or(file_name).into(),
+        category: recipe
+            .metadata("category")
+            .or(recipe.metadata("course"))
+            .map(|c| c.into()),
+        description: recipe.steps.join("\n\n"),
+        duration: decode_duration(&recipe),
+        ingredients: recipe
+            .ingredients
+            .iter()
+            .map(|i| ForeignIngredient::new(&i.quantity, &i.units, &i.name, &i.note))
+            .collect(),
+    }
+}
+
+pub fn decode_recipes_from_paths(paths: &[impl AsRef<Path>]) -> Result<Vec<ForeignRecipe>> {
+    let mut recipes = vec![];
+    for path in paths {
+        let path = path.as_ref();
+        let file_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_default();
+        recipes.push(decode_recipe(&file_name, &std::fs::read_to_string(path)?));
+    }
+    Ok(recipes)
+}
+
+#[test]
+fn decode_recipe_test() {
+    use crate::database::models::IngredientMeasurement::*;
+
+    let recipe = decode_recipe(
+        "Scrambled Eggs",
+        "\
+>> course: Breakfast
+>> time: 1 hour
+
+Whisk @eggs{3} with @milk{2%tbsp} and @chives{1%bunch}(chopped).
+
+Cook slowly.
+",
+    );
+    assert_eq!(recipe.name, "Scrambled Eggs");
+    assert_eq!(recipe.category.as_deref(), Some("Breakfast"));
+    assert_eq!(recipe.duration, Some(RecipeDuration::Medium));
+    assert_eq!(
+        recipe.description,
+        "Whisk eggs with milk and chives.\n\nCook slowly."
+    );
+    assert_eq!(recipe.ingredients[0].quantity, 3.0);
+    assert_eq!(recipe.ingredients[1].units, Some(Tablespoons));
+    assert_eq!(recipe.ingredients[2].note, "bunch; chopped");
+
+    let recipe = decode_recipe("untitled", ">> title: Toast\n>> duration: really long\n");
+    assert_eq!(recipe.name, "Toast");
+    assert_eq!(recipe.duration, Some(RecipeDuration::ReallyLong));
+
+    assert_eq!(time_minutes("45 minutes"), Some(45));
+    assert_eq!(time_minutes("2 hours"), Some(120));
+    assert_eq!(time_minutes("20"), Some(20));
+    assert_eq!(time_minutes("a while"), None);
+}
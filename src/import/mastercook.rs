@@ -0,0 +1,335 @@
+//! Recipes exported from MasterCook, either as XML (`.mx2`) or as its plain text format (`.mxp`).
+
+use super::{ForeignIngredient, ForeignRecipe};
+use crate::database::models::RecipeDuration;
+use crate::Result;
+use std::path::Path;
+
+/// MasterCook writes files in the Windows code page rather than UTF-8, which for the characters
+/// found in recipes is mostly the same as Latin-1.
+fn decode_text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| e.into_bytes().iter().map(|&b| b as char).collect())
+}
+
+pub fn decode_recipes_from_path(path: impl AsRef<Path>) -> Result<Vec<ForeignRecipe>> {
+    let path = path.as_ref();
+    let text = decode_text(std::fs::read(path)?);
+    if path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("mx2"))
+    {
+        decode_mx2(&text)
+    } else {
+        Ok(decode_mxp(&text))
+    }
+}
+
+/// A time like "1:30", in minutes.
+fn elapsed_minutes(s: &str) -> Option<u32> {
+    let (hours, minutes) = s.trim().split_once(':')?;
+    Some(hours.trim().parse::<u32>().ok()? * 60 + minutes.trim().parse::<u32>().ok()?)
+}
+
+fn all_text(node: roxmltree::Node<'_, '_>) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<String>()
+        .trim()
+        .into()
+}
+
+fn decode_mx2(text: &str) -> Result<Vec<ForeignRecipe>> {
+    // MasterCook puts the encoding after `standalone` in the declaration, which isn't valid XML,
+    // and the text is already decoded anyway.
+    let text = match text.trim_start().strip_prefix("<?xml") {
+        Some(rest) => rest.split_once("?>").map_or(rest, |(_, rest)| rest),
+        None => text,
+    };
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = roxmltree::Document::parse_with_options(text, options)?;
+
+    let mut recipes = vec![];
+    for r in document.descendants().filter(|n| n.has_tag_name("RcpE")) {
+        let child = |name: &str| r.children().find(|n| n.has_tag_name(name));
+        let duration = child("TTim")
+            .or_else(|| child("PrpT"))
+            .and_then(|n| n.attribute("elapsed"))
+            .and_then(elapsed_minutes)
+            .map(RecipeDuration::from_minutes);
+        let category = r
+            .descendants()
+            .find(|n| n.has_tag_name("CatT"))
+            .map(all_text)
+            .filter(|c| !c.is_empty());
+
+        let mut ingredients = vec![];
+        for i in r.children().filter(|n| n.has_tag_name("IngR")) {
+            // Subtitles and text mixed in with the ingredients.
+            if matches!(i.attribute("code"), Some("S" | "T")) {
+                continue;
+            }
+            let name = i.attribute("name").unwrap_or_default();
+            if name.trim().is_empty() {
+                continue;
+            }
+            let preparation = i
+                .children()
+                .find(|n| n.has_tag_name("IPrp"))
+                .map(all_text)
+                .unwrap_or_default();
+            ingredients.push(ForeignIngredient::new(
+                i.attribute("qty").unwrap_or_default(),
+                i.attribute("unit").unwrap_or_default(),
+                name,
+                &preparation,
+            ));
+        }
+
+        let mut description: Vec<_> = r
+            .descendants()
+            .filter(|n| n.has_tag_name("DirT"))
+            .map(all_text)
+            .filter(|d| !d.is_empty())
+            .collect();
+        description.extend(child("Natn").map(all_text).filter(|n| !n.is_empty()));
+
+        recipes.push(ForeignRecipe {
+            name: r.attribute("name").unwrap_or_default().trim().into(),
+            category,
+            description: description.join("\n\n"),
+            duration,
+            ingredients,
+        });
+    }
+    Ok(recipes)
+}
+
+#[test]
+fn decode_mx2_test() {
+    use crate::database::models::IngredientMeasurement::*;
+
+    let text = r#"<?xml version="1.0" standalone="yes" encoding="ISO-8859-1"?>
+<!DOCTYPE mx2 SYSTEM "mx2.dtd">
+<mx2 source="MasterCook 5.0" date="May 15, 2002">
+  <Summ><Nam>Chicken Soup</Nam></Summ>
+  <RcpE name="Chicken Soup" author="">
+    <Serv qty="4"/>
+    <PrpT elapsed="1:30"/>
+    <CatS><CatT>Soups</CatT><CatT>Main Dish</CatT></CatS>
+    <IngR name="For the broth" code="S"></IngR>
+    <IngR name="chicken broth" unit="cups" qty="4"><IPrp>low sodium</IPrp></IngR>
+    <IngR name="carrots" qty="2"><IPrp><![CDATA[diced]]></IPrp></IngR>
+    <DirS>
+      <DirT>Bring to a boil.</DirT>
+      <DirT>Simmer &amp; serve.</DirT>
+    </DirS>
+    <Natn>Freezes well.</Natn>
+  </RcpE>
+</mx2>"#;
+    let recipes = decode_mx2(text).unwrap();
+    assert_eq!(recipes.len(), 1);
+    let recipe = &recipes[0];
+    assert_eq!(recipe.name, "Chicken Soup");
+    assert_eq!(recipe.category.as_deref(), Some("Soups"));
+    assert_eq!(recipe.duration, Some(RecipeDuration::Long));
+    assert_eq!(elapsed_minutes("1:30"), Some(90));
+    assert_eq!(
+        recipe.description,
+        "Bring to a boil.\n\nSimmer & serve.\n\nFreezes well."
+    );
+    assert_eq!(
+        recipe.ingredients,
+        vec![
+            ForeignIngredient::new("4", "cups", "chicken broth", "low sodium"),
+            ForeignIngredient::new("2", "", "carrots", "diced"),
+        ]
+    );
+    assert_eq!(recipe.ingredients[0].units, Some(Cups));
+}
+
+/// The columns of the ingredient table, from the line of dashes under its heading.
+fn columns(dashes: &str) -> Vec<usize> {
+    let mut starts = vec![];
+    let mut previous = ' ';
+    for (i, c) in dashes.chars().enumerate() {
+        if c == '-' && previous != '-' {
+            starts.push(i);
+        }
+        previous = c;
+    }
+    starts
+}
+
+/// The text of a line from one column to the next.
+fn column(line: &[char], start: usize, end: Option<usize>) -> String {
+    let end = end.unwrap_or(line.len()).min(line.len());
+    line.get(start..end)
+        .map(|c| c.iter().collect::<String>().trim().into())
+        .unwrap_or_default()
+}
+
+fn decode_mxp_recipe(lines: &[&str]) -> Option<ForeignRecipe> {
+    let mut lines = lines.iter().map(|l| l.trim_end()).peekable();
+    let name = lines.by_ref().find(|l| !l.trim().is_empty())?.trim();
+    let mut recipe = ForeignRecipe {
+        name: name.into(),
+        ..Default::default()
+    };
+
+    // The fields at the top, up to the ingredient table.
+    let mut dashes = None;
+    for line in lines.by_ref() {
+        if line.trim_start().starts_with("--------") {
+            dashes = Some(line);
+            break;
+        }
+        if let Some(categories) = line.strip_prefix("Categories") {
+            let categories = categories.trim_start().trim_start_matches(':');
+            recipe.category = categories
+                .split("  ")
+                .map(|c| c.trim())
+                .find(|c| !c.is_empty())
+                .map(|c| c.into());
+        }
+        if let Some((_, time)) = line.split_once("Preparation Time :") {
+            recipe.duration = elapsed_minutes(time).map(RecipeDuration::from_minutes);
+        }
+    }
+    let columns = columns(dashes?);
+    let [amount, measure, ingredient] = columns[..] else {
+        return Some(recipe);
+    };
+
+    while let Some(line) = lines.next_if(|l| !l.trim().is_empty()) {
+        let line: Vec<char> = line.chars().collect();
+        let text = column(&line, ingredient, None);
+        let (name, preparation) = text.split_once("--").unwrap_or((&text, ""));
+        // Long preparations carry on in the next line.
+        if name.trim().is_empty() {
+            if let Some(previous) = recipe.ingredients.last_mut() {
+                previous.note = format!("{} {}", previous.note, preparation.trim());
+            }
+            continue;
+        }
+        recipe.ingredients.push(ForeignIngredient::new(
+            &column(&line, amount, Some(measure)),
+            &column(&line, measure, Some(ingredient)),
+            name,
+            preparation,
+        ));
+    }
+
+    // Then the directions, and after a dashed line, the notes.
+    let mut directions = vec![];
+    let mut paragraph = vec![];
+    let mut in_notes = false;
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with("- - - -") {
+            in_notes = true;
+        } else if trimmed.is_empty() {
+            if !paragraph.is_empty() {
+                directions.push(paragraph.join(" "));
+                paragraph.clear();
+            }
+        } else if !in_notes {
+            paragraph.push(trimmed);
+        } else if let Some(notes) = trimmed.strip_prefix("NOTES :") {
+            paragraph.push(notes.trim());
+        } else if !paragraph.is_empty() {
+            paragraph.push(trimmed);
+        }
+    }
+    if !paragraph.is_empty() {
+        directions.push(paragraph.join(" "));
+    }
+    recipe.description = directions.join("\n\n");
+    Some(recipe)
+}
+
+fn decode_mxp(text: &str) -> Vec<ForeignRecipe> {
+    let lines: Vec<_> = text.lines().collect();
+    lines
+        .split(|l| l.contains("Exported from") && l.contains("MasterCook"))
+        .skip(1)
+        .filter_map(decode_mxp_recipe)
+        .collect()
+}
+
+#[test]
+fn decode_mxp_test() {
+    use crate::database::models::IngredientMeasurement::*;
+
+    let text = "
+                     *  Exported from  MasterCook  *
+
+                              Chicken Soup
+
+Recipe By     : Grandma
+Serving Size  : 4     Preparation Time :0:45
+Categories    : Soups                            Main Dish
+
+  Amount  Measure       Ingredient -- Preparation Method
+--------  ------------  --------------------------------
+   4      cups          chicken broth -- low sodium
+   1      pound         chicken
+   2      cloves        garlic -- crushed and
+                        -- finely chopped
+   2                    carrots
+
+Bring the broth to a boil
+with everything in it.
+
+Simmer for half an hour.
+
+                   - - - - - - - - - - - - - - - - - - -
+
+NOTES : Freezes well.
+
+                     *  Exported from  MasterCook  *
+
+                                 Toast
+
+Recipe By     :
+Serving Size  : 1     Preparation Time :0:05
+Categories    :
+
+  Amount  Measure       Ingredient -- Preparation Method
+--------  ------------  --------------------------------
+   1      slice         bread
+
+Toast it.
+";
+    let recipes = decode_mxp(text);
+    assert_eq!(recipes.len(), 2);
+    let soup = &recipes[0];
+    assert_eq!(soup.name, "Chicken Soup");
+    assert_eq!(soup.category.as_deref(), Some("Soups"));
+    assert_eq!(soup.duration, Some(RecipeDuration::Medium));
+    assert_eq!(
+        soup.description,
+        "Bring the broth to a boil with everything in it.\n\nSimmer for half an hour.\n\n\
+        Freezes well."
+    );
+    assert_eq!(soup.ingredients.len(), 4);
+    assert_eq!(
+        soup.ingredients[0],
+        ForeignIngredient::new("4", "cups", "chicken broth", "low sodium")
+    );
+    assert_eq!(soup.ingredients[1].units, Some(Pounds));
+    assert_eq!(
+        soup.ingredients[2].note,
+        "cloves; crushed and finely chopped"
+    );
+    assert_eq!(soup.ingredients[3].quantity, 2.0);
+
+    let toast = &recipes[1];
+    assert_eq!(toast.name, "Toast");
+    assert_eq!(toast.category, None);
+    assert_eq!(toast.description, "Toast it.");
+    assert_eq!(toast.ingredients[0].note, "slice");
+}
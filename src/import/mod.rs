@@ -1,25 +1,43 @@
 // Copyright 2023 Remi Bernotavicius
 
 use crate::database;
+use crate::query;
+use crate::unit_conversion::{
+    convert_quantity_to_system, measurement_parse, quantity_parse, quantity_with_unit_parse,
+    MeasurementSystem,
+};
 use crate::Result;
 use database::models::{
-    Ingredient, IngredientId, IngredientMeasurement, IngredientUsage, IngredientUsageId, Recipe,
-    RecipeCategory, RecipeCategoryId, RecipeDuration, RecipeHandle, RecipeId,
+    IngredientCategory, IngredientCategoryId, IngredientId, IngredientMeasurement, Recipe,
+    RecipeCategoryId, RecipeDuration, RecipeHandle, RecipeId,
 };
 use diesel::prelude::OptionalExtension as _;
+use diesel::Connection as _;
 use diesel::ExpressionMethods as _;
 use diesel::QueryDsl as _;
 use diesel::RunQueryDsl as _;
 use diesel::SelectableHelper as _;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write as _;
-use std::mem;
 use std::path::Path;
 
+mod cooklang;
+mod mastercook;
+#[cfg(feature = "ocr")]
+mod ocr;
+mod photos;
 mod plist;
+mod recipe_keeper;
+mod text;
+
+#[cfg(feature = "ocr")]
+pub use ocr::recognize_text;
+pub use photos::{match_photos, PhotoImporter, PhotoMatch};
+pub use text::{parse_recipe_text, RecipeText};
 
 impl IngredientMeasurement {
-    fn import(s: &str) -> Self {
-        match s {
+    fn import(s: &str) -> Option<Self> {
+        Some(match s {
             "c." => Self::Cups,
             "fl. oz." => Self::FluidOunces,
             "lb." => Self::Pounds,
@@ -27,8 +45,22 @@ impl IngredientMeasurement {
             "tbsp." => Self::Tablespoons,
             "tsp." => Self::Teaspoons,
             "qt." => Self::Quart,
-            _ => panic!("couldn't import measurement {s:?}"),
-        }
+            _ => return None,
+        })
+    }
+
+    /// The inverse of `import`. The old app only knows these US units.
+    fn export(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Cups => "c.",
+            Self::FluidOunces => "fl. oz.",
+            Self::Pounds => "lb.",
+            Self::Ounces => "oz.",
+            Self::Tablespoons => "tbsp.",
+            Self::Teaspoons => "tsp.",
+            Self::Quart => "qt.",
+            _ => return None,
+        })
     }
 }
 
@@ -42,199 +74,775 @@ impl RecipeDuration {
             v => panic!("unexpected value {v:?} for time"),
         }
     }
+
+    fn export(&self) -> &'static str {
+        match self {
+            Self::Long => "Long",
+            Self::Medium => "Medium",
+            Self::ReallyLong => "Really Long",
+            Self::Short => "Short",
+        }
+    }
+
+    fn from_minutes(minutes: u32) -> Self {
+        match minutes {
+            0..=30 => Self::Short,
+            31..=60 => Self::Medium,
+            61..=180 => Self::Long,
+            _ => Self::ReallyLong,
+        }
+    }
+}
+
+/// Units as other apps write them. Abbreviations are case-sensitive there, with "T" being
+/// tablespoons and "t" teaspoons.
+fn foreign_units_parse(s: &str) -> Option<IngredientMeasurement> {
+    match s.trim() {
+        "T" | "Tb" | "TB" | "Tbs" | "Tbsp" => Some(IngredientMeasurement::Tablespoons),
+        "t" | "ts" => Some(IngredientMeasurement::Teaspoons),
+        s => measurement_parse(s),
+    }
 }
 
-impl Recipe {
-    fn import(
-        recipe_id: RecipeId,
-        recipe_category_id: RecipeCategoryId,
-        recipe: plist::Recipe,
-    ) -> Self {
+/// An ingredient of a recipe exported from another app.
+#[derive(Debug, PartialEq)]
+struct ForeignIngredient {
+    name: String,
+    quantity: f32,
+    units: Option<IngredientMeasurement>,
+    note: String,
+}
+
+impl ForeignIngredient {
+    /// From an ingredient whose quantity and units are already apart from the name. Units which
+    /// aren't understood, like "clove", are kept in the note.
+    fn new(quantity: &str, units: &str, name: &str, note: &str) -> Self {
+        let parsed_units = foreign_units_parse(units);
+        let mut notes = vec![];
+        if parsed_units.is_none() && !units.trim().is_empty() {
+            notes.push(units.trim());
+        }
+        if !note.trim().is_empty() {
+            notes.push(note.trim());
+        }
+        Self {
+            name: name.trim().into(),
+            quantity: quantity_parse(quantity).unwrap_or(1.0),
+            units: parsed_units,
+            note: notes.join("; "),
+        }
+    }
+
+    /// From a line like "1 1/2 cups flour, sifted". Anything after a comma is the note.
+    fn parse(line: &str) -> Self {
+        let words: Vec<_> = line.split_whitespace().collect();
+        let (mut quantity, mut units, mut rest) = (None, None, 0);
+        for n in (1..=words.len().min(4)).rev() {
+            if let Some((q, u)) = quantity_with_unit_parse(&words[..n].join(" ")) {
+                (quantity, units, rest) = (Some(q), u, n);
+                break;
+            }
+        }
+        if quantity.is_some() && units.is_none() {
+            if let Some(u) = words.get(rest).and_then(|w| foreign_units_parse(w)) {
+                (units, rest) = (Some(u), rest + 1);
+            }
+        }
+        let rest = words[rest..].join(" ");
+        let (name, note) = rest.split_once(',').unwrap_or((&rest, ""));
         Self {
-            id: recipe_id,
-            name: recipe.name,
-            description: recipe.other,
-            duration: RecipeDuration::import(&recipe.time[..]),
-            category: recipe_category_id,
+            name: name.trim().into(),
+            quantity: quantity.unwrap_or(1.0),
+            units,
+            note: note.trim().into(),
+        }
+    }
+}
+
+#[test]
+fn foreign_ingredient_test() {
+    use IngredientMeasurement::*;
+
+    let ingredient = |name: &str, quantity, units, note: &str| ForeignIngredient {
+        name: name.into(),
+        quantity,
+        units,
+        note: note.into(),
+    };
+    assert_eq!(
+        ForeignIngredient::parse("1 1/2 cups flour, sifted"),
+        ingredient("flour", 1.5, Some(Cups), "sifted")
+    );
+    assert_eq!(
+        ForeignIngredient::parse("2 eggs"),
+        ingredient("eggs", 2.0, None, "")
+    );
+    assert_eq!(
+        ForeignIngredient::parse("500g minced beef"),
+        ingredient("minced beef", 500.0, Some(Grams), "")
+    );
+    assert_eq!(
+        ForeignIngredient::parse("1 T olive oil"),
+        ingredient("olive oil", 1.0, Some(Tablespoons), "")
+    );
+    assert_eq!(
+        ForeignIngredient::parse("Salt and pepper, to taste"),
+        ingredient("Salt and pepper", 1.0, None, "to taste")
+    );
+    assert_eq!(
+        ForeignIngredient::new("2", "cloves", "garlic", "minced"),
+        ingredient("garlic", 2.0, None, "cloves; minced")
+    );
+    assert_eq!(
+        ForeignIngredient::new("1/2", "t", "salt", ""),
+        ingredient("salt", 0.5, Some(Teaspoons), "")
+    );
+}
+
+/// A recipe exported from another app.
+#[derive(Debug, Default, PartialEq)]
+struct ForeignRecipe {
+    name: String,
+    category: Option<String>,
+    /// The directions and any notes.
+    description: String,
+    duration: Option<RecipeDuration>,
+    ingredients: Vec<ForeignIngredient>,
+}
+
+/// A recipe about to be imported, which can be renamed, put in another category or left out
+/// before the import starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreviewRecipe {
+    pub name: String,
+    pub category: String,
+    pub num_ingredients: usize,
+    pub include: bool,
+}
+
+/// Importers which can show what they're going to import before they start.
+pub trait Preview {
+    /// The recipes to be imported, all included.
+    fn preview(&self) -> Vec<PreviewRecipe>;
+
+    /// Imports only the included recipes, named and categorized as they are in the preview. The
+    /// preview has to have come from [`Preview::preview`].
+    fn apply_preview(&mut self, preview: &[PreviewRecipe]);
+}
+
+/// Imports recipes exported from other recipe apps. Their categories are matched up with the
+/// existing ones by name, and any without a category go in one named after the app.
+pub struct ForeignRecipeImporter {
+    /// Each with the key it's recorded under once it's imported.
+    recipes: Vec<(String, ForeignRecipe)>,
+    /// The file the recipes came from, if there is one to resume importing from later.
+    source: Option<String>,
+    default_category: String,
+    categories: HashMap<String, RecipeCategoryId>,
+
+    num_imported: usize,
+    total_num_recipes: usize,
+}
+
+impl ForeignRecipeImporter {
+    fn new(recipes: Vec<ForeignRecipe>, app: &str) -> Result<Self> {
+        if recipes.is_empty() {
+            return Err(format!("no {app} recipes found").into());
+        }
+        Ok(Self {
+            total_num_recipes: recipes.len(),
+            recipes: recipes
+                .into_iter()
+                .enumerate()
+                .map(|(i, r)| (format!("{i}/{}", r.name), r))
+                .collect(),
+            source: None,
+            default_category: format!("From {app}"),
+            categories: HashMap::new(),
+            num_imported: 0,
+        })
+    }
+
+    fn with_source(mut self, paths: &[impl AsRef<Path>]) -> Self {
+        let paths: Vec<_> = paths
+            .iter()
+            .map(|p| p.as_ref().display().to_string())
+            .collect();
+        self.source = Some(paths.join("\n"));
+        self
+    }
+
+    /// From a MasterCook `.mx2` or `.mxp` export.
+    pub fn new_mastercook(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(
+            Self::new(mastercook::decode_recipes_from_path(&path)?, "MasterCook")?
+                .with_source(&[path]),
+        )
+    }
+
+    /// From Cooklang `.cook` files, one recipe each.
+    pub fn new_cooklang(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        Ok(Self::new(cooklang::decode_recipes_from_paths(paths)?, "Cooklang")?.with_source(paths))
+    }
+
+    /// From a share code someone sent, as made by [`crate::share::encode_share_code`].
+    #[cfg(feature = "share")]
+    pub fn new_from_share_code(code: &str) -> Result<Self> {
+        let text = crate::share::decode_share_code(code)?;
+        Self::new(
+            vec![cooklang::decode_recipe("Shared Recipe", &text)],
+            "Share Codes",
+        )
+    }
+
+    /// From a recipe the user has checked over after it was picked out of some text. It goes in
+    /// the given category, or a new one if that's empty.
+    pub fn new_from_text(recipe: &RecipeText, category: &str) -> Result<Self> {
+        if recipe.name.trim().is_empty() {
+            return Err("the recipe needs a name".into());
+        }
+        let recipe = ForeignRecipe {
+            name: recipe.name.trim().into(),
+            category: (!category.trim().is_empty()).then(|| category.trim().into()),
+            description: recipe.directions.trim().into(),
+            duration: None,
+            ingredients: recipe.foreign_ingredients(),
+        };
+        Self::new(vec![recipe], "Recipe Cards")
+    }
+
+    /// From a Recipe Keeper export, either the zip file or the `recipes.html` inside it.
+    pub fn new_recipe_keeper(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(
+            recipe_keeper::decode_recipes_from_path(&path)?,
+            "Recipe Keeper",
+        )?
+        .with_source(&[path]))
+    }
+
+    fn category(
+        &mut self,
+        conn: &mut database::Connection,
+        name: Option<&str>,
+    ) -> Result<RecipeCategoryId> {
+        let name = name.unwrap_or(&self.default_category);
+        if self.categories.is_empty() {
+            for (c, _) in query::get_recipe_categories(conn)? {
+                self.categories.insert(c.name.to_lowercase(), c.id);
+            }
+        }
+        if let Some(id) = self.categories.get(&name.to_lowercase()) {
+            return Ok(*id);
+        }
+        let id = query::add_category(conn, name)?;
+        self.categories.insert(name.to_lowercase(), id);
+        Ok(id)
+    }
+
+    fn import_recipe(
+        &mut self,
+        conn: &mut database::Connection,
+        recipe: ForeignRecipe,
+    ) -> Result<()> {
+        let category_id = self.category(conn, recipe.category.as_deref())?;
+        let recipe_id = query::add_recipe(conn, &recipe.name, category_id)?;
+        if let Some(duration) = recipe.duration {
+            query::edit_recipe_duration(conn, recipe_id, duration)?;
+        }
+        query::edit_recipe_description(conn, recipe_id, &recipe.description)?;
+        for ingredient in recipe.ingredients {
+            let name = ingredient.name.to_lowercase();
+            let ingredient_id = match query::get_ingredient_by_name(conn, &name)? {
+                Some(existing) => existing.id,
+                None => query::add_ingredient(conn, &name)?.id,
+            };
+            query::add_recipe_ingredient_with_units(
+                conn,
+                recipe_id,
+                ingredient_id,
+                ingredient.quantity,
+                ingredient.units,
+                &ingredient.note,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Preview for ForeignRecipeImporter {
+    fn preview(&self) -> Vec<PreviewRecipe> {
+        self.recipes
+            .iter()
+            .map(|(_, r)| PreviewRecipe {
+                name: r.name.clone(),
+                category: r.category.clone().unwrap_or(self.default_category.clone()),
+                num_ingredients: r.ingredients.len(),
+                include: true,
+            })
+            .collect()
+    }
+
+    fn apply_preview(&mut self, preview: &[PreviewRecipe]) {
+        assert_eq!(preview.len(), self.recipes.len());
+        self.recipes = std::mem::take(&mut self.recipes)
+            .into_iter()
+            .zip(preview)
+            .filter(|(_, p)| p.include)
+            .map(|((key, recipe), p)| {
+                let recipe = ForeignRecipe {
+                    name: p.name.trim().into(),
+                    category: Some(p.category.trim())
+                        .filter(|c| !c.is_empty())
+                        .map(Into::into),
+                    ..recipe
+                };
+                (key, recipe)
+            })
+            .collect();
+        self.total_num_recipes = self.recipes.len().max(1);
+    }
+}
+
+impl Importer for ForeignRecipeImporter {
+    fn done(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    fn num_imported(&self) -> usize {
+        self.num_imported
+    }
+
+    fn percent_done(&self) -> f32 {
+        self.num_imported as f32 / self.total_num_recipes as f32
+    }
+
+    fn import_one(&mut self, conn: &mut database::Connection, log: &mut String) -> Result<()> {
+        assert!(!self.done());
+
+        const BATCH_SIZE: usize = 20;
+        let batch: Vec<_> = self
+            .recipes
+            .drain(..BATCH_SIZE.min(self.recipes.len()))
+            .collect();
+        let num_recipes = batch.len();
+        let result = conn.transaction(|conn| {
+            for (key, recipe) in batch {
+                if recipe.ingredients.is_empty() {
+                    writeln!(log, "warning: recipe {:?} has no ingredients", recipe.name)?;
+                }
+                self.import_recipe(conn, recipe)?;
+                if let Some(source) = &self.source {
+                    mark_imported(conn, source, &key)?;
+                }
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => self.num_imported += num_recipes,
+            // Categories added in the batch were rolled back with it.
+            Err(_) => self.categories.clear(),
         }
+        result
+    }
+
+    fn skip_imported(&mut self, conn: &mut database::Connection) -> Result<usize> {
+        let Some(source) = &self.source else {
+            return Ok(0);
+        };
+        let imported = already_imported(conn, source)?;
+        let before = self.recipes.len();
+        self.recipes.retain(|(key, _)| !imported.contains(key));
+        self.total_num_recipes = self.recipes.len().max(1);
+        Ok(before - self.recipes.len())
     }
 }
 
+/// The keys of the records from the source which were imported before.
+fn already_imported(conn: &mut database::Connection, from: &str) -> Result<HashSet<String>> {
+    use database::schema::imported_records::dsl::*;
+
+    Ok(imported_records
+        .select(record_key)
+        .filter(source.eq(from))
+        .load::<String>(conn)?
+        .into_iter()
+        .collect())
+}
+
+/// Records that the record from the source was imported, so it's skipped if the source is
+/// imported again.
+fn mark_imported(conn: &mut database::Connection, to: &str, key: &str) -> Result<()> {
+    use database::schema::imported_records::dsl::*;
+
+    diesel::insert_into(imported_records)
+        .values((source.eq(to), record_key.eq(key)))
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+    Ok(())
+}
+
+#[test]
+fn foreign_recipe_importer_test() {
+    let mut conn = crate::database::fixture::test_connection();
+    let existing = query::add_category(&mut conn, "Soups").unwrap();
+    let recipes = vec![
+        ForeignRecipe {
+            name: "Carrot Soup".into(),
+            category: Some("soups".into()),
+            description: "Simmer.".into(),
+            duration: Some(RecipeDuration::Medium),
+            ingredients: vec![ForeignIngredient::parse("2 cups Carrots")],
+        },
+        ForeignRecipe {
+            name: "Toast".into(),
+            ..Default::default()
+        },
+    ];
+    let mut importer = ForeignRecipeImporter::new(recipes, "Elsewhere").unwrap();
+    let mut log = String::new();
+    while !importer.done() {
+        importer.import_one(&mut conn, &mut log).unwrap();
+    }
+    assert_eq!(importer.num_imported(), 2);
+    assert!(log.contains("\"Toast\" has no ingredients"));
+
+    let soup = query::search_recipes(&mut conn, &mut None, "Carrot Soup", false).unwrap()[0].0;
+    let (recipe, _, ingredients) = query::get_recipe(&mut conn, soup).unwrap();
+    assert_eq!(recipe.category, existing);
+    assert_eq!(recipe.duration, RecipeDuration::Medium);
+    assert_eq!(recipe.description, "Simmer.");
+    assert_eq!(ingredients[0].ingredient.name, "carrots");
+    assert_eq!(
+        ingredients[0].quantity_units,
+        Some(IngredientMeasurement::Cups)
+    );
+
+    let categories = query::get_recipe_categories(&mut conn).unwrap();
+    assert!(categories.iter().any(|(c, _)| c.name == "From Elsewhere"));
+
+    assert!(ForeignRecipeImporter::new(vec![], "Elsewhere").is_err());
+}
+
+#[test]
+fn foreign_recipe_importer_resume_test() {
+    let mut conn = crate::database::fixture::test_connection();
+    let recipes = || {
+        (0..30)
+            .map(|i| ForeignRecipe {
+                name: format!("Recipe {i}"),
+                ..Default::default()
+            })
+            .collect()
+    };
+    let new_importer = || {
+        ForeignRecipeImporter::new(recipes(), "Elsewhere")
+            .unwrap()
+            .with_source(&["recipes.mx2"])
+    };
+
+    // Cancelled after the first batch.
+    let mut importer = new_importer();
+    let mut log = String::new();
+    importer.import_one(&mut conn, &mut log).unwrap();
+    assert_eq!(importer.num_imported(), 20);
+
+    let mut importer = new_importer();
+    assert_eq!(importer.skip_imported(&mut conn).unwrap(), 20);
+    while !importer.done() {
+        importer.import_one(&mut conn, &mut log).unwrap();
+    }
+    assert_eq!(importer.num_imported(), 10);
+    assert_eq!(
+        query::search_recipes(&mut conn, &mut None, "Recipe", false)
+            .unwrap()
+            .len(),
+        30
+    );
+}
+
+#[test]
+fn foreign_recipe_importer_preview_test() {
+    let mut conn = crate::database::fixture::test_connection();
+    let recipe = |name: &str| ForeignRecipe {
+        name: name.into(),
+        ..Default::default()
+    };
+    let mut importer =
+        ForeignRecipeImporter::new(vec![recipe("Toast"), recipe("Jam")], "Elsewhere").unwrap();
+    let mut preview = importer.preview();
+    assert_eq!(preview[0].category, "From Elsewhere");
+
+    preview[0].category = "Breakfast".into();
+    preview[1].include = false;
+    importer.apply_preview(&preview);
+    let mut log = String::new();
+    while !importer.done() {
+        importer.import_one(&mut conn, &mut log).unwrap();
+    }
+    assert_eq!(importer.num_imported(), 1);
+    let categories = query::get_recipe_categories(&mut conn).unwrap();
+    let names: Vec<_> = categories.iter().map(|(c, _)| c.name.as_str()).collect();
+    assert_eq!(names, ["Breakfast"]);
+}
+
+fn import_ingredient_category(
+    conn: &mut database::Connection,
+    category: &str,
+) -> Result<Option<IngredientCategoryId>> {
+    use database::schema::ingredient_categories::dsl::*;
+
+    let category = category.trim();
+    if category.is_empty() {
+        return Ok(None);
+    }
+
+    let existing = ingredient_categories
+        .select(IngredientCategory::as_select())
+        .load(conn)?
+        .into_iter()
+        .find(|c| c.name.to_lowercase() == category.to_lowercase());
+    if let Some(existing) = existing {
+        return Ok(Some(existing.id));
+    }
+
+    diesel::insert_into(ingredient_categories)
+        .values(name.eq(category))
+        .execute(conn)?;
+    Ok(Some(
+        ingredient_categories
+            .select(id)
+            .filter(name.eq(category))
+            .get_result(conn)?,
+    ))
+}
+
 fn import_ingredient(
     conn: &mut database::Connection,
-    plist_ingredient: plist::Ingredient,
-    recipe_id: RecipeId,
-    ingredient_usage_id: &mut IngredientUsageId,
-    ingredient_id: &mut IngredientId,
-) -> Result<()> {
+    plist_ingredient: &plist::Ingredient,
+) -> Result<IngredientId> {
     use database::schema::ingredients::dsl::*;
 
     let new_ingredient_name = plist_ingredient.name.to_lowercase();
     let existing_ingredient = ingredients
-        .select(Ingredient::as_select())
+        .select(id)
         .filter(name.eq(&new_ingredient_name))
         .get_result(conn)
-        .optional()
-        .unwrap();
-    let ingredient_id = if let Some(existing) = existing_ingredient {
-        existing.id
-    } else {
-        let new_id = *ingredient_id;
-        let new_ingredient = Ingredient {
-            id: new_id,
-            name: new_ingredient_name,
-            category: (!plist_ingredient.category.is_empty()).then_some(plist_ingredient.category),
-        };
-        diesel::insert_into(ingredients)
-            .values(new_ingredient)
-            .execute(conn)
-            .unwrap();
-
-        *ingredient_id = ingredient_id.next();
-        new_id
-    };
-
-    let new_usage = IngredientUsage {
-        id: *ingredient_usage_id,
-        recipe_id,
-        ingredient_id,
-        quantity: plist_ingredient.quantity as f32,
-        quantity_units: (!plist_ingredient.measurement.trim().is_empty())
-            .then(|| IngredientMeasurement::import(&plist_ingredient.measurement)),
-    };
-
-    diesel::insert_into(database::schema::ingredient_usages::dsl::ingredient_usages)
-        .values(new_usage)
-        .execute(conn)
-        .unwrap();
-    *ingredient_usage_id = ingredient_usage_id.next();
+        .optional()?;
+    if let Some(existing) = existing_ingredient {
+        return Ok(existing);
+    }
 
-    Ok(())
+    let new_category_id = import_ingredient_category(conn, &plist_ingredient.category)?;
+    diesel::insert_into(ingredients)
+        .values((
+            name.eq(&new_ingredient_name),
+            category_id.eq(new_category_id),
+        ))
+        .execute(conn)?;
+    Ok(ingredients
+        .select(id)
+        .filter(name.eq(&new_ingredient_name))
+        .get_result(conn)?)
 }
 
 fn import_recipes_from_box(
     conn: &mut database::Connection,
+    log: &mut String,
     num_imported: &mut usize,
     recipes: Vec<plist::Recipe>,
     recipe_category_id: RecipeCategoryId,
-    recipe_id: &mut RecipeId,
-    ingredient_usage_id: &mut IngredientUsageId,
-    ingredient_id: &mut IngredientId,
 ) -> Result<()> {
-    for mut plist_recipe in recipes {
-        let id = *recipe_id;
-        let plist_ingredients = mem::take(&mut plist_recipe.ingredients);
-        let new_recipe = Recipe::import(id, recipe_category_id, plist_recipe);
-        diesel::insert_into(database::schema::recipes::dsl::recipes)
-            .values(new_recipe)
-            .execute(conn)
-            .unwrap();
-        *recipe_id = recipe_id.next();
-
-        for plist_ingredient in plist_ingredients {
-            import_ingredient(
-                conn,
-                plist_ingredient,
-                id,
-                ingredient_usage_id,
-                ingredient_id,
+    use database::schema::{ingredient_usages, recipes};
+
+    if recipes.is_empty() {
+        return Ok(());
+    }
+
+    let new_recipes: Vec<_> = recipes
+        .iter()
+        .map(|r| {
+            (
+                recipes::name.eq(&r.name),
+                recipes::description.eq(&r.other),
+                recipes::duration.eq(RecipeDuration::import(&r.time[..])),
+                recipes::category.eq(recipe_category_id),
+            )
+        })
+        .collect();
+    diesel::insert_into(recipes::table)
+        .values(&new_recipes)
+        .execute(conn)?;
+
+    // This runs in a transaction, so the recipes just inserted are the ones with the highest ids.
+    let mut new_recipe_ids: Vec<RecipeId> = recipes::table
+        .select(recipes::id)
+        .order_by(recipes::id.desc())
+        .limit(recipes.len() as i64)
+        .load(conn)?;
+    new_recipe_ids.reverse();
+
+    let mut new_usages = vec![];
+    for (recipe_id, plist_recipe) in new_recipe_ids.into_iter().zip(&recipes) {
+        if plist_recipe.ingredients.is_empty() {
+            writeln!(
+                log,
+                "warning: recipe {:?} has no ingredients",
+                plist_recipe.name
             )?;
         }
+        for plist_ingredient in &plist_recipe.ingredients {
+            let ingredient_id = import_ingredient(conn, plist_ingredient)?;
+            let measurement = plist_ingredient.measurement.trim();
+            let units = IngredientMeasurement::import(measurement);
+            if units.is_none() && !measurement.is_empty() {
+                writeln!(
+                    log,
+                    "warning: unknown units {measurement:?} for {:?} in recipe {:?}, left out",
+                    plist_ingredient.name, plist_recipe.name
+                )?;
+            }
+            new_usages.push((
+                ingredient_usages::recipe_id.eq(recipe_id),
+                ingredient_usages::ingredient_id.eq(ingredient_id),
+                ingredient_usages::quantity.eq(plist_ingredient.quantity as f32),
+                ingredient_usages::quantity_units.eq(units),
+            ));
+        }
         *num_imported += 1;
     }
+    if !new_usages.is_empty() {
+        diesel::insert_into(ingredient_usages::table)
+            .values(&new_usages)
+            .execute(conn)?;
+    }
     Ok(())
 }
 
+/// The category with the name, added if there isn't one already.
 fn import_recipe_category(
     conn: &mut database::Connection,
-    name: String,
-    recipe_category_id: &mut RecipeCategoryId,
+    new_name: &str,
 ) -> Result<RecipeCategoryId> {
-    let id = *recipe_category_id;
-    let new_category = RecipeCategory { id, name };
-    diesel::insert_into(database::schema::recipe_categories::dsl::recipe_categories)
-        .values(new_category)
-        .execute(conn)
-        .unwrap();
-    *recipe_category_id = recipe_category_id.next();
-    Ok(id)
+    use database::schema::recipe_categories::dsl::*;
+
+    let existing = recipe_categories
+        .select(id)
+        .filter(name.eq(new_name))
+        .first(conn)
+        .optional()?;
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+    diesel::insert_into(recipe_categories)
+        .values(name.eq(new_name))
+        .execute(conn)?;
+    Ok(recipe_categories
+        .select(id)
+        .order_by(id.desc())
+        .first(conn)?)
 }
 
 pub trait Importer {
+    /// Imports the next batch. Each batch is a transaction, so stopping between batches leaves
+    /// nothing half imported.
     fn import_one(&mut self, conn: &mut database::Connection, log: &mut String) -> Result<()>;
     fn percent_done(&self) -> f32;
     fn done(&self) -> bool;
     fn num_imported(&self) -> usize;
+
+    /// Leaves out what was imported from the same file before, like by an import which was
+    /// cancelled part way through. Returns how many records were left out.
+    fn skip_imported(&mut self, _conn: &mut database::Connection) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+/// A recipe box from the old version, which is imported as one category. Each recipe has the key
+/// it's recorded under once it's imported.
+struct ImportBox {
+    name: String,
+    recipes: Vec<(String, plist::Recipe)>,
 }
 
 pub struct RecipeImporter {
-    recipe_boxes: Vec<plist::RecipeBox>,
-    working_recipe_box: Option<(RecipeCategoryId, plist::RecipeBox)>,
+    recipe_boxes: Vec<ImportBox>,
+    source: String,
 
     num_imported: usize,
     total_num_recipes: usize,
-
-    recipe_category_id_vendor: RecipeCategoryId,
-    recipe_id_vendor: RecipeId,
-    ingredient_usage_id_vendor: IngredientUsageId,
-    ingredient_id_vendor: IngredientId,
 }
 
 impl RecipeImporter {
-    pub fn new(conn: &mut database::Connection, path: impl AsRef<Path>) -> Result<Self> {
-        let recipe_boxes = plist::decode_recipes_from_path(path)?;
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let recipe_boxes: Vec<_> = plist::decode_recipes_from_path(&path)?
+            .into_iter()
+            .map(|b| ImportBox {
+                recipes: b
+                    .recipes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, r)| (format!("{}/{i}/{}", b.name, r.name), r))
+                    .collect(),
+                name: b.name,
+            })
+            .collect();
 
         let total_num_recipes = recipe_boxes.iter().map(|b| b.recipes.len()).sum();
 
-        use database::schema::{ingredient_usages, ingredients, recipe_categories, recipes};
-        use diesel::dsl::max;
-
-        let recipe_category_id_vendor = recipe_categories::table
-            .select(max(recipe_categories::id))
-            .first::<Option<RecipeCategoryId>>(conn)
-            .unwrap()
-            .map(|v| v.next())
-            .unwrap_or(RecipeCategoryId::INITIAL);
-
-        let recipe_id_vendor = recipes::table
-            .select(max(recipes::id))
-            .first::<Option<RecipeId>>(conn)
-            .unwrap()
-            .map(|v| v.next())
-            .unwrap_or(RecipeId::INITIAL);
-
-        let ingredient_usage_id_vendor = ingredient_usages::table
-            .select(max(ingredient_usages::id))
-            .first::<Option<IngredientUsageId>>(conn)
-            .unwrap()
-            .map(|v| v.next())
-            .unwrap_or(IngredientUsageId::INITIAL);
-
-        let ingredient_id_vendor = ingredients::table
-            .select(max(ingredients::id))
-            .first::<Option<IngredientId>>(conn)
-            .unwrap()
-            .map(|v| v.next())
-            .unwrap_or(IngredientId::INITIAL);
-
         Ok(Self {
             recipe_boxes,
-            working_recipe_box: None,
+            source: path.as_ref().display().to_string(),
 
             num_imported: 0,
             total_num_recipes,
-
-            recipe_category_id_vendor,
-            recipe_id_vendor,
-            ingredient_usage_id_vendor,
-            ingredient_id_vendor,
         })
     }
 }
 
+impl Preview for RecipeImporter {
+    fn preview(&self) -> Vec<PreviewRecipe> {
+        self.recipe_boxes
+            .iter()
+            .flat_map(|b| {
+                b.recipes.iter().map(|(_, r)| PreviewRecipe {
+                    name: r.name.clone(),
+                    category: b.name.clone(),
+                    num_ingredients: r.ingredients.len(),
+                    include: true,
+                })
+            })
+            .collect()
+    }
+
+    /// Recipes moved to another category are moved to that category's box, so each box is still
+    /// imported as one category.
+    fn apply_preview(&mut self, preview: &[PreviewRecipe]) {
+        let recipes = std::mem::take(&mut self.recipe_boxes)
+            .into_iter()
+            .flat_map(|b| {
+                let name = b.name;
+                b.recipes.into_iter().map(move |r| (name.clone(), r))
+            });
+        for ((box_name, (key, mut recipe)), p) in recipes.zip(preview).filter(|(_, p)| p.include) {
+            recipe.name = p.name.trim().into();
+            let category = Some(p.category.trim())
+                .filter(|c| !c.is_empty())
+                .unwrap_or(&box_name);
+            match self.recipe_boxes.iter_mut().find(|b| b.name == category) {
+                Some(b) => b.recipes.push((key, recipe)),
+                None => self.recipe_boxes.push(ImportBox {
+                    name: category.to_owned(),
+                    recipes: vec![(key, recipe)],
+                }),
+            }
+        }
+        self.total_num_recipes = preview.iter().filter(|p| p.include).count().max(1);
+    }
+}
+
 impl Importer for RecipeImporter {
     fn done(&self) -> bool {
-        self.recipe_boxes.is_empty() && self.working_recipe_box.is_none()
+        self.recipe_boxes.is_empty()
     }
 
     fn num_imported(&self) -> usize {
@@ -245,41 +853,166 @@ impl Importer for RecipeImporter {
         self.num_imported as f32 / self.total_num_recipes as f32
     }
 
-    fn import_one(&mut self, conn: &mut database::Connection, _: &mut String) -> Result<()> {
+    fn import_one(&mut self, conn: &mut database::Connection, log: &mut String) -> Result<()> {
         assert!(!self.done());
 
-        if self.working_recipe_box.is_none() {
-            let plist_recipe_box = self.recipe_boxes.remove(0);
-            let recipe_category_id = import_recipe_category(
+        const BATCH_SIZE: usize = 20;
+        let working = &mut self.recipe_boxes[0];
+        let split_point = working.recipes.len().saturating_sub(BATCH_SIZE);
+        let (keys, recipe_batch): (Vec<_>, Vec<_>) =
+            working.recipes.split_off(split_point).into_iter().unzip();
+        let category_name = working.name.clone();
+        if working.recipes.is_empty() {
+            self.recipe_boxes.remove(0);
+        }
+
+        // Each batch is one transaction, rather than one for every row inserted. The category is
+        // looked up again each time, in case an earlier batch was rolled back.
+        let mut num_imported = 0;
+        conn.transaction::<_, crate::Error, _>(|conn| {
+            let recipe_category_id = import_recipe_category(conn, &category_name)?;
+            import_recipes_from_box(
                 conn,
-                plist_recipe_box.name.clone(),
-                &mut self.recipe_category_id_vendor,
+                log,
+                &mut num_imported,
+                recipe_batch,
+                recipe_category_id,
             )?;
-            self.working_recipe_box = Some((recipe_category_id, plist_recipe_box));
-        }
+            for key in &keys {
+                mark_imported(conn, &self.source, key)?;
+            }
+            Ok(())
+        })?;
+        self.num_imported += num_imported;
+        Ok(())
+    }
 
-        let (recipe_category_id, working) = &mut self.working_recipe_box.as_mut().unwrap();
+    fn skip_imported(&mut self, conn: &mut database::Connection) -> Result<usize> {
+        let imported = already_imported(conn, &self.source)?;
+        let mut skipped = 0;
+        self.recipe_boxes.retain_mut(|b| {
+            let before = b.recipes.len();
+            b.recipes.retain(|(key, _)| !imported.contains(key));
+            skipped += before - b.recipes.len();
+            // Boxes which were imported completely would only add their category again.
+            before == 0 || !b.recipes.is_empty()
+        });
+        self.total_num_recipes = self
+            .recipe_boxes
+            .iter()
+            .map(|b| b.recipes.len())
+            .sum::<usize>()
+            .max(1);
+        Ok(skipped)
+    }
+}
 
-        const BATCH_SIZE: usize = 5;
-        let split_point = working.recipes.len().saturating_sub(BATCH_SIZE);
-        let recipe_batch = working.recipes.split_off(split_point);
-
-        import_recipes_from_box(
-            conn,
-            &mut self.num_imported,
-            recipe_batch,
-            *recipe_category_id,
-            &mut self.recipe_id_vendor,
-            &mut self.ingredient_usage_id_vendor,
-            &mut self.ingredient_id_vendor,
-        )?;
+/// Writes every recipe to a recipe book the old app can open, with a recipe box for each category.
+/// Metric quantities are converted, since the old app only has US units. Returns how many recipes
+/// were exported.
+pub fn export_recipe_book(
+    conn: &mut database::Connection,
+    path: impl AsRef<Path>,
+) -> Result<usize> {
+    use database::schema::{ingredient_categories, recipe_categories, recipes};
 
-        if working.recipes.is_empty() {
-            self.working_recipe_box = None;
+    let categories: Vec<(RecipeCategoryId, String)> = recipe_categories::table
+        .select((recipe_categories::id, recipe_categories::name))
+        .order_by(recipe_categories::name.asc())
+        .load(conn)?;
+    let ingredient_category_names: HashMap<IngredientCategoryId, String> =
+        ingredient_categories::table
+            .select((ingredient_categories::id, ingredient_categories::name))
+            .load(conn)?
+            .into_iter()
+            .collect();
+
+    let mut num_exported = 0;
+    let mut recipe_boxes = vec![];
+    for (category_id, category_name) in categories {
+        let category_recipes: Vec<Recipe> = recipes::table
+            .filter(recipes::category.eq(category_id))
+            .select(Recipe::as_select())
+            .order_by(recipes::name.asc())
+            .load(conn)?;
+        if category_recipes.is_empty() {
+            continue;
         }
 
-        Ok(())
+        let mut plist_recipes = vec![];
+        for recipe in category_recipes {
+            let ingredients = query::get_ingredients_for_recipe(conn, recipe.id)?
+                .into_iter()
+                .map(|(usage, ingredient)| {
+                    let (quantity, units) = convert_quantity_to_system(
+                        usage.quantity,
+                        usage.quantity_units,
+                        MeasurementSystem::Us,
+                    );
+                    plist::Ingredient {
+                        name: ingredient.name,
+                        category: ingredient
+                            .category_id
+                            .and_then(|c| ingredient_category_names.get(&c).cloned())
+                            .unwrap_or_default(),
+                        quantity: quantity.into(),
+                        measurement: units.and_then(|u| u.export()).unwrap_or("").into(),
+                    }
+                })
+                .collect();
+            plist_recipes.push(plist::Recipe {
+                name: recipe.name,
+                other: recipe.description,
+                time: recipe.duration.export().into(),
+                ingredients,
+            });
+            num_exported += 1;
+        }
+        recipe_boxes.push(plist::RecipeBox {
+            name: category_name,
+            recipes: plist_recipes,
+        });
     }
+
+    plist::encode_recipes_to_path(path, &recipe_boxes)?;
+    Ok(num_exported)
+}
+
+#[test]
+fn export_recipe_book_test() {
+    let mut f = database::fixture::Fixture::open();
+    let path = std::env::temp_dir().join(format!("export-test-{}.recipebook", std::process::id()));
+
+    assert_eq!(export_recipe_book(&mut f.conn, &path).unwrap(), 3);
+    let recipe_boxes = plist::decode_recipes_from_path(&path).unwrap();
+    let names: Vec<_> = recipe_boxes.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, ["Dessert", "Dinner"]);
+    let soup = recipe_boxes[1]
+        .recipes
+        .iter()
+        .find(|r| r.name == "Carrot Soup")
+        .unwrap();
+    assert_eq!(soup.time, "Short");
+    let ingredients: Vec<_> = soup
+        .ingredients
+        .iter()
+        .map(|i| (i.name.as_str(), i.quantity, i.measurement.as_str()))
+        .collect();
+    assert_eq!(
+        ingredients,
+        [("carrot", 4.0, ""), ("onion", 1.0, ""), ("salt", 0.5, "")]
+    );
+
+    // And it imports back in as it was.
+    let mut conn = database::fixture::test_connection();
+    let mut importer = RecipeImporter::new(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut log = String::new();
+    while !importer.done() {
+        importer.import_one(&mut conn, &mut log).unwrap();
+    }
+    assert_eq!(importer.num_imported(), 3);
+    assert!(log.is_empty(), "{log}");
 }
 
 fn find_recipes(conn: &mut database::Connection, search_name: &str) -> Vec<RecipeId> {
@@ -380,6 +1113,13 @@ impl Importer for CalendarImporter {
         } else {
             self.num_ignored += 1;
         }
+        if self.done() && self.num_ignored > 0 {
+            writeln!(
+                log,
+                "{} weeks had nothing new to import and were left out",
+                self.num_ignored
+            )?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,20 @@
+//! Reading the text off a photo of a recipe card with Tesseract, which has to be installed
+//! separately and be on the `PATH`.
+
+use crate::Result;
+use std::path::Path;
+use std::process::Command;
+
+/// The text in the image. This can take a few seconds for a large photo.
+pub fn recognize_text(image: impl AsRef<Path>) -> Result<String> {
+    let output = Command::new("tesseract")
+        .arg(image.as_ref())
+        .arg("stdout")
+        .output()
+        .map_err(|e| format!("couldn't run tesseract, is it installed? ({e})"))?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tesseract failed: {}", error.trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,188 @@
+//! Attaching a folder of photos to the recipes they're named after, like the pictures from
+//! photographing an old recipe box one card at a time.
+
+use super::Importer;
+use crate::database;
+use crate::database::models::RecipeId;
+use crate::duplicates::edit_distance;
+use crate::query;
+use crate::Result;
+use std::path::{Path, PathBuf};
+
+const EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// How alike two names have to be for a photo to be matched with a recipe, from 0 to 1.
+const MIN_SIMILARITY: f32 = 0.6;
+
+/// Lowercases the name and splits it into words, leaving off any number at the end, like the
+/// "2" in "apple_pie_2".
+fn normalize(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let mut words: Vec<_> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.len() > 1
+        && words
+            .last()
+            .is_some_and(|w| w.chars().all(|c| c.is_ascii_digit()))
+    {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// How alike a file name and a recipe name are, from 0 for nothing alike to 1 for the same.
+fn similarity(file_name: &str, recipe_name: &str) -> f32 {
+    let a = normalize(file_name);
+    let b = normalize(recipe_name);
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - edit_distance(&a, &b) as f32 / longest as f32
+}
+
+#[test]
+fn similarity_test() {
+    assert_eq!(normalize("Apple_Pie-2"), "apple pie");
+    assert_eq!(normalize("1984"), "1984");
+    assert_eq!(similarity("apple_pie", "Apple Pie"), 1.0);
+    assert!(similarity("chicken soup (2)", "Chicken Soup") > 0.99);
+    assert!(similarity("chiken-soup", "Chicken Soup") > MIN_SIMILARITY);
+    assert!(similarity("IMG_0042", "Chicken Soup") < MIN_SIMILARITY);
+}
+
+/// A photo in the folder, and the recipe it seems to be of.
+pub struct PhotoMatch {
+    pub path: PathBuf,
+    pub recipe: Option<(RecipeId, String)>,
+}
+
+impl PhotoMatch {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Finds the recipe each photo in the folder is named after, if any is close enough.
+pub fn match_photos(
+    conn: &mut database::Connection,
+    folder: impl AsRef<Path>,
+) -> Result<Vec<PhotoMatch>> {
+    let recipes = query::search_recipes(conn, &mut None, "", true)?;
+    let mut matches = vec![];
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        let is_image = path
+            .extension()
+            .is_some_and(|e| EXTENSIONS.iter().any(|x| e.eq_ignore_ascii_case(x)));
+        if !is_image {
+            continue;
+        }
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let recipe = recipes
+            .iter()
+            .map(|r| (similarity(&stem, &r.1), r))
+            .filter(|(s, _)| *s >= MIN_SIMILARITY)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, r)| r.clone());
+        matches.push(PhotoMatch { path, recipe });
+    }
+    matches.sort_by_key(|m| m.file_name().to_lowercase());
+    Ok(matches)
+}
+
+/// Reads the photos in and attaches them to their recipes.
+pub struct PhotoImporter {
+    photos: Vec<(PathBuf, RecipeId)>,
+    num_imported: usize,
+    total_num_photos: usize,
+}
+
+impl PhotoImporter {
+    pub fn new(photos: Vec<(PathBuf, RecipeId)>) -> Self {
+        Self {
+            total_num_photos: photos.len(),
+            photos,
+            num_imported: 0,
+        }
+    }
+}
+
+impl Importer for PhotoImporter {
+    fn done(&self) -> bool {
+        self.photos.is_empty()
+    }
+
+    fn num_imported(&self) -> usize {
+        self.num_imported
+    }
+
+    fn percent_done(&self) -> f32 {
+        self.num_imported as f32 / self.total_num_photos as f32
+    }
+
+    fn import_one(&mut self, conn: &mut database::Connection, _: &mut String) -> Result<()> {
+        assert!(!self.done());
+
+        let (path, recipe_id) = self.photos.remove(0);
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        query::add_recipe_photo(conn, recipe_id, &file_name, &std::fs::read(&path)?)?;
+        self.num_imported += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn match_photos_test() {
+    use crate::database::fixture::Fixture;
+
+    let folder = std::env::temp_dir().join(format!("photos-test-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    for name in [
+        "carrot_soup.jpg",
+        "Onion-Tart 2.PNG",
+        "IMG_0042.jpg",
+        "notes.txt",
+    ] {
+        std::fs::write(folder.join(name), name).unwrap();
+    }
+
+    let mut f = Fixture::open();
+    let matches = match_photos(&mut f.conn, &folder).unwrap();
+    let found: Vec<_> = matches
+        .iter()
+        .map(|m| (m.file_name(), m.recipe.as_ref().map(|r| r.0)))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("carrot_soup.jpg".into(), Some(f.soup)),
+            ("IMG_0042.jpg".into(), None),
+            ("Onion-Tart 2.PNG".into(), Some(f.tart)),
+        ]
+    );
+
+    let mut importer = PhotoImporter::new(vec![(matches[0].path.clone(), f.soup)]);
+    while !importer.done() {
+        importer
+            .import_one(&mut f.conn, &mut String::new())
+            .unwrap();
+    }
+    let photos = query::get_recipe_photos(&mut f.conn, f.soup).unwrap();
+    assert_eq!(photos.len(), 1);
+    assert_eq!(photos[0].file_name, "carrot_soup.jpg");
+    assert_eq!(photos[0].data, b"carrot_soup.jpg");
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
@@ -440,6 +440,168 @@ pub fn decode_recipes_from_path(path: impl AsRef<Path>) -> crate::Result<Vec<Rec
     Ok(decode_recipes(&value)?)
 }
 
+/// Builds the `$objects` table of a keyed archive, the reverse of [`ArchiverDataDecoder`]. Every
+/// object is added once and referred to by its index.
+struct ArchiverDataEncoder {
+    objects: Vec<Value>,
+    classes: HashMap<&'static str, Uid>,
+}
+
+impl ArchiverDataEncoder {
+    fn new() -> Self {
+        Self {
+            // The archiver always keeps nil at index zero.
+            objects: vec!["$null".into()],
+            classes: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, object: Value) -> Uid {
+        self.objects.push(object);
+        Uid::new(self.objects.len() as u64 - 1)
+    }
+
+    fn encode_class(&mut self, class_name: &'static str, superclasses: &[&str]) -> Uid {
+        if let Some(uid) = self.classes.get(class_name) {
+            return *uid;
+        }
+        let mut classes = vec![Value::from(class_name)];
+        classes.extend(superclasses.iter().map(|&c| Value::from(c)));
+        classes.push("NSObject".into());
+
+        let mut class = Dictionary::new();
+        class.insert("$classname".into(), class_name.into());
+        class.insert("$classes".into(), classes.into());
+        let uid = self.add(class.into());
+        self.classes.insert(class_name, uid);
+        uid
+    }
+
+    fn encode_object(&mut self, class: Uid, mut fields: Dictionary) -> Uid {
+        fields.insert("$class".into(), Value::Uid(class));
+        self.add(fields.into())
+    }
+
+    fn encode_ns_mutable_array(&mut self, values: Vec<Uid>) -> Uid {
+        let class = self.encode_class("NSMutableArray", &["NSArray"]);
+        let mut fields = Dictionary::new();
+        let values: Vec<Value> = values.into_iter().map(Value::Uid).collect();
+        fields.insert("NS.objects".into(), values.into());
+        self.encode_object(class, fields)
+    }
+
+    fn encode_ns_mutable_dictionary(&mut self, entries: Vec<(&str, Value)>) -> Uid {
+        let class = self.encode_class("NSMutableDictionary", &["NSDictionary"]);
+        let mut keys = vec![];
+        let mut values = vec![];
+        for (key, value) in entries {
+            keys.push(Value::Uid(self.add(key.into())));
+            let value = match value {
+                Value::Uid(uid) => uid,
+                value => self.add(value),
+            };
+            values.push(Value::Uid(value));
+        }
+        let mut fields = Dictionary::new();
+        fields.insert("NS.keys".into(), keys.into());
+        fields.insert("NS.objects".into(), values.into());
+        self.encode_object(class, fields)
+    }
+
+    fn encode_ns_mutable_data(&mut self, data: &[u8]) -> Uid {
+        let class = self.encode_class("NSMutableData", &["NSData"]);
+        let mut fields = Dictionary::new();
+        fields.insert("NS.data".into(), Value::Data(data.to_vec()));
+        self.encode_object(class, fields)
+    }
+
+    /// One of the app's own objects, which all have their fields in a properties dictionary.
+    fn encode_app_object(
+        &mut self,
+        class_name: &'static str,
+        properties: Vec<(&str, Value)>,
+        children: Vec<(&str, Uid)>,
+    ) -> Uid {
+        let class = self.encode_class(class_name, &[]);
+        let mut fields = Dictionary::new();
+        let properties = self.encode_ns_mutable_dictionary(properties);
+        fields.insert("properties".into(), Value::Uid(properties));
+        for (key, child) in children {
+            fields.insert(key.into(), Value::Uid(child));
+        }
+        self.encode_object(class, fields)
+    }
+
+    fn finish(self, root: Uid) -> Value {
+        let mut top = Dictionary::new();
+        top.insert("root".into(), Value::Uid(root));
+
+        let mut file = Dictionary::new();
+        file.insert("$archiver".into(), "NSKeyedArchiver".into());
+        file.insert("$version".into(), 100_000u64.into());
+        file.insert("$top".into(), top.into());
+        file.insert("$objects".into(), self.objects.into());
+        file.into()
+    }
+}
+
+fn encode_recipes(recipe_boxes: &[RecipeBox]) -> Value {
+    let mut encoder = ArchiverDataEncoder::new();
+
+    let mut boxes = vec![];
+    for b in recipe_boxes {
+        let mut recipes = vec![];
+        for r in &b.recipes {
+            let mut ingredients = vec![];
+            for i in &r.ingredients {
+                ingredients.push(encoder.encode_app_object(
+                    "Ingredient",
+                    vec![
+                        ("Name", i.name.as_str().into()),
+                        // Misspelled in the old app too.
+                        ("Catagory", i.category.as_str().into()),
+                        ("Quantity", i.quantity.into()),
+                        ("Measurement", i.measurement.as_str().into()),
+                    ],
+                    vec![],
+                ));
+            }
+            let ingredients = encoder.encode_ns_mutable_array(ingredients);
+            let other = encoder.encode_ns_mutable_data(r.other.as_bytes());
+            recipes.push(encoder.encode_app_object(
+                "Recipe",
+                vec![
+                    ("Name", r.name.as_str().into()),
+                    ("Other", Value::Uid(other)),
+                    ("Time", r.time.as_str().into()),
+                ],
+                vec![("ingredients", ingredients)],
+            ));
+        }
+        let recipes = encoder.encode_ns_mutable_array(recipes);
+        boxes.push(encoder.encode_app_object(
+            "RecipeBox",
+            vec![("Name", b.name.as_str().into())],
+            vec![("recipes", recipes)],
+        ));
+    }
+    let boxes = encoder.encode_ns_mutable_array(boxes);
+    let class = encoder.encode_class("RecipeBook", &[]);
+    let mut fields = Dictionary::new();
+    fields.insert("recipeBoxes".into(), Value::Uid(boxes));
+    let root = encoder.encode_object(class, fields);
+    encoder.finish(root)
+}
+
+/// Writes the recipe boxes as a file the old app can open, and `decode_recipes_from_path` can read.
+pub fn encode_recipes_to_path(
+    path: impl AsRef<Path>,
+    recipe_boxes: &[RecipeBox],
+) -> crate::Result<()> {
+    encode_recipes(recipe_boxes).to_file_binary(path)?;
+    Ok(())
+}
+
 #[derive(Copy, Clone, Debug, derive_more::Display, strum::EnumIter, Hash, PartialEq, Eq)]
 #[repr(u32)]
 pub enum DayOfWeek {
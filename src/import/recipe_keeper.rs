@@ -0,0 +1,298 @@
+//! Recipes exported from Recipe Keeper. Its export is a zip file of a web page with every recipe,
+//! marked up with schema.org microdata, along with their photos.
+
+use super::{ForeignIngredient, ForeignRecipe};
+use crate::database::models::RecipeDuration;
+use crate::Result;
+use std::io::Read as _;
+use std::path::Path;
+
+pub fn decode_recipes_from_path(path: impl AsRef<Path>) -> Result<Vec<ForeignRecipe>> {
+    let path = path.as_ref();
+    let html = if path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+    {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+        let name = archive
+            .file_names()
+            .find(|n| n.ends_with("recipes.html"))
+            .ok_or("no recipes.html found in the zip file")?
+            .to_owned();
+        let mut html = String::new();
+        archive.by_name(&name)?.read_to_string(&mut html)?;
+        html
+    } else {
+        std::fs::read_to_string(path)?
+    };
+    Ok(decode_html(&html))
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "wbr",
+];
+
+/// Elements which start a new line of text.
+const BLOCK_ELEMENTS: &[&str] = &["br", "div", "li", "p", "tr", "h1", "h2", "h3", "h4"];
+
+/// The value of an attribute in the inside of a tag, like `content` in
+/// `meta content="PT5M" itemprop="prepTime"`.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(i) = rest.find(name) {
+        let before = rest[..i].chars().last();
+        rest = &rest[i + name.len()..];
+        let Some(value) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or_default(),
+            _ => value
+                .split(|c: char| c.is_whitespace())
+                .next()
+                .unwrap_or_default(),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        decoded += &rest[..i];
+        rest = &rest[i..];
+        let entity = rest[1..].split_once(';').map(|(e, _)| e);
+        let c = entity.and_then(|e| match e {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            e => match e.strip_prefix("#x").or_else(|| e.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => e.strip_prefix('#')?.parse().ok().and_then(char::from_u32),
+            },
+        });
+        match (c, entity) {
+            (Some(c), Some(entity)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded + rest
+}
+
+/// Collapses the whitespace in each line, dropping the empty ones.
+fn tidy(text: &str) -> String {
+    text.lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Every microdata property in the page, in order, like `("name", "Banana Bread")`. The text of
+/// elements like paragraphs inside a property are on their own lines.
+fn microdata(html: &str) -> Vec<(String, String)> {
+    let mut properties: Vec<(String, String)> = vec![];
+    // The open elements, and the property each one is, if any.
+    let mut open: Vec<(String, Option<usize>)> = vec![];
+    let append =
+        |properties: &mut Vec<(String, String)>, open: &[(String, Option<usize>)], text: &str| {
+            for &(_, property) in open {
+                if let Some(i) = property {
+                    properties[i].1 += text;
+                }
+            }
+        };
+
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let text = decode_entities(&rest[..start]).replace(['\n', '\r'], " ");
+        append(&mut properties, &open, &text);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.split_once("-->").map_or("", |(_, r)| r);
+            continue;
+        }
+        let Some((tag, after)) = rest[1..].split_once('>') else {
+            break;
+        };
+        rest = after;
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim().to_lowercase();
+            if let Some(i) = open.iter().rposition(|(n, _)| *n == name) {
+                open.truncate(i);
+            }
+            if BLOCK_ELEMENTS.contains(&name.as_str()) {
+                append(&mut properties, &open, "\n");
+            }
+            continue;
+        }
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+        let name: String = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            append(&mut properties, &open, "\n");
+        }
+        let property = attribute(tag, "itemprop").map(|p| {
+            properties.push((p, attribute(tag, "content").unwrap_or_default()));
+            properties.len() - 1
+        });
+        if !VOID_ELEMENTS.contains(&name.as_str()) && !tag.ends_with('/') {
+            open.push((name, property));
+        }
+    }
+    properties
+        .into_iter()
+        .map(|(p, value)| (p, tidy(&value)))
+        .collect()
+}
+
+/// An ISO 8601 duration like "PT1H30M", in minutes.
+fn duration_minutes(duration: &str) -> Option<u32> {
+    let mut minutes = 0;
+    let mut number = String::new();
+    for c in duration.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'D' | 'H' | 'M' | 'S' => {
+                let n: u32 = number.parse().ok()?;
+                number.clear();
+                minutes += match c {
+                    'D' => n * 24 * 60,
+                    'H' => n * 60,
+                    'M' => n,
+                    _ => 0,
+                };
+            }
+            _ => return None,
+        }
+    }
+    Some(minutes)
+}
+
+fn decode_recipe(html: &str) -> Option<ForeignRecipe> {
+    let properties = microdata(html);
+    let first = |name: &str| {
+        properties
+            .iter()
+            .find(|(p, v)| p == name && !v.is_empty())
+            .map(|(_, v)| v.as_str())
+    };
+    let name = first("name")?;
+    let times: Vec<_> = ["prepTime", "cookTime"]
+        .into_iter()
+        .filter_map(|t| first(t).and_then(duration_minutes))
+        .collect();
+
+    let ingredients = first("recipeIngredients")
+        .unwrap_or_default()
+        .lines()
+        // Headings like "For the sauce:".
+        .filter(|l| !l.ends_with(':'))
+        .map(ForeignIngredient::parse)
+        .filter(|i| !i.name.is_empty())
+        .collect();
+    let description: Vec<_> = ["recipeDirections", "recipeNotes"]
+        .into_iter()
+        .filter_map(first)
+        .map(|text| text.replace('\n', "\n\n"))
+        .collect();
+
+    Some(ForeignRecipe {
+        name: name.into(),
+        category: first("recipeCategory")
+            .or_else(|| first("recipeCourse"))
+            .map(|c| c.into()),
+        description: description.join("\n\n"),
+        duration: (!times.is_empty()).then(|| RecipeDuration::from_minutes(times.iter().sum())),
+        ingredients,
+    })
+}
+
+fn decode_html(html: &str) -> Vec<ForeignRecipe> {
+    html.split("class=\"recipe-details\"")
+        .skip(1)
+        .filter_map(decode_recipe)
+        .collect()
+}
+
+#[test]
+fn decode_html_test() {
+    use crate::database::models::IngredientMeasurement::*;
+
+    let html = r#"<html><head><style>p { margin: 0 }</style></head><body>
+<div class="recipe-details">
+  <table><tr><td valign="top">
+    <h2 itemprop="name">Banana &amp; Walnut Bread</h2>
+    <meta content="abc" itemprop="recipeId">
+    <div>Courses: <span itemprop="recipeCourse">Dessert</span></div>
+    <div>Categories: <span>Baking</span><meta content="Baking" itemprop="recipeCategory"></div>
+    <div>Preparation time: <span>15 mins</span><meta content="PT15M" itemprop="prepTime"></div>
+    <div>Cooking time: <span>1 hour</span><meta content="PT1H" itemprop="cookTime"></div>
+  </td><td><img src="images/abc.jpg"/></td></tr></table>
+  <div itemprop="recipeIngredients"><p>3 ripe bananas, mashed</p><p>For the top:</p>
+    <p>1 1/2 cups flour</p><p>
+      1/2 tsp salt</p></div>
+  <div itemprop="recipeDirections"><p>Mash the bananas.</p><!-- a comment --><p>Bake.</p></div>
+  <div itemprop="recipeNotes"></div>
+</div>
+<div class="recipe-details">
+  <h2 itemprop="name">Toast</h2>
+  <div itemprop="recipeIngredients">1 slice bread<br>butter</div>
+  <div itemprop="recipeDirections"><p>Toast it.</p></div>
+</div>
+</body></html>"#;
+    let recipes = decode_html(html);
+    assert_eq!(recipes.len(), 2);
+
+    let bread = &recipes[0];
+    assert_eq!(bread.name, "Banana & Walnut Bread");
+    assert_eq!(bread.category.as_deref(), Some("Baking"));
+    assert_eq!(bread.duration, Some(RecipeDuration::Long));
+    assert_eq!(bread.description, "Mash the bananas.\n\nBake.");
+    assert_eq!(
+        bread.ingredients,
+        vec![
+            ForeignIngredient::parse("3 ripe bananas, mashed"),
+            ForeignIngredient::parse("1 1/2 cups flour"),
+            ForeignIngredient::parse("1/2 tsp salt"),
+        ]
+    );
+    assert_eq!(bread.ingredients[0].note, "mashed");
+    assert_eq!(bread.ingredients[2].units, Some(Teaspoons));
+
+    let toast = &recipes[1];
+    assert_eq!(toast.category, None);
+    assert_eq!(toast.duration, None);
+    assert_eq!(toast.ingredients.len(), 2);
+    assert_eq!(toast.ingredients[1].name, "butter");
+
+    assert_eq!(duration_minutes("PT1H30M"), Some(90));
+    assert_eq!(duration_minutes("P1DT2H"), Some(26 * 60));
+    assert_eq!(duration_minutes("soon"), None);
+    assert_eq!(
+        decode_entities("a &lt;b&gt; &#233; &#xE9; & c"),
+        "a <b> é é & c"
+    );
+}
@@ -0,0 +1,144 @@
+//! Recipes as plain text, like pasted from a web page or read off a photographed recipe card. The
+//! name comes first, then the ingredients, which are the lines starting with a quantity, and
+//! everything else is the directions.
+
+use super::ForeignIngredient;
+use crate::unit_conversion::quantity_with_unit_parse;
+
+/// A recipe picked out of some text, for the user to check over before it's added.
+#[derive(Debug, Default, PartialEq)]
+pub struct RecipeText {
+    pub name: String,
+    /// One ingredient on each line, like "2 cups flour, sifted".
+    pub ingredients: String,
+    pub directions: String,
+}
+
+/// Headings which are left out, since the ingredients and directions are told apart anyway.
+const HEADINGS: &[&str] = &[
+    "ingredients",
+    "directions",
+    "instructions",
+    "method",
+    "preparation",
+    "steps",
+];
+
+fn is_heading(line: &str) -> bool {
+    let line = line.trim().trim_end_matches(':').to_lowercase();
+    HEADINGS.contains(&line.as_str())
+}
+
+/// Bullets and numbering which lists start with, like "- " or "3. ".
+fn strip_bullet(line: &str) -> &str {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix(['-', '*', '•']) {
+        return rest.trim_start();
+    }
+    let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+    match line[digits..].strip_prefix(['.', ')']) {
+        Some(rest) if digits > 0 && rest.starts_with(' ') => rest.trim_start(),
+        _ => line,
+    }
+}
+
+fn is_ingredient(line: &str) -> bool {
+    let first = line.split_whitespace().next().unwrap_or_default();
+    quantity_with_unit_parse(first).is_some()
+}
+
+pub fn parse_recipe_text(text: &str) -> RecipeText {
+    let mut lines = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+    let mut recipe = RecipeText {
+        name: lines.next().unwrap_or_default().into(),
+        ..Default::default()
+    };
+
+    let mut ingredients = vec![];
+    let mut paragraphs: Vec<String> = vec![];
+    for line in text
+        .lines()
+        .map(|l| l.trim())
+        .skip_while(|l| l.is_empty())
+        .skip(1)
+    {
+        if is_heading(line) {
+            continue;
+        }
+        if line.is_empty() {
+            if paragraphs.last().is_some_and(|p| !p.is_empty()) {
+                paragraphs.push(String::new());
+            }
+        } else if is_ingredient(strip_bullet(line)) {
+            ingredients.push(strip_bullet(line));
+        } else {
+            // Numbered steps are each their own paragraph.
+            let step = strip_bullet(line);
+            match paragraphs.last_mut() {
+                Some(p) if !p.is_empty() && step == line => {
+                    p.push(' ');
+                    p.push_str(step);
+                }
+                Some(p) if p.is_empty() => p.push_str(step),
+                _ => paragraphs.push(step.into()),
+            }
+        }
+    }
+    recipe.ingredients = ingredients.join("\n");
+    recipe.directions = paragraphs
+        .iter()
+        .filter(|p| !p.is_empty())
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    recipe
+}
+
+impl RecipeText {
+    pub(super) fn foreign_ingredients(&self) -> Vec<ForeignIngredient> {
+        self.ingredients
+            .lines()
+            .map(|l| ForeignIngredient::parse(strip_bullet(l)))
+            .filter(|i| !i.name.is_empty())
+            .collect()
+    }
+}
+
+#[test]
+fn parse_recipe_text_test() {
+    let text = "
+Grandma's Pancakes
+
+Ingredients:
+1 1/2 cups flour
+2 eggs, beaten
+- 1 tbsp sugar
+
+Directions
+1. Mix everything
+together.
+2. Fry in butter.
+
+Serve warm.
+";
+    let recipe = parse_recipe_text(text);
+    assert_eq!(recipe.name, "Grandma's Pancakes");
+    assert_eq!(
+        recipe.ingredients,
+        "1 1/2 cups flour\n2 eggs, beaten\n1 tbsp sugar"
+    );
+    assert_eq!(
+        recipe.directions,
+        "Mix everything together.\n\nFry in butter.\n\nServe warm."
+    );
+    assert_eq!(parse_recipe_text(""), RecipeText::default());
+
+    let recipe = RecipeText {
+        ingredients: "2 eggs\n\n- 1/2 tsp salt".into(),
+        ..Default::default()
+    };
+    let ingredients = recipe.foreign_ingredients();
+    assert_eq!(ingredients.len(), 2);
+    assert_eq!(ingredients[1].name, "salt");
+    assert_eq!(ingredients[1].quantity, 0.5);
+}
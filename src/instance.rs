@@ -0,0 +1,59 @@
+//! Making sure only one running copy of the program changes the database at a time. The first one
+//! holds a lock on a file next to the database for as long as it runs.
+
+use recipe_manager::{data_path, Result};
+use std::fs::File;
+use std::path::Path;
+
+/// How this instance can use the database.
+pub enum Access {
+    /// This instance holds the lock, for as long as it has this.
+    ReadWrite { _lock: InstanceLock },
+    /// Another instance is changing the database. Once it's closed, this one can take over.
+    InUse,
+    /// Asked for with `--read-only`, or the database file isn't writable, like on a shared drive.
+    ReadOnly,
+}
+
+impl Access {
+    pub fn is_read_write(&self) -> bool {
+        matches!(self, Self::ReadWrite { .. })
+    }
+}
+
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Takes the lock, or returns `None` if another instance already has it.
+    pub fn acquire() -> Result<Option<Self>> {
+        Self::acquire_at(&data_path()?.join("recipe-manager.lock"))
+    }
+
+    fn acquire_at(path: &Path) -> Result<Option<Self>> {
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+            Err(std::fs::TryLockError::Error(error)) => Err(error.into()),
+        }
+    }
+}
+
+#[test]
+fn instance_lock_test() {
+    let path = std::env::temp_dir().join(format!("recipe-manager-{}.lock", std::process::id()));
+
+    let first = InstanceLock::acquire_at(&path).unwrap();
+    assert!(first.is_some());
+    assert!(InstanceLock::acquire_at(&path).unwrap().is_none());
+    drop(first);
+    assert!(InstanceLock::acquire_at(&path).unwrap().is_some());
+
+    std::fs::remove_file(&path).unwrap();
+}
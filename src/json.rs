@@ -0,0 +1,341 @@
+//! The recipe book as JSON: its recipes by category, its ingredients along with their calories,
+//! and the calendar. Everything but the categories can be left out, which is how the bundled
+//! sample recipes are written.
+
+use crate::database;
+use crate::database::models::{CalendarEntry, IngredientMeasurement, RecipeDuration, RecipeStatus};
+use crate::query;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonIngredientUsage {
+    pub name: String,
+    pub quantity: f32,
+    pub units: Option<String>,
+    pub note: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonRecipe {
+    /// What the calendar refers to it by. It's only the same as its id in the database it was
+    /// exported from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    pub name: String,
+    pub duration: String,
+    pub description: String,
+    #[serde(default)]
+    pub rating: Option<i32>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub servings: Option<i32>,
+    pub ingredients: Vec<JsonIngredientUsage>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonCategory {
+    pub name: String,
+    #[serde(default)]
+    pub default_duration: Option<String>,
+    pub recipes: Vec<JsonRecipe>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonCalories {
+    pub calories: f32,
+    pub quantity: f32,
+    pub units: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonIngredient {
+    pub name: String,
+    #[serde(default)]
+    pub staple: bool,
+    #[serde(default)]
+    pub season: i32,
+    #[serde(default)]
+    pub diet: i32,
+    #[serde(default)]
+    pub density: Option<f32>,
+    #[serde(default)]
+    pub price: Option<f32>,
+    #[serde(default)]
+    pub price_units: Option<String>,
+    #[serde(default)]
+    pub calories: Vec<JsonCalories>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonCalendarEntry {
+    pub day: chrono::NaiveDate,
+    /// The `id` of one of the recipes.
+    pub recipe: i32,
+    #[serde(default)]
+    pub cooked: bool,
+    #[serde(default)]
+    pub servings: Option<i32>,
+    #[serde(default)]
+    pub from_freezer: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonRecipeBook {
+    pub categories: Vec<JsonCategory>,
+    #[serde(default)]
+    pub ingredients: Vec<JsonIngredient>,
+    #[serde(default)]
+    pub calendar: Vec<JsonCalendarEntry>,
+}
+
+pub fn parse_duration(duration: &str) -> Result<RecipeDuration> {
+    RecipeDuration::iter()
+        .find(|d| d.to_string() == duration)
+        .ok_or_else(|| format!("unknown recipe duration {duration:?}").into())
+}
+
+pub fn parse_units(units: &str) -> Result<IngredientMeasurement> {
+    IngredientMeasurement::iter()
+        .find(|u| u.as_str() == units)
+        .ok_or_else(|| format!("unknown ingredient units {units:?}").into())
+}
+
+fn parse_optional_units(units: Option<&str>) -> Result<Option<IngredientMeasurement>> {
+    units.map(parse_units).transpose()
+}
+
+pub fn parse_status(status: &str) -> Result<RecipeStatus> {
+    <RecipeStatus as strum::IntoEnumIterator>::iter()
+        .find(|s| s.to_string() == status)
+        .ok_or_else(|| format!("unknown recipe status {status:?}").into())
+}
+
+/// Checks that everything in the recipe book makes sense before any of it is added.
+pub fn parse(json: &str) -> Result<JsonRecipeBook> {
+    let book: JsonRecipeBook = serde_json::from_str(json)?;
+    let mut ids = vec![];
+    for category in &book.categories {
+        if let Some(duration) = &category.default_duration {
+            parse_duration(duration)?;
+        }
+        for recipe in &category.recipes {
+            parse_duration(&recipe.duration)?;
+            if let Some(status) = &recipe.status {
+                parse_status(status)?;
+            }
+            for ingredient in &recipe.ingredients {
+                parse_optional_units(ingredient.units.as_deref())?;
+            }
+            ids.extend(recipe.id);
+        }
+    }
+    for ingredient in &book.ingredients {
+        parse_optional_units(ingredient.price_units.as_deref())?;
+        for calories in &ingredient.calories {
+            parse_optional_units(calories.units.as_deref())?;
+        }
+    }
+    for entry in &book.calendar {
+        if !ids.contains(&entry.recipe) {
+            return Err(format!("{} is on the calendar, but isn't a recipe", entry.day).into());
+        }
+    }
+    Ok(book)
+}
+
+#[test]
+fn parse_test() {
+    assert_eq!(
+        parse_duration("really long").unwrap(),
+        RecipeDuration::ReallyLong
+    );
+    assert!(parse_duration("forever").is_err());
+    assert_eq!(
+        parse_units("tbsp.").unwrap(),
+        IngredientMeasurement::Tablespoons
+    );
+    assert!(parse_units("handfuls").is_err());
+    assert_eq!(
+        parse_status("want to try").unwrap(),
+        RecipeStatus::WantToTry
+    );
+
+    let book = parse(r#"{ "categories": [], "calendar": [{ "day": "2024-01-01", "recipe": 1 }] }"#);
+    assert!(book.is_err());
+}
+
+/// The whole recipe book, archived recipes included.
+pub fn export(conn: &mut database::Connection) -> Result<String> {
+    let mut recipes: HashMap<_, Vec<_>> = HashMap::new();
+    for recipe in query::get_all_recipes(conn)? {
+        let ingredients = query::get_ingredients_for_recipe(conn, recipe.id)?
+            .into_iter()
+            .map(|(usage, ingredient)| JsonIngredientUsage {
+                name: ingredient.name,
+                quantity: usage.quantity,
+                units: usage.quantity_units.map(|u| u.as_str().into()),
+                note: usage.note,
+            })
+            .collect();
+        recipes
+            .entry(recipe.category)
+            .or_default()
+            .push(JsonRecipe {
+                id: Some(recipe.id.to_i32()),
+                name: recipe.name,
+                duration: recipe.duration.to_string(),
+                description: recipe.description,
+                rating: recipe.rating,
+                archived: recipe.archived,
+                status: Some(recipe.status.to_string()),
+                servings: recipe.servings,
+                ingredients,
+            });
+    }
+    let categories = query::get_recipe_categories(conn)?
+        .into_iter()
+        .map(|(category, _)| JsonCategory {
+            name: category.name,
+            default_duration: category.default_duration.map(|d| d.to_string()),
+            recipes: recipes.remove(&category.id).unwrap_or_default(),
+        })
+        .collect();
+
+    let mut ingredients = vec![];
+    for ingredient in query::get_all_ingredients(conn)? {
+        let calories = query::get_ingredient_calories(conn, ingredient.id)?
+            .into_iter()
+            .map(|c| JsonCalories {
+                calories: c.calories,
+                quantity: c.quantity,
+                units: c.quantity_units.map(|u| u.as_str().into()),
+            })
+            .collect();
+        ingredients.push(JsonIngredient {
+            name: ingredient.name,
+            staple: ingredient.staple,
+            season: ingredient.season,
+            diet: ingredient.diet,
+            density: ingredient.density,
+            price: ingredient.price,
+            price_units: ingredient.price_units.map(|u| u.as_str().into()),
+            calories,
+        });
+    }
+
+    let calendar = query::get_all_calendar_entries(conn)?
+        .into_iter()
+        .map(|entry| JsonCalendarEntry {
+            day: entry.day,
+            recipe: entry.recipe_id.to_i32(),
+            cooked: entry.cooked,
+            servings: entry.servings,
+            from_freezer: entry.from_freezer,
+        })
+        .collect();
+
+    let book = JsonRecipeBook {
+        categories,
+        ingredients,
+        calendar,
+    };
+    Ok(serde_json::to_string_pretty(&book)?)
+}
+
+/// Adds the recipe book to the database, returning how many recipes were added. Ingredients which
+/// already exist are reused as they are.
+pub fn import(conn: &mut database::Connection, json: &str) -> Result<usize> {
+    let book = parse(json)?;
+
+    let mut ingredient_ids = HashMap::new();
+    let mut ingredient_id = |conn: &mut database::Connection, name: &str| -> Result<_> {
+        if let Some(&id) = ingredient_ids.get(name) {
+            return Ok((id, false));
+        }
+        let (id, added) = match query::get_ingredient_by_name(conn, name)? {
+            Some(i) => (i.id, false),
+            None => (query::add_ingredient(conn, name)?.id, true),
+        };
+        ingredient_ids.insert(name.to_owned(), id);
+        Ok((id, added))
+    };
+
+    for ingredient in &book.ingredients {
+        let (id, added) = ingredient_id(conn, &ingredient.name)?;
+        if !added {
+            continue;
+        }
+        query::set_ingredient_staple(conn, id, ingredient.staple)?;
+        query::set_ingredient_season(conn, id, ingredient.season)?;
+        query::set_ingredient_diet(conn, id, ingredient.diet)?;
+        query::set_ingredient_density(conn, id, ingredient.density)?;
+        let price_units = parse_optional_units(ingredient.price_units.as_deref())?;
+        query::set_ingredient_price(conn, id, ingredient.price, price_units)?;
+        for calories in &ingredient.calories {
+            let units = parse_optional_units(calories.units.as_deref())?;
+            query::add_ingredient_calories_entry(
+                conn,
+                id,
+                calories.calories,
+                calories.quantity,
+                units,
+            )?;
+        }
+    }
+
+    let mut recipe_ids = HashMap::new();
+    let mut num_added = 0;
+    for category in book.categories {
+        let category_id = query::add_category(conn, &category.name)?;
+        if let Some(duration) = &category.default_duration {
+            let duration = Some(parse_duration(duration)?);
+            query::edit_category_default_duration(conn, category_id, duration)?;
+        }
+        for recipe in category.recipes {
+            let recipe_id = query::add_recipe(conn, &recipe.name, category_id)?;
+            query::edit_recipe_duration(conn, recipe_id, parse_duration(&recipe.duration)?)?;
+            query::edit_recipe_description(conn, recipe_id, &recipe.description)?;
+            query::edit_recipe_rating(conn, recipe_id, recipe.rating)?;
+            query::edit_recipe_archived(conn, recipe_id, recipe.archived)?;
+            query::edit_recipe_servings(conn, recipe_id, recipe.servings)?;
+            if let Some(status) = &recipe.status {
+                query::edit_recipe_status(conn, recipe_id, parse_status(status)?)?;
+            }
+            for ingredient in recipe.ingredients {
+                let (id, _) = ingredient_id(conn, &ingredient.name)?;
+                let units = parse_optional_units(ingredient.units.as_deref())?;
+                query::add_recipe_ingredient_with_units(
+                    conn,
+                    recipe_id,
+                    id,
+                    ingredient.quantity,
+                    units,
+                    &ingredient.note,
+                )?;
+            }
+            if let Some(id) = recipe.id {
+                recipe_ids.insert(id, recipe_id);
+            }
+            num_added += 1;
+        }
+    }
+
+    for entry in &book.calendar {
+        query::restore_calendar_entry(
+            conn,
+            &CalendarEntry {
+                day: entry.day,
+                recipe_id: recipe_ids[&entry.recipe],
+                cooked: entry.cooked,
+                servings: entry.servings,
+                from_freezer: entry.from_freezer,
+            },
+        )?;
+    }
+    Ok(num_added)
+}
@@ -0,0 +1,99 @@
+// Copyright 2023 Remi Bernotavicius
+
+//! The recipe database and everything which works with it without needing a display: queries,
+//! importing, unit conversion and calories. The GUI is a thin layer on top of this.
+
+use std::path::PathBuf;
+
+pub mod compound;
+pub mod cooklang;
+pub mod credentials;
+pub mod csv;
+pub mod database;
+pub mod diet;
+pub mod duplicates;
+#[cfg(feature = "email")]
+pub mod email;
+#[cfg(feature = "nutrition-lookup")]
+pub mod fdc;
+#[cfg(feature = "grocery-export")]
+pub mod grocery;
+#[cfg(feature = "http-api")]
+pub mod http_api;
+#[cfg(feature = "legacy-import")]
+pub mod import;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod query;
+pub mod recipe;
+#[cfg(feature = "share")]
+pub mod share;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod unit_conversion;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The environment variable which moves the user-data somewhere else, like onto a USB stick.
+pub const DATA_DIR_VAR: &str = "RECIPE_MANAGER_DATA_DIR";
+
+/// Where the user-data was asked to be with `--data-dir=PATH` or `--data-dir PATH`, or else
+/// `DATA_DIR_VAR`.
+fn data_dir_override(
+    mut args: impl Iterator<Item = std::ffi::OsString>,
+    var: Option<std::ffi::OsString>,
+) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--data-dir" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.to_str().and_then(|a| a.strip_prefix("--data-dir=")) {
+            return Some(path.into());
+        }
+    }
+    var.filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// This is where the database and other user-data lives on-disk. On Linux it should be like:
+/// `~/.local/share/recipe_manager/`, unless it's overridden with `--data-dir` or `DATA_DIR_VAR`.
+pub fn data_path() -> Result<PathBuf> {
+    let path = match data_dir_override(std::env::args_os(), std::env::var_os(DATA_DIR_VAR)) {
+        Some(path) => path,
+        None => {
+            let dirs = directories::BaseDirs::new().expect("failed to get user home directory");
+            dirs.data_dir().join("recipe-manager")
+        }
+    };
+    std::fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+#[test]
+fn data_dir_override_test() {
+    let args = |a: &[&str]| a.iter().map(Into::into).collect::<Vec<_>>().into_iter();
+
+    assert_eq!(data_dir_override(args(&["recipe-manager"]), None), None);
+    assert_eq!(
+        data_dir_override(
+            args(&["recipe-manager", "--data-dir=/a"]),
+            Some("/b".into())
+        ),
+        Some("/a".into())
+    );
+    assert_eq!(
+        data_dir_override(
+            args(&["recipe-manager", "--data-dir", "/a", "--read-only"]),
+            None
+        ),
+        Some("/a".into())
+    );
+    assert_eq!(
+        data_dir_override(args(&["recipe-manager"]), Some("/b".into())),
+        Some("/b".into())
+    );
+    assert_eq!(
+        data_dir_override(args(&["recipe-manager"]), Some("".into())),
+        None
+    );
+}
@@ -0,0 +1,171 @@
+//! Logging to a file in the data directory, since there is no console to see it in on Windows.
+//! The log is rotated when it gets big, keeping a few old ones around.
+
+use recipe_manager::{data_path, Result};
+use std::fs::{self, File};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// When the log gets this big, it is moved aside and a new one started.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// How many old logs are kept, as `recipe-manager.log.1` and so on, `.1` being the newest.
+const KEPT_LOGS: usize = 4;
+
+pub fn log_path() -> Result<PathBuf> {
+    let dir = data_path()?.join("logs");
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join("recipe-manager.log"))
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    name.into()
+}
+
+/// Moves the log to `.1`, `.1` to `.2` and so on, dropping the oldest.
+fn rotate(path: &Path) -> std::io::Result<()> {
+    for n in (1..KEPT_LOGS).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            fs::rename(from, rotated_path(path, n + 1))?;
+        }
+    }
+    if path.exists() {
+        fs::rename(path, rotated_path(path, 1))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn rotate_test() {
+    let dir = std::env::temp_dir().join(format!("recipe-manager-rotate-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("test.log");
+
+    for i in 0..=KEPT_LOGS + 1 {
+        fs::write(&path, i.to_string()).unwrap();
+        rotate(&path).unwrap();
+    }
+    assert!(!path.exists());
+    for n in 1..=KEPT_LOGS {
+        let contents = fs::read_to_string(rotated_path(&path, n)).unwrap();
+        assert_eq!(contents, (KEPT_LOGS + 2 - n).to_string());
+    }
+    assert!(!rotated_path(&path, KEPT_LOGS + 1).exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn format_record(time: chrono::DateTime<chrono::Local>, record: &log::Record<'_>) -> String {
+    format!(
+        "{} {:<5} {}: {}\n",
+        time.format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+#[test]
+fn format_record_test() {
+    use chrono::TimeZone as _;
+
+    let time = chrono::Local
+        .with_ymd_and_hms(2024, 3, 5, 18, 30, 2)
+        .unwrap();
+    let line = format_record(
+        time,
+        &log::Record::builder()
+            .level(log::Level::Warn)
+            .target("recipe_manager::import")
+            .args(format_args!("no such file"))
+            .build(),
+    );
+    let offset = time.format("%:z");
+    assert_eq!(
+        line,
+        format!("2024-03-05T18:30:02.000{offset} WARN  recipe_manager::import: no such file\n")
+    );
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = File::options().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    fn write(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size > MAX_LOG_SIZE {
+            rotate(&self.path)?;
+            *self = Self::open(self.path.clone())?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Writes each message both to the log file and to stderr.
+struct Logger {
+    level: log::LevelFilter,
+    file: Mutex<Option<LogFile>>,
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_record(chrono::Local::now(), record);
+        eprint!("{line}");
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(f) = &mut *file {
+            if let Err(error) = f.write(&line) {
+                eprintln!("failed to write to log file: {error}");
+                *file = None;
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(f) = &mut *self.file.lock().unwrap_or_else(|e| e.into_inner()) {
+            let _ = f.file.flush();
+        }
+    }
+}
+
+/// Starts logging warnings and errors, or whatever level `RUST_LOG` asks for. If the log file
+/// can't be opened, messages still go to stderr.
+pub fn init() {
+    let level = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(log::LevelFilter::Warn);
+    let file = match log_path().and_then(|path| Ok(LogFile::open(path)?)) {
+        Ok(file) => Some(file),
+        Err(error) => {
+            eprintln!("failed to open log file: {error}");
+            None
+        }
+    };
+    let logger = Logger {
+        level,
+        file: Mutex::new(file),
+    };
+    log::set_boxed_logger(Box::new(logger)).unwrap();
+    log::set_max_level(level);
+}
@@ -2,25 +2,16 @@
 
 #![windows_subsystem = "windows"]
 
-use std::path::PathBuf;
+use recipe_manager::{data_path, database, Result};
+use std::path::{Path, PathBuf};
 
-mod database;
-mod import;
+mod instance;
+mod logging;
 mod ui;
 
-type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
-type Result<T> = std::result::Result<T, Error>;
-
-/// This is where the database and other user-data lives on-disk. On Linux it should be like:
-/// `~/.local/share/recipe_manager/`
-fn data_path() -> Result<PathBuf> {
-    let dirs = directories::BaseDirs::new().expect("failed to get user home directory");
-    let path = dirs.data_dir().join("recipe-manager");
-    std::fs::create_dir_all(&path)?;
-    Ok(path)
-}
-
-fn run(conn: database::Connection) -> Result<()> {
+/// Opens the database in the UI, where the progress of upgrading it can be shown.
+fn run(path: PathBuf, access: instance::Access) -> Result<()> {
+    ui::install_panic_hook();
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_maximized(true)
@@ -33,21 +24,61 @@ fn run(conn: database::Connection) -> Result<()> {
     eframe::run_native(
         "Recipe Manager",
         native_options,
-        Box::new(|_cc| Ok(Box::new(ui::RecipeManager::new(conn)))),
+        Box::new(|_cc| Ok(Box::new(ui::Startup::new(path, access, opened)))),
     )
     .unwrap();
 
     Ok(())
 }
 
+/// Starts serving the recipe book if asked for with `--serve[=PORT]`, or in the settings. Other
+/// devices can only connect with `--lan`, or when that's turned on in the settings too.
+#[cfg(feature = "http-api")]
+fn start_http_api(conn: &mut database::Connection, path: &Path, read_only: bool) {
+    use recipe_manager::http_api;
+
+    let port = std::env::args()
+        .find_map(|a| match a.strip_prefix("--serve")? {
+            "" => Some(http_api::DEFAULT_PORT),
+            port => port.strip_prefix('=')?.parse().ok(),
+        })
+        .or_else(|| {
+            recipe_manager::query::get_setting(conn, http_api::PORT_SETTING)
+                .ok()??
+                .parse()
+                .ok()
+        });
+    let lan = std::env::args().any(|a| a == "--lan")
+        || recipe_manager::query::get_setting(conn, http_api::LAN_SETTING)
+            .ok()
+            .flatten()
+            .is_some_and(|v| v == "true");
+    if let Some(port) = port {
+        if let Err(error) = http_api::start(path, port, lan, read_only) {
+            log::error!("failed to serve on port {port}: {error}");
+        }
+    }
+}
+
+/// Called once the database is open and upgraded, before the UI starts using it.
+#[cfg_attr(not(feature = "http-api"), expect(unused_variables))]
+fn opened(conn: &mut database::Connection, path: &Path, read_only: bool) {
+    #[cfg(feature = "http-api")]
+    start_http_api(conn, path, read_only);
+}
+
 fn main() -> Result<()> {
-    simple_logger::SimpleLogger::new()
-        .with_level(log::LevelFilter::Warn)
-        .env()
-        .init()
-        .unwrap();
-
-    let conn = database::establish_connection(data_path()?.join("data.sqlite"))?;
-    run(conn)?;
+    logging::init();
+
+    let path = data_path()?.join("data.sqlite");
+    let access = if std::env::args().any(|a| a == "--read-only") || !database::is_writable(&path) {
+        instance::Access::ReadOnly
+    } else if let Some(lock) = instance::InstanceLock::acquire()? {
+        instance::Access::ReadWrite { _lock: lock }
+    } else {
+        log::warn!("Recipe Manager is already running, opening the database read-only");
+        instance::Access::InUse
+    };
+    run(path, access)?;
     Ok(())
 }
@@ -0,0 +1,2557 @@
+use crate::compound::CompoundDefinition;
+use crate::database;
+use crate::database::models::{
+    CalendarEntry, CookJournalEntry, CookJournalEntryId, FreezerEntry, FreezerEntryId, Ingredient,
+    IngredientCaloriesEntry, IngredientCaloriesEntryId, IngredientCategory, IngredientCategoryId,
+    IngredientComponent, IngredientComponentId, IngredientCompound, IngredientId,
+    IngredientMeasurement, IngredientUsage, IngredientUsageId, Recipe, RecipeCategory,
+    RecipeCategoryId, RecipeDuration, RecipeHandle, RecipeId, RecipePhoto, RecipePhotoId,
+    RecipeStatus, RecipeStep, RecipeStepId,
+};
+use crate::recipe::{servings_scale, RecipeIngredient};
+use diesel::BoolExpressionMethods as _;
+use diesel::Connection as _;
+use diesel::ExpressionMethods as _;
+use diesel::IntoSql as _;
+use diesel::JoinOnDsl as _;
+use diesel::NullableExpressionMethods as _;
+use diesel::QueryDsl as _;
+use diesel::QueryResult;
+use diesel::RunQueryDsl as _;
+use diesel::SelectableHelper as _;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+pub fn add_category(
+    conn: &mut database::Connection,
+    new_category_name: &str,
+) -> QueryResult<RecipeCategoryId> {
+    use database::schema::recipe_categories::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(recipe_categories)
+        .values(name.eq(new_category_name))
+        .execute(conn)?;
+    recipe_categories.select(id).order_by(id.desc()).first(conn)
+}
+
+/// Whether there are no recipes or recipe categories yet, like on the first run.
+pub fn is_recipe_book_empty(conn: &mut database::Connection) -> QueryResult<bool> {
+    use database::schema::{recipe_categories, recipes};
+
+    let num_categories: i64 = recipe_categories::table.count().get_result(conn)?;
+    let num_recipes: i64 = recipes::table.count().get_result(conn)?;
+    Ok(num_categories == 0 && num_recipes == 0)
+}
+
+pub fn add_ingredient_calories_entry(
+    conn: &mut database::Connection,
+    new_ingredient_id: IngredientId,
+    new_calories: f32,
+    new_quantity: f32,
+    new_quantity_units: Option<IngredientMeasurement>,
+) -> QueryResult<()> {
+    use database::schema::ingredient_calories::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(ingredient_calories)
+        .values((
+            ingredient_id.eq(new_ingredient_id),
+            calories.eq(new_calories),
+            quantity.eq(new_quantity),
+            quantity_units.eq(new_quantity_units),
+        ))
+        .execute(conn)?;
+    CALORIES_CACHE.with_borrow_mut(|c| c.remove(&new_ingredient_id));
+    Ok(())
+}
+
+pub fn delete_ingredient_calories_entry(
+    conn: &mut database::Connection,
+    delete_id: IngredientCaloriesEntryId,
+) -> QueryResult<()> {
+    use database::schema::ingredient_calories::dsl::*;
+    use diesel::delete;
+
+    delete(ingredient_calories)
+        .filter(id.eq(delete_id))
+        .execute(conn)?;
+    CALORIES_CACHE
+        .with_borrow_mut(|c| c.retain(|_, entries| entries.iter().all(|e| e.id != delete_id)));
+    Ok(())
+}
+
+pub fn delete_category(
+    conn: &mut database::Connection,
+    delete_id: RecipeCategoryId,
+) -> QueryResult<bool> {
+    use database::schema::{recipe_categories, recipes};
+    use diesel::delete;
+    use diesel::dsl::{exists, not};
+
+    let affected = delete(recipe_categories::table.filter(
+        recipe_categories::id.eq(delete_id).and(not(exists(
+            recipes::table.filter(recipes::category.eq(delete_id)),
+        ))),
+    ))
+    .execute(conn)?;
+
+    Ok(affected > 0)
+}
+
+pub fn delete_ingredient(
+    conn: &mut database::Connection,
+    delete_id: IngredientId,
+) -> QueryResult<bool> {
+    use database::schema::{ingredient_components, ingredient_usages, ingredients};
+    use diesel::delete;
+    use diesel::dsl::{exists, not};
+
+    // Calories and components go along with the ingredient.
+    let affected = delete(
+        ingredients::table.filter(
+            ingredients::id
+                .eq(delete_id)
+                .and(not(exists(
+                    ingredient_usages::table.filter(ingredient_usages::ingredient_id.eq(delete_id)),
+                )))
+                .and(not(exists(
+                    ingredient_components::table
+                        .filter(ingredient_components::component_id.eq(delete_id)),
+                ))),
+        ),
+    )
+    .execute(conn)?;
+    CALORIES_CACHE.with_borrow_mut(|c| c.remove(&delete_id));
+
+    Ok(affected > 0)
+}
+
+pub fn edit_category(
+    conn: &mut database::Connection,
+    id_to_edit: RecipeCategoryId,
+    new_name: &str,
+) -> QueryResult<()> {
+    use database::schema::recipe_categories::dsl::*;
+    use diesel::update;
+
+    update(recipe_categories.filter(id.eq(id_to_edit)))
+        .set(name.eq(new_name))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_category_default_duration(
+    conn: &mut database::Connection,
+    id_to_edit: RecipeCategoryId,
+    new_default_duration: Option<RecipeDuration>,
+) -> QueryResult<()> {
+    use database::schema::recipe_categories::dsl::*;
+    use diesel::update;
+
+    update(recipe_categories.filter(id.eq(id_to_edit)))
+        .set(default_duration.eq(new_default_duration))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn delete_recipe(conn: &mut database::Connection, delete_id: RecipeId) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::delete;
+
+    // Its ingredients, steps, and calendar entries go along with it.
+    delete(recipes.filter(id.eq(delete_id))).execute(conn)?;
+    Ok(())
+}
+
+pub fn add_recipe(
+    conn: &mut database::Connection,
+    new_name: &str,
+    new_category: RecipeCategoryId,
+) -> QueryResult<RecipeId> {
+    use database::schema::recipe_categories;
+    use database::schema::recipes::dsl::*;
+    use diesel::insert_into;
+    use diesel::OptionalExtension as _;
+
+    // It takes what its category's recipes usually do.
+    let new_duration = recipe_categories::table
+        .find(new_category)
+        .select(recipe_categories::default_duration)
+        .first::<Option<RecipeDuration>>(conn)
+        .optional()?
+        .flatten()
+        .unwrap_or(RecipeDuration::Short);
+    insert_into(recipes)
+        .values((
+            name.eq(new_name),
+            description.eq(""),
+            duration.eq(new_duration),
+            category.eq(new_category),
+        ))
+        .execute(conn)?;
+    recipes.select(id).order_by(id.desc()).first(conn)
+}
+
+pub fn delete_recipe_ingredient(
+    conn: &mut database::Connection,
+    usage_id: IngredientUsageId,
+) -> QueryResult<()> {
+    use database::schema::ingredient_usages::dsl::*;
+    use diesel::delete;
+
+    delete(ingredient_usages)
+        .filter(id.eq(usage_id))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn restore_recipe_ingredient(
+    conn: &mut database::Connection,
+    usage: &IngredientUsage,
+) -> QueryResult<()> {
+    use database::schema::ingredient_usages::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(ingredient_usages)
+        .values(usage.clone())
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn add_recipe_ingredient(
+    conn: &mut database::Connection,
+    new_recipe_id: RecipeId,
+    new_ingredient_id: IngredientId,
+    new_quantity: f32,
+) -> QueryResult<IngredientUsageId> {
+    use database::schema::ingredient_usages::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(ingredient_usages)
+        .values((
+            recipe_id.eq(new_recipe_id),
+            ingredient_id.eq(new_ingredient_id),
+            quantity.eq(new_quantity),
+        ))
+        .execute(conn)?;
+    ingredient_usages.select(id).order_by(id.desc()).first(conn)
+}
+
+#[cfg(any(feature = "json", feature = "legacy-import"))]
+pub fn add_recipe_ingredient_with_units(
+    conn: &mut database::Connection,
+    new_recipe_id: RecipeId,
+    new_ingredient_id: IngredientId,
+    new_quantity: f32,
+    new_quantity_units: Option<IngredientMeasurement>,
+    new_note: &str,
+) -> QueryResult<()> {
+    use database::schema::ingredient_usages::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(ingredient_usages)
+        .values((
+            recipe_id.eq(new_recipe_id),
+            ingredient_id.eq(new_ingredient_id),
+            quantity.eq(new_quantity),
+            quantity_units.eq(new_quantity_units),
+            note.eq(new_note.trim()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_ingredient(
+    conn: &mut database::Connection,
+    usage_id: IngredientUsageId,
+    new_ingredient: &Ingredient,
+    new_quantity: f32,
+    new_quantity_units: Option<IngredientMeasurement>,
+    new_note: &str,
+) -> QueryResult<()> {
+    use database::schema::ingredient_usages::dsl::*;
+    use diesel::update;
+
+    update(ingredient_usages)
+        .filter(id.eq(usage_id))
+        .set((
+            ingredient_id.eq(new_ingredient.id),
+            quantity.eq(new_quantity),
+            quantity_units.eq(new_quantity_units),
+            note.eq(new_note.trim()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_duration(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_duration: RecipeDuration,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(duration.eq(new_duration))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_rating(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_rating: Option<i32>,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(rating.eq(new_rating))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_servings(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_servings: Option<i32>,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(servings.eq(new_servings))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_archived(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_archived: bool,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(archived.eq(new_archived))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_status(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_status: RecipeStatus,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(status.eq(new_status))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// The recipes waiting to be tried which aren't archived, the ones added longest ago first.
+pub fn get_want_to_try_recipes(conn: &mut database::Connection) -> QueryResult<Vec<RecipeHandle>> {
+    use database::schema::recipes::dsl::*;
+
+    recipes
+        .select(RecipeHandle::as_select())
+        .filter(status.eq(RecipeStatus::WantToTry))
+        .filter(archived.eq(false))
+        .order_by(id.asc())
+        .load(conn)
+}
+
+pub fn edit_recipe_category(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_category_id: RecipeCategoryId,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(category.eq(new_category_id))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_description(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_description: &str,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(description.eq(new_description))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn edit_recipe_name(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    new_name: &str,
+) -> QueryResult<()> {
+    use database::schema::recipes::dsl::*;
+    use diesel::update;
+
+    update(recipes)
+        .filter(id.eq(recipe_id))
+        .set(name.eq(new_name))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// How long after typing stops before a search which needs the database is run.
+pub const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+thread_local! {
+    /// Whether a search was put off until typing stops, so another frame is needed to run it.
+    static SEARCH_PENDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether a search was put off since the last call, in which case the UI should be repainted
+/// after `SEARCH_DEBOUNCE`.
+pub fn take_search_pending() -> bool {
+    SEARCH_PENDING.take()
+}
+
+pub struct CachedQuery<IdT> {
+    query: String,
+    pub results: Vec<(IdT, String)>,
+    /// A query which couldn't be answered from the results, and when it was first asked for.
+    pending: Option<(String, Instant)>,
+}
+
+/// Whether everything matching `query` also matches `previous`, so the results for `previous`
+/// only need filtering. This mirrors `LIKE`, which ignores ASCII case.
+fn narrows(previous: &str, query: &str) -> bool {
+    !query.contains(['%', '_'])
+        && query
+            .to_ascii_lowercase()
+            .contains(&previous.to_ascii_lowercase())
+}
+
+/// Answers a `LIKE '%query%'` search from the cache when possible. Otherwise `search` is only run
+/// once the query has stayed the same for `SEARCH_DEBOUNCE`; until then the previous results are
+/// returned.
+fn cached_search<IdT: Clone>(
+    cache: &mut Option<CachedQuery<IdT>>,
+    query: &str,
+    search: impl FnOnce() -> QueryResult<Vec<(IdT, String)>>,
+) -> QueryResult<Vec<(IdT, String)>> {
+    if let Some(cached) = cache {
+        if cached.query == query {
+            cached.pending = None;
+            return Ok(cached.results.clone());
+        }
+        if narrows(&cached.query, query) {
+            let query_lower = query.to_ascii_lowercase();
+            cached
+                .results
+                .retain(|(_, text)| text.to_ascii_lowercase().contains(&query_lower));
+            cached.query = query.into();
+            cached.pending = None;
+            return Ok(cached.results.clone());
+        }
+        let asked = match &cached.pending {
+            Some((pending, asked)) if pending == query => *asked,
+            _ => cached.pending.insert((query.into(), Instant::now())).1,
+        };
+        if asked.elapsed() < SEARCH_DEBOUNCE {
+            SEARCH_PENDING.set(true);
+            return Ok(cached.results.clone());
+        }
+    }
+
+    let results = search()?;
+    *cache = Some(CachedQuery {
+        query: query.into(),
+        results: results.clone(),
+        pending: None,
+    });
+    Ok(results)
+}
+
+#[test]
+fn cached_search_test() {
+    let fruit = || {
+        Ok(vec![
+            (1, "Apple".to_owned()),
+            (2, "Grape".to_owned()),
+            (3, "Pineapple".to_owned()),
+        ])
+    };
+    let fail = || -> QueryResult<Vec<(i32, String)>> { panic!("searched the database") };
+    let names = |r: QueryResult<Vec<(i32, String)>>| -> Vec<String> {
+        r.unwrap().into_iter().map(|(_, n)| n).collect()
+    };
+
+    let mut cache = None;
+    assert_eq!(names(cached_search(&mut cache, "ap", fruit)).len(), 3);
+    assert_eq!(names(cached_search(&mut cache, "ap", fail)).len(), 3);
+
+    // Typing more narrows the results without searching again
+    assert_eq!(
+        names(cached_search(&mut cache, "APPL", fail)),
+        ["Apple", "Pineapple"]
+    );
+
+    // Anything else waits until typing stops
+    assert_eq!(names(cached_search(&mut cache, "gr", fail)).len(), 2);
+    assert!(take_search_pending());
+    std::thread::sleep(SEARCH_DEBOUNCE);
+    assert_eq!(names(cached_search(&mut cache, "gr", fruit)).len(), 3);
+    assert!(!take_search_pending());
+
+    // LIKE wildcards can't be matched by filtering
+    assert!(!narrows("gr", "gr%e"));
+}
+
+pub fn search_ingredients(
+    conn: &mut database::Connection,
+    cached_ingredient_search: &mut Option<CachedQuery<Ingredient>>,
+    query: &str,
+) -> QueryResult<Vec<(Ingredient, String)>> {
+    cached_search(cached_ingredient_search, query, || {
+        use database::schema::ingredients::dsl::*;
+        use diesel::expression_methods::TextExpressionMethods as _;
+
+        Ok(ingredients
+            .select(Ingredient::as_select())
+            .filter(name.like(format!("%{query}%")))
+            .order_by(name.asc())
+            .load(conn)?
+            .into_iter()
+            .map(|i| (i.clone(), i.name))
+            .collect())
+    })
+}
+
+pub fn get_calendar_week(
+    conn: &mut database::Connection,
+    start: chrono::NaiveWeek,
+) -> QueryResult<HashMap<chrono::Weekday, RecipeHandle>> {
+    use chrono::Datelike as _;
+    use database::schema::calendar::dsl::*;
+    use diesel::BoolExpressionMethods as _;
+
+    Ok(calendar
+        .inner_join(database::schema::recipes::table)
+        .select((day, RecipeHandle::as_select()))
+        .filter(day.ge(start.first_day()).and(day.le(start.last_day())))
+        .load(conn)?
+        .into_iter()
+        .map(|(d, r): (chrono::NaiveDate, RecipeHandle)| (d.weekday(), r))
+        .collect())
+}
+
+pub fn delete_calendar_entry(
+    conn: &mut database::Connection,
+    delete_day: chrono::NaiveDate,
+) -> QueryResult<()> {
+    use database::schema::calendar::dsl::*;
+    use diesel::delete;
+
+    delete(calendar.filter(day.eq(delete_day))).execute(conn)?;
+    Ok(())
+}
+
+pub fn restore_calendar_entry(
+    conn: &mut database::Connection,
+    entry: &CalendarEntry,
+) -> QueryResult<()> {
+    use database::schema::calendar::dsl::*;
+    use diesel::replace_into;
+
+    replace_into(calendar).values(entry.clone()).execute(conn)?;
+    Ok(())
+}
+
+/// Every day on the calendar, past and future.
+pub fn get_all_calendar_entries(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<CalendarEntry>> {
+    use database::schema::calendar::dsl::*;
+
+    calendar
+        .select(CalendarEntry::as_select())
+        .order_by(day.asc())
+        .load(conn)
+}
+
+pub fn insert_or_update_calendar_entry(
+    conn: &mut database::Connection,
+    edit_date: chrono::NaiveDate,
+    edit_recipe_id: RecipeId,
+) -> QueryResult<()> {
+    use database::schema::calendar::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(calendar)
+        .values((day.eq(edit_date), recipe_id.eq(edit_recipe_id)))
+        .on_conflict(day)
+        .do_update()
+        .set((
+            recipe_id.eq(edit_recipe_id),
+            cooked.eq(false),
+            from_freezer.eq(false),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn get_calendar_week_cooked(
+    conn: &mut database::Connection,
+    start: chrono::NaiveWeek,
+) -> QueryResult<HashSet<chrono::Weekday>> {
+    use chrono::Datelike as _;
+    use database::schema::calendar::dsl::*;
+    use diesel::BoolExpressionMethods as _;
+
+    Ok(calendar
+        .select(day)
+        .filter(day.ge(start.first_day()).and(day.le(start.last_day())))
+        .filter(cooked.eq(true))
+        .load(conn)?
+        .into_iter()
+        .map(|d: chrono::NaiveDate| d.weekday())
+        .collect())
+}
+
+/// How many people are being fed on the days of the week which have been given one.
+pub fn get_calendar_week_servings(
+    conn: &mut database::Connection,
+    start: chrono::NaiveWeek,
+) -> QueryResult<HashMap<chrono::Weekday, i32>> {
+    use chrono::Datelike as _;
+    use database::schema::calendar::dsl::*;
+    use diesel::BoolExpressionMethods as _;
+
+    Ok(calendar
+        .select((day, servings.assume_not_null()))
+        .filter(day.ge(start.first_day()).and(day.le(start.last_day())))
+        .filter(servings.is_not_null())
+        .load(conn)?
+        .into_iter()
+        .map(|(d, s): (chrono::NaiveDate, i32)| (d.weekday(), s))
+        .collect())
+}
+
+pub fn set_calendar_entry_servings(
+    conn: &mut database::Connection,
+    edit_day: chrono::NaiveDate,
+    new_servings: Option<i32>,
+) -> QueryResult<()> {
+    use database::schema::calendar::dsl::*;
+    use diesel::update;
+
+    update(calendar.filter(day.eq(edit_day)))
+        .set(servings.eq(new_servings))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// The days of the week whose recipe comes out of the freezer.
+pub fn get_calendar_week_from_freezer(
+    conn: &mut database::Connection,
+    start: chrono::NaiveWeek,
+) -> QueryResult<HashSet<chrono::Weekday>> {
+    use chrono::Datelike as _;
+    use database::schema::calendar::dsl::*;
+    use diesel::BoolExpressionMethods as _;
+
+    Ok(calendar
+        .select(day)
+        .filter(day.ge(start.first_day()).and(day.le(start.last_day())))
+        .filter(from_freezer.eq(true))
+        .load(conn)?
+        .into_iter()
+        .map(|d: chrono::NaiveDate| d.weekday())
+        .collect())
+}
+
+pub fn set_calendar_entry_cooked(
+    conn: &mut database::Connection,
+    edit_day: chrono::NaiveDate,
+    new_cooked: bool,
+) -> QueryResult<()> {
+    use database::schema::{calendar, recipes};
+    use diesel::update;
+
+    update(calendar::table.filter(calendar::day.eq(edit_day)))
+        .set(calendar::cooked.eq(new_cooked))
+        .execute(conn)?;
+
+    // Once it's been cooked it has been tried.
+    if new_cooked {
+        let cooked_recipes = calendar::table
+            .filter(calendar::day.eq(edit_day))
+            .select(calendar::recipe_id);
+        update(recipes::table.filter(recipes::id.eq_any(cooked_recipes)))
+            .set(recipes::status.eq(RecipeStatus::Tried))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// The ingredients which have been checked off the shopping list for the week.
+pub fn get_checked_shopping_list_items(
+    conn: &mut database::Connection,
+    list_week: chrono::NaiveWeek,
+) -> QueryResult<HashSet<IngredientId>> {
+    use database::schema::shopping_list_items::dsl::*;
+
+    Ok(shopping_list_items
+        .filter(week.eq(list_week.first_day()).and(checked.eq(true)))
+        .select(ingredient_id)
+        .load(conn)?
+        .into_iter()
+        .collect())
+}
+
+pub fn set_shopping_list_item_checked(
+    conn: &mut database::Connection,
+    list_week: chrono::NaiveWeek,
+    item_ingredient_id: IngredientId,
+    new_checked: bool,
+) -> QueryResult<()> {
+    use database::schema::shopping_list_items::dsl::*;
+    use diesel::replace_into;
+
+    replace_into(shopping_list_items)
+        .values((
+            week.eq(list_week.first_day()),
+            ingredient_id.eq(item_ingredient_id),
+            checked.eq(new_checked),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn get_calendar_history(
+    conn: &mut database::Connection,
+    end: chrono::NaiveDate,
+) -> QueryResult<Vec<CalendarEntry>> {
+    use database::schema::calendar::dsl::*;
+
+    calendar
+        .select(CalendarEntry::as_select())
+        .filter(day.le(end))
+        .order_by(day.asc())
+        .load(conn)
+}
+
+/// The steps of every multi-day recipe scheduled between the given days, along with the day
+/// each recipe is scheduled on.
+pub fn get_calendar_recipe_steps(
+    conn: &mut database::Connection,
+    first: chrono::NaiveDate,
+    last: chrono::NaiveDate,
+) -> QueryResult<Vec<(chrono::NaiveDate, RecipeHandle, RecipeStep)>> {
+    use database::schema::{calendar, recipe_steps, recipes};
+
+    calendar::table
+        .inner_join(recipes::table.inner_join(recipe_steps::table))
+        .select((
+            calendar::day,
+            RecipeHandle::as_select(),
+            RecipeStep::as_select(),
+        ))
+        .filter(calendar::day.ge(first).and(calendar::day.le(last)))
+        .order_by((calendar::day.asc(), recipe_steps::day.asc()))
+        .load(conn)
+}
+
+/// The longest any recipe's steps span, in days.
+pub fn get_longest_recipe_steps(conn: &mut database::Connection) -> QueryResult<i32> {
+    use database::schema::recipe_steps;
+
+    Ok(recipe_steps::table
+        .select(diesel::dsl::max(recipe_steps::day))
+        .first::<Option<i32>>(conn)?
+        .unwrap_or(0))
+}
+
+/// The recipes with `query` in their name. Archived ones are left out unless `include_archived`,
+/// which has to stay the same for the cached results to be used.
+pub fn search_recipes(
+    conn: &mut database::Connection,
+    cached_recipe_search: &mut Option<CachedQuery<RecipeId>>,
+    query: &str,
+    include_archived: bool,
+) -> QueryResult<Vec<(RecipeId, String)>> {
+    cached_search(cached_recipe_search, query, || {
+        use database::schema::recipes::dsl::*;
+        use diesel::expression_methods::TextExpressionMethods as _;
+
+        Ok(recipes
+            .select(RecipeHandle::as_select())
+            .filter(name.like(format!("%{query}%")))
+            .filter(
+                archived
+                    .eq(false)
+                    .or(include_archived.into_sql::<diesel::sql_types::Bool>()),
+            )
+            .order_by(name.asc())
+            .load(conn)?
+            .into_iter()
+            .map(|i| (i.id, i.name))
+            .collect())
+    })
+}
+
+#[cfg(any(feature = "json", feature = "legacy-import"))]
+pub fn get_ingredient_by_name(
+    conn: &mut database::Connection,
+    ingredient_name: &str,
+) -> QueryResult<Option<Ingredient>> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::OptionalExtension as _;
+
+    ingredients
+        .select(Ingredient::as_select())
+        .filter(name.eq(ingredient_name))
+        .first(conn)
+        .optional()
+}
+
+pub fn add_ingredient(conn: &mut database::Connection, new_name: &str) -> QueryResult<Ingredient> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(ingredients)
+        .values(name.eq(new_name))
+        .execute(conn)?;
+    ingredients
+        .select(Ingredient::as_select())
+        .order_by(id.desc())
+        .first(conn)
+}
+
+pub fn search_ingredient_categories(
+    conn: &mut database::Connection,
+    cached_category_search: &mut Option<CachedQuery<IngredientCategoryId>>,
+    query: &str,
+) -> QueryResult<Vec<(IngredientCategoryId, String)>> {
+    cached_search(cached_category_search, query, || {
+        use database::schema::ingredient_categories::dsl::*;
+        use diesel::expression_methods::TextExpressionMethods as _;
+
+        Ok(ingredient_categories
+            .select(IngredientCategory::as_select())
+            .filter(name.like(format!("%{query}%")))
+            .order_by(name.asc())
+            .load(conn)?
+            .into_iter()
+            .map(|c| (c.id, c.name))
+            .collect())
+    })
+}
+
+pub fn update_ingredient(
+    conn: &mut database::Connection,
+    edit_id: IngredientId,
+    edit_name: &str,
+    edit_category: &str,
+) -> QueryResult<()> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::update;
+
+    let edit_category = find_or_add_ingredient_category(conn, edit_category)?;
+    update(ingredients)
+        .filter(id.eq(edit_id))
+        .set((name.eq(edit_name), category_id.eq(edit_category)))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn get_ingredient_categories(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<IngredientCategory>> {
+    use database::schema::ingredient_categories::dsl::*;
+
+    ingredient_categories
+        .select(IngredientCategory::as_select())
+        .order_by(name.asc())
+        .load(conn)
+}
+
+pub fn get_ingredient_category_names(
+    conn: &mut database::Connection,
+) -> QueryResult<HashMap<IngredientCategoryId, String>> {
+    Ok(get_ingredient_categories(conn)?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect())
+}
+
+/// How many ingredients are in each category. Categories without any are left out.
+pub fn get_ingredient_category_counts(
+    conn: &mut database::Connection,
+) -> QueryResult<HashMap<IngredientCategoryId, usize>> {
+    use database::schema::ingredients::dsl::*;
+
+    let mut counts = HashMap::new();
+    for c in ingredients
+        .select(category_id)
+        .load::<Option<IngredientCategoryId>>(conn)?
+        .into_iter()
+        .flatten()
+    {
+        *counts.entry(c).or_default() += 1;
+    }
+    Ok(counts)
+}
+
+/// Finds the category with the given name, ignoring case and surrounding whitespace, or adds it if
+/// there isn't one. An empty name means no category.
+pub fn find_or_add_ingredient_category(
+    conn: &mut database::Connection,
+    category_name: &str,
+) -> QueryResult<Option<IngredientCategoryId>> {
+    use database::schema::ingredient_categories::dsl::*;
+    use diesel::insert_into;
+
+    let category_name = category_name.trim();
+    if category_name.is_empty() {
+        return Ok(None);
+    }
+    let existing = get_ingredient_categories(conn)?
+        .into_iter()
+        .find(|c| c.name.to_lowercase() == category_name.to_lowercase());
+    if let Some(existing) = existing {
+        return Ok(Some(existing.id));
+    }
+
+    insert_into(ingredient_categories)
+        .values(name.eq(category_name))
+        .execute(conn)?;
+    Ok(Some(
+        ingredient_categories
+            .select(id)
+            .filter(name.eq(category_name))
+            .get_result(conn)?,
+    ))
+}
+
+pub fn rename_ingredient_category(
+    conn: &mut database::Connection,
+    edit_id: IngredientCategoryId,
+    new_name: &str,
+) -> QueryResult<()> {
+    use database::schema::ingredient_categories::dsl::*;
+    use diesel::update;
+
+    update(ingredient_categories.filter(id.eq(edit_id)))
+        .set(name.eq(new_name.trim()))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Moves all the ingredients in one category to another, and deletes the now empty category.
+pub fn merge_ingredient_categories(
+    conn: &mut database::Connection,
+    from: IngredientCategoryId,
+    into: IngredientCategoryId,
+) -> QueryResult<()> {
+    use database::schema::{ingredient_categories, ingredients};
+    use diesel::{delete, update};
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        update(ingredients::table.filter(ingredients::category_id.eq(from)))
+            .set(ingredients::category_id.eq(into))
+            .execute(conn)?;
+        delete(ingredient_categories::table.filter(ingredient_categories::id.eq(from)))
+            .execute(conn)?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+pub fn delete_ingredient_category(
+    conn: &mut database::Connection,
+    delete_id: IngredientCategoryId,
+) -> QueryResult<bool> {
+    use database::schema::{ingredient_categories, ingredients};
+    use diesel::delete;
+    use diesel::dsl::{exists, not};
+
+    let affected = delete(ingredient_categories::table.filter(
+        ingredient_categories::id.eq(delete_id).and(not(exists(
+            ingredients::table.filter(ingredients::category_id.eq(delete_id)),
+        ))),
+    ))
+    .execute(conn)?;
+
+    Ok(affected > 0)
+}
+
+pub fn set_ingredient_staple(
+    conn: &mut database::Connection,
+    edit_id: IngredientId,
+    new_staple: bool,
+) -> QueryResult<()> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::update;
+
+    update(ingredients)
+        .filter(id.eq(edit_id))
+        .set(staple.eq(new_staple))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Sets the months the ingredient is in season, see [`Ingredient::season`].
+pub fn set_ingredient_season(
+    conn: &mut database::Connection,
+    edit_id: IngredientId,
+    new_season: i32,
+) -> QueryResult<()> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::update;
+
+    update(ingredients)
+        .filter(id.eq(edit_id))
+        .set(season.eq(new_season))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Sets how many grams a milliliter of the ingredient weighs, or that it isn't known.
+pub fn set_ingredient_density(
+    conn: &mut database::Connection,
+    edit_id: IngredientId,
+    new_density: Option<f32>,
+) -> QueryResult<()> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::update;
+
+    update(ingredients)
+        .filter(id.eq(edit_id))
+        .set(density.eq(new_density))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Sets what one `new_units` of the ingredient costs, or that it isn't known.
+pub fn set_ingredient_price(
+    conn: &mut database::Connection,
+    edit_id: IngredientId,
+    new_price: Option<f32>,
+    new_units: Option<IngredientMeasurement>,
+) -> QueryResult<()> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::update;
+
+    update(ingredients)
+        .filter(id.eq(edit_id))
+        .set((price.eq(new_price), price_units.eq(new_units)))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Sets the allergens the ingredient contains and the diets it's safe for.
+pub fn set_ingredient_diet(
+    conn: &mut database::Connection,
+    edit_id: IngredientId,
+    new_diet: i32,
+) -> QueryResult<()> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::update;
+
+    update(ingredients)
+        .filter(id.eq(edit_id))
+        .set(diet.eq(new_diet))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// A recipe using ingredients which are in season.
+pub struct InSeasonRecipe {
+    pub recipe: RecipeHandle,
+    /// The names of its ingredients which are in season.
+    pub in_season: Vec<String>,
+    /// How many ingredients it has in all.
+    pub ingredients: usize,
+}
+
+impl InSeasonRecipe {
+    /// How much of the recipe is in season, from 0 to 1.
+    pub fn share(&self) -> f32 {
+        self.in_season.len() as f32 / self.ingredients as f32
+    }
+}
+
+/// The recipes using anything in season in the month, from 1 for January to 12. The ones with
+/// the most of their ingredients in season come first.
+pub fn get_in_season_recipes(
+    conn: &mut database::Connection,
+    month: u32,
+) -> QueryResult<Vec<InSeasonRecipe>> {
+    use database::schema::{ingredient_usages, ingredients, recipes};
+
+    let rows: Vec<(RecipeHandle, Ingredient)> = ingredient_usages::table
+        .inner_join(recipes::table)
+        .inner_join(ingredients::table)
+        .filter(recipes::archived.eq(false))
+        .select((RecipeHandle::as_select(), Ingredient::as_select()))
+        .load(conn)?;
+
+    let mut by_recipe: HashMap<RecipeId, InSeasonRecipe> = HashMap::new();
+    for (recipe, ingredient) in rows {
+        let entry = by_recipe
+            .entry(recipe.id)
+            .or_insert_with(|| InSeasonRecipe {
+                recipe,
+                in_season: vec![],
+                ingredients: 0,
+            });
+        entry.ingredients += 1;
+        if ingredient.in_season(month) && !entry.in_season.contains(&ingredient.name) {
+            entry.in_season.push(ingredient.name);
+        }
+    }
+
+    let mut recipes: Vec<_> = by_recipe
+        .into_values()
+        .filter(|r| !r.in_season.is_empty())
+        .collect();
+    recipes.sort_by(|a, b| {
+        b.share()
+            .total_cmp(&a.share())
+            .then_with(|| b.in_season.len().cmp(&a.in_season.len()))
+            .then_with(|| a.recipe.name.cmp(&b.recipe.name))
+    });
+    Ok(recipes)
+}
+
+/// Sets the category of all the given ingredients at once. An empty category name removes their
+/// category. Returns how many ingredients were changed.
+pub fn set_ingredients_category(
+    conn: &mut database::Connection,
+    edit_ids: &[IngredientId],
+    edit_category: &str,
+) -> QueryResult<usize> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::update;
+
+    let edit_category = find_or_add_ingredient_category(conn, edit_category)?;
+    update(ingredients)
+        .filter(id.eq_any(edit_ids))
+        .set(category_id.eq(edit_category))
+        .execute(conn)
+}
+
+pub fn search_recipes_including_any_ingredient(
+    conn: &mut database::Connection,
+    ingredient_ids: Vec<IngredientId>,
+    include_archived: bool,
+) -> QueryResult<Vec<RecipeHandle>> {
+    use database::schema::{ingredient_usages, ingredients, recipes};
+
+    recipes::table
+        .inner_join(ingredient_usages::table.on(ingredient_usages::recipe_id.eq(recipes::id)))
+        .inner_join(ingredients::table.on(ingredient_usages::ingredient_id.eq(ingredients::id)))
+        .filter(ingredients::id.eq_any(ingredient_ids))
+        .filter(
+            recipes::archived
+                .eq(false)
+                .or(include_archived.into_sql::<diesel::sql_types::Bool>()),
+        )
+        .select(RecipeHandle::as_select())
+        .distinct()
+        .order_by(recipes::name.asc())
+        .load(conn)
+}
+
+pub fn search_recipes_including_at_least_ingredients(
+    conn: &mut database::Connection,
+    ingredient_ids: Vec<IngredientId>,
+    at_least: usize,
+    include_archived: bool,
+) -> QueryResult<Vec<RecipeHandle>> {
+    use database::schema::{ingredient_usages, ingredients, recipes};
+    use diesel::dsl::count;
+
+    recipes::table
+        .inner_join(ingredient_usages::table.on(ingredient_usages::recipe_id.eq(recipes::id)))
+        .inner_join(ingredients::table.on(ingredient_usages::ingredient_id.eq(ingredients::id)))
+        .filter(ingredients::id.eq_any(ingredient_ids))
+        .filter(
+            recipes::archived
+                .eq(false)
+                .or(include_archived.into_sql::<diesel::sql_types::Bool>()),
+        )
+        .select(RecipeHandle::as_select())
+        .group_by(recipes::id)
+        .having(count(ingredient_usages::ingredient_id).ge(at_least as i64))
+        .order_by(recipes::name.asc())
+        .load(conn)
+}
+
+pub fn search_recipes_including_all_ingredient(
+    conn: &mut database::Connection,
+    ingredient_ids: Vec<IngredientId>,
+    include_archived: bool,
+) -> QueryResult<Vec<RecipeHandle>> {
+    let num_ingredients = ingredient_ids.len();
+    search_recipes_including_at_least_ingredients(
+        conn,
+        ingredient_ids,
+        num_ingredients,
+        include_archived,
+    )
+}
+
+/// The least of an ingredient a recipe has to use for a search to count it as including it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MinimumQuantity {
+    pub ingredient: IngredientId,
+    pub quantity: f32,
+    pub units: Option<IngredientMeasurement>,
+}
+
+/// The quantity in the other units, if they measure the same kind of thing.
+fn convert_quantity(
+    quantity: f32,
+    from: Option<IngredientMeasurement>,
+    to: Option<IngredientMeasurement>,
+) -> Option<f32> {
+    use crate::unit_conversion::conversion_factor;
+
+    match (from, to) {
+        (a, b) if a == b => Some(quantity),
+        (Some(a), Some(b)) => Some(quantity * conversion_factor(a, b).ok()?),
+        _ => None,
+    }
+}
+
+/// Narrows the results of an ingredient search to the recipes which still include `required` of
+/// the ingredients once those with a minimum only count when the recipe uses at least that much.
+/// Quantities which can't be converted to the minimum's units don't count towards it.
+pub fn filter_recipes_by_quantity(
+    conn: &mut database::Connection,
+    recipes: Vec<RecipeHandle>,
+    ingredient_ids: &[IngredientId],
+    minimums: &[MinimumQuantity],
+    required: usize,
+) -> QueryResult<Vec<RecipeHandle>> {
+    use database::schema::ingredient_usages;
+
+    let recipe_ids: Vec<_> = recipes.iter().map(|r| r.id).collect();
+    let usages: Vec<IngredientUsage> = ingredient_usages::table
+        .filter(ingredient_usages::recipe_id.eq_any(&recipe_ids))
+        .filter(ingredient_usages::ingredient_id.eq_any(ingredient_ids))
+        .select(IngredientUsage::as_select())
+        .load(conn)?;
+
+    let minimum = |id: IngredientId| minimums.iter().find(|m| m.ingredient == id);
+    let mut totals: HashMap<(RecipeId, IngredientId), f32> = HashMap::new();
+    for usage in usages {
+        let total = totals
+            .entry((usage.recipe_id, usage.ingredient_id))
+            .or_default();
+        if let Some(m) = minimum(usage.ingredient_id) {
+            *total +=
+                convert_quantity(usage.quantity, usage.quantity_units, m.units).unwrap_or(0.0);
+        }
+    }
+
+    Ok(recipes
+        .into_iter()
+        .filter(|recipe| {
+            let included = ingredient_ids
+                .iter()
+                .filter(|&&id| match (totals.get(&(recipe.id, id)), minimum(id)) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    // A little leeway, so rounding in the conversion doesn't leave out a recipe
+                    // using exactly the minimum.
+                    (Some(total), Some(m)) => *total >= m.quantity * 0.999,
+                })
+                .count();
+            included >= required
+        })
+        .collect())
+}
+
+pub fn get_ingredients_for_recipe(
+    conn: &mut database::Connection,
+    get_recipe_id: RecipeId,
+) -> QueryResult<Vec<(IngredientUsage, Ingredient)>> {
+    use database::schema::{ingredient_usages, ingredients};
+
+    ingredient_usages::table
+        .filter(ingredient_usages::recipe_id.eq(get_recipe_id))
+        .inner_join(ingredients::table)
+        .select((IngredientUsage::as_select(), Ingredient::as_select()))
+        .order_by(ingredients::name.asc())
+        .load(conn)
+}
+
+pub fn get_ingredient_calories(
+    conn: &mut database::Connection,
+    get_ingredient_id: IngredientId,
+) -> QueryResult<Vec<IngredientCaloriesEntry>> {
+    use database::schema::ingredient_calories;
+
+    ingredient_calories::table
+        .filter(ingredient_calories::ingredient_id.eq(get_ingredient_id))
+        .select(IngredientCaloriesEntry::as_select())
+        .load(conn)
+}
+
+/// The units the ingredient is most often measured in by recipes, if any use units for it.
+pub fn get_most_common_usage_units(
+    conn: &mut database::Connection,
+    get_ingredient_id: IngredientId,
+) -> QueryResult<Option<IngredientMeasurement>> {
+    use database::schema::ingredient_usages;
+    use diesel::dsl::count;
+    use diesel::OptionalExtension as _;
+
+    let units: Option<Option<IngredientMeasurement>> = ingredient_usages::table
+        .filter(ingredient_usages::ingredient_id.eq(get_ingredient_id))
+        .filter(ingredient_usages::quantity_units.is_not_null())
+        .group_by(ingredient_usages::quantity_units)
+        .select(ingredient_usages::quantity_units)
+        .order_by((
+            count(ingredient_usages::id).desc(),
+            ingredient_usages::quantity_units.asc(),
+        ))
+        .first(conn)
+        .optional()?;
+    Ok(units.flatten())
+}
+
+thread_local! {
+    /// The calorie entries of ingredients which have been looked at, since every window showing
+    /// a recipe needs them. Entries are dropped whenever the ingredient's calories change.
+    static CALORIES_CACHE: RefCell<HashMap<IngredientId, Vec<IngredientCaloriesEntry>>> =
+        RefCell::new(HashMap::new());
+
+    /// The summaries of recipes which have been read, for previews shown while hovering over a
+    /// recipe. These aren't kept up to date by the queries here, see `forget_recipe_summaries`.
+    static SUMMARY_CACHE: RefCell<HashMap<RecipeId, RecipeSummary>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Forgets everything cached, for when the database may have been changed by something else.
+pub fn clear_cache() {
+    CALORIES_CACHE.take();
+    SUMMARY_CACHE.take();
+}
+
+/// Forgets the cached recipe summaries, after something they show has been changed.
+pub fn forget_recipe_summaries() {
+    SUMMARY_CACHE.take();
+}
+
+pub fn get_ingredient_calories_many(
+    conn: &mut database::Connection,
+    get_ingredient_ids: Vec<IngredientId>,
+) -> QueryResult<Vec<IngredientCaloriesEntry>> {
+    use database::schema::ingredient_calories;
+
+    let missing: Vec<_> = CALORIES_CACHE.with_borrow(|c| {
+        get_ingredient_ids
+            .iter()
+            .filter(|id| !c.contains_key(id))
+            .copied()
+            .collect()
+    });
+    if !missing.is_empty() {
+        let loaded: Vec<IngredientCaloriesEntry> = ingredient_calories::table
+            .select(IngredientCaloriesEntry::as_select())
+            .filter(ingredient_calories::ingredient_id.eq_any(&missing))
+            .load(conn)?;
+        CALORIES_CACHE.with_borrow_mut(|c| {
+            for id in missing {
+                c.insert(id, vec![]);
+            }
+            for entry in loaded {
+                c.get_mut(&entry.ingredient_id).unwrap().push(entry);
+            }
+        });
+    }
+
+    let unique: HashSet<_> = get_ingredient_ids.into_iter().collect();
+    Ok(
+        CALORIES_CACHE
+            .with_borrow(|c| unique.iter().flat_map(|id| c[id].iter().cloned()).collect()),
+    )
+}
+
+pub fn get_recipe(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+) -> QueryResult<(Recipe, String, Vec<RecipeIngredient>)> {
+    use database::schema::{recipe_categories, recipes};
+
+    let (recipe, category) = recipes::table
+        .inner_join(recipe_categories::table)
+        .filter(recipes::id.eq(recipe_id))
+        .select((Recipe::as_select(), recipe_categories::name))
+        .get_result(conn)?;
+    let mut ingredients: Vec<_> = get_ingredients_for_recipe(conn, recipe_id)?
+        .into_iter()
+        .map(|(u, i)| RecipeIngredient::new(u, i))
+        .collect();
+    add_calories_to_ingredients(conn, &mut ingredients)?;
+
+    Ok((recipe, category, ingredients))
+}
+
+#[test]
+fn calories_cache_test() {
+    let mut conn = database::fixture::test_connection();
+    let salt = add_ingredient(&mut conn, "salt").unwrap().id;
+    assert!(get_ingredient_calories_many(&mut conn, vec![salt])
+        .unwrap()
+        .is_empty());
+
+    add_ingredient_calories_entry(&mut conn, salt, 10.0, 1.0, None).unwrap();
+    let entries = get_ingredient_calories_many(&mut conn, vec![salt, salt]).unwrap();
+    assert_eq!(entries.len(), 1);
+
+    delete_ingredient_calories_entry(&mut conn, entries[0].id).unwrap();
+    assert!(get_ingredient_calories_many(&mut conn, vec![salt])
+        .unwrap()
+        .is_empty());
+}
+
+/// One ingredient of a recipe, for updating it after it was added or edited.
+pub fn get_recipe_ingredient(
+    conn: &mut database::Connection,
+    usage_id: IngredientUsageId,
+) -> QueryResult<RecipeIngredient> {
+    use database::schema::{ingredient_usages, ingredients};
+
+    let (usage, ingredient) = ingredient_usages::table
+        .filter(ingredient_usages::id.eq(usage_id))
+        .inner_join(ingredients::table)
+        .select((IngredientUsage::as_select(), Ingredient::as_select()))
+        .get_result(conn)?;
+    let mut ingredients = [RecipeIngredient::new(usage, ingredient)];
+    add_calories_to_ingredients(conn, &mut ingredients)?;
+    let [ingredient] = ingredients;
+    Ok(ingredient)
+}
+
+fn add_calories_to_ingredients(
+    conn: &mut database::Connection,
+    ingredients: &mut [RecipeIngredient],
+) -> QueryResult<()> {
+    let mut index_map = HashMap::<IngredientId, Vec<usize>>::new();
+    for (i, u) in ingredients.iter().enumerate() {
+        index_map.entry(u.ingredient.id).or_default().push(i);
+    }
+    for entry in
+        get_ingredient_calories_many(conn, ingredients.iter().map(|u| u.ingredient.id).collect())?
+    {
+        for index in &index_map[&entry.ingredient_id] {
+            ingredients[*index].calories.push(entry.clone());
+        }
+    }
+    Ok(())
+}
+
+pub fn get_calendar_week_ingredients(
+    conn: &mut database::Connection,
+    start: chrono::NaiveWeek,
+) -> QueryResult<Vec<(chrono::Weekday, RecipeIngredient)>> {
+    use chrono::Datelike as _;
+
+    Ok(
+        get_calendar_ingredients(conn, start.first_day(), start.last_day())?
+            .into_iter()
+            .map(|(d, i)| (d.weekday(), i))
+            .collect(),
+    )
+}
+
+/// The ingredients of every recipe scheduled between the given days.
+pub fn get_calendar_ingredients(
+    conn: &mut database::Connection,
+    first: chrono::NaiveDate,
+    last: chrono::NaiveDate,
+) -> QueryResult<Vec<(chrono::NaiveDate, RecipeIngredient)>> {
+    use database::schema::{calendar, ingredient_usages, ingredients};
+
+    let rows: Vec<(chrono::NaiveDate, IngredientUsage, Ingredient)> = calendar::table
+        .inner_join(
+            ingredient_usages::table.on(ingredient_usages::recipe_id.eq(calendar::recipe_id)),
+        )
+        .inner_join(ingredients::table.on(ingredient_usages::ingredient_id.eq(ingredients::id)))
+        .filter(calendar::day.ge(first).and(calendar::day.le(last)))
+        .select((
+            calendar::day,
+            IngredientUsage::as_select(),
+            Ingredient::as_select(),
+        ))
+        .load(conn)?;
+
+    let days: Vec<_> = rows.iter().map(|(d, _, _)| *d).collect();
+    let mut ingredients: Vec<_> = rows
+        .into_iter()
+        .map(|(_, u, i)| RecipeIngredient::new(u, i))
+        .collect();
+    add_calories_to_ingredients(conn, &mut ingredients)?;
+
+    Ok(days.into_iter().zip(ingredients).collect())
+}
+
+/// The ingredients to shop for the recipes scheduled in the week, with each recipe's quantities
+/// scaled by how many people are being fed that day. Recipes coming out of the freezer are left
+/// out.
+pub fn get_calendar_week_shopping_ingredients(
+    conn: &mut database::Connection,
+    start: chrono::NaiveWeek,
+) -> QueryResult<Vec<(IngredientUsage, Ingredient)>> {
+    use database::schema::{calendar, ingredient_usages, ingredients, recipes};
+
+    let rows: Vec<(Option<i32>, Option<i32>, IngredientUsage, Ingredient)> = calendar::table
+        .inner_join(recipes::table)
+        .inner_join(
+            ingredient_usages::table.on(ingredient_usages::recipe_id.eq(calendar::recipe_id)),
+        )
+        .inner_join(ingredients::table.on(ingredient_usages::ingredient_id.eq(ingredients::id)))
+        .filter(
+            calendar::day
+                .ge(start.first_day())
+                .and(calendar::day.le(start.last_day())),
+        )
+        .filter(calendar::from_freezer.eq(false))
+        .select((
+            calendar::servings,
+            recipes::servings,
+            IngredientUsage::as_select(),
+            Ingredient::as_select(),
+        ))
+        .order_by((calendar::day.asc(), ingredients::name.asc()))
+        .load(conn)?;
+    Ok(rows
+        .into_iter()
+        .map(|(scheduled, recipe, mut usage, ingredient)| {
+            usage.quantity *= servings_scale(scheduled, recipe);
+            (usage, ingredient)
+        })
+        .collect())
+}
+
+/// Every ingredient of every recipe, along with the recipe it is used in.
+pub fn get_all_recipe_ingredients(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<(RecipeHandle, RecipeIngredient)>> {
+    use database::schema::{ingredient_usages, ingredients, recipes};
+
+    let rows: Vec<(RecipeHandle, IngredientUsage, Ingredient)> = ingredient_usages::table
+        .inner_join(recipes::table)
+        .inner_join(ingredients::table)
+        .select((
+            RecipeHandle::as_select(),
+            IngredientUsage::as_select(),
+            Ingredient::as_select(),
+        ))
+        .load(conn)?;
+
+    let recipes: Vec<_> = rows.iter().map(|(r, _, _)| r.clone()).collect();
+    let mut ingredients: Vec<_> = rows
+        .into_iter()
+        .map(|(_, u, i)| RecipeIngredient::new(u, i))
+        .collect();
+    add_calories_to_ingredients(conn, &mut ingredients)?;
+
+    Ok(recipes.into_iter().zip(ingredients).collect())
+}
+
+/// How many people each recipe feeds, for the recipes which say.
+pub fn get_all_recipe_servings(
+    conn: &mut database::Connection,
+) -> QueryResult<HashMap<RecipeId, i32>> {
+    use database::schema::recipes::dsl::*;
+
+    Ok(recipes
+        .select((id, servings.assume_not_null()))
+        .filter(servings.is_not_null())
+        .load(conn)?
+        .into_iter()
+        .collect())
+}
+
+pub fn search_recipe_categories(
+    conn: &mut database::Connection,
+    cached_category_search: &mut Option<CachedQuery<RecipeCategoryId>>,
+    query: &str,
+) -> QueryResult<Vec<(RecipeCategoryId, String)>> {
+    cached_search(cached_category_search, query, || {
+        use database::schema::recipe_categories::dsl::*;
+        use diesel::expression_methods::TextExpressionMethods as _;
+
+        Ok(recipe_categories
+            .select(RecipeCategory::as_select())
+            .filter(name.like(format!("%{query}%")))
+            .order_by(name.asc())
+            .load(conn)?
+            .into_iter()
+            .map(|c| (c.id, c.name))
+            .collect())
+    })
+}
+
+/// The recipe usages and blend components which refer to the given ingredient.
+pub fn get_ingredient_references(
+    conn: &mut database::Connection,
+    ingredient: IngredientId,
+) -> QueryResult<(Vec<IngredientUsageId>, Vec<IngredientComponentId>)> {
+    use database::schema::{ingredient_components, ingredient_usages};
+
+    let usages = ingredient_usages::table
+        .select(ingredient_usages::id)
+        .filter(ingredient_usages::ingredient_id.eq(ingredient))
+        .load(conn)?;
+    let components = ingredient_components::table
+        .select(ingredient_components::id)
+        .filter(ingredient_components::component_id.eq(ingredient))
+        .load(conn)?;
+    Ok((usages, components))
+}
+
+/// Points the given recipe usages and blend components back at an ingredient, undoing
+/// `replace_ingredient`.
+pub fn restore_ingredient_references(
+    conn: &mut database::Connection,
+    ingredient: IngredientId,
+    usages: &[IngredientUsageId],
+    components: &[IngredientComponentId],
+) -> QueryResult<()> {
+    use database::schema::{ingredient_components, ingredient_usages};
+    use diesel::update;
+
+    update(ingredient_components::table.filter(ingredient_components::id.eq_any(components)))
+        .set(ingredient_components::component_id.eq(ingredient))
+        .execute(conn)?;
+
+    update(ingredient_usages::table.filter(ingredient_usages::id.eq_any(usages)))
+        .set(ingredient_usages::ingredient_id.eq(ingredient))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn replace_ingredient(
+    conn: &mut database::Connection,
+    remove: IngredientId,
+    fill: IngredientId,
+) -> QueryResult<usize> {
+    use database::schema::{ingredient_components, ingredient_usages};
+    use diesel::update;
+
+    update(ingredient_components::table.filter(ingredient_components::component_id.eq(remove)))
+        .set(ingredient_components::component_id.eq(fill))
+        .execute(conn)?;
+
+    update(ingredient_usages::table.filter(ingredient_usages::ingredient_id.eq(remove)))
+        .set(ingredient_usages::ingredient_id.eq(fill))
+        .execute(conn)
+}
+
+/// Every category ordered by name, with how many recipes are in it.
+pub fn get_recipe_categories(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<(RecipeCategory, usize)>> {
+    use database::schema::{recipe_categories, recipes};
+    use diesel::dsl::count;
+
+    let categories: Vec<(RecipeCategory, i64)> = recipe_categories::table
+        .left_join(recipes::table)
+        .group_by(recipe_categories::id)
+        .select((RecipeCategory::as_select(), count(recipes::id.nullable())))
+        .order_by(recipe_categories::name.asc())
+        .load(conn)?;
+    Ok(categories
+        .into_iter()
+        .map(|(category, count)| (category, count as usize))
+        .collect())
+}
+
+/// The last day a recipe from each category is on the calendar, for the categories which have
+/// ever been.
+pub fn get_recipe_categories_last_used(
+    conn: &mut database::Connection,
+) -> QueryResult<HashMap<RecipeCategoryId, chrono::NaiveDate>> {
+    use database::schema::{calendar, recipes};
+
+    let last_used: Vec<(RecipeCategoryId, Option<chrono::NaiveDate>)> = calendar::table
+        .inner_join(recipes::table)
+        .group_by(recipes::category)
+        .select((recipes::category, diesel::dsl::max(calendar::day)))
+        .load(conn)?;
+    Ok(last_used
+        .into_iter()
+        .filter_map(|(category, day)| Some((category, day?)))
+        .collect())
+}
+
+pub fn count_recipes(
+    conn: &mut database::Connection,
+    category_id: RecipeCategoryId,
+) -> QueryResult<usize> {
+    use database::schema::recipes::dsl::*;
+    let count: i64 = recipes
+        .filter(category.eq(category_id))
+        .filter(archived.eq(false))
+        .count()
+        .get_result(conn)?;
+    Ok(count as usize)
+}
+
+/// How big the recipe book is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecipeBookTotals {
+    pub recipes: usize,
+    pub ingredients: usize,
+    pub categories: usize,
+}
+
+pub fn get_recipe_book_totals(conn: &mut database::Connection) -> QueryResult<RecipeBookTotals> {
+    use database::schema::{ingredients, recipe_categories, recipes};
+
+    let recipes: i64 = recipes::table.count().get_result(conn)?;
+    let ingredients: i64 = ingredients::table.count().get_result(conn)?;
+    let categories: i64 = recipe_categories::table.count().get_result(conn)?;
+    Ok(RecipeBookTotals {
+        recipes: recipes as usize,
+        ingredients: ingredients as usize,
+        categories: categories as usize,
+    })
+}
+
+/// The names of the ingredients used by the most recipes, with how many use them, most first.
+pub fn get_most_used_ingredients(
+    conn: &mut database::Connection,
+    limit: i64,
+) -> QueryResult<Vec<(String, usize)>> {
+    use database::schema::{ingredient_usages, ingredients};
+    use diesel::dsl::count_distinct;
+
+    let used: Vec<(String, i64)> = ingredient_usages::table
+        .inner_join(ingredients::table)
+        .group_by(ingredients::id)
+        .select((
+            ingredients::name,
+            count_distinct(ingredient_usages::recipe_id),
+        ))
+        .order_by((
+            count_distinct(ingredient_usages::recipe_id).desc(),
+            ingredients::name.asc(),
+        ))
+        .limit(limit)
+        .load(conn)?;
+    Ok(used
+        .into_iter()
+        .map(|(name, count)| (name, count as usize))
+        .collect())
+}
+
+pub fn count_ingredients(conn: &mut database::Connection, query: &str) -> QueryResult<usize> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::expression_methods::TextExpressionMethods as _;
+
+    let count: i64 = ingredients
+        .filter(name.like(format!("%{query}%")))
+        .count()
+        .get_result(conn)?;
+    Ok(count as usize)
+}
+
+/// The ingredients with `query` in their name ordered by name, starting from `offset`.
+pub fn get_ingredients_page(
+    conn: &mut database::Connection,
+    query: &str,
+    offset: i64,
+    limit: i64,
+) -> QueryResult<Vec<Ingredient>> {
+    use database::schema::ingredients::dsl::*;
+    use diesel::expression_methods::TextExpressionMethods as _;
+
+    ingredients
+        .select(Ingredient::as_select())
+        .filter(name.like(format!("%{query}%")))
+        .order_by((name.asc(), id.asc()))
+        .offset(offset)
+        .limit(limit)
+        .load(conn)
+}
+
+/// The recipes in the category which aren't archived ordered by name, starting from `offset`.
+pub fn get_recipes_page(
+    conn: &mut database::Connection,
+    category_id: RecipeCategoryId,
+    offset: i64,
+    limit: i64,
+) -> QueryResult<Vec<RecipeHandle>> {
+    use database::schema::recipes::dsl::*;
+    recipes
+        .select(RecipeHandle::as_select())
+        .filter(category.eq(category_id))
+        .filter(archived.eq(false))
+        .order_by((name.asc(), id.asc()))
+        .offset(offset)
+        .limit(limit)
+        .load(conn)
+}
+
+/// The recipes in the category with `query` in their name which aren't archived, ordered by name.
+pub fn search_category_recipes(
+    conn: &mut database::Connection,
+    cached_recipe_search: &mut Option<CachedQuery<RecipeId>>,
+    category_id: RecipeCategoryId,
+    query: &str,
+) -> QueryResult<Vec<(RecipeId, String)>> {
+    cached_search(cached_recipe_search, query, || {
+        use database::schema::recipes::dsl::*;
+        use diesel::expression_methods::TextExpressionMethods as _;
+
+        Ok(recipes
+            .select(RecipeHandle::as_select())
+            .filter(category.eq(category_id))
+            .filter(name.like(format!("%{query}%")))
+            .filter(archived.eq(false))
+            .order_by((name.asc(), id.asc()))
+            .load(conn)?
+            .into_iter()
+            .map(|r| (r.id, r.name))
+            .collect())
+    })
+}
+
+/// A recipe with what the recipe list shows about it.
+#[derive(Clone)]
+pub struct RecipeSummary {
+    pub id: RecipeId,
+    pub name: String,
+    pub duration: RecipeDuration,
+    pub rating: Option<i32>,
+    /// The last day it was checked off as cooked on the calendar.
+    pub last_cooked: Option<chrono::NaiveDate>,
+    /// For working out the calories.
+    pub ingredients: Vec<RecipeIngredient>,
+}
+
+/// Every recipe in the category which isn't archived ordered by name, with their details read all
+/// at once rather than one recipe at a time.
+pub fn get_recipe_summaries(
+    conn: &mut database::Connection,
+    category_id: RecipeCategoryId,
+) -> QueryResult<Vec<RecipeSummary>> {
+    use database::schema::{calendar, ingredient_usages, ingredients, recipes};
+
+    type Row = (
+        RecipeId,
+        String,
+        RecipeDuration,
+        Option<i32>,
+        Option<chrono::NaiveDate>,
+    );
+    let rows: Vec<Row> = recipes::table
+        .left_join(
+            calendar::table.on(calendar::recipe_id
+                .eq(recipes::id)
+                .and(calendar::cooked.eq(true))),
+        )
+        .filter(recipes::category.eq(category_id))
+        .filter(recipes::archived.eq(false))
+        .group_by(recipes::id)
+        .select((
+            recipes::id,
+            recipes::name,
+            recipes::duration,
+            recipes::rating,
+            diesel::dsl::max(calendar::day.nullable()),
+        ))
+        .order_by((recipes::name.asc(), recipes::id.asc()))
+        .load(conn)?;
+
+    let usages: Vec<(IngredientUsage, Ingredient)> = ingredient_usages::table
+        .inner_join(ingredients::table)
+        .inner_join(recipes::table)
+        .filter(recipes::category.eq(category_id))
+        .select((IngredientUsage::as_select(), Ingredient::as_select()))
+        .order_by(ingredients::name.asc())
+        .load(conn)?;
+    let recipe_ids: Vec<_> = usages.iter().map(|(u, _)| u.recipe_id).collect();
+    let mut ingredients: Vec<_> = usages
+        .into_iter()
+        .map(|(u, i)| RecipeIngredient::new(u, i))
+        .collect();
+    add_calories_to_ingredients(conn, &mut ingredients)?;
+    let mut ingredients_by_recipe: HashMap<RecipeId, Vec<RecipeIngredient>> = HashMap::new();
+    for (recipe_id, ingredient) in recipe_ids.into_iter().zip(ingredients) {
+        ingredients_by_recipe
+            .entry(recipe_id)
+            .or_default()
+            .push(ingredient);
+    }
+
+    let summaries: Vec<_> = rows
+        .into_iter()
+        .map(|(id, name, duration, rating, last_cooked)| RecipeSummary {
+            ingredients: ingredients_by_recipe.remove(&id).unwrap_or_default(),
+            id,
+            name,
+            duration,
+            rating,
+            last_cooked,
+        })
+        .collect();
+    SUMMARY_CACHE.with_borrow_mut(|c| c.extend(summaries.iter().map(|s| (s.id, s.clone()))));
+    Ok(summaries)
+}
+
+/// One recipe's summary, from the cache if it has been read already.
+pub fn get_recipe_summary(
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+) -> QueryResult<RecipeSummary> {
+    use database::schema::{calendar, recipes};
+
+    if let Some(summary) = SUMMARY_CACHE.with_borrow(|c| c.get(&recipe_id).cloned()) {
+        return Ok(summary);
+    }
+
+    let (name, duration, rating) = recipes::table
+        .filter(recipes::id.eq(recipe_id))
+        .select((recipes::name, recipes::duration, recipes::rating))
+        .get_result(conn)?;
+    let last_cooked = calendar::table
+        .filter(calendar::recipe_id.eq(recipe_id))
+        .filter(calendar::cooked.eq(true))
+        .select(diesel::dsl::max(calendar::day))
+        .get_result(conn)?;
+    let mut ingredients: Vec<_> = get_ingredients_for_recipe(conn, recipe_id)?
+        .into_iter()
+        .map(|(u, i)| RecipeIngredient::new(u, i))
+        .collect();
+    add_calories_to_ingredients(conn, &mut ingredients)?;
+
+    let summary = RecipeSummary {
+        id: recipe_id,
+        name,
+        duration,
+        rating,
+        last_cooked,
+        ingredients,
+    };
+    SUMMARY_CACHE.with_borrow_mut(|c| c.insert(recipe_id, summary.clone()));
+    Ok(summary)
+}
+
+pub fn get_setting(
+    conn: &mut database::Connection,
+    setting_name: &str,
+) -> QueryResult<Option<String>> {
+    use database::schema::settings::dsl::*;
+    use diesel::prelude::OptionalExtension as _;
+
+    settings
+        .select(value)
+        .filter(name.eq(setting_name))
+        .get_result(conn)
+        .optional()
+}
+
+/// The setting for the day weeks start on, "sunday" or "monday". The HTTP API goes by it as well
+/// as the calendar.
+pub const WEEK_START_SETTING: &str = "week_start";
+
+/// The day weeks start on, Sunday unless it's been set to Monday.
+pub fn get_week_start(conn: &mut database::Connection) -> QueryResult<chrono::Weekday> {
+    Ok(match get_setting(conn, WEEK_START_SETTING)?.as_deref() {
+        Some("monday") => chrono::Weekday::Mon,
+        _ => chrono::Weekday::Sun,
+    })
+}
+
+pub fn set_setting(
+    conn: &mut database::Connection,
+    setting_name: &str,
+    new_value: Option<&str>,
+) -> QueryResult<()> {
+    use database::schema::settings::dsl::*;
+    use diesel::{delete, insert_into};
+
+    if let Some(new_value) = new_value {
+        insert_into(settings)
+            .values((name.eq(setting_name), value.eq(new_value)))
+            .on_conflict(name)
+            .do_update()
+            .set(value.eq(new_value))
+            .execute(conn)?;
+    } else {
+        delete(settings.filter(name.eq(setting_name))).execute(conn)?;
+    }
+    Ok(())
+}
+
+pub fn get_ingredient_compound(
+    conn: &mut database::Connection,
+    get_ingredient_id: IngredientId,
+) -> QueryResult<Option<IngredientCompound>> {
+    use database::schema::ingredient_compounds;
+    use diesel::prelude::OptionalExtension as _;
+
+    ingredient_compounds::table
+        .filter(ingredient_compounds::ingredient_id.eq(get_ingredient_id))
+        .select(IngredientCompound::as_select())
+        .get_result(conn)
+        .optional()
+}
+
+pub fn set_ingredient_compound(
+    conn: &mut database::Connection,
+    edit_ingredient_id: IngredientId,
+    new_quantity: f32,
+    new_quantity_units: Option<IngredientMeasurement>,
+) -> QueryResult<()> {
+    use database::schema::ingredient_compounds::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(ingredient_compounds)
+        .values((
+            ingredient_id.eq(edit_ingredient_id),
+            quantity.eq(new_quantity),
+            quantity_units.eq(new_quantity_units),
+        ))
+        .on_conflict(ingredient_id)
+        .do_update()
+        .set((
+            quantity.eq(new_quantity),
+            quantity_units.eq(new_quantity_units),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn get_ingredient_components(
+    conn: &mut database::Connection,
+    get_compound_id: IngredientId,
+) -> QueryResult<Vec<(IngredientComponent, Ingredient)>> {
+    use database::schema::{ingredient_components, ingredients};
+
+    ingredient_components::table
+        .inner_join(ingredients::table)
+        .filter(ingredient_components::compound_id.eq(get_compound_id))
+        .select((IngredientComponent::as_select(), Ingredient::as_select()))
+        .order_by(ingredients::name.asc())
+        .load(conn)
+}
+
+pub fn add_ingredient_component(
+    conn: &mut database::Connection,
+    new_compound_id: IngredientId,
+    new_component_id: IngredientId,
+    new_quantity: f32,
+    new_quantity_units: Option<IngredientMeasurement>,
+) -> QueryResult<()> {
+    use database::schema::ingredient_components::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(ingredient_components)
+        .values((
+            compound_id.eq(new_compound_id),
+            component_id.eq(new_component_id),
+            quantity.eq(new_quantity),
+            quantity_units.eq(new_quantity_units),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Whether `ingredient` is a blend containing `contained`, either directly or through the blends
+/// it's made with.
+pub fn ingredient_contains(
+    conn: &mut database::Connection,
+    ingredient: IngredientId,
+    contained: IngredientId,
+) -> QueryResult<bool> {
+    use database::schema::ingredient_components;
+
+    let mut seen = HashSet::from([ingredient]);
+    let mut next = vec![ingredient];
+    while !next.is_empty() {
+        let components: Vec<IngredientId> = ingredient_components::table
+            .filter(ingredient_components::compound_id.eq_any(&next))
+            .select(ingredient_components::component_id)
+            .load(conn)?;
+        if components.contains(&contained) {
+            return Ok(true);
+        }
+        next = components.into_iter().filter(|c| seen.insert(*c)).collect();
+    }
+    Ok(false)
+}
+
+/// Deletes the component, and the compound itself once it has no components left.
+pub fn delete_ingredient_component(
+    conn: &mut database::Connection,
+    delete_id: IngredientComponentId,
+    delete_compound_id: IngredientId,
+) -> QueryResult<()> {
+    use database::schema::{ingredient_components, ingredient_compounds};
+    use diesel::delete;
+    use diesel::dsl::{exists, not};
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        delete(ingredient_components::table.filter(ingredient_components::id.eq(delete_id)))
+            .execute(conn)?;
+        delete(
+            ingredient_compounds::table.filter(
+                ingredient_compounds::ingredient_id
+                    .eq(delete_compound_id)
+                    .and(not(exists(ingredient_components::table.filter(
+                        ingredient_components::compound_id.eq(delete_compound_id),
+                    )))),
+            ),
+        )
+        .execute(conn)?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+pub fn get_compound_definitions(
+    conn: &mut database::Connection,
+    get_ingredient_ids: Vec<IngredientId>,
+) -> QueryResult<HashMap<IngredientId, CompoundDefinition>> {
+    use database::schema::{ingredient_components, ingredient_compounds, ingredients};
+
+    let mut definitions: HashMap<_, _> = ingredient_compounds::table
+        .filter(ingredient_compounds::ingredient_id.eq_any(&get_ingredient_ids))
+        .select(IngredientCompound::as_select())
+        .load(conn)?
+        .into_iter()
+        .map(|c| {
+            (
+                c.ingredient_id,
+                CompoundDefinition {
+                    compound: c,
+                    components: vec![],
+                },
+            )
+        })
+        .collect();
+
+    let components: Vec<(IngredientComponent, Ingredient)> = ingredient_components::table
+        .inner_join(ingredients::table)
+        .filter(ingredient_components::compound_id.eq_any(&get_ingredient_ids))
+        .select((IngredientComponent::as_select(), Ingredient::as_select()))
+        .load(conn)?;
+    for (component, ingredient) in components {
+        if let Some(d) = definitions.get_mut(&component.compound_id) {
+            d.components.push((component, ingredient));
+        }
+    }
+    Ok(definitions)
+}
+
+pub fn get_recipe_steps(
+    conn: &mut database::Connection,
+    get_recipe_id: RecipeId,
+) -> QueryResult<Vec<RecipeStep>> {
+    use database::schema::recipe_steps::dsl::*;
+
+    recipe_steps
+        .select(RecipeStep::as_select())
+        .filter(recipe_id.eq(get_recipe_id))
+        .order_by((day.asc(), id.asc()))
+        .load(conn)
+}
+
+pub fn add_recipe_step(
+    conn: &mut database::Connection,
+    new_recipe_id: RecipeId,
+    new_day: i32,
+    new_label: &str,
+) -> QueryResult<()> {
+    use database::schema::recipe_steps::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(recipe_steps)
+        .values((
+            recipe_id.eq(new_recipe_id),
+            day.eq(new_day),
+            label.eq(new_label),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn delete_recipe_step(
+    conn: &mut database::Connection,
+    delete_id: RecipeStepId,
+) -> QueryResult<()> {
+    use database::schema::recipe_steps::dsl::*;
+    use diesel::delete;
+
+    delete(recipe_steps.filter(id.eq(delete_id))).execute(conn)?;
+    Ok(())
+}
+
+pub fn get_recipe_photos(
+    conn: &mut database::Connection,
+    get_recipe_id: RecipeId,
+) -> QueryResult<Vec<RecipePhoto>> {
+    use database::schema::recipe_photos::dsl::*;
+
+    recipe_photos
+        .select(RecipePhoto::as_select())
+        .filter(recipe_id.eq(get_recipe_id))
+        .order_by(id.asc())
+        .load(conn)
+}
+
+pub fn add_recipe_photo(
+    conn: &mut database::Connection,
+    new_recipe_id: RecipeId,
+    new_file_name: &str,
+    new_data: &[u8],
+) -> QueryResult<()> {
+    use database::schema::recipe_photos::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(recipe_photos)
+        .values((
+            recipe_id.eq(new_recipe_id),
+            file_name.eq(new_file_name),
+            data.eq(new_data),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn delete_recipe_photo(
+    conn: &mut database::Connection,
+    delete_id: RecipePhotoId,
+) -> QueryResult<()> {
+    use database::schema::recipe_photos::dsl::*;
+    use diesel::delete;
+
+    delete(recipe_photos.filter(id.eq(delete_id))).execute(conn)?;
+    Ok(())
+}
+
+/// The recipe's journal, oldest first.
+pub fn get_cook_journal(
+    conn: &mut database::Connection,
+    get_recipe_id: RecipeId,
+) -> QueryResult<Vec<CookJournalEntry>> {
+    use database::schema::cook_journal::dsl::*;
+
+    cook_journal
+        .select(CookJournalEntry::as_select())
+        .filter(recipe_id.eq(get_recipe_id))
+        .order_by((day.asc(), id.asc()))
+        .load(conn)
+}
+
+pub fn add_cook_journal_entry(
+    conn: &mut database::Connection,
+    new_recipe_id: RecipeId,
+    new_day: chrono::NaiveDate,
+    new_rating: Option<i32>,
+    new_notes: &str,
+) -> QueryResult<()> {
+    use database::schema::cook_journal::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(cook_journal)
+        .values((
+            recipe_id.eq(new_recipe_id),
+            day.eq(new_day),
+            rating.eq(new_rating),
+            notes.eq(new_notes),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn delete_cook_journal_entry(
+    conn: &mut database::Connection,
+    delete_id: CookJournalEntryId,
+) -> QueryResult<()> {
+    use database::schema::cook_journal::dsl::*;
+    use diesel::delete;
+
+    delete(cook_journal.filter(id.eq(delete_id))).execute(conn)?;
+    Ok(())
+}
+
+/// Everything in the freezer, what was frozen longest ago first.
+pub fn get_freezer_inventory(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<(FreezerEntry, RecipeHandle)>> {
+    use database::schema::{freezer, recipes};
+
+    freezer::table
+        .inner_join(recipes::table)
+        .select((FreezerEntry::as_select(), RecipeHandle::as_select()))
+        .order_by((freezer::frozen.asc(), freezer::id.asc()))
+        .load(conn)
+}
+
+/// How many portions of each recipe there are in the freezer.
+pub fn get_freezer_stock(conn: &mut database::Connection) -> QueryResult<HashMap<RecipeId, i32>> {
+    use database::schema::freezer::dsl::*;
+
+    let stock: Vec<(RecipeId, Option<i64>)> = freezer
+        .group_by(recipe_id)
+        .select((recipe_id, diesel::dsl::sum(portions)))
+        .load(conn)?;
+    Ok(stock
+        .into_iter()
+        .map(|(r, p)| (r, p.unwrap_or(0) as i32))
+        .collect())
+}
+
+pub fn add_freezer_entry(
+    conn: &mut database::Connection,
+    new_recipe_id: RecipeId,
+    new_portions: i32,
+    new_frozen: chrono::NaiveDate,
+) -> QueryResult<()> {
+    use database::schema::freezer::dsl::*;
+    use diesel::insert_into;
+
+    insert_into(freezer)
+        .values((
+            recipe_id.eq(new_recipe_id),
+            portions.eq(new_portions),
+            frozen.eq(new_frozen),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Changes how many portions there are, taking them out of the freezer when there are none left.
+pub fn set_freezer_portions(
+    conn: &mut database::Connection,
+    edit_id: FreezerEntryId,
+    new_portions: i32,
+) -> QueryResult<()> {
+    use database::schema::freezer::dsl::*;
+    use diesel::{delete, update};
+
+    if new_portions > 0 {
+        update(freezer.filter(id.eq(edit_id)))
+            .set(portions.eq(new_portions))
+            .execute(conn)?;
+    } else {
+        delete(freezer.filter(id.eq(edit_id))).execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Takes a portion of the recipe scheduled on the day out of the freezer, the one frozen longest
+/// ago, and marks the day as coming out of the freezer. Returns false if there wasn't any.
+pub fn use_frozen_portion(
+    conn: &mut database::Connection,
+    use_day: chrono::NaiveDate,
+) -> QueryResult<bool> {
+    use database::schema::{calendar, freezer};
+    use diesel::{update, OptionalExtension as _};
+
+    conn.transaction(|conn| {
+        let oldest: Option<FreezerEntry> = calendar::table
+            .inner_join(freezer::table.on(freezer::recipe_id.eq(calendar::recipe_id)))
+            .filter(calendar::day.eq(use_day))
+            .select(FreezerEntry::as_select())
+            .order_by((freezer::frozen.asc(), freezer::id.asc()))
+            .first(conn)
+            .optional()?;
+        let Some(oldest) = oldest else {
+            return Ok(false);
+        };
+        set_freezer_portions(conn, oldest.id, oldest.portions - 1)?;
+        update(calendar::table.filter(calendar::day.eq(use_day)))
+            .set(calendar::from_freezer.eq(true))
+            .execute(conn)?;
+        Ok(true)
+    })
+}
+
+/// The recipes checked off as cooked on the days from `start` up to but not including `end`
+/// which don't have a journal entry for that day yet, oldest first.
+pub fn get_cooked_without_journal(
+    conn: &mut database::Connection,
+    start: chrono::NaiveDate,
+    end: chrono::NaiveDate,
+) -> QueryResult<Vec<(chrono::NaiveDate, RecipeHandle)>> {
+    use database::schema::{calendar, cook_journal, recipes};
+    use diesel::dsl::{exists, not};
+
+    calendar::table
+        .inner_join(recipes::table)
+        .filter(calendar::cooked.eq(true))
+        .filter(calendar::day.ge(start))
+        .filter(calendar::day.lt(end))
+        .filter(not(exists(
+            cook_journal::table
+                .filter(cook_journal::recipe_id.eq(calendar::recipe_id))
+                .filter(cook_journal::day.eq(calendar::day)),
+        )))
+        .select((calendar::day, RecipeHandle::as_select()))
+        .order_by(calendar::day.asc())
+        .load(conn)
+}
+
+/// Every day a recipe was checked off as cooked on the calendar, oldest first.
+pub fn get_cook_history(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<(chrono::NaiveDate, RecipeHandle)>> {
+    use database::schema::{calendar, recipes};
+
+    calendar::table
+        .inner_join(recipes::table)
+        .filter(calendar::cooked.eq(true))
+        .select((calendar::day, RecipeHandle::as_select()))
+        .order_by(calendar::day.asc())
+        .load(conn)
+}
+
+/// Ingredient usages which refer to a recipe or ingredient that no longer exists.
+pub fn get_orphaned_ingredient_usages(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<IngredientUsageId>> {
+    use database::schema::{ingredient_usages, ingredients, recipes};
+    use diesel::dsl::{exists, not};
+
+    ingredient_usages::table
+        .select(ingredient_usages::id)
+        .filter(
+            not(exists(
+                recipes::table.filter(recipes::id.eq(ingredient_usages::recipe_id)),
+            ))
+            .or(not(exists(
+                ingredients::table.filter(ingredients::id.eq(ingredient_usages::ingredient_id)),
+            ))),
+        )
+        .load(conn)
+}
+
+pub fn delete_ingredient_usages(
+    conn: &mut database::Connection,
+    delete_ids: &[IngredientUsageId],
+) -> QueryResult<()> {
+    use database::schema::ingredient_usages::dsl::*;
+    use diesel::delete;
+
+    delete(ingredient_usages.filter(id.eq_any(delete_ids))).execute(conn)?;
+    Ok(())
+}
+
+/// Days on the calendar which refer to a recipe that no longer exists.
+pub fn get_orphaned_calendar_entries(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<chrono::NaiveDate>> {
+    use database::schema::{calendar, recipes};
+    use diesel::dsl::{exists, not};
+
+    calendar::table
+        .select(calendar::day)
+        .filter(not(exists(
+            recipes::table.filter(recipes::id.eq(calendar::recipe_id)),
+        )))
+        .order_by(calendar::day.asc())
+        .load(conn)
+}
+
+pub fn delete_calendar_entries(
+    conn: &mut database::Connection,
+    delete_days: &[chrono::NaiveDate],
+) -> QueryResult<()> {
+    use database::schema::calendar::dsl::*;
+    use diesel::delete;
+
+    delete(calendar.filter(day.eq_any(delete_days))).execute(conn)?;
+    Ok(())
+}
+
+/// Every recipe, archived ones included.
+pub fn get_all_recipes(conn: &mut database::Connection) -> QueryResult<Vec<Recipe>> {
+    use database::schema::recipes::dsl::*;
+    recipes
+        .select(Recipe::as_select())
+        .order_by(id.asc())
+        .load(conn)
+}
+
+pub fn get_all_ingredients(conn: &mut database::Connection) -> QueryResult<Vec<Ingredient>> {
+    use database::schema::ingredients::dsl::*;
+    ingredients
+        .select(Ingredient::as_select())
+        .order_by(id.asc())
+        .load(conn)
+}
+
+/// Ingredient usages with a quantity below zero, along with the recipe and ingredient names.
+pub fn get_negative_ingredient_usages(
+    conn: &mut database::Connection,
+) -> QueryResult<Vec<(IngredientUsageId, String, String)>> {
+    use database::schema::{ingredient_usages, ingredients, recipes};
+
+    ingredient_usages::table
+        .inner_join(recipes::table)
+        .inner_join(ingredients::table)
+        .select((ingredient_usages::id, recipes::name, ingredients::name))
+        .filter(ingredient_usages::quantity.lt(0.0))
+        .order_by((recipes::name.asc(), ingredients::name.asc()))
+        .load(conn)
+}
+
+pub fn negate_ingredient_usage_quantities(
+    conn: &mut database::Connection,
+    edit_ids: &[IngredientUsageId],
+) -> QueryResult<()> {
+    use database::schema::ingredient_usages::dsl::*;
+    use diesel::update;
+
+    update(ingredient_usages.filter(id.eq_any(edit_ids)))
+        .set(quantity.eq(quantity * -1.0))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Replaces each of `remove` with `keep` everywhere it is used, then deletes it.
+pub fn merge_ingredients(
+    conn: &mut database::Connection,
+    keep: IngredientId,
+    remove: &[IngredientId],
+) -> QueryResult<()> {
+    conn.transaction(|conn| {
+        for &r in remove {
+            replace_ingredient(conn, r, keep)?;
+            delete_ingredient(conn, r)?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests;
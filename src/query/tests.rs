@@ -0,0 +1,782 @@
+use super::*;
+use crate::database::fixture::{day, Fixture};
+
+fn names(recipes: Vec<RecipeHandle>) -> Vec<String> {
+    recipes.into_iter().map(|r| r.name).collect()
+}
+
+#[test]
+fn search_recipes_test() {
+    let mut f = Fixture::open();
+    let found = search_recipes(&mut f.conn, &mut None, "carrot", false).unwrap();
+    assert_eq!(
+        found,
+        [
+            (f.cake, "Carrot Cake".into()),
+            (f.soup, "Carrot Soup".into())
+        ]
+    );
+
+    // LIKE ignores case
+    let found = search_recipes(&mut f.conn, &mut None, "TART", false).unwrap();
+    assert_eq!(found, [(f.tart, "Onion Tart".into())]);
+
+    assert!(search_recipes(&mut f.conn, &mut None, "pie", false)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn archived_recipes_test() {
+    let mut f = Fixture::open();
+    edit_recipe_archived(&mut f.conn, f.soup, true).unwrap();
+
+    let found = search_recipes(&mut f.conn, &mut None, "carrot", false).unwrap();
+    assert_eq!(found, [(f.cake, "Carrot Cake".into())]);
+    let found = search_recipes(&mut f.conn, &mut None, "carrot", true).unwrap();
+    assert_eq!(found.len(), 2);
+
+    let any = search_recipes_including_any_ingredient(&mut f.conn, vec![f.carrot], false);
+    assert_eq!(names(any.unwrap()), ["Carrot Cake"]);
+    let any = search_recipes_including_any_ingredient(&mut f.conn, vec![f.carrot], true);
+    assert_eq!(names(any.unwrap()), ["Carrot Cake", "Carrot Soup"]);
+
+    // Left out of its category's list, but not the calendar.
+    assert_eq!(count_recipes(&mut f.conn, f.dinner).unwrap(), 1);
+    let page = get_recipes_page(&mut f.conn, f.dinner, 0, 10).unwrap();
+    assert_eq!(names(page), ["Onion Tart"]);
+    let summaries = get_recipe_summaries(&mut f.conn, f.dinner).unwrap();
+    assert!(summaries.iter().all(|s| s.id != f.soup));
+    let week = get_calendar_week(&mut f.conn, day(1).week(chrono::Weekday::Mon)).unwrap();
+    assert_eq!(week[&chrono::Weekday::Mon].id, f.soup);
+}
+
+#[test]
+fn search_category_recipes_test() {
+    let mut f = Fixture::open();
+    let mut cache = None;
+    let found = search_category_recipes(&mut f.conn, &mut cache, f.dinner, "").unwrap();
+    let found: Vec<_> = found.into_iter().map(|(id, _)| id).collect();
+    assert_eq!(found, [f.soup, f.tart]);
+
+    let found = search_category_recipes(&mut f.conn, &mut None, f.dinner, "TART").unwrap();
+    assert_eq!(found, [(f.tart, "Onion Tart".into())]);
+    // The cake has carrot in its name too, but it's in another category.
+    let found = search_category_recipes(&mut f.conn, &mut None, f.dinner, "carrot").unwrap();
+    assert_eq!(found, [(f.soup, "Carrot Soup".into())]);
+
+    edit_recipe_archived(&mut f.conn, f.soup, true).unwrap();
+    let found = search_category_recipes(&mut f.conn, &mut None, f.dinner, "carrot").unwrap();
+    assert!(found.is_empty());
+}
+
+#[test]
+fn want_to_try_test() {
+    let mut f = Fixture::open();
+    assert!(get_want_to_try_recipes(&mut f.conn).unwrap().is_empty());
+
+    edit_recipe_status(&mut f.conn, f.cake, RecipeStatus::WantToTry).unwrap();
+    edit_recipe_status(&mut f.conn, f.soup, RecipeStatus::WantToTry).unwrap();
+    let want_to_try = get_want_to_try_recipes(&mut f.conn).unwrap();
+    assert_eq!(names(want_to_try), ["Carrot Soup", "Carrot Cake"]);
+
+    // Cooking the soup means it's been tried.
+    set_calendar_entry_cooked(&mut f.conn, day(1), true).unwrap();
+    let want_to_try = get_want_to_try_recipes(&mut f.conn).unwrap();
+    assert_eq!(names(want_to_try), ["Carrot Cake"]);
+    let (soup, _, _) = get_recipe(&mut f.conn, f.soup).unwrap();
+    assert_eq!(soup.status, RecipeStatus::Tried);
+
+    edit_recipe_archived(&mut f.conn, f.cake, true).unwrap();
+    assert!(get_want_to_try_recipes(&mut f.conn).unwrap().is_empty());
+}
+
+#[test]
+fn cook_history_test() {
+    let mut f = Fixture::open();
+    assert!(get_cook_history(&mut f.conn).unwrap().is_empty());
+
+    set_calendar_entry_cooked(&mut f.conn, day(3), true).unwrap();
+    set_calendar_entry_cooked(&mut f.conn, day(1), true).unwrap();
+    let history = get_cook_history(&mut f.conn).unwrap();
+    let history: Vec<_> = history.into_iter().map(|(d, r)| (d, r.id)).collect();
+    assert_eq!(history, [(day(1), f.soup), (day(3), f.tart)]);
+}
+
+#[test]
+fn cook_journal_test() {
+    let mut f = Fixture::open();
+    set_calendar_entry_cooked(&mut f.conn, day(1), true).unwrap();
+    set_calendar_entry_cooked(&mut f.conn, day(3), true).unwrap();
+
+    // Only the days before the end are asked about.
+    let cooked = get_cooked_without_journal(&mut f.conn, day(1), day(3)).unwrap();
+    let cooked: Vec<_> = cooked.into_iter().map(|(d, r)| (d, r.id)).collect();
+    assert_eq!(cooked, [(day(1), f.soup)]);
+
+    add_cook_journal_entry(&mut f.conn, f.soup, day(1), Some(4), "less salt").unwrap();
+    add_cook_journal_entry(&mut f.conn, f.soup, day(8), None, "").unwrap();
+    assert!(get_cooked_without_journal(&mut f.conn, day(1), day(3))
+        .unwrap()
+        .is_empty());
+
+    let journal = get_cook_journal(&mut f.conn, f.soup).unwrap();
+    let days: Vec<_> = journal.iter().map(|e| e.day).collect();
+    assert_eq!(days, [day(1), day(8)]);
+    assert_eq!(journal[0].rating, Some(4));
+    assert_eq!(journal[0].notes, "less salt");
+
+    delete_cook_journal_entry(&mut f.conn, journal[1].id).unwrap();
+    assert_eq!(get_cook_journal(&mut f.conn, f.soup).unwrap().len(), 1);
+    assert!(get_cook_journal(&mut f.conn, f.tart).unwrap().is_empty());
+}
+
+#[test]
+fn category_default_duration_test() {
+    let mut f = Fixture::open();
+    let pie = add_recipe(&mut f.conn, "Apple Pie", f.dessert).unwrap();
+    assert_eq!(
+        get_recipe(&mut f.conn, pie).unwrap().0.duration,
+        RecipeDuration::Short
+    );
+
+    edit_category_default_duration(&mut f.conn, f.dessert, Some(RecipeDuration::Long)).unwrap();
+    let (categories, _): (Vec<_>, Vec<_>) = get_recipe_categories(&mut f.conn)
+        .unwrap()
+        .into_iter()
+        .unzip();
+    let dessert = categories.iter().find(|c| c.id == f.dessert).unwrap();
+    assert_eq!(dessert.default_duration, Some(RecipeDuration::Long));
+
+    let crumble = add_recipe(&mut f.conn, "Crumble", f.dessert).unwrap();
+    assert_eq!(
+        get_recipe(&mut f.conn, crumble).unwrap().0.duration,
+        RecipeDuration::Long
+    );
+    let stew = add_recipe(&mut f.conn, "Stew", f.dinner).unwrap();
+    assert_eq!(
+        get_recipe(&mut f.conn, stew).unwrap().0.duration,
+        RecipeDuration::Short
+    );
+}
+
+#[test]
+fn recipe_book_stats_test() {
+    let mut f = Fixture::open();
+    assert_eq!(
+        get_recipe_book_totals(&mut f.conn).unwrap(),
+        RecipeBookTotals {
+            recipes: 3,
+            ingredients: 4,
+            categories: 2,
+        }
+    );
+
+    // Ties go by name.
+    assert_eq!(
+        get_most_used_ingredients(&mut f.conn, 3).unwrap(),
+        [
+            ("carrot".into(), 2),
+            ("onion".into(), 2),
+            ("salt".into(), 2)
+        ]
+    );
+}
+
+#[test]
+fn search_ingredients_test() {
+    let mut f = Fixture::open();
+    let found = search_ingredients(&mut f.conn, &mut None, "s").unwrap();
+    let found: Vec<_> = found.into_iter().map(|(i, _)| i.id).collect();
+    assert_eq!(found, [f.salt, f.sugar]);
+}
+
+#[test]
+fn search_recipes_including_ingredients_test() {
+    let mut f = Fixture::open();
+    let any = search_recipes_including_any_ingredient(&mut f.conn, vec![f.sugar, f.onion], false);
+    assert_eq!(
+        names(any.unwrap()),
+        ["Carrot Cake", "Carrot Soup", "Onion Tart"]
+    );
+
+    let all = search_recipes_including_all_ingredient(&mut f.conn, vec![f.carrot, f.onion], false);
+    assert_eq!(names(all.unwrap()), ["Carrot Soup"]);
+
+    let at_least = search_recipes_including_at_least_ingredients(
+        &mut f.conn,
+        vec![f.carrot, f.salt, f.sugar],
+        2,
+        false,
+    );
+    assert_eq!(names(at_least.unwrap()), ["Carrot Cake", "Carrot Soup"]);
+}
+
+#[test]
+fn filter_recipes_by_quantity_test() {
+    use IngredientMeasurement::{Cups, Grams, Tablespoons};
+
+    let mut f = Fixture::open();
+    let mut set_units = |recipe, ingredient, quantity, units| {
+        let (usage, i) = get_ingredients_for_recipe(&mut f.conn, recipe)
+            .unwrap()
+            .into_iter()
+            .find(|(u, _)| u.ingredient_id == ingredient)
+            .unwrap();
+        edit_recipe_ingredient(&mut f.conn, usage.id, &i, quantity, units, "").unwrap();
+    };
+    set_units(f.soup, f.carrot, 4.0, Some(Tablespoons));
+    set_units(f.cake, f.carrot, 2.0, Some(Cups));
+    set_units(f.tart, f.salt, 5.0, Some(Grams));
+
+    let mut filter = |ingredients: Vec<IngredientId>, minimums: &[MinimumQuantity], required| {
+        let recipes =
+            search_recipes_including_any_ingredient(&mut f.conn, ingredients.clone(), false)
+                .unwrap();
+        names(
+            filter_recipes_by_quantity(&mut f.conn, recipes, &ingredients, minimums, required)
+                .unwrap(),
+        )
+    };
+    let minimum = |ingredient, quantity, units| MinimumQuantity {
+        ingredient,
+        quantity,
+        units,
+    };
+
+    // Four tablespoons is only a quarter cup.
+    let half_cup = minimum(f.carrot, 0.5, Some(Cups));
+    assert_eq!(filter(vec![f.carrot], &[half_cup], 1), ["Carrot Cake"]);
+    let quarter_cup = minimum(f.carrot, 0.25, Some(Cups));
+    assert_eq!(
+        filter(vec![f.carrot], &[quarter_cup], 1),
+        ["Carrot Cake", "Carrot Soup"]
+    );
+
+    // Quantities without units are compared as they are.
+    let two_onions = minimum(f.onion, 2.0, None);
+    assert_eq!(filter(vec![f.onion], &[two_onions], 1), ["Onion Tart"]);
+
+    // Grams can't be compared with tablespoons.
+    let salt = minimum(f.salt, 1.0, Some(Tablespoons));
+    assert!(filter(vec![f.salt], &[salt], 1).is_empty());
+
+    // The soup uses too little carrot for it to count, but its onion still does.
+    assert!(filter(vec![f.carrot, f.onion], &[half_cup], 2).is_empty());
+    assert_eq!(
+        filter(vec![f.carrot, f.onion], &[half_cup], 1),
+        ["Carrot Cake", "Carrot Soup", "Onion Tart"]
+    );
+}
+
+#[test]
+fn delete_recipe_test() {
+    let mut f = Fixture::open();
+    add_recipe_step(&mut f.conn, f.soup, 1, "simmer").unwrap();
+    delete_recipe(&mut f.conn, f.soup).unwrap();
+
+    // Its ingredients, steps and calendar entries go along with it.
+    assert!(get_ingredients_for_recipe(&mut f.conn, f.soup)
+        .unwrap()
+        .is_empty());
+    assert!(get_recipe_steps(&mut f.conn, f.soup).unwrap().is_empty());
+    let week = get_calendar_week(&mut f.conn, day(1).week(chrono::Weekday::Mon)).unwrap();
+    assert_eq!(week.keys().collect::<Vec<_>>(), [&chrono::Weekday::Wed]);
+    assert!(get_orphaned_ingredient_usages(&mut f.conn)
+        .unwrap()
+        .is_empty());
+    assert!(get_orphaned_calendar_entries(&mut f.conn)
+        .unwrap()
+        .is_empty());
+
+    // The ingredients themselves are kept.
+    assert_eq!(get_all_ingredients(&mut f.conn).unwrap().len(), 4);
+}
+
+#[test]
+fn delete_ingredient_test() {
+    let mut f = Fixture::open();
+
+    // Ingredients used by a recipe can't be deleted.
+    assert!(!delete_ingredient(&mut f.conn, f.sugar).unwrap());
+    delete_recipe(&mut f.conn, f.cake).unwrap();
+    add_ingredient_calories_entry(&mut f.conn, f.sugar, 770.0, 1.0, None).unwrap();
+    assert!(delete_ingredient(&mut f.conn, f.sugar).unwrap());
+
+    // Its calories go along with it.
+    assert!(get_ingredient_calories(&mut f.conn, f.sugar)
+        .unwrap()
+        .is_empty());
+    assert_eq!(get_all_ingredients(&mut f.conn).unwrap().len(), 3);
+}
+
+#[test]
+fn get_most_common_usage_units_test() {
+    use IngredientMeasurement::{Cups, Grams};
+
+    let mut f = Fixture::open();
+    assert_eq!(
+        get_most_common_usage_units(&mut f.conn, f.onion).unwrap(),
+        None
+    );
+
+    let mut set_units = |recipe, ingredient, units| {
+        let (usage, i) = get_ingredients_for_recipe(&mut f.conn, recipe)
+            .unwrap()
+            .into_iter()
+            .find(|(u, _)| u.ingredient_id == ingredient)
+            .unwrap();
+        edit_recipe_ingredient(&mut f.conn, usage.id, &i, usage.quantity, units, "").unwrap();
+    };
+    set_units(f.soup, f.onion, Some(Cups));
+    set_units(f.tart, f.onion, Some(Cups));
+    set_units(f.soup, f.carrot, Some(Grams));
+
+    assert_eq!(
+        get_most_common_usage_units(&mut f.conn, f.onion).unwrap(),
+        Some(Cups)
+    );
+    // Usages without units don't count.
+    assert_eq!(
+        get_most_common_usage_units(&mut f.conn, f.carrot).unwrap(),
+        Some(Grams)
+    );
+}
+
+#[test]
+fn delete_category_test() {
+    let mut f = Fixture::open();
+
+    // Categories with recipes in them can't be deleted.
+    assert!(!delete_category(&mut f.conn, f.dessert).unwrap());
+    delete_recipe(&mut f.conn, f.cake).unwrap();
+    assert!(delete_category(&mut f.conn, f.dessert).unwrap());
+    assert_eq!(get_recipe_categories(&mut f.conn).unwrap().len(), 1);
+}
+
+#[test]
+fn get_ingredients_page_test() {
+    let mut f = Fixture::open();
+    assert_eq!(count_ingredients(&mut f.conn, "").unwrap(), 4);
+    assert_eq!(count_ingredients(&mut f.conn, "S").unwrap(), 2);
+
+    let page = |conn: &mut _, query, offset| -> Vec<IngredientId> {
+        get_ingredients_page(conn, query, offset, 2)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.id)
+            .collect()
+    };
+    assert_eq!(page(&mut f.conn, "", 0), [f.carrot, f.onion]);
+    assert_eq!(page(&mut f.conn, "", 2), [f.salt, f.sugar]);
+    assert!(page(&mut f.conn, "", 4).is_empty());
+    assert_eq!(page(&mut f.conn, "s", 1), [f.sugar]);
+}
+
+#[test]
+fn get_recipe_summaries_test() {
+    let mut f = Fixture::open();
+    edit_recipe_rating(&mut f.conn, f.tart, Some(4)).unwrap();
+    set_calendar_entry_cooked(&mut f.conn, day(3), true).unwrap();
+    insert_or_update_calendar_entry(&mut f.conn, day(10), f.tart).unwrap();
+    add_ingredient_calories_entry(&mut f.conn, f.onion, 40.0, 1.0, None).unwrap();
+
+    let summaries = get_recipe_summaries(&mut f.conn, f.dinner).unwrap();
+    let names: Vec<_> = summaries.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Carrot Soup", "Onion Tart"]);
+
+    let (soup, tart) = (&summaries[0], &summaries[1]);
+    assert_eq!(soup.rating, None);
+    assert_eq!(soup.last_cooked, None);
+    assert_eq!(soup.ingredients.len(), 3);
+    assert_eq!(tart.rating, Some(4));
+    // Only the day it was checked off as cooked counts, not when it's scheduled next.
+    assert_eq!(tart.last_cooked, Some(day(3)));
+    let calories: Vec<_> = tart.ingredients.iter().map(|i| i.calories()).collect();
+    assert_eq!(calories, [Some(120.0), None]);
+}
+
+#[test]
+fn ingredient_density_test() {
+    use IngredientMeasurement::{Cups, Grams};
+
+    let mut f = Fixture::open();
+    add_ingredient_calories_entry(&mut f.conn, f.onion, 40.0, 100.0, Some(Grams)).unwrap();
+    let (usage, onion) = get_ingredients_for_recipe(&mut f.conn, f.tart)
+        .unwrap()
+        .into_iter()
+        .find(|(u, _)| u.ingredient_id == f.onion)
+        .unwrap();
+    edit_recipe_ingredient(&mut f.conn, usage.id, &onion, 2.0, Some(Cups), "").unwrap();
+
+    let onion_calories = |conn: &mut database::Connection| {
+        let summaries = get_recipe_summaries(conn, f.dinner).unwrap();
+        let tart = summaries.iter().find(|s| s.name == "Onion Tart").unwrap();
+        let onion = tart.ingredients.iter().find(|i| i.ingredient.id == f.onion);
+        onion.unwrap().calories()
+    };
+    // Cups can't be turned into grams until the onion has a density.
+    assert_eq!(onion_calories(&mut f.conn), None);
+
+    set_ingredient_density(&mut f.conn, f.onion, Some(0.5)).unwrap();
+    let calories = onion_calories(&mut f.conn).unwrap();
+    assert!((calories - 94.635).abs() < 0.01, "{calories}");
+}
+
+#[test]
+fn recipe_cost_test() {
+    use crate::recipe::recipe_cost;
+    use IngredientMeasurement::{Cups, Grams, Kilograms};
+
+    let mut f = Fixture::open();
+    let costs = |conn: &mut database::Connection| {
+        let ingredients = get_all_recipe_ingredients(conn).unwrap();
+        let tart = ingredients.iter().filter(|(r, _)| r.id == f.tart);
+        recipe_cost(tart.map(|(_, i)| i))
+    };
+    let missing = costs(&mut f.conn).unwrap_err();
+    let missing: Vec<_> = missing.into_iter().map(|(name, _)| name).collect();
+    assert_eq!(missing, ["onion", "salt"]);
+
+    // The tart's onions and salt are counted, not weighed.
+    set_ingredient_price(&mut f.conn, f.onion, Some(0.5), None).unwrap();
+    set_ingredient_price(&mut f.conn, f.salt, Some(2.0), Some(Kilograms)).unwrap();
+    let missing = costs(&mut f.conn).unwrap_err();
+    assert_eq!(
+        missing,
+        [(
+            "salt".into(),
+            "quantity has no units, the price is per kg".into()
+        )]
+    );
+
+    set_ingredient_price(&mut f.conn, f.salt, Some(0.4), None).unwrap();
+    let cost = costs(&mut f.conn).unwrap();
+    assert!((cost - 1.6).abs() < 0.001, "{cost}");
+
+    // Prices by weight are converted, through the density for volumes.
+    let (usage, onion) = get_ingredients_for_recipe(&mut f.conn, f.tart)
+        .unwrap()
+        .into_iter()
+        .find(|(u, _)| u.ingredient_id == f.onion)
+        .unwrap();
+    edit_recipe_ingredient(&mut f.conn, usage.id, &onion, 500.0, Some(Grams), "").unwrap();
+    set_ingredient_price(&mut f.conn, f.onion, Some(3.0), Some(Kilograms)).unwrap();
+    let cost = costs(&mut f.conn).unwrap();
+    assert!((cost - 1.6).abs() < 0.001, "{cost}");
+    edit_recipe_ingredient(&mut f.conn, usage.id, &onion, 1.0, Some(Cups), "").unwrap();
+    assert!(costs(&mut f.conn).is_err());
+    set_ingredient_density(&mut f.conn, f.onion, Some(0.5)).unwrap();
+    let cost = costs(&mut f.conn).unwrap();
+    assert!((cost - 0.455).abs() < 0.01, "{cost}");
+
+    assert!(get_all_recipe_servings(&mut f.conn).unwrap().is_empty());
+    edit_recipe_servings(&mut f.conn, f.tart, Some(4)).unwrap();
+    let servings = get_all_recipe_servings(&mut f.conn).unwrap();
+    assert_eq!(servings, HashMap::from([(f.tart, 4)]));
+}
+
+#[test]
+fn leftovers_test() {
+    use crate::recipe::leftovers;
+
+    let mut f = Fixture::open();
+    let week = day(1).week(chrono::Weekday::Mon);
+    let left = |f: &mut Fixture| -> Vec<(String, f32)> {
+        let ingredients = get_calendar_week_shopping_ingredients(&mut f.conn, week).unwrap();
+        leftovers(&ingredients)
+            .into_iter()
+            .map(|(i, left)| (i.name, left))
+            .collect()
+    };
+    // Three quarters of the salt is used, the four onions are used up.
+    assert_eq!(left(&mut f), [("salt".into(), 0.25)]);
+
+    // Scaling the soup up uses the rest of the salt, but leaves half an onion.
+    edit_recipe_servings(&mut f.conn, f.soup, Some(4)).unwrap();
+    set_calendar_entry_servings(&mut f.conn, day(1), Some(6)).unwrap();
+    assert_eq!(left(&mut f), [("onion".into(), 0.5)]);
+
+    set_ingredient_staple(&mut f.conn, f.onion, true).unwrap();
+    assert!(left(&mut f).is_empty());
+}
+
+#[test]
+fn get_recipe_summary_test() {
+    let mut f = Fixture::open();
+    forget_recipe_summaries();
+    edit_recipe_rating(&mut f.conn, f.tart, Some(4)).unwrap();
+    set_calendar_entry_cooked(&mut f.conn, day(3), true).unwrap();
+
+    let tart = get_recipe_summary(&mut f.conn, f.tart).unwrap();
+    assert_eq!(tart.name, "Onion Tart");
+    assert_eq!(tart.rating, Some(4));
+    assert_eq!(tart.last_cooked, Some(day(3)));
+    assert_eq!(tart.ingredients.len(), 2);
+
+    // It's answered from the cache until the summaries are forgotten.
+    edit_recipe_rating(&mut f.conn, f.tart, Some(2)).unwrap();
+    assert_eq!(
+        get_recipe_summary(&mut f.conn, f.tart).unwrap().rating,
+        Some(4)
+    );
+    forget_recipe_summaries();
+    assert_eq!(
+        get_recipe_summary(&mut f.conn, f.tart).unwrap().rating,
+        Some(2)
+    );
+}
+
+#[test]
+fn get_recipe_categories_test() {
+    let mut f = Fixture::open();
+    add_category(&mut f.conn, "Breakfast").unwrap();
+    let categories: Vec<_> = get_recipe_categories(&mut f.conn)
+        .unwrap()
+        .into_iter()
+        .map(|(c, count)| (c.name, count))
+        .collect();
+    assert_eq!(
+        categories,
+        [
+            ("Breakfast".into(), 0),
+            ("Dessert".into(), 1),
+            ("Dinner".into(), 2)
+        ]
+    );
+
+    let last_used = get_recipe_categories_last_used(&mut f.conn).unwrap();
+    assert_eq!(last_used.len(), 1);
+    assert_eq!(last_used[&f.dinner], day(3));
+}
+
+#[test]
+fn foreign_keys_test() {
+    let mut f = Fixture::open();
+    let (usage, _) = get_ingredients_for_recipe(&mut f.conn, f.cake)
+        .unwrap()
+        .remove(0);
+    delete_recipe(&mut f.conn, f.cake).unwrap();
+
+    // Nothing can refer to a recipe which doesn't exist, like when undoing removing an ingredient
+    // from a recipe which was deleted since.
+    assert!(restore_recipe_ingredient(&mut f.conn, &usage).is_err());
+    assert!(insert_or_update_calendar_entry(&mut f.conn, day(2), f.cake).is_err());
+}
+
+#[test]
+fn calendar_test() {
+    let mut f = Fixture::open();
+    let week = day(1).week(chrono::Weekday::Mon);
+
+    let planned = get_calendar_week(&mut f.conn, week).unwrap();
+    assert_eq!(planned.len(), 2);
+    assert_eq!(planned[&chrono::Weekday::Mon].id, f.soup);
+    assert_eq!(planned[&chrono::Weekday::Wed].id, f.tart);
+
+    set_calendar_entry_cooked(&mut f.conn, day(1), true).unwrap();
+    let cooked = get_calendar_week_cooked(&mut f.conn, week).unwrap();
+    assert_eq!(cooked, [chrono::Weekday::Mon].into());
+
+    // Planning something else for the day means it hasn't been cooked yet.
+    insert_or_update_calendar_entry(&mut f.conn, day(1), f.cake).unwrap();
+    let planned = get_calendar_week(&mut f.conn, week).unwrap();
+    assert_eq!(planned[&chrono::Weekday::Mon].id, f.cake);
+    assert!(get_calendar_week_cooked(&mut f.conn, week)
+        .unwrap()
+        .is_empty());
+
+    // The next week is empty.
+    let next_week = day(8).week(chrono::Weekday::Mon);
+    assert!(get_calendar_week(&mut f.conn, next_week)
+        .unwrap()
+        .is_empty());
+
+    let history = get_calendar_history(&mut f.conn, day(2)).unwrap();
+    let history: Vec<_> = history.into_iter().map(|e| (e.day, e.recipe_id)).collect();
+    assert_eq!(history, [(day(1), f.cake)]);
+
+    delete_calendar_entry(&mut f.conn, day(3)).unwrap();
+    let planned = get_calendar_week(&mut f.conn, week).unwrap();
+    assert_eq!(planned.len(), 1);
+}
+
+#[test]
+fn calendar_ingredients_test() {
+    let mut f = Fixture::open();
+    let ingredients = get_calendar_ingredients(&mut f.conn, day(1), day(7)).unwrap();
+    let mut ingredients: Vec<_> = ingredients
+        .into_iter()
+        .map(|(d, i)| (d, i.ingredient.name, i.quantity))
+        .collect();
+    ingredients.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    assert_eq!(
+        ingredients,
+        [
+            (day(1), "carrot".into(), 4.0),
+            (day(1), "onion".into(), 1.0),
+            (day(1), "salt".into(), 0.5),
+            (day(3), "onion".into(), 3.0),
+            (day(3), "salt".into(), 0.25),
+        ]
+    );
+}
+
+#[test]
+fn calendar_servings_test() {
+    let mut f = Fixture::open();
+    let week = day(1).week(chrono::Weekday::Mon);
+    let shopping = |f: &mut Fixture| -> Vec<(String, f32)> {
+        get_calendar_week_shopping_ingredients(&mut f.conn, week)
+            .unwrap()
+            .into_iter()
+            .map(|(u, i)| (i.name, u.quantity))
+            .collect()
+    };
+    let as_written = vec![
+        ("carrot".into(), 4.0),
+        ("onion".into(), 1.0),
+        ("salt".into(), 0.5),
+        ("onion".into(), 3.0),
+        ("salt".into(), 0.25),
+    ];
+    assert_eq!(shopping(&mut f), as_written);
+
+    // Without knowing how many the soup feeds, there's nothing to scale by.
+    set_calendar_entry_servings(&mut f.conn, day(1), Some(6)).unwrap();
+    assert_eq!(shopping(&mut f), as_written);
+    let servings = get_calendar_week_servings(&mut f.conn, week).unwrap();
+    assert_eq!(servings, HashMap::from([(chrono::Weekday::Mon, 6)]));
+
+    edit_recipe_servings(&mut f.conn, f.soup, Some(4)).unwrap();
+    assert_eq!(
+        shopping(&mut f),
+        [
+            ("carrot".into(), 6.0),
+            ("onion".into(), 1.5),
+            ("salt".into(), 0.75),
+            ("onion".into(), 3.0),
+            ("salt".into(), 0.25),
+        ]
+    );
+
+    set_calendar_entry_servings(&mut f.conn, day(1), None).unwrap();
+    assert_eq!(shopping(&mut f), as_written);
+}
+
+#[test]
+fn freezer_test() {
+    let mut f = Fixture::open();
+    let week = day(1).week(chrono::Weekday::Mon);
+    assert!(!use_frozen_portion(&mut f.conn, day(1)).unwrap());
+
+    add_freezer_entry(&mut f.conn, f.soup, 1, day(2)).unwrap();
+    add_freezer_entry(&mut f.conn, f.soup, 2, day(1)).unwrap();
+    add_freezer_entry(&mut f.conn, f.cake, 3, day(1)).unwrap();
+    let stock = get_freezer_stock(&mut f.conn).unwrap();
+    assert_eq!(stock, HashMap::from([(f.soup, 3), (f.cake, 3)]));
+
+    // The portion frozen longest ago is used first, and the soup isn't shopped for any more.
+    assert!(use_frozen_portion(&mut f.conn, day(1)).unwrap());
+    let inventory: Vec<_> = get_freezer_inventory(&mut f.conn)
+        .unwrap()
+        .into_iter()
+        .map(|(e, r)| (r.id, e.portions, e.frozen))
+        .collect();
+    assert_eq!(
+        inventory,
+        [
+            (f.soup, 1, day(1)),
+            (f.cake, 3, day(1)),
+            (f.soup, 1, day(2))
+        ]
+    );
+    let from_freezer = get_calendar_week_from_freezer(&mut f.conn, week).unwrap();
+    assert_eq!(from_freezer, HashSet::from([chrono::Weekday::Mon]));
+    let shopping = get_calendar_week_shopping_ingredients(&mut f.conn, week).unwrap();
+    assert!(shopping.iter().all(|(u, _)| u.recipe_id == f.tart));
+
+    // Using the last portion takes it out of the freezer.
+    assert!(use_frozen_portion(&mut f.conn, day(1)).unwrap());
+    assert_eq!(get_freezer_stock(&mut f.conn).unwrap()[&f.soup], 1);
+    assert_eq!(get_freezer_inventory(&mut f.conn).unwrap().len(), 2);
+
+    // Scheduling something else on the day means it needs shopping for again.
+    insert_or_update_calendar_entry(&mut f.conn, day(1), f.cake).unwrap();
+    assert!(get_calendar_week_from_freezer(&mut f.conn, week)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn merge_ingredients_test() {
+    let mut f = Fixture::open();
+    let shallot = add_ingredient(&mut f.conn, "Onion").unwrap().id;
+    add_recipe_ingredient(&mut f.conn, f.cake, shallot, 1.0).unwrap();
+
+    merge_ingredients(&mut f.conn, f.onion, &[shallot]).unwrap();
+    let cake = get_ingredients_for_recipe(&mut f.conn, f.cake).unwrap();
+    assert!(cake.iter().any(|(_, i)| i.id == f.onion));
+    assert!(get_all_ingredients(&mut f.conn)
+        .unwrap()
+        .iter()
+        .all(|i| i.id != shallot));
+}
+
+#[test]
+fn ingredient_contains_test() {
+    let mut f = Fixture::open();
+    set_ingredient_compound(&mut f.conn, f.carrot, 1.0, None).unwrap();
+    add_ingredient_component(&mut f.conn, f.carrot, f.onion, 1.0, None).unwrap();
+    set_ingredient_compound(&mut f.conn, f.onion, 1.0, None).unwrap();
+    add_ingredient_component(&mut f.conn, f.onion, f.salt, 1.0, None).unwrap();
+
+    assert!(ingredient_contains(&mut f.conn, f.carrot, f.onion).unwrap());
+    assert!(ingredient_contains(&mut f.conn, f.carrot, f.salt).unwrap());
+    assert!(!ingredient_contains(&mut f.conn, f.onion, f.carrot).unwrap());
+    assert!(!ingredient_contains(&mut f.conn, f.salt, f.salt).unwrap());
+}
+
+#[test]
+fn shopping_list_items_test() {
+    let mut f = Fixture::open();
+    let week = day(1).week(chrono::Weekday::Sun);
+    let next_week = day(8).week(chrono::Weekday::Sun);
+    set_shopping_list_item_checked(&mut f.conn, week, f.carrot, true).unwrap();
+    set_shopping_list_item_checked(&mut f.conn, week, f.onion, true).unwrap();
+    set_shopping_list_item_checked(&mut f.conn, week, f.onion, false).unwrap();
+
+    let checked = get_checked_shopping_list_items(&mut f.conn, week).unwrap();
+    assert_eq!(checked, HashSet::from([f.carrot]));
+    assert!(get_checked_shopping_list_items(&mut f.conn, next_week)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn get_in_season_recipes_test() {
+    let mut f = Fixture::open();
+    // Carrots in January and February, onions all year.
+    set_ingredient_season(&mut f.conn, f.carrot, 0b11).unwrap();
+    set_ingredient_season(&mut f.conn, f.onion, 0xfff).unwrap();
+
+    let recipes = |conn: &mut _, month| -> Vec<(String, usize)> {
+        get_in_season_recipes(conn, month)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.recipe.name, r.in_season.len()))
+            .collect()
+    };
+    assert_eq!(
+        recipes(&mut f.conn, 1),
+        [
+            ("Carrot Soup".into(), 2),
+            ("Carrot Cake".into(), 1),
+            ("Onion Tart".into(), 1),
+        ]
+    );
+    assert_eq!(
+        recipes(&mut f.conn, 6),
+        [("Onion Tart".into(), 1), ("Carrot Soup".into(), 1)]
+    );
+}
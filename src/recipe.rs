@@ -0,0 +1,256 @@
+//! Ingredients as they are used in a recipe, and working out their calories.
+
+use crate::database::models::{
+    Ingredient, IngredientCaloriesEntry, IngredientMeasurement, IngredientUsage, IngredientUsageId,
+};
+use crate::unit_conversion;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// How much to multiply a recipe's quantities by to feed `scheduled` people, when the recipe
+/// feeds `recipe` people. Without both there's nothing to go on, so it's made as written.
+pub fn servings_scale(scheduled: Option<i32>, recipe: Option<i32>) -> f32 {
+    match (scheduled, recipe) {
+        (Some(scheduled), Some(recipe)) if scheduled > 0 && recipe > 0 => {
+            scheduled as f32 / recipe as f32
+        }
+        _ => 1.0,
+    }
+}
+
+#[test]
+fn servings_scale_test() {
+    assert_eq!(servings_scale(Some(6), Some(4)), 1.5);
+    assert_eq!(servings_scale(Some(2), Some(4)), 0.5);
+    assert_eq!(servings_scale(Some(6), None), 1.0);
+    assert_eq!(servings_scale(None, Some(4)), 1.0);
+    assert_eq!(servings_scale(Some(6), Some(0)), 1.0);
+}
+
+/// Below this much of an item left over, it's just rounding.
+const LEFTOVER_EPSILON: f32 = 0.01;
+
+/// What's likely to be left over once the ingredients are bought, like half an onion. Only things
+/// counted without units are bought whole, and staples are always around, so they're left out.
+pub fn leftovers(ingredients: &[(IngredientUsage, Ingredient)]) -> Vec<(Ingredient, f32)> {
+    let mut totals: Vec<(Ingredient, f32)> = vec![];
+    for (usage, ingredient) in ingredients {
+        if usage.quantity_units.is_some() || ingredient.staple {
+            continue;
+        }
+        match totals.iter_mut().find(|(i, _)| i.id == ingredient.id) {
+            Some((_, total)) => *total += usage.quantity,
+            None => totals.push((ingredient.clone(), usage.quantity)),
+        }
+    }
+    let mut leftovers: Vec<_> = totals
+        .into_iter()
+        .map(|(ingredient, total)| (ingredient, total.ceil() - total))
+        .filter(|(_, left)| *left > LEFTOVER_EPSILON && *left < 1.0 - LEFTOVER_EPSILON)
+        .collect();
+    leftovers.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+    leftovers
+}
+
+#[derive(Clone)]
+pub struct RecipeIngredient {
+    pub id: IngredientUsageId,
+    pub ingredient: Ingredient,
+    pub quantity: f32,
+    pub quantity_units: Option<IngredientMeasurement>,
+    pub note: String,
+    pub calories: Vec<IngredientCaloriesEntry>,
+}
+
+impl RecipeIngredient {
+    pub fn new(usage: IngredientUsage, ingredient: Ingredient) -> Self {
+        Self {
+            id: usage.id,
+            ingredient,
+            quantity: usage.quantity,
+            quantity_units: usage.quantity_units,
+            note: usage.note,
+            calories: vec![],
+        }
+    }
+
+    /// Finds the calorie entry to use for this ingredient, along with the factor to convert this
+    /// usage's units to the entry's units.
+    pub fn calorie_source(&self) -> Result<(&IngredientCaloriesEntry, f32), String> {
+        use unit_conversion::{conversion_factor, conversion_factor_with_density};
+
+        for c in &self.calories {
+            if c.quantity_units == self.quantity_units {
+                return Ok((c, 1.0));
+            }
+        }
+        // Entries of the same kind are preferred, since a density is only approximate.
+        for c in &self.calories {
+            if let (Some(a), Some(b)) = (self.quantity_units, c.quantity_units) {
+                if let Ok(factor) = conversion_factor(a, b) {
+                    return Ok((c, factor));
+                }
+            }
+        }
+        for c in &self.calories {
+            if let (Some(a), Some(b)) = (self.quantity_units, c.quantity_units) {
+                let density = self.ingredient.density;
+                if let Ok(factor) = conversion_factor_with_density(a, b, density) {
+                    return Ok((c, factor));
+                }
+            }
+        }
+        let available: Vec<_> = self.calories.iter().map(|c| c.quantity_units).collect();
+        Err(missing_calories_reason(self.quantity_units, &available))
+    }
+
+    pub fn calories(&self) -> Option<f32> {
+        let (c, factor) = self.calorie_source().ok()?;
+        Some(c.calories * factor * self.quantity / c.quantity)
+    }
+
+    /// Why `calories` can't be calculated, if it can't.
+    pub fn missing_calories(&self) -> Option<String> {
+        self.calorie_source().err()
+    }
+
+    /// What this much of the ingredient costs, going by its price.
+    pub fn cost(&self) -> Result<f32, String> {
+        use unit_conversion::conversion_factor_with_density;
+
+        let Some(price) = self.ingredient.price else {
+            return Err("no price for this ingredient".into());
+        };
+        let factor = match (self.quantity_units, self.ingredient.price_units) {
+            (None, None) => 1.0,
+            (Some(a), Some(b)) => conversion_factor_with_density(a, b, self.ingredient.density)
+                .map_err(|e| e.to_string())?,
+            (None, Some(b)) => {
+                return Err(format!(
+                    "quantity has no units, the price is per {}",
+                    b.as_str()
+                ))
+            }
+            (Some(a), None) => {
+                return Err(format!(
+                    "quantity is in {}, the price is per item",
+                    a.as_str()
+                ))
+            }
+        };
+        Ok(price * factor * self.quantity)
+    }
+}
+
+/// What a recipe costs to make as written, or the names of the ingredients with costs that can't
+/// be worked out, along with why.
+pub fn recipe_cost<'a>(
+    ingredients: impl IntoIterator<Item = &'a RecipeIngredient>,
+) -> Result<f32, Vec<(String, String)>> {
+    let mut total = 0.0;
+    let mut missing = vec![];
+    for i in ingredients {
+        match i.cost() {
+            Ok(cost) => total += cost,
+            Err(reason) => missing.push((i.ingredient.name.clone(), reason)),
+        }
+    }
+    if missing.is_empty() {
+        Ok(total)
+    } else {
+        Err(missing)
+    }
+}
+
+/// The sum of the calories of the given ingredients. Ingredients with unknown calories are
+/// counted as zero.
+pub fn total_calories<'a>(ingredients: impl IntoIterator<Item = &'a RecipeIngredient>) -> f32 {
+    let total = ingredients
+        .into_iter()
+        .filter_map(|i| i.calories())
+        .sum::<f32>();
+    // An empty sum of floats is -0.0
+    if total == -0.0 {
+        0.0
+    } else {
+        total
+    }
+}
+
+/// Total calories for each day, given the ingredients of the recipe scheduled on that day.
+pub fn calories_by_day<Day: Copy + Eq + Hash>(
+    ingredients: &[(Day, RecipeIngredient)],
+) -> HashMap<Day, f32> {
+    let mut days: HashMap<_, Vec<_>> = HashMap::new();
+    for (day, ingredient) in ingredients {
+        days.entry(*day).or_default().push(ingredient);
+    }
+    days.into_iter()
+        .map(|(day, ingredients)| (day, total_calories(ingredients)))
+        .collect()
+}
+
+pub fn missing_calories_reason(
+    units: Option<IngredientMeasurement>,
+    available: &[Option<IngredientMeasurement>],
+) -> String {
+    use unit_conversion::MeasurementKind;
+
+    if available.is_empty() {
+        return "no calorie information for this ingredient".into();
+    }
+    let mut available_units: Vec<_> = available
+        .iter()
+        .map(|u| u.map(|u| u.as_str()).unwrap_or("no units"))
+        .collect();
+    available_units.dedup();
+    let available_units = available_units.join(", ");
+
+    let Some(units) = units else {
+        return format!(
+            "quantity has no units, calorie information is only given for {available_units}"
+        );
+    };
+    let kind = MeasurementKind::from(units);
+    let other_kinds: Vec<_> = available
+        .iter()
+        .flatten()
+        .map(|u| MeasurementKind::from(*u))
+        .filter(|k| *k != kind)
+        .collect();
+    if let Some(other_kind) = other_kinds.first() {
+        format!(
+            "{} is a {kind} but calorie information is only given by {other_kind} \
+            ({kind} vs {other_kind}, no density)",
+            units.as_str()
+        )
+    } else {
+        format!(
+            "{} can't be converted, calorie information is only given for {available_units}",
+            units.as_str()
+        )
+    }
+}
+
+#[test]
+fn missing_calories_reason_test() {
+    use IngredientMeasurement::*;
+
+    assert_eq!(
+        missing_calories_reason(Some(Cups), &[]),
+        "no calorie information for this ingredient"
+    );
+    assert_eq!(
+        missing_calories_reason(None, &[Some(Grams), Some(Grams), Some(Cups)]),
+        "quantity has no units, calorie information is only given for g, cups"
+    );
+    assert_eq!(
+        missing_calories_reason(Some(Cups), &[Some(Grams)]),
+        "cups is a volume but calorie information is only given by weight \
+        (volume vs weight, no density)"
+    );
+    assert_eq!(
+        missing_calories_reason(Some(Pounds), &[None]),
+        "lbs. can't be converted, calorie information is only given for no units"
+    );
+}
@@ -0,0 +1,74 @@
+//! Share codes, which carry a whole recipe in a short piece of text that can be sent in a message
+//! or scanned as a QR code. The recipe is written as Cooklang, compressed, and base64 encoded.
+
+use crate::cooklang;
+use crate::database::models::Recipe;
+use crate::recipe::RecipeIngredient;
+use base64::Engine as _;
+use std::io::{Read as _, Write as _};
+
+/// Starts every share code, so they look like a link and can be told apart from other text.
+pub const PREFIX: &str = "recipe-manager://share/";
+
+/// The most text a share code is allowed to expand to, so a bad one can't use up all the memory.
+const MAX_LENGTH: u64 = 1 << 20;
+
+const ENGINE: base64::engine::GeneralPurpose = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+pub fn encode_share_code(
+    recipe: &Recipe,
+    category: &str,
+    ingredients: &[RecipeIngredient],
+) -> String {
+    let text = cooklang::recipe_to_cooklang(recipe, category, ingredients);
+    let mut encoder = flate2::write::DeflateEncoder::new(vec![], flate2::Compression::best());
+    encoder
+        .write_all(text.as_bytes())
+        .expect("writing to a Vec can't fail");
+    let compressed = encoder.finish().expect("writing to a Vec can't fail");
+    format!("{PREFIX}{}", ENGINE.encode(compressed))
+}
+
+/// The Cooklang text of the recipe in a share code. The prefix can be left off, and any
+/// whitespace from the code being wrapped in a message is ignored.
+pub fn decode_share_code(code: &str) -> crate::Result<String> {
+    let code = code.trim();
+    let code: String = code
+        .strip_prefix(PREFIX)
+        .unwrap_or(code)
+        .split_whitespace()
+        .collect();
+    let compressed = ENGINE.decode(code).map_err(|_| "that isn't a share code")?;
+    let mut text = String::new();
+    flate2::read::DeflateDecoder::new(&compressed[..])
+        .take(MAX_LENGTH)
+        .read_to_string(&mut text)
+        .map_err(|_| "the share code is damaged or incomplete")?;
+    Ok(text)
+}
+
+#[test]
+fn share_code_test() {
+    use crate::database::fixture::Fixture;
+    use crate::query;
+
+    let mut f = Fixture::open();
+    let (recipe, category, ingredients) = query::get_recipe(&mut f.conn, f.soup).unwrap();
+    let code = encode_share_code(&recipe, &category, &ingredients);
+    assert!(code.starts_with(PREFIX));
+    let is_url_safe = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    assert!(code[PREFIX.len()..].chars().all(is_url_safe));
+
+    let text = cooklang::recipe_to_cooklang(&recipe, &category, &ingredients);
+    assert_eq!(decode_share_code(&code).unwrap(), text);
+
+    // Wrapped across lines in an email, without the prefix.
+    let wrapped = code.as_bytes()[PREFIX.len()..]
+        .chunks(20)
+        .map(|c| std::str::from_utf8(c).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(decode_share_code(&wrapped).unwrap(), text);
+
+    assert!(decode_share_code("hello there!").is_err());
+}
@@ -0,0 +1,805 @@
+//! Keeping the recipe book in step between machines through a folder which something like Dropbox
+//! or Syncthing keeps in sync.
+//!
+//! Each machine writes a copy of its database into the folder, named after the machine, and never
+//! touches the other machines' copies, so the sync service never sees two machines change the same
+//! file. At startup the changes in the other copies are merged into the database here. Every change
+//! is journaled in the `change_log` table, which tells which rows each side changed since they last
+//! merged; a row changed on both sides is a conflict for the user to settle.
+//!
+//! Rows are matched by their key. Two machines adding something at the same time can give the rows
+//! the same id, or the same name where names can't repeat, so before merging, the machine whose id
+//! sorts later makes room: its own row moves to a new id, or is folded into the other machine's row
+//! if they're the same thing. The machine whose id sorts first leaves the other one's changes until
+//! its copy shows that's been done.
+
+use crate::database;
+use crate::query;
+use crate::Result;
+use diesel::connection::SimpleConnection as _;
+use diesel::sql_types::{BigInt, Bool, Nullable, Text};
+use diesel::{Connection as _, QueryableByName, RunQueryDsl as _};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const MACHINE_ID: &str = "sync_machine_id";
+
+#[derive(Debug, PartialEq)]
+struct SyncedTable {
+    name: &'static str,
+    key: &'static str,
+    /// A column besides the key which can't have the same value in two rows.
+    unique: Option<&'static str>,
+    /// Selects what the row is, for telling the user about a conflict. `{db}` is replaced with the
+    /// database the row is in.
+    describe: &'static str,
+}
+
+/// Every table of the recipe book. Settings are left out since they belong to each machine, and so
+/// are the shopping list check marks, which only matter for the week.
+const TABLES: &[SyncedTable] = &[
+    SyncedTable {
+        name: "calendar",
+        key: "day",
+        unique: None,
+        describe: "SELECT 'the calendar on ' || day AS value FROM {db}.calendar WHERE day = ?1",
+    },
+    SyncedTable {
+        name: "cook_journal",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'the journal for ' || r.name || ' on ' || j.day AS value \
+            FROM {db}.cook_journal j JOIN {db}.recipes r ON r.id = j.recipe_id WHERE j.id = ?1",
+    },
+    SyncedTable {
+        name: "freezer",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'the frozen ' || r.name || ' from ' || f.frozen AS value \
+            FROM {db}.freezer f JOIN {db}.recipes r ON r.id = f.recipe_id WHERE f.id = ?1",
+    },
+    SyncedTable {
+        name: "ingredient_calories",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'the calories of ' || i.name AS value FROM {db}.ingredient_calories c \
+            JOIN {db}.ingredients i ON i.id = c.ingredient_id WHERE c.id = ?1",
+    },
+    SyncedTable {
+        name: "ingredient_categories",
+        key: "id",
+        unique: Some("name"),
+        describe: "SELECT 'the ingredient category ' || name AS value \
+            FROM {db}.ingredient_categories WHERE id = ?1",
+    },
+    SyncedTable {
+        name: "ingredient_components",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'a component of ' || i.name AS value \
+            FROM {db}.ingredient_components c \
+            JOIN {db}.ingredients i ON i.id = c.compound_id WHERE c.id = ?1",
+    },
+    SyncedTable {
+        name: "ingredient_compounds",
+        key: "ingredient_id",
+        unique: None,
+        describe: "SELECT 'what ' || i.name || ' is made of' AS value \
+            FROM {db}.ingredient_compounds c \
+            JOIN {db}.ingredients i ON i.id = c.ingredient_id WHERE c.ingredient_id = ?1",
+    },
+    SyncedTable {
+        name: "ingredient_usages",
+        key: "id",
+        unique: None,
+        describe: "SELECT i.name || ' in ' || r.name AS value FROM {db}.ingredient_usages u \
+            JOIN {db}.ingredients i ON i.id = u.ingredient_id \
+            JOIN {db}.recipes r ON r.id = u.recipe_id WHERE u.id = ?1",
+    },
+    SyncedTable {
+        name: "ingredients",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'the ingredient ' || name AS value FROM {db}.ingredients WHERE id = ?1",
+    },
+    SyncedTable {
+        name: "recipe_categories",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'the category ' || name AS value FROM {db}.recipe_categories \
+            WHERE id = ?1",
+    },
+    SyncedTable {
+        name: "recipe_photos",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'a photo of ' || r.name AS value FROM {db}.recipe_photos p \
+            JOIN {db}.recipes r ON r.id = p.recipe_id WHERE p.id = ?1",
+    },
+    SyncedTable {
+        name: "recipe_steps",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'a step of ' || r.name AS value FROM {db}.recipe_steps s \
+            JOIN {db}.recipes r ON r.id = s.recipe_id WHERE s.id = ?1",
+    },
+    SyncedTable {
+        name: "recipes",
+        key: "id",
+        unique: None,
+        describe: "SELECT 'the recipe ' || name AS value FROM {db}.recipes WHERE id = ?1",
+    },
+];
+
+fn synced_table(name: &str) -> Option<&'static SyncedTable> {
+    TABLES.iter().find(|t| t.name == name)
+}
+
+#[derive(QueryableByName)]
+struct TextRow {
+    #[diesel(sql_type = Text)]
+    value: String,
+}
+
+#[derive(QueryableByName)]
+struct OptionalTextRow {
+    #[diesel(sql_type = Nullable<Text>)]
+    value: Option<String>,
+}
+
+#[derive(QueryableByName)]
+struct NumberRow {
+    #[diesel(sql_type = BigInt)]
+    value: i64,
+}
+
+#[derive(QueryableByName)]
+struct BoolRow {
+    #[diesel(sql_type = Bool)]
+    value: bool,
+}
+
+#[derive(QueryableByName, PartialEq, Eq, Hash)]
+struct Change {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    row_key: String,
+}
+
+/// A column of `table_name` which refers to another table.
+#[derive(QueryableByName)]
+struct Reference {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    column_name: String,
+}
+
+/// The name this machine's copy goes by in the sync folder, made up the first time it's needed
+/// from the computer's name and the time.
+pub fn machine_id(conn: &mut database::Connection) -> diesel::QueryResult<String> {
+    if let Some(id) = query::get_setting(conn, MACHINE_ID)? {
+        return Ok(id);
+    }
+    let host: String = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "machine".into())
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let id = format!("{host}-{:x}", nanos as u64 ^ u64::from(std::process::id()));
+    query::set_setting(conn, MACHINE_ID, Some(&id))?;
+    Ok(id)
+}
+
+fn copy_path(folder: &Path, machine: &str) -> PathBuf {
+    folder.join(format!("{machine}.sqlite"))
+}
+
+/// Writes this machine's copy of the database into the sync folder.
+pub fn export(conn: &mut database::Connection, folder: &Path) -> Result<()> {
+    let machine = machine_id(conn)?;
+    let path = copy_path(folder, &machine);
+    // Written next to the old copy first, so the sync service never picks up half of it.
+    let partial = path.with_extension("sqlite.partial");
+    if partial.exists() {
+        std::fs::remove_file(&partial)?;
+    }
+    diesel::sql_query("VACUUM INTO ?")
+        .bind::<Text, _>(partial.to_string_lossy())
+        .execute(conn)?;
+    // The settings belong to this machine, and nothing else needs to see them.
+    let mut copy = database::Connection::establish(&partial.to_string_lossy())?;
+    copy.batch_execute("DELETE FROM settings; VACUUM")?;
+    drop(copy);
+    std::fs::rename(&partial, &path)?;
+    Ok(())
+}
+
+/// A row which was changed both here and on another machine since they last merged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub machine: String,
+    /// What the row is, like "the recipe Carrot Soup".
+    pub description: String,
+    pub deleted_here: bool,
+    pub deleted_there: bool,
+    table: &'static SyncedTable,
+    key: String,
+    /// The other machine's last change when the conflict was found.
+    last_change_id: i64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Resolution {
+    #[default]
+    KeepMine,
+    UseTheirs,
+}
+
+#[derive(Default)]
+pub struct Merge {
+    /// How many rows were changed to match the other machines.
+    pub num_changed: usize,
+    pub conflicts: Vec<Conflict>,
+    /// The copies which couldn't be merged, and why.
+    pub errors: Vec<(String, crate::Error)>,
+}
+
+/// Merges the changes from every other machine's copy in the sync folder. Changes which don't
+/// conflict with any made here are applied; the conflicts are returned to be settled with
+/// [`resolve`].
+pub fn merge(conn: &mut database::Connection, folder: &Path) -> Result<Merge> {
+    let machine = machine_id(conn)?;
+    let mut merge = Merge::default();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|e| e != "sqlite") {
+            continue;
+        }
+        let Some(other) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        if other == machine {
+            continue;
+        }
+        let result = with_attached(conn, &path, |conn| {
+            merge_from(conn, &machine, &other, &mut merge.num_changed)
+        });
+        match result {
+            Ok(conflicts) => merge.conflicts.extend(conflicts),
+            Err(error) => merge.errors.push((other, error)),
+        }
+    }
+    Ok(merge)
+}
+
+/// Settles conflicts found by [`merge`]. The rows to be kept as they are here are taken on by the
+/// other machine the next time it merges.
+pub fn resolve(
+    conn: &mut database::Connection,
+    folder: &Path,
+    resolutions: &[(Conflict, Resolution)],
+) -> Result<()> {
+    let mut by_machine: BTreeMap<&str, Vec<&(Conflict, Resolution)>> = BTreeMap::new();
+    for r in resolutions {
+        by_machine.entry(r.0.machine.as_str()).or_default().push(r);
+    }
+    for (machine, resolutions) in by_machine {
+        with_attached(conn, &copy_path(folder, machine), |conn| {
+            without_journaling(conn, |conn| {
+                for (conflict, resolution) in &resolutions {
+                    if *resolution == Resolution::UseTheirs {
+                        copy_row(conn, conflict.table, &conflict.key)?;
+                    }
+                }
+                let last_change_id = resolutions.iter().map(|r| r.0.last_change_id).max();
+                set_last_merged(conn, machine, last_change_id.unwrap_or(0))
+            })
+        })?;
+    }
+    Ok(())
+}
+
+/// Runs `f` with another machine's copy attached as the `theirs` database.
+fn with_attached<T>(
+    conn: &mut database::Connection,
+    path: &Path,
+    f: impl FnOnce(&mut database::Connection) -> Result<T>,
+) -> Result<T> {
+    diesel::sql_query("ATTACH DATABASE ? AS theirs")
+        .bind::<Text, _>(path.to_string_lossy())
+        .execute(conn)?;
+    let result = f(conn);
+    conn.batch_execute("DETACH DATABASE theirs")?;
+    result
+}
+
+/// Runs `f` in a transaction, leaving the changes it makes out of the journal, since they were
+/// made on another machine already.
+fn without_journaling<T>(
+    conn: &mut database::Connection,
+    f: impl FnOnce(&mut database::Connection) -> Result<T>,
+) -> Result<T> {
+    conn.transaction(|conn| {
+        // Rows are copied one at a time, so the foreign keys only hold once they all are.
+        conn.batch_execute("PRAGMA defer_foreign_keys = ON")?;
+        let before = last_change_id(conn, "main")?;
+        let result = f(conn)?;
+        diesel::sql_query("DELETE FROM main.change_log WHERE id > ?")
+            .bind::<BigInt, _>(before)
+            .execute(conn)?;
+        Ok(result)
+    })
+}
+
+fn merge_from(
+    conn: &mut database::Connection,
+    machine: &str,
+    other: &str,
+    num_changed: &mut usize,
+) -> Result<Vec<Conflict>> {
+    if schema_version(conn, "main")? != schema_version(conn, "theirs")? {
+        return Err("it was made by a different version of Recipe Manager".into());
+    }
+    let merged_here = last_merged(conn, "main", other)?;
+    let merged_there = last_merged(conn, "theirs", machine)?;
+    let last_change_id = last_change_id(conn, "theirs")?;
+    let theirs = changes(conn, "theirs", merged_here)?;
+    let mine: HashSet<Change> = changes(conn, "main", merged_there)?.into_iter().collect();
+
+    let key_clashes = key_clashes(conn, &theirs, merged_here, merged_there)?;
+    if machine < other && !(key_clashes.is_empty() && unique_clashes(conn, &theirs)?.is_empty()) {
+        log::info!("leaving the changes from {other} until it makes room for the ones from here");
+        return Ok(vec![]);
+    }
+
+    conn.transaction(|conn| {
+        // Making room is journaled, since the other machine needs to take it on too.
+        conn.batch_execute("PRAGMA defer_foreign_keys = ON")?;
+        let mut moved = HashSet::new();
+        for (table, key) in key_clashes {
+            move_row(conn, table, &key)?;
+            moved.insert((table.name, key));
+        }
+        for (table, mine, theirs) in unique_clashes(conn, &theirs)? {
+            let SyncedTable { name, key: k, .. } = table;
+            repoint(conn, table, &mine, &theirs)?;
+            diesel::sql_query(format!("DELETE FROM main.{name} WHERE {k} = ?"))
+                .bind::<Text, _>(&mine)
+                .execute(conn)?;
+        }
+
+        without_journaling(conn, |conn| {
+            let mut conflicts = vec![];
+            let mut seen = HashSet::new();
+            for change in theirs {
+                let Some(table) = synced_table(&change.table_name) else {
+                    continue;
+                };
+                if !seen.insert((table.name, change.row_key.clone()))
+                    || rows_equal(conn, table, &change.row_key)?
+                {
+                    continue;
+                }
+                if mine.contains(&change) && !moved.contains(&(table.name, change.row_key.clone()))
+                {
+                    conflicts.push(Conflict {
+                        machine: other.into(),
+                        description: describe(conn, table, &change.row_key)?,
+                        deleted_here: !row_exists(conn, "main", table, &change.row_key)?,
+                        deleted_there: !row_exists(conn, "theirs", table, &change.row_key)?,
+                        table,
+                        key: change.row_key,
+                        last_change_id,
+                    });
+                } else {
+                    copy_row(conn, table, &change.row_key)?;
+                    *num_changed += 1;
+                }
+            }
+            if conflicts.is_empty() {
+                set_last_merged(conn, other, last_change_id)?;
+            }
+            Ok(conflicts)
+        })
+    })
+}
+
+/// Rows which were added both here and there under the same key since the machines last merged.
+/// Only rows with made up ids can be told apart like this; two rows for the same day on the
+/// calendar, say, are the same row changed on both machines.
+fn key_clashes(
+    conn: &mut database::Connection,
+    theirs: &[Change],
+    merged_here: i64,
+    merged_there: i64,
+) -> Result<Vec<(&'static SyncedTable, String)>> {
+    let mut clashes = vec![];
+    let mut seen = HashSet::new();
+    for change in theirs {
+        let Some(table) = synced_table(&change.table_name) else {
+            continue;
+        };
+        let key = &change.row_key;
+        if table.key != "id" || !seen.insert((table.name, key)) {
+            continue;
+        }
+        if first_change(conn, "theirs", table, key)? > merged_here
+            && first_change(conn, "main", table, key)? > merged_there
+            && row_exists(conn, "main", table, key)?
+            && row_exists(conn, "theirs", table, key)?
+            && !rows_equal(conn, table, key)?
+        {
+            clashes.push((table, key.clone()));
+        }
+    }
+    Ok(clashes)
+}
+
+/// Rows which were changed there to have the same value as a different row here in a column which
+/// can't repeat, like an ingredient category added on both machines. Returns the key here and the
+/// key there of each.
+fn unique_clashes(
+    conn: &mut database::Connection,
+    theirs: &[Change],
+) -> Result<Vec<(&'static SyncedTable, String, String)>> {
+    let mut clashes = vec![];
+    let mut seen = HashSet::new();
+    for change in theirs {
+        let Some(
+            table @ SyncedTable {
+                name,
+                key: k,
+                unique: Some(unique),
+                ..
+            },
+        ) = synced_table(&change.table_name)
+        else {
+            continue;
+        };
+        if !seen.insert((table.name, &change.row_key)) {
+            continue;
+        }
+        let rows: Vec<TextRow> = diesel::sql_query(format!(
+            "SELECT CAST(m.{k} AS TEXT) AS value FROM main.{name} m \
+            JOIN theirs.{name} t ON m.{unique} = t.{unique} WHERE t.{k} = ?1 AND m.{k} IS NOT t.{k}"
+        ))
+        .bind::<Text, _>(&change.row_key)
+        .load(conn)?;
+        if let Some(row) = rows.into_iter().next() {
+            clashes.push((table, row.value, change.row_key.clone()));
+        }
+    }
+    Ok(clashes)
+}
+
+/// The first change to the row in `db`'s journal, or 0 if there isn't any.
+fn first_change(
+    conn: &mut database::Connection,
+    db: &str,
+    table: &SyncedTable,
+    key: &str,
+) -> Result<i64> {
+    let row: NumberRow = diesel::sql_query(format!(
+        "SELECT COALESCE(MIN(id), 0) AS value FROM {db}.change_log \
+        WHERE table_name = ?1 AND row_key = ?2"
+    ))
+    .bind::<Text, _>(table.name)
+    .bind::<Text, _>(key)
+    .get_result(conn)?;
+    Ok(row.value)
+}
+
+/// Moves the row here to a key which isn't used on either machine.
+fn move_row(conn: &mut database::Connection, table: &SyncedTable, key: &str) -> Result<()> {
+    let SyncedTable { name, key: k, .. } = table;
+    let row: NumberRow = diesel::sql_query(format!(
+        "SELECT MAX(\
+            (SELECT COALESCE(MAX({k}), 0) FROM main.{name}), \
+            (SELECT COALESCE(MAX({k}), 0) FROM theirs.{name})\
+        ) + 1 AS value"
+    ))
+    .get_result(conn)?;
+    let new_key = row.value.to_string();
+    diesel::sql_query(format!("UPDATE main.{name} SET {k} = ?2 WHERE {k} = ?1"))
+        .bind::<Text, _>(key)
+        .bind::<Text, _>(&new_key)
+        .execute(conn)?;
+    repoint(conn, table, key, &new_key)
+}
+
+/// Points everything here which refers to the row `from` of the table at the row `to` instead.
+fn repoint(
+    conn: &mut database::Connection,
+    table: &SyncedTable,
+    from: &str,
+    to: &str,
+) -> Result<()> {
+    let references: Vec<Reference> = diesel::sql_query(
+        "SELECT m.name AS table_name, f.\"from\" AS column_name \
+        FROM main.sqlite_master m JOIN pragma_foreign_key_list(m.name) f \
+        WHERE m.type = 'table' AND f.\"table\" = ?",
+    )
+    .bind::<Text, _>(table.name)
+    .load(conn)?;
+    for Reference {
+        table_name,
+        column_name,
+    } in references
+    {
+        diesel::sql_query(format!(
+            "UPDATE main.{table_name} SET {column_name} = ?2 WHERE {column_name} = ?1"
+        ))
+        .bind::<Text, _>(from)
+        .bind::<Text, _>(to)
+        .execute(conn)?;
+        // Rows keyed by what they refer to, like what a blend is made of, have moved as well.
+        if let Some(referencing) = synced_table(&table_name).filter(|t| t.key == column_name) {
+            repoint(conn, referencing, from, to)?;
+        }
+    }
+    Ok(())
+}
+
+fn schema_version(conn: &mut database::Connection, db: &str) -> Result<Option<String>> {
+    let row: OptionalTextRow = diesel::sql_query(format!(
+        "SELECT MAX(version) AS value FROM {db}.__diesel_schema_migrations"
+    ))
+    .get_result(conn)?;
+    Ok(row.value)
+}
+
+fn last_change_id(conn: &mut database::Connection, db: &str) -> Result<i64> {
+    let row: NumberRow = diesel::sql_query(format!(
+        "SELECT COALESCE(MAX(id), 0) AS value FROM {db}.change_log"
+    ))
+    .get_result(conn)?;
+    Ok(row.value)
+}
+
+/// The last of `machine`'s changes which have been merged into `db`.
+fn last_merged(conn: &mut database::Connection, db: &str, machine: &str) -> Result<i64> {
+    let row: NumberRow = diesel::sql_query(format!(
+        "SELECT COALESCE(MAX(last_change_id), 0) AS value FROM {db}.sync_state WHERE machine = ?"
+    ))
+    .bind::<Text, _>(machine)
+    .get_result(conn)?;
+    Ok(row.value)
+}
+
+fn set_last_merged(conn: &mut database::Connection, machine: &str, change_id: i64) -> Result<()> {
+    diesel::sql_query(
+        "INSERT INTO main.sync_state (machine, last_change_id) VALUES (?1, ?2) \
+        ON CONFLICT (machine) DO UPDATE SET last_change_id = excluded.last_change_id",
+    )
+    .bind::<Text, _>(machine)
+    .bind::<BigInt, _>(change_id)
+    .execute(conn)?;
+    Ok(())
+}
+
+/// The rows changed in `db` after the change `after`.
+fn changes(conn: &mut database::Connection, db: &str, after: i64) -> Result<Vec<Change>> {
+    Ok(diesel::sql_query(format!(
+        "SELECT table_name, row_key FROM {db}.change_log WHERE id > ? ORDER BY id"
+    ))
+    .bind::<BigInt, _>(after)
+    .load(conn)?)
+}
+
+fn rows_equal(conn: &mut database::Connection, table: &SyncedTable, key: &str) -> Result<bool> {
+    let SyncedTable { name, key: k, .. } = table;
+    let row: BoolRow = diesel::sql_query(format!(
+        "SELECT NOT EXISTS (\
+            SELECT * FROM main.{name} WHERE {k} = ?1 \
+            EXCEPT SELECT * FROM theirs.{name} WHERE {k} = ?1\
+        ) AND NOT EXISTS (\
+            SELECT * FROM theirs.{name} WHERE {k} = ?1 \
+            EXCEPT SELECT * FROM main.{name} WHERE {k} = ?1\
+        ) AS value"
+    ))
+    .bind::<Text, _>(key)
+    .get_result(conn)?;
+    Ok(row.value)
+}
+
+fn row_exists(
+    conn: &mut database::Connection,
+    db: &str,
+    table: &SyncedTable,
+    key: &str,
+) -> Result<bool> {
+    let SyncedTable { name, key: k, .. } = table;
+    let row: BoolRow = diesel::sql_query(format!(
+        "SELECT EXISTS (SELECT * FROM {db}.{name} WHERE {k} = ?) AS value"
+    ))
+    .bind::<Text, _>(key)
+    .get_result(conn)?;
+    Ok(row.value)
+}
+
+fn describe(conn: &mut database::Connection, table: &SyncedTable, key: &str) -> Result<String> {
+    for db in ["main", "theirs"] {
+        let rows: Vec<TextRow> = diesel::sql_query(table.describe.replace("{db}", db))
+            .bind::<Text, _>(key)
+            .load(conn)?;
+        if let Some(row) = rows.into_iter().next() {
+            return Ok(row.value);
+        }
+    }
+    Ok(format!("{} {key}", table.name))
+}
+
+/// Makes the row here match the one in the other machine's copy, deleting it if it's gone there.
+fn copy_row(conn: &mut database::Connection, table: &SyncedTable, key: &str) -> Result<()> {
+    let SyncedTable { name, key: k, .. } = table;
+    let columns: Vec<TextRow> = diesel::sql_query("SELECT name AS value FROM pragma_table_info(?)")
+        .bind::<Text, _>(*name)
+        .load(conn)?;
+    let updates = columns
+        .iter()
+        .map(|c| format!("{0} = excluded.{0}", c.value))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // An upsert rather than a replace, since replacing deletes the row first, which would delete
+    // everything referring to it as well.
+    diesel::sql_query(format!(
+        "INSERT INTO main.{name} SELECT * FROM theirs.{name} WHERE {k} = ?1 \
+        ON CONFLICT ({k}) DO UPDATE SET {updates}"
+    ))
+    .bind::<Text, _>(key)
+    .execute(conn)?;
+    diesel::sql_query(format!(
+        "DELETE FROM main.{name} WHERE {k} = ?1 \
+        AND NOT EXISTS (SELECT * FROM theirs.{name} WHERE {k} = ?1)"
+    ))
+    .bind::<Text, _>(key)
+    .execute(conn)?;
+    Ok(())
+}
+
+#[test]
+fn merge_test() {
+    use crate::database::fixture::{test_connection, Fixture};
+
+    let folder = std::env::temp_dir().join(format!("sync-test-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    let name = |conn: &mut database::Connection, id| query::get_recipe(conn, id).unwrap().0.name;
+
+    let mut a = Fixture::open();
+    query::set_setting(&mut a.conn, MACHINE_ID, Some("a")).unwrap();
+    export(&mut a.conn, &folder).unwrap();
+    // The copy leaves out the settings, which can have passwords in them.
+    let copy = copy_path(&folder, "a");
+    let mut copy = database::Connection::establish(&copy.to_string_lossy()).unwrap();
+    assert_eq!(query::get_setting(&mut copy, MACHINE_ID).unwrap(), None);
+
+    // A new machine takes on everything, without journaling it as its own changes.
+    let mut b = test_connection();
+    query::set_setting(&mut b, MACHINE_ID, Some("b")).unwrap();
+    let merged = merge(&mut b, &folder).unwrap();
+    assert!(merged.conflicts.is_empty() && merged.errors.is_empty());
+    assert_eq!(name(&mut b, a.soup), "Carrot Soup");
+    assert!(changes(&mut b, "main", 0).unwrap().is_empty());
+    export(&mut b, &folder).unwrap();
+
+    query::edit_recipe_name(&mut a.conn, a.soup, "Soup A").unwrap();
+    query::edit_recipe_name(&mut a.conn, a.tart, "Tart A").unwrap();
+    query::edit_recipe_name(&mut b, a.soup, "Soup B").unwrap();
+    query::edit_recipe_name(&mut b, a.cake, "Cake B").unwrap();
+    export(&mut a.conn, &folder).unwrap();
+    export(&mut b, &folder).unwrap();
+
+    let merged = merge(&mut b, &folder).unwrap();
+    assert_eq!(merged.num_changed, 1);
+    assert_eq!(name(&mut b, a.tart), "Tart A");
+    let [conflict] = &merged.conflicts[..] else {
+        panic!("expected one conflict, got {:?}", merged.conflicts);
+    };
+    assert_eq!(conflict.machine, "a");
+    assert_eq!(conflict.description, "the recipe Soup B");
+    assert!(!conflict.deleted_here && !conflict.deleted_there);
+
+    // Until it's settled, the conflict is found again.
+    assert_eq!(merge(&mut b, &folder).unwrap().conflicts, merged.conflicts);
+    resolve(
+        &mut b,
+        &folder,
+        &[(conflict.clone(), Resolution::UseTheirs)],
+    )
+    .unwrap();
+    assert_eq!(name(&mut b, a.soup), "Soup A");
+    assert!(merge(&mut b, &folder).unwrap().conflicts.is_empty());
+    export(&mut b, &folder).unwrap();
+
+    // The other machine takes on the changes which were kept.
+    let merged = merge(&mut a.conn, &folder).unwrap();
+    assert!(merged.conflicts.is_empty());
+    assert_eq!(name(&mut a.conn, a.cake), "Cake B");
+    assert_eq!(name(&mut a.conn, a.soup), "Soup A");
+
+    // Deleting a recipe deletes its ingredients on the other machine as well.
+    query::delete_recipe(&mut a.conn, a.tart).unwrap();
+    export(&mut a.conn, &folder).unwrap();
+    merge(&mut b, &folder).unwrap();
+    assert!(query::get_recipe(&mut b, a.tart).is_err());
+    assert!(query::get_ingredients_for_recipe(&mut b, a.tart)
+        .unwrap()
+        .is_empty());
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn merge_added_on_both_test() {
+    use crate::database::fixture::{test_connection, Fixture};
+
+    let folder = std::env::temp_dir().join(format!("sync-added-test-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+
+    let mut a = Fixture::open();
+    query::set_setting(&mut a.conn, MACHINE_ID, Some("a")).unwrap();
+    export(&mut a.conn, &folder).unwrap();
+    let mut b = test_connection();
+    query::set_setting(&mut b, MACHINE_ID, Some("b")).unwrap();
+    merge(&mut b, &folder).unwrap();
+    export(&mut b, &folder).unwrap();
+    merge(&mut a.conn, &folder).unwrap();
+
+    // Both machines add a recipe, which get the same id, and an ingredient category by the same
+    // name, which doesn't.
+    let pie = query::add_recipe(&mut a.conn, "Pie", a.dessert).unwrap();
+    query::update_ingredient(&mut a.conn, a.sugar, "sugar", "Baking").unwrap();
+    let stew = query::add_recipe(&mut b, "Stew", a.dinner).unwrap();
+    assert_eq!(pie, stew);
+    query::add_recipe_ingredient(&mut b, stew, a.onion, 2.0).unwrap();
+    query::update_ingredient(&mut b, a.salt, "salt", "Spices").unwrap();
+    query::update_ingredient(&mut b, a.onion, "onion", "Baking").unwrap();
+    export(&mut a.conn, &folder).unwrap();
+    export(&mut b, &folder).unwrap();
+
+    // The machine whose id sorts first waits for the other one to make room.
+    let merged = merge(&mut a.conn, &folder).unwrap();
+    assert_eq!(merged.num_changed, 0);
+    assert!(merged.conflicts.is_empty() && merged.errors.is_empty());
+    let merged = merge(&mut b, &folder).unwrap();
+    assert!(merged.conflicts.is_empty() && merged.errors.is_empty());
+    export(&mut b, &folder).unwrap();
+    let merged = merge(&mut a.conn, &folder).unwrap();
+    assert!(merged.conflicts.is_empty() && merged.errors.is_empty());
+
+    let mut recipes = vec![];
+    for conn in [&mut a.conn, &mut b] {
+        let all: Vec<_> = query::get_all_recipes(conn)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.id, r.name))
+            .collect();
+        let (stew, _) = all.iter().find(|(_, name)| name == "Stew").unwrap();
+        let ingredients = query::get_ingredients_for_recipe(conn, *stew).unwrap();
+        assert_eq!(ingredients.len(), 1);
+        assert_eq!(ingredients[0].1.name, "onion");
+
+        let categories = query::get_ingredient_category_names(conn).unwrap();
+        assert_eq!(categories.len(), 2);
+        let mut category = |name| {
+            let ingredient = query::get_ingredient_by_name(conn, name).unwrap().unwrap();
+            categories[&ingredient.category_id.unwrap()].clone()
+        };
+        assert_eq!(category("sugar"), "Baking");
+        assert_eq!(category("onion"), "Baking");
+        assert_eq!(category("salt"), "Spices");
+        recipes.push(all);
+    }
+    assert_eq!(recipes[0], recipes[1]);
+    assert!(recipes[0].contains(&(pie, "Pie".into())));
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
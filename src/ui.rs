@@ -3,80 +3,384 @@
 mod about;
 mod calendar;
 mod category_list;
+mod cook_journal;
+mod cook_mode;
+mod description_template;
+mod diagnostics;
+mod diet;
+mod errors;
+mod freezer;
+#[cfg(feature = "rtf")]
 mod generate_rtf;
+mod i18n;
+#[cfg(feature = "legacy-import")]
 mod import;
+mod in_season;
 mod ingredient_calories;
+mod ingredient_category_list;
+mod ingredient_components;
+mod ingredient_duplicates;
 mod ingredient_list;
 mod ingredient_replace;
-mod query;
+mod integrity;
+#[cfg(feature = "keep-awake")]
+mod keep_awake;
+#[cfg(feature = "rtf")]
+mod leftovers;
+mod log_viewer;
+mod markdown;
+#[cfg(feature = "rtf")]
+mod menu_template;
+mod nutrition;
+mod nutrition_audit;
+mod nutrition_dashboard;
+#[cfg(feature = "nutrition-lookup")]
+mod nutrition_lookup;
+mod paged;
+mod prep_plan;
+#[cfg(feature = "print")]
+mod print;
 mod recipe;
+mod recipe_browser;
+mod recipe_costs;
 mod recipe_list;
+mod recipe_preview;
+mod recipe_steps;
+#[cfg(feature = "sample-recipes")]
+mod sample_recipes;
 mod search;
-mod unit_conversion;
+mod settings;
+#[cfg(feature = "share")]
+mod share;
+mod shopping_comparison;
+mod shortcuts;
+mod startup;
+mod stats;
+#[cfg(feature = "sync")]
+mod sync;
+mod try_next;
+mod undo;
+mod welcome;
 
-use crate::database;
-use crate::database::models::{IngredientHandle, IngredientId, RecipeCategoryId, RecipeId};
+use crate::instance::{Access, InstanceLock};
 use about::AboutWindow;
 use calendar::CalendarWindow;
 use category_list::CategoryListWindow;
+use cook_journal::CookJournalPromptWindow;
+use cook_mode::CookModeWindow;
+use diagnostics::Diagnostics;
+use errors::QueryResultExt as _;
+use freezer::FreezerWindow;
+#[cfg(feature = "legacy-import")]
 use import::ImportWindow;
-use ingredient_calories::IngredientCaloriesWindow;
-use ingredient_list::IngredientListWindow;
+use in_season::InSeasonWindow;
+use ingredient_category_list::IngredientCategoryListWindow;
+use ingredient_duplicates::IngredientDuplicatesWindow;
+use ingredient_list::{IngredientListWindow, IngredientWindows};
 use ingredient_replace::IngredientReplaceWindow;
+use integrity::IntegrityWindow;
+use log_viewer::LogWindow;
+use nutrition_audit::NutritionAuditWindow;
+use nutrition_dashboard::NutritionDashboardWindow;
 use recipe::RecipeWindow;
+use recipe_browser::RecipeBrowserWindow;
+use recipe_costs::RecipeCostsWindow;
 use recipe_list::RecipeListWindow;
+use recipe_manager::database;
+use recipe_manager::database::models::{
+    IngredientHandle, IngredientId, RecipeCategoryId, RecipeId,
+};
+use recipe_manager::query;
+use recipe_manager::unit_conversion::UnitChoices;
+use recipe_steps::RecipeStepsWindow;
 use search::{IngredientSearchControl, RecipeSearchWindow, SearchResultsWindow};
+use settings::{RecipeLayout, Settings, SettingsWindow};
+use shopping_comparison::ShoppingComparisonWindow;
+use shortcuts::{Shortcut, ShortcutsWindow};
+use stats::StatsWindow;
 use std::collections::HashMap;
 use std::mem;
+#[cfg(feature = "sync")]
+use sync::SyncConflictsWindow;
+use try_next::TryNextWindow;
+
+pub use errors::install_panic_hook;
+pub use startup::Startup;
+use welcome::WelcomeWindow;
+
+thread_local! {
+    /// Whether motion is reduced, from the settings, for the toasts made away from them.
+    static REDUCE_MOTION: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Toasts go away after the given time, unless motion is reduced, when they stay until clicked.
+pub fn toast_options(seconds: f64) -> egui_toast::ToastOptions {
+    let options = egui_toast::ToastOptions::default().show_icon(true);
+    if REDUCE_MOTION.get() {
+        options.duration(None).show_progress(false)
+    } else {
+        options.duration_in_seconds(seconds)
+    }
+}
 
 pub fn new_error_toast(msg: impl Into<egui::WidgetText>) -> egui_toast::Toast {
     egui_toast::Toast {
         text: msg.into(),
         kind: egui_toast::ToastKind::Error,
-        options: egui_toast::ToastOptions::default()
-            .duration_in_seconds(10.0)
-            .show_progress(false)
-            .show_icon(true),
+        options: toast_options(10.0).show_progress(false),
         ..Default::default()
     }
 }
 
+pub fn new_success_toast(msg: impl Into<egui::WidgetText>) -> egui_toast::Toast {
+    egui_toast::Toast {
+        text: msg.into(),
+        kind: egui_toast::ToastKind::Success,
+        options: toast_options(10.0).show_progress(!REDUCE_MOTION.get()),
+        ..Default::default()
+    }
+}
+
+/// Makes the visuals easier to tell apart: plain backgrounds with text and outlines in the
+/// opposite color.
+fn high_contrast(visuals: &mut egui::Visuals) {
+    let (fg, bg) = if visuals.dark_mode {
+        (egui::Color32::WHITE, egui::Color32::BLACK)
+    } else {
+        (egui::Color32::BLACK, egui::Color32::WHITE)
+    };
+    visuals.override_text_color = Some(fg);
+    visuals.panel_fill = bg;
+    visuals.window_fill = bg;
+    visuals.extreme_bg_color = bg;
+    visuals.faint_bg_color = bg;
+    visuals.window_stroke = egui::Stroke::new(2.0, fg);
+    visuals.selection.stroke = egui::Stroke::new(2.0, fg);
+    let widgets = &mut visuals.widgets;
+    for w in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        w.fg_stroke = egui::Stroke::new(w.fg_stroke.width.max(1.5), fg);
+        w.bg_stroke = egui::Stroke::new(w.bg_stroke.width.max(1.0), fg);
+    }
+}
+
+/// Fits the style of both themes to the accessibility settings. Since it starts over from egui's
+/// defaults, it's only done when they change.
+fn apply_style(ctx: &egui::Context, settings: &Settings) {
+    REDUCE_MOTION.set(settings.reduce_motion);
+
+    let id = egui::Id::new("accessibility style");
+    let wanted = (settings.high_contrast, settings.reduce_motion);
+    if ctx.data(|d| d.get_temp::<(bool, bool)>(id)) == Some(wanted) {
+        return;
+    }
+    ctx.data_mut(|d| d.insert_temp(id, wanted));
+
+    for theme in [egui::Theme::Dark, egui::Theme::Light] {
+        let mut style = egui::Style {
+            visuals: theme.default_visuals(),
+            ..Default::default()
+        };
+        if settings.high_contrast {
+            high_contrast(&mut style.visuals);
+        }
+        if settings.reduce_motion {
+            style.animation_time = 0.0;
+        }
+        ctx.set_style_of(theme, style);
+    }
+}
+
 pub struct RecipeManager {
     category_list: CategoryListWindow,
     conn: database::Connection,
     toasts: egui_toast::Toasts,
+    #[cfg(feature = "legacy-import")]
     import_window: Option<ImportWindow>,
     recipe_lists: HashMap<RecipeCategoryId, RecipeListWindow>,
+    recipe_browser_window: Option<RecipeBrowserWindow>,
     recipes: HashMap<RecipeId, RecipeWindow>,
     ingredient_list_window: Option<IngredientListWindow>,
+    ingredient_category_list_window: Option<IngredientCategoryListWindow>,
     calendar_window: Option<CalendarWindow>,
     search_result_windows: Vec<SearchResultsWindow>,
     next_search_results_window_id: u64,
     recipe_search_window: Option<RecipeSearchWindow>,
-    ingredient_calories_windows: HashMap<IngredientId, IngredientCaloriesWindow>,
+    ingredient_windows: IngredientWindows,
+    recipe_steps_windows: HashMap<RecipeId, RecipeStepsWindow>,
     ingredient_replace_window: Option<IngredientReplaceWindow>,
+    ingredient_duplicates_window: Option<IngredientDuplicatesWindow>,
     about_window: Option<AboutWindow>,
+    shortcuts_window: Option<ShortcutsWindow>,
+    log_window: Option<LogWindow>,
+    cook_mode_window: Option<CookModeWindow>,
+    recipe_tabs: Vec<RecipeId>,
+    selected_recipe_tab: Option<RecipeId>,
+    settings: Settings,
+    settings_window: Option<SettingsWindow>,
+    stats_window: Option<StatsWindow>,
+    nutrition_audit_window: Option<NutritionAuditWindow>,
+    recipe_costs_window: Option<RecipeCostsWindow>,
+    in_season_window: Option<InSeasonWindow>,
+    try_next_window: Option<TryNextWindow>,
+    freezer_window: Option<FreezerWindow>,
+    cook_journal_prompt_window: Option<CookJournalPromptWindow>,
+    shopping_comparison_window: Option<ShoppingComparisonWindow>,
+    nutrition_dashboard_window: Option<NutritionDashboardWindow>,
+    welcome_window: Option<WelcomeWindow>,
+    integrity_window: Option<IntegrityWindow>,
+    #[cfg(feature = "sync")]
+    sync_conflicts_window: Option<SyncConflictsWindow>,
+    crash_message: Option<String>,
+    access: Access,
+    diagnostics: Option<Diagnostics>,
 }
 
 impl RecipeManager {
-    pub fn new(mut conn: database::Connection) -> Self {
-        Self {
+    pub fn new(mut conn: database::Connection, access: Access) -> Self {
+        let settings = Settings::load(&mut conn);
+        #[cfg_attr(not(feature = "sync"), expect(unused_mut))]
+        let mut manager = Self {
+            diagnostics: settings.diagnostics.then(|| Diagnostics::start(&mut conn)),
+            welcome_window: (access.is_read_write()
+                && query::is_recipe_book_empty(&mut conn).unwrap_or_report())
+            .then(WelcomeWindow::new),
+            cook_journal_prompt_window: access
+                .is_read_write()
+                .then(|| CookJournalPromptWindow::new(&mut conn))
+                .flatten(),
+            crash_message: None,
+            access,
             category_list: CategoryListWindow::new(&mut conn),
+            settings,
             conn,
+            #[cfg(feature = "legacy-import")]
             import_window: None,
             recipe_lists: Default::default(),
+            recipe_browser_window: None,
             recipes: Default::default(),
             ingredient_list_window: None,
+            ingredient_category_list_window: None,
             calendar_window: None,
             search_result_windows: Default::default(),
             next_search_results_window_id: 0,
             recipe_search_window: None,
-            ingredient_calories_windows: Default::default(),
+            ingredient_windows: Default::default(),
+            recipe_steps_windows: Default::default(),
             ingredient_replace_window: None,
+            ingredient_duplicates_window: None,
             about_window: None,
+            shortcuts_window: None,
+            log_window: None,
+            cook_mode_window: None,
+            recipe_tabs: vec![],
+            selected_recipe_tab: None,
+            settings_window: None,
+            stats_window: None,
+            nutrition_audit_window: None,
+            recipe_costs_window: None,
+            in_season_window: None,
+            try_next_window: None,
+            freezer_window: None,
+            shopping_comparison_window: None,
+            nutrition_dashboard_window: None,
+            integrity_window: None,
+            #[cfg(feature = "sync")]
+            sync_conflicts_window: None,
             toasts: egui_toast::Toasts::new()
                 .anchor(egui::Align2::LEFT_BOTTOM, (10.0, 10.0))
-                .direction(egui::Direction::BottomUp),
+                .direction(egui::Direction::BottomUp)
+                .custom_contents(undo::UNDO_TOAST_KIND, undo::toast_contents)
+                .custom_contents(
+                    errors::DATABASE_ERROR_TOAST_KIND,
+                    errors::database_error_toast_contents,
+                ),
+        };
+        #[cfg(feature = "sync")]
+        manager.sync();
+        manager
+    }
+
+    /// Merges the changes made on other machines, and writes out a copy with the ones made here,
+    /// if a sync folder is set.
+    #[cfg(feature = "sync")]
+    fn sync(&mut self) {
+        let Some(folder) = self.settings.sync_folder.clone() else {
+            return;
+        };
+        if !self.access.is_read_write() {
+            return;
+        }
+        match recipe_manager::sync::merge(&mut self.conn, &folder) {
+            Ok(merge) => {
+                for (machine, error) in merge.errors {
+                    log::error!("failed to merge changes from {machine}: {error}");
+                    self.toasts.add(new_error_toast(format!(
+                        "Couldn't merge changes from {machine}: {error}"
+                    )));
+                }
+                if merge.num_changed > 0 {
+                    self.toasts.add(new_success_toast(format!(
+                        "Merged {} changes from other machines",
+                        merge.num_changed
+                    )));
+                    self.reload();
+                }
+                if !merge.conflicts.is_empty() {
+                    self.sync_conflicts_window = Some(SyncConflictsWindow::new(merge.conflicts));
+                }
+            }
+            Err(error) => {
+                log::error!("failed to sync: {error}");
+                self.toasts
+                    .add(new_error_toast(format!("Couldn't sync: {error}")));
+            }
+        }
+        self.export_sync_copy();
+    }
+
+    #[cfg(feature = "sync")]
+    fn export_sync_copy(&mut self) {
+        let Some(folder) = &self.settings.sync_folder else {
+            return;
+        };
+        if !self.access.is_read_write() {
+            return;
+        }
+        if let Err(error) = recipe_manager::sync::export(&mut self.conn, folder) {
+            log::error!("failed to write the sync copy: {error}");
+            self.toasts.add(new_error_toast(format!(
+                "Couldn't write to the sync folder: {error}"
+            )));
+        }
+    }
+
+    /// Saves every recipe as a recipe book for the old version, wherever the user picks.
+    #[cfg(feature = "legacy-import")]
+    fn export_recipe_book(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("recipebook", &["recipebook"])
+            .set_file_name("Recipes.recipebook")
+            .save_file()
+        else {
+            return;
+        };
+        match recipe_manager::import::export_recipe_book(&mut self.conn, path) {
+            Ok(num_exported) => {
+                self.toasts.add(new_success_toast(format!(
+                    "Exported {num_exported} recipes"
+                )));
+            }
+            Err(error) => {
+                log::error!("failed to export the recipe book: {error}");
+                self.toasts
+                    .add(new_error_toast(format!("Couldn't export: {error}")));
+            }
         }
     }
 
@@ -86,29 +390,77 @@ impl RecipeManager {
         next_search_results_window_id: &mut u64,
         control: IngredientSearchControl,
         ingredients: Vec<IngredientHandle>,
+        minimums: Vec<query::MinimumQuantity>,
+        include_archived: bool,
     ) {
         let ingredient_ids: Vec<_> = ingredients.iter().map(|i| i.id).collect();
         let qualifier;
-        let results = match control {
+        let required;
+        let Some(mut results) = (match control {
             IngredientSearchControl::All => {
                 qualifier = "all".into();
-                query::search_recipes_including_all_ingredient(conn, ingredient_ids)
+                required = ingredient_ids.len();
+                query::search_recipes_including_all_ingredient(
+                    conn,
+                    ingredient_ids.clone(),
+                    include_archived,
+                )
+                .or_report()
             }
             IngredientSearchControl::Any => {
                 qualifier = "any".into();
-                query::search_recipes_including_any_ingredient(conn, ingredient_ids)
+                required = 1;
+                query::search_recipes_including_any_ingredient(
+                    conn,
+                    ingredient_ids.clone(),
+                    include_archived,
+                )
+                .or_report()
             }
             IngredientSearchControl::AtLeast(at_least) => {
                 qualifier = format!("at least {at_least}");
-                query::search_recipes_including_at_least_ingredients(conn, ingredient_ids, at_least)
+                required = at_least;
+                query::search_recipes_including_at_least_ingredients(
+                    conn,
+                    ingredient_ids.clone(),
+                    at_least,
+                    include_archived,
+                )
+                .or_report()
+            }
+        }) else {
+            return;
+        };
+        if !minimums.is_empty() {
+            let Some(filtered) = query::filter_recipes_by_quantity(
+                conn,
+                results,
+                &ingredient_ids,
+                &minimums,
+                required,
+            )
+            .or_report() else {
+                return;
+            };
+            results = filtered;
+        }
+
+        let describe = |i: &IngredientHandle| match minimums.iter().find(|m| m.ingredient == i.id) {
+            Some(m) => {
+                let units = m
+                    .units
+                    .map(|u| format!(" {}", i18n::units(u)))
+                    .unwrap_or_default();
+                format!("\"{}\" (at least {}{units})", i.name, m.quantity)
             }
+            None => format!("\"{}\"", i.name),
         };
         let query = if ingredients.len() == 1 {
-            format!("Recipes using \"{}\"", &ingredients[0].name)
+            format!("Recipes using {}", describe(&ingredients[0]))
         } else {
-            let mut query = format!("Recipes using {qualifier} of \"{}\"", &ingredients[0].name);
+            let mut query = format!("Recipes using {qualifier} of {}", describe(&ingredients[0]));
             for i in &ingredients[1..] {
-                query += &format!(", \"{}\"", &i.name);
+                query += &format!(", {}", describe(i));
             }
             query
         };
@@ -122,12 +474,17 @@ impl RecipeManager {
     }
 
     fn update_category_list_window(&mut self, ctx: &egui::Context) {
-        self.category_list.update(
+        let changed = self.category_list.update(
             ctx,
             &mut self.conn,
             &mut self.toasts,
             &mut self.recipe_lists,
         );
+        if changed {
+            if let Some(window) = &mut self.recipe_browser_window {
+                window.categories_changed(&mut self.conn);
+            }
+        }
     }
 
     fn update_about_window(&mut self, ctx: &egui::Context) {
@@ -138,28 +495,378 @@ impl RecipeManager {
         }
     }
 
+    fn update_cook_mode_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.cook_mode_window {
+            if window.update(ctx) {
+                self.cook_mode_window = None;
+            }
+        }
+    }
+
+    fn update_shortcuts_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.shortcuts_window {
+            if window.update(ctx) {
+                self.shortcuts_window = None;
+            }
+        }
+    }
+
+    fn update_log_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.log_window {
+            if window.update(ctx) {
+                self.log_window = None;
+            }
+        }
+    }
+
+    fn close_window(&mut self, id: egui::Id) {
+        self.recipes.retain(|_, w| w.window_id() != id);
+        self.recipe_lists.retain(|_, w| w.window_id() != id);
+        self.recipe_steps_windows.retain(|_, w| w.window_id() != id);
+
+        let is = |title: &str| egui::Id::new(title) == id;
+        if is("Ingredients") {
+            self.ingredient_list_window = None;
+        } else if is("Ingredient Categories") {
+            self.ingredient_category_list_window = None;
+        } else if is("Replace Ingredients") {
+            self.ingredient_replace_window = None;
+        } else if is("Duplicate Ingredients") {
+            self.ingredient_duplicates_window = None;
+        } else if is("Recipe Search") {
+            self.recipe_search_window = None;
+        } else if is("All Recipes") {
+            self.recipe_browser_window = None;
+        } else if is("Calendar") {
+            self.calendar_window = None;
+        } else if is("Shopping Comparison") {
+            self.shopping_comparison_window = None;
+        } else if is("Stats") {
+            self.stats_window = None;
+        } else if is("Nutrition Dashboard") {
+            self.nutrition_dashboard_window = None;
+        } else if is("Missing Nutrition") {
+            self.nutrition_audit_window = None;
+        } else if is("Recipe Costs") {
+            self.recipe_costs_window = None;
+        } else if is("In Season") {
+            self.in_season_window = None;
+        } else if is("Try Next") {
+            self.try_next_window = None;
+        } else if is("Freezer") {
+            self.freezer_window = None;
+        } else if is("How Did It Go?") {
+            self.cook_journal_prompt_window = None;
+        } else if is("Check Database") {
+            self.integrity_window = None;
+        } else if is("Settings") {
+            self.settings_window = None;
+        } else if is("Keyboard Shortcuts") {
+            self.shortcuts_window = None;
+        } else if is("Log") {
+            self.log_window = None;
+        } else if is("About") {
+            self.about_window = None;
+        }
+        #[cfg(feature = "sync")]
+        if is("Sync Conflicts") {
+            self.sync_conflicts_window = None;
+        }
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        let focused = ctx.top_layer_id().map(|l| l.id);
+        for shortcut in shortcuts::pressed(ctx) {
+            match shortcut {
+                Shortcut::NewRecipe => {
+                    let list = self
+                        .recipe_lists
+                        .values_mut()
+                        .find(|l| Some(l.window_id()) == focused);
+                    if let Some(list) = list.filter(|_| is_editable(ctx)) {
+                        list.start_new_recipe();
+                    }
+                }
+                Shortcut::Search => {
+                    if self.recipe_search_window.is_none() {
+                        self.recipe_search_window = Some(RecipeSearchWindow::new());
+                    }
+                }
+                Shortcut::CloseWindow => {
+                    if let Some(id) = focused {
+                        self.close_window(id);
+                    }
+                }
+                Shortcut::Delete => {
+                    if let Some(window) = &mut self.ingredient_list_window {
+                        if focused == Some(egui::Id::new("Ingredients")) && is_editable(ctx) {
+                            window.delete_selected();
+                        }
+                    }
+                }
+                Shortcut::Help => {
+                    if self.shortcuts_window.is_none() {
+                        self.shortcuts_window = Some(ShortcutsWindow::new());
+                    } else {
+                        self.shortcuts_window = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_stats_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.stats_window {
+            if window.update(ctx, &mut self.conn, &mut self.toasts) {
+                self.stats_window = None;
+            }
+        }
+    }
+
+    fn update_ingredient_category_list_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.ingredient_category_list_window {
+            for event in window.update(ctx, &mut self.conn, &mut self.toasts) {
+                match event {
+                    ingredient_category_list::UpdateEvent::Closed => {
+                        self.ingredient_category_list_window = None;
+                        break;
+                    }
+                    ingredient_category_list::UpdateEvent::CategoriesChanged => {
+                        if let Some(window) = &mut self.ingredient_list_window {
+                            window.categories_changed(&mut self.conn);
+                        }
+                        for r in self.recipes.values_mut() {
+                            r.reload(&mut self.conn);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_nutrition_audit_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.nutrition_audit_window {
+            if window.update(ctx, &mut self.conn, &mut self.ingredient_windows.calories) {
+                self.nutrition_audit_window = None;
+            }
+        }
+    }
+
+    fn update_recipe_costs_window(&mut self, ctx: &egui::Context) {
+        let selected_week = self.calendar_window.as_ref().map(|w| w.week());
+        if let Some(window) = &mut self.recipe_costs_window {
+            if window.update(ctx, &mut self.conn, &mut self.recipes, selected_week) {
+                self.recipe_costs_window = None;
+            }
+        }
+    }
+
+    fn update_in_season_window(&mut self, ctx: &egui::Context) {
+        let selected_week = self.calendar_window.as_ref().map(|w| w.week());
+        if let Some(window) = &mut self.in_season_window {
+            if window.update(ctx, &mut self.conn, &mut self.recipes, selected_week) {
+                self.in_season_window = None;
+            }
+        }
+    }
+
+    fn update_try_next_window(&mut self, ctx: &egui::Context) {
+        let selected_week = self.calendar_window.as_ref().map(|w| w.week());
+        if let Some(window) = &mut self.try_next_window {
+            if window.update(ctx, &mut self.conn, &mut self.recipes, selected_week) {
+                self.try_next_window = None;
+            }
+        }
+    }
+
+    fn update_freezer_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.freezer_window else {
+            return;
+        };
+        let events = window.update(ctx, &mut self.conn, &mut self.toasts, &self.settings);
+        for e in events {
+            match e {
+                freezer::UpdateEvent::Closed => {
+                    self.freezer_window = None;
+                }
+                freezer::UpdateEvent::FreezerChanged => {
+                    if let Some(c) = &mut self.calendar_window {
+                        c.freezer_changed(&mut self.conn);
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_cook_journal_prompt_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.cook_journal_prompt_window else {
+            return;
+        };
+        let (closed, journaled) = window.update(ctx, &mut self.conn, &self.settings);
+        for id in journaled {
+            if let Some(recipe) = self.recipes.get_mut(&id) {
+                recipe.journal_changed(&mut self.conn);
+            }
+        }
+        if closed {
+            self.cook_journal_prompt_window = None;
+        }
+    }
+
+    fn update_integrity_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.integrity_window {
+            let events = window.update(ctx, &mut self.conn);
+            for e in events {
+                match e {
+                    integrity::UpdateEvent::Closed => {
+                        self.integrity_window = None;
+                    }
+                    integrity::UpdateEvent::Repaired => self.reload(),
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    fn update_sync_conflicts_window(&mut self, ctx: &egui::Context) {
+        let Some(folder) = &self.settings.sync_folder else {
+            return;
+        };
+        if let Some(window) = &mut self.sync_conflicts_window {
+            let events = window.update(ctx, &mut self.conn, folder, &mut self.toasts);
+            for e in events {
+                match e {
+                    sync::UpdateEvent::Closed => {
+                        self.sync_conflicts_window = None;
+                    }
+                    sync::UpdateEvent::Resolved => {
+                        self.reload();
+                        self.export_sync_copy();
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_shopping_comparison_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.shopping_comparison_window {
+            if window.update(ctx, &mut self.conn, &self.settings) {
+                self.shopping_comparison_window = None;
+            }
+        }
+    }
+
+    fn update_nutrition_dashboard_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.nutrition_dashboard_window {
+            if window.update(ctx, &mut self.conn, &self.settings) {
+                self.nutrition_dashboard_window = None;
+            }
+        }
+    }
+
+    fn update_settings_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.settings_window {
+            if window.update(ctx, &mut self.conn, &mut self.toasts, &mut self.settings) {
+                self.settings_window = None;
+            }
+        }
+        if self.settings.diagnostics != self.diagnostics.is_some() {
+            self.diagnostics = match self.diagnostics.take() {
+                Some(diagnostics) => {
+                    diagnostics.stop(&mut self.conn);
+                    None
+                }
+                None => Some(Diagnostics::start(&mut self.conn)),
+            };
+        }
+    }
+
+    fn ingredients_changed(&mut self) {
+        query::forget_recipe_summaries();
+        if let Some(c) = &mut self.calendar_window {
+            c.ingredients_changed();
+        }
+        // For the calories column.
+        for list in self.recipe_lists.values_mut() {
+            list.recipe_details_changed(&mut self.conn);
+        }
+        if let Some(window) = &mut self.recipe_browser_window {
+            window.recipe_details_changed(&mut self.conn);
+        }
+        if let Some(window) = &mut self.nutrition_audit_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.recipe_costs_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.nutrition_dashboard_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.in_season_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.stats_window {
+            window.refresh(&mut self.conn);
+        }
+    }
+
+    fn undo(&mut self, action: undo::UndoAction) {
+        action.undo(&mut self.conn).or_report();
+        for r in self.recipes.values_mut() {
+            r.reload(&mut self.conn);
+        }
+        if let Some(c) = &mut self.calendar_window {
+            c.recipe_scheduled(&mut self.conn);
+        }
+        if let Some(window) = &mut self.shopping_comparison_window {
+            window.refresh(&mut self.conn);
+        }
+        self.ingredients_changed();
+    }
+
+    /// Reads what the main windows show from the database again, after an error.
+    fn reload(&mut self) {
+        query::clear_cache();
+        self.category_list.categories_changed(&mut self.conn);
+        for list in self.recipe_lists.values_mut() {
+            list.recipe_category_changed(&mut self.conn);
+        }
+        if let Some(window) = &mut self.recipe_browser_window {
+            window.categories_changed(&mut self.conn);
+        }
+        for r in self.recipes.values_mut() {
+            r.reload(&mut self.conn);
+        }
+        if let Some(c) = &mut self.calendar_window {
+            c.recipe_scheduled(&mut self.conn);
+        }
+        if let Some(window) = &mut self.ingredient_list_window {
+            window.categories_changed(&mut self.conn);
+        }
+        if let Some(window) = &mut self.ingredient_category_list_window {
+            window.ingredients_changed(&mut self.conn);
+        }
+        if let Some(window) = &mut self.shopping_comparison_window {
+            window.refresh(&mut self.conn);
+        }
+        self.ingredients_changed();
+    }
+
     fn update_recipe_list_windows(&mut self, ctx: &egui::Context) {
         let selected_week = self.calendar_window.as_ref().map(|w| w.week());
         for (id, mut list) in mem::take(&mut self.recipe_lists) {
             let mut closed = false;
-            let events = list.update(ctx, &mut self.conn, selected_week, &mut self.recipes);
+            let events = list.update(
+                ctx,
+                &mut self.conn,
+                &self.settings,
+                selected_week,
+                &mut self.recipes,
+            );
             for event in events {
                 match event {
                     recipe_list::UpdateEvent::Closed => closed = true,
-                    recipe_list::UpdateEvent::RecipeDeleted(id) => {
-                        for s in &mut self.search_result_windows {
-                            s.recipe_deleted(id);
-                        }
-                        if let Some(window) = &mut self.recipe_search_window {
-                            window.recipe_deleted(id);
-                        }
-                        if let Some(c) = self.calendar_window.as_mut() {
-                            c.recipe_deleted(&mut self.conn);
-                        }
-                        for recipe in self.recipes.values_mut() {
-                            recipe.recipe_deleted(&mut self.conn);
-                        }
-                    }
+                    recipe_list::UpdateEvent::RecipeDeleted(id) => self.recipe_deleted(id),
                 }
             }
 
@@ -169,21 +876,155 @@ impl RecipeManager {
         }
     }
 
+    fn update_recipe_browser_window(&mut self, ctx: &egui::Context) {
+        let selected_week = self.calendar_window.as_ref().map(|w| w.week());
+        let Some(window) = &mut self.recipe_browser_window else {
+            return;
+        };
+        let events = window.update(
+            ctx,
+            &mut self.conn,
+            &self.settings,
+            selected_week,
+            &mut self.recipes,
+        );
+        for event in events {
+            match event {
+                recipe_list::UpdateEvent::Closed => self.recipe_browser_window = None,
+                recipe_list::UpdateEvent::RecipeDeleted(id) => self.recipe_deleted(id),
+            }
+        }
+    }
+
+    fn recipe_deleted(&mut self, id: RecipeId) {
+        for s in &mut self.search_result_windows {
+            s.recipe_deleted(id);
+        }
+        if let Some(window) = &mut self.recipe_search_window {
+            window.recipe_deleted(id);
+        }
+        if let Some(c) = self.calendar_window.as_mut() {
+            c.recipe_deleted(&mut self.conn);
+        }
+        for recipe in self.recipes.values_mut() {
+            recipe.recipe_deleted(&mut self.conn);
+        }
+        self.recipe_steps_windows.remove(&id);
+        if let Some(window) = &mut self.nutrition_audit_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.recipe_costs_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.in_season_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.try_next_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.freezer_window {
+            window.refresh(&mut self.conn);
+        }
+        if let Some(window) = &mut self.stats_window {
+            window.refresh(&mut self.conn);
+        }
+    }
+
+    /// Shows the open recipes as tabs in the main window, with the selected one's contents below.
+    fn update_recipe_tabs(
+        &mut self,
+        ctx: &egui::Context,
+    ) -> Vec<(RecipeId, Vec<recipe::UpdateEvent>)> {
+        let mut events = vec![];
+
+        self.recipe_tabs.retain(|id| self.recipes.contains_key(id));
+        let opened: Vec<_> = self
+            .recipes
+            .keys()
+            .filter(|id| !self.recipe_tabs.contains(id))
+            .copied()
+            .collect();
+        if let Some(id) = opened.last() {
+            self.selected_recipe_tab = Some(*id);
+        }
+        self.recipe_tabs.extend(opened);
+        if !self
+            .selected_recipe_tab
+            .is_some_and(|id| self.recipes.contains_key(&id))
+        {
+            self.selected_recipe_tab = self.recipe_tabs.first().copied();
+        }
+        let Some(selected) = self.selected_recipe_tab else {
+            return events;
+        };
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for id in &self.recipe_tabs {
+                    let title = self.recipes[id].title();
+                    ui.selectable_value(&mut self.selected_recipe_tab, Some(*id), title);
+                    let close = ui
+                        .small_button("x")
+                        .accessible_name(&format!("Close {}", self.recipes[id].title()));
+                    if close.clicked() {
+                        events.push((*id, vec![recipe::UpdateEvent::Closed]));
+                    }
+                }
+            });
+            ui.separator();
+            let recipe = self.recipes.get_mut(&selected).unwrap();
+            events.push((
+                selected,
+                recipe.update_contents(
+                    ui,
+                    &mut self.conn,
+                    &mut self.toasts,
+                    &mut self.ingredient_windows.calories,
+                    &mut self.recipe_steps_windows,
+                    &self.settings,
+                ),
+            ));
+        });
+        events
+    }
+
     fn update_recipes(&mut self, ctx: &egui::Context) {
         let mut recipe_scheduled = vec![];
-        for (id, mut recipe) in mem::take(&mut self.recipes) {
-            let mut closed = false;
-            let events = recipe.update(
-                ctx,
-                &mut self.conn,
-                &mut self.toasts,
-                &mut self.ingredient_calories_windows,
-            );
+        let mut ingredients_changed = false;
+        let recipe_events = match self.settings.recipe_layout {
+            RecipeLayout::Windows => self
+                .recipes
+                .iter_mut()
+                .map(|(id, recipe)| {
+                    let events = recipe.update(
+                        ctx,
+                        &mut self.conn,
+                        &mut self.toasts,
+                        &mut self.ingredient_windows.calories,
+                        &mut self.recipe_steps_windows,
+                        &self.settings,
+                    );
+                    (*id, events)
+                })
+                .collect(),
+            RecipeLayout::Tabs => self.update_recipe_tabs(ctx),
+        };
+        for (id, events) in recipe_events {
             for e in events {
                 match e {
-                    recipe::UpdateEvent::Closed => closed = true,
+                    recipe::UpdateEvent::Closed => {
+                        self.recipes.remove(&id);
+                    }
                     recipe::UpdateEvent::Renamed(recipe) => {
+                        query::forget_recipe_summaries();
                         if let Some(list) = self.recipe_lists.get_mut(&recipe.category) {
+                            list.recipe_name_changed(recipe.id, recipe.name.clone());
+                        }
+                        if let Some(list) = self
+                            .recipe_browser_window
+                            .as_mut()
+                            .and_then(|w| w.list_mut(recipe.category))
+                        {
                             list.recipe_name_changed(recipe.id, recipe.name);
                         }
                     }
@@ -194,13 +1035,54 @@ impl RecipeManager {
                         for r in self.recipe_lists.values_mut() {
                             r.recipe_category_changed(&mut self.conn);
                         }
+                        if let Some(window) = &mut self.recipe_browser_window {
+                            window.recipe_category_changed(&mut self.conn);
+                        }
+                    }
+                    recipe::UpdateEvent::DetailsChanged(category) => {
+                        query::forget_recipe_summaries();
+                        if let Some(window) = &mut self.try_next_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(list) = self.recipe_lists.get_mut(&category) {
+                            list.recipe_details_changed(&mut self.conn);
+                        }
+                        if let Some(list) = self
+                            .recipe_browser_window
+                            .as_mut()
+                            .and_then(|w| w.list_mut(category))
+                        {
+                            list.recipe_details_changed(&mut self.conn);
+                        }
+                    }
+                    recipe::UpdateEvent::ArchivedChanged(category) => {
+                        query::forget_recipe_summaries();
+                        if let Some(window) = &mut self.try_next_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(list) = self.recipe_lists.get_mut(&category) {
+                            list.recipe_archived_changed(&mut self.conn);
+                        }
+                        if let Some(list) = self
+                            .recipe_browser_window
+                            .as_mut()
+                            .and_then(|w| w.list_mut(category))
+                        {
+                            list.recipe_archived_changed(&mut self.conn);
+                        }
+                    }
+                    recipe::UpdateEvent::IngredientsChanged => ingredients_changed = true,
+                    recipe::UpdateEvent::Cook(id) => {
+                        self.cook_mode_window =
+                            CookModeWindow::new(&mut self.conn, id, &self.settings).or_report();
+                    }
+                    recipe::UpdateEvent::IngredientCreated => {
+                        if let Some(window) = &mut self.ingredient_list_window {
+                            window.ingredient_created(&mut self.conn);
+                        }
                     }
                 }
             }
-
-            if !closed {
-                self.recipes.insert(id, recipe);
-            }
         }
 
         for week in recipe_scheduled {
@@ -211,46 +1093,186 @@ impl RecipeManager {
                 recipe.recipe_scheduled(&mut self.conn, week);
             }
         }
+        if ingredients_changed {
+            self.ingredients_changed();
+        }
     }
 
     fn update_menu(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("Window", |ui| {
-                    if ui.button("Ingredients").clicked() {
+                ui.menu_button(i18n::tr("Window"), |ui| {
+                    if ui.button(i18n::tr("Ingredients")).clicked() {
                         if self.ingredient_list_window.is_none() {
-                            self.ingredient_list_window = Some(IngredientListWindow::new());
+                            self.ingredient_list_window =
+                                Some(IngredientListWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Ingredient Categories")).clicked() {
+                        if self.ingredient_category_list_window.is_none() {
+                            self.ingredient_category_list_window =
+                                Some(IngredientCategoryListWindow::new(&mut self.conn));
                         }
                         ui.close_menu();
                     }
-                    if ui.button("Ingredient Replace").clicked() {
+                    if ui.button(i18n::tr("Ingredient Replace")).clicked() {
                         if self.ingredient_replace_window.is_none() {
                             self.ingredient_replace_window = Some(IngredientReplaceWindow::new());
                         }
                         ui.close_menu();
                     }
-                    if ui.button("Recipe Search").clicked() {
+                    if ui.button(i18n::tr("Duplicate Ingredients")).clicked() {
+                        if self.ingredient_duplicates_window.is_none() {
+                            self.ingredient_duplicates_window =
+                                Some(IngredientDuplicatesWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Recipe Search")).clicked() {
                         if self.recipe_search_window.is_none() {
                             self.recipe_search_window = Some(RecipeSearchWindow::new());
                         }
                         ui.close_menu();
                     }
-                    if ui.button("Calendar").clicked() {
+                    if ui.button(i18n::tr("All Recipes")).clicked() {
+                        if self.recipe_browser_window.is_none() {
+                            self.recipe_browser_window =
+                                Some(RecipeBrowserWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Calendar")).clicked() {
                         if self.calendar_window.is_none() {
-                            self.calendar_window = Some(CalendarWindow::new(&mut self.conn));
+                            self.calendar_window =
+                                Some(CalendarWindow::new(&mut self.conn, &self.settings));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Shopping Comparison")).clicked() {
+                        if self.shopping_comparison_window.is_none() {
+                            let week = self.calendar_window.as_ref().map(|w| w.week());
+                            self.shopping_comparison_window = Some(ShoppingComparisonWindow::new(
+                                &mut self.conn,
+                                week,
+                                self.settings.week_start.weekday(),
+                            ));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Stats")).clicked() {
+                        if self.stats_window.is_none() {
+                            self.stats_window = Some(StatsWindow::new(
+                                &mut self.conn,
+                                self.settings.week_start.weekday(),
+                            ));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Nutrition Dashboard")).clicked() {
+                        if self.nutrition_dashboard_window.is_none() {
+                            let week = self.calendar_window.as_ref().map(|w| w.week());
+                            self.nutrition_dashboard_window = Some(NutritionDashboardWindow::new(
+                                &mut self.conn,
+                                week,
+                                self.settings.week_start.weekday(),
+                            ));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Missing Nutrition")).clicked() {
+                        if self.nutrition_audit_window.is_none() {
+                            self.nutrition_audit_window =
+                                Some(NutritionAuditWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Recipe Costs")).clicked() {
+                        if self.recipe_costs_window.is_none() {
+                            self.recipe_costs_window = Some(RecipeCostsWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("In Season")).clicked() {
+                        if self.in_season_window.is_none() {
+                            self.in_season_window = Some(InSeasonWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Try Next")).clicked() {
+                        if self.try_next_window.is_none() {
+                            self.try_next_window = Some(TryNextWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Freezer")).clicked() {
+                        if self.freezer_window.is_none() {
+                            self.freezer_window = Some(FreezerWindow::new(&mut self.conn));
                         }
                         ui.close_menu();
                     }
                     ui.separator();
-                    if ui.button("Import").clicked() {
+                    #[cfg(feature = "legacy-import")]
+                    if ui
+                        .add_enabled(
+                            self.access.is_read_write(),
+                            egui::Button::new(i18n::tr("Import")),
+                        )
+                        .clicked()
+                    {
                         if self.import_window.is_none() {
                             self.import_window = Some(ImportWindow::default());
                         }
                         ui.close_menu();
                     }
-                    if ui.button("About").clicked() {
+                    #[cfg(feature = "legacy-import")]
+                    if ui
+                        .button(i18n::tr("Export Recipe Book"))
+                        .on_hover_text("For opening in the old version of Recipe Manager")
+                        .clicked()
+                    {
+                        self.export_recipe_book();
+                        ui.close_menu();
+                    }
+                    #[cfg(feature = "sync")]
+                    let can_sync =
+                        self.settings.sync_folder.is_some() && self.access.is_read_write();
+                    #[cfg(feature = "sync")]
+                    if ui
+                        .add_enabled(can_sync, egui::Button::new(i18n::tr("Sync Now")))
+                        .on_disabled_hover_text("Choose a sync folder in the settings first")
+                        .clicked()
+                    {
+                        self.sync();
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Check Database")).clicked() {
+                        if self.integrity_window.is_none() {
+                            self.integrity_window = Some(IntegrityWindow::new(&mut self.conn));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Settings")).clicked() {
+                        if self.settings_window.is_none() {
+                            self.settings_window = Some(SettingsWindow::new(&self.settings));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Keyboard Shortcuts")).clicked() {
+                        if self.shortcuts_window.is_none() {
+                            self.shortcuts_window = Some(ShortcutsWindow::new());
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("Log")).clicked() {
+                        if self.log_window.is_none() {
+                            self.log_window = Some(LogWindow::new());
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n::tr("About")).clicked() {
                         if self.about_window.is_none() {
-                            self.about_window = Some(AboutWindow::new());
+                            self.about_window = Some(AboutWindow::new(&mut self.conn));
                         }
                         ui.close_menu();
                     }
@@ -259,6 +1281,77 @@ impl RecipeManager {
         });
     }
 
+    fn update_read_only_banner(&mut self, ctx: &egui::Context) {
+        set_editable(ctx, self.access.is_read_write());
+        let message = match self.access {
+            Access::ReadWrite { .. } => return,
+            Access::InUse => {
+                "Recipe Manager is already open in another window, so nothing can be changed \
+                here. Close the other one and try again to make changes."
+            }
+            Access::ReadOnly => "The recipe database is open read-only, for browsing.",
+        };
+        egui::TopBottomPanel::top("read only banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, message);
+                if matches!(self.access, Access::InUse) && ui.button("Try Again").clicked() {
+                    match InstanceLock::acquire() {
+                        // Starts over rather than making this connection writable, since the
+                        // database may need upgrading first, and the HTTP API would stay
+                        // read-only.
+                        Ok(Some(lock)) => {
+                            drop(lock);
+                            match restart() {
+                                Ok(()) => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+                                Err(error) => {
+                                    self.toasts
+                                        .add(new_error_toast(format!("Couldn't restart: {error}")));
+                                }
+                            }
+                        }
+                        Ok(None) => {
+                            self.toasts
+                                .add(new_error_toast("Recipe Manager is still open elsewhere"));
+                        }
+                        Err(error) => {
+                            self.toasts
+                                .add(new_error_toast(format!("Couldn't check: {error}")));
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    fn update_welcome_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.welcome_window {
+            let events = window.update(ctx, &mut self.conn, &mut self.toasts);
+            for e in events {
+                match e {
+                    welcome::UpdateEvent::Closed => {
+                        self.welcome_window = None;
+                    }
+                    #[cfg(feature = "legacy-import")]
+                    welcome::UpdateEvent::Import => {
+                        if self.import_window.is_none() {
+                            self.import_window = Some(ImportWindow::default());
+                        }
+                    }
+                    welcome::UpdateEvent::RecipesAdded => {
+                        self.category_list.categories_changed(&mut self.conn);
+                        if let Some(window) = &mut self.recipe_browser_window {
+                            window.categories_changed(&mut self.conn);
+                        }
+                        if let Some(window) = &mut self.ingredient_list_window {
+                            window.ingredient_created(&mut self.conn);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "legacy-import")]
     fn update_import_window(&mut self, ctx: &egui::Context) {
         if let Some(window) = &mut self.import_window {
             let events = window.update(&mut self.conn, ctx);
@@ -268,7 +1361,14 @@ impl RecipeManager {
                         self.import_window = None;
                     }
                     import::UpdateEvent::Imported => {
-                        self.category_list.recipes_imported(&mut self.conn);
+                        query::forget_recipe_summaries();
+                        self.category_list.categories_changed(&mut self.conn);
+                        if let Some(window) = &mut self.recipe_browser_window {
+                            window.categories_changed(&mut self.conn);
+                        }
+                        for r in self.recipes.values_mut() {
+                            r.reload(&mut self.conn);
+                        }
                         if let Some(c) = &mut self.calendar_window {
                             c.calendar_imported(&mut self.conn);
                         }
@@ -287,33 +1387,50 @@ impl RecipeManager {
                     &mut self.next_search_results_window_id,
                     IngredientSearchControl::All,
                     ingredients,
+                    vec![],
+                    false,
                 )
             };
             let events = window.update(
                 &mut self.conn,
                 &mut self.toasts,
-                &mut self.ingredient_calories_windows,
+                &mut self.ingredient_windows,
                 search_for_ingredient,
                 ctx,
             );
             for e in events {
                 match e {
                     ingredient_list::UpdateEvent::Closed => self.ingredient_list_window = None,
-                    ingredient_list::UpdateEvent::IngredientEdited => {
+                    ingredient_list::UpdateEvent::IngredientsEdited(ids) => {
+                        query::forget_recipe_summaries();
+                        if let Some(window) = &mut self.in_season_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        // For the prices.
+                        if let Some(window) = &mut self.recipe_costs_window {
+                            window.refresh(&mut self.conn);
+                        }
                         for r in self.recipes.values_mut() {
-                            r.ingredient_edited(&mut self.conn);
+                            r.ingredients_edited(&mut self.conn, &ids);
+                        }
+                        if let Some(window) = &mut self.ingredient_category_list_window {
+                            window.ingredients_changed(&mut self.conn);
+                        }
+                        if let Some(c) = &mut self.calendar_window {
+                            c.ingredients_changed();
                         }
                     }
                     ingredient_list::UpdateEvent::IngredientDeleted(id) => {
-                        for r in self.recipes.values_mut() {
-                            r.ingredient_deleted(&mut self.conn);
-                        }
+                        self.ingredient_windows.components.remove(&id);
                         if let Some(window) = &mut self.ingredient_replace_window {
                             window.ingredient_deleted(&mut self.conn);
                         }
                         if let Some(window) = &mut self.recipe_search_window {
                             window.ingredient_deleted(id)
                         }
+                        if let Some(window) = &mut self.nutrition_audit_window {
+                            window.refresh(&mut self.conn);
+                        }
                     }
                 }
             }
@@ -322,7 +1439,7 @@ impl RecipeManager {
 
     fn update_calendar_window(&mut self, ctx: &egui::Context) {
         if let Some(window) = &mut self.calendar_window {
-            let events = window.update(ctx, &mut self.conn, &mut self.toasts);
+            let events = window.update(ctx, &mut self.conn, &mut self.toasts, &self.settings);
             for e in events {
                 match e {
                     calendar::UpdateEvent::Closed => {
@@ -332,6 +1449,38 @@ impl RecipeManager {
                         for recipe in self.recipes.values_mut() {
                             recipe.recipe_scheduled(&mut self.conn, week);
                         }
+                        if let Some(window) = &mut self.stats_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(window) = &mut self.shopping_comparison_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(window) = &mut self.nutrition_dashboard_window {
+                            window.refresh(&mut self.conn);
+                        }
+                    }
+                    calendar::UpdateEvent::FreezerChanged => {
+                        if let Some(window) = &mut self.freezer_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(window) = &mut self.shopping_comparison_window {
+                            window.refresh(&mut self.conn);
+                        }
+                    }
+                    calendar::UpdateEvent::CalendarChanged => {
+                        // Cooking a recipe means it has been tried.
+                        if let Some(window) = &mut self.try_next_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(window) = &mut self.stats_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(window) = &mut self.shopping_comparison_window {
+                            window.refresh(&mut self.conn);
+                        }
+                        if let Some(window) = &mut self.nutrition_dashboard_window {
+                            window.refresh(&mut self.conn);
+                        }
                     }
                 }
             }
@@ -341,15 +1490,18 @@ impl RecipeManager {
     fn update_recipe_search_window(&mut self, ctx: &egui::Context) {
         let selected_week = self.calendar_window.as_ref().map(|w| w.week());
         if let Some(window) = &mut self.recipe_search_window {
-            let search_by_ingredients = |conn: &mut database::Connection, control, ingredients| {
-                Self::ingredient_search(
-                    conn,
-                    &mut self.search_result_windows,
-                    &mut self.next_search_results_window_id,
-                    control,
-                    ingredients,
-                )
-            };
+            let search_by_ingredients =
+                |conn: &mut database::Connection, control, ingredients, minimums, archived| {
+                    Self::ingredient_search(
+                        conn,
+                        &mut self.search_result_windows,
+                        &mut self.next_search_results_window_id,
+                        control,
+                        ingredients,
+                        minimums,
+                        archived,
+                    )
+                };
             if window.update(
                 ctx,
                 &mut self.conn,
@@ -381,19 +1533,31 @@ impl RecipeManager {
                         self.ingredient_replace_window = None;
                     }
                     ingredient_replace::UpdateEvent::IngredientReplaced => {
-                        for r in self.recipes.values_mut() {
-                            r.ingredient_edited(&mut self.conn);
-                        }
+                        self.ingredient_replaced();
                     }
                     ingredient_replace::UpdateEvent::IngredientDeleted(id) => {
-                        if let Some(window) = &mut self.ingredient_list_window {
-                            window.ingredient_deleted();
-                        }
-                        for r in self.recipes.values_mut() {
-                            r.ingredient_deleted(&mut self.conn);
-                        }
-                        if let Some(window) = &mut self.recipe_search_window {
-                            window.ingredient_deleted(id)
+                        self.ingredient_merged_away(id);
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_ingredient_duplicates_window(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &mut self.ingredient_duplicates_window {
+            let events = window.update(ctx, &mut self.conn);
+            for e in events {
+                match e {
+                    ingredient_duplicates::UpdateEvent::Closed => {
+                        self.ingredient_duplicates_window = None;
+                    }
+                    ingredient_duplicates::UpdateEvent::IngredientReplaced => {
+                        self.ingredient_replaced();
+                    }
+                    ingredient_duplicates::UpdateEvent::IngredientDeleted(id) => {
+                        self.ingredient_merged_away(id);
+                        if let Some(window) = &mut self.ingredient_replace_window {
+                            window.ingredient_deleted(&mut self.conn);
                         }
                     }
                 }
@@ -401,26 +1565,88 @@ impl RecipeManager {
         }
     }
 
+    /// After an ingredient's usages were moved to another ingredient.
+    fn ingredient_replaced(&mut self) {
+        query::forget_recipe_summaries();
+        for r in self.recipes.values_mut() {
+            r.reload(&mut self.conn);
+        }
+        if let Some(c) = &mut self.calendar_window {
+            c.ingredients_changed();
+        }
+        if let Some(window) = &mut self.nutrition_audit_window {
+            window.refresh(&mut self.conn);
+        }
+    }
+
+    /// After an ingredient was deleted once it was replaced everywhere it was used.
+    fn ingredient_merged_away(&mut self, id: IngredientId) {
+        self.ingredient_windows.components.remove(&id);
+        self.ingredient_windows.calories.remove(&id);
+        if let Some(window) = &mut self.ingredient_list_window {
+            window.ingredient_deleted(&mut self.conn);
+        }
+        if let Some(window) = &mut self.recipe_search_window {
+            window.ingredient_deleted(id)
+        }
+        if let Some(window) = &mut self.nutrition_audit_window {
+            window.refresh(&mut self.conn);
+        }
+    }
+
     fn update_ingredient_calories_windows(&mut self, ctx: &egui::Context) {
-        for (id, mut ingredient_calories) in mem::take(&mut self.ingredient_calories_windows) {
+        for (id, mut ingredient_calories) in mem::take(&mut self.ingredient_windows.calories) {
             let mut closed = false;
-            let events = ingredient_calories.update(ctx, &mut self.conn);
+            let events = ingredient_calories.update(ctx, &mut self.conn, &self.settings);
             for event in events {
                 match event {
                     ingredient_calories::UpdateEvent::Closed => closed = true,
                     ingredient_calories::UpdateEvent::IngredientEdited => {
                         for r in self.recipes.values_mut() {
-                            r.ingredient_edited(&mut self.conn);
+                            r.ingredients_edited(&mut self.conn, &[id]);
                         }
+                        self.ingredients_changed();
                     }
                 }
             }
             if !closed {
-                self.ingredient_calories_windows
+                self.ingredient_windows
+                    .calories
                     .insert(id, ingredient_calories);
             }
         }
     }
+
+    fn update_ingredient_components_windows(&mut self, ctx: &egui::Context) {
+        for (id, mut ingredient_components) in mem::take(&mut self.ingredient_windows.components) {
+            if !ingredient_components.update(ctx, &mut self.conn, &mut self.toasts, &self.settings)
+            {
+                self.ingredient_windows
+                    .components
+                    .insert(id, ingredient_components);
+            }
+        }
+    }
+
+    fn update_recipe_steps_windows(&mut self, ctx: &egui::Context) {
+        for (id, mut recipe_steps) in mem::take(&mut self.recipe_steps_windows) {
+            let mut closed = false;
+            let events = recipe_steps.update(ctx, &mut self.conn, &mut self.toasts, &self.settings);
+            for event in events {
+                match event {
+                    recipe_steps::UpdateEvent::Closed => closed = true,
+                    recipe_steps::UpdateEvent::StepsChanged => {
+                        if let Some(c) = self.calendar_window.as_mut() {
+                            c.recipe_scheduled(&mut self.conn);
+                        }
+                    }
+                }
+            }
+            if !closed {
+                self.recipe_steps_windows.insert(id, recipe_steps);
+            }
+        }
+    }
 }
 
 impl eframe::App for RecipeManager {
@@ -432,22 +1658,133 @@ impl eframe::App for RecipeManager {
         }
     }
 
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        #[cfg(feature = "sync")]
+        self.export_sync_copy();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui_extras::install_image_loaders(ctx);
+        ctx.set_theme(self.settings.theme.preference());
+        apply_style(ctx, &self.settings);
+        set_unit_choices(ctx, self.settings.unit_choices);
+        i18n::set_language(self.settings.language);
+
+        if let Some(message) = &self.crash_message {
+            match errors::update_crash_window(ctx, message) {
+                Some(errors::CrashAction::Continue) => self.crash_message = None,
+                Some(errors::CrashAction::Quit) => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close)
+                }
+                None => {}
+            }
+            return;
+        }
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.start_frame();
+        }
+        self.crash_message = errors::catch_panic(|| self.update_windows(ctx));
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.end_frame();
+            diagnostics.update_overlay(ctx);
+        }
+    }
+}
 
+impl RecipeManager {
+    fn update_windows(&mut self, ctx: &egui::Context) {
         self.update_menu(ctx);
+        self.update_read_only_banner(ctx);
+        self.update_welcome_window(ctx);
+        #[cfg(feature = "legacy-import")]
         self.update_import_window(ctx);
         self.update_ingredient_window(ctx);
         self.update_category_list_window(ctx);
         self.update_recipe_list_windows(ctx);
+        self.update_recipe_browser_window(ctx);
         self.update_recipes(ctx);
         self.update_calendar_window(ctx);
         self.update_search_result_windows(ctx);
         self.update_recipe_search_window(ctx);
         self.update_ingredient_calories_windows(ctx);
+        self.update_ingredient_components_windows(ctx);
+        self.update_recipe_steps_windows(ctx);
         self.update_ingredient_replace_window(ctx);
+        self.update_ingredient_duplicates_window(ctx);
+        self.update_stats_window(ctx);
+        self.update_ingredient_category_list_window(ctx);
+        self.update_nutrition_audit_window(ctx);
+        self.update_recipe_costs_window(ctx);
+        self.update_in_season_window(ctx);
+        self.update_try_next_window(ctx);
+        self.update_freezer_window(ctx);
+        self.update_cook_journal_prompt_window(ctx);
+        self.update_shopping_comparison_window(ctx);
+        self.update_nutrition_dashboard_window(ctx);
+        self.update_integrity_window(ctx);
+        #[cfg(feature = "sync")]
+        self.update_sync_conflicts_window(ctx);
+        self.update_settings_window(ctx);
+        self.update_shortcuts_window(ctx);
+        self.update_log_window(ctx);
         self.update_about_window(ctx);
+        self.update_cook_mode_window(ctx);
+        // After the windows, so any shortcuts they handle themselves take priority
+        self.handle_shortcuts(ctx);
+        if query::take_search_pending() {
+            ctx.request_repaint_after(query::SEARCH_DEBOUNCE);
+        }
+        errors::show_database_errors(&mut self.toasts);
         self.toasts.show(ctx);
+        if let Some(action) = undo::take_clicked(ctx) {
+            self.undo(action);
+        }
+        if errors::take_reload_clicked(ctx) {
+            self.reload();
+        }
+    }
+}
+
+fn editable_id() -> egui::Id {
+    egui::Id::new("database editable")
+}
+
+fn set_editable(ctx: &egui::Context, editable: bool) {
+    ctx.data_mut(|d| d.insert_temp(editable_id(), editable));
+}
+
+/// Whether the database can be changed. When it can't, editing is turned off everywhere.
+fn is_editable(ctx: &egui::Context) -> bool {
+    ctx.data(|d| d.get_temp(editable_id())).unwrap_or(true)
+}
+
+fn unit_choices_id() -> egui::Id {
+    egui::Id::new("unit choices")
+}
+
+fn set_unit_choices(ctx: &egui::Context, choices: UnitChoices) {
+    ctx.data_mut(|d| d.insert_temp(unit_choices_id(), choices));
+}
+
+/// Which units the lists of units offer, from the settings.
+fn unit_choices(ctx: &egui::Context) -> UnitChoices {
+    ctx.data(|d| d.get_temp(unit_choices_id()))
+        .unwrap_or_default()
+}
+
+trait EditToggleExt {
+    /// The button which turns a window's edit mode on and off, which is kept off when the database
+    /// is read-only.
+    fn edit_toggle(&mut self, edit_mode: &mut bool) -> egui::Response;
+}
+
+impl EditToggleExt for egui::Ui {
+    fn edit_toggle(&mut self, edit_mode: &mut bool) -> egui::Response {
+        let editable = is_editable(self.ctx());
+        *edit_mode &= editable;
+        self.add_enabled_ui(editable, |ui| ui.toggle_value(edit_mode, "Edit"))
+            .inner
+            .on_disabled_hover_text("The database is read-only")
     }
 }
 
@@ -465,3 +1802,25 @@ impl PressedEnterExt for egui::Response {
         }
     }
 }
+
+/// Names a button which only shows a symbol, so screen readers have something to read out.
+trait AccessibleNameExt {
+    fn accessible_name(self, name: &str) -> Self;
+}
+
+impl AccessibleNameExt for egui::Response {
+    fn accessible_name(self, name: &str) -> Self {
+        let enabled = self.enabled();
+        self.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, enabled, name));
+        self
+    }
+}
+
+/// Starts another copy of the program the same way this one was started. It's up to the caller to
+/// close this one.
+fn restart() -> std::io::Result<()> {
+    std::process::Command::new(std::env::current_exe()?)
+        .args(std::env::args_os().skip(1))
+        .spawn()?;
+    Ok(())
+}
@@ -1,8 +1,17 @@
-pub struct AboutWindow {}
+use recipe_manager::database;
+
+pub struct AboutWindow {
+    /// The newest migration the database has been upgraded with.
+    schema_version: Option<String>,
+}
 
 impl AboutWindow {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(conn: &mut database::Connection) -> Self {
+        let schema_version = database::schema_version(conn)
+            .inspect_err(|error| log::error!("failed to get schema version: {error}"))
+            .ok()
+            .flatten();
+        Self { schema_version }
     }
 
     pub fn update(&mut self, ctx: &egui::Context) -> bool {
@@ -20,6 +29,9 @@ impl AboutWindow {
                     );
                     ui.heading("Recipe Manager");
                     ui.label(format!("Version {}", env!("CARGO_PKG_VERSION")));
+                    if let Some(schema_version) = &self.schema_version {
+                        ui.weak(format!("Database schema {schema_version}"));
+                    }
                     ui.label("Copyright Remi Bernotavicius 2024");
                     ui.hyperlink_to(
                         "Code on GitHub",
@@ -1,105 +1,283 @@
-use super::{generate_rtf, new_error_toast, query, search::SearchWidget, PressedEnterExt as _};
-use crate::database;
-use crate::database::models::{RecipeHandle, RecipeId};
-use std::collections::HashMap;
+use super::errors::QueryResultExt as _;
+#[cfg(feature = "email")]
+use super::menu_template::MenuTemplate;
+#[cfg(any(feature = "email", feature = "grocery-export"))]
+use super::new_success_toast;
+use super::{
+    diet,
+    i18n::{self, tr},
+    is_editable, new_error_toast, nutrition,
+    prep_plan::PrepPlanWindow,
+    recipe_preview::RecipePreviewExt as _,
+    search::SearchWidget,
+    settings::Settings,
+    undo::{self, UndoAction},
+    EditToggleExt as _, PressedEnterExt as _,
+};
+#[cfg(feature = "rtf")]
+use super::{
+    generate_rtf, ingredient_components, leftovers::LeftoversWindow, menu_template::MenuWindow,
+};
+use derive_more::Display;
+use recipe_manager::database;
+use recipe_manager::database::models::{CalendarEntry, RecipeHandle, RecipeId, RecipeStep};
+#[cfg(feature = "rtf")]
+use recipe_manager::database::models::{Ingredient, IngredientUsage};
+#[cfg(feature = "email")]
+use recipe_manager::email;
+use recipe_manager::query;
+use recipe_manager::recipe::calories_by_day;
+use recipe_manager::unit_conversion;
+use std::collections::{HashMap, HashSet};
+#[cfg(any(feature = "email", feature = "grocery-export"))]
+use std::sync::mpsc;
+use strum::{EnumIter, IntoEnumIterator as _};
 
-pub fn this_week() -> chrono::NaiveWeek {
+pub fn this_week(week_start: chrono::Weekday) -> chrono::NaiveWeek {
     let today = chrono::Local::now().date_naive();
-    today.week(chrono::Weekday::Sun)
+    today.week(week_start)
 }
 
 pub fn full_day_name(day: chrono::Weekday) -> &'static str {
     use chrono::Weekday::*;
 
     match day {
-        Sun => "Sunday",
-        Mon => "Monday",
-        Tue => "Tuesday",
-        Wed => "Wednesday",
-        Thu => "Thursday",
-        Fri => "Friday",
-        Sat => "Saturday",
+        Sun => tr("Sunday"),
+        Mon => tr("Monday"),
+        Tue => tr("Tuesday"),
+        Wed => tr("Wednesday"),
+        Thu => tr("Thursday"),
+        Fri => tr("Friday"),
+        Sat => tr("Saturday"),
     }
 }
 
+/// The date a step of a multi-day recipe falls on. Recipes are scheduled on the day of their last
+/// step, so earlier steps happen in the days leading up to it.
+fn step_date(scheduled: chrono::NaiveDate, day: i32, last_day: i32) -> Option<chrono::NaiveDate> {
+    scheduled.checked_sub_days(chrono::Days::new((last_day - day).max(0) as u64))
+}
+
+#[test]
+fn step_date_test() {
+    let date = |d| chrono::NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+
+    assert_eq!(step_date(date(10), 1, 3), Some(date(8)));
+    assert_eq!(step_date(date(10), 2, 3), Some(date(9)));
+    assert_eq!(step_date(date(10), 3, 3), Some(date(10)));
+    assert_eq!(step_date(date(1), 1, 2), Some(date(1).pred_opt().unwrap()));
+}
+
+/// A step of a multi-day recipe which falls on a different day than the recipe may be scheduled.
+#[derive(Clone)]
+pub struct LinkedStep {
+    pub recipe: RecipeHandle,
+    pub scheduled: chrono::NaiveDate,
+    pub step: RecipeStep,
+}
+
+fn get_week_steps(
+    conn: &mut database::Connection,
+    week: chrono::NaiveWeek,
+) -> HashMap<chrono::Weekday, Vec<LinkedStep>> {
+    use chrono::Datelike as _;
+
+    let longest = query::get_longest_recipe_steps(conn).unwrap_or_report();
+    if longest < 1 {
+        return HashMap::new();
+    }
+
+    // Recipes scheduled after this week can still have steps which fall in it.
+    let last = week
+        .last_day()
+        .checked_add_days(chrono::Days::new(longest as u64 - 1))
+        .unwrap();
+    let rows = query::get_calendar_recipe_steps(conn, week.first_day(), last).unwrap_or_report();
+
+    let mut last_days: HashMap<RecipeId, i32> = HashMap::new();
+    for (_, recipe, step) in &rows {
+        let last_day = last_days.entry(recipe.id).or_default();
+        *last_day = (*last_day).max(step.day);
+    }
+
+    let mut steps: HashMap<_, Vec<_>> = HashMap::new();
+    for (scheduled, recipe, step) in rows {
+        let Some(date) = step_date(scheduled, step.day, last_days[&recipe.id]) else {
+            continue;
+        };
+        if date >= week.first_day() && date <= week.last_day() {
+            steps.entry(date.weekday()).or_default().push(LinkedStep {
+                recipe,
+                scheduled,
+                step,
+            });
+        }
+    }
+    steps
+}
+
 pub struct RecipeWeek {
     start: chrono::NaiveWeek,
     week: HashMap<chrono::Weekday, RecipeHandle>,
+    cooked: HashSet<chrono::Weekday>,
+    servings: HashMap<chrono::Weekday, i32>,
+    from_freezer: HashSet<chrono::Weekday>,
+    steps: HashMap<chrono::Weekday, Vec<LinkedStep>>,
 }
 
 impl RecipeWeek {
     pub fn new(conn: &mut database::Connection, week: chrono::NaiveWeek) -> Self {
         Self {
-            week: query::get_calendar_week(conn, week),
+            week: query::get_calendar_week(conn, week).unwrap_or_report(),
+            cooked: query::get_calendar_week_cooked(conn, week).unwrap_or_report(),
+            servings: query::get_calendar_week_servings(conn, week).unwrap_or_report(),
+            from_freezer: query::get_calendar_week_from_freezer(conn, week).unwrap_or_report(),
+            steps: get_week_steps(conn, week),
             start: week,
         }
     }
 
+    /// The day the week starts on, from the settings when it was made.
+    fn start_day(&self) -> chrono::Weekday {
+        use chrono::Datelike as _;
+
+        self.start.first_day().weekday()
+    }
+
+    /// The days of the week in order, starting with the first.
+    fn days(&self) -> impl Iterator<Item = chrono::Weekday> {
+        std::iter::successors(Some(self.start_day()), |d| Some(d.succ())).take(7)
+    }
+
+    /// Switches to the week starting on the given day which includes the first day of this one.
+    pub fn set_start_day(&mut self, conn: &mut database::Connection, start_day: chrono::Weekday) {
+        if self.start_day() != start_day {
+            self.start = self.start.first_day().week(start_day);
+            self.refresh(conn);
+        }
+    }
+
     pub fn pick_date(
         &mut self,
         conn: &mut database::Connection,
         body: impl FnOnce(&mut chrono::NaiveDate),
     ) {
-        use chrono::Weekday::*;
-
         let mut date = self.start.first_day();
         body(&mut date);
-        let new_start = date.week(Sun);
+        let new_start = date.week(self.start_day());
         if self.start != new_start {
             self.start = new_start;
-            self.week = query::get_calendar_week(conn, self.start);
+            self.refresh(conn);
         }
     }
 
     pub fn recipes(&self) -> Vec<(chrono::Weekday, Option<RecipeHandle>)> {
-        use chrono::Weekday::*;
-
-        [Sun, Mon, Tue, Wed, Thu, Fri, Sat]
-            .into_iter()
+        self.days()
             .map(|day| (day, self.week.get(&day).cloned()))
             .collect()
     }
 
-    pub fn advance(&mut self, conn: &mut database::Connection) {
-        use chrono::Weekday::*;
-
+    pub fn advance(&mut self, conn: &mut database::Connection, weeks: u64) {
         self.start = self
             .start
             .first_day()
-            .checked_add_days(chrono::Days::new(7))
+            .checked_add_days(chrono::Days::new(7 * weeks))
             .unwrap()
-            .week(Sun);
-        self.week = query::get_calendar_week(conn, self.start);
+            .week(self.start_day());
+        self.refresh(conn);
     }
 
-    pub fn previous(&mut self, conn: &mut database::Connection) {
-        use chrono::Weekday::*;
-
+    pub fn previous(&mut self, conn: &mut database::Connection, weeks: u64) {
         self.start = self
             .start
             .first_day()
-            .checked_sub_days(chrono::Days::new(7))
+            .checked_sub_days(chrono::Days::new(7 * weeks))
             .unwrap()
-            .week(Sun);
-        self.week = query::get_calendar_week(conn, self.start);
+            .week(self.start_day());
+        self.refresh(conn);
     }
 
     pub fn date_for_day(&self, day: chrono::Weekday) -> chrono::NaiveDate {
-        use chrono::Weekday::*;
-
-        let day_number = [Sun, Mon, Tue, Wed, Thu, Fri, Sat]
-            .into_iter()
-            .position(|i| i == day)
-            .unwrap();
+        let day_number = day.days_since(self.start_day());
         self.start
             .first_day()
-            .checked_add_days(chrono::Days::new(day_number as u64))
+            .checked_add_days(chrono::Days::new(day_number.into()))
             .unwrap()
     }
 
-    pub fn clear_day(&mut self, conn: &mut database::Connection, day: chrono::Weekday) {
-        query::delete_calendar_entry(conn, self.date_for_day(day));
-        self.week.remove(&day);
+    /// Removes the recipe scheduled on the given day, returning the entry which was removed.
+    pub fn clear_day(
+        &mut self,
+        conn: &mut database::Connection,
+        day: chrono::Weekday,
+    ) -> Option<CalendarEntry> {
+        let date = self.date_for_day(day);
+        query::delete_calendar_entry(conn, date).or_report()?;
+        let recipe = self.week.remove(&day)?;
+        let cooked = self.cooked.remove(&day);
+        let servings = self.servings.remove(&day);
+        let from_freezer = self.from_freezer.remove(&day);
+        self.steps = get_week_steps(conn, self.start);
+        Some(CalendarEntry {
+            day: date,
+            recipe_id: recipe.id,
+            cooked,
+            servings,
+            from_freezer,
+        })
+    }
+
+    pub fn steps(&self, day: chrono::Weekday) -> Vec<LinkedStep> {
+        self.steps.get(&day).cloned().unwrap_or_default()
+    }
+
+    pub fn is_cooked(&self, day: chrono::Weekday) -> bool {
+        self.cooked.contains(&day)
+    }
+
+    pub fn set_cooked(
+        &mut self,
+        conn: &mut database::Connection,
+        day: chrono::Weekday,
+        cooked: bool,
+    ) {
+        query::set_calendar_entry_cooked(conn, self.date_for_day(day), cooked).or_report();
+        if cooked {
+            self.cooked.insert(day);
+        } else {
+            self.cooked.remove(&day);
+        }
+    }
+
+    pub fn servings(&self, day: chrono::Weekday) -> Option<i32> {
+        self.servings.get(&day).copied()
+    }
+
+    pub fn set_servings(
+        &mut self,
+        conn: &mut database::Connection,
+        day: chrono::Weekday,
+        servings: Option<i32>,
+    ) {
+        query::set_calendar_entry_servings(conn, self.date_for_day(day), servings).or_report();
+        match servings {
+            Some(servings) => self.servings.insert(day, servings),
+            None => self.servings.remove(&day),
+        };
+    }
+
+    pub fn is_from_freezer(&self, day: chrono::Weekday) -> bool {
+        self.from_freezer.contains(&day)
+    }
+
+    /// Takes a portion of the day's recipe out of the freezer instead of cooking it.
+    pub fn use_frozen_portion(&mut self, conn: &mut database::Connection, day: chrono::Weekday) {
+        if query::use_frozen_portion(conn, self.date_for_day(day))
+            .or_report()
+            .unwrap_or(false)
+        {
+            self.from_freezer.insert(day);
+        }
     }
 
     pub fn schedule(
@@ -108,7 +286,7 @@ impl RecipeWeek {
         day: chrono::Weekday,
         id: RecipeId,
     ) {
-        query::insert_or_update_calendar_entry(conn, self.date_for_day(day), id);
+        query::insert_or_update_calendar_entry(conn, self.date_for_day(day), id).or_report();
         *self = Self::new(conn, self.start);
     }
 
@@ -117,7 +295,12 @@ impl RecipeWeek {
     }
 
     pub fn refresh(&mut self, conn: &mut database::Connection) {
-        self.week = query::get_calendar_week(conn, self.start);
+        self.week = query::get_calendar_week(conn, self.start).unwrap_or_report();
+        self.cooked = query::get_calendar_week_cooked(conn, self.start).unwrap_or_report();
+        self.servings = query::get_calendar_week_servings(conn, self.start).unwrap_or_report();
+        self.from_freezer =
+            query::get_calendar_week_from_freezer(conn, self.start).unwrap_or_report();
+        self.steps = get_week_steps(conn, self.start);
     }
 }
 
@@ -131,24 +314,150 @@ struct RecipeBeingSelected {
 pub enum UpdateEvent {
     Closed,
     RecipeScheduled { week: chrono::NaiveWeek },
+    CalendarChanged,
+    FreezerChanged,
 }
 
-pub struct CalendarWindow {
-    week: RecipeWeek,
-    edit_mode: bool,
-    recipes_being_selected: HashMap<chrono::Weekday, RecipeBeingSelected>,
+struct WeekCalories {
+    week: chrono::NaiveWeek,
+    days: HashMap<chrono::Weekday, f32>,
 }
 
-impl CalendarWindow {
-    pub fn new(conn: &mut database::Connection) -> Self {
-        Self::new_with_args(conn, false)
+impl WeekCalories {
+    fn new(conn: &mut database::Connection, week: chrono::NaiveWeek) -> Self {
+        let ingredients = query::get_calendar_week_ingredients(conn, week).unwrap_or_report();
+        Self {
+            week,
+            days: calories_by_day(&ingredients),
+        }
+    }
+
+    fn total(&self) -> f32 {
+        self.days.values().sum()
     }
+}
+
+fn calories_text(ui: &egui::Ui, calories: f32, target: Option<f32>) -> egui::RichText {
+    let text = egui::RichText::new(nutrition::calories_display(calories));
+    match target {
+        Some(target) if calories > target => text.color(ui.visuals().error_fg_color),
+        Some(_) => text.color(egui::Color32::from_rgb(0x3c, 0xa0, 0x3c)),
+        None => text,
+    }
+}
+
+/// How many weeks the calendar shows side by side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+enum CalendarSpan {
+    #[default]
+    #[display("1 Week")]
+    OneWeek,
+    #[display("2 Weeks")]
+    TwoWeeks,
+    #[display("4 Weeks")]
+    FourWeeks,
+}
+
+impl CalendarSpan {
+    fn weeks(self) -> usize {
+        match self {
+            Self::OneWeek => 1,
+            Self::TwoWeeks => 2,
+            Self::FourWeeks => 4,
+        }
+    }
+}
+
+/// How wide each week is at least, when more than one is shown.
+const MIN_WEEK_WIDTH: f32 = 300.0;
 
-    fn new_with_args(conn: &mut database::Connection, edit_mode: bool) -> Self {
+/// One of the weeks shown in the calendar, with the recipes being picked for its empty days.
+struct CalendarWeek {
+    week: RecipeWeek,
+    recipes_being_selected: HashMap<chrono::Weekday, RecipeBeingSelected>,
+    calories: Option<WeekCalories>,
+    /// How many portions of each recipe are in the freezer.
+    freezer_stock: HashMap<RecipeId, i32>,
+}
+
+impl CalendarWeek {
+    fn new(conn: &mut database::Connection, week: chrono::NaiveWeek) -> Self {
         Self {
-            week: RecipeWeek::new(conn, this_week()),
-            edit_mode,
+            week: RecipeWeek::new(conn, week),
             recipes_being_selected: HashMap::new(),
+            calories: None,
+            freezer_stock: query::get_freezer_stock(conn).unwrap_or_report(),
+        }
+    }
+
+    /// Offers to use a frozen portion of the day's recipe when there are any, or shows that it's
+    /// coming out of the freezer.
+    fn update_freezer(
+        &mut self,
+        conn: &mut database::Connection,
+        day: chrono::Weekday,
+        recipe: &RecipeHandle,
+        ui: &mut egui::Ui,
+    ) -> Option<UpdateEvent> {
+        if self.week.is_from_freezer(day) {
+            ui.weak("Frozen")
+                .on_hover_text("Coming out of the freezer, so it's left off the shopping list");
+            return None;
+        }
+        let portions = self.freezer_stock.get(&recipe.id).copied().unwrap_or(0);
+        if portions == 0 {
+            return None;
+        }
+        let clicked = ui
+            .add_enabled(is_editable(ui.ctx()), egui::Button::new("Use Frozen"))
+            .on_hover_text(format!(
+                "{portions} in the freezer. Take one out instead of cooking, and leave it off \
+                the shopping list."
+            ))
+            .clicked();
+        if !clicked {
+            return None;
+        }
+        self.week.use_frozen_portion(conn, day);
+        Some(UpdateEvent::FreezerChanged)
+    }
+
+    fn calories(&mut self, conn: &mut database::Connection) -> &WeekCalories {
+        let week = self.week.week();
+        if self.calories.as_ref().is_none_or(|c| c.week != week) {
+            self.calories = Some(WeekCalories::new(conn, week));
+        }
+        self.calories.as_ref().unwrap()
+    }
+
+    fn refresh(&mut self, conn: &mut database::Connection) {
+        self.week.refresh(conn);
+        self.calories = None;
+    }
+
+    /// How many people are eating on the day, with nothing meaning as many as the recipe serves.
+    fn update_servings(
+        &mut self,
+        conn: &mut database::Connection,
+        day: chrono::Weekday,
+        ui: &mut egui::Ui,
+    ) {
+        let mut servings = self.week.servings(day).unwrap_or(0);
+        let changed = ui
+            .add(
+                egui::DragValue::new(&mut servings)
+                    .range(0..=99)
+                    .suffix(" people")
+                    .custom_formatter(|n, _| if n == 0.0 { "-".into() } else { n.to_string() }),
+            )
+            .on_hover_text(
+                "How many people are eating, for scaling the shopping list to. It's only scaled \
+                when the recipe says how many it serves.",
+            )
+            .changed();
+        if changed {
+            self.week
+                .set_servings(conn, day, (servings > 0).then_some(servings));
         }
     }
 
@@ -156,9 +465,15 @@ impl CalendarWindow {
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
+        edit_mode: bool,
+        prep_plan: &mut Option<PrepPlanWindow>,
         body: &mut egui_extras::TableBody<'_>,
     ) -> Vec<UpdateEvent> {
         let mut events = vec![];
+        let calories = self.calories(conn).days.clone();
+        let first_day = self.week.week().first_day();
+        let today = chrono::Local::now().date_naive();
         for (day, recipe) in self.week.recipes() {
             body.row(20.0, |mut row| {
                 row.col(|ui| {
@@ -166,26 +481,79 @@ impl CalendarWindow {
                 });
                 if let Some(recipe) = recipe {
                     row.col(|ui| {
-                        ui.label(recipe.name.clone());
+                        ui.label(recipe.name.clone())
+                            .on_hover_recipe_preview(conn, recipe.id);
+                        diet::show_conflicts(ui, conn, recipe.id, &settings.diet_restrictions);
                     });
                     row.col(|ui| {
-                        if self.edit_mode && ui.button("Clear").clicked() {
-                            self.week.clear_day(conn, day);
+                        if !edit_mode
+                            && ui
+                                .button("Plan")
+                                .on_hover_text("When to start cooking for dinner")
+                                .clicked()
+                        {
+                            *prep_plan = Some(PrepPlanWindow::new(
+                                conn,
+                                settings,
+                                self.week.date_for_day(day),
+                                Some(recipe.clone()),
+                                self.week.steps(day),
+                            ));
+                        }
+                        if !edit_mode {
+                            events.extend(self.update_freezer(conn, day, &recipe, ui));
+                        }
+                        if edit_mode && ui.button("Clear").clicked() {
+                            if let Some(entry) = self.week.clear_day(conn, day) {
+                                undo::add_undo_toast(
+                                    ui.ctx(),
+                                    toasts,
+                                    format!("Cleared {} from {}", recipe.name, full_day_name(day)),
+                                    UndoAction::ClearedCalendarEntry(entry),
+                                );
+                            }
+                            self.calories = None;
+                            events.push(UpdateEvent::CalendarChanged);
+                        }
+                        if edit_mode {
+                            self.update_servings(conn, day, ui);
                         }
                     });
-                    row.col(|_| {});
+                    row.col(|ui| {
+                        let mut cooked = self.week.is_cooked(day);
+                        let past = self.week.date_for_day(day) <= today;
+                        if !edit_mode
+                            && ui
+                                .add_enabled(
+                                    past && is_editable(ui.ctx()),
+                                    egui::SelectableLabel::new(cooked, "Cooked"),
+                                )
+                                .clicked()
+                        {
+                            cooked = !cooked;
+                            self.week.set_cooked(conn, day, cooked);
+                            events.push(UpdateEvent::CalendarChanged);
+                        }
+                    });
+                    row.col(|ui| {
+                        let day_calories = calories.get(&day).copied().unwrap_or(0.0);
+                        let text = calories_text(ui, day_calories, settings.daily_calorie_target);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(text);
+                        });
+                    });
                 } else {
                     row.col(|ui| {
-                        ui.label("No Recipe");
+                        ui.label(tr("No Recipe"));
                     });
-                    if self.edit_mode {
+                    if edit_mode {
                         let entry = self.recipes_being_selected.entry(day).or_default();
                         let mut selected = false;
                         row.col(|ui| {
                             selected |= ui
                                 .add(
                                     SearchWidget::new(
-                                        ("calendar select recipe", day),
+                                        ("calendar select recipe", first_day, day),
                                         &mut entry.name,
                                         &mut entry.recipe_id,
                                         |query| {
@@ -193,23 +561,27 @@ impl CalendarWindow {
                                                 conn,
                                                 &mut entry.cached_recipe_search,
                                                 query,
+                                                false,
                                             )
+                                            .unwrap_or_report()
                                         },
                                     )
                                     .desired_width(ui.available_width() - 20.0)
-                                    .hint_text("search for recipe"),
+                                    .hint_text(tr("search for recipe")),
                                 )
                                 .pressed_enter();
                         });
 
                         let e = !entry.name.is_empty();
                         row.col(|ui| {
-                            selected |= ui.add_enabled(e, egui::Button::new("Select")).clicked();
+                            selected |=
+                                ui.add_enabled(e, egui::Button::new(tr("Select"))).clicked();
                         });
 
                         if selected && e {
                             if let Some(recipe_id) = entry.recipe_id {
                                 self.week.schedule(conn, day, recipe_id);
+                                self.calories = None;
                                 *entry = Default::default();
 
                                 events.push(UpdateEvent::RecipeScheduled {
@@ -222,58 +594,541 @@ impl CalendarWindow {
                     }
                 }
             });
+            for linked in self.week.steps(day) {
+                body.row(20.0, |mut row| {
+                    row.col(|_| {});
+                    row.col(|ui| {
+                        let label = unit_conversion::with_temperature_conversions(
+                            &linked.step.label,
+                            settings.measurement_system,
+                        );
+                        ui.weak(format!(
+                            "{} - Day {}: {label}",
+                            &linked.recipe.name, linked.step.day
+                        ))
+                        .on_hover_text(format!(
+                            "{} is scheduled for {}",
+                            &linked.recipe.name,
+                            linked
+                                .scheduled
+                                .format(&format!("%A, {}", settings.date_format.without_year()))
+                        ));
+                    });
+                });
+            }
         }
         events
     }
+}
 
-    fn update_controls(
+pub struct CalendarWindow {
+    /// The first week shown, followed by the rest of the weeks in the span.
+    weeks: Vec<CalendarWeek>,
+    span: CalendarSpan,
+    edit_mode: bool,
+    prep_plan: Option<PrepPlanWindow>,
+    #[cfg(feature = "rtf")]
+    menu_window: Option<MenuWindow>,
+    #[cfg(feature = "rtf")]
+    leftovers_window: Option<LeftoversWindow>,
+    #[cfg(feature = "rtf")]
+    expand_blends: bool,
+    #[cfg(feature = "rtf")]
+    include_staples: bool,
+    #[cfg(feature = "email")]
+    sending: Option<mpsc::Receiver<recipe_manager::Result<()>>>,
+    /// The grocery list app the shopping list is being sent to, and the result.
+    #[cfg(feature = "grocery-export")]
+    exporting: Option<(&'static str, mpsc::Receiver<recipe_manager::Result<()>>)>,
+}
+
+impl CalendarWindow {
+    pub fn new(conn: &mut database::Connection, settings: &Settings) -> Self {
+        Self::new_with_args(conn, false, settings.week_start.weekday())
+    }
+
+    fn new_with_args(
+        conn: &mut database::Connection,
+        edit_mode: bool,
+        week_start: chrono::Weekday,
+    ) -> Self {
+        Self {
+            weeks: vec![CalendarWeek::new(conn, this_week(week_start))],
+            span: CalendarSpan::default(),
+            edit_mode,
+            prep_plan: None,
+            #[cfg(feature = "rtf")]
+            menu_window: None,
+            #[cfg(feature = "rtf")]
+            leftovers_window: None,
+            #[cfg(feature = "rtf")]
+            expand_blends: false,
+            #[cfg(feature = "rtf")]
+            include_staples: false,
+            #[cfg(feature = "email")]
+            sending: None,
+            #[cfg(feature = "grocery-export")]
+            exporting: None,
+        }
+    }
+
+    fn first_week(&mut self) -> &mut CalendarWeek {
+        &mut self.weeks[0]
+    }
+
+    /// Makes the weeks after the first follow on from it, reading the ones which moved.
+    fn fill_weeks(&mut self, conn: &mut database::Connection) {
+        let first = self.weeks[0].week.week();
+        let start_day = self.weeks[0].week.start_day();
+        self.weeks.truncate(self.span.weeks());
+        for i in 1..self.span.weeks() {
+            let week = first
+                .first_day()
+                .checked_add_days(chrono::Days::new(7 * i as u64))
+                .unwrap()
+                .week(start_day);
+            match self.weeks.get_mut(i) {
+                Some(shown) if shown.week.week() == week => {}
+                Some(shown) => *shown = CalendarWeek::new(conn, week),
+                None => self.weeks.push(CalendarWeek::new(conn, week)),
+            }
+        }
+    }
+
+    #[cfg(feature = "rtf")]
+    fn shopping_list_ingredients(
+        &self,
+        conn: &mut database::Connection,
+    ) -> Vec<(IngredientUsage, Ingredient)> {
+        let mut ingredients = vec![];
+        for shown in &self.weeks {
+            ingredients.extend(
+                query::get_calendar_week_shopping_ingredients(conn, shown.week.week())
+                    .unwrap_or_report(),
+            );
+        }
+        if self.expand_blends {
+            ingredients = ingredient_components::expand_compounds(conn, ingredients);
+        }
+        if !self.include_staples {
+            ingredients.retain(|(_, i)| !i.staple);
+        }
+        ingredients
+    }
+
+    /// Suggests recipes for the free days which would use up what the shopping list leaves over.
+    #[cfg(feature = "rtf")]
+    fn suggest_leftovers(
         &mut self,
         conn: &mut database::Connection,
+        ingredients: &[(IngredientUsage, Ingredient)],
+    ) {
+        let today = chrono::Local::now().date_naive();
+        let mut scheduled = HashSet::new();
+        let mut free_days = vec![];
+        for shown in &self.weeks {
+            for (day, recipe) in shown.week.recipes() {
+                match recipe {
+                    Some(recipe) => {
+                        scheduled.insert(recipe.id);
+                    }
+                    None => {
+                        let date = shown.week.date_for_day(day);
+                        if date >= today {
+                            free_days.push(date);
+                        }
+                    }
+                }
+            }
+        }
+        self.leftovers_window = LeftoversWindow::new(conn, ingredients, &scheduled, free_days);
+    }
+
+    #[cfg(feature = "rtf")]
+    fn update_leftovers_window(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        let Some(window) = &mut self.leftovers_window else {
+            return;
+        };
+        let (closed, scheduled) = window.update(ctx, conn, settings);
+        if closed {
+            self.leftovers_window = None;
+        }
+        if scheduled.is_empty() {
+            return;
+        }
+        self.recipe_scheduled(conn);
+        let week_start = self.weeks[0].week.start_day();
+        for day in scheduled {
+            events.push(UpdateEvent::RecipeScheduled {
+                week: day.week(week_start),
+            });
+        }
+    }
+
+    /// Emails the menu and shopping list in the background, since the server can take a while.
+    #[cfg(feature = "email")]
+    fn send(&mut self, ctx: &egui::Context, conn: &mut database::Connection, settings: &Settings) {
+        let calories = self.first_week().calories(conn).days.clone();
+        let week = &self.weeks[0].week;
+        let template = MenuTemplate::load().unwrap_or_else(|error| {
+            log::warn!("using the default menu template: {error}");
+            MenuTemplate::default()
+        });
+        let menu = generate_rtf::menu_document(
+            week.week(),
+            &generate_rtf::menu_recipes(week),
+            &calories,
+            &template,
+            settings.date_format,
+        );
+        let shopping_list = generate_rtf::shopping_list_document(
+            week.week(),
+            self.shopping_list_ingredients(conn),
+            &query::get_ingredient_category_names(conn).unwrap_or_report(),
+            settings.fraction_precision,
+            settings.measurement_system,
+            settings.date_format,
+        );
+        let subject = format!(
+            "Menu for the week of {}",
+            week.week()
+                .first_day()
+                .format(settings.date_format.without_year())
+        );
+        let body = format!("{}\n\n{}", menu.plain_text, shopping_list.plain_text);
+        let attachments = [menu, shopping_list]
+            .into_iter()
+            .map(|d| email::Attachment {
+                file_name: d.file_name,
+                content_type: "application/rtf",
+                content: d.rich_text.into_bytes(),
+            })
+            .collect();
+
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        let smtp = settings.email.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(email::send(&smtp, &subject, body, attachments));
+            ctx.request_repaint();
+        });
+        self.sending = Some(receiver);
+    }
+
+    #[cfg(feature = "email")]
+    fn poll_sending(&mut self, toasts: &mut egui_toast::Toasts) {
+        let Some(sending) = &self.sending else {
+            return;
+        };
+        match sending.try_recv() {
+            Ok(Ok(())) => {
+                toasts.add(new_success_toast("Sent the menu and shopping list"));
+                self.sending = None;
+            }
+            Ok(Err(error)) => {
+                toasts.add(new_error_toast(format!("Couldn't send email: {error}")));
+                self.sending = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                toasts.add(new_error_toast("Sending email stopped unexpectedly"));
+                self.sending = None;
+            }
+        }
+    }
+
+    #[cfg(feature = "grocery-export")]
+    fn grocery_items(
+        &self,
+        conn: &mut database::Connection,
+        settings: &Settings,
+    ) -> Vec<recipe_manager::grocery::GroceryItem> {
+        generate_rtf::shopping_list_grocery_items(
+            self.shopping_list_ingredients(conn),
+            &query::get_ingredient_category_names(conn).unwrap_or_report(),
+            settings.fraction_precision,
+            settings.measurement_system,
+        )
+    }
+
+    /// The menu of grocery list apps to send the shopping list to. The ones with an API are sent
+    /// to in the background, like email.
+    #[cfg(feature = "grocery-export")]
+    fn update_grocery_export(
+        &mut self,
+        ui: &mut egui::Ui,
+        conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
+    ) {
+        if self.exporting.is_some() {
+            ui.spinner();
+            return;
+        }
+        ui.menu_button("Send List To", |ui| {
+            for (name, exporter) in settings.grocery.exporters() {
+                if ui.button(name).clicked() {
+                    let items = self.grocery_items(conn, settings);
+                    let (sender, receiver) = mpsc::channel();
+                    let ctx = ui.ctx().clone();
+                    std::thread::spawn(move || {
+                        let _ = sender.send(exporter.push(&items));
+                        ctx.request_repaint();
+                    });
+                    self.exporting = Some((name, receiver));
+                    ui.close_menu();
+                }
+            }
+            let anylist = ui
+                .button("AnyList")
+                .on_hover_text("Copy the list to paste into AnyList's \"Add Items\"");
+            if anylist.clicked() {
+                let items = self.grocery_items(conn, settings);
+                let lines: Vec<_> = items.iter().map(|i| i.line()).collect();
+                ui.ctx().copy_text(lines.join("\n"));
+                toasts.add(new_success_toast("Copied the shopping list for AnyList"));
+                ui.close_menu();
+            }
+            if settings.grocery.exporters().is_empty() {
+                ui.label("Set up Todoist or Bring! in the settings");
+            }
+        });
+    }
+
+    #[cfg(feature = "grocery-export")]
+    fn poll_exporting(&mut self, toasts: &mut egui_toast::Toasts) {
+        let Some((name, exporting)) = &self.exporting else {
+            return;
+        };
+        match exporting.try_recv() {
+            Ok(Ok(())) => {
+                toasts.add(new_success_toast(format!(
+                    "Sent the shopping list to {name}"
+                )));
+                self.exporting = None;
+            }
+            Ok(Err(error)) => {
+                toasts.add(new_error_toast(format!(
+                    "Couldn't send the shopping list to {name}: {error}"
+                )));
+                self.exporting = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                toasts.add(new_error_toast(format!(
+                    "Sending the shopping list to {name} stopped unexpectedly"
+                )));
+                self.exporting = None;
+            }
+        }
+    }
+
+    fn update_controls(
+        &mut self,
+        conn: &mut database::Connection,
+        #[cfg_attr(not(feature = "rtf"), expect(unused_variables))] toasts: &mut egui_toast::Toasts,
+        #[cfg_attr(not(feature = "rtf"), expect(unused_variables))] settings: &Settings,
         ui: &mut egui::Ui,
     ) {
         ui.separator();
         ui.horizontal(|ui| {
-            ui.toggle_value(&mut self.edit_mode, "Edit");
-            if ui.button("Previous").clicked() {
-                self.week.previous(conn);
-                self.recipes_being_selected.clear();
+            ui.edit_toggle(&mut self.edit_mode);
+            // Moving by the whole span, so pay periods can be planned one after another.
+            let weeks = self.span.weeks() as u64;
+            if ui.button(tr("Previous")).clicked() {
+                self.first_week().week.previous(conn, weeks);
+                self.first_week().recipes_being_selected.clear();
+                self.fill_weeks(conn);
             }
-            if ui.button("Next").clicked() {
-                self.week.advance(conn);
-                self.recipes_being_selected.clear();
+            if ui.button(tr("Next")).clicked() {
+                self.first_week().week.advance(conn, weeks);
+                self.first_week().recipes_being_selected.clear();
+                self.fill_weeks(conn);
             }
+            #[cfg(feature = "rtf")]
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("Menu").clicked() {
-                    if let Err(error) = generate_rtf::generate_and_open_menu(&self.week) {
-                        toasts.add(new_error_toast(format!("Error generating menu: {error}")));
+                #[cfg(feature = "email")]
+                if self.sending.is_some() {
+                    ui.spinner();
+                } else {
+                    let configured = settings.email.is_configured();
+                    let send = ui
+                        .add_enabled(configured, egui::Button::new(tr("Send")))
+                        .on_hover_text("Email the menu and shopping list")
+                        .on_disabled_hover_text("Set up email in the settings first");
+                    if send.clicked() {
+                        self.send(ui.ctx(), conn, settings);
                     }
                 }
-                if ui.button("Shopping List").clicked() {
-                    let mut ingredients = vec![];
-                    for (_, recipe) in self.week.recipes() {
-                        if let Some(recipe) = recipe {
-                            ingredients.extend(query::get_ingredients_for_recipe(conn, recipe.id));
+                if ui.button(tr("Menu")).clicked() {
+                    let calories = self.first_week().calories(conn).days.clone();
+                    let week = &self.weeks[0].week;
+                    self.menu_window = Some(MenuWindow::new(
+                        week.week(),
+                        generate_rtf::menu_recipes(week),
+                        calories,
+                        settings.date_format,
+                    ));
+                }
+                let shopping_list = ui.button(tr("Shopping List"));
+                let shopping_list = if self.weeks.len() > 1 {
+                    shopping_list.on_hover_text("Everything for all of the weeks shown")
+                } else {
+                    shopping_list
+                };
+                if shopping_list.clicked() {
+                    let ingredients = self.shopping_list_ingredients(conn);
+                    match generate_rtf::generate_and_open_shopping_list(
+                        self.weeks[0].week.week(),
+                        ingredients.clone(),
+                        &query::get_ingredient_category_names(conn).unwrap_or_report(),
+                        settings.fraction_precision,
+                        settings.measurement_system,
+                        settings.date_format,
+                    ) {
+                        Ok(()) => self.suggest_leftovers(conn, &ingredients),
+                        Err(error) => {
+                            toasts.add(new_error_toast(format!(
+                                "Error generating shopping list: {error}"
+                            )));
                         }
                     }
-                    if let Err(error) =
-                        generate_rtf::generate_and_open_shopping_list(self.week.week(), ingredients)
-                    {
-                        toasts.add(new_error_toast(format!(
-                            "Error generating shopping list: {error}"
-                        )));
-                    }
                 }
+                #[cfg(feature = "grocery-export")]
+                self.update_grocery_export(ui, conn, toasts, settings);
+                ui.checkbox(&mut self.expand_blends, tr("Expand blends"));
+                ui.checkbox(&mut self.include_staples, tr("Include staples"));
+            });
+        });
+    }
+
+    fn update_title(
+        &mut self,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        ui: &mut egui::Ui,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Week of"));
+            let first = &mut self.weeks[0];
+            let before = first.week.week();
+            first.week.pick_date(conn, |date| {
+                ui.add(
+                    egui_extras::DatePickerButton::new(date).format(settings.date_format.short()),
+                );
+            });
+            if first.week.week() != before {
+                first.recipes_being_selected.clear();
+            }
+            let span = self.span;
+            egui::ComboBox::from_id_salt("calendar span")
+                .selected_text(self.span.to_string())
+                .show_ui(ui, |ui| {
+                    for s in CalendarSpan::iter() {
+                        ui.selectable_value(&mut self.span, s, s.to_string());
+                    }
+                });
+            if self.span != span || self.weeks[0].week.week() != before {
+                self.fill_weeks(conn);
+            }
+
+            let total: f32 = self
+                .weeks
+                .iter_mut()
+                .map(|w| w.calories(conn).total())
+                .sum();
+            let days = 7.0 * self.weeks.len() as f32;
+            let target = settings.daily_calorie_target.map(|t| t * days);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(calories_text(ui, total, target));
+                ui.label(tr("Total Calories:"));
             });
         });
     }
 
+    /// The weeks side by side, each with a heading when there's more than one.
+    fn update_weeks(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
+        ui: &mut egui::Ui,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let multiple = self.weeks.len() > 1;
+        egui_extras::StripBuilder::new(ui)
+            .sizes(egui_extras::Size::remainder(), self.weeks.len())
+            .horizontal(|mut strip| {
+                for (i, shown) in self.weeks.iter_mut().enumerate() {
+                    strip.cell(|ui| {
+                        let first_day = shown.week.week().first_day();
+                        if multiple {
+                            ui.horizontal(|ui| {
+                                ui.strong(format!(
+                                    "{} {}",
+                                    tr("Week of"),
+                                    i18n::format_date(first_day, settings.date_format.short())
+                                ));
+                                let total = shown.calories(conn).total();
+                                let target = settings.daily_calorie_target.map(|t| t * 7.0);
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        ui.label(calories_text(ui, total, target));
+                                    },
+                                );
+                            });
+                        }
+                        egui_extras::TableBuilder::new(ui)
+                            .id_salt(("calendar table", i))
+                            .striped(false)
+                            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                            .column(egui_extras::Column::exact(80.0))
+                            .column(egui_extras::Column::auto())
+                            .column(egui_extras::Column::remainder())
+                            .column(egui_extras::Column::exact(60.0))
+                            .column(egui_extras::Column::exact(60.0))
+                            .body(|mut body| {
+                                events.extend(shown.update_table(
+                                    conn,
+                                    toasts,
+                                    settings,
+                                    self.edit_mode,
+                                    &mut self.prep_plan,
+                                    &mut body,
+                                ));
+                            });
+                    });
+                }
+            });
+        if events
+            .iter()
+            .any(|e| matches!(e, UpdateEvent::FreezerChanged))
+        {
+            self.freezer_changed(conn);
+        }
+        events
+    }
+
     pub fn update(
         &mut self,
         ctx: &egui::Context,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
     ) -> Vec<UpdateEvent> {
+        // The settings can change which day weeks start on while the calendar is open.
+        self.first_week()
+            .week
+            .set_start_day(conn, settings.week_start.weekday());
+        self.fill_weeks(conn);
+
         let style = ctx.style();
         let text_height = egui::TextStyle::Body
             .resolve(&style)
@@ -288,44 +1143,51 @@ impl CalendarWindow {
         let title_height = text_height + spacing;
         let controls_height = button_height + spacing + separator_height;
 
+        #[cfg(feature = "email")]
+        self.poll_sending(toasts);
+        #[cfg(feature = "grocery-export")]
+        self.poll_exporting(toasts);
+
         let mut events = vec![];
         let mut open = true;
-        egui::Window::new("Calendar")
+        let mut window = egui::Window::new("Calendar")
             .open(&mut open)
             .default_width(500.0)
-            .default_height(100.0)
-            .show(ctx, |ui| {
-                egui_extras::StripBuilder::new(ui)
-                    .size(egui_extras::Size::exact(title_height))
-                    .size(egui_extras::Size::remainder())
-                    .size(egui_extras::Size::exact(controls_height))
-                    .vertical(|mut strip| {
-                        strip.cell(|ui| {
-                            ui.horizontal(|ui| {
-                                ui.label(format!("Week of "));
-                                self.week.pick_date(conn, |date| {
-                                    ui.add(egui_extras::DatePickerButton::new(date));
-                                });
-                            });
-                        });
-                        strip.cell(|ui| {
-                            egui_extras::TableBuilder::new(ui)
-                                .id_salt("calendar table")
-                                .striped(false)
-                                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                                .column(egui_extras::Column::exact(80.0))
-                                .column(egui_extras::Column::auto())
-                                .column(egui_extras::Column::remainder())
-                                .column(egui_extras::Column::exact(50.0))
-                                .body(|mut body| {
-                                    events.extend(self.update_table(conn, toasts, &mut body));
-                                });
-                        });
-                        strip.cell(|ui| {
-                            self.update_controls(conn, toasts, ui);
-                        });
+            .default_height(100.0);
+        if self.weeks.len() > 1 {
+            window = window.min_width(MIN_WEEK_WIDTH * self.weeks.len() as f32);
+        }
+        window.show(ctx, |ui| {
+            egui_extras::StripBuilder::new(ui)
+                .size(egui_extras::Size::exact(title_height))
+                .size(egui_extras::Size::remainder())
+                .size(egui_extras::Size::exact(controls_height))
+                .vertical(|mut strip| {
+                    strip.cell(|ui| {
+                        self.update_title(conn, settings, ui);
                     });
-            });
+                    strip.cell(|ui| {
+                        events.extend(self.update_weeks(conn, toasts, settings, ui));
+                    });
+                    strip.cell(|ui| {
+                        self.update_controls(conn, toasts, settings, ui);
+                    });
+                });
+        });
+
+        if let Some(prep_plan) = &mut self.prep_plan {
+            if prep_plan.update(ctx) {
+                self.prep_plan = None;
+            }
+        }
+        #[cfg(feature = "rtf")]
+        if let Some(menu_window) = &mut self.menu_window {
+            if menu_window.update(ctx, toasts) {
+                self.menu_window = None;
+            }
+        }
+        #[cfg(feature = "rtf")]
+        self.update_leftovers_window(ctx, conn, settings, &mut events);
 
         if !open {
             events.push(UpdateEvent::Closed);
@@ -334,18 +1196,41 @@ impl CalendarWindow {
     }
 
     pub fn recipe_scheduled(&mut self, conn: &mut database::Connection) {
-        self.week.refresh(conn);
+        for shown in &mut self.weeks {
+            shown.refresh(conn);
+        }
     }
 
+    #[cfg(feature = "legacy-import")]
     pub fn calendar_imported(&mut self, conn: &mut database::Connection) {
-        self.week.refresh(conn);
+        for shown in &mut self.weeks {
+            shown.refresh(conn);
+        }
+    }
+
+    /// Reads how much of each recipe is in the freezer again.
+    pub fn freezer_changed(&mut self, conn: &mut database::Connection) {
+        let stock = query::get_freezer_stock(conn).unwrap_or_report();
+        for shown in &mut self.weeks {
+            shown.freezer_stock = stock.clone();
+        }
+    }
+
+    pub fn ingredients_changed(&mut self) {
+        for shown in &mut self.weeks {
+            shown.calories = None;
+        }
     }
 
     pub fn recipe_deleted(&mut self, conn: &mut database::Connection) {
-        *self = Self::new_with_args(conn, self.edit_mode);
+        let span = self.span;
+        *self = Self::new_with_args(conn, self.edit_mode, self.weeks[0].week.start_day());
+        self.span = span;
+        self.fill_weeks(conn);
     }
 
+    /// The first week shown.
     pub fn week(&self) -> chrono::NaiveWeek {
-        self.week.week()
+        self.weeks[0].week.week()
     }
 }
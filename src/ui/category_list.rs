@@ -1,32 +1,71 @@
-use super::{query, recipe_list::RecipeListWindow, PressedEnterExt as _};
-use crate::database;
-use crate::database::models::{RecipeCategory, RecipeCategoryId};
+use super::errors::QueryResultExt as _;
+use super::{
+    recipe_list::RecipeListWindow, toast_options, EditToggleExt as _, PressedEnterExt as _,
+};
+use derive_more::Display;
+use recipe_manager::database;
+use recipe_manager::database::models::{RecipeCategory, RecipeCategoryId, RecipeDuration};
+use recipe_manager::query;
 use std::collections::HashMap;
+use strum::{EnumIter, IntoEnumIterator as _};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+enum CategorySort {
+    #[default]
+    #[display("name")]
+    Alphabetical,
+    #[display("most recipes")]
+    RecipeCount,
+    /// By the last time one of the category's recipes was on the calendar.
+    #[display("recently used")]
+    RecentlyUsed,
+}
 
 struct CategoryBeingEdited {
     id: RecipeCategoryId,
     name: String,
+    default_duration: Option<RecipeDuration>,
 }
 
 pub struct CategoryListWindow {
-    categories: Vec<RecipeCategory>,
+    categories: Vec<(RecipeCategory, usize)>,
+    last_used: HashMap<RecipeCategoryId, chrono::NaiveDate>,
+    sort: CategorySort,
     new_category_name: String,
     edit_mode: bool,
     category_being_edited: Option<CategoryBeingEdited>,
 }
 
 impl CategoryListWindow {
-    fn new_with_args(conn: &mut database::Connection, edit_mode: bool) -> Self {
-        Self {
-            categories: query::get_recipe_categories(conn),
+    fn new_with_args(conn: &mut database::Connection, edit_mode: bool, sort: CategorySort) -> Self {
+        let mut window = Self {
+            categories: query::get_recipe_categories(conn).unwrap_or_report(),
+            last_used: query::get_recipe_categories_last_used(conn).unwrap_or_report(),
+            sort,
             new_category_name: String::new(),
             edit_mode,
             category_being_edited: None,
-        }
+        };
+        window.sort_categories();
+        window
     }
 
     pub fn new(conn: &mut database::Connection) -> Self {
-        Self::new_with_args(conn, false)
+        Self::new_with_args(conn, false, CategorySort::default())
+    }
+
+    /// Ties are broken by name, since the sorts are stable.
+    fn sort_categories(&mut self) {
+        self.categories.sort_by_key(|(c, _)| c.name.to_lowercase());
+        match self.sort {
+            CategorySort::Alphabetical => {}
+            CategorySort::RecipeCount => self
+                .categories
+                .sort_by_key(|(_, count)| std::cmp::Reverse(*count)),
+            CategorySort::RecentlyUsed => self
+                .categories
+                .sort_by_key(|(c, _)| std::cmp::Reverse(self.last_used.get(&c.id).copied())),
+        }
     }
 
     fn update_table_contents(
@@ -37,16 +76,47 @@ impl CategoryListWindow {
         body: &mut egui_extras::TableBody<'_>,
         refresh_self: &mut bool,
     ) {
-        for RecipeCategory { name, id: cat_id } in &self.categories {
+        for (category, count) in &self.categories {
+            let RecipeCategory {
+                name,
+                id: cat_id,
+                default_duration,
+            } = category;
             if let Some(e) = &mut self.category_being_edited {
                 if e.id == *cat_id {
                     body.row(20.0, |mut row| {
                         row.col(|ui| {
-                            ui.add(egui::TextEdit::singleline(&mut e.name));
+                            ui.add(egui::TextEdit::singleline(&mut e.name).desired_width(120.0));
+                            let describe = |d: Option<RecipeDuration>| {
+                                d.map_or("short".to_owned(), |d| d.to_string())
+                            };
+                            egui::ComboBox::from_id_salt(("category default duration", e.id))
+                                .selected_text(describe(e.default_duration))
+                                .show_ui(ui, |ui| {
+                                    for d in RecipeDuration::iter() {
+                                        let value = (d != RecipeDuration::Short).then_some(d);
+                                        ui.selectable_value(
+                                            &mut e.default_duration,
+                                            value,
+                                            describe(value),
+                                        );
+                                    }
+                                })
+                                .response
+                                .on_hover_text(
+                                    "How long new recipes in the category start out taking",
+                                );
                         });
+                        row.col(|_| {});
                         row.col(|ui| {
                             if ui.button("Save").clicked() {
-                                query::edit_category(conn, e.id, &e.name);
+                                query::edit_category(conn, e.id, &e.name).or_report();
+                                query::edit_category_default_duration(
+                                    conn,
+                                    e.id,
+                                    e.default_duration,
+                                )
+                                .or_report();
                                 if let Some(w) = recipe_list_windows.get_mut(&e.id) {
                                     w.category_name_changed(e.name.clone());
                                 }
@@ -63,16 +133,21 @@ impl CategoryListWindow {
                 row.col(|ui| {
                     ui.toggle_value(&mut shown, name.clone());
                 });
+                row.col(|ui| {
+                    ui.weak(count.to_string())
+                        .on_hover_text(format!("{count} recipes"));
+                });
                 row.col(|ui| {
                     if self.edit_mode {
                         if ui.button("Edit").clicked() {
                             self.category_being_edited = Some(CategoryBeingEdited {
                                 id: *cat_id,
                                 name: name.clone(),
+                                default_duration: *default_duration,
                             });
                         }
                         if ui.button("Delete").clicked() {
-                            if query::delete_category(conn, *cat_id) {
+                            if query::delete_category(conn, *cat_id).unwrap_or_report() {
                                 *refresh_self = true;
                                 shown = false;
                             } else {
@@ -80,10 +155,7 @@ impl CategoryListWindow {
                                     text: "Couldn't delete category, it still contains recipes"
                                         .into(),
                                     kind: egui_toast::ToastKind::Error,
-                                    options: egui_toast::ToastOptions::default()
-                                        .duration_in_seconds(3.0)
-                                        .show_progress(false)
-                                        .show_icon(true),
+                                    options: toast_options(3.0).show_progress(false),
                                     ..Default::default()
                                 });
                             }
@@ -92,11 +164,10 @@ impl CategoryListWindow {
                 });
 
                 if shown && !recipe_list_windows.contains_key(&cat_id) {
-                    let cat = RecipeCategory {
-                        id: *cat_id,
-                        name: name.clone(),
-                    };
-                    recipe_list_windows.insert(*cat_id, RecipeListWindow::new(conn, cat, false));
+                    recipe_list_windows.insert(
+                        *cat_id,
+                        RecipeListWindow::new(conn, category.clone(), false),
+                    );
                 } else if !shown {
                     recipe_list_windows.remove(cat_id);
                 }
@@ -118,6 +189,7 @@ impl CategoryListWindow {
             .striped(false)
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
             .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::auto())
             .column(egui_extras::Column::exact(90.0))
             .min_scrolled_height(0.0)
             .max_scroll_height(available_height)
@@ -139,7 +211,21 @@ impl CategoryListWindow {
         refresh_self: &mut bool,
     ) {
         ui.horizontal(|ui| {
-            ui.toggle_value(&mut self.edit_mode, "Edit");
+            ui.edit_toggle(&mut self.edit_mode);
+            if !self.edit_mode {
+                let sort = self.sort;
+                ui.label("Sort by:");
+                egui::ComboBox::from_id_salt("category sort")
+                    .selected_text(self.sort.to_string())
+                    .show_ui(ui, |ui| {
+                        for s in CategorySort::iter() {
+                            ui.selectable_value(&mut self.sort, s, s.to_string());
+                        }
+                    });
+                if self.sort != sort {
+                    self.sort_categories();
+                }
+            }
             if self.edit_mode {
                 let mut new_category = false;
                 new_category |= ui
@@ -154,8 +240,12 @@ impl CategoryListWindow {
                     .add_enabled(e, egui::Button::new("New Category"))
                     .clicked();
 
-                if new_category && e {
-                    query::add_category(conn, &self.new_category_name);
+                if new_category
+                    && e
+                    && query::add_category(conn, &self.new_category_name)
+                        .or_report()
+                        .is_some()
+                {
                     self.new_category_name = "".into();
                     *refresh_self = true;
                 }
@@ -163,13 +253,14 @@ impl CategoryListWindow {
         });
     }
 
+    /// Returns whether any categories were added, renamed or deleted.
     pub fn update(
         &mut self,
         ctx: &egui::Context,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
         recipe_list_windows: &mut HashMap<RecipeCategoryId, RecipeListWindow>,
-    ) {
+    ) -> bool {
         let style = ctx.style();
         let button_height = (egui::TextStyle::Button.resolve(&style).size
             + style.spacing.button_padding.y as f32 * 2.0)
@@ -196,11 +287,12 @@ impl CategoryListWindow {
         });
 
         if refresh_self {
-            *self = Self::new_with_args(conn, self.edit_mode);
+            *self = Self::new_with_args(conn, self.edit_mode, self.sort);
         }
+        refresh_self
     }
 
-    pub fn recipes_imported(&mut self, conn: &mut database::Connection) {
-        *self = Self::new_with_args(conn, self.edit_mode);
+    pub fn categories_changed(&mut self, conn: &mut database::Connection) {
+        *self = Self::new_with_args(conn, self.edit_mode, self.sort);
     }
 }
@@ -0,0 +1,242 @@
+//! Notes on how each time a recipe was cooked turned out, kept with the recipe. After the days a
+//! recipe was checked off as cooked on the calendar, the user is asked how it went.
+
+use super::errors::QueryResultExt as _;
+use super::{i18n, is_editable, settings::Settings, AccessibleNameExt as _};
+use recipe_manager::database;
+use recipe_manager::database::models::{CookJournalEntry, RecipeHandle, RecipeId};
+use recipe_manager::query;
+
+/// The last day the user has been asked about, so the same days aren't asked about again.
+const ASKED_THROUGH: &str = "cook_journal_asked_through";
+
+/// How far back to ask about, so a calendar which was never checked off isn't all asked about.
+const DAYS_TO_ASK_ABOUT: i64 = 7;
+
+/// Stars which set the rating when clicked, or clear it when the current rating is clicked.
+fn update_stars(ui: &mut egui::Ui, rating: &mut Option<i32>) {
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for star in 1..=5 {
+            let text = if rating.is_some_and(|r| r >= star) {
+                "★"
+            } else {
+                "☆"
+            };
+            let name = format!("{star} out of 5");
+            let clicked = ui
+                .add(egui::Button::new(text).frame(false))
+                .accessible_name(&name)
+                .on_hover_text(name)
+                .clicked();
+            if clicked {
+                *rating = (*rating != Some(star)).then_some(star);
+            }
+        }
+    });
+}
+
+fn show_stars(ui: &mut egui::Ui, rating: Option<i32>) {
+    if let Some(rating) = rating {
+        let stars: String = (1..=5)
+            .map(|s| if s <= rating { '★' } else { '☆' })
+            .collect();
+        ui.label(stars);
+    }
+}
+
+/// A journal entry being written.
+struct Draft {
+    day: chrono::NaiveDate,
+    rating: Option<i32>,
+    notes: String,
+}
+
+impl Draft {
+    fn new(day: chrono::NaiveDate) -> Self {
+        Self {
+            day,
+            rating: None,
+            notes: String::new(),
+        }
+    }
+
+    fn update_notes(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            egui::TextEdit::multiline(&mut self.notes)
+                .hint_text("how it went, what to change next time")
+                .desired_rows(2)
+                .desired_width(300.0),
+        );
+    }
+
+    fn save(&self, conn: &mut database::Connection, recipe_id: RecipeId) -> Option<()> {
+        query::add_cook_journal_entry(conn, recipe_id, self.day, self.rating, self.notes.trim())
+            .or_report()
+    }
+}
+
+/// The journal shown in the recipe window, oldest entry first, with a form for adding to it.
+pub struct RecipeJournal {
+    recipe_id: RecipeId,
+    entries: Vec<CookJournalEntry>,
+    draft: Draft,
+}
+
+impl RecipeJournal {
+    pub fn new(conn: &mut database::Connection, recipe_id: RecipeId) -> diesel::QueryResult<Self> {
+        Ok(Self {
+            recipe_id,
+            entries: query::get_cook_journal(conn, recipe_id)?,
+            draft: Draft::new(chrono::Local::now().date_naive()),
+        })
+    }
+
+    pub fn update(
+        &mut self,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        edit_mode: bool,
+        ui: &mut egui::Ui,
+    ) {
+        let editable = is_editable(ui.ctx());
+        let mut deleted = None;
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show(ui, |ui| {
+                if self.entries.is_empty() {
+                    ui.weak("Nothing in the journal yet.");
+                }
+                for entry in &self.entries {
+                    ui.horizontal(|ui| {
+                        ui.strong(i18n::format_date(entry.day, settings.date_format.short()));
+                        show_stars(ui, entry.rating);
+                        if edit_mode && editable && ui.small_button("Delete").clicked() {
+                            deleted = Some(entry.id);
+                        }
+                    });
+                    if !entry.notes.is_empty() {
+                        ui.label(&entry.notes);
+                    }
+                    ui.add_space(ui.spacing().item_spacing.y);
+                }
+            });
+        if let Some(id) = deleted {
+            if query::delete_cook_journal_entry(conn, id)
+                .or_report()
+                .is_some()
+            {
+                self.entries.retain(|e| e.id != id);
+            }
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui_extras::DatePickerButton::new(&mut self.draft.day)
+                    .format(settings.date_format.short()),
+            );
+            update_stars(ui, &mut self.draft.rating);
+        });
+        self.draft.update_notes(ui);
+        if ui.add_enabled(editable, egui::Button::new("Add")).clicked()
+            && self.draft.save(conn, self.recipe_id).is_some()
+        {
+            self.draft = Draft::new(self.draft.day);
+            self.refresh(conn);
+        }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        if let Some(entries) = query::get_cook_journal(conn, self.recipe_id).or_report() {
+            self.entries = entries;
+        }
+    }
+}
+
+/// Asks how the recipes cooked in the last few days turned out.
+pub struct CookJournalPromptWindow {
+    cooked: Vec<(RecipeHandle, Draft)>,
+}
+
+impl CookJournalPromptWindow {
+    /// Only if there's something cooked which hasn't been asked about yet.
+    pub fn new(conn: &mut database::Connection) -> Option<Self> {
+        let today = chrono::Local::now().date_naive();
+        let mut start = today - chrono::Duration::days(DAYS_TO_ASK_ABOUT);
+        let asked_through = query::get_setting(conn, ASKED_THROUGH)
+            .or_report()?
+            .and_then(|v| v.parse::<chrono::NaiveDate>().ok());
+        if let Some(asked_through) = asked_through {
+            start = start.max(asked_through + chrono::Duration::days(1));
+        }
+        let cooked = query::get_cooked_without_journal(conn, start, today).or_report()?;
+        (!cooked.is_empty()).then(|| Self {
+            cooked: cooked
+                .into_iter()
+                .map(|(day, recipe)| (recipe, Draft::new(day)))
+                .collect(),
+        })
+    }
+
+    /// Remembers the days up to today have been asked about.
+    fn asked(conn: &mut database::Connection) {
+        let yesterday = chrono::Local::now().date_naive() - chrono::Duration::days(1);
+        query::set_setting(conn, ASKED_THROUGH, Some(&yesterday.to_string())).or_report();
+    }
+
+    /// Returns true when the window is closed, and the recipes which got journal entries.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+    ) -> (bool, Vec<RecipeId>) {
+        let mut open = true;
+        let mut journaled = vec![];
+        egui::Window::new("How Did It Go?")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                let editable = is_editable(ui.ctx());
+                let mut done = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, (recipe, draft)) in self.cooked.iter_mut().enumerate() {
+                        ui.push_id(i, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.strong(&recipe.name);
+                                ui.label(i18n::format_date(
+                                    draft.day,
+                                    settings.date_format.short(),
+                                ));
+                            });
+                            update_stars(ui, &mut draft.rating);
+                            draft.update_notes(ui);
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .add_enabled(editable, egui::Button::new("Save"))
+                                    .clicked()
+                                    && draft.save(conn, recipe.id).is_some()
+                                {
+                                    journaled.push(recipe.id);
+                                    done = Some(i);
+                                }
+                                if ui.button("Skip").clicked() {
+                                    done = Some(i);
+                                }
+                            });
+                            ui.separator();
+                        });
+                    }
+                });
+                if let Some(i) = done {
+                    self.cooked.remove(i);
+                }
+            });
+        let closed = !open || self.cooked.is_empty();
+        if closed && is_editable(ctx) {
+            Self::asked(conn);
+        }
+        (closed, journaled)
+    }
+}
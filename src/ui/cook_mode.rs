@@ -0,0 +1,190 @@
+#[cfg(feature = "keep-awake")]
+use super::keep_awake::KeepAwake;
+use super::recipe::RecipeIngredientExt as _;
+use super::settings::Settings;
+use diesel::QueryResult;
+use recipe_manager::database;
+use recipe_manager::database::models::RecipeId;
+use recipe_manager::query;
+use recipe_manager::unit_conversion::with_temperature_conversions;
+
+/// Size of the text, big enough to read from across the counter.
+const TEXT_SIZE: f32 = 28.0;
+
+/// Splits a recipe's description into the steps to show one at a time. Paragraphs are steps, unless
+/// there is only one, in which case each line is.
+fn cooking_steps(description: &str) -> Vec<String> {
+    let paragraphs: Vec<_> = description
+        .split("\n\n")
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let steps = if paragraphs.len() > 1 {
+        paragraphs
+    } else {
+        description
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect()
+    };
+    steps.into_iter().map(String::from).collect()
+}
+
+#[test]
+fn cooking_steps_test() {
+    assert_eq!(
+        cooking_steps("Chop the onions.\nFry them.\n\nServe.\n"),
+        vec!["Chop the onions.\nFry them.", "Serve."]
+    );
+    assert_eq!(
+        cooking_steps("  Boil water.\n\nAdd pasta.\n\n\n\nDrain.  "),
+        vec!["Boil water.", "Add pasta.", "Drain."]
+    );
+    assert_eq!(
+        cooking_steps("1. Mix\n2. Bake\n"),
+        vec!["1. Mix", "2. Bake"]
+    );
+    assert_eq!(cooking_steps(" \n"), Vec::<String>::new());
+}
+
+pub struct CookModeWindow {
+    name: String,
+    ingredients: Vec<(String, bool)>,
+    steps: Vec<String>,
+    current: usize,
+    fullscreen: bool,
+    /// Let go of when the window is closed.
+    #[cfg(feature = "keep-awake")]
+    _keep_awake: Option<KeepAwake>,
+}
+
+impl CookModeWindow {
+    pub fn new(
+        conn: &mut database::Connection,
+        recipe_id: RecipeId,
+        settings: &Settings,
+    ) -> QueryResult<Self> {
+        let (recipe, _, ingredients) = query::get_recipe(conn, recipe_id)?;
+        Ok(Self {
+            name: recipe.name,
+            ingredients: ingredients
+                .iter()
+                .map(|usage| (usage.display(settings), false))
+                .collect(),
+            steps: cooking_steps(&with_temperature_conversions(
+                &recipe.description,
+                settings.measurement_system,
+            )),
+            current: 0,
+            fullscreen: false,
+            #[cfg(feature = "keep-awake")]
+            _keep_awake: KeepAwake::new(),
+        })
+    }
+
+    fn update_ingredients(&mut self, ui: &mut egui::Ui) {
+        ui.heading(egui::RichText::new("Ingredients").size(TEXT_SIZE));
+        egui::ScrollArea::vertical()
+            .id_salt("cook mode ingredients")
+            .show(ui, |ui| {
+                for (line, checked) in &mut self.ingredients {
+                    let mut text = egui::RichText::new(line.as_str()).size(TEXT_SIZE);
+                    if *checked {
+                        text = text.strikethrough().weak();
+                    }
+                    ui.checkbox(checked, text);
+                }
+            });
+    }
+
+    fn update_step(&mut self, ui: &mut egui::Ui) {
+        let num_steps = self.steps.len();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    self.current > 0,
+                    egui::Button::new(egui::RichText::new("Previous").size(TEXT_SIZE)),
+                )
+                .clicked()
+            {
+                self.current -= 1;
+            }
+            if ui
+                .add_enabled(
+                    self.current + 1 < num_steps,
+                    egui::Button::new(egui::RichText::new("Next").size(TEXT_SIZE)),
+                )
+                .clicked()
+            {
+                self.current += 1;
+            }
+            if num_steps > 0 {
+                ui.label(
+                    egui::RichText::new(format!("Step {} of {num_steps}", self.current + 1))
+                        .size(TEXT_SIZE),
+                );
+            }
+        });
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .id_salt("cook mode step")
+            .show(ui, |ui| match self.steps.get(self.current) {
+                Some(step) => {
+                    ui.add(
+                        egui::Label::new(egui::RichText::new(step).size(TEXT_SIZE * 1.25)).wrap(),
+                    );
+                }
+                None => {
+                    ui.weak(egui::RichText::new("This recipe has no description").size(TEXT_SIZE));
+                }
+            });
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) -> bool {
+        if !self.fullscreen {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(true));
+            self.fullscreen = true;
+        }
+
+        let num_steps = self.steps.len();
+        let (previous, next, mut done) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowLeft),
+                i.key_pressed(egui::Key::ArrowRight) || i.key_pressed(egui::Key::Space),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if previous && self.current > 0 {
+            self.current -= 1;
+        }
+        if next && self.current + 1 < num_steps {
+            self.current += 1;
+        }
+
+        egui::Window::new("Cook")
+            .order(egui::Order::Foreground)
+            .fixed_rect(ctx.screen_rect())
+            .title_bar(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(egui::RichText::new(&self.name).size(TEXT_SIZE * 1.5));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        done |= ui
+                            .button(egui::RichText::new("Done").size(TEXT_SIZE))
+                            .clicked();
+                    });
+                });
+                ui.separator();
+                ui.columns(2, |columns| {
+                    self.update_ingredients(&mut columns[0]);
+                    self.update_step(&mut columns[1]);
+                });
+            });
+
+        if done {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(false));
+        }
+        done
+    }
+}
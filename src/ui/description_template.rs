@@ -0,0 +1,75 @@
+//! Templates new recipes' descriptions can start from, so they all have the same headings. They're
+//! kept in the settings, each one starting with a line like `=== Prep, Cook, Serve`.
+
+const NAME_PREFIX: &str = "=== ";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescriptionTemplate {
+    pub name: String,
+    pub text: String,
+}
+
+impl DescriptionTemplate {
+    fn new(name: &str, text: &str) -> Self {
+        Self {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+
+    /// The templates there are until the user changes them.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("Prep, Cook, Serve", "## Prep\n\n## Cook\n\n## Serve\n"),
+            Self::new("Source and Notes", "## Source\n\n## Notes\n"),
+        ]
+    }
+
+    pub fn parse_all(s: &str) -> Vec<Self> {
+        let mut templates: Vec<Self> = vec![];
+        for line in s.lines() {
+            match line.strip_prefix(NAME_PREFIX) {
+                Some(name) => templates.push(Self::new(name.trim(), "")),
+                None => {
+                    // Anything before the first name isn't part of a template.
+                    if let Some(template) = templates.last_mut() {
+                        template.text.push_str(line);
+                        template.text.push('\n');
+                    }
+                }
+            }
+        }
+        templates
+    }
+
+    pub fn write_all(templates: &[Self]) -> String {
+        let mut s = String::new();
+        for template in templates {
+            s.push_str(NAME_PREFIX);
+            s.push_str(template.name.trim());
+            s.push('\n');
+            s.push_str(&template.text);
+            if !template.text.is_empty() && !template.text.ends_with('\n') {
+                s.push('\n');
+            }
+        }
+        s
+    }
+}
+
+#[test]
+fn parse_all_test() {
+    let templates = DescriptionTemplate::defaults();
+    assert_eq!(
+        DescriptionTemplate::parse_all(&DescriptionTemplate::write_all(&templates)),
+        templates
+    );
+    assert_eq!(
+        DescriptionTemplate::parse_all("ignored\n=== Empty\n=== Steps\n1. \n2. "),
+        [
+            DescriptionTemplate::new("Empty", ""),
+            DescriptionTemplate::new("Steps", "1. \n2. \n"),
+        ]
+    );
+    assert_eq!(DescriptionTemplate::parse_all(""), []);
+}
@@ -0,0 +1,163 @@
+//! An opt-in mode for finding out what is slow with a big database: how long queries and frames
+//! take is logged, and shown in a small overlay.
+
+use diesel::connection::{Connection as _, InstrumentationEvent};
+use recipe_manager::database;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Queries taking at least this long are logged as warnings.
+const SLOW_QUERY: Duration = Duration::from_millis(20);
+
+/// Frames taking at least this long are logged as warnings.
+const SLOW_FRAME: Duration = Duration::from_millis(50);
+
+/// How many of the most recent frames the overlay is about.
+const FRAMES_KEPT: usize = 120;
+
+#[derive(Default)]
+struct QueryStats {
+    started: Option<Instant>,
+    count: usize,
+    total: Duration,
+    slowest: Option<(Duration, String)>,
+}
+
+thread_local! {
+    /// The queries run since the start of the current frame.
+    static QUERIES: RefCell<QueryStats> = RefCell::new(QueryStats::default());
+}
+
+fn on_connection_event(event: InstrumentationEvent<'_>) {
+    match event {
+        InstrumentationEvent::StartQuery { .. } => {
+            QUERIES.with_borrow_mut(|q| q.started = Some(Instant::now()));
+        }
+        InstrumentationEvent::FinishQuery { query, .. } => {
+            QUERIES.with_borrow_mut(|q| {
+                let Some(started) = q.started.take() else {
+                    return;
+                };
+                let elapsed = started.elapsed();
+                let sql = query.to_string();
+                if elapsed >= SLOW_QUERY {
+                    log::warn!("slow query took {elapsed:?}: {sql}");
+                } else {
+                    log::info!("query took {elapsed:?}: {sql}");
+                }
+                q.count += 1;
+                q.total += elapsed;
+                if q.slowest.as_ref().is_none_or(|(d, _)| elapsed > *d) {
+                    q.slowest = Some((elapsed, sql));
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Frame times and query statistics, collected while diagnostics are turned on.
+pub struct Diagnostics {
+    frame_start: Option<Instant>,
+    frame_times: VecDeque<Duration>,
+    last_frame_queries: QueryStats,
+}
+
+impl Diagnostics {
+    /// Turns diagnostics on, timing every query made on the connection.
+    pub fn start(conn: &mut database::Connection) -> Self {
+        conn.set_instrumentation(on_connection_event);
+        QUERIES.take();
+        Self {
+            frame_start: None,
+            frame_times: VecDeque::new(),
+            last_frame_queries: QueryStats::default(),
+        }
+    }
+
+    /// Turns diagnostics off again.
+    pub fn stop(self, conn: &mut database::Connection) {
+        conn.set_instrumentation(|_: InstrumentationEvent<'_>| {});
+    }
+
+    pub fn start_frame(&mut self) {
+        self.frame_start = Some(Instant::now());
+    }
+
+    pub fn end_frame(&mut self) {
+        let Some(start) = self.frame_start.take() else {
+            return;
+        };
+        let elapsed = start.elapsed();
+        let queries = QUERIES.take();
+        if elapsed >= SLOW_FRAME {
+            log::warn!(
+                "slow frame took {elapsed:?}, {} queries took {:?}",
+                queries.count,
+                queries.total
+            );
+        }
+        self.last_frame_queries = queries;
+        if self.frame_times.len() == FRAMES_KEPT {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(elapsed);
+    }
+
+    fn average_frame_time(&self) -> Duration {
+        self.frame_times.iter().sum::<Duration>() / (self.frame_times.len().max(1) as u32)
+    }
+
+    /// Shows the statistics in a corner of the screen.
+    pub fn update_overlay(&self, ctx: &egui::Context) {
+        let average = self.average_frame_time();
+        let slowest = self.frame_times.iter().max().copied().unwrap_or_default();
+        let queries = &self.last_frame_queries;
+
+        egui::Area::new(egui::Id::new("diagnostics overlay"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .order(egui::Order::Tooltip)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let text = |s: String| egui::RichText::new(s).monospace().small();
+                    ui.label(text(format!(
+                        "frame: {average:.1?} average, {slowest:.1?} slowest"
+                    )));
+                    ui.label(text(format!(
+                        "queries: {} taking {:.1?}",
+                        queries.count, queries.total
+                    )));
+                    if let Some((elapsed, sql)) = &queries.slowest {
+                        ui.label(text(format!("slowest: {elapsed:.1?}")))
+                            .on_hover_text(sql);
+                    }
+                });
+            });
+    }
+}
+
+#[test]
+fn frame_times_test() {
+    let mut conn = database::establish_connection(":memory:").unwrap();
+    let mut diagnostics = Diagnostics::start(&mut conn);
+    assert_eq!(diagnostics.average_frame_time(), Duration::ZERO);
+
+    for _ in 0..(FRAMES_KEPT + 10) {
+        diagnostics.start_frame();
+        recipe_manager::query::get_setting(&mut conn, "theme").unwrap();
+        diagnostics.end_frame();
+    }
+    assert_eq!(diagnostics.frame_times.len(), FRAMES_KEPT);
+    assert_eq!(diagnostics.last_frame_queries.count, 1);
+    assert!(diagnostics.last_frame_queries.slowest.is_some());
+
+    // A frame which was never started isn't counted.
+    diagnostics.end_frame();
+    assert_eq!(diagnostics.frame_times.len(), FRAMES_KEPT);
+
+    diagnostics.stop(&mut conn);
+    recipe_manager::query::get_setting(&mut conn, "theme").unwrap();
+    assert_eq!(QUERIES.with_borrow(|q| q.count), 0);
+}
@@ -0,0 +1,50 @@
+use super::errors::QueryResultExt as _;
+use recipe_manager::database;
+use recipe_manager::database::models::RecipeId;
+use recipe_manager::diet::{self, DietFlag};
+use recipe_manager::query;
+
+/// The recipe's allergens and the diets it's safe for, from its ingredients.
+pub fn recipe_badges(conn: &mut database::Connection, recipe_id: RecipeId) -> Vec<DietFlag> {
+    query::get_recipe_summary(conn, recipe_id)
+        .or_report()
+        .map(|s| diet::recipe_badges(s.ingredients.iter().map(|i| i.ingredient.diet)))
+        .unwrap_or_default()
+}
+
+/// Shows each badge as a small tag, allergens in a warning color.
+pub fn show_badges(ui: &mut egui::Ui, badges: &[DietFlag]) {
+    for badge in badges {
+        let color = if badge.is_allergen() {
+            ui.visuals().warn_fg_color
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        egui::Frame::none()
+            .stroke(egui::Stroke::new(1.0, color))
+            .rounding(4.0)
+            .inner_margin(egui::Margin::symmetric(4.0, 0.0))
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new(badge.to_string()).small().color(color));
+            });
+    }
+}
+
+/// A warning sign if the recipe goes against any of the household's restrictions, saying which.
+pub fn show_conflicts(
+    ui: &mut egui::Ui,
+    conn: &mut database::Connection,
+    recipe_id: RecipeId,
+    restrictions: &[DietFlag],
+) {
+    if restrictions.is_empty() {
+        return;
+    }
+    let conflicts = diet::conflicts(&recipe_badges(conn, recipe_id), restrictions);
+    if conflicts.is_empty() {
+        return;
+    }
+    let names: Vec<_> = conflicts.iter().map(|c| c.restriction()).collect();
+    ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+        .on_hover_text(format!("Doesn't fit: {}", names.join(", ")));
+}
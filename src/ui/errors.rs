@@ -0,0 +1,175 @@
+//! Reporting errors to the user instead of crashing: failed database queries are shown as toasts
+//! with a button to reload everything, and panics while drawing a frame are shown in a window.
+
+use std::cell::RefCell;
+
+/// The `egui_toast::ToastKind::Custom` used for database errors.
+pub const DATABASE_ERROR_TOAST_KIND: u32 = 2;
+
+thread_local! {
+    /// Errors from queries which failed this frame, waiting to be shown.
+    static DATABASE_ERRORS: RefCell<Vec<diesel::result::Error>> = const { RefCell::new(vec![]) };
+}
+
+/// For query results where there is nothing better to do with an error than tell the user.
+pub trait QueryResultExt<T> {
+    /// The value, or `None` if the query failed, in which case the error is reported.
+    fn or_report(self) -> Option<T>;
+
+    /// The value, or the default if the query failed, in which case the error is reported.
+    fn unwrap_or_report(self) -> T
+    where
+        T: Default;
+}
+
+impl<T> QueryResultExt<T> for diesel::QueryResult<T> {
+    fn or_report(self) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(error) => {
+                log::error!("database error: {error}");
+                DATABASE_ERRORS.with_borrow_mut(|errors| errors.push(error));
+                None
+            }
+        }
+    }
+
+    fn unwrap_or_report(self) -> T
+    where
+        T: Default,
+    {
+        self.or_report().unwrap_or_default()
+    }
+}
+
+#[test]
+fn or_report_test() {
+    assert_eq!(diesel::QueryResult::Ok(3).or_report(), Some(3));
+    assert_eq!(DATABASE_ERRORS.with_borrow(|e| e.len()), 0);
+
+    let failed: diesel::QueryResult<Vec<i32>> = Err(diesel::result::Error::NotFound);
+    assert_eq!(failed.unwrap_or_report(), Vec::<i32>::new());
+    assert_eq!(DATABASE_ERRORS.take().len(), 1);
+}
+
+/// Adds a toast for each database error reported since the last call.
+pub fn show_database_errors(toasts: &mut egui_toast::Toasts) {
+    for error in DATABASE_ERRORS.take() {
+        toasts.add(egui_toast::Toast {
+            kind: egui_toast::ToastKind::Custom(DATABASE_ERROR_TOAST_KIND),
+            ..super::new_error_toast(format!("Database error: {error}"))
+        });
+    }
+}
+
+fn reload_clicked_id() -> egui::Id {
+    egui::Id::new("reload clicked")
+}
+
+/// Draws a database error toast with a reload button, for `egui_toast::Toasts::custom_contents`.
+pub fn database_error_toast_contents(
+    ui: &mut egui::Ui,
+    toast: &mut egui_toast::Toast,
+) -> egui::Response {
+    egui::Frame::window(ui.style())
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(toast.style.error_icon.clone());
+                ui.label(toast.text.clone());
+                if ui.button("Reload").clicked() {
+                    ui.ctx()
+                        .data_mut(|d| d.insert_temp(reload_clicked_id(), true));
+                    toast.close();
+                }
+                if ui.button(toast.style.close_button_text.clone()).clicked() {
+                    toast.close();
+                }
+            })
+        })
+        .response
+}
+
+/// Whether the reload button of a database error toast was clicked.
+pub fn take_reload_clicked(ctx: &egui::Context) -> bool {
+    ctx.data_mut(|d| d.remove_temp(reload_clicked_id()))
+        .unwrap_or(false)
+}
+
+thread_local! {
+    /// The message of the last panic, recorded by the panic hook.
+    static PANIC_MESSAGE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Records panic messages, along with where they happened, so they can be shown to the user.
+/// Since the program has no console on Windows, they are also written to the data directory.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info.to_string();
+        log::error!("{message}");
+        if let Ok(path) = recipe_manager::data_path() {
+            let _ = std::fs::write(path.join("crash.log"), &message);
+        }
+        PANIC_MESSAGE.set(Some(message));
+        default_hook(info);
+    }));
+}
+
+/// Runs one frame of the UI, catching any panic so it can be reported instead of crashing.
+pub fn catch_panic(f: impl FnOnce()) -> Option<String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+        .err()
+        .map(|payload| {
+            PANIC_MESSAGE
+                .take()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown error".into())
+        })
+}
+
+#[test]
+fn catch_panic_test() {
+    assert_eq!(catch_panic(|| {}), None);
+    assert_eq!(catch_panic(|| panic!("oh no")), Some("oh no".into()));
+    let n = 3;
+    assert_eq!(catch_panic(|| panic!("oh no {n}")), Some("oh no 3".into()));
+}
+
+pub enum CrashAction {
+    Continue,
+    Quit,
+}
+
+/// Shows what went wrong after a panic, letting the user choose whether to carry on.
+pub fn update_crash_window(ctx: &egui::Context, message: &str) -> Option<CrashAction> {
+    let mut action = None;
+    egui::Window::new("Something Went Wrong")
+        .collapsible(false)
+        .resizable([false, false])
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            ui.label(
+                "Recipe Manager ran into a problem. Your data is saved, but what you were \
+                doing last may not have finished.",
+            );
+            ui.add_space(5.0);
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    ui.add(egui::Label::new(egui::RichText::new(message).monospace()).wrap());
+                });
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                if ui.button("Continue").clicked() {
+                    action = Some(CrashAction::Continue);
+                }
+                if ui.button("Quit").clicked() {
+                    action = Some(CrashAction::Quit);
+                }
+            });
+        });
+    action
+}
@@ -0,0 +1,182 @@
+//! Portions of recipes which were made ahead and frozen. The calendar offers to use them instead of
+//! cooking, which leaves the recipe off the shopping list.
+
+use super::errors::QueryResultExt as _;
+use super::{i18n, is_editable, new_error_toast, search::SearchWidget, settings::Settings};
+use recipe_manager::database;
+use recipe_manager::database::models::{FreezerEntry, RecipeHandle, RecipeId};
+use recipe_manager::query;
+
+pub enum UpdateEvent {
+    Closed,
+    FreezerChanged,
+}
+
+pub struct FreezerWindow {
+    inventory: Vec<(FreezerEntry, RecipeHandle)>,
+    new_recipe_name: String,
+    new_recipe_id: Option<RecipeId>,
+    cached_recipe_search: Option<query::CachedQuery<RecipeId>>,
+    new_portions: i32,
+    new_frozen: chrono::NaiveDate,
+}
+
+impl FreezerWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        Self {
+            inventory: query::get_freezer_inventory(conn).unwrap_or_report(),
+            new_recipe_name: String::new(),
+            new_recipe_id: None,
+            cached_recipe_search: None,
+            new_portions: 1,
+            new_frozen: chrono::Local::now().date_naive(),
+        }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        self.inventory = query::get_freezer_inventory(conn).unwrap_or_report();
+    }
+
+    fn update_inventory(
+        &mut self,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        ui: &mut egui::Ui,
+    ) -> bool {
+        let editable = is_editable(ui.ctx());
+        let mut changed = None;
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .show(ui, |ui| {
+                egui::Grid::new("freezer grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (entry, recipe) in &self.inventory {
+                            ui.label(&recipe.name);
+                            let mut portions = entry.portions;
+                            let response = ui.add_enabled(
+                                editable,
+                                egui::DragValue::new(&mut portions)
+                                    .range(0..=99)
+                                    .suffix(" portions"),
+                            );
+                            if response.changed() {
+                                changed = Some((entry.id, portions));
+                            }
+                            ui.label(i18n::format_date(
+                                entry.frozen,
+                                settings.date_format.short(),
+                            ));
+                            if ui
+                                .add_enabled(editable, egui::Button::new("Remove"))
+                                .clicked()
+                            {
+                                changed = Some((entry.id, 0));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        let Some((id, portions)) = changed else {
+            return false;
+        };
+        query::set_freezer_portions(conn, id, portions).or_report();
+        self.refresh(conn);
+        true
+    }
+
+    fn update_add(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
+        ui: &mut egui::Ui,
+    ) -> bool {
+        let mut added = false;
+        ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    SearchWidget::new(
+                        "freezer recipe",
+                        &mut self.new_recipe_name,
+                        &mut self.new_recipe_id,
+                        |query| {
+                            query::search_recipes(conn, &mut self.cached_recipe_search, query, true)
+                                .unwrap_or_report()
+                        },
+                    )
+                    .desired_width(150.0)
+                    .hint_text("search for recipe"),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.new_portions)
+                        .range(1..=99)
+                        .suffix(" portions"),
+                );
+                ui.add(
+                    egui_extras::DatePickerButton::new(&mut self.new_frozen)
+                        .id_salt("freezer frozen date")
+                        .format(settings.date_format.short()),
+                );
+                let e = !self.new_recipe_name.is_empty();
+                if ui.add_enabled(e, egui::Button::new("Add")).clicked() {
+                    match self.new_recipe_id {
+                        Some(recipe_id) => {
+                            added = query::add_freezer_entry(
+                                conn,
+                                recipe_id,
+                                self.new_portions,
+                                self.new_frozen,
+                            )
+                            .or_report()
+                            .is_some();
+                        }
+                        None => {
+                            toasts.add(new_error_toast("Couldn't find recipe"));
+                        }
+                    }
+                }
+            });
+        });
+        if added {
+            self.new_recipe_name.clear();
+            self.new_recipe_id = None;
+            self.new_portions = 1;
+            self.refresh(conn);
+        }
+        added
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let mut open = true;
+        egui::Window::new("Freezer")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                if self.inventory.is_empty() {
+                    ui.label(
+                        "Nothing in the freezer. Add portions of recipes made ahead, and the \
+                        calendar will offer to use them instead of cooking.",
+                    );
+                } else if self.update_inventory(conn, settings, ui) {
+                    events.push(UpdateEvent::FreezerChanged);
+                }
+                ui.separator();
+                if self.update_add(conn, toasts, settings, ui) {
+                    events.push(UpdateEvent::FreezerChanged);
+                }
+            });
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+}
@@ -1,7 +1,16 @@
 use super::calendar::{full_day_name, RecipeWeek};
-use super::recipe::quantity_display;
-use crate::database::models::{Ingredient, IngredientId, IngredientMeasurement, IngredientUsage};
-use std::collections::BTreeMap;
+use super::i18n::{self, tr};
+use super::menu_template::MenuTemplate;
+use super::nutrition::calories_display;
+use super::recipe::{quantity_display_with_precision, FractionPrecision};
+use super::settings::DateFormat;
+use recipe_manager::database::models::{
+    Ingredient, IngredientCategoryId, IngredientId, IngredientMeasurement, IngredientUsage,
+};
+#[cfg(feature = "grocery-export")]
+use recipe_manager::grocery::GroceryItem;
+use recipe_manager::unit_conversion::{convert_totals_to_system, MeasurementSystem};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 
 fn rich_text_header() -> String {
@@ -18,77 +27,199 @@ fn rich_text_header() -> String {
     rich_text
 }
 
-fn rich_text_heading(text: &str, week: chrono::NaiveWeek) -> String {
+fn rich_text_heading(text: &str, week: chrono::NaiveWeek, date_format: DateFormat) -> String {
     let mut rich_text = String::new();
-    rich_text += &format!("\\f0\\b\\fs24 \\cf0 {text} for the Week \\\n");
-    rich_text += &week
-        .first_day()
-        .format_with_items(chrono::format::StrftimeItems::new("of the %e, %B %Y\n"))
-        .to_string();
+    let date = i18n::format_date(week.first_day(), date_format.long());
+    let title = tr("{title} for the Week").replace("{title}", text);
+    rich_text += &format!("\\f0\\b\\fs24 \\cf0 {title} \\\n");
+    rich_text += &format!("{}\n", tr("of {date}").replace("{date}", &date));
     rich_text += "\\f1\\b0 ";
     rich_text
 }
 
-pub fn generate_and_open_menu(week: &RecipeWeek) -> crate::Result<()> {
-    let mut rich_text = rich_text_header();
-    rich_text += &rich_text_heading("Menu", week.week());
-    for (day, recipe) in week.recipes() {
-        let day_str = full_day_name(day);
-        let recipe = recipe.map(|r| r.name).unwrap_or("No Recipe".into());
-        let tabs = if day == chrono::Weekday::Wed {
-            "\t"
-        } else {
-            "\t\t"
-        };
+fn plain_text_heading(text: &str, week: chrono::NaiveWeek, date_format: DateFormat) -> String {
+    let date = i18n::format_date(week.first_day(), date_format.long());
+    let heading = tr("{title} for the week of {date}").replace("{title}", text);
+    format!("{}\n", heading.replace("{date}", &date))
+}
+
+/// A menu or shopping list, both as a document and as plain text for putting in an email.
+pub struct Document {
+    pub file_name: String,
+    pub rich_text: String,
+    #[cfg_attr(not(feature = "email"), expect(dead_code))]
+    pub plain_text: String,
+}
 
-        rich_text += &format!("\\\n{day_str}{tabs}{recipe}");
+impl Document {
+    fn save_and_open(&self, dir: &str) -> recipe_manager::Result<()> {
+        let dir = recipe_manager::data_path()?.join(dir);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(&self.file_name);
+        std::fs::write(&path, &self.rich_text)?;
+        open::that(path)?;
+        Ok(())
     }
-    rich_text += "}";
+}
 
-    let menus_dir = crate::data_path()?.join("menus");
-    std::fs::create_dir_all(&menus_dir)?;
-    let menu_path = menus_dir.join(format!("menu-{}.rtf", week.week().first_day()));
-    std::fs::write(&menu_path, rich_text)?;
-    open::that(menu_path)?;
-    Ok(())
+fn menu_plain_text(
+    week: chrono::NaiveWeek,
+    recipes: &[(chrono::Weekday, Option<String>)],
+    calories: &HashMap<chrono::Weekday, f32>,
+    date_format: DateFormat,
+) -> String {
+    let mut text = plain_text_heading(tr("Menu"), week, date_format);
+    for (day, recipe) in recipes {
+        let day_str = full_day_name(*day);
+        match recipe {
+            Some(recipe) => {
+                let day_calories = calories.get(day).copied().unwrap_or(0.0);
+                text += &format!(
+                    "\n{day_str}: {recipe} ({} cal)",
+                    calories_display(day_calories)
+                );
+            }
+            None => text += &format!("\n{day_str}: {}", tr("No Recipe")),
+        }
+    }
+    let total: f32 = calories.values().sum();
+    text += &format!(
+        "\n\n{} {}\n",
+        tr("Total Calories:"),
+        calories_display(total)
+    );
+    text
+}
+
+#[test]
+fn menu_plain_text_test() {
+    use chrono::Weekday::*;
+    use maplit::hashmap;
+
+    let week = chrono::NaiveDate::from_ymd_opt(2024, 3, 3)
+        .unwrap()
+        .week(Sun);
+    let recipes = vec![(Sun, Some("Pancakes".into())), (Mon, None)];
+    let calories = hashmap! { Sun => 1234.4 };
+    assert_eq!(
+        menu_plain_text(week, &recipes, &calories, DateFormat::MonthFirst),
+        "Menu for the week of March 3, 2024\n\
+        \nSunday: Pancakes (1,234 cal)\
+        \nMonday: No Recipe\
+        \n\nTotal Calories: 1,234\n"
+    );
+}
+
+/// The recipe names for each day of the week, as they go in the menu.
+pub fn menu_recipes(week: &RecipeWeek) -> Vec<(chrono::Weekday, Option<String>)> {
+    week.recipes()
+        .into_iter()
+        .map(|(day, recipe)| (day, recipe.map(|r| r.name)))
+        .collect()
+}
+
+pub fn menu_document(
+    week: chrono::NaiveWeek,
+    recipes: &[(chrono::Weekday, Option<String>)],
+    calories: &HashMap<chrono::Weekday, f32>,
+    template: &MenuTemplate,
+    date_format: DateFormat,
+) -> Document {
+    Document {
+        file_name: format!("menu-{}.rtf", week.first_day()),
+        rich_text: template.rich_text(week, recipes, calories, date_format),
+        plain_text: menu_plain_text(week, recipes, calories, date_format),
+    }
+}
+
+pub fn generate_and_open_menu(
+    week: chrono::NaiveWeek,
+    recipes: &[(chrono::Weekday, Option<String>)],
+    calories: &HashMap<chrono::Weekday, f32>,
+    template: &MenuTemplate,
+    date_format: DateFormat,
+) -> recipe_manager::Result<()> {
+    menu_document(week, recipes, calories, template, date_format).save_and_open("menus")
 }
 
 struct ShoppingListItem {
     name: String,
     usages: BTreeMap<Option<IngredientMeasurement>, f32>,
+    notes: BTreeSet<String>,
+    precision: FractionPrecision,
 }
 
 impl ShoppingListItem {
-    fn new(name: String) -> Self {
+    fn new(name: String, precision: FractionPrecision) -> Self {
         Self {
             name,
             usages: BTreeMap::new(),
+            notes: BTreeSet::new(),
+            precision,
         }
     }
 }
 
 impl fmt::Display for ShoppingListItem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quantity_display = |q: f32, m: &Option<IngredientMeasurement>| {
+            quantity_display_with_precision(q, m, self.precision)
+        };
         let mut usages = self.usages.iter().filter_map(|(m, u)| m.map(|m| (m, u)));
         if let Some((m, u)) = usages.next() {
-            write!(f, "{} {}", quantity_display(*u, &Some(m)), m.as_str())?;
+            write!(f, "{} {}", quantity_display(*u, &Some(m)), i18n::units(m))?;
         }
         for (m, u) in usages {
-            write!(f, " and {} {}", quantity_display(*u, &Some(m)), m.as_str())?;
+            let quantity = quantity_display(*u, &Some(m));
+            write!(f, " {} {quantity} {}", tr("and"), i18n::units(m))?;
         }
         if let Some(u) = self.usages.get(&None) {
             if self.usages.len() > 1 {
-                write!(f, " and {} {}", quantity_display(*u, &None), self.name)?;
+                write!(
+                    f,
+                    " {} {} {}",
+                    tr("and"),
+                    quantity_display(*u, &None),
+                    self.name
+                )?;
             } else {
                 write!(f, "{} {}", quantity_display(*u, &None), self.name)?;
             }
         } else {
-            write!(f, " of {}", self.name)?;
+            write!(f, " {} {}", tr("of"), self.name)?;
+        }
+        if !self.notes.is_empty() {
+            let notes: Vec<_> = self.notes.iter().map(|n| n.as_str()).collect();
+            write!(f, " ({})", notes.join("; "))?;
         }
         Ok(())
     }
 }
 
+#[cfg(feature = "grocery-export")]
+impl ShoppingListItem {
+    /// How much to get, without the name, like "2 cups and 3" for "2 cups and 3 cans of tomatoes".
+    fn specification(&self) -> String {
+        let mut amounts: Vec<_> = self
+            .usages
+            .iter()
+            .filter_map(|(m, u)| {
+                let quantity = quantity_display_with_precision(*u, m, self.precision);
+                m.map(|m| format!("{quantity} {}", i18n::units(m)))
+            })
+            .collect();
+        if let Some(u) = self.usages.get(&None) {
+            amounts.push(quantity_display_with_precision(*u, &None, self.precision));
+        }
+        let mut specification = amounts.join(&format!(" {} ", tr("and")));
+        if !self.notes.is_empty() {
+            let notes: Vec<_> = self.notes.iter().map(|n| n.as_str()).collect();
+            specification += &format!(" ({})", notes.join("; "));
+        }
+        specification.trim().into()
+    }
+}
+
 #[test]
 fn shopping_list_item() {
     use maplit::btreemap;
@@ -98,6 +229,8 @@ fn shopping_list_item() {
         usages: btreemap! {
             Some(IngredientMeasurement::Cups) => 2.0,
         },
+        notes: BTreeSet::new(),
+        precision: FractionPrecision::Exact,
     };
     assert_eq!(item.to_string(), "2 cups of tomatoes");
 
@@ -107,6 +240,8 @@ fn shopping_list_item() {
             Some(IngredientMeasurement::Cups) => 2.0,
             None => 3.0,
         },
+        notes: BTreeSet::new(),
+        precision: FractionPrecision::Exact,
     };
     assert_eq!(item.to_string(), "2 cups and 3 cans of tomatoes");
 
@@ -117,6 +252,8 @@ fn shopping_list_item() {
             Some(IngredientMeasurement::Tablespoons) => 0.5,
             None => 3.0,
         },
+        notes: BTreeSet::new(),
+        precision: FractionPrecision::Exact,
     };
     assert_eq!(
         item.to_string(),
@@ -128,39 +265,71 @@ fn shopping_list_item() {
         usages: btreemap! {
             None => 3.0,
         },
+        notes: BTreeSet::new(),
+        precision: FractionPrecision::Exact,
     };
     assert_eq!(item.to_string(), "3 cans of tomatoes");
+
+    let item = ShoppingListItem {
+        name: "flour".into(),
+        usages: btreemap! {
+            Some(IngredientMeasurement::Cups) => 23.0 / 97.0,
+        },
+        notes: BTreeSet::new(),
+        precision: FractionPrecision::Friendly,
+    };
+    assert_eq!(item.to_string(), "1/4 cups of flour");
+
+    let item = ShoppingListItem {
+        name: "onions".into(),
+        usages: btreemap! {
+            None => 2.0,
+        },
+        notes: ["diced".into(), "finely chopped".into()].into(),
+        precision: FractionPrecision::Exact,
+    };
+    assert_eq!(item.to_string(), "2 onions (diced; finely chopped)");
+    #[cfg(feature = "grocery-export")]
+    assert_eq!(item.specification(), "2 (diced; finely chopped)");
 }
 
 type CategorizedIngredients = BTreeMap<Option<String>, BTreeMap<IngredientId, ShoppingListItem>>;
 
 fn sort_ingredients_by_category(
     ingredients: Vec<(IngredientUsage, Ingredient)>,
+    categories: &HashMap<IngredientCategoryId, String>,
+    precision: FractionPrecision,
+    system: MeasurementSystem,
 ) -> CategorizedIngredients {
     let mut map: CategorizedIngredients = BTreeMap::new();
     for (usage, i) in ingredients {
-        *map.entry(i.category)
+        let category = i.category_id.and_then(|c| categories.get(&c).cloned());
+        let item = map
+            .entry(category)
             .or_default()
             .entry(i.id)
-            .or_insert(ShoppingListItem::new(i.name))
-            .usages
-            .entry(usage.quantity_units)
-            .or_default() += usage.quantity;
+            .or_insert(ShoppingListItem::new(i.name, precision));
+        *item.usages.entry(usage.quantity_units).or_default() += usage.quantity;
+        if !usage.note.is_empty() {
+            item.notes.insert(usage.note);
+        }
+    }
+    for item in map.values_mut().flat_map(|items| items.values_mut()) {
+        item.usages = convert_totals_to_system(&item.usages, system);
     }
     map
 }
 
-pub fn generate_and_open_shopping_list(
+fn shopping_list_rich_text(
     week: chrono::NaiveWeek,
-    ingredients: Vec<(IngredientUsage, Ingredient)>,
-) -> crate::Result<()> {
-    let ingredients = sort_ingredients_by_category(ingredients);
-
+    ingredients: &CategorizedIngredients,
+    date_format: DateFormat,
+) -> String {
     let mut rich_text = rich_text_header();
-    rich_text += &rich_text_heading("Shopping List", week);
+    rich_text += &rich_text_heading(tr("Shopping List"), week, date_format);
     rich_text += "\\\n";
 
-    for (cat, ingredients) in &ingredients {
+    for (cat, ingredients) in ingredients {
         if let Some(cat) = cat {
             rich_text += &format!("\\\n\\f0\\b ****{cat}****\n\\f1\\b0 ");
             for i in ingredients.values() {
@@ -180,11 +349,81 @@ pub fn generate_and_open_shopping_list(
     }
 
     rich_text += "}";
+    rich_text
+}
+
+fn shopping_list_plain_text(
+    week: chrono::NaiveWeek,
+    ingredients: &CategorizedIngredients,
+    date_format: DateFormat,
+) -> String {
+    let mut text = plain_text_heading(tr("Shopping list"), week, date_format);
+    // Like the document, all the uncategorized ingredients go at the end
+    let categorized = ingredients
+        .iter()
+        .filter_map(|(cat, items)| Some((cat.as_deref()?, items)));
+    let uncategorized = ingredients.get(&None).map(|items| (tr("Other"), items));
+    for (cat, items) in categorized.chain(uncategorized) {
+        text += &format!("\n{cat}\n");
+        for i in items.values() {
+            text += &format!("  {i}\n");
+        }
+    }
+    text
+}
+
+pub fn shopping_list_document(
+    week: chrono::NaiveWeek,
+    ingredients: Vec<(IngredientUsage, Ingredient)>,
+    categories: &HashMap<IngredientCategoryId, String>,
+    precision: FractionPrecision,
+    system: MeasurementSystem,
+    date_format: DateFormat,
+) -> Document {
+    let ingredients = sort_ingredients_by_category(ingredients, categories, precision, system);
+    Document {
+        file_name: format!("shopping-list-{}.rtf", week.first_day()),
+        rich_text: shopping_list_rich_text(week, &ingredients, date_format),
+        plain_text: shopping_list_plain_text(week, &ingredients, date_format),
+    }
+}
 
-    let menus_dir = crate::data_path()?.join("shopping-lists");
-    std::fs::create_dir_all(&menus_dir)?;
-    let menu_path = menus_dir.join(format!("shopping-list-{}.rtf", week.first_day()));
-    std::fs::write(&menu_path, rich_text)?;
-    open::that(menu_path)?;
-    Ok(())
+/// The shopping list as items for a grocery list app, in the same order as the document.
+#[cfg(feature = "grocery-export")]
+pub fn shopping_list_grocery_items(
+    ingredients: Vec<(IngredientUsage, Ingredient)>,
+    categories: &HashMap<IngredientCategoryId, String>,
+    precision: FractionPrecision,
+    system: MeasurementSystem,
+) -> Vec<GroceryItem> {
+    let ingredients = sort_ingredients_by_category(ingredients, categories, precision, system);
+    let categorized = ingredients.iter().filter(|(cat, _)| cat.is_some());
+    let uncategorized = ingredients.iter().filter(|(cat, _)| cat.is_none());
+    categorized
+        .chain(uncategorized)
+        .flat_map(|(_, items)| items.values())
+        .map(|i| GroceryItem {
+            name: i.name.clone(),
+            specification: i.specification(),
+        })
+        .collect()
+}
+
+pub fn generate_and_open_shopping_list(
+    week: chrono::NaiveWeek,
+    ingredients: Vec<(IngredientUsage, Ingredient)>,
+    categories: &HashMap<IngredientCategoryId, String>,
+    precision: FractionPrecision,
+    system: MeasurementSystem,
+    date_format: DateFormat,
+) -> recipe_manager::Result<()> {
+    shopping_list_document(
+        week,
+        ingredients,
+        categories,
+        precision,
+        system,
+        date_format,
+    )
+    .save_and_open("shopping-lists")
 }
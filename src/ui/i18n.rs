@@ -0,0 +1,473 @@
+//! Translations of the UI. Strings are looked up by their English text, so the code reads the same
+//! in any language, and anything which hasn't been translated yet is shown in English.
+
+use derive_more::Display;
+use recipe_manager::database::models::IngredientMeasurement;
+use std::cell::Cell;
+use strum::EnumIter;
+
+thread_local! {
+    /// The language the UI is in, from the settings.
+    static LANGUAGE: Cell<Language> = const { Cell::new(Language::English) };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum Language {
+    #[default]
+    #[display("English")]
+    English,
+    #[display("Français")]
+    French,
+}
+
+impl Language {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::French => "fr",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|l| l.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+
+    fn locale(&self) -> chrono::Locale {
+        match self {
+            Self::English => chrono::Locale::en_US,
+            Self::French => chrono::Locale::fr_FR,
+        }
+    }
+
+    fn translations(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::English => &[],
+            Self::French => FRENCH,
+        }
+    }
+}
+
+pub fn set_language(language: Language) {
+    LANGUAGE.set(language);
+}
+
+/// The text in the UI's language.
+pub fn tr(english: &'static str) -> &'static str {
+    LANGUAGE
+        .get()
+        .translations()
+        .iter()
+        .find(|(e, _)| *e == english)
+        .map_or(english, |(_, t)| t)
+}
+
+/// How the units are written in the UI's language.
+pub fn units(units: IngredientMeasurement) -> &'static str {
+    tr(units.as_str())
+}
+
+/// A date with the month and day names in the UI's language. The format is a `strftime` one.
+pub fn format_date(date: chrono::NaiveDate, format: &str) -> String {
+    date.and_time(chrono::NaiveTime::MIN)
+        .and_utc()
+        .format_localized(format, LANGUAGE.get().locale())
+        .to_string()
+}
+
+#[test]
+fn tr_test() {
+    let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 3).unwrap();
+    assert_eq!(tr("Calendar"), "Calendar");
+    assert_eq!(units(IngredientMeasurement::Tablespoons), "tbsp.");
+    assert_eq!(format_date(date, "%A %-d %B"), "Sunday 3 March");
+
+    set_language(Language::French);
+    assert_eq!(tr("Calendar"), "Calendrier");
+    assert_eq!(tr("{count} recipes").replace("{count}", "3"), "3 recettes");
+    assert_eq!(tr("not translated"), "not translated");
+    assert_eq!(units(IngredientMeasurement::Tablespoons), "c. à s.");
+    assert_eq!(format_date(date, "%A %-d %B"), "dimanche 3 mars");
+    set_language(Language::English);
+}
+
+const FRENCH: &[(&str, &str)] = &[
+    // The main menu
+    ("Window", "Fenêtre"),
+    ("Ingredients", "Ingrédients"),
+    ("Ingredient Categories", "Catégories d'ingrédients"),
+    ("Ingredient Replace", "Remplacer un ingrédient"),
+    ("Duplicate Ingredients", "Ingrédients en double"),
+    ("Recipe Search", "Recherche de recettes"),
+    ("All Recipes", "Toutes les recettes"),
+    ("Calendar", "Calendrier"),
+    ("Shopping Comparison", "Comparaison des courses"),
+    ("Stats", "Statistiques"),
+    ("Nutrition Dashboard", "Tableau nutritionnel"),
+    ("Missing Nutrition", "Nutrition manquante"),
+    ("Recipe Costs", "Coût des recettes"),
+    ("In Season", "De saison"),
+    ("Try Next", "À essayer"),
+    ("Freezer", "Congélateur"),
+    ("Import", "Importer"),
+    ("Export Recipe Book", "Exporter le livre de recettes"),
+    ("Sync Now", "Synchroniser"),
+    ("Check Database", "Vérifier la base de données"),
+    ("Settings", "Paramètres"),
+    ("Keyboard Shortcuts", "Raccourcis clavier"),
+    ("Log", "Journal"),
+    ("About", "À propos"),
+    // The calendar
+    ("Week of", "Semaine du"),
+    ("Total Calories:", "Total des calories :"),
+    ("Previous", "Précédente"),
+    ("Next", "Suivante"),
+    ("Menu", "Menu"),
+    ("Shopping List", "Liste de courses"),
+    ("Send", "Envoyer"),
+    ("Expand blends", "Détailler les mélanges"),
+    ("Include staples", "Inclure les produits de base"),
+    ("No Recipe", "Pas de recette"),
+    ("Select", "Choisir"),
+    ("search for recipe", "chercher une recette"),
+    ("Edit", "Modifier"),
+    // The settings
+    ("Language:", "Langue :"),
+    ("Daily calorie target:", "Objectif de calories par jour :"),
+    ("Fractions:", "Fractions :"),
+    ("Measurements:", "Mesures :"),
+    ("Unit lists:", "Listes d'unités :"),
+    ("Theme:", "Thème :"),
+    ("Weeks start on:", "Les semaines commencent le :"),
+    ("Dates:", "Dates :"),
+    ("Open recipes in:", "Ouvrir les recettes dans :"),
+    ("Diagnostics:", "Diagnostics :"),
+    ("Accessibility:", "Accessibilité :"),
+    ("Sync folder:", "Dossier de synchronisation :"),
+    ("Household diet:", "Régime du foyer :"),
+    ("Save", "Enregistrer"),
+    ("Sunday", "dimanche"),
+    ("Monday", "lundi"),
+    ("Tuesday", "mardi"),
+    ("Wednesday", "mercredi"),
+    ("Thursday", "jeudi"),
+    ("Friday", "vendredi"),
+    ("Saturday", "samedi"),
+    // Recipes
+    (
+        "convert to {quantity} {units}",
+        "convertir en {quantity} {units}",
+    ),
+    (
+        "Calories unknown: {reason}",
+        "Calories inconnues : {reason}",
+    ),
+    (
+        "Using {calories} calories per {quantity} {units}",
+        "{calories} calories pour {quantity} {units}",
+    ),
+    ("e.g. finely chopped", "p. ex. finement haché"),
+    ("edit", "modifier"),
+    ("Couldn't find ingredient", "Ingrédient introuvable"),
+    ("Cancel", "Annuler"),
+    ("Delete", "Supprimer"),
+    (
+        "Removed {ingredient} from {recipe}",
+        "{ingredient} retiré de {recipe}",
+    ),
+    ("Add Ingredient:", "Ajouter un ingrédient :"),
+    ("search for ingredient", "chercher un ingrédient"),
+    ("Add", "Ajouter"),
+    ("Name", "Nom"),
+    ("Note", "Note"),
+    ("Category", "Catégorie"),
+    ("Qty", "Qté"),
+    ("Cal.", "Cal."),
+    ("Name:", "Nom :"),
+    ("Category:", "Catégorie :"),
+    ("search for category", "chercher une catégorie"),
+    (
+        "Couldn't find recipe category",
+        "Catégorie de recettes introuvable",
+    ),
+    ("Duration:", "Durée :"),
+    ("Description:", "Description :"),
+    ("Revert", "Annuler les modifications"),
+    ("Recipe needs a name", "La recette doit avoir un nom"),
+    ("{star} out of 5", "{star} sur 5"),
+    ("Want to try", "À essayer"),
+    (
+        "Listed in the Try Next window until it's cooked",
+        "Listée dans la fenêtre À essayer jusqu'à ce qu'elle soit cuisinée",
+    ),
+    ("Serves:", "Portions :"),
+    (
+        "How many people it feeds as written",
+        "Combien de personnes elle nourrit telle qu'écrite",
+    ),
+    ("Archived", "Archivée"),
+    (
+        "Left out of the recipe lists and searches, but still on the calendar where it was",
+        "Absente des listes et des recherches, mais toujours sur le calendrier où elle était",
+    ),
+    ("Rating:", "Note :"),
+    ("find in description", "chercher dans la description"),
+    ("no matches", "aucun résultat"),
+    ("{current} of {total}", "{current} sur {total}"),
+    ("Previous match", "Résultat précédent"),
+    ("Next match", "Résultat suivant"),
+    ("Close find", "Fermer la recherche"),
+    ("Remove", "Retirer"),
+    (
+        "Error removing photo: {error}",
+        "Erreur en retirant la photo : {error}",
+    ),
+    ("Schedule", "Planifier"),
+    ("Print", "Imprimer"),
+    (
+        "Error printing recipe: {error}",
+        "Erreur en imprimant la recette : {error}",
+    ),
+    ("Export", "Exporter"),
+    (
+        "Error exporting recipe: {error}",
+        "Erreur en exportant la recette : {error}",
+    ),
+    ("Share", "Partager"),
+    ("Photos", "Photos"),
+    ("Journal", "Journal"),
+    ("Cook", "Cuisiner"),
+    ("Pin", "Épingler"),
+    (
+        "Keep this recipe above the other windows",
+        "Garder cette recette au-dessus des autres fenêtres",
+    ),
+    ("Detach", "Détacher"),
+    (
+        "Show this recipe in its own window",
+        "Afficher cette recette dans sa propre fenêtre",
+    ),
+    ("Steps", "Étapes"),
+    // Ingredients
+    ("No ingredients", "Aucun ingrédient"),
+    (
+        "{count} ingredient, showing {first}–{last}",
+        "{count} ingrédient, {first}–{last} affichés",
+    ),
+    (
+        "{count} ingredients, showing {first}–{last}",
+        "{count} ingrédients, {first}–{last} affichés",
+    ),
+    (
+        "Leave off shopping lists unless staples are included",
+        "Absent des listes de courses sauf si les produits de base sont inclus",
+    ),
+    ("Season", "Saison"),
+    ("The months it's in season", "Les mois où il est de saison"),
+    ("Density", "Densité"),
+    (
+        "How much a milliliter weighs, for converting cups to grams and back",
+        "Combien pèse un millilitre, pour convertir les tasses en grammes et inversement",
+    ),
+    ("Price", "Prix"),
+    ("per", "par"),
+    ("item", "pièce"),
+    (
+        "What it costs, for working out what recipes cost",
+        "Ce qu'il coûte, pour calculer le coût des recettes",
+    ),
+    ("Diet", "Régime"),
+    (
+        "Allergens it contains and diets it's safe for",
+        "Les allergènes qu'il contient et les régimes qui le permettent",
+    ),
+    ("Search", "Chercher"),
+    ("Calories", "Calories"),
+    ("Blend", "Mélange"),
+    ("Next ⏵", "Suivante ⏵"),
+    ("⏴ Previous", "⏴ Précédente"),
+    ("Staple", "Produit de base"),
+    (
+        "Remove the category from {count} ingredients?",
+        "Retirer la catégorie de {count} ingrédients ?",
+    ),
+    (
+        "Set the category of {count} ingredients to {category}?",
+        "Mettre {count} ingrédients dans la catégorie {category} ?",
+    ),
+    ("Yes", "Oui"),
+    ("No", "Non"),
+    ("{count} selected", "{count} sélectionnés"),
+    ("Set Category", "Choisir la catégorie"),
+    ("Clear", "Effacer"),
+    (
+        "Couldn't delete {count} ingredients, they are still being used by recipes",
+        "Impossible de supprimer {count} ingrédients, des recettes les utilisent encore",
+    ),
+    ("search by name", "chercher par nom"),
+    // Recipe search
+    ("Create '{name}'", "Créer « {name} »"),
+    ("Nothing found", "Rien trouvé"),
+    ("Search Results", "Résultats de la recherche"),
+    ("at least", "au moins"),
+    (
+        "Ingredient already in search",
+        "Ingrédient déjà dans la recherche",
+    ),
+    ("for recipes including", "pour les recettes contenant"),
+    ("of the listed ingredient", "des ingrédients listés"),
+    ("include archived", "inclure les archivées"),
+    // Importing
+    ("Import Data", "Importer des données"),
+    (
+        "This dialog lets you import data from older versions of Recipe Manager.",
+        "Cette fenêtre importe les données des anciennes versions de Recipe Manager.",
+    ),
+    ("Import Recipes", "Importer des recettes"),
+    ("Import Calendar", "Importer un calendrier"),
+    (
+        "Or recipes exported from other apps.",
+        "Ou des recettes exportées d'autres applications.",
+    ),
+    ("Import from MasterCook", "Importer depuis MasterCook"),
+    ("Import Cooklang Files", "Importer des fichiers Cooklang"),
+    ("Import from Recipe Keeper", "Importer depuis Recipe Keeper"),
+    (
+        "Or a recipe written out as text.",
+        "Ou une recette écrite en texte.",
+    ),
+    ("Paste Recipe Text", "Coller le texte d'une recette"),
+    ("Paste Share Code", "Coller un code de partage"),
+    (
+        "Read Recipe Card Photo",
+        "Lire la photo d'une fiche recette",
+    ),
+    (
+        "Or photos named after the recipes they're of.",
+        "Ou des photos nommées d'après leurs recettes.",
+    ),
+    (
+        "Attach Photos from Folder",
+        "Joindre les photos d'un dossier",
+    ),
+    (
+        "Paste or fix up the text, then check over the recipe picked out of it.",
+        "Collez ou corrigez le texte, puis vérifiez la recette qui en est tirée.",
+    ),
+    (
+        "the recipe's name, ingredients and directions",
+        "le nom, les ingrédients et les instructions de la recette",
+    ),
+    ("Read Text", "Lire le texte"),
+    ("From Recipe Cards", "Fiches recettes"),
+    ("Ingredients:", "Ingrédients :"),
+    (
+        "one on each line, like 2 cups flour",
+        "un par ligne, comme 2 tasses de farine",
+    ),
+    ("Directions:", "Instructions :"),
+    ("Add Recipe", "Ajouter la recette"),
+    (
+        "Paste the share code for a recipe someone sent you.",
+        "Collez le code de partage d'une recette qu'on vous a envoyée.",
+    ),
+    (
+        "No photos were found in that folder.",
+        "Aucune photo n'a été trouvée dans ce dossier.",
+    ),
+    ("okay", "d'accord"),
+    (
+        "Check which recipe each photo is of. Photos without a recipe are left out.",
+        "Vérifiez la recette de chaque photo. Les photos sans recette sont ignorées.",
+    ),
+    ("(none)", "(aucune)"),
+    ("Attach {count} Photos", "Joindre {count} photos"),
+    (
+        "Check over the recipes before they're added. Unchecked ones are left out.",
+        "Vérifiez les recettes avant leur ajout. Celles non cochées sont ignorées.",
+    ),
+    ("Include All", "Tout inclure"),
+    ("Include None", "Ne rien inclure"),
+    ("Import {count} Recipes", "Importer {count} recettes"),
+    ("importing data..", "importation des données.."),
+    (
+        "Stops after the batch being imported. Everything before it is kept.",
+        "S'arrête après le lot en cours. Tout ce qui précède est conservé.",
+    ),
+    (
+        "import failed with error: {error}",
+        "l'importation a échoué avec l'erreur : {error}",
+    ),
+    (
+        "import cancelled. {count} items imported. \
+        Importing the same file again picks up where it left off.",
+        "importation annulée. {count} éléments importés. \
+        Importer à nouveau le même fichier reprend là où elle s'est arrêtée.",
+    ),
+    (
+        "import succeeded. {count} items imported.",
+        "importation réussie. {count} éléments importés.",
+    ),
+    ("No warnings.", "Aucun avertissement."),
+    ("1 warning:", "1 avertissement :"),
+    ("{count} warnings:", "{count} avertissements :"),
+    ("Copy", "Copier"),
+    // Stats
+    ("Recipes:", "Recettes :"),
+    ("Categories:", "Catégories :"),
+    ("Most used ingredients", "Ingrédients les plus utilisés"),
+    ("{count} recipes", "{count} recettes"),
+    ("Calories per recipe", "Calories par recette"),
+    ("Recipes per category", "Recettes par catégorie"),
+    ("Current", "Actuelle"),
+    ("Best", "Record"),
+    ("{n} days", "{n} jours"),
+    ("Cooking streak:", "Série en cuisine :"),
+    ("{n} weeks", "{n} semaines"),
+    ("Planning streak:", "Série de planification :"),
+    ("Badges", "Badges"),
+    ("{n}-day cooking streak", "{n} jours de suite en cuisine"),
+    (
+        "{n}-week planning streak",
+        "{n} semaines de suite planifiées",
+    ),
+    ("Recipe Book", "Livre de recettes"),
+    ("Export CSV", "Exporter en CSV"),
+    (
+        "Save the days recipes were cooked, for a spreadsheet",
+        "Enregistrer les jours où des recettes ont été cuisinées, pour un tableur",
+    ),
+    (
+        "Couldn't export: {error}",
+        "Impossible d'exporter : {error}",
+    ),
+    // Units, as `IngredientMeasurement::as_str` writes them
+    ("cups", "tasses"),
+    ("fl. oz.", "oz liq."),
+    ("lbs.", "lb"),
+    ("oz.", "oz"),
+    ("qt.", "pte"),
+    ("tbsp.", "c. à s."),
+    ("tsp.", "c. à c."),
+    // The menu and shopping list documents
+    ("Shopping list", "Liste de courses"),
+    ("{title} for the Week", "{title} de la semaine"),
+    ("of {date}", "du {date}"),
+    (
+        "{title} for the week of {date}",
+        "{title} de la semaine du {date}",
+    ),
+    ("Other", "Autre"),
+    ("and", "et"),
+    ("of", "de"),
+    ("*{title} for the Week*", "*{title} de la semaine*"),
+    ("*of {week}*", "*du {week}*"),
+    ("{day}\tNo Recipe", "{day}\tPas de recette"),
+    (
+        "*Total Calories:* {total}",
+        "*Total des calories :* {total}",
+    ),
+];
@@ -1,24 +1,54 @@
-use crate::database;
-use crate::import;
+use super::i18n::tr;
+use recipe_manager::database;
+use recipe_manager::database::models::RecipeId;
+use recipe_manager::import::{self, Preview as _};
+use recipe_manager::query;
+use std::fmt::Write as _;
 
 #[derive(Default)]
 pub enum ImportWindow {
     #[default]
     Ready,
     ImportingRecipes {
-        importer: crate::import::RecipeImporter,
+        importer: recipe_manager::import::RecipeImporter,
         log: String,
+        /// The recipes to check over before the import starts, until it does.
+        preview: Option<Vec<import::PreviewRecipe>>,
     },
     ImportingCalendar {
-        importer: crate::import::CalendarImporter,
+        importer: recipe_manager::import::CalendarImporter,
+        log: String,
+    },
+    ImportingForeignRecipes {
+        importer: recipe_manager::import::ForeignRecipeImporter,
+        log: String,
+        preview: Option<Vec<import::PreviewRecipe>>,
+    },
+    PreviewingText {
+        text: String,
+        recipe: import::RecipeText,
+        category: String,
+    },
+    #[cfg(feature = "share")]
+    EnteringShareCode {
+        code: String,
+    },
+    MatchingPhotos {
+        matches: Vec<import::PhotoMatch>,
+        recipes: Vec<(RecipeId, String)>,
+    },
+    ImportingPhotos {
+        importer: recipe_manager::import::PhotoImporter,
         log: String,
     },
     Failed {
-        error: crate::Error,
+        error: recipe_manager::Error,
     },
     Success {
         num_imported: usize,
         log: String,
+        /// Whether it was cancelled before everything was imported.
+        cancelled: bool,
     },
 }
 
@@ -35,23 +65,62 @@ impl ImportWindow {
     ) -> Vec<UpdateEvent> {
         let mut events = vec![];
         let mut open = true;
-        egui::Window::new("Import Data from Previous Version")
+        egui::Window::new(tr("Import Data"))
+            .id(egui::Id::new("Import Data"))
             .open(&mut open)
             .show(ctx, |ui| {
                 let next = match self {
                     Self::Ready => Self::update_ready(conn, ui),
-                    Self::ImportingRecipes { log, importer } => {
+                    Self::ImportingRecipes {
+                        log,
+                        importer,
+                        preview,
+                    } => {
+                        if preview.is_some() {
+                            Self::update_previewing(preview, importer, log, ui)
+                        } else {
+                            ctx.request_repaint_after(std::time::Duration::from_millis(0));
+                            Self::update_importing(conn, log, importer, &mut events, ui)
+                        }
+                    }
+                    Self::ImportingCalendar { log, importer } => {
                         ctx.request_repaint_after(std::time::Duration::from_millis(0));
                         Self::update_importing(conn, log, importer, &mut events, ui)
                     }
-                    Self::ImportingCalendar { log, importer } => {
+                    Self::ImportingForeignRecipes {
+                        log,
+                        importer,
+                        preview,
+                    } => {
+                        if preview.is_some() {
+                            Self::update_previewing(preview, importer, log, ui)
+                        } else {
+                            ctx.request_repaint_after(std::time::Duration::from_millis(0));
+                            Self::update_importing(conn, log, importer, &mut events, ui)
+                        }
+                    }
+                    Self::PreviewingText {
+                        text,
+                        recipe,
+                        category,
+                    } => Self::update_previewing_text(text, recipe, category, ui),
+                    #[cfg(feature = "share")]
+                    Self::EnteringShareCode { code } => {
+                        Self::update_entering_share_code(conn, code, ui)
+                    }
+                    Self::MatchingPhotos { matches, recipes } => {
+                        Self::update_matching_photos(matches, recipes, ui)
+                    }
+                    Self::ImportingPhotos { log, importer } => {
                         ctx.request_repaint_after(std::time::Duration::from_millis(0));
                         Self::update_importing(conn, log, importer, &mut events, ui)
                     }
                     Self::Failed { error } => Self::update_failed(error, ui),
-                    Self::Success { num_imported, log } => {
-                        Self::update_success(*num_imported, log, ui)
-                    }
+                    Self::Success {
+                        num_imported,
+                        log,
+                        cancelled,
+                    } => Self::update_success(*num_imported, log, *cancelled, ui),
                 };
                 if let Some(next) = next {
                     *self = next;
@@ -64,41 +133,440 @@ impl ImportWindow {
     }
 
     fn update_ready(conn: &mut database::Connection, ui: &mut egui::Ui) -> Option<Self> {
-        ui.label("This dialog lets you import data from older versions of Recipe Manager.");
-        ui.horizontal(|ui| {
-            if ui.button("Import Recipes").clicked() {
-                if let Some(file) = rfd::FileDialog::new()
-                    .add_filter("recipebook", &["recipebook"])
-                    .set_directory("/")
-                    .pick_file()
-                {
-                    return Some(match import::RecipeImporter::new(conn, file) {
-                        Ok(importer) => Self::ImportingRecipes {
-                            importer,
-                            log: String::new(),
-                        },
-                        Err(error) => Self::Failed { error },
+        ui.label(tr(
+            "This dialog lets you import data from older versions of Recipe Manager.",
+        ));
+        let previous_version = ui
+            .horizontal(|ui| {
+                if ui.button(tr("Import Recipes")).clicked() {
+                    if let Some(file) = rfd::FileDialog::new()
+                        .add_filter("recipebook", &["recipebook"])
+                        .set_directory("/")
+                        .pick_file()
+                    {
+                        let previewing =
+                            import::RecipeImporter::new(file).and_then(|mut importer| {
+                                let log = Self::resume(conn, &mut importer)?;
+                                Ok(Self::ImportingRecipes {
+                                    preview: Some(importer.preview()),
+                                    importer,
+                                    log,
+                                })
+                            });
+                        return Some(previewing.unwrap_or_else(|error| Self::Failed { error }));
+                    }
+                }
+                if ui.button(tr("Import Calendar")).clicked() {
+                    if let Some(file) = rfd::FileDialog::new()
+                        .add_filter("recipecalendar", &["recipecalendar"])
+                        .set_directory("/")
+                        .pick_file()
+                    {
+                        return Some(match import::CalendarImporter::new(file) {
+                            Ok(importer) => Self::ImportingCalendar {
+                                importer,
+                                log: String::new(),
+                            },
+                            Err(error) => Self::Failed { error },
+                        });
+                    }
+                }
+                None
+            })
+            .inner;
+
+        ui.separator();
+        ui.label(tr("Or recipes exported from other apps."));
+        let other_apps = ui
+            .horizontal(|ui| {
+                if ui.button(tr("Import from MasterCook")).clicked() {
+                    if let Some(file) = rfd::FileDialog::new()
+                        .add_filter("MasterCook", &["mx2", "mxp"])
+                        .set_directory("/")
+                        .pick_file()
+                    {
+                        return Some(Self::previewing_foreign_recipes(
+                            conn,
+                            import::ForeignRecipeImporter::new_mastercook(file),
+                        ));
+                    }
+                }
+                if ui.button(tr("Import Cooklang Files")).clicked() {
+                    if let Some(files) = rfd::FileDialog::new()
+                        .add_filter("Cooklang", &["cook"])
+                        .set_directory("/")
+                        .pick_files()
+                    {
+                        return Some(Self::previewing_foreign_recipes(
+                            conn,
+                            import::ForeignRecipeImporter::new_cooklang(&files),
+                        ));
+                    }
+                }
+                if ui.button(tr("Import from Recipe Keeper")).clicked() {
+                    if let Some(file) = rfd::FileDialog::new()
+                        .add_filter("Recipe Keeper", &["zip", "html"])
+                        .set_directory("/")
+                        .pick_file()
+                    {
+                        return Some(Self::previewing_foreign_recipes(
+                            conn,
+                            import::ForeignRecipeImporter::new_recipe_keeper(file),
+                        ));
+                    }
+                }
+                None
+            })
+            .inner;
+
+        ui.separator();
+        ui.label(tr("Or a recipe written out as text."));
+        let text = ui
+            .horizontal(|ui| {
+                if ui.button(tr("Paste Recipe Text")).clicked() {
+                    return Some(Self::previewing_text(String::new()));
+                }
+                #[cfg(feature = "share")]
+                if ui.button(tr("Paste Share Code")).clicked() {
+                    return Some(Self::EnteringShareCode {
+                        code: String::new(),
                     });
                 }
+                #[cfg(feature = "ocr")]
+                if ui.button(tr("Read Recipe Card Photo")).clicked() {
+                    if let Some(file) = rfd::FileDialog::new()
+                        .add_filter("image", &["jpg", "jpeg", "png", "tif", "tiff"])
+                        .set_directory("/")
+                        .pick_file()
+                    {
+                        return Some(match import::recognize_text(file) {
+                            Ok(text) => Self::previewing_text(text),
+                            Err(error) => Self::Failed { error },
+                        });
+                    }
+                }
+                None
+            })
+            .inner;
+
+        ui.separator();
+        ui.label(tr("Or photos named after the recipes they're of."));
+        let photos = ui
+            .button(tr("Attach Photos from Folder"))
+            .clicked()
+            .then(|| rfd::FileDialog::new().set_directory("/").pick_folder())
+            .flatten()
+            .map(|folder| Self::matching_photos(conn, folder));
+        previous_version.or(other_apps).or(text).or(photos)
+    }
+
+    fn previewing_text(text: String) -> Self {
+        Self::PreviewingText {
+            recipe: import::parse_recipe_text(&text),
+            text,
+            category: String::new(),
+        }
+    }
+
+    /// Shows the recipe picked out of the text so it can be fixed up before it's added.
+    fn update_previewing_text(
+        text: &mut String,
+        recipe: &mut import::RecipeText,
+        category: &mut String,
+        ui: &mut egui::Ui,
+    ) -> Option<Self> {
+        ui.label(tr(
+            "Paste or fix up the text, then check over the recipe picked out of it.",
+        ));
+        egui::ScrollArea::vertical()
+            .id_salt("recipe text")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(text)
+                        .hint_text(tr("the recipe's name, ingredients and directions"))
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        if ui.button(tr("Read Text")).clicked() {
+            *recipe = import::parse_recipe_text(text);
+        }
+        ui.separator();
+
+        egui::Grid::new("recipe preview")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(tr("Name:"));
+                ui.text_edit_singleline(&mut recipe.name);
+                ui.end_row();
+                ui.label(tr("Category:"));
+                ui.add(egui::TextEdit::singleline(category).hint_text(tr("From Recipe Cards")));
+                ui.end_row();
+                ui.label(tr("Ingredients:"));
+                ui.add(
+                    egui::TextEdit::multiline(&mut recipe.ingredients)
+                        .hint_text(tr("one on each line, like 2 cups flour"))
+                        .desired_width(f32::INFINITY),
+                );
+                ui.end_row();
+                ui.label(tr("Directions:"));
+                ui.add(
+                    egui::TextEdit::multiline(&mut recipe.directions).desired_width(f32::INFINITY),
+                );
+                ui.end_row();
+            });
+        ui.separator();
+
+        let mut next = None;
+        ui.horizontal(|ui| {
+            if ui.button(tr("Add Recipe")).clicked() {
+                next = Some(Self::importing_foreign_recipes(
+                    import::ForeignRecipeImporter::new_from_text(recipe, category),
+                ));
+            }
+            if ui.button(tr("Cancel")).clicked() {
+                next = Some(Self::Ready);
             }
-            if ui.button("Import Calendar").clicked() {
-                if let Some(file) = rfd::FileDialog::new()
-                    .add_filter("recipecalendar", &["recipecalendar"])
-                    .set_directory("/")
-                    .pick_file()
-                {
-                    return Some(match import::CalendarImporter::new(file) {
-                        Ok(importer) => Self::ImportingCalendar {
-                            importer,
-                            log: String::new(),
-                        },
-                        Err(error) => Self::Failed { error },
+        });
+        next
+    }
+
+    #[cfg(feature = "share")]
+    fn update_entering_share_code(
+        conn: &mut database::Connection,
+        code: &mut String,
+        ui: &mut egui::Ui,
+    ) -> Option<Self> {
+        ui.label(tr("Paste the share code for a recipe someone sent you."));
+        ui.add(
+            egui::TextEdit::multiline(code)
+                .hint_text(recipe_manager::share::PREFIX)
+                .desired_width(f32::INFINITY),
+        );
+        let mut next = None;
+        ui.horizontal(|ui| {
+            if ui.button(tr("Add Recipe")).clicked() {
+                next = Some(Self::previewing_foreign_recipes(
+                    conn,
+                    import::ForeignRecipeImporter::new_from_share_code(code),
+                ));
+            }
+            if ui.button(tr("Cancel")).clicked() {
+                next = Some(Self::Ready);
+            }
+        });
+        next
+    }
+
+    fn matching_photos(conn: &mut database::Connection, folder: std::path::PathBuf) -> Self {
+        let matched = import::match_photos(conn, folder).and_then(|matches| {
+            Ok(Self::MatchingPhotos {
+                matches,
+                recipes: query::search_recipes(conn, &mut None, "", true)?,
+            })
+        });
+        matched.unwrap_or_else(|error| Self::Failed { error })
+    }
+
+    /// Shows which recipe each photo seems to be of, letting the user fix or leave out any before
+    /// they're attached.
+    fn update_matching_photos(
+        matches: &mut [import::PhotoMatch],
+        recipes: &[(RecipeId, String)],
+        ui: &mut egui::Ui,
+    ) -> Option<Self> {
+        if matches.is_empty() {
+            ui.label(tr("No photos were found in that folder."));
+            return ui.button(tr("okay")).clicked().then_some(Self::Ready);
+        }
+        ui.label(tr(
+            "Check which recipe each photo is of. Photos without a recipe are left out.",
+        ));
+        let scroll_height = ui.available_height() - 35.0;
+        egui::ScrollArea::vertical()
+            .max_height(scroll_height)
+            .show(ui, |ui| {
+                egui::Grid::new("photo matches")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, m) in matches.iter_mut().enumerate() {
+                            ui.label(m.file_name());
+                            let selected = m.recipe.as_ref().map(|r| r.1.as_str());
+                            egui::ComboBox::from_id_salt(("photo recipe", i))
+                                .selected_text(selected.unwrap_or(tr("(none)")))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut m.recipe, None, tr("(none)"));
+                                    for r in recipes {
+                                        ui.selectable_value(&mut m.recipe, Some(r.clone()), &r.1);
+                                    }
+                                });
+                            ui.end_row();
+                        }
                     });
-                }
+            });
+        ui.separator();
+        let photos: Vec<_> = matches
+            .iter()
+            .filter_map(|m| Some((m.path.clone(), m.recipe.as_ref()?.0)))
+            .collect();
+        let mut next = None;
+        ui.horizontal(|ui| {
+            let attach = tr("Attach {count} Photos").replace("{count}", &photos.len().to_string());
+            if ui
+                .add_enabled(!photos.is_empty(), egui::Button::new(attach))
+                .clicked()
+            {
+                next = Some(Self::ImportingPhotos {
+                    importer: import::PhotoImporter::new(photos),
+                    log: String::new(),
+                });
+            }
+            if ui.button(tr("Cancel")).clicked() {
+                next = Some(Self::Ready);
+            }
+        });
+        next
+    }
+
+    fn importing_foreign_recipes(
+        importer: recipe_manager::Result<import::ForeignRecipeImporter>,
+    ) -> Self {
+        match importer {
+            Ok(importer) => Self::ImportingForeignRecipes {
+                importer,
+                log: String::new(),
+                preview: None,
+            },
+            Err(error) => Self::Failed { error },
+        }
+    }
+
+    /// Like `importing_foreign_recipes`, but showing what will be imported first.
+    fn previewing_foreign_recipes(
+        conn: &mut database::Connection,
+        importer: recipe_manager::Result<import::ForeignRecipeImporter>,
+    ) -> Self {
+        let previewing = importer.and_then(|mut importer| {
+            let log = Self::resume(conn, &mut importer)?;
+            Ok(Self::ImportingForeignRecipes {
+                preview: Some(importer.preview()),
+                importer,
+                log,
+            })
+        });
+        previewing.unwrap_or_else(|error| Self::Failed { error })
+    }
+
+    /// Leaves out anything imported from the same file before, like by an import which was
+    /// cancelled. Returns the start of the log, saying if anything was.
+    fn resume(
+        conn: &mut database::Connection,
+        importer: &mut impl import::Importer,
+    ) -> recipe_manager::Result<String> {
+        let skipped = importer.skip_imported(conn)?;
+        let mut log = String::new();
+        if skipped > 0 {
+            writeln!(
+                log,
+                "{skipped} recipes were imported from this file before, so they were left out."
+            )?;
+        }
+        Ok(log)
+    }
+
+    /// Shows the recipes about to be imported, so they can be renamed, put in other categories or
+    /// left out before any of them are added.
+    fn update_previewing(
+        preview: &mut Option<Vec<import::PreviewRecipe>>,
+        importer: &mut impl import::Preview,
+        log: &str,
+        ui: &mut egui::Ui,
+    ) -> Option<Self> {
+        let recipes = preview.as_mut().unwrap();
+        ui.label(tr(
+            "Check over the recipes before they're added. Unchecked ones are left out.",
+        ));
+        if !log.is_empty() {
+            ui.weak(log.trim_end());
+        }
+        ui.horizontal(|ui| {
+            if ui.button(tr("Include All")).clicked() {
+                recipes.iter_mut().for_each(|r| r.include = true);
+            }
+            if ui.button(tr("Include None")).clicked() {
+                recipes.iter_mut().for_each(|r| r.include = false);
             }
-            None
-        })
-        .inner
+        });
+
+        let table_height = ui.available_height() - 35.0;
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("import preview table")
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::exact(20.0))
+            .column(egui_extras::Column::initial(200.0).resizable(true))
+            .column(egui_extras::Column::initial(150.0).resizable(true))
+            .column(egui_extras::Column::exact(80.0))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(table_height)
+            .header(20.0, |mut header| {
+                header.col(|_| {});
+                header.col(|ui| {
+                    ui.heading(tr("Name"));
+                });
+                header.col(|ui| {
+                    ui.heading(tr("Category"));
+                });
+                header.col(|ui| {
+                    ui.heading(tr("Ingredients"));
+                });
+            })
+            .body(|body| {
+                body.rows(20.0, recipes.len(), |mut row| {
+                    let r = &mut recipes[row.index()];
+                    row.col(|ui| {
+                        ui.checkbox(&mut r.include, "");
+                    });
+                    row.col(|ui| {
+                        ui.add_enabled(
+                            r.include,
+                            egui::TextEdit::singleline(&mut r.name).desired_width(f32::INFINITY),
+                        );
+                    });
+                    row.col(|ui| {
+                        ui.add_enabled(
+                            r.include,
+                            egui::TextEdit::singleline(&mut r.category)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                    row.col(|ui| {
+                        ui.label(r.num_ingredients.to_string());
+                    });
+                });
+            });
+        ui.separator();
+
+        let included = recipes.iter().filter(|r| r.include).count();
+        let mut next = None;
+        let mut start = false;
+        ui.horizontal(|ui| {
+            start = ui
+                .add_enabled(
+                    included > 0,
+                    egui::Button::new(
+                        tr("Import {count} Recipes").replace("{count}", &included.to_string()),
+                    ),
+                )
+                .clicked();
+            if ui.button(tr("Cancel")).clicked() {
+                next = Some(Self::Ready);
+            }
+        });
+        if start {
+            importer.apply_preview(&preview.take().unwrap());
+        }
+        next
     }
 
     fn update_importing(
@@ -108,8 +576,24 @@ impl ImportWindow {
         events: &mut Vec<UpdateEvent>,
         ui: &mut egui::Ui,
     ) -> Option<Self> {
-        ui.label("importing data..");
+        ui.label(tr("importing data.."));
         ui.add(egui::widgets::ProgressBar::new(importer.percent_done()));
+        if ui
+            .button(tr("Cancel"))
+            .on_hover_text(tr(
+                "Stops after the batch being imported. Everything before it is kept.",
+            ))
+            .clicked()
+        {
+            if importer.num_imported() > 0 {
+                events.push(UpdateEvent::Imported);
+            }
+            return Some(Self::Success {
+                num_imported: importer.num_imported(),
+                log: std::mem::take(log),
+                cancelled: true,
+            });
+        }
 
         if !importer.done() {
             if let Err(error) = importer.import_one(conn, log) {
@@ -120,20 +604,50 @@ impl ImportWindow {
             return Some(Self::Success {
                 num_imported: importer.num_imported(),
                 log: std::mem::take(log),
+                cancelled: false,
             });
         }
 
         None
     }
 
-    fn update_failed(error: &crate::Error, ui: &mut egui::Ui) -> Option<Self> {
-        ui.label(format!("import failed with error: {error}"));
-        ui.button("okay").clicked().then_some(Self::Ready)
+    fn update_failed(error: &recipe_manager::Error, ui: &mut egui::Ui) -> Option<Self> {
+        ui.label(tr("import failed with error: {error}").replace("{error}", &error.to_string()));
+        ui.button(tr("okay")).clicked().then_some(Self::Ready)
     }
 
-    fn update_success(num_imported: usize, log: &str, ui: &mut egui::Ui) -> Option<Self> {
-        ui.label(format!("import succeeded. {num_imported} items imported."));
+    fn update_success(
+        num_imported: usize,
+        log: &str,
+        cancelled: bool,
+        ui: &mut egui::Ui,
+    ) -> Option<Self> {
+        if cancelled {
+            ui.label(
+                tr("import cancelled. {count} items imported. \
+                Importing the same file again picks up where it left off.")
+                .replace("{count}", &num_imported.to_string()),
+            );
+        } else {
+            ui.label(
+                tr("import succeeded. {count} items imported.")
+                    .replace("{count}", &num_imported.to_string()),
+            );
+        }
         if !log.is_empty() {
+            let warnings = log.lines().filter(|l| l.starts_with("warning:")).count();
+            ui.horizontal(|ui| {
+                match warnings {
+                    0 => ui.label(tr("No warnings.")),
+                    1 => ui.label(tr("1 warning:")),
+                    n => ui.label(tr("{count} warnings:").replace("{count}", &n.to_string())),
+                };
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(tr("Copy")).clicked() {
+                        ui.ctx().copy_text(log.into());
+                    }
+                });
+            });
             let scroll_height = ui.available_height() - 35.0;
             egui::ScrollArea::vertical()
                 .auto_shrink(false)
@@ -143,6 +657,6 @@ impl ImportWindow {
                 });
         }
         ui.separator();
-        ui.button("okay").clicked().then_some(Self::Ready)
+        ui.button(tr("okay")).clicked().then_some(Self::Ready)
     }
 }
@@ -0,0 +1,128 @@
+use super::errors::QueryResultExt as _;
+use super::recipe::RecipeWindow;
+use super::recipe_preview::RecipePreviewExt as _;
+use chrono::Datelike as _;
+use recipe_manager::database;
+use recipe_manager::database::models::RecipeId;
+use recipe_manager::query;
+use std::collections::HashMap;
+
+fn month_name(month: u32) -> &'static str {
+    chrono::Month::try_from(month as u8).map_or("", |m| m.name())
+}
+
+/// Suggests recipes to cook because of what's in season, the ones with the most of their
+/// ingredients in season first.
+pub struct InSeasonWindow {
+    month: u32,
+    recipes: Vec<query::InSeasonRecipe>,
+}
+
+impl InSeasonWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        let month = chrono::Local::now().month();
+        Self {
+            month,
+            recipes: query::get_in_season_recipes(conn, month).unwrap_or_report(),
+        }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        self.recipes = query::get_in_season_recipes(conn, self.month).unwrap_or_report();
+    }
+
+    fn update_month(&mut self, conn: &mut database::Connection, ui: &mut egui::Ui) {
+        let before = self.month;
+        ui.horizontal(|ui| {
+            ui.label("In season in");
+            egui::ComboBox::from_id_salt("in season month")
+                .selected_text(month_name(self.month))
+                .show_ui(ui, |ui| {
+                    for month in 1..=12 {
+                        ui.selectable_value(&mut self.month, month, month_name(month));
+                    }
+                });
+        });
+        if self.month != before {
+            self.refresh(conn);
+        }
+    }
+
+    fn update_table(
+        &self,
+        conn: &mut database::Connection,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+        selected_week: Option<chrono::NaiveWeek>,
+        ui: &mut egui::Ui,
+    ) {
+        let available_height = ui.available_height();
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("in season table")
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::remainder().clip(true))
+            .column(egui_extras::Column::exact(90.0))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(available_height)
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.heading("Recipe");
+                });
+                header.col(|ui| {
+                    ui.heading("In Season");
+                });
+            })
+            .body(|body| {
+                body.rows(20.0, self.recipes.len(), |mut row| {
+                    let r = &self.recipes[row.index()];
+                    let id = r.recipe.id;
+                    let mut shown = recipe_windows.contains_key(&id);
+                    row.col(|ui| {
+                        ui.toggle_value(&mut shown, &r.recipe.name)
+                            .on_hover_recipe_preview(conn, id);
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{} of {}", r.in_season.len(), r.ingredients))
+                            .on_hover_text(r.in_season.join("\n"));
+                    });
+
+                    if shown && !recipe_windows.contains_key(&id) {
+                        if let Some(window) =
+                            RecipeWindow::new(conn, id, selected_week, false).or_report()
+                        {
+                            recipe_windows.insert(id, window);
+                        }
+                    } else if !shown {
+                        recipe_windows.remove(&id);
+                    }
+                });
+            });
+    }
+
+    /// Returns true when the window is closed.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+        selected_week: Option<chrono::NaiveWeek>,
+    ) -> bool {
+        let mut open = true;
+        egui::Window::new("In Season")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                self.update_month(conn, ui);
+                ui.separator();
+                if self.recipes.is_empty() {
+                    ui.label(
+                        "No recipes use anything in season. \
+                        Set the months ingredients are in season from the Ingredients window.",
+                    );
+                } else {
+                    self.update_table(conn, recipe_windows, selected_week, ui);
+                }
+            });
+        !open
+    }
+}
@@ -1,19 +1,47 @@
-use super::query;
-use super::recipe::{quantity_display, quantity_parse};
-use crate::database;
-use crate::database::models::{IngredientCaloriesEntry, IngredientHandle, IngredientMeasurement};
+use super::errors::QueryResultExt as _;
+use super::i18n;
+use super::is_editable;
+#[cfg(feature = "nutrition-lookup")]
+use super::nutrition_lookup::NutritionLookupWindow;
+use super::recipe::quantity_label;
+use super::settings::Settings;
+use super::unit_choices;
+use recipe_manager::database;
+use recipe_manager::database::models::{
+    IngredientCaloriesEntry, IngredientHandle, IngredientMeasurement,
+};
+use recipe_manager::query;
+use recipe_manager::unit_conversion::quantity_parse;
 
-#[derive(Default)]
 struct NewEntry {
     calories: String,
     quantity: String,
     quantity_units: Option<IngredientMeasurement>,
 }
 
+impl NewEntry {
+    /// Starts out in the units recipes most often use for the ingredient, so the calories can be
+    /// worked out for them without converting. Otherwise it's per 100 grams, like nutrition labels.
+    fn new(conn: &mut database::Connection, ingredient: &IngredientHandle) -> Self {
+        let units = query::get_most_common_usage_units(conn, ingredient.id).unwrap_or_report();
+        let (quantity, units) = match units {
+            Some(units) => ("1", units),
+            None => ("100", IngredientMeasurement::Grams),
+        };
+        Self {
+            calories: String::new(),
+            quantity: quantity.into(),
+            quantity_units: Some(units),
+        }
+    }
+}
+
 pub struct IngredientCaloriesWindow {
     ingredient: IngredientHandle,
     ingredient_calories: Vec<IngredientCaloriesEntry>,
     new_entry: NewEntry,
+    #[cfg(feature = "nutrition-lookup")]
+    lookup: Option<NutritionLookupWindow>,
 }
 
 pub enum UpdateEvent {
@@ -23,18 +51,23 @@ pub enum UpdateEvent {
 
 impl IngredientCaloriesWindow {
     pub fn new(conn: &mut database::Connection, ingredient: IngredientHandle) -> Self {
-        let ingredient_calories = query::get_ingredient_calories(conn, ingredient.id);
+        let ingredient_calories =
+            query::get_ingredient_calories(conn, ingredient.id).unwrap_or_report();
 
+        let new_entry = NewEntry::new(conn, &ingredient);
         Self {
             ingredient,
             ingredient_calories,
-            new_entry: NewEntry::default(),
+            new_entry,
+            #[cfg(feature = "nutrition-lookup")]
+            lookup: None,
         }
     }
 
     fn update_table(
         &mut self,
         conn: &mut database::Connection,
+        settings: &Settings,
         ui: &mut egui::Ui,
         refresh_self: &mut bool,
     ) -> Vec<UpdateEvent> {
@@ -72,14 +105,27 @@ impl IngredientCaloriesWindow {
                             ui.label(c.calories.to_string());
                         });
                         row.col(|ui| {
-                            ui.label(quantity_display(c.quantity, &c.quantity_units));
+                            quantity_label(
+                                ui,
+                                c.quantity,
+                                &c.quantity_units,
+                                settings.fraction_precision,
+                            );
                         });
                         row.col(|ui| {
-                            ui.label(c.quantity_units.as_ref().map(|c| c.as_str()).unwrap_or(""));
+                            ui.label(
+                                c.quantity_units
+                                    .as_ref()
+                                    .map(|c| i18n::units(*c))
+                                    .unwrap_or(""),
+                            );
                         });
                         row.col(|ui| {
-                            if ui.button("Delete").clicked() {
-                                query::delete_ingredient_calories_entry(conn, c.id);
+                            if ui
+                                .add_enabled(is_editable(ui.ctx()), egui::Button::new("Delete"))
+                                .clicked()
+                            {
+                                query::delete_ingredient_calories_entry(conn, c.id).or_report();
                                 *refresh_self = true;
                                 events.push(UpdateEvent::IngredientEdited);
                             }
@@ -97,61 +143,105 @@ impl IngredientCaloriesWindow {
         refresh_self: &mut bool,
     ) -> Vec<UpdateEvent> {
         let mut events = vec![];
-        egui_extras::StripBuilder::new(ui)
+        let strip = egui_extras::StripBuilder::new(ui)
             .size(egui_extras::Size::exact(80.0))
             .size(egui_extras::Size::exact(80.0))
             .size(egui_extras::Size::remainder())
-            .size(egui_extras::Size::exact(50.0))
-            .horizontal(|mut strip| {
-                strip.cell(|ui| {
-                    ui.add(
-                        egui::TextEdit::singleline(&mut self.new_entry.calories)
-                            .hint_text("calories"),
-                    );
-                });
-                strip.cell(|ui| {
-                    ui.add(
-                        egui::TextEdit::singleline(&mut self.new_entry.quantity)
-                            .hint_text("quantity"),
-                    );
-                });
-                strip.cell(|ui| {
-                    egui::ComboBox::from_id_salt((
-                        "new quantity measurement calories",
-                        self.ingredient.id,
-                    ))
-                    .selected_text(
-                        self.new_entry
-                            .quantity_units
-                            .as_ref()
-                            .map(|q| q.as_str())
-                            .unwrap_or(""),
-                    )
-                    .show_ui(ui, |ui| {
-                        for m in IngredientMeasurement::iter() {
-                            ui.selectable_value(
-                                &mut self.new_entry.quantity_units,
-                                Some(m),
-                                m.as_str(),
-                            );
-                        }
-                        ui.selectable_value(&mut self.new_entry.quantity_units, None, "");
-                    });
-                });
-                strip.cell(|ui| {
-                    if ui.button("Add").clicked() {
-                        query::add_ingredient_calories_entry(
-                            conn,
-                            self.ingredient.id,
-                            self.new_entry.calories.parse().unwrap_or(0.0),
-                            quantity_parse(&self.new_entry.quantity).unwrap_or(0.0),
-                            self.new_entry.quantity_units,
+            .size(egui_extras::Size::exact(50.0));
+        #[cfg(feature = "nutrition-lookup")]
+        let strip = strip.size(egui_extras::Size::exact(60.0));
+        strip.horizontal(|mut strip| {
+            strip.cell(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_entry.calories).hint_text("calories"),
+                );
+            });
+            strip.cell(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_entry.quantity).hint_text("quantity"),
+                );
+            });
+            strip.cell(|ui| {
+                egui::ComboBox::from_id_salt((
+                    "new quantity measurement calories",
+                    self.ingredient.id,
+                ))
+                .selected_text(
+                    self.new_entry
+                        .quantity_units
+                        .as_ref()
+                        .map(|q| i18n::units(*q))
+                        .unwrap_or(""),
+                )
+                .show_ui(ui, |ui| {
+                    for m in unit_choices(ui.ctx()).units() {
+                        ui.selectable_value(
+                            &mut self.new_entry.quantity_units,
+                            Some(m),
+                            i18n::units(m),
                         );
-                        *refresh_self = true;
-                        events.push(UpdateEvent::IngredientEdited);
                     }
+                    ui.selectable_value(&mut self.new_entry.quantity_units, None, "");
                 });
             });
+            strip.cell(|ui| {
+                if ui
+                    .add_enabled(is_editable(ui.ctx()), egui::Button::new("Add"))
+                    .clicked()
+                {
+                    query::add_ingredient_calories_entry(
+                        conn,
+                        self.ingredient.id,
+                        self.new_entry.calories.parse().unwrap_or(0.0),
+                        quantity_parse(&self.new_entry.quantity).unwrap_or(0.0),
+                        self.new_entry.quantity_units,
+                    )
+                    .or_report();
+                    *refresh_self = true;
+                    events.push(UpdateEvent::IngredientEdited);
+                }
+            });
+            #[cfg(feature = "nutrition-lookup")]
+            strip.cell(|ui| {
+                let mut lookup_shown = self.lookup.is_some();
+                ui.toggle_value(&mut lookup_shown, "Lookup");
+                if lookup_shown && self.lookup.is_none() {
+                    self.lookup = Some(NutritionLookupWindow::new(self.ingredient.clone()));
+                } else if !lookup_shown {
+                    self.lookup = None;
+                }
+            });
+        });
+        events
+    }
+
+    #[cfg(feature = "nutrition-lookup")]
+    fn update_lookup(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        refresh_self: &mut bool,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        if let Some(lookup) = &mut self.lookup {
+            let (selected, closed) = lookup.update(ctx, settings.fdc_api_key());
+            if let Some(calories) = selected {
+                query::add_ingredient_calories_entry(
+                    conn,
+                    self.ingredient.id,
+                    calories,
+                    100.0,
+                    Some(IngredientMeasurement::Grams),
+                )
+                .or_report();
+                *refresh_self = true;
+                events.push(UpdateEvent::IngredientEdited);
+            }
+            if closed {
+                self.lookup = None;
+            }
+        }
         events
     }
 
@@ -159,6 +249,7 @@ impl IngredientCaloriesWindow {
         &mut self,
         ctx: &egui::Context,
         conn: &mut database::Connection,
+        settings: &Settings,
     ) -> Vec<UpdateEvent> {
         let style = ctx.style();
         let button_height = (egui::TextStyle::Button.resolve(&style).size
@@ -184,7 +275,12 @@ impl IngredientCaloriesWindow {
                     .vertical(|mut strip| {
                         strip.cell(|ui| {
                             egui::ScrollArea::vertical().show(ui, |ui| {
-                                events.extend(self.update_table(conn, ui, &mut refresh_self));
+                                events.extend(self.update_table(
+                                    conn,
+                                    settings,
+                                    ui,
+                                    &mut refresh_self,
+                                ));
                             });
                         });
                         strip.cell(|ui| {
@@ -194,8 +290,17 @@ impl IngredientCaloriesWindow {
                     });
             });
 
+        #[cfg(feature = "nutrition-lookup")]
+        events.extend(self.update_lookup(ctx, conn, settings, &mut refresh_self));
+
         if refresh_self {
+            #[cfg(feature = "nutrition-lookup")]
+            let lookup = self.lookup.take();
             *self = Self::new(conn, self.ingredient.clone());
+            #[cfg(feature = "nutrition-lookup")]
+            {
+                self.lookup = lookup;
+            }
         }
 
         if !open {
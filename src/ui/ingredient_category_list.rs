@@ -0,0 +1,261 @@
+use super::errors::QueryResultExt as _;
+use super::{new_error_toast, search::SearchWidget, EditToggleExt as _};
+use recipe_manager::database;
+use recipe_manager::database::models::{IngredientCategory, IngredientCategoryId};
+use recipe_manager::query;
+use std::collections::HashMap;
+
+struct CategoryBeingEdited {
+    id: IngredientCategoryId,
+    name: String,
+    merge_name: String,
+    merge_into: Option<IngredientCategoryId>,
+    cached_category_search: Option<query::CachedQuery<IngredientCategoryId>>,
+}
+
+impl CategoryBeingEdited {
+    fn new(category: &IngredientCategory) -> Self {
+        Self {
+            id: category.id,
+            name: category.name.clone(),
+            merge_name: String::new(),
+            merge_into: None,
+            cached_category_search: None,
+        }
+    }
+}
+
+pub enum UpdateEvent {
+    Closed,
+    CategoriesChanged,
+}
+
+pub struct IngredientCategoryListWindow {
+    categories: Vec<IngredientCategory>,
+    counts: HashMap<IngredientCategoryId, usize>,
+    edit_mode: bool,
+    category_being_edited: Option<CategoryBeingEdited>,
+}
+
+impl IngredientCategoryListWindow {
+    fn new_with_args(conn: &mut database::Connection, edit_mode: bool) -> Self {
+        Self {
+            categories: query::get_ingredient_categories(conn).unwrap_or_report(),
+            counts: query::get_ingredient_category_counts(conn).unwrap_or_report(),
+            edit_mode,
+            category_being_edited: None,
+        }
+    }
+
+    pub fn new(conn: &mut database::Connection) -> Self {
+        Self::new_with_args(conn, false)
+    }
+
+    fn update_category_editing(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        category: &IngredientCategory,
+        body: &mut egui_extras::TableBody<'_>,
+        refresh_self: &mut bool,
+    ) -> bool {
+        let Some(e) = &mut self.category_being_edited else {
+            return false;
+        };
+        if e.id != category.id {
+            return false;
+        }
+
+        let count = self.counts.get(&category.id).copied().unwrap_or(0);
+        body.row(20.0, |mut row| {
+            row.col(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut e.name));
+            });
+            row.col(|ui| {
+                ui.label(count.to_string());
+            });
+            row.col(|ui| {
+                if ui.button("Save").clicked() {
+                    let new_name = e.name.trim();
+                    let existing = query::get_ingredient_categories(conn)
+                        .unwrap_or_report()
+                        .into_iter()
+                        .find(|c| c.id != e.id && c.name.to_lowercase() == new_name.to_lowercase());
+                    if new_name.is_empty() {
+                        toasts.add(new_error_toast("Category needs a name"));
+                    } else if let Some(existing) = existing {
+                        toasts.add(new_error_toast(format!(
+                            "There is already a category named {:?}, merge into it instead",
+                            existing.name
+                        )));
+                    } else {
+                        query::rename_ingredient_category(conn, e.id, new_name).or_report();
+                        *refresh_self = true;
+                    }
+                }
+            });
+        });
+        body.row(20.0, |mut row| {
+            row.col(|ui| {
+                ui.add(
+                    SearchWidget::new(
+                        ("ingredient category merge", e.id),
+                        &mut e.merge_name,
+                        &mut e.merge_into,
+                        |query| {
+                            query::search_ingredient_categories(
+                                conn,
+                                &mut e.cached_category_search,
+                                query,
+                            )
+                            .unwrap_or_report()
+                        },
+                    )
+                    .hint_text("merge into category"),
+                );
+            });
+            row.col(|_| {});
+            row.col(|ui| {
+                let target = e.merge_into.filter(|c| *c != e.id);
+                if ui
+                    .add_enabled(target.is_some(), egui::Button::new("Merge"))
+                    .clicked()
+                {
+                    query::merge_ingredient_categories(conn, e.id, target.unwrap()).or_report();
+                    *refresh_self = true;
+                }
+            });
+        });
+        true
+    }
+
+    fn update_table_contents(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        body: &mut egui_extras::TableBody<'_>,
+        refresh_self: &mut bool,
+    ) {
+        let categories = std::mem::take(&mut self.categories);
+        for category in &categories {
+            if !self.update_category_editing(conn, toasts, category, body, refresh_self) {
+                body.row(20.0, |mut row| {
+                    let count = self.counts.get(&category.id).copied().unwrap_or(0);
+                    row.col(|ui| {
+                        ui.label(&category.name);
+                    });
+                    row.col(|ui| {
+                        ui.label(count.to_string());
+                    });
+                    row.col(|ui| {
+                        if self.edit_mode {
+                            if ui.button("Edit").clicked() {
+                                self.category_being_edited =
+                                    Some(CategoryBeingEdited::new(category));
+                            }
+                            if ui.button("Delete").clicked() {
+                                if query::delete_ingredient_category(conn, category.id)
+                                    .unwrap_or_report()
+                                {
+                                    *refresh_self = true;
+                                } else {
+                                    toasts.add(new_error_toast(
+                                        "Couldn't delete category, it still contains ingredients",
+                                    ));
+                                }
+                            }
+                        }
+                    });
+                });
+            }
+        }
+        self.categories = categories;
+    }
+
+    fn update_table(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+    ) {
+        let available_height = ui.available_height();
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("ingredient category table")
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::exact(80.0))
+            .column(egui_extras::Column::exact(90.0))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(available_height)
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.heading("Name");
+                });
+                header.col(|ui| {
+                    ui.heading("Ingredients");
+                });
+                header.col(|ui| {
+                    ui.heading("");
+                });
+            })
+            .body(|mut body| {
+                self.update_table_contents(conn, toasts, &mut body, refresh_self);
+            });
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+    ) -> Vec<UpdateEvent> {
+        let style = ctx.style();
+        let button_height = (egui::TextStyle::Button.resolve(&style).size
+            + style.spacing.button_padding.y * 2.0)
+            .max(style.spacing.interact_size.y);
+        let spacing = style.spacing.item_spacing.y;
+        let separator_height = 6.0;
+
+        let edit_height = button_height + spacing + separator_height + 2.0;
+
+        let mut events = vec![];
+        let mut open = true;
+        let mut refresh_self = false;
+        egui::Window::new("Ingredient Categories")
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                egui_extras::StripBuilder::new(ui)
+                    .size(egui_extras::Size::remainder())
+                    .size(egui_extras::Size::exact(edit_height))
+                    .vertical(|mut strip| {
+                        strip.cell(|ui| {
+                            self.update_table(conn, toasts, ui, &mut refresh_self);
+                        });
+                        strip.cell(|ui| {
+                            ui.separator();
+                            ui.edit_toggle(&mut self.edit_mode);
+                        });
+                    });
+            });
+
+        if !self.edit_mode {
+            self.category_being_edited = None;
+        }
+
+        if refresh_self {
+            *self = Self::new_with_args(conn, self.edit_mode);
+            events.push(UpdateEvent::CategoriesChanged);
+        }
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+
+    pub fn ingredients_changed(&mut self, conn: &mut database::Connection) {
+        *self = Self::new_with_args(conn, self.edit_mode);
+    }
+}
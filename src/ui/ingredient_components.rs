@@ -0,0 +1,371 @@
+use super::errors::QueryResultExt as _;
+use super::i18n;
+use super::recipe::{quantity_display, quantity_label};
+use super::{is_editable, new_error_toast, search::SearchWidget, settings::Settings, unit_choices};
+use recipe_manager::database;
+#[cfg(feature = "rtf")]
+use recipe_manager::database::models::IngredientUsage;
+use recipe_manager::database::models::{
+    Ingredient, IngredientComponent, IngredientCompound, IngredientHandle, IngredientMeasurement,
+};
+use recipe_manager::query;
+use recipe_manager::unit_conversion::quantity_parse;
+
+/// Replaces any blends in the given ingredients with their components, scaled to the quantity
+/// used. Blends which have no definition or whose units can't be converted are left alone.
+#[cfg(feature = "rtf")]
+pub fn expand_compounds(
+    conn: &mut database::Connection,
+    ingredients: Vec<(IngredientUsage, Ingredient)>,
+) -> Vec<(IngredientUsage, Ingredient)> {
+    let definitions =
+        query::get_compound_definitions(conn, ingredients.iter().map(|(_, i)| i.id).collect())
+            .unwrap_or_report();
+
+    let mut expanded = vec![];
+    for (usage, ingredient) in ingredients {
+        match definitions
+            .get(&ingredient.id)
+            .and_then(|d| d.expand(&usage))
+        {
+            Some(components) => expanded.extend(components),
+            None => expanded.push((usage, ingredient)),
+        }
+    }
+    expanded
+}
+
+#[derive(Default)]
+struct NewComponent {
+    name: String,
+    ingredient: Option<Ingredient>,
+    cached_ingredient_search: Option<query::CachedQuery<Ingredient>>,
+    quantity: String,
+    quantity_units: Option<IngredientMeasurement>,
+}
+
+pub struct IngredientComponentsWindow {
+    ingredient: IngredientHandle,
+    compound: Option<IngredientCompound>,
+    components: Vec<(IngredientComponent, Ingredient)>,
+    yield_quantity: String,
+    yield_units: Option<IngredientMeasurement>,
+    new_component: NewComponent,
+}
+
+fn units_combo_box(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    units: &mut Option<IngredientMeasurement>,
+) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(units.as_ref().map(|q| i18n::units(*q)).unwrap_or(""))
+        .width(50.0)
+        .show_ui(ui, |ui| {
+            for m in unit_choices(ui.ctx()).units() {
+                ui.selectable_value(units, Some(m), i18n::units(m));
+            }
+            ui.selectable_value(units, None, "");
+        });
+}
+
+impl IngredientComponentsWindow {
+    pub fn new(conn: &mut database::Connection, ingredient: IngredientHandle) -> Self {
+        let compound = query::get_ingredient_compound(conn, ingredient.id).unwrap_or_report();
+        let components = query::get_ingredient_components(conn, ingredient.id).unwrap_or_report();
+
+        Self {
+            yield_quantity: compound
+                .as_ref()
+                .map(|c| quantity_display(c.quantity, &c.quantity_units))
+                .unwrap_or("1".into()),
+            yield_units: compound.as_ref().and_then(|c| c.quantity_units),
+            ingredient,
+            compound,
+            components,
+            new_component: NewComponent::default(),
+        }
+    }
+
+    fn parsed_yield(&self, toasts: &mut egui_toast::Toasts) -> Option<f32> {
+        match quantity_parse(&self.yield_quantity) {
+            Some(q) if q > 0.0 => Some(q),
+            _ => {
+                toasts.add(new_error_toast(
+                    "Blend must make a quantity greater than zero",
+                ));
+                None
+            }
+        }
+    }
+
+    fn update_yield(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Makes:");
+            ui.add(egui::TextEdit::singleline(&mut self.yield_quantity).desired_width(50.0));
+            units_combo_box(
+                ui,
+                ("compound yield units", self.ingredient.id),
+                &mut self.yield_units,
+            );
+            if ui
+                .add_enabled(
+                    self.compound.is_some() && is_editable(ui.ctx()),
+                    egui::Button::new("Save"),
+                )
+                .clicked()
+            {
+                if let Some(quantity) = self.parsed_yield(toasts) {
+                    query::set_ingredient_compound(
+                        conn,
+                        self.ingredient.id,
+                        quantity,
+                        self.yield_units,
+                    )
+                    .or_report();
+                    *refresh_self = true;
+                }
+            }
+        });
+    }
+
+    fn update_table(
+        &mut self,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+    ) {
+        let available_height = ui.available_height();
+        egui_extras::TableBuilder::new(ui)
+            .id_salt(("ingredient components table", self.ingredient.id))
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::exact(40.0))
+            .column(egui_extras::Column::exact(40.0))
+            .column(egui_extras::Column::exact(50.0))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(available_height)
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.heading("Name");
+                });
+                header.col(|ui| {
+                    ui.heading("Qty");
+                });
+                header.col(|ui| {
+                    ui.heading("Unit");
+                });
+                header.col(|ui| {
+                    ui.heading("");
+                });
+            })
+            .body(|mut body| {
+                for (component, ingredient) in &self.components {
+                    body.row(20.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label(&ingredient.name);
+                        });
+                        row.col(|ui| {
+                            quantity_label(
+                                ui,
+                                component.quantity,
+                                &component.quantity_units,
+                                settings.fraction_precision,
+                            );
+                        });
+                        row.col(|ui| {
+                            ui.label(
+                                component
+                                    .quantity_units
+                                    .as_ref()
+                                    .map(|c| i18n::units(*c))
+                                    .unwrap_or(""),
+                            );
+                        });
+                        row.col(|ui| {
+                            if ui
+                                .add_enabled(is_editable(ui.ctx()), egui::Button::new("Delete"))
+                                .clicked()
+                            {
+                                query::delete_ingredient_component(
+                                    conn,
+                                    component.id,
+                                    self.ingredient.id,
+                                )
+                                .or_report();
+                                *refresh_self = true;
+                            }
+                        });
+                    });
+                }
+            });
+    }
+
+    fn update_add_component(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+    ) {
+        egui_extras::StripBuilder::new(ui)
+            .size(egui_extras::Size::remainder())
+            .size(egui_extras::Size::exact(50.0))
+            .size(egui_extras::Size::exact(60.0))
+            .size(egui_extras::Size::exact(40.0))
+            .horizontal(|mut strip| {
+                let new = &mut self.new_component;
+                strip.cell(|ui| {
+                    ui.add(
+                        SearchWidget::new(
+                            ("ingredient component search", self.ingredient.id),
+                            &mut new.name,
+                            &mut new.ingredient,
+                            |query| {
+                                query::search_ingredients(
+                                    conn,
+                                    &mut new.cached_ingredient_search,
+                                    query,
+                                )
+                                .unwrap_or_report()
+                            },
+                        )
+                        .hint_text("search for ingredient")
+                        .desired_width(f32::INFINITY),
+                    );
+                });
+                strip.cell(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut new.quantity).hint_text("qty"));
+                });
+                strip.cell(|ui| {
+                    units_combo_box(
+                        ui,
+                        ("new component units", self.ingredient.id),
+                        &mut new.quantity_units,
+                    );
+                });
+                let mut added = false;
+                strip.cell(|ui| {
+                    added = ui
+                        .add_enabled(is_editable(ui.ctx()), egui::Button::new("Add"))
+                        .clicked();
+                });
+
+                if added {
+                    match &self.new_component.ingredient {
+                        None => {
+                            toasts.add(new_error_toast("Couldn't find ingredient"));
+                        }
+                        Some(component) if component.id == self.ingredient.id => {
+                            toasts.add(new_error_toast("A blend can't contain itself"));
+                        }
+                        Some(component)
+                            if query::ingredient_contains(
+                                conn,
+                                component.id,
+                                self.ingredient.id,
+                            )
+                            .or_report()
+                                != Some(false) =>
+                        {
+                            toasts.add(new_error_toast(format!(
+                                "{} is made with {}, so it can't go in it",
+                                component.name, self.ingredient.name
+                            )));
+                        }
+                        Some(component) => {
+                            let component_id = component.id;
+                            let Some(quantity) = quantity_parse(&self.new_component.quantity)
+                            else {
+                                toasts.add(new_error_toast("Quantity must be a number"));
+                                return;
+                            };
+                            if self.compound.is_none() {
+                                let Some(made) = self.parsed_yield(toasts) else {
+                                    return;
+                                };
+                                query::set_ingredient_compound(
+                                    conn,
+                                    self.ingredient.id,
+                                    made,
+                                    self.yield_units,
+                                )
+                                .or_report();
+                            }
+                            query::add_ingredient_component(
+                                conn,
+                                self.ingredient.id,
+                                component_id,
+                                quantity,
+                                self.new_component.quantity_units,
+                            )
+                            .or_report();
+                            *refresh_self = true;
+                        }
+                    }
+                }
+            });
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
+    ) -> bool {
+        let style = ctx.style();
+        let button_height = (egui::TextStyle::Button.resolve(&style).size
+            + style.spacing.button_padding.y * 2.0)
+            .max(style.spacing.interact_size.y);
+        let spacing = style.spacing.item_spacing.y;
+        let separator_height = 6.0;
+
+        let yield_height = button_height + spacing + separator_height;
+        let table_height = (20.0 + spacing) * (self.components.len() + 1) as f32;
+        let add_height = button_height + spacing + separator_height + 2.0;
+
+        let mut open = true;
+        let mut refresh_self = false;
+        egui::Window::new(format!("{} - Blend", &self.ingredient.name))
+            .id(egui::Id::new(("ingredient components", self.ingredient.id)))
+            .default_height(yield_height + table_height + add_height)
+            .default_width(400.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui_extras::StripBuilder::new(ui)
+                    .size(egui_extras::Size::exact(yield_height))
+                    .size(egui_extras::Size::remainder())
+                    .size(egui_extras::Size::exact(add_height))
+                    .vertical(|mut strip| {
+                        strip.cell(|ui| {
+                            self.update_yield(conn, toasts, ui, &mut refresh_self);
+                            ui.separator();
+                        });
+                        strip.cell(|ui| {
+                            egui::ScrollArea::vertical().show(ui, |ui| {
+                                self.update_table(conn, settings, ui, &mut refresh_self);
+                            });
+                        });
+                        strip.cell(|ui| {
+                            ui.separator();
+                            self.update_add_component(conn, toasts, ui, &mut refresh_self);
+                        });
+                    });
+            });
+
+        if refresh_self {
+            *self = Self::new(conn, self.ingredient.clone());
+        }
+
+        !open
+    }
+}
@@ -0,0 +1,122 @@
+use super::errors::QueryResultExt as _;
+use super::is_editable;
+use recipe_manager::database;
+use recipe_manager::database::models::{Ingredient, IngredientId};
+use recipe_manager::duplicates::{likely_duplicates, DuplicateReason};
+use recipe_manager::query;
+
+pub enum UpdateEvent {
+    Closed,
+    IngredientReplaced,
+    IngredientDeleted(IngredientId),
+}
+
+struct Suggestion {
+    a: Ingredient,
+    b: Ingredient,
+    reason: DuplicateReason,
+}
+
+/// Suggests ingredients to merge because their names are nearly the same.
+pub struct IngredientDuplicatesWindow {
+    suggestions: Vec<Suggestion>,
+}
+
+impl IngredientDuplicatesWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        let ingredients = query::get_all_ingredients(conn).unwrap_or_report();
+        let names: Vec<_> = ingredients.iter().map(|i| i.name.as_str()).collect();
+        let suggestions = likely_duplicates(&names)
+            .into_iter()
+            .map(|(a, b, reason)| Suggestion {
+                a: ingredients[a].clone(),
+                b: ingredients[b].clone(),
+                reason,
+            })
+            .collect();
+        Self { suggestions }
+    }
+
+    /// Replaces `remove` with `keep` in every recipe and blend, then deletes it.
+    fn merge(
+        &mut self,
+        conn: &mut database::Connection,
+        keep: IngredientId,
+        remove: IngredientId,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        if query::merge_ingredients(conn, keep, &[remove])
+            .or_report()
+            .is_none()
+        {
+            return;
+        }
+        events.push(UpdateEvent::IngredientReplaced);
+        events.push(UpdateEvent::IngredientDeleted(remove));
+        self.suggestions
+            .retain(|s| s.a.id != remove && s.b.id != remove);
+    }
+
+    fn update_suggestions(
+        &mut self,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        let mut merge = None;
+        let mut ignore = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, s) in self.suggestions.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:?} and {:?}", s.a.name, s.b.name));
+                    ui.weak(format!("({})", s.reason));
+                });
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+                        if ui.button(format!("Keep {:?}", s.a.name)).clicked() {
+                            merge = Some((s.a.id, s.b.id));
+                        }
+                        if ui.button(format!("Keep {:?}", s.b.name)).clicked() {
+                            merge = Some((s.b.id, s.a.id));
+                        }
+                    });
+                    if ui.button("Not The Same").clicked() {
+                        ignore = Some(i);
+                    }
+                });
+                ui.separator();
+            }
+        });
+        if let Some((keep, remove)) = merge {
+            self.merge(conn, keep, remove, events);
+        }
+        if let Some(i) = ignore {
+            self.suggestions.remove(i);
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let mut open = true;
+        egui::Window::new("Duplicate Ingredients")
+            .open(&mut open)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                if self.suggestions.is_empty() {
+                    ui.label("No ingredients look like duplicates.");
+                } else {
+                    ui.label("Merging replaces the other ingredient everywhere and deletes it.");
+                    ui.separator();
+                    self.update_suggestions(conn, ui, &mut events);
+                }
+            });
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+}
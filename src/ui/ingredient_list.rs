@@ -1,56 +1,307 @@
+use super::errors::QueryResultExt as _;
 use super::{
-    ingredient_calories::IngredientCaloriesWindow, query, search::SearchWidget,
-    PressedEnterExt as _,
+    i18n::{self, tr},
+    ingredient_calories::IngredientCaloriesWindow,
+    ingredient_components::IngredientComponentsWindow,
+    is_editable, new_error_toast,
+    search::SearchWidget,
+    toast_options, EditToggleExt as _, PressedEnterExt as _,
 };
-use crate::database;
-use crate::database::models::{Ingredient, IngredientHandle, IngredientId};
-use std::collections::HashMap;
+use recipe_manager::database;
+use recipe_manager::database::models::{
+    Ingredient, IngredientCategoryId, IngredientHandle, IngredientId, IngredientMeasurement,
+};
+use recipe_manager::diet::DietFlag;
+use recipe_manager::query;
+use std::collections::{HashMap, HashSet};
 
 struct IngredientBeingEdited {
     id: IngredientId,
     name: String,
     category: String,
-    cached_category_search: Option<query::CachedQuery<()>>,
+    saved: (String, String),
+    cached_category_search: Option<query::CachedQuery<IngredientCategoryId>>,
 }
 
 impl IngredientBeingEdited {
-    fn new(ingredient: Ingredient) -> Self {
+    fn new(ingredient: Ingredient, categories: &HashMap<IngredientCategoryId, String>) -> Self {
+        let category = ingredient
+            .category_id
+            .and_then(|c| categories.get(&c).cloned())
+            .unwrap_or_default();
         Self {
             id: ingredient.id,
+            saved: (ingredient.name.clone(), category.clone()),
             name: ingredient.name,
-            category: ingredient.category.unwrap_or_default(),
+            category,
             cached_category_search: None,
         }
     }
+
+    fn is_changed(&self) -> bool {
+        self.name != self.saved.0 || self.category != self.saved.1
+    }
+}
+
+/// The windows opened for individual ingredients, by the ingredient they're for.
+#[derive(Default)]
+pub struct IngredientWindows {
+    pub calories: HashMap<IngredientId, IngredientCaloriesWindow>,
+    pub components: HashMap<IngredientId, IngredientComponentsWindow>,
+}
+
+#[derive(Default)]
+struct BatchCategory {
+    category: String,
+    cached_category_search: Option<query::CachedQuery<IngredientCategoryId>>,
+    confirming: bool,
 }
 
 pub enum UpdateEvent {
     Closed,
-    IngredientEdited,
+    IngredientsEdited(Vec<IngredientId>),
     IngredientDeleted(IngredientId),
 }
 
+/// How many ingredients are shown at once.
+const PAGE_SIZE: usize = 50;
+
+/// The ingredients matching the search on the page being shown.
+struct IngredientPage {
+    search: String,
+    page: usize,
+    /// How many ingredients match the search, on all the pages.
+    count: usize,
+    ingredients: Vec<Ingredient>,
+}
+
+impl IngredientPage {
+    fn new(conn: &mut database::Connection, search: &str, page: usize) -> Self {
+        let count = query::count_ingredients(conn, search).unwrap_or_report();
+        // The page may be past the end after ingredients were deleted.
+        let page = page.min(count.saturating_sub(1) / PAGE_SIZE);
+        let ingredients =
+            query::get_ingredients_page(conn, search, (page * PAGE_SIZE) as i64, PAGE_SIZE as i64)
+                .unwrap_or_report();
+        Self {
+            search: search.into(),
+            page,
+            count,
+            ingredients,
+        }
+    }
+
+    fn num_pages(&self) -> usize {
+        self.count.div_ceil(PAGE_SIZE)
+    }
+
+    /// Like "312 ingredients, showing 1–50".
+    fn description(&self) -> String {
+        if self.count == 0 {
+            return tr("No ingredients").into();
+        }
+        let first = self.page * PAGE_SIZE + 1;
+        let last = first + self.ingredients.len() - 1;
+        let description = if self.count == 1 {
+            tr("{count} ingredient, showing {first}–{last}")
+        } else {
+            tr("{count} ingredients, showing {first}–{last}")
+        };
+        description
+            .replace("{count}", &self.count.to_string())
+            .replace("{first}", &first.to_string())
+            .replace("{last}", &last.to_string())
+    }
+}
+
 pub struct IngredientListWindow {
-    all_ingredients: Option<query::CachedQuery<Ingredient>>,
+    page: usize,
+    listing: Option<IngredientPage>,
+    categories: HashMap<IngredientCategoryId, String>,
     edit_mode: bool,
     new_ingredient_name: String,
     ingredient_being_edited: Option<IngredientBeingEdited>,
     name_search: String,
+    selected: HashSet<IngredientId>,
+    batch_category: BatchCategory,
+    delete_selected: bool,
 }
 
 impl IngredientListWindow {
-    pub fn new_with_args(edit_mode: bool, name_search: String) -> Self {
+    pub fn new_with_args(
+        conn: &mut database::Connection,
+        edit_mode: bool,
+        name_search: String,
+        page: usize,
+    ) -> Self {
         Self {
-            all_ingredients: None,
+            page,
+            listing: None,
+            categories: query::get_ingredient_category_names(conn).unwrap_or_report(),
             edit_mode,
             new_ingredient_name: String::new(),
             ingredient_being_edited: None,
             name_search,
+            selected: HashSet::new(),
+            batch_category: BatchCategory::default(),
+            delete_selected: false,
         }
     }
 
-    pub fn new() -> Self {
-        Self::new_with_args(false, String::new())
+    pub fn new(conn: &mut database::Connection) -> Self {
+        Self::new_with_args(conn, false, String::new(), 0)
+    }
+
+    fn update_staple(
+        ingredient: &Ingredient,
+        conn: &mut database::Connection,
+        row: &mut egui_extras::TableRow<'_, '_>,
+        refresh_self: &mut bool,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        row.col(|ui| {
+            let mut staple = ingredient.staple;
+            if ui
+                .add_enabled(
+                    is_editable(ui.ctx()),
+                    egui::Checkbox::without_text(&mut staple),
+                )
+                .on_hover_text(tr("Leave off shopping lists unless staples are included"))
+                .changed()
+            {
+                query::set_ingredient_staple(conn, ingredient.id, staple).or_report();
+                *refresh_self = true;
+                events.push(UpdateEvent::IngredientsEdited(vec![ingredient.id]));
+            }
+        });
+    }
+
+    fn update_season(
+        ingredient: &Ingredient,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        ui.menu_button(tr("Season"), |ui| {
+            ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+                let mut season = ingredient.season;
+                for month in 1..=12u8 {
+                    let bit = 1 << (month - 1);
+                    let mut in_season = season & bit != 0;
+                    let name = chrono::Month::try_from(month).map_or("", |m| m.name());
+                    if ui.checkbox(&mut in_season, name).changed() {
+                        season ^= bit;
+                    }
+                }
+                if season != ingredient.season {
+                    query::set_ingredient_season(conn, ingredient.id, season).or_report();
+                    *refresh_self = true;
+                    events.push(UpdateEvent::IngredientsEdited(vec![ingredient.id]));
+                }
+            });
+        })
+        .response
+        .on_hover_text(tr("The months it's in season"));
+    }
+
+    fn update_density(
+        ingredient: &Ingredient,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        ui.menu_button(tr("Density"), |ui| {
+            ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+                let mut density = ingredient.density.unwrap_or(0.0);
+                let changed = ui
+                    .add(
+                        egui::DragValue::new(&mut density)
+                            .speed(0.01)
+                            .range(0.0..=25.0)
+                            .suffix(" g/mL"),
+                    )
+                    .changed();
+                if changed {
+                    let density = (density > 0.0).then_some(density);
+                    query::set_ingredient_density(conn, ingredient.id, density).or_report();
+                    *refresh_self = true;
+                    events.push(UpdateEvent::IngredientsEdited(vec![ingredient.id]));
+                }
+            });
+        })
+        .response
+        .on_hover_text(tr(
+            "How much a milliliter weighs, for converting cups to grams and back",
+        ));
+    }
+
+    fn update_price(
+        ingredient: &Ingredient,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        ui.menu_button(tr("Price"), |ui| {
+            ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+                let mut price = ingredient.price.unwrap_or(0.0);
+                let mut units = ingredient.price_units;
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut price)
+                            .speed(0.01)
+                            .range(0.0..=10_000.0)
+                            .max_decimals(2),
+                    );
+                    ui.label(tr("per"));
+                    egui::ComboBox::from_id_salt(("ingredient price units", ingredient.id))
+                        .selected_text(units.map_or(tr("item"), i18n::units))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut units, None, tr("item"));
+                            for m in IngredientMeasurement::iter() {
+                                ui.selectable_value(&mut units, Some(m), i18n::units(m));
+                            }
+                        });
+                });
+                if price != ingredient.price.unwrap_or(0.0) || units != ingredient.price_units {
+                    let price = (price > 0.0).then_some(price);
+                    query::set_ingredient_price(conn, ingredient.id, price, units).or_report();
+                    *refresh_self = true;
+                    events.push(UpdateEvent::IngredientsEdited(vec![ingredient.id]));
+                }
+            });
+        })
+        .response
+        .on_hover_text(tr("What it costs, for working out what recipes cost"));
+    }
+
+    fn update_diet(
+        ingredient: &Ingredient,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        ui.menu_button(tr("Diet"), |ui| {
+            ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+                let mut diet = ingredient.diet;
+                for flag in DietFlag::iter() {
+                    let mut set = flag.is_set(diet);
+                    if ui.checkbox(&mut set, flag.to_string()).changed() {
+                        flag.set(&mut diet, set);
+                    }
+                }
+                if diet != ingredient.diet {
+                    query::set_ingredient_diet(conn, ingredient.id, diet).or_report();
+                    *refresh_self = true;
+                    events.push(UpdateEvent::IngredientsEdited(vec![ingredient.id]));
+                }
+            });
+        })
+        .response
+        .on_hover_text(tr("Allergens it contains and diets it's safe for"));
     }
 
     fn update_ingredient_editing(
@@ -76,17 +327,24 @@ impl IngredientListWindow {
             ui.add(
                 SearchWidget::new(i.id, &mut i.category, &mut unused, |query| {
                     query::search_ingredient_categories(conn, &mut i.cached_category_search, query)
+                        .unwrap_or_report()
                 })
-                .hint_text("search for category"),
+                .hint_text(tr("search for category")),
             );
         });
+        Self::update_staple(ingredient, conn, row, refresh_self, events);
+        let mut cancelled = false;
         row.col(|ui| {
-            if ui.button("Save").clicked() {
-                query::update_ingredient(conn, i.id, &i.name, &i.category);
+            if ui.button(tr("Save")).clicked() {
+                query::update_ingredient(conn, i.id, &i.name, &i.category).or_report();
                 *refresh_self = true;
-                events.push(UpdateEvent::IngredientEdited);
+                events.push(UpdateEvent::IngredientsEdited(vec![i.id]));
             }
+            cancelled = ui.button(tr("Cancel")).clicked();
         });
+        if cancelled {
+            self.ingredient_being_edited = None;
+        }
         true
     }
 
@@ -95,42 +353,57 @@ impl IngredientListWindow {
         ingredient: &Ingredient,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
-        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+        ingredient_windows: &mut IngredientWindows,
         mut search_for_ingredient: impl FnMut(&mut database::Connection, Vec<IngredientHandle>),
         row: &mut egui_extras::TableRow<'_, '_>,
         events: &mut Vec<UpdateEvent>,
         refresh_self: &mut bool,
     ) {
         row.col(|ui| {
+            if self.edit_mode {
+                let mut selected = self.selected.contains(&ingredient.id);
+                if ui.checkbox(&mut selected, "").changed() {
+                    if selected {
+                        self.selected.insert(ingredient.id);
+                    } else {
+                        self.selected.remove(&ingredient.id);
+                    }
+                    self.batch_category.confirming = false;
+                }
+            }
             ui.label(&ingredient.name);
         });
         row.col(|ui| {
-            ui.label(ingredient.category.as_deref().unwrap_or(""));
+            let category = ingredient.category_id.and_then(|c| self.categories.get(&c));
+            ui.label(category.map(|c| c.as_str()).unwrap_or(""));
         });
+        Self::update_staple(ingredient, conn, row, refresh_self, events);
 
-        let mut calories_shown = ingredient_calories_windows.contains_key(&ingredient.id);
+        let mut calories_shown = ingredient_windows.calories.contains_key(&ingredient.id);
+        let mut components_shown = ingredient_windows.components.contains_key(&ingredient.id);
 
         if self.edit_mode {
             row.col(|ui| {
-                if ui.button("Edit").clicked() {
-                    self.ingredient_being_edited =
-                        Some(IngredientBeingEdited::new(ingredient.clone()))
+                if ui.button(tr("Edit")).clicked() {
+                    self.ingredient_being_edited = Some(IngredientBeingEdited::new(
+                        ingredient.clone(),
+                        &self.categories,
+                    ))
                 }
-                if ui.button("Delete").clicked() {
-                    if query::delete_ingredient(conn, ingredient.id) {
+                if ui.button(tr("Delete")).clicked() {
+                    if query::delete_ingredient(conn, ingredient.id).unwrap_or_report() {
+                        self.selected.remove(&ingredient.id);
                         *refresh_self = true;
                         events.push(UpdateEvent::IngredientDeleted(ingredient.id));
                         calories_shown = false;
+                        components_shown = false;
                     } else {
                         toasts.add(egui_toast::Toast {
                             text: "Couldn't delete ingredient, \
                                     it is still being used by recipes"
                                 .into(),
                             kind: egui_toast::ToastKind::Error,
-                            options: egui_toast::ToastOptions::default()
-                                .duration_in_seconds(3.0)
-                                .show_progress(false)
-                                .show_icon(true),
+                            options: toast_options(3.0).show_progress(false),
                             ..Default::default()
                         });
                     }
@@ -138,7 +411,7 @@ impl IngredientListWindow {
             });
         } else {
             row.col(|ui| {
-                if ui.button("Search").clicked() {
+                if ui.button(tr("Search")).clicked() {
                     search_for_ingredient(
                         conn,
                         vec![IngredientHandle {
@@ -147,16 +420,29 @@ impl IngredientListWindow {
                         }],
                     );
                 }
-                ui.toggle_value(&mut calories_shown, "Calories");
+                ui.toggle_value(&mut calories_shown, tr("Calories"));
+                ui.toggle_value(&mut components_shown, tr("Blend"));
+                Self::update_season(ingredient, conn, ui, refresh_self, events);
+                Self::update_diet(ingredient, conn, ui, refresh_self, events);
+                Self::update_density(ingredient, conn, ui, refresh_self, events);
+                Self::update_price(ingredient, conn, ui, refresh_self, events);
             });
         }
-        if calories_shown && !ingredient_calories_windows.contains_key(&ingredient.id) {
-            ingredient_calories_windows.insert(
+        if calories_shown && !ingredient_windows.calories.contains_key(&ingredient.id) {
+            ingredient_windows.calories.insert(
                 ingredient.id,
                 IngredientCaloriesWindow::new(conn, ingredient.to_handle()),
             );
         } else if !calories_shown {
-            ingredient_calories_windows.remove(&ingredient.id);
+            ingredient_windows.calories.remove(&ingredient.id);
+        }
+        if components_shown && !ingredient_windows.components.contains_key(&ingredient.id) {
+            ingredient_windows.components.insert(
+                ingredient.id,
+                IngredientComponentsWindow::new(conn, ingredient.to_handle()),
+            );
+        } else if !components_shown {
+            ingredient_windows.components.remove(&ingredient.id);
         }
     }
 
@@ -164,52 +450,79 @@ impl IngredientListWindow {
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
-        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+        ingredient_windows: &mut IngredientWindows,
         mut search_for_ingredient: impl FnMut(&mut database::Connection, Vec<IngredientHandle>),
         refresh_self: &mut bool,
-        body: &mut egui_extras::TableBody<'_>,
+        body: egui_extras::TableBody<'_>,
     ) -> Vec<UpdateEvent> {
         let mut events = vec![];
 
-        query::search_ingredients(conn, &mut self.all_ingredients, &self.name_search);
-        let all_ingredients = std::mem::take(&mut self.all_ingredients);
-        let all_ingredients_iter = all_ingredients
+        let listing = self.listing(conn);
+        let ingredients = std::mem::take(&mut listing.ingredients);
+        body.rows(20.0, ingredients.len(), |mut row| {
+            let ingredient = &ingredients[row.index()];
+            if self.update_ingredient_editing(ingredient, conn, &mut row, refresh_self, &mut events)
+            {
+                return;
+            }
+            self.update_ingredient_row(
+                ingredient,
+                conn,
+                toasts,
+                ingredient_windows,
+                &mut search_for_ingredient,
+                &mut row,
+                &mut events,
+                refresh_self,
+            );
+        });
+        if let Some(listing) = &mut self.listing {
+            listing.ingredients = ingredients;
+        }
+        events
+    }
+
+    /// The page being shown, read again if the search or page changed.
+    fn listing(&mut self, conn: &mut database::Connection) -> &mut IngredientPage {
+        if self
+            .listing
             .as_ref()
-            .map(|c| c.results.iter())
-            .into_iter()
-            .flatten();
-        for (ingredient, _) in all_ingredients_iter {
-            body.row(20.0, |mut row| {
-                if self.update_ingredient_editing(
-                    ingredient,
-                    conn,
-                    &mut row,
-                    refresh_self,
-                    &mut events,
-                ) {
-                    return;
+            .is_none_or(|l| l.search != self.name_search || l.page != self.page)
+        {
+            let listing = IngredientPage::new(conn, &self.name_search, self.page);
+            self.page = listing.page;
+            self.listing = Some(listing);
+        }
+        self.listing.as_mut().unwrap()
+    }
+
+    fn update_page_controls(&mut self, conn: &mut database::Connection, ui: &mut egui::Ui) {
+        let listing = self.listing(conn);
+        let (description, num_pages) = (listing.description(), listing.num_pages());
+        ui.horizontal(|ui| {
+            ui.label(description);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(self.page + 1 < num_pages, egui::Button::new(tr("Next ⏵")))
+                    .clicked()
+                {
+                    self.page += 1;
+                }
+                if ui
+                    .add_enabled(self.page > 0, egui::Button::new(tr("⏴ Previous")))
+                    .clicked()
+                {
+                    self.page -= 1;
                 }
-                self.update_ingredient_row(
-                    ingredient,
-                    conn,
-                    toasts,
-                    ingredient_calories_windows,
-                    &mut search_for_ingredient,
-                    &mut row,
-                    &mut events,
-                    refresh_self,
-                );
             });
-        }
-        self.all_ingredients = all_ingredients;
-        events
+        });
     }
 
     fn update_table(
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
-        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+        ingredient_windows: &mut IngredientWindows,
         search_for_ingredient: impl FnMut(&mut database::Connection, Vec<IngredientHandle>),
         refresh_self: &mut bool,
         ui: &mut egui::Ui,
@@ -223,28 +536,32 @@ impl IngredientListWindow {
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
             .column(egui_extras::Column::remainder())
             .column(egui_extras::Column::remainder())
-            .column(egui_extras::Column::exact(110.0))
+            .column(egui_extras::Column::exact(50.0))
+            .column(egui_extras::Column::exact(380.0))
             .min_scrolled_height(0.0)
             .max_scroll_height(available_height)
             .header(20.0, |mut header| {
                 header.col(|ui| {
-                    ui.heading("Name");
+                    ui.heading(tr("Name"));
                 });
                 header.col(|ui| {
-                    ui.heading("Category");
+                    ui.heading(tr("Category"));
+                });
+                header.col(|ui| {
+                    ui.heading(tr("Staple"));
                 });
                 header.col(|ui| {
                     ui.heading("");
                 });
             })
-            .body(|mut body| {
+            .body(|body| {
                 events = self.update_listing(
                     conn,
                     toasts,
-                    ingredient_calories_windows,
+                    ingredient_windows,
                     search_for_ingredient,
                     refresh_self,
-                    &mut body,
+                    body,
                 );
             });
         events
@@ -263,7 +580,7 @@ impl IngredientListWindow {
                 .size(egui_extras::Size::exact(35.0))
                 .horizontal(|mut strip| {
                     strip.cell(|ui| {
-                        ui.toggle_value(&mut self.edit_mode, "Edit");
+                        ui.edit_toggle(&mut self.edit_mode);
                     });
                     let mut added = false;
                     strip.cell(|ui| {
@@ -276,16 +593,112 @@ impl IngredientListWindow {
                     });
                     let e = !self.new_ingredient_name.is_empty();
                     strip.cell(|ui| {
-                        added |= ui.add_enabled(e, egui::Button::new("Add")).clicked();
+                        added |= ui.add_enabled(e, egui::Button::new(tr("Add"))).clicked();
                     });
-                    if added && e {
-                        query::add_ingredient(conn, &self.new_ingredient_name);
+                    if added
+                        && e
+                        && query::add_ingredient(conn, &self.new_ingredient_name)
+                            .or_report()
+                            .is_some()
+                    {
                         self.new_ingredient_name = "".into();
                         *refresh_self = true;
                     }
                 });
         } else {
-            ui.toggle_value(&mut self.edit_mode, "Edit");
+            ui.edit_toggle(&mut self.edit_mode);
+        }
+    }
+
+    fn update_batch_category(
+        &mut self,
+        conn: &mut database::Connection,
+        refresh_self: &mut bool,
+        events: &mut Vec<UpdateEvent>,
+        ui: &mut egui::Ui,
+    ) {
+        let b = &mut self.batch_category;
+        let count = self.selected.len();
+        ui.horizontal(|ui| {
+            if b.confirming {
+                let category = b.category.trim();
+                if category.is_empty() {
+                    ui.label(
+                        tr("Remove the category from {count} ingredients?")
+                            .replace("{count}", &count.to_string()),
+                    );
+                } else {
+                    ui.label(
+                        tr("Set the category of {count} ingredients to {category}?")
+                            .replace("{count}", &count.to_string())
+                            .replace("{category}", &format!("{category:?}")),
+                    );
+                }
+                if ui.button(tr("Yes")).clicked() {
+                    let ids: Vec<_> = self.selected.drain().collect();
+                    query::set_ingredients_category(conn, &ids, category).unwrap_or_report();
+                    b.confirming = false;
+                    *refresh_self = true;
+                    events.push(UpdateEvent::IngredientsEdited(ids));
+                }
+                if ui.button(tr("No")).clicked() {
+                    b.confirming = false;
+                }
+            } else {
+                ui.label(tr("{count} selected").replace("{count}", &count.to_string()));
+                let mut unused = None;
+                ui.add(
+                    SearchWidget::new(
+                        "ingredient batch category",
+                        &mut b.category,
+                        &mut unused,
+                        |query| {
+                            query::search_ingredient_categories(
+                                conn,
+                                &mut b.cached_category_search,
+                                query,
+                            )
+                            .unwrap_or_report()
+                        },
+                    )
+                    .hint_text(tr("search for category"))
+                    .desired_width(ui.available_width() - 160.0),
+                );
+                if ui.button(tr("Set Category")).clicked() {
+                    b.confirming = true;
+                }
+                if ui.button(tr("Clear")).clicked() {
+                    self.selected.clear();
+                }
+            }
+        });
+    }
+
+    fn delete_selected_ingredients(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ingredient_windows: &mut IngredientWindows,
+        refresh_self: &mut bool,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        let mut still_used = 0;
+        for id in std::mem::take(&mut self.selected) {
+            if query::delete_ingredient(conn, id).unwrap_or_report() {
+                ingredient_windows.calories.remove(&id);
+                ingredient_windows.components.remove(&id);
+                events.push(UpdateEvent::IngredientDeleted(id));
+                *refresh_self = true;
+            } else {
+                self.selected.insert(id);
+                still_used += 1;
+            }
+        }
+        if still_used > 0 {
+            toasts.add(new_error_toast(
+                tr("Couldn't delete {count} ingredients, they are still being used by recipes")
+                    .replace("{count}", &still_used.to_string()),
+            ));
         }
     }
 
@@ -293,7 +706,7 @@ impl IngredientListWindow {
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
-        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+        ingredient_windows: &mut IngredientWindows,
         search_for_ingredient: impl FnMut(&mut database::Connection, Vec<IngredientHandle>),
         ctx: &egui::Context,
     ) -> Vec<UpdateEvent> {
@@ -305,52 +718,89 @@ impl IngredientListWindow {
         let separator_height = 6.0;
 
         let add_height = button_height + spacing + separator_height + 2.0;
-        let search_height = button_height + spacing + separator_height + 2.0;
+        let search_height = (button_height + spacing) * 2.0 + separator_height + 2.0;
+        let show_batch = self.edit_mode && !self.selected.is_empty();
+        let batch_height = if show_batch {
+            button_height + spacing + separator_height + 2.0
+        } else {
+            0.0
+        };
 
         let mut open = true;
         let mut events = vec![];
         let mut refresh_self = false;
-        egui::Window::new("Ingredients")
-            .open(&mut open)
-            .show(ctx, |ui| {
-                egui_extras::StripBuilder::new(ui)
-                    .size(egui_extras::Size::exact(search_height))
-                    .size(egui_extras::Size::remainder())
-                    .size(egui_extras::Size::exact(add_height))
-                    .vertical(|mut strip| {
-                        strip.cell(|ui| {
-                            ui.add(
-                                egui::TextEdit::singleline(&mut self.name_search)
-                                    .hint_text("search by name")
-                                    .desired_width(f32::INFINITY),
-                            );
-                            ui.separator();
-                        });
-                        strip.cell(|ui| {
-                            egui::ScrollArea::vertical().show(ui, |ui| {
-                                events.extend(self.update_table(
-                                    conn,
-                                    toasts,
-                                    ingredient_calories_windows,
-                                    search_for_ingredient,
-                                    &mut refresh_self,
-                                    ui,
-                                ));
-                            });
+        let unsaved = self
+            .ingredient_being_edited
+            .as_ref()
+            .is_some_and(|i| i.is_changed());
+        egui::Window::new(if unsaved {
+            format!("{}*", tr("Ingredients"))
+        } else {
+            tr("Ingredients").into()
+        })
+        .id(egui::Id::new("Ingredients"))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            egui_extras::StripBuilder::new(ui)
+                .size(egui_extras::Size::exact(search_height))
+                .size(egui_extras::Size::remainder())
+                .size(egui_extras::Size::exact(batch_height))
+                .size(egui_extras::Size::exact(add_height))
+                .vertical(|mut strip| {
+                    strip.cell(|ui| {
+                        let search = ui.add(
+                            egui::TextEdit::singleline(&mut self.name_search)
+                                .hint_text(tr("search by name"))
+                                .desired_width(f32::INFINITY),
+                        );
+                        if search.changed() {
+                            self.page = 0;
+                        }
+                        self.update_page_controls(conn, ui);
+                        ui.separator();
+                    });
+                    strip.cell(|ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            events.extend(self.update_table(
+                                conn,
+                                toasts,
+                                ingredient_windows,
+                                search_for_ingredient,
+                                &mut refresh_self,
+                                ui,
+                            ));
                         });
-                        strip.cell(|ui| {
+                    });
+                    strip.cell(|ui| {
+                        if show_batch {
                             ui.separator();
-                            self.update_add_ingredient(conn, &mut refresh_self, ui);
-                        })
+                            self.update_batch_category(conn, &mut refresh_self, &mut events, ui);
+                        }
                     });
-            });
+                    strip.cell(|ui| {
+                        ui.separator();
+                        self.update_add_ingredient(conn, &mut refresh_self, ui);
+                    })
+                });
+        });
+
+        if std::mem::take(&mut self.delete_selected) && self.edit_mode {
+            self.delete_selected_ingredients(
+                conn,
+                toasts,
+                ingredient_windows,
+                &mut refresh_self,
+                &mut events,
+            );
+        }
 
         if !self.edit_mode {
             self.ingredient_being_edited = None;
+            self.selected.clear();
         }
 
         if refresh_self {
-            *self = Self::new_with_args(self.edit_mode, std::mem::take(&mut self.name_search));
+            self.refresh(conn);
         }
         if !open {
             events.push(UpdateEvent::Closed);
@@ -358,7 +808,32 @@ impl IngredientListWindow {
         events
     }
 
-    pub fn ingredient_deleted(&mut self) {
-        *self = Self::new_with_args(self.edit_mode, std::mem::take(&mut self.name_search));
+    fn refresh(&mut self, conn: &mut database::Connection) {
+        let selected = std::mem::take(&mut self.selected);
+        *self = Self::new_with_args(
+            conn,
+            self.edit_mode,
+            std::mem::take(&mut self.name_search),
+            self.page,
+        );
+        self.selected = selected;
+    }
+
+    pub fn ingredient_deleted(&mut self, conn: &mut database::Connection) {
+        self.selected.clear();
+        self.refresh(conn);
+    }
+
+    /// Deletes the selected ingredients the next time the window updates.
+    pub fn delete_selected(&mut self) {
+        self.delete_selected = true;
+    }
+
+    pub fn ingredient_created(&mut self, conn: &mut database::Connection) {
+        self.refresh(conn);
+    }
+
+    pub fn categories_changed(&mut self, conn: &mut database::Connection) {
+        self.refresh(conn);
     }
 }
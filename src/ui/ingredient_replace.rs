@@ -1,8 +1,11 @@
-use crate::database::models::{Ingredient, IngredientId};
-use crate::{
-    database,
-    ui::{new_error_toast, query, search::SearchWidget},
+use crate::ui::{
+    errors::QueryResultExt as _,
+    is_editable, new_error_toast,
+    search::SearchWidget,
+    undo::{self, UndoAction},
 };
+use recipe_manager::database::models::{Ingredient, IngredientId};
+use recipe_manager::{database, query};
 
 pub enum UpdateEvent {
     Closed,
@@ -65,6 +68,7 @@ impl IngredientReplaceWindow {
                                             &mut self.remove_cached_query,
                                             query,
                                         )
+                                        .unwrap_or_report()
                                     },
                                 )
                                 .desired_width(f32::INFINITY),
@@ -85,6 +89,7 @@ impl IngredientReplaceWindow {
                                             &mut self.fill_cached_query,
                                             query,
                                         )
+                                        .unwrap_or_report()
                                     },
                                 )
                                 .desired_width(f32::INFINITY),
@@ -94,15 +99,43 @@ impl IngredientReplaceWindow {
                             ui.checkbox(&mut self.delete, "and delete");
                         });
                         strip.cell(|ui| {
-                            if ui.button("Execute").clicked() {
+                            if ui
+                                .add_enabled(is_editable(ui.ctx()), egui::Button::new("Execute"))
+                                .clicked()
+                            {
                                 match (&self.remove, &self.fill) {
                                     (Some(remove), Some(fill)) => {
-                                        let num_replaced =
-                                            query::replace_ingredient(conn, remove.id, fill.id);
+                                        let replaced =
+                                            query::get_ingredient_references(conn, remove.id)
+                                                .and_then(|references| {
+                                                    let num_replaced = query::replace_ingredient(
+                                                        conn, remove.id, fill.id,
+                                                    )?;
+                                                    Ok((references, num_replaced))
+                                                });
+                                        let Some(((usages, components), num_replaced)) =
+                                            replaced.or_report()
+                                        else {
+                                            return;
+                                        };
                                         events.push(UpdateEvent::IngredientReplaced);
                                         if self.delete {
-                                            query::delete_ingredient(conn, remove.id);
+                                            query::delete_ingredient(conn, remove.id).or_report();
                                             events.push(UpdateEvent::IngredientDeleted(remove.id));
+                                        } else {
+                                            undo::add_undo_toast(
+                                                ctx,
+                                                toasts,
+                                                format!(
+                                                    "Replaced {} with {} in {num_replaced} recipes",
+                                                    remove.name, fill.name
+                                                ),
+                                                UndoAction::ReplacedIngredient {
+                                                    ingredient: remove.id,
+                                                    usages,
+                                                    components,
+                                                },
+                                            );
                                         }
                                         *self = Self::new();
                                         self.result_text =
@@ -0,0 +1,225 @@
+//! Checks for data which doesn't make sense, like what can be left behind by importing from the
+//! previous version, with a way to fix each problem.
+
+use super::errors::QueryResultExt as _;
+use super::is_editable;
+use recipe_manager::database;
+use recipe_manager::database::models::{IngredientId, IngredientUsageId};
+use recipe_manager::query;
+use std::collections::BTreeMap;
+
+enum Fix {
+    DeleteIngredientUsages(Vec<IngredientUsageId>),
+    DeleteCalendarEntries(Vec<chrono::NaiveDate>),
+    MergeIngredients(IngredientId, Vec<IngredientId>),
+    NegateQuantities(Vec<IngredientUsageId>),
+}
+
+impl Fix {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::DeleteIngredientUsages(_) | Self::DeleteCalendarEntries(_) => "Delete",
+            Self::MergeIngredients(..) => "Merge",
+            Self::NegateQuantities(_) => "Make Positive",
+        }
+    }
+
+    fn apply(&self, conn: &mut database::Connection) -> diesel::QueryResult<()> {
+        match self {
+            Self::DeleteIngredientUsages(ids) => query::delete_ingredient_usages(conn, ids),
+            Self::DeleteCalendarEntries(days) => query::delete_calendar_entries(conn, days),
+            Self::MergeIngredients(keep, remove) => query::merge_ingredients(conn, *keep, remove),
+            Self::NegateQuantities(ids) => query::negate_ingredient_usage_quantities(conn, ids),
+        }
+    }
+}
+
+struct Problem {
+    description: String,
+    details: Vec<String>,
+    fix: Fix,
+}
+
+/// Groups of items whose names only differ by case or surrounding whitespace.
+fn duplicate_groups<T>(items: Vec<T>, name: impl Fn(&T) -> &str) -> Vec<Vec<T>> {
+    let mut groups: BTreeMap<String, Vec<T>> = BTreeMap::new();
+    for item in items {
+        groups
+            .entry(name(&item).trim().to_lowercase())
+            .or_default()
+            .push(item);
+    }
+    groups.into_values().filter(|g| g.len() > 1).collect()
+}
+
+#[test]
+fn duplicate_groups_test() {
+    let names = vec!["Salt", "pepper", "salt ", "Sugar", "Pepper", "SALT"];
+    assert_eq!(
+        duplicate_groups(names, |n| n),
+        vec![vec!["pepper", "Pepper"], vec!["Salt", "salt ", "SALT"]]
+    );
+    assert!(duplicate_groups(vec!["a", "b"], |n| n).is_empty());
+}
+
+fn check(conn: &mut database::Connection) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    let usages = query::get_orphaned_ingredient_usages(conn).unwrap_or_report();
+    if !usages.is_empty() {
+        problems.push(Problem {
+            description: format!(
+                "{} recipe ingredients refer to a missing recipe or ingredient",
+                usages.len()
+            ),
+            details: vec![],
+            fix: Fix::DeleteIngredientUsages(usages),
+        });
+    }
+
+    let days = query::get_orphaned_calendar_entries(conn).unwrap_or_report();
+    if !days.is_empty() {
+        problems.push(Problem {
+            description: format!("{} calendar days refer to a missing recipe", days.len()),
+            details: days
+                .iter()
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .collect(),
+            fix: Fix::DeleteCalendarEntries(days),
+        });
+    }
+
+    let ingredients = query::get_all_ingredients(conn).unwrap_or_report();
+    for group in duplicate_groups(ingredients, |i| &i.name) {
+        // The ingredient entered first is kept.
+        let keep = group[0].id;
+        problems.push(Problem {
+            description: format!(
+                "{:?} is entered {} times with different capitalization",
+                group[0].name,
+                group.len()
+            ),
+            details: group.iter().map(|i| format!("{:?}", i.name)).collect(),
+            fix: Fix::MergeIngredients(keep, group[1..].iter().map(|i| i.id).collect()),
+        });
+    }
+
+    let negative = query::get_negative_ingredient_usages(conn).unwrap_or_report();
+    if !negative.is_empty() {
+        problems.push(Problem {
+            description: format!(
+                "{} recipe ingredients have a negative quantity",
+                negative.len()
+            ),
+            details: negative
+                .iter()
+                .map(|(_, recipe, ingredient)| format!("{ingredient} in {recipe}"))
+                .collect(),
+            fix: Fix::NegateQuantities(negative.into_iter().map(|(id, _, _)| id).collect()),
+        });
+    }
+
+    problems
+}
+
+pub enum UpdateEvent {
+    Closed,
+    Repaired,
+}
+
+pub struct IntegrityWindow {
+    problems: Vec<Problem>,
+}
+
+impl IntegrityWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        Self {
+            problems: check(conn),
+        }
+    }
+
+    fn update_problems(
+        &mut self,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        let mut fixed = false;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, problem) in self.problems.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(&problem.description);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .add_enabled(
+                                is_editable(ui.ctx()),
+                                egui::Button::new(problem.fix.label()),
+                            )
+                            .clicked()
+                            && problem.fix.apply(conn).or_report().is_some()
+                        {
+                            fixed = true;
+                        }
+                    });
+                });
+                if !problem.details.is_empty() {
+                    egui::CollapsingHeader::new("Details")
+                        .id_salt(("integrity problem", i))
+                        .show(ui, |ui| {
+                            for detail in &problem.details {
+                                ui.label(detail);
+                            }
+                        });
+                }
+                ui.separator();
+            }
+        });
+        if fixed {
+            self.problems = check(conn);
+            events.push(UpdateEvent::Repaired);
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let mut open = true;
+        egui::Window::new("Check Database")
+            .open(&mut open)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if self.problems.is_empty() {
+                        ui.label("No problems found");
+                    } else {
+                        ui.label(format!("{} problems found", self.problems.len()));
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Check Again").clicked() {
+                            self.problems = check(conn);
+                        }
+                        if !self.problems.is_empty()
+                            && ui
+                                .add_enabled(is_editable(ui.ctx()), egui::Button::new("Fix All"))
+                                .clicked()
+                        {
+                            for problem in &self.problems {
+                                problem.fix.apply(conn).or_report();
+                            }
+                            self.problems = check(conn);
+                            events.push(UpdateEvent::Repaired);
+                        }
+                    });
+                });
+                ui.separator();
+                self.update_problems(conn, ui, &mut events);
+            });
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+}
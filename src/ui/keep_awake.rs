@@ -0,0 +1,155 @@
+//! Keeping the screen from dimming or locking while cook mode is open, since nobody touches it with
+//! their hands covered in flour.
+
+/// Holds off the screen saver and sleep until it's dropped.
+pub struct KeepAwake {
+    _inhibitor: platform::Inhibitor,
+}
+
+impl KeepAwake {
+    /// None when the system wouldn't do it. Cook mode works the same, the screen may just turn off.
+    pub fn new() -> Option<Self> {
+        match platform::Inhibitor::new() {
+            Ok(inhibitor) => Some(Self {
+                _inhibitor: inhibitor,
+            }),
+            Err(error) => {
+                log::warn!("couldn't keep the screen awake: {error}");
+                None
+            }
+        }
+    }
+}
+
+const REASON: &str = "Cooking a recipe";
+
+#[cfg(target_os = "linux")]
+mod platform {
+    const SERVICE: &str = "org.freedesktop.ScreenSaver";
+    const PATH: &str = "/org/freedesktop/ScreenSaver";
+
+    /// What desktops like GNOME and KDE offer to video players and the like over D-Bus.
+    pub struct Inhibitor {
+        connection: zbus::blocking::Connection,
+        cookie: u32,
+    }
+
+    impl Inhibitor {
+        pub fn new() -> recipe_manager::Result<Self> {
+            let connection = zbus::blocking::Connection::session()?;
+            let reply = connection.call_method(
+                Some(SERVICE),
+                PATH,
+                Some(SERVICE),
+                "Inhibit",
+                &("Recipe Manager", super::REASON),
+            )?;
+            let cookie = reply.body().deserialize()?;
+            Ok(Self { connection, cookie })
+        }
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            // It's also let go when the connection closes, so there's nothing to do if this fails.
+            let _ = self.connection.call_method(
+                Some(SERVICE),
+                PATH,
+                Some(SERVICE),
+                "UnInhibit",
+                &(self.cookie,),
+            );
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows_sys::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+    };
+
+    /// The state belongs to the UI thread, which is the one that creates and drops it.
+    pub struct Inhibitor;
+
+    impl Inhibitor {
+        pub fn new() -> recipe_manager::Result<Self> {
+            // SAFETY: only sets flags on the calling thread.
+            let previous = unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS | ES_DISPLAY_REQUIRED | ES_SYSTEM_REQUIRED)
+            };
+            if previous == 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            Ok(Self)
+        }
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            // SAFETY: as above.
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::base::TCFType as _;
+    use core_foundation::string::{CFString, CFStringRef};
+
+    const ASSERTION_LEVEL_ON: u32 = 255;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMAssertionCreateWithName(
+            assertion_type: CFStringRef,
+            level: u32,
+            name: CFStringRef,
+            id: *mut u32,
+        ) -> i32;
+        fn IOPMAssertionRelease(id: u32) -> i32;
+    }
+
+    /// A power management assertion, like the ones video players take out.
+    pub struct Inhibitor(u32);
+
+    impl Inhibitor {
+        pub fn new() -> recipe_manager::Result<Self> {
+            let assertion_type = CFString::new("PreventUserIdleDisplaySleep");
+            let name = CFString::new(super::REASON);
+            let mut id = 0;
+            // SAFETY: the strings outlive the call, and `id` is only written to.
+            let result = unsafe {
+                IOPMAssertionCreateWithName(
+                    assertion_type.as_concrete_TypeRef(),
+                    ASSERTION_LEVEL_ON,
+                    name.as_concrete_TypeRef(),
+                    &mut id,
+                )
+            };
+            if result != 0 {
+                return Err(format!("IOPMAssertionCreateWithName returned {result}").into());
+            }
+            Ok(Self(id))
+        }
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            // SAFETY: the assertion was created by `new` and is only released once.
+            unsafe { IOPMAssertionRelease(self.0) };
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+mod platform {
+    pub struct Inhibitor;
+
+    impl Inhibitor {
+        pub fn new() -> recipe_manager::Result<Self> {
+            Err("it isn't supported on this system".into())
+        }
+    }
+}
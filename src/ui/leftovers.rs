@@ -0,0 +1,140 @@
+//! What's likely to be left over after shopping, with recipes which would use it up on the free
+//! days later on.
+
+use super::errors::QueryResultExt as _;
+use super::i18n;
+use super::is_editable;
+use super::recipe::quantity_display;
+use super::settings::Settings;
+use recipe_manager::database;
+use recipe_manager::database::models::{Ingredient, IngredientUsage, RecipeHandle, RecipeId};
+use recipe_manager::query;
+use recipe_manager::recipe::leftovers;
+use std::collections::HashSet;
+
+struct Leftover {
+    ingredient: Ingredient,
+    left: f32,
+    /// Recipes using it which aren't already on the calendar.
+    suggestions: Vec<RecipeHandle>,
+}
+
+/// Lists the free days to put the recipe on, returning the day it was put on.
+fn update_schedule(
+    conn: &mut database::Connection,
+    settings: &Settings,
+    free_days: &[chrono::NaiveDate],
+    recipe: &RecipeHandle,
+    ui: &mut egui::Ui,
+) -> Option<chrono::NaiveDate> {
+    let mut scheduled = None;
+    let enabled = is_editable(ui.ctx()) && !free_days.is_empty();
+    ui.add_enabled_ui(enabled, |ui| {
+        ui.menu_button("Schedule", |ui| {
+            for &day in free_days {
+                let label =
+                    i18n::format_date(day, &format!("%A, {}", settings.date_format.without_year()));
+                if ui.button(label).clicked() {
+                    if query::insert_or_update_calendar_entry(conn, day, recipe.id)
+                        .or_report()
+                        .is_some()
+                    {
+                        scheduled = Some(day);
+                    }
+                    ui.close_menu();
+                }
+            }
+        })
+        .response
+        .on_disabled_hover_text("There are no free days left");
+    });
+    scheduled
+}
+
+pub struct LeftoversWindow {
+    leftovers: Vec<Leftover>,
+    /// The days without a recipe yet, from today on.
+    free_days: Vec<chrono::NaiveDate>,
+}
+
+impl LeftoversWindow {
+    /// Only if something is left over.
+    pub fn new(
+        conn: &mut database::Connection,
+        ingredients: &[(IngredientUsage, Ingredient)],
+        scheduled: &HashSet<RecipeId>,
+        free_days: Vec<chrono::NaiveDate>,
+    ) -> Option<Self> {
+        let leftovers: Vec<_> = leftovers(ingredients)
+            .into_iter()
+            .map(|(ingredient, left)| {
+                let mut suggestions = query::search_recipes_including_any_ingredient(
+                    conn,
+                    vec![ingredient.id],
+                    false,
+                )
+                .unwrap_or_report();
+                suggestions.retain(|r| !scheduled.contains(&r.id));
+                Leftover {
+                    ingredient,
+                    left,
+                    suggestions,
+                }
+            })
+            .collect();
+        (!leftovers.is_empty()).then_some(Self {
+            leftovers,
+            free_days,
+        })
+    }
+
+    /// Returns true when the window is closed, and the days recipes were put on.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+    ) -> (bool, Vec<chrono::NaiveDate>) {
+        let mut open = true;
+        let mut scheduled = vec![];
+        egui::Window::new("Leftovers")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                ui.label("The shopping list will probably leave some of these over:");
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for leftover in &mut self.leftovers {
+                        ui.push_id(leftover.ingredient.id, |ui| {
+                            ui.strong(format!(
+                                "{} {}",
+                                quantity_display(leftover.left, &None),
+                                leftover.ingredient.name
+                            ));
+                            if leftover.suggestions.is_empty() {
+                                ui.weak("No other recipes use it");
+                            }
+                            let mut done = None;
+                            for (i, recipe) in leftover.suggestions.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.label(&recipe.name);
+                                    let free_days = &self.free_days;
+                                    if let Some(day) =
+                                        update_schedule(conn, settings, free_days, recipe, ui)
+                                    {
+                                        scheduled.push(day);
+                                        done = Some(i);
+                                    }
+                                });
+                            }
+                            if let Some(i) = done {
+                                leftover.suggestions.remove(i);
+                            }
+                            ui.separator();
+                        });
+                    }
+                });
+            });
+        self.free_days.retain(|d| !scheduled.contains(d));
+        (!open, scheduled)
+    }
+}
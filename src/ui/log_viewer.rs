@@ -0,0 +1,74 @@
+//! Shows the end of the log, so problems can be looked into and reported without a console.
+
+use std::path::PathBuf;
+
+/// How many of the most recent lines of the log are shown.
+const SHOWN_LINES: usize = 1000;
+
+pub struct LogWindow {
+    path: Option<PathBuf>,
+    text: String,
+}
+
+impl LogWindow {
+    pub fn new() -> Self {
+        let mut window = Self {
+            path: crate::logging::log_path().ok(),
+            text: String::new(),
+        };
+        window.load();
+        window
+    }
+
+    fn load(&mut self) {
+        let contents = match &self.path {
+            Some(path) => std::fs::read_to_string(path),
+            None => {
+                self.text = "The log file couldn't be found".into();
+                return;
+            }
+        };
+        self.text = match contents {
+            Ok(contents) if contents.is_empty() => "Nothing has been logged yet".into(),
+            Ok(contents) => {
+                let lines: Vec<_> = contents.lines().collect();
+                lines[lines.len().saturating_sub(SHOWN_LINES)..].join("\n")
+            }
+            Err(error) => format!("Couldn't read the log: {error}"),
+        };
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        egui::Window::new("Log")
+            .open(&mut open)
+            .default_size([700.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if let Some(path) = &self.path {
+                        ui.label(path.display().to_string());
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Copy").clicked() {
+                            ui.ctx().copy_text(self.text.clone());
+                        }
+                        if ui.button("Reload").clicked() {
+                            self.load();
+                        }
+                    });
+                });
+                ui.separator();
+                egui::ScrollArea::both()
+                    .auto_shrink(false)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.text.as_str())
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+            });
+        !open
+    }
+}
@@ -0,0 +1,226 @@
+//! A little markdown for showing recipe descriptions: headings, bullet and numbered lists, bold,
+//! italics and links. Anything else is shown as it's written.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Span {
+    text: String,
+    bold: bool,
+    italic: bool,
+    link: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Block {
+    Heading(Vec<Span>),
+    Bullet(Vec<Span>),
+    Numbered(String, Vec<Span>),
+    Line(Vec<Span>),
+    Blank,
+}
+
+/// A link written like `[text](url)`, with how much of `s` it takes up.
+fn parse_link(s: &str) -> Option<(&str, &str, usize)> {
+    let close = s.find("](")?;
+    let text = &s[1..close];
+    let url_start = close + 2;
+    let url_len = s[url_start..].find(')')?;
+    let url = &s[url_start..url_start + url_len];
+    if text.contains('\n') || url.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((text, url, url_start + url_len + 1))
+}
+
+fn parse_inline(text: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut current = Span::default();
+    let flush = |spans: &mut Vec<Span>, current: &mut Span| {
+        if !current.text.is_empty() {
+            let next = Span {
+                text: String::new(),
+                ..current.clone()
+            };
+            spans.push(std::mem::replace(current, next));
+        }
+    };
+
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        // Emphasis only starts right before a word and when there's something to end it.
+        let opens = |marker: &str| {
+            let after = &rest[marker.len()..];
+            after.starts_with(|c: char| !c.is_whitespace() && c != '*') && after.contains(marker)
+        };
+        if rest.starts_with("**") && (current.bold || opens("**")) {
+            flush(&mut spans, &mut current);
+            current.bold = !current.bold;
+            rest = &rest[2..];
+            continue;
+        }
+        if c == '*' && (current.italic || opens("*")) {
+            flush(&mut spans, &mut current);
+            current.italic = !current.italic;
+            rest = &rest[1..];
+            continue;
+        }
+        if c == '[' {
+            if let Some((link_text, url, len)) = parse_link(rest) {
+                flush(&mut spans, &mut current);
+                spans.push(Span {
+                    text: link_text.into(),
+                    link: Some(url.into()),
+                    ..current.clone()
+                });
+                rest = &rest[len..];
+                continue;
+            }
+        }
+        if rest.starts_with("http://") || rest.starts_with("https://") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ')', ';']);
+            flush(&mut spans, &mut current);
+            spans.push(Span {
+                text: url.into(),
+                link: Some(url.into()),
+                ..current.clone()
+            });
+            rest = &rest[url.len()..];
+            continue;
+        }
+        current.text.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    flush(&mut spans, &mut current);
+    spans
+}
+
+fn parse(text: &str) -> Vec<Block> {
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                return Block::Blank;
+            }
+            if let Some(heading) = trimmed
+                .strip_prefix('#')
+                .map(|h| h.trim_start_matches('#'))
+                .and_then(|h| h.strip_prefix(' '))
+            {
+                return Block::Heading(parse_inline(heading));
+            }
+            if let Some(item) = trimmed
+                .strip_prefix("- ")
+                .or_else(|| trimmed.strip_prefix("* "))
+            {
+                return Block::Bullet(parse_inline(item));
+            }
+            let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits > 0 {
+                if let Some(item) = trimmed[digits..].strip_prefix(". ") {
+                    return Block::Numbered(trimmed[..digits].into(), parse_inline(item));
+                }
+            }
+            Block::Line(parse_inline(line))
+        })
+        .collect()
+}
+
+#[test]
+fn parse_test() {
+    let text = |t: &str| Span {
+        text: t.into(),
+        ..Default::default()
+    };
+    let bold = |t: &str| Span {
+        bold: true,
+        ..text(t)
+    };
+    let italic = |t: &str| Span {
+        italic: true,
+        ..text(t)
+    };
+    let link = |t: &str, url: &str| Span {
+        link: Some(url.into()),
+        ..text(t)
+    };
+
+    assert_eq!(
+        parse("## Sauce\n- **2** cups\n\n3. Stir *well*"),
+        [
+            Block::Heading(vec![text("Sauce")]),
+            Block::Bullet(vec![bold("2"), text(" cups")]),
+            Block::Blank,
+            Block::Numbered("3".into(), vec![text("Stir "), italic("well")]),
+        ]
+    );
+    assert_eq!(
+        parse("From [the blog](https://example.com/soup), see https://example.com."),
+        [Block::Line(vec![
+            text("From "),
+            link("the blog", "https://example.com/soup"),
+            text(", see "),
+            link("https://example.com", "https://example.com"),
+            text("."),
+        ])]
+    );
+    // Stars which aren't emphasis are left alone.
+    assert_eq!(
+        parse("Serves 2 * 3, #1 pick"),
+        [Block::Line(vec![text("Serves 2 * 3, #1 pick")])]
+    );
+    assert_eq!(
+        parse("**unfinished"),
+        [Block::Line(vec![text("**unfinished")])]
+    );
+}
+
+fn show_spans(ui: &mut egui::Ui, spans: &[Span], heading: bool) {
+    for span in spans {
+        let mut text = egui::RichText::new(&span.text);
+        if heading {
+            text = text.heading();
+        }
+        if span.bold {
+            text = text.strong();
+        }
+        if span.italic {
+            text = text.italics();
+        }
+        match &span.link {
+            Some(url) => {
+                ui.hyperlink_to(text, url);
+            }
+            None => {
+                ui.label(text);
+            }
+        }
+    }
+}
+
+/// Shows the text with its markdown formatting, wrapping to the available width.
+pub fn show(ui: &mut egui::Ui, text: &str) {
+    ui.vertical(|ui| {
+        for block in parse(text) {
+            if block == Block::Blank {
+                ui.add_space(ui.spacing().item_spacing.y);
+                continue;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing.x = 0.0;
+                match &block {
+                    Block::Heading(spans) => show_spans(ui, spans, true),
+                    Block::Bullet(spans) => {
+                        ui.label("  •  ");
+                        show_spans(ui, spans, false);
+                    }
+                    Block::Numbered(number, spans) => {
+                        ui.label(format!("  {number}.  "));
+                        show_spans(ui, spans, false);
+                    }
+                    Block::Line(spans) => show_spans(ui, spans, false),
+                    Block::Blank => {}
+                }
+            });
+        }
+    });
+}
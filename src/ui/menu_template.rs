@@ -0,0 +1,430 @@
+//! The layout of the menu document, from a template the user can edit. A template is lines of
+//! `key: value`, where the values have placeholders in braces, `*bold*` parts and `\t` for a tab.
+
+use super::calendar::full_day_name;
+use super::generate_rtf;
+use super::i18n::{self, tr};
+use super::new_error_toast;
+use super::nutrition::calories_display;
+use super::settings::DateFormat;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = "menu-template.txt";
+
+/// What the template file starts as, giving the same layout as `MenuTemplate::default`.
+const DEFAULT_TEMPLATE: &str = "\
+# The layout of the menu. Lines starting with # are ignored.
+#
+# {title}, {week}, {day}, {recipe}, {calories} and {total} are filled in. Put *stars* around
+# the parts to be bold, and \\t where the recipes should line up at the tab stop.
+font: Helvetica
+bold font: HelveticaNeue-Bold
+font size: 12
+# In twips, which are a twentieth of a point.
+tab stop: 1680
+# The week is written like other dates, as in the settings, unless it has its own format.
+# week format: %e, %B %Y
+heading: *{title} for the Week*
+heading: *of {week}*
+day: {day}\\t{recipe} ({calories} cal)
+no recipe: {day}\\tNo Recipe
+total: *Total Calories:* {total}
+";
+
+/// A line of the menu in parts, with whether each is bold. Tabs are left in the text.
+type Line = Vec<(String, bool)>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MenuTemplate {
+    font: String,
+    bold_font: String,
+    font_size: u32,
+    tab_stop: u32,
+    week_format: Option<String>,
+    heading: Vec<String>,
+    day: String,
+    no_recipe: String,
+    total: String,
+}
+
+impl Default for MenuTemplate {
+    fn default() -> Self {
+        Self {
+            font: "Helvetica".into(),
+            bold_font: "HelveticaNeue-Bold".into(),
+            font_size: 12,
+            tab_stop: 1680,
+            week_format: None,
+            heading: vec![
+                tr("*{title} for the Week*").into(),
+                tr("*of {week}*").into(),
+            ],
+            day: "{day}\t{recipe} ({calories} cal)".into(),
+            no_recipe: tr("{day}\tNo Recipe").into(),
+            total: tr("*Total Calories:* {total}").into(),
+        }
+    }
+}
+
+fn fill(format: &str, values: &[(&str, &str)]) -> String {
+    let mut text = format.to_owned();
+    for (name, value) in values {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+    text
+}
+
+/// Fills in the placeholders after splitting out the bold parts, so stars in recipe names stay
+/// as they are.
+fn fill_styled(format: &str, values: &[(&str, &str)]) -> Line {
+    format
+        .split('*')
+        .enumerate()
+        .filter(|(_, part)| !part.is_empty())
+        .map(|(i, part)| (fill(part, values), i % 2 == 1))
+        .collect()
+}
+
+fn rich_text_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_ascii() => escaped.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    escaped += &format!("\\u{}?", *unit as i16);
+                }
+            }
+        }
+    }
+    escaped
+}
+
+impl MenuTemplate {
+    fn path() -> recipe_manager::Result<PathBuf> {
+        Ok(recipe_manager::data_path()?.join(FILE_NAME))
+    }
+
+    /// The user's template, or the default one if they haven't made one.
+    pub fn load() -> recipe_manager::Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Opens the template file to edit, writing out the default one first if there isn't one.
+    pub fn edit() -> recipe_manager::Result<()> {
+        let path = Self::path()?;
+        if !path.exists() {
+            std::fs::write(&path, DEFAULT_TEMPLATE)?;
+        }
+        open::that(path)?;
+        Ok(())
+    }
+
+    /// Anything left out of the template is the same as the default.
+    fn parse(text: &str) -> recipe_manager::Result<Self> {
+        let mut template = Self::default();
+        let mut headings = vec![];
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(format!("line {}: expected \"key: value\"", number + 1).into());
+            };
+            let value = value.trim().replace("\\t", "\t");
+            let number_value = || {
+                value
+                    .parse()
+                    .map_err(|_| format!("line {}: {value:?} isn't a number", number + 1))
+            };
+            match key.trim() {
+                "font" => template.font = value,
+                "bold font" => template.bold_font = value,
+                "font size" => template.font_size = number_value()?,
+                "tab stop" => template.tab_stop = number_value()?,
+                "week format" => template.week_format = Some(value),
+                "heading" => headings.push(value),
+                "day" => template.day = value,
+                "no recipe" => template.no_recipe = value,
+                "total" => template.total = value,
+                key => return Err(format!("line {}: unknown key {key:?}", number + 1).into()),
+            }
+        }
+        if !headings.is_empty() {
+            template.heading = headings;
+        }
+        Ok(template)
+    }
+
+    /// The menu's lines, with a blank one before the days and another before the total.
+    fn lines(
+        &self,
+        week: chrono::NaiveWeek,
+        recipes: &[(chrono::Weekday, Option<String>)],
+        calories: &HashMap<chrono::Weekday, f32>,
+        date_format: DateFormat,
+    ) -> Vec<Line> {
+        let week_format = self.week_format.as_deref().unwrap_or(date_format.long());
+        let week_str = i18n::format_date(week.first_day(), week_format);
+        let mut lines: Vec<_> = self
+            .heading
+            .iter()
+            .map(|h| fill_styled(h, &[("title", tr("Menu")), ("week", &week_str)]))
+            .collect();
+        lines.push(vec![]);
+
+        for (day, recipe) in recipes {
+            let day_str = full_day_name(*day);
+            let line = match recipe {
+                Some(recipe) => {
+                    let day_calories = calories.get(day).copied().unwrap_or(0.0);
+                    fill_styled(
+                        &self.day,
+                        &[
+                            ("day", day_str),
+                            ("recipe", recipe),
+                            ("calories", &calories_display(day_calories)),
+                        ],
+                    )
+                }
+                None => fill_styled(&self.no_recipe, &[("day", day_str)]),
+            };
+            lines.push(line);
+        }
+        lines.push(vec![]);
+
+        let total: f32 = calories.values().sum();
+        lines.push(fill_styled(
+            &self.total,
+            &[("total", &calories_display(total))],
+        ));
+        lines
+    }
+
+    pub fn rich_text(
+        &self,
+        week: chrono::NaiveWeek,
+        recipes: &[(chrono::Weekday, Option<String>)],
+        calories: &HashMap<chrono::Weekday, f32>,
+        date_format: DateFormat,
+    ) -> String {
+        let mut rich_text = String::new();
+        rich_text += "{\\rtf1\n";
+        rich_text += &format!(
+            "{{\\fonttbl\\f0\\fnil\\fcharset0 {};\\f1\\fswiss\\fcharset0 {};}}\n",
+            self.bold_font, self.font
+        );
+        rich_text += &format!(
+            "\\pard\\tx{}\\pardirnatural\\partightenfactor0\n",
+            self.tab_stop
+        );
+        rich_text += &format!("\\f1\\b0\\fs{} \\cf0 ", self.font_size * 2);
+
+        let lines: Vec<String> = self
+            .lines(week, recipes, calories, date_format)
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .map(|(text, bold)| {
+                        if bold {
+                            format!("\\f0\\b {}\\f1\\b0 ", rich_text_escape(&text))
+                        } else {
+                            rich_text_escape(&text)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        rich_text += &lines.join("\\\n");
+        rich_text += "}";
+        rich_text
+    }
+
+    /// Shows the menu roughly as it'll look in the document.
+    fn show_preview(
+        &self,
+        ui: &mut egui::Ui,
+        week: chrono::NaiveWeek,
+        recipes: &[(chrono::Weekday, Option<String>)],
+        calories: &HashMap<chrono::Weekday, f32>,
+        date_format: DateFormat,
+    ) {
+        // Points in the document are about the same size as they are on screen.
+        let tab_stop = self.tab_stop as f32 / 20.0;
+        let font_size = self.font_size as f32;
+        for line in self.lines(week, recipes, calories, date_format) {
+            if line.is_empty() {
+                ui.add_space(font_size);
+                continue;
+            }
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 0.0;
+                let start = ui.cursor().min.x;
+                for (text, bold) in line {
+                    for (i, part) in text.split('\t').enumerate() {
+                        if i > 0 {
+                            let x = ui.cursor().min.x - start;
+                            let next = ((x / tab_stop).floor() + 1.0) * tab_stop;
+                            ui.add_space(next - x);
+                        }
+                        let mut part = egui::RichText::new(part).size(font_size);
+                        if bold {
+                            part = part.strong();
+                        }
+                        ui.label(part);
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[test]
+fn parse_test() {
+    assert_eq!(
+        MenuTemplate::parse(DEFAULT_TEMPLATE).unwrap(),
+        MenuTemplate::default()
+    );
+
+    let template = MenuTemplate::parse("font size: 14\nheading: {title}\n").unwrap();
+    assert_eq!(template.font_size, 14);
+    assert_eq!(template.heading, ["{title}"]);
+    assert_eq!(template.day, MenuTemplate::default().day);
+
+    assert!(MenuTemplate::parse("font size: big").is_err());
+    assert!(MenuTemplate::parse("colour: red").is_err());
+    assert!(MenuTemplate::parse("just some text").is_err());
+}
+
+#[test]
+fn menu_rich_text_calories() {
+    use chrono::Weekday::*;
+    use maplit::hashmap;
+
+    let week = chrono::NaiveDate::from_ymd_opt(2024, 3, 3)
+        .unwrap()
+        .week(Sun);
+    let recipes = vec![
+        (Sun, Some("Pancakes".into())),
+        (Mon, None),
+        (Wed, Some("*Thick* Soup {1}".into())),
+    ];
+    let calories = hashmap! { Sun => 1234.4, Wed => 500.0 };
+    let rich_text =
+        MenuTemplate::default().rich_text(week, &recipes, &calories, DateFormat::MonthFirst);
+
+    assert!(rich_text.contains("\\tx1680\\"));
+    assert!(rich_text.contains("\\f0\\b Menu for the Week\\f1\\b0 \\\n"));
+    assert!(rich_text.contains("\\f0\\b of March 3, 2024\\f1\\b0 \\\n"));
+    assert!(rich_text.contains("\\\nSunday\tPancakes (1,234 cal)"));
+    assert!(rich_text.contains("\\\nMonday\tNo Recipe"));
+    assert!(rich_text.contains("\\\nWednesday\t*Thick* Soup \\{1\\} (500 cal)"));
+    assert!(rich_text.ends_with("Total Calories:\\f1\\b0  1,734}"));
+}
+
+/// Shows how the menu for the week will come out before opening it, with buttons to change the
+/// template.
+pub struct MenuWindow {
+    week: chrono::NaiveWeek,
+    recipes: Vec<(chrono::Weekday, Option<String>)>,
+    calories: HashMap<chrono::Weekday, f32>,
+    date_format: DateFormat,
+    template: MenuTemplate,
+    /// Why the template couldn't be loaded, in which case the default one is used.
+    error: Option<String>,
+}
+
+impl MenuWindow {
+    pub fn new(
+        week: chrono::NaiveWeek,
+        recipes: Vec<(chrono::Weekday, Option<String>)>,
+        calories: HashMap<chrono::Weekday, f32>,
+        date_format: DateFormat,
+    ) -> Self {
+        let mut window = Self {
+            week,
+            recipes,
+            calories,
+            date_format,
+            template: MenuTemplate::default(),
+            error: None,
+        };
+        window.reload();
+        window
+    }
+
+    fn reload(&mut self) {
+        match MenuTemplate::load() {
+            Ok(template) => {
+                self.template = template;
+                self.error = None;
+            }
+            Err(error) => {
+                self.template = MenuTemplate::default();
+                self.error = Some(error.to_string());
+            }
+        }
+    }
+
+    /// Returns true when the window is closed.
+    pub fn update(&mut self, ctx: &egui::Context, toasts: &mut egui_toast::Toasts) -> bool {
+        let mut open = true;
+        egui::Window::new("Menu").open(&mut open).show(ctx, |ui| {
+            if let Some(error) = &self.error {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("Using the default template, since yours has an error: {error}"),
+                );
+                ui.separator();
+            }
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                self.template.show_preview(
+                    ui,
+                    self.week,
+                    &self.recipes,
+                    &self.calories,
+                    self.date_format,
+                );
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Open Menu").clicked() {
+                    if let Err(error) = generate_rtf::generate_and_open_menu(
+                        self.week,
+                        &self.recipes,
+                        &self.calories,
+                        &self.template,
+                        self.date_format,
+                    ) {
+                        toasts.add(new_error_toast(format!("Error generating menu: {error}")));
+                    }
+                }
+                if ui.button("Edit Template").clicked() {
+                    if let Err(error) = MenuTemplate::edit() {
+                        toasts.add(new_error_toast(format!(
+                            "Couldn't open the template: {error}"
+                        )));
+                    }
+                }
+                if ui
+                    .button("Reload Template")
+                    .on_hover_text("After saving changes to the template")
+                    .clicked()
+                {
+                    self.reload();
+                }
+            });
+        });
+        !open
+    }
+}
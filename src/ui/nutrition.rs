@@ -0,0 +1,42 @@
+//! Showing calorie totals, shared by the recipe and calendar windows and the RTF exports.
+
+/// Calories rounded to a whole number, with thousands separators.
+pub fn calories_display(calories: f32) -> String {
+    use thousands::Separable as _;
+
+    (calories.round() as i64).separate_with_commas()
+}
+
+/// Average calories per day over each run of `window` consecutive periods, for every run which
+/// fits. Each period is given as its total calories and the number of days which had any.
+pub fn rolling_daily_averages(periods: &[(f32, u32)], window: usize) -> Vec<Option<f32>> {
+    if window == 0 {
+        return vec![];
+    }
+    periods
+        .windows(window)
+        .map(|w| {
+            let calories: f32 = w.iter().map(|(c, _)| c).sum();
+            let days: u32 = w.iter().map(|(_, d)| d).sum();
+            (days > 0).then(|| calories / days as f32)
+        })
+        .collect()
+}
+
+#[test]
+fn rolling_daily_averages_test() {
+    assert_eq!(
+        rolling_daily_averages(&[(1000.0, 1), (3000.0, 2), (0.0, 0), (500.0, 1)], 2),
+        vec![Some(4000.0 / 3.0), Some(1500.0), Some(500.0)]
+    );
+    assert_eq!(rolling_daily_averages(&[(0.0, 0), (0.0, 0)], 2), vec![None]);
+    assert_eq!(rolling_daily_averages(&[(100.0, 1)], 2), vec![]);
+}
+
+#[test]
+fn calories_display_test() {
+    assert_eq!(calories_display(0.0), "0");
+    assert_eq!(calories_display(-0.0), "0");
+    assert_eq!(calories_display(849.6), "850");
+    assert_eq!(calories_display(12345.4), "12,345");
+}
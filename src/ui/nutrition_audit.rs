@@ -0,0 +1,169 @@
+use super::errors::QueryResultExt as _;
+use super::ingredient_calories::IngredientCaloriesWindow;
+use recipe_manager::database;
+use recipe_manager::database::models::{Ingredient, IngredientId};
+use recipe_manager::query;
+use std::collections::HashMap;
+
+struct MissingNutrition {
+    ingredient: Ingredient,
+    recipes: Vec<String>,
+    reasons: Vec<String>,
+}
+
+pub struct NutritionAuditWindow {
+    missing: Vec<MissingNutrition>,
+}
+
+impl NutritionAuditWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        let mut missing: HashMap<IngredientId, MissingNutrition> = HashMap::new();
+        for (recipe, usage) in query::get_all_recipe_ingredients(conn).unwrap_or_report() {
+            let Some(reason) = usage.missing_calories() else {
+                continue;
+            };
+            let entry = missing
+                .entry(usage.ingredient.id)
+                .or_insert_with(|| MissingNutrition {
+                    ingredient: usage.ingredient.clone(),
+                    recipes: vec![],
+                    reasons: vec![],
+                });
+            if !entry.recipes.contains(&recipe.name) {
+                entry.recipes.push(recipe.name);
+            }
+            if !entry.reasons.contains(&reason) {
+                entry.reasons.push(reason);
+            }
+        }
+
+        let mut missing: Vec<_> = missing.into_values().collect();
+        for m in &mut missing {
+            m.recipes.sort();
+        }
+        missing.sort_by(|a, b| {
+            b.recipes
+                .len()
+                .cmp(&a.recipes.len())
+                .then_with(|| a.ingredient.name.cmp(&b.ingredient.name))
+        });
+        Self { missing }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        *self = Self::new(conn);
+    }
+
+    fn update_table(
+        &self,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+    ) {
+        let available_height = ui.available_height();
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("nutrition audit table")
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::initial(150.0).clip(true))
+            .column(egui_extras::Column::exact(70.0))
+            .column(egui_extras::Column::remainder().clip(true))
+            .column(egui_extras::Column::exact(70.0))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(available_height)
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.heading("Ingredient");
+                });
+                header.col(|ui| {
+                    ui.heading("Recipes");
+                });
+                header.col(|ui| {
+                    ui.heading("Problem");
+                });
+                header.col(|ui| {
+                    ui.heading("");
+                });
+            })
+            .body(|mut body| {
+                for m in &self.missing {
+                    body.row(20.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label(&m.ingredient.name);
+                        });
+                        row.col(|ui| {
+                            ui.label(m.recipes.len().to_string())
+                                .on_hover_text(m.recipes.join("\n"));
+                        });
+                        row.col(|ui| {
+                            ui.label(&m.reasons[0]).on_hover_text(m.reasons.join("\n"));
+                        });
+                        row.col(|ui| {
+                            let id = m.ingredient.id;
+                            let mut calories_shown = ingredient_calories_windows.contains_key(&id);
+                            ui.toggle_value(&mut calories_shown, "Calories");
+                            if calories_shown && !ingredient_calories_windows.contains_key(&id) {
+                                ingredient_calories_windows.insert(
+                                    id,
+                                    IngredientCaloriesWindow::new(conn, m.ingredient.to_handle()),
+                                );
+                            } else if !calories_shown {
+                                ingredient_calories_windows.remove(&id);
+                            }
+                        });
+                    });
+                }
+            });
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+    ) -> bool {
+        let style = ctx.style();
+        let text_height = egui::TextStyle::Body
+            .resolve(&style)
+            .size
+            .max(style.spacing.interact_size.y);
+        let spacing = style.spacing.item_spacing.y;
+        let separator_height = 6.0;
+
+        let summary_height = text_height + spacing + separator_height;
+
+        let mut open = true;
+        egui::Window::new("Missing Nutrition")
+            .open(&mut open)
+            .default_width(500.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                egui_extras::StripBuilder::new(ui)
+                    .size(egui_extras::Size::exact(summary_height))
+                    .size(egui_extras::Size::remainder())
+                    .vertical(|mut strip| {
+                        strip.cell(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(format!(
+                                    "{} ingredients are missing calorie information",
+                                    self.missing.len()
+                                ));
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui.button("Refresh").clicked() {
+                                            self.refresh(conn);
+                                        }
+                                    },
+                                );
+                            });
+                            ui.separator();
+                        });
+                        strip.cell(|ui| {
+                            self.update_table(conn, ui, ingredient_calories_windows);
+                        });
+                    });
+            });
+        !open
+    }
+}
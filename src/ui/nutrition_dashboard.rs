@@ -0,0 +1,256 @@
+use super::errors::QueryResultExt as _;
+use super::{
+    calendar::this_week,
+    nutrition::{calories_display, rolling_daily_averages},
+    settings::Settings,
+};
+use recipe_manager::database;
+use recipe_manager::query;
+use recipe_manager::recipe::calories_by_day;
+use std::collections::HashMap;
+
+/// How many weeks of history are shown.
+const HISTORY_WEEKS: usize = 8;
+
+/// How many weeks each rolling average covers.
+const ROLLING_WEEKS: usize = 4;
+
+const WEEKDAYS: [chrono::Weekday; 7] = {
+    use chrono::Weekday::*;
+    [Sun, Mon, Tue, Wed, Thu, Fri, Sat]
+};
+
+struct WeekHistory {
+    week: chrono::NaiveWeek,
+    calories: f32,
+    days: u32,
+    rolling_average: Option<f32>,
+}
+
+impl WeekHistory {
+    fn average(&self) -> f32 {
+        if self.days == 0 {
+            0.0
+        } else {
+            self.calories / self.days as f32
+        }
+    }
+}
+
+pub struct NutritionDashboardWindow {
+    week: chrono::NaiveWeek,
+    days: HashMap<chrono::Weekday, f32>,
+    history: Vec<WeekHistory>,
+}
+
+impl NutritionDashboardWindow {
+    pub fn new(
+        conn: &mut database::Connection,
+        week: Option<chrono::NaiveWeek>,
+        week_start: chrono::Weekday,
+    ) -> Self {
+        use chrono::Datelike as _;
+
+        let week = week.unwrap_or_else(|| this_week(week_start));
+        let week_start = week.first_day().weekday();
+
+        // The first rolling average shown needs some weeks before it.
+        let num_weeks = HISTORY_WEEKS + ROLLING_WEEKS - 1;
+        let weeks: Vec<_> = (0..num_weeks)
+            .rev()
+            .map(|i| {
+                week.first_day()
+                    .checked_sub_days(chrono::Days::new(7 * i as u64))
+                    .unwrap()
+                    .week(week_start)
+            })
+            .collect();
+
+        let ingredients =
+            query::get_calendar_ingredients(conn, weeks[0].first_day(), week.last_day())
+                .unwrap_or_report();
+        let by_date = calories_by_day(&ingredients);
+
+        let mut totals: HashMap<chrono::NaiveDate, (f32, u32)> = HashMap::new();
+        for (date, calories) in &by_date {
+            let total = totals.entry(date.week(week_start).first_day()).or_default();
+            total.0 += calories;
+            total.1 += 1;
+        }
+        let totals: Vec<_> = weeks
+            .iter()
+            .map(|w| totals.get(&w.first_day()).copied().unwrap_or_default())
+            .collect();
+        let rolling = rolling_daily_averages(&totals, ROLLING_WEEKS);
+
+        let history = weeks
+            .into_iter()
+            .zip(totals)
+            .skip(ROLLING_WEEKS - 1)
+            .zip(rolling)
+            .map(|((week, (calories, days)), rolling_average)| WeekHistory {
+                week,
+                calories,
+                days,
+                rolling_average,
+            })
+            .collect();
+
+        let days = by_date
+            .into_iter()
+            .filter(|(date, _)| *date >= week.first_day())
+            .map(|(date, calories)| (date.weekday(), calories))
+            .collect();
+
+        Self {
+            week,
+            days,
+            history,
+        }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        use chrono::Datelike as _;
+
+        *self = Self::new(conn, Some(self.week), self.week.first_day().weekday());
+    }
+
+    fn update_day_chart(&self, ui: &mut egui::Ui, settings: &Settings, height: f32) {
+        use egui_plot::{Bar, BarChart, HLine, Legend, Plot};
+
+        let bars = WEEKDAYS
+            .into_iter()
+            .enumerate()
+            .map(|(i, day)| {
+                let calories = self.days.get(&day).copied().unwrap_or(0.0);
+                Bar::new(i as f64, calories as f64).name(day).width(0.7)
+            })
+            .collect();
+
+        Plot::new("nutrition dashboard days")
+            .height(height)
+            .legend(Legend::default())
+            .allow_zoom(false)
+            .allow_drag(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .include_y(0.0)
+            .x_axis_formatter(|mark, _| {
+                let i = mark.value.round();
+                if (mark.value - i).abs() > f64::EPSILON || !(0.0..7.0).contains(&i) {
+                    return String::new();
+                }
+                WEEKDAYS[i as usize].to_string()
+            })
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(bars).name("Calories").element_formatter(
+                    Box::new(|bar, _| {
+                        format!("{}: {}", bar.name, calories_display(bar.value as f32))
+                    }),
+                ));
+                if let Some(target) = settings.daily_calorie_target {
+                    plot_ui.hline(HLine::new(target).name("Target"));
+                }
+            });
+    }
+
+    fn update_history_chart(&self, ui: &mut egui::Ui, settings: &Settings, height: f32) {
+        use egui_plot::{Bar, BarChart, HLine, Legend, Line, Plot, PlotPoints};
+
+        let week_label = |week: chrono::NaiveWeek| week.first_day().format("%b %-d").to_string();
+        let bars = self
+            .history
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                Bar::new(i as f64, h.average() as f64)
+                    .name(week_label(h.week))
+                    .width(0.7)
+            })
+            .collect();
+        let rolling: PlotPoints = self
+            .history
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.rolling_average.map(|a| [i as f64, a as f64]))
+            .collect();
+        let labels: Vec<_> = self.history.iter().map(|h| week_label(h.week)).collect();
+
+        Plot::new("nutrition dashboard history")
+            .height(height)
+            .legend(Legend::default())
+            .allow_zoom(false)
+            .allow_drag(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .include_y(0.0)
+            .x_axis_formatter(move |mark, _| {
+                let i = mark.value.round();
+                if (mark.value - i).abs() > f64::EPSILON || i < 0.0 {
+                    return String::new();
+                }
+                labels.get(i as usize).cloned().unwrap_or_default()
+            })
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(bars).name("Daily average").element_formatter(
+                    Box::new(|bar, _| {
+                        format!(
+                            "Week of {}: {}",
+                            bar.name,
+                            calories_display(bar.value as f32)
+                        )
+                    }),
+                ));
+                plot_ui.line(Line::new(rolling).name(format!("{ROLLING_WEEKS}-week average")));
+                if let Some(target) = settings.daily_calorie_target {
+                    plot_ui.hline(HLine::new(target).name("Target"));
+                }
+            });
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+    ) -> bool {
+        let mut open = true;
+        egui::Window::new("Nutrition Dashboard")
+            .open(&mut open)
+            .default_width(500.0)
+            .default_height(500.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Week of");
+                    let mut date = self.week.first_day();
+                    let week_start = settings.week_start.weekday();
+                    ui.add(
+                        egui_extras::DatePickerButton::new(&mut date)
+                            .format(settings.date_format.short()),
+                    );
+                    let week = date.week(week_start);
+                    if week != self.week {
+                        *self = Self::new(conn, Some(week), week_start);
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if let Some(latest) = self.history.last() {
+                            ui.label(calories_display(latest.calories));
+                            ui.label("Total Calories:");
+                        }
+                    });
+                });
+                ui.separator();
+
+                let spacing = ui.spacing().item_spacing.y;
+                let heading_height = egui::TextStyle::Heading.resolve(ui.style()).size + spacing;
+                let height =
+                    ((ui.available_height() - heading_height * 2.0) / 2.0 - spacing).max(100.0);
+
+                ui.heading("Calories per day");
+                self.update_day_chart(ui, settings, height);
+                ui.heading("Average calories per planned day");
+                self.update_history_chart(ui, settings, height);
+            });
+        !open
+    }
+}
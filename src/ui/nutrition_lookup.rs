@@ -0,0 +1,131 @@
+use super::{is_editable, PressedEnterExt as _};
+use recipe_manager::database::models::IngredientHandle;
+use recipe_manager::fdc;
+use std::sync::mpsc;
+
+pub struct NutritionLookupWindow {
+    ingredient: IngredientHandle,
+    query: String,
+    pending: Option<mpsc::Receiver<recipe_manager::Result<Vec<fdc::Food>>>>,
+    results: Vec<fdc::Food>,
+    error: Option<String>,
+}
+
+impl NutritionLookupWindow {
+    pub fn new(ingredient: IngredientHandle) -> Self {
+        Self {
+            query: ingredient.name.clone(),
+            ingredient,
+            pending: None,
+            results: vec![],
+            error: None,
+        }
+    }
+
+    fn search(&mut self, ctx: &egui::Context, api_key: &str) {
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        let api_key = api_key.to_owned();
+        let query = self.query.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(fdc::search(&api_key, &query));
+            ctx.request_repaint();
+        });
+        self.pending = Some(receiver);
+        self.error = None;
+    }
+
+    fn poll(&mut self) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        match pending.try_recv() {
+            Ok(Ok(results)) => {
+                self.results = results;
+                self.pending = None;
+            }
+            Ok(Err(error)) => {
+                self.error = Some(error.to_string());
+                self.pending = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.error = Some("lookup stopped unexpectedly".into());
+                self.pending = None;
+            }
+        }
+    }
+
+    fn update_results(&self, ui: &mut egui::Ui) -> Option<f32> {
+        let mut selected = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new(("nutrition lookup results", self.ingredient.id))
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for food in &self.results {
+                        let label = ui.label(&food.description);
+                        if let Some(brand) = &food.brand {
+                            label.on_hover_text(brand);
+                        }
+                        match food.calories_per_100g {
+                            Some(calories) => {
+                                ui.label(format!("{calories} cal / 100 g"));
+                                if ui
+                                    .add_enabled(is_editable(ui.ctx()), egui::Button::new("Use"))
+                                    .clicked()
+                                {
+                                    selected = Some(calories);
+                                }
+                            }
+                            None => {
+                                ui.weak("no calorie data");
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        selected
+    }
+
+    /// Returns the calories per 100 grams of the food the user picked, if any, and whether the
+    /// window was closed.
+    pub fn update(&mut self, ctx: &egui::Context, api_key: &str) -> (Option<f32>, bool) {
+        self.poll();
+
+        let mut open = true;
+        let mut selected = None;
+        egui::Window::new(format!("{} - Nutrition Lookup", &self.ingredient.name))
+            .id(egui::Id::new(("nutrition lookup", self.ingredient.id)))
+            .default_width(400.0)
+            .default_height(300.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let searching = self.pending.is_some();
+                    let mut search = ui
+                        .add_enabled(
+                            !searching,
+                            egui::TextEdit::singleline(&mut self.query).desired_width(250.0),
+                        )
+                        .pressed_enter();
+                    search |= ui
+                        .add_enabled(!searching, egui::Button::new("Search"))
+                        .clicked();
+                    if searching {
+                        ui.spinner();
+                    } else if search && !self.query.is_empty() {
+                        self.search(ctx, api_key);
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                ui.separator();
+                selected = self.update_results(ui);
+            });
+        (selected, !open)
+    }
+}
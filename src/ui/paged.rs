@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 100;
+
+/// The rows of a query which are loaded a page at a time, as they are scrolled into view.
+pub struct Paged<T> {
+    len: usize,
+    pages: HashMap<usize, Vec<T>>,
+}
+
+impl<T> Paged<T> {
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            pages: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The row at `index`, loading its page first if needed. `load` is given the offset and limit
+    /// of the page.
+    pub fn get(&mut self, index: usize, load: impl FnOnce(i64, i64) -> Vec<T>) -> Option<&T> {
+        let page = index / PAGE_SIZE;
+        self.pages
+            .entry(page)
+            .or_insert_with(|| load((page * PAGE_SIZE) as i64, PAGE_SIZE as i64))
+            .get(index % PAGE_SIZE)
+    }
+
+    /// The rows which have been loaded so far, in no particular order.
+    pub fn loaded_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.pages.values_mut().flatten()
+    }
+}
+
+#[test]
+fn paged_test() {
+    let mut loads = vec![];
+    let mut paged = Paged::new(250);
+    let mut load = |offset: i64, limit: i64| {
+        loads.push(offset);
+        (offset..(offset + limit).min(250)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(paged.get(5, &mut load), Some(&5));
+    assert_eq!(paged.get(99, &mut load), Some(&99));
+    assert_eq!(paged.get(249, &mut load), Some(&249));
+    assert_eq!(paged.get(250, &mut load), None);
+    assert_eq!(loads, [0, 200]);
+    assert_eq!(paged.loaded_mut().count(), 150);
+}
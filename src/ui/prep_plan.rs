@@ -0,0 +1,229 @@
+//! A plan for one day's cooking: when to start each recipe so it's all ready at dinner time, with
+//! the steps of recipes for later days fitted in before.
+
+use super::calendar::{full_day_name, LinkedStep};
+use super::errors::QueryResultExt as _;
+use super::settings::{DateFormat, Settings};
+use chrono::Datelike as _;
+use recipe_manager::database;
+use recipe_manager::database::models::RecipeHandle;
+use recipe_manager::query;
+use recipe_manager::unit_conversion;
+
+/// Steps for later days are mostly waiting, like soaking or marinating, so they're only given
+/// long enough to get them going.
+const AHEAD_STEP_MINUTES: u32 = 15;
+
+#[derive(Clone, Debug, PartialEq)]
+struct PrepTask {
+    recipe: String,
+    description: String,
+    minutes: u32,
+    /// Whether it's being served tonight, so it should finish at dinner time rather than just
+    /// be out of the way by then.
+    for_dinner: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct PlannedTask {
+    start: chrono::NaiveTime,
+    task: PrepTask,
+}
+
+/// Works back from dinner time. The dishes for dinner are started so they finish together, longest
+/// first, and the steps for later days are done one after another before any of them.
+fn plan(dinner: chrono::NaiveTime, tasks: Vec<PrepTask>) -> Vec<PlannedTask> {
+    let minutes = |m: u32| chrono::Duration::minutes(m.into());
+    let (mut for_dinner, mut ahead): (Vec<_>, Vec<_>) =
+        tasks.into_iter().partition(|t| t.for_dinner);
+    for_dinner.sort_by_key(|t| std::cmp::Reverse(t.minutes));
+    ahead.sort_by_key(|t| std::cmp::Reverse(t.minutes));
+
+    let mut planned: Vec<_> = for_dinner
+        .into_iter()
+        .map(|task| PlannedTask {
+            start: dinner - minutes(task.minutes),
+            task,
+        })
+        .collect();
+    let mut cursor = planned.first().map_or(dinner, |p| p.start);
+    for task in ahead.into_iter().rev() {
+        cursor -= minutes(task.minutes);
+        planned.insert(
+            0,
+            PlannedTask {
+                start: cursor,
+                task,
+            },
+        );
+    }
+    planned
+}
+
+#[test]
+fn plan_test() {
+    let time = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+    let task = |recipe: &str, minutes, for_dinner| PrepTask {
+        recipe: recipe.into(),
+        description: String::new(),
+        minutes,
+        for_dinner,
+    };
+
+    let planned = plan(
+        time(18, 0),
+        vec![
+            task("Rice", 30, true),
+            task("Beans", 15, false),
+            task("Stew", 180, true),
+            task("Dough", 45, false),
+        ],
+    );
+    let starts: Vec<_> = planned
+        .iter()
+        .map(|p| (p.task.recipe.as_str(), p.start))
+        .collect();
+    assert_eq!(
+        starts,
+        [
+            ("Dough", time(14, 0)),
+            ("Beans", time(14, 45)),
+            ("Stew", time(15, 0)),
+            ("Rice", time(17, 30)),
+        ]
+    );
+
+    assert!(plan(time(18, 0), vec![]).is_empty());
+    let planned = plan(time(18, 0), vec![task("Beans", 15, false)]);
+    assert_eq!(planned[0].start, time(17, 45));
+}
+
+pub struct PrepPlanWindow {
+    date: chrono::NaiveDate,
+    date_format: DateFormat,
+    dinner: chrono::NaiveTime,
+    tasks: Vec<PrepTask>,
+    planned: Vec<PlannedTask>,
+}
+
+impl PrepPlanWindow {
+    pub fn new(
+        conn: &mut database::Connection,
+        settings: &Settings,
+        date: chrono::NaiveDate,
+        recipe: Option<RecipeHandle>,
+        steps: Vec<LinkedStep>,
+    ) -> Self {
+        let mut tasks = vec![];
+        let step_label = |step: &LinkedStep| {
+            unit_conversion::with_temperature_conversions(
+                &step.step.label,
+                settings.measurement_system,
+            )
+        };
+        if let Some(recipe) = &recipe {
+            if let Some((r, _, _)) = query::get_recipe(conn, recipe.id).or_report() {
+                // Its steps for today are part of cooking it.
+                let today: Vec<_> = steps
+                    .iter()
+                    .filter(|s| s.recipe.id == recipe.id && s.scheduled == date)
+                    .map(step_label)
+                    .collect();
+                tasks.push(PrepTask {
+                    recipe: recipe.name.clone(),
+                    description: today.join(", "),
+                    minutes: r.duration.estimated_minutes(),
+                    for_dinner: true,
+                });
+            }
+        }
+        for step in &steps {
+            if step.scheduled != date {
+                tasks.push(PrepTask {
+                    recipe: step.recipe.name.clone(),
+                    description: format!(
+                        "Day {}: {}, for {}",
+                        step.step.day,
+                        step_label(step),
+                        full_day_name(step.scheduled.weekday())
+                    ),
+                    minutes: AHEAD_STEP_MINUTES,
+                    for_dinner: false,
+                });
+            }
+        }
+
+        let dinner = chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        Self {
+            date,
+            date_format: settings.date_format,
+            dinner,
+            planned: plan(dinner, tasks.clone()),
+            tasks,
+        }
+    }
+
+    fn update_dinner_time(&mut self, ui: &mut egui::Ui) {
+        use chrono::Timelike as _;
+
+        let (mut hour, mut minute) = (self.dinner.hour(), self.dinner.minute());
+        ui.horizontal(|ui| {
+            ui.label("Dinner at");
+            let changed = ui
+                .add(egui::DragValue::new(&mut hour).range(0..=23))
+                .changed()
+                | ui.add(
+                    egui::DragValue::new(&mut minute)
+                        .range(0..=59)
+                        .speed(0.25)
+                        .custom_formatter(|m, _| format!("{m:02}")),
+                )
+                .changed();
+            if changed {
+                self.dinner = chrono::NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+                self.planned = plan(self.dinner, self.tasks.clone());
+            }
+        });
+    }
+
+    /// Returns true when the window is closed.
+    pub fn update(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        let format = format!("%A, {}", self.date_format.without_year());
+        egui::Window::new(format!("Prep Plan for {}", self.date.format(&format)))
+            .id(egui::Id::new("prep plan"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                self.update_dinner_time(ui);
+                ui.separator();
+                if self.planned.is_empty() {
+                    ui.label("Nothing is planned for this day.");
+                }
+                egui::Grid::new("prep plan grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for p in &self.planned {
+                            ui.strong(p.start.format("%-I:%M %p").to_string());
+                            ui.vertical(|ui| {
+                                if p.task.for_dinner {
+                                    ui.label(format!("Start {}", p.task.recipe));
+                                } else {
+                                    ui.label(&p.task.recipe);
+                                }
+                                if !p.task.description.is_empty() {
+                                    ui.weak(&p.task.description);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                        if self.planned.iter().any(|p| p.task.for_dinner) {
+                            ui.strong(self.dinner.format("%-I:%M %p").to_string());
+                            ui.label("Dinner");
+                            ui.end_row();
+                        }
+                    });
+            });
+        !open
+    }
+}
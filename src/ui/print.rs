@@ -0,0 +1,137 @@
+//! Printable recipes, written as HTML and opened in the browser, which takes care of pagination and
+//! the print dialog.
+
+use super::nutrition::calories_display;
+use super::recipe::RecipeIngredientExt as _;
+use super::settings::Settings;
+use recipe_manager::database::models::Recipe;
+use recipe_manager::recipe::{total_calories, RecipeIngredient};
+use recipe_manager::unit_conversion::with_temperature_conversions;
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn escape_html_test() {
+    assert_eq!(escape_html("salt & pepper"), "salt &amp; pepper");
+    assert_eq!(
+        escape_html("<b>\"hot\"</b>"),
+        "&lt;b&gt;&quot;hot&quot;&lt;/b&gt;"
+    );
+}
+
+const STYLE: &str = "\
+@page { margin: 2cm; }
+body { font-family: Helvetica, Arial, sans-serif; font-size: 12pt; }
+h1 { margin-bottom: 0; }
+h2 { break-after: avoid; }
+li, p { break-inside: avoid; }
+.details { color: #555; }
+";
+
+fn recipe_html(
+    name: &str,
+    duration: &str,
+    ingredients: &[String],
+    description: &str,
+    calories: &str,
+) -> String {
+    let mut html = String::new();
+    html += "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n";
+    html += &format!("<title>{}</title>\n", escape_html(name));
+    html += &format!("<style>\n{STYLE}</style>\n");
+    html += "</head>\n<body onload=\"window.print()\">\n";
+    html += &format!("<h1>{}</h1>\n", escape_html(name));
+    html += &format!(
+        "<p class=\"details\">{} &middot; {calories} calories</p>\n",
+        escape_html(duration)
+    );
+    if !ingredients.is_empty() {
+        html += "<h2>Ingredients</h2>\n<ul>\n";
+        for i in ingredients {
+            html += &format!("<li>{}</li>\n", escape_html(i));
+        }
+        html += "</ul>\n";
+    }
+    let paragraphs: Vec<_> = description
+        .split("\n\n")
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if !paragraphs.is_empty() {
+        html += "<h2>Directions</h2>\n";
+        for p in paragraphs {
+            html += &format!("<p>{}</p>\n", escape_html(p).replace('\n', "<br>\n"));
+        }
+    }
+    html += "</body>\n</html>\n";
+    html
+}
+
+#[test]
+fn recipe_html_test() {
+    let html = recipe_html(
+        "Mac & Cheese",
+        "short",
+        &["2 cups macaroni".into(), "1 cups cheese, grated".into()],
+        "Boil the pasta.\n\nStir in the cheese.\nServe.",
+        "1,200",
+    );
+    assert!(html.contains("<h1>Mac &amp; Cheese</h1>"));
+    assert!(html.contains("<p class=\"details\">short &middot; 1,200 calories</p>"));
+    assert!(html.contains("<li>2 cups macaroni</li>\n<li>1 cups cheese, grated</li>"));
+    assert!(html.contains("<p>Boil the pasta.</p>\n<p>Stir in the cheese.<br>\nServe.</p>"));
+
+    let html = recipe_html("Toast", "short", &[], " ", "0");
+    assert!(!html.contains("Ingredients"));
+    assert!(!html.contains("Directions"));
+}
+
+/// File name for the printable recipe, made of the safe characters of its name.
+fn file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("recipe-{name}.html")
+}
+
+#[test]
+fn file_name_test() {
+    assert_eq!(file_name("Mac & Cheese"), "recipe-Mac---Cheese.html");
+}
+
+pub fn print_recipe(
+    recipe: &Recipe,
+    ingredients: &[RecipeIngredient],
+    settings: &Settings,
+) -> recipe_manager::Result<()> {
+    let lines: Vec<_> = ingredients.iter().map(|i| i.display(settings)).collect();
+    let description =
+        with_temperature_conversions(&recipe.description, settings.measurement_system);
+    let html = recipe_html(
+        &recipe.name,
+        &recipe.duration.to_string(),
+        &lines,
+        &description,
+        &calories_display(total_calories(ingredients)),
+    );
+
+    let printed_dir = recipe_manager::data_path()?.join("printed");
+    std::fs::create_dir_all(&printed_dir)?;
+    let path = printed_dir.join(file_name(&recipe.name));
+    std::fs::write(&path, html)?;
+    open::that(path)?;
+    Ok(())
+}
@@ -1,16 +1,36 @@
+use super::errors::QueryResultExt as _;
+#[cfg(feature = "print")]
+use super::print;
+#[cfg(feature = "share")]
+use super::share::RecipeShare;
 use super::{
     calendar::{this_week, RecipeWeek},
+    cook_journal::RecipeJournal,
+    diet,
+    i18n::{self, tr},
     ingredient_calories::IngredientCaloriesWindow,
-    new_error_toast, query,
+    is_editable, markdown, new_error_toast,
+    recipe_steps::RecipeStepsWindow,
     search::SearchWidget,
-    unit_conversion, PressedEnterExt as _,
+    settings::Settings,
+    undo::{self, UndoAction},
+    unit_choices, AccessibleNameExt as _, EditToggleExt as _, PressedEnterExt as _,
 };
-use crate::database;
-use crate::database::models::{
-    Ingredient, IngredientCaloriesEntry, IngredientId, IngredientMeasurement, IngredientUsageId,
-    Recipe, RecipeCategoryId, RecipeDuration, RecipeId,
+use derive_more::Display;
+use diesel::QueryResult;
+#[cfg(feature = "legacy-import")]
+use recipe_manager::cooklang;
+use recipe_manager::database;
+use recipe_manager::database::models::{
+    Ingredient, IngredientCategoryId, IngredientId, IngredientMeasurement, IngredientUsage,
+    IngredientUsageId, Recipe, RecipeCategoryId, RecipeDuration, RecipeId, RecipePhoto,
+    RecipeStatus,
 };
+use recipe_manager::query;
+use recipe_manager::recipe::{total_calories, RecipeIngredient};
+use recipe_manager::unit_conversion::{self, quantity_parse, quantity_with_unit_parse};
 use std::collections::HashMap;
+use strum::EnumIter;
 
 struct IngredientBeingEdited {
     usage_id: IngredientUsageId,
@@ -18,6 +38,7 @@ struct IngredientBeingEdited {
     ingredient: Option<Ingredient>,
     quantity: String,
     quantity_units: Option<IngredientMeasurement>,
+    note: String,
     cached_ingredient_search: Option<query::CachedQuery<Ingredient>>,
 }
 
@@ -29,43 +50,151 @@ impl IngredientBeingEdited {
             ingredient: Some(usage.ingredient.clone()),
             quantity: quantity_display(usage.quantity, &usage.quantity_units),
             quantity_units: usage.quantity_units,
+            note: usage.note.clone(),
             cached_ingredient_search: None,
         }
     }
+
+    fn is_changed(&self, usage: &RecipeIngredient) -> bool {
+        self.ingredient.as_ref().map(|i| i.id) != Some(usage.ingredient.id)
+            || self.parsed_quantity() != Some(usage.quantity)
+            || self.quantity_units != usage.quantity_units
+            || self.note != usage.note
+    }
+
+    fn parsed_quantity(&self) -> Option<f32> {
+        quantity_with_unit_parse(&self.quantity)
+            .map(|(q, _)| q)
+            .or_else(|| quantity_parse(&self.quantity))
+    }
+
+    /// Offers to switch to a more natural unit for the quantity, like 2 tbsp. instead of 0.125 cups.
+    fn update_unit_suggestion(&mut self, ui: &mut egui::Ui) {
+        let (Some(quantity), Some(units)) = (self.parsed_quantity(), self.quantity_units) else {
+            return;
+        };
+        if quantity <= 0.0 {
+            return;
+        }
+        let (best_quantity, best_units) = unit_conversion::best_unit_for(quantity, units);
+        if best_units == units {
+            return;
+        }
+        let best_quantity = quantity_display(best_quantity, &Some(best_units));
+        if ui
+            .small_button(
+                tr("convert to {quantity} {units}")
+                    .replace("{quantity}", &best_quantity)
+                    .replace("{units}", i18n::units(best_units)),
+            )
+            .clicked()
+        {
+            self.quantity = best_quantity;
+            self.quantity_units = Some(best_units);
+        }
+    }
 }
 
-pub struct RecipeIngredient {
-    pub id: IngredientUsageId,
-    pub ingredient: Ingredient,
-    pub quantity: f32,
-    pub quantity_units: Option<IngredientMeasurement>,
-    pub calories: Vec<IngredientCaloriesEntry>,
+/// Edits to the recipe's name, duration and description which haven't been saved yet.
+struct RecipeDraft {
+    name: String,
+    duration: RecipeDuration,
+    description: String,
 }
 
-impl RecipeIngredient {
-    fn calories(&self) -> Option<f32> {
-        use unit_conversion::{conversion_factor, MeasurementKind};
+impl RecipeDraft {
+    fn new(recipe: &Recipe) -> Self {
+        Self {
+            name: recipe.name.clone(),
+            duration: recipe.duration,
+            description: recipe.description.clone(),
+        }
+    }
 
-        for c in &self.calories {
-            if c.quantity_units == self.quantity_units {
-                return Some(c.calories * self.quantity / c.quantity);
-            }
+    fn is_changed(&self, recipe: &Recipe) -> bool {
+        self.name != recipe.name
+            || self.duration != recipe.duration
+            || self.description != recipe.description
+    }
+}
+
+/// Heights of the sections of the recipe window.
+struct SectionHeights {
+    info: f32,
+    controls: f32,
+    add_ingredient: f32,
+    edit_info: f32,
+    edit: f32,
+}
+
+/// Showing a recipe ingredient the way the settings ask for.
+pub trait RecipeIngredientExt {
+    /// The ingredient as it would be written in a recipe, like "2 cups onion, finely chopped".
+    fn display(&self, settings: &Settings) -> String;
+
+    /// Describes how `calories` was calculated, or why it couldn't be.
+    fn calories_explanation(&self) -> String;
+}
+
+impl RecipeIngredientExt for RecipeIngredient {
+    fn display(&self, settings: &Settings) -> String {
+        let (quantity, units) = unit_conversion::convert_quantity_to_system(
+            self.quantity,
+            self.quantity_units,
+            settings.measurement_system,
+        );
+        let mut line =
+            quantity_display_with_precision(quantity, &units, settings.fraction_precision);
+        if let Some(units) = units {
+            line += " ";
+            line += i18n::units(units);
         }
-        for c in &self.calories {
-            if let (Some(a), Some(b)) = (self.quantity_units, c.quantity_units) {
-                if MeasurementKind::from(a) == MeasurementKind::from(b) {
-                    return Some(c.calories * conversion_factor(a, b) * self.quantity / c.quantity);
-                }
-            }
+        line += " ";
+        line += &self.ingredient.name;
+        if !self.note.is_empty() {
+            line += ", ";
+            line += &self.note;
         }
-        None
+        line
     }
-}
 
-fn right_align_cell(ui: &mut egui::Ui, text: String) {
-    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-        ui.label(text);
-    });
+    fn calories_explanation(&self) -> String {
+        let (c, factor) = match self.calorie_source() {
+            Ok(source) => source,
+            Err(reason) => return tr("Calories unknown: {reason}").replace("{reason}", &reason),
+        };
+        let units = |u: &Option<IngredientMeasurement>| u.map(i18n::units).unwrap_or("");
+        let short = |v: f32| {
+            let s = format!("{v:.3}");
+            s.trim_end_matches('0').trim_end_matches('.').to_owned()
+        };
+        let mut explanation = tr("Using {calories} calories per {quantity} {units}")
+            .replace("{calories}", &c.calories.to_string())
+            .replace(
+                "{quantity}",
+                &quantity_display(c.quantity, &c.quantity_units),
+            )
+            .replace("{units}", units(&c.quantity_units));
+        let quantity = self.quantity * factor;
+        if self.quantity_units != c.quantity_units {
+            explanation += &format!(
+                "\n{} {} = {} {} (\u{d7} {})",
+                quantity_display(self.quantity, &self.quantity_units),
+                units(&self.quantity_units),
+                quantity_display(quantity, &c.quantity_units),
+                units(&c.quantity_units),
+                short(factor),
+            );
+        }
+        explanation += &format!(
+            "\n{} \u{d7} {} / {} = {:.2}",
+            c.calories,
+            short(quantity),
+            short(c.quantity),
+            c.calories * quantity / c.quantity
+        );
+        explanation
+    }
 }
 
 fn equal_epsilon(a: f32, b: f32, e: f32) -> bool {
@@ -95,20 +224,130 @@ fn fractional_quantity_display(q: f32) -> String {
     q.to_string()
 }
 
+const FRIENDLY_DENOMINATORS: [u32; 5] = [2, 3, 4, 8, 16];
+
+/// Rounds to the nearest fraction with a denominator commonly found on measuring cups and spoons.
+fn friendly_quantity_display(q: f32) -> String {
+    let mut whole_part = q as u32;
+    let frac = q - whole_part as f32;
+
+    let (mut numerator, mut denominator) = (0, 1);
+    let mut best_error = frac;
+    if 1.0 - frac < best_error {
+        (numerator, best_error) = (1, 1.0 - frac);
+    }
+    for d in FRIENDLY_DENOMINATORS {
+        for n in 1u32..d {
+            let error = (n as f32 / d as f32 - frac).abs();
+            if error < best_error {
+                (numerator, denominator, best_error) = (n, d, error);
+            }
+        }
+    }
+    if numerator == denominator {
+        whole_part += 1;
+        numerator = 0;
+    }
+
+    match (whole_part, numerator) {
+        // Don't round a small amount away to nothing
+        (0, 0) if q > 0.0 => q.to_string(),
+        (whole_part, 0) => whole_part.to_string(),
+        (0, n) => format!("{n}/{denominator}"),
+        (whole_part, n) => format!("{whole_part} {n}/{denominator}"),
+    }
+}
+
+#[test]
+fn friendly_quantity_display_test() {
+    assert_eq!(friendly_quantity_display(1.0), "1");
+    assert_eq!(friendly_quantity_display(0.5), "1/2");
+    assert_eq!(friendly_quantity_display(23.0 / 97.0), "1/4");
+    assert_eq!(friendly_quantity_display(0.3), "5/16");
+    assert_eq!(friendly_quantity_display(3.333), "3 1/3");
+    assert_eq!(friendly_quantity_display(1.98), "2");
+    assert_eq!(friendly_quantity_display(0.01), "0.01");
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum FractionPrecision {
+    #[default]
+    #[display("exact")]
+    Exact,
+    #[display("cooking-friendly (1/2, 1/3, 1/4, 1/8, 1/16)")]
+    Friendly,
+    #[display("kitchen measures (1/4 tsp., 5 g)")]
+    Kitchen,
+}
+
+impl FractionPrecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Friendly => "friendly",
+            Self::Kitchen => "kitchen",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|p| p.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+}
+
 pub fn quantity_display(q: f32, units: &Option<IngredientMeasurement>) -> String {
-    use unit_conversion::MeasurementClass;
+    quantity_display_with_precision(q, units, FractionPrecision::Exact)
+}
+
+pub fn quantity_display_with_precision(
+    q: f32,
+    units: &Option<IngredientMeasurement>,
+    precision: FractionPrecision,
+) -> String {
+    use unit_conversion::{KitchenMeasure, MeasurementClass};
 
     let measurement_class = units
         .as_ref()
         .map(|units| MeasurementClass::from(units.clone()))
         .unwrap_or(MeasurementClass::Us);
+    let q = match precision {
+        FractionPrecision::Kitchen => KitchenMeasure::for_units(*units).round(q),
+        _ => q,
+    };
     if measurement_class == MeasurementClass::Us {
-        fractional_quantity_display(q)
+        match precision {
+            FractionPrecision::Exact | FractionPrecision::Kitchen => fractional_quantity_display(q),
+            FractionPrecision::Friendly => friendly_quantity_display(q),
+        }
+    } else if precision == FractionPrecision::Kitchen {
+        // Rounding to a step like 0.05 leaves a little float error behind.
+        let s = format!("{q:.3}");
+        s.trim_end_matches('0').trim_end_matches('.').to_owned()
     } else {
         q.to_string()
     }
 }
 
+/// Shows a quantity, with the exact value on hover if it was rounded for display.
+pub fn quantity_label(
+    ui: &mut egui::Ui,
+    q: f32,
+    units: &Option<IngredientMeasurement>,
+    precision: FractionPrecision,
+) -> egui::Response {
+    let text = quantity_display_with_precision(q, units, precision);
+    let rounded = quantity_parse(&text).is_none_or(|p| !equal_epsilon(p, q, 0.001));
+    let label = ui.label(text);
+    if rounded {
+        label.on_hover_text(format!("exactly {q}"))
+    } else {
+        label
+    }
+}
+
 #[test]
 fn quantity_display_test() {
     assert_eq!(quantity_display(1.0, &None), "1");
@@ -122,50 +361,199 @@ fn quantity_display_test() {
         quantity_display(0.333, &Some(IngredientMeasurement::Liters)),
         "0.333"
     );
-}
+    assert_eq!(
+        quantity_display_with_precision(0.3, &None, FractionPrecision::Friendly),
+        "5/16"
+    );
+    assert_eq!(
+        quantity_display_with_precision(
+            0.3,
+            &Some(IngredientMeasurement::Liters),
+            FractionPrecision::Friendly
+        ),
+        "0.3"
+    );
 
-pub fn quantity_parse(q: &str) -> Option<f32> {
-    use std::str::FromStr as _;
+    let kitchen = |q, units| quantity_display_with_precision(q, &units, FractionPrecision::Kitchen);
+    assert_eq!(kitchen(2.0 / 3.0, Some(IngredientMeasurement::Cups)), "2/3");
+    assert_eq!(kitchen(0.3, Some(IngredientMeasurement::Teaspoons)), "1/4");
+    assert_eq!(kitchen(412.3, Some(IngredientMeasurement::Grams)), "410");
+    assert_eq!(kitchen(1.23, Some(IngredientMeasurement::Liters)), "1.25");
+    assert_eq!(
+        kitchen(1.0 / 3.0, Some(IngredientMeasurement::Milliliters)),
+        "0.333"
+    );
+}
 
-    if q.contains(" ") {
-        let parts: Vec<_> = q.split(" ").filter_map(|p| quantity_parse(p)).collect();
-        if !parts.is_empty() {
-            return Some(parts.into_iter().sum());
-        } else {
-            return None;
+#[test]
+fn quantity_display_parse_roundtrip() {
+    for w in 0u32..=100 {
+        for f in 0u32..=1000 {
+            let v = w as f32 + (f as f32) / 1000.0;
+            let formatted = quantity_display(v, &None);
+            let parsed = quantity_parse(&formatted).unwrap();
+            assert!(equal_epsilon(parsed, v, 0.01));
         }
     }
+}
 
-    if q.contains("/") {
-        let parts: Vec<_> = q.split("/").collect();
-        if parts.len() != 2 {
-            return None;
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn quantity_display_parse_roundtrip_with_units(
+        v in 0.0f32..1000.0,
+        units in proptest::option::of(proptest::sample::select(
+            IngredientMeasurement::iter().collect::<Vec<_>>()
+        )),
+        precision in proptest::sample::select(FractionPrecision::iter().collect::<Vec<_>>()),
+        before in "[ \t]*",
+        after in "[ \t]*",
+    ) {
+        let formatted = quantity_display_with_precision(v, &units, precision);
+        let parsed = quantity_parse(&format!("{before}{formatted}{after}")).unwrap();
+        // Friendly fractions are only as close as the nearest 1/16th.
+        let e: f32 = match precision {
+            FractionPrecision::Exact => 0.01,
+            FractionPrecision::Friendly => 1.0 / 32.0 + 0.001,
+            // Kitchen measures are as coarse as 10 milligrams.
+            FractionPrecision::Kitchen => 5.0 + 0.001,
+        };
+        proptest::prop_assert!(equal_epsilon(parsed, v, e), "{formatted:?}");
+
+        let with_units = match units {
+            Some(units) => format!("{formatted} {}", i18n::units(units)),
+            None => formatted.clone(),
+        };
+        let (parsed, parsed_units) = quantity_with_unit_parse(&with_units).unwrap();
+        proptest::prop_assert!(equal_epsilon(parsed, v, e), "{with_units:?}");
+        proptest::prop_assert_eq!(parsed_units, units);
+    }
+}
+
+fn find_matches(text: &str, query: &str) -> Vec<std::ops::Range<usize>> {
+    let mut matches = vec![];
+    if query.is_empty() {
+        return matches;
+    }
+
+    let mut start = 0;
+    while let Some(first) = text[start..].chars().next() {
+        let mut haystack = text[start..].char_indices();
+        let mut needle = query.chars();
+        let end = loop {
+            match (needle.next(), haystack.next()) {
+                (None, rest) => {
+                    break Some(start + rest.map(|(i, _)| i).unwrap_or(text.len() - start))
+                }
+                (Some(_), None) => break None,
+                (Some(n), Some((_, h))) => {
+                    if !n.to_lowercase().eq(h.to_lowercase()) {
+                        break None;
+                    }
+                }
+            }
+        };
+        if let Some(end) = end {
+            matches.push(start..end);
+            start = end;
+        } else {
+            start += first.len_utf8();
         }
-        let n = f32::from_str(parts[0]).ok()?;
-        let d = f32::from_str(parts[1]).ok()?;
-        Some(n / d)
-    } else {
-        q.parse().ok()
     }
+    matches
 }
 
 #[test]
-fn quantity_parse_test() {
-    assert_eq!(quantity_parse("1/2").unwrap(), 0.5);
-    assert_eq!(quantity_parse("1 1/2").unwrap(), 1.5);
-    assert!(equal_epsilon(quantity_parse("1/3").unwrap(), 0.333, 0.001));
-    assert_eq!(quantity_parse("3").unwrap(), 3.0);
-    assert_eq!(quantity_parse("0.123").unwrap(), 0.123);
+fn find_matches_test() {
+    assert_eq!(find_matches("Stir the stew", "st"), vec![0..2, 9..11]);
+    assert_eq!(find_matches("aaaa", "aa"), vec![0..2, 2..4]);
+    assert_eq!(find_matches("crème brûlée", "BRÛ"), vec![7..11]);
+    assert_eq!(find_matches("bake", "baked"), vec![]);
+    assert_eq!(find_matches("bake", ""), vec![]);
 }
 
-#[test]
-fn quantity_display_parse_roundtrip() {
-    for w in 0u32..=100 {
-        for f in 0u32..=1000 {
-            let v = w as f32 + (f as f32) / 1000.0;
-            let formatted = quantity_display(v, &None);
-            let parsed = quantity_parse(&formatted).unwrap();
-            assert!(equal_epsilon(parsed, v, 0.01));
+#[derive(Default)]
+struct DescriptionFind {
+    query: String,
+    current: usize,
+    scroll_to_current: bool,
+    focus_query: bool,
+}
+
+impl DescriptionFind {
+    fn new() -> Self {
+        Self {
+            focus_query: true,
+            ..Default::default()
+        }
+    }
+
+    fn step(&mut self, num_matches: usize, forward: bool) {
+        if num_matches == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % num_matches
+        } else {
+            (self.current + num_matches - 1) % num_matches
+        };
+        self.scroll_to_current = true;
+    }
+
+    fn layout_job(
+        &self,
+        ui: &egui::Ui,
+        text: &str,
+        color: egui::Color32,
+        wrap_width: f32,
+    ) -> egui::text::LayoutJob {
+        let font_id = egui::TextStyle::Body.resolve(ui.style());
+        let plain = egui::TextFormat::simple(font_id, color);
+        let highlighted = |background: egui::Color32| egui::TextFormat {
+            background,
+            ..plain.clone()
+        };
+        let selection = ui.visuals().selection.bg_fill;
+
+        let mut job = egui::text::LayoutJob::default();
+        job.wrap.max_width = wrap_width;
+        let mut last = 0;
+        for (i, m) in find_matches(text, &self.query).into_iter().enumerate() {
+            job.append(&text[last..m.start], 0.0, plain.clone());
+            let background = if i == self.current {
+                selection
+            } else {
+                selection.gamma_multiply(0.4)
+            };
+            job.append(&text[m.clone()], 0.0, highlighted(background));
+            last = m.end;
+        }
+        job.append(&text[last..], 0.0, plain);
+        job
+    }
+
+    fn scroll_to_current(
+        &mut self,
+        ui: &egui::Ui,
+        text: &str,
+        galley: &egui::Galley,
+        galley_pos: egui::Pos2,
+    ) {
+        if !self.scroll_to_current {
+            return;
+        }
+        self.scroll_to_current = false;
+
+        if let Some(m) = find_matches(text, &self.query).get(self.current) {
+            let start = text[..m.start].chars().count();
+            let end = start + text[m.clone()].chars().count();
+            let rect = galley
+                .pos_from_ccursor(egui::text::CCursor::new(start))
+                .union(galley.pos_from_ccursor(egui::text::CCursor::new(end)));
+            ui.scroll_to_rect(
+                rect.translate(galley_pos.to_vec2()),
+                Some(egui::Align::Center),
+            );
         }
     }
 }
@@ -175,6 +563,20 @@ pub enum UpdateEvent {
     Renamed(Recipe),
     Scheduled(chrono::NaiveWeek),
     CategoryChanged,
+    /// Something shown in the recipe list about the recipe changed, like its rating.
+    DetailsChanged(RecipeCategoryId),
+    /// Archived or brought back, so it's left out of or back in its category's list.
+    ArchivedChanged(RecipeCategoryId),
+    IngredientsChanged,
+    IngredientCreated,
+    Cook(RecipeId),
+}
+
+/// A change to one of the recipe's ingredients, so that only its row needs updating.
+enum UsageChange {
+    /// Added or edited, so the row is read again.
+    Saved(IngredientUsageId),
+    Removed(IngredientUsageId),
 }
 
 pub struct RecipeWindow {
@@ -182,6 +584,7 @@ pub struct RecipeWindow {
 
     ingredients: Vec<RecipeIngredient>,
     ingredient_being_edited: Option<IngredientBeingEdited>,
+    ingredient_categories: HashMap<IngredientCategoryId, String>,
 
     new_ingredient_name: String,
     new_ingredient: Option<Ingredient>,
@@ -193,7 +596,20 @@ pub struct RecipeWindow {
     new_category: Option<RecipeCategoryId>,
     cached_category_search: Option<query::CachedQuery<RecipeCategoryId>>,
 
+    find: Option<DescriptionFind>,
+
+    photos: Vec<RecipePhoto>,
+    journal: RecipeJournal,
+    /// Made when the share menu is opened, so it has any changes to the recipe.
+    #[cfg(feature = "share")]
+    share: Option<RecipeShare>,
+
+    draft: RecipeDraft,
     edit_mode: bool,
+    /// Kept above the other windows, for cross-referencing with another recipe.
+    pinned: bool,
+    /// Shown in its own native window, which can go on another monitor.
+    detached: bool,
 }
 
 impl RecipeWindow {
@@ -202,26 +618,41 @@ impl RecipeWindow {
         recipe_id: RecipeId,
         selected_week: Option<chrono::NaiveWeek>,
         edit_mode: bool,
-    ) -> Self {
-        let (recipe, category_name, ingredients) = query::get_recipe(conn, recipe_id);
-        Self {
+    ) -> QueryResult<Self> {
+        let (recipe, category_name, ingredients) = query::get_recipe(conn, recipe_id)?;
+        Ok(Self {
+            draft: RecipeDraft::new(&recipe),
             recipe,
 
             ingredients,
             ingredient_being_edited: None,
+            ingredient_categories: query::get_ingredient_category_names(conn)?,
 
             new_ingredient_name: String::new(),
             new_ingredient: None,
             cached_ingredient_search: None,
 
-            week: RecipeWeek::new(conn, selected_week.unwrap_or_else(|| this_week())),
+            // Moved to the week start in the settings when the controls are shown.
+            week: RecipeWeek::new(
+                conn,
+                selected_week.unwrap_or_else(|| this_week(chrono::Weekday::Sun)),
+            ),
 
             new_category_name: category_name,
             new_category: None,
             cached_category_search: None,
 
+            find: None,
+
+            photos: query::get_recipe_photos(conn, recipe_id)?,
+            journal: RecipeJournal::new(conn, recipe_id)?,
+            #[cfg(feature = "share")]
+            share: None,
+
             edit_mode,
-        }
+            pinned: false,
+            detached: false,
+        })
     }
 
     fn update_ingredient_editing(
@@ -231,7 +662,7 @@ impl RecipeWindow {
         usage: &RecipeIngredient,
         row: &mut egui_extras::TableRow<'_, '_>,
         ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
-        refresh_self: &mut bool,
+        changed_usages: &mut Vec<UsageChange>,
     ) -> bool {
         let Some(e) = &mut self.ingredient_being_edited else {
             return false;
@@ -245,33 +676,53 @@ impl RecipeWindow {
                     e.usage_id,
                     &mut e.new_ingredient_name,
                     &mut e.ingredient,
-                    |query| query::search_ingredients(conn, &mut e.cached_ingredient_search, query),
+                    |query| {
+                        query::search_ingredients(conn, &mut e.cached_ingredient_search, query)
+                            .unwrap_or_report()
+                    },
                 )
                 .desired_width(ui.available_width() - 20.0),
             );
         });
+        row.col(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut e.note)
+                    .hint_text(tr("e.g. finely chopped"))
+                    .desired_width(f32::INFINITY),
+            );
+        });
 
         row.col(|ui| {
-            if let Some(Ingredient {
-                category: Some(category),
-                ..
-            }) = &e.ingredient
-            {
-                ui.label(category.as_str());
-            } else {
-                ui.label("");
-            }
+            let category = e
+                .ingredient
+                .as_ref()
+                .and_then(|i| i.category_id)
+                .and_then(|c| self.ingredient_categories.get(&c));
+            ui.label(category.map(|c| c.as_str()).unwrap_or(""));
+            e.update_unit_suggestion(ui);
         });
         row.col(|ui| {
-            ui.add(egui::TextEdit::singleline(&mut e.quantity));
+            if ui
+                .add(egui::TextEdit::singleline(&mut e.quantity))
+                .changed()
+            {
+                if let Some((_, Some(units))) = quantity_with_unit_parse(&e.quantity) {
+                    e.quantity_units = Some(units);
+                }
+            }
         });
         row.col(|ui| {
             egui::ComboBox::from_id_salt(("recipe ingredient quantity units", self.recipe.id))
-                .selected_text(e.quantity_units.as_ref().map(|q| q.as_str()).unwrap_or(""))
+                .selected_text(
+                    e.quantity_units
+                        .as_ref()
+                        .map(|q| i18n::units(*q))
+                        .unwrap_or(""),
+                )
                 .width(40.0)
                 .show_ui(ui, |ui| {
-                    for m in IngredientMeasurement::iter() {
-                        ui.selectable_value(&mut e.quantity_units, Some(m), m.as_str());
+                    for m in unit_choices(ui.ctx()).units() {
+                        ui.selectable_value(&mut e.quantity_units, Some(m), i18n::units(m));
                     }
                     ui.selectable_value(&mut e.quantity_units, None, "");
                 });
@@ -279,7 +730,7 @@ impl RecipeWindow {
         row.col(|ui| {
             if let Some(ingredient) = &e.ingredient {
                 let mut calories_shown = ingredient_calories_windows.contains_key(&ingredient.id);
-                ui.toggle_value(&mut calories_shown, "edit");
+                ui.toggle_value(&mut calories_shown, tr("edit"));
                 if calories_shown && !ingredient_calories_windows.contains_key(&ingredient.id) {
                     ingredient_calories_windows.insert(
                         ingredient.id,
@@ -290,68 +741,112 @@ impl RecipeWindow {
                 }
             }
         });
+        let mut saved = false;
+        let mut cancelled = false;
         row.col(|ui| {
-            if ui.button("Save").clicked() {
+            if ui.button(tr("Save")).clicked() {
                 if e.ingredient.is_some() {
                     query::edit_recipe_ingredient(
                         conn,
                         e.usage_id,
                         e.ingredient.as_ref().unwrap(),
-                        quantity_parse(&e.quantity).unwrap_or(0.0),
+                        e.parsed_quantity().unwrap_or(0.0),
                         e.quantity_units,
-                    );
-                    *refresh_self = true;
+                        &e.note,
+                    )
+                    .or_report();
+                    changed_usages.push(UsageChange::Saved(e.usage_id));
+                    saved = true;
                 } else {
-                    toasts.add(new_error_toast("Couldn't find ingredient"));
+                    toasts.add(new_error_toast(tr("Couldn't find ingredient")));
                 }
             }
+            cancelled = ui.button(tr("Cancel")).clicked();
         });
+        if saved || cancelled {
+            self.ingredient_being_edited = None;
+        }
         true
     }
 
     fn update_ingredient_row(
         &mut self,
         conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
         usage: &RecipeIngredient,
         row: &mut egui_extras::TableRow<'_, '_>,
-        refresh_self: &mut bool,
+        changed_usages: &mut Vec<UsageChange>,
     ) {
         row.col(|ui| {
             ui.label(&usage.ingredient.name);
         });
         row.col(|ui| {
-            ui.label(usage.ingredient.category.as_deref().unwrap_or(""));
+            ui.weak(&usage.note);
+        });
+        row.col(|ui| {
+            let category = usage
+                .ingredient
+                .category_id
+                .and_then(|c| self.ingredient_categories.get(&c));
+            ui.label(category.map(|c| c.as_str()).unwrap_or(""));
+        });
+        let (quantity, quantity_units) = unit_conversion::convert_quantity_to_system(
+            usage.quantity,
+            usage.quantity_units,
+            settings.measurement_system,
+        );
+        row.col(|ui| {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                quantity_label(ui, quantity, &quantity_units, settings.fraction_precision);
+            });
         });
-        row.col(|ui| right_align_cell(ui, quantity_display(usage.quantity, &usage.quantity_units)));
         row.col(|ui| {
             ui.label(
-                usage
-                    .quantity_units
+                quantity_units
                     .as_ref()
-                    .map(|c| c.as_str())
+                    .map(|c| i18n::units(*c))
                     .unwrap_or(""),
             );
         });
         row.col(|ui| {
-            right_align_cell(
-                ui,
-                usage
-                    .calories()
-                    .map(|c| format!("{c:.2}"))
-                    .unwrap_or_default(),
-            )
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                match usage.calories() {
+                    Some(c) => ui.label(format!("{c:.2}")),
+                    None => ui.weak("?"),
+                }
+                .on_hover_text(usage.calories_explanation());
+            });
         });
 
         if self.edit_mode {
             row.col(|ui| {
                 if self.ingredient_being_edited.is_none() {
                     ui.horizontal(|ui| {
-                        if ui.button("Edit").clicked() {
+                        if ui.button(tr("Edit")).clicked() {
                             self.ingredient_being_edited = Some(IngredientBeingEdited::new(usage));
                         }
-                        if ui.button("Delete").clicked() {
-                            query::delete_recipe_ingredient(conn, usage.id);
-                            *refresh_self = true;
+                        if ui.button(tr("Delete")).clicked()
+                            && query::delete_recipe_ingredient(conn, usage.id)
+                                .or_report()
+                                .is_some()
+                        {
+                            undo::add_undo_toast(
+                                ui.ctx(),
+                                toasts,
+                                tr("Removed {ingredient} from {recipe}")
+                                    .replace("{ingredient}", &usage.ingredient.name)
+                                    .replace("{recipe}", &self.recipe.name),
+                                UndoAction::RemovedRecipeIngredient(IngredientUsage {
+                                    id: usage.id,
+                                    recipe_id: self.recipe.id,
+                                    ingredient_id: usage.ingredient.id,
+                                    quantity: usage.quantity,
+                                    quantity_units: usage.quantity_units,
+                                    note: usage.note.clone(),
+                                }),
+                            );
+                            changed_usages.push(UsageChange::Removed(usage.id));
                         }
                     });
                 }
@@ -363,9 +858,10 @@ impl RecipeWindow {
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
         body: &mut egui_extras::TableBody<'_>,
         ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
-        refresh_self: &mut bool,
+        changed_usages: &mut Vec<UsageChange>,
     ) {
         let ingredients = std::mem::take(&mut self.ingredients);
         for usage in &ingredients {
@@ -376,11 +872,11 @@ impl RecipeWindow {
                     usage,
                     &mut row,
                     ingredient_calories_windows,
-                    refresh_self,
+                    changed_usages,
                 ) {
                     return;
                 }
-                self.update_ingredient_row(conn, usage, &mut row, refresh_self);
+                self.update_ingredient_row(conn, toasts, settings, usage, &mut row, changed_usages);
             });
         }
         self.ingredients = ingredients;
@@ -391,8 +887,9 @@ impl RecipeWindow {
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
         ui: &mut egui::Ui,
-        refresh_self: &mut bool,
-    ) {
+        changed_usages: &mut Vec<UsageChange>,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
         ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
             egui_extras::StripBuilder::new(ui)
                 .size(egui_extras::Size::exact(100.0))
@@ -400,10 +897,11 @@ impl RecipeWindow {
                 .size(egui_extras::Size::exact(40.0))
                 .horizontal(|mut strip| {
                     strip.cell(|ui| {
-                        ui.label("Add Ingredient:");
+                        ui.label(tr("Add Ingredient:"));
                     });
 
                     let mut added = false;
+                    let mut create = false;
 
                     strip.cell(|ui| {
                         added |= ui
@@ -418,39 +916,62 @@ impl RecipeWindow {
                                             &mut self.cached_ingredient_search,
                                             query,
                                         )
+                                        .unwrap_or_report()
                                     },
                                 )
-                                .hint_text("search for ingredient")
-                                .desired_width(f32::INFINITY),
+                                .hint_text(tr("search for ingredient"))
+                                .desired_width(f32::INFINITY)
+                                .offer_create(&mut create),
                             )
                             .pressed_enter();
                     });
 
                     strip.cell(|ui| {
-                        added |= ui.button("Add").clicked();
+                        added |= ui.button(tr("Add")).clicked();
                     });
 
+                    if create {
+                        if let Some(ingredient) =
+                            query::add_ingredient(conn, self.new_ingredient_name.trim()).or_report()
+                        {
+                            self.new_ingredient = Some(ingredient);
+                            self.cached_ingredient_search = None;
+                            events.push(UpdateEvent::IngredientCreated);
+                            added = true;
+                        }
+                    }
+
                     if added {
                         if let Some(ingredient) = &self.new_ingredient {
-                            query::add_recipe_ingredient(conn, self.recipe.id, ingredient.id, 1.0);
+                            if let Some(usage_id) = query::add_recipe_ingredient(
+                                conn,
+                                self.recipe.id,
+                                ingredient.id,
+                                1.0,
+                            )
+                            .or_report()
+                            {
+                                changed_usages.push(UsageChange::Saved(usage_id));
+                            }
                             self.new_ingredient_name = "".into();
                             self.new_ingredient = None;
-                            *refresh_self = true;
                         } else {
-                            toasts.add(new_error_toast("Couldn't find ingredient"));
+                            toasts.add(new_error_toast(tr("Couldn't find ingredient")));
                         }
                     }
                 });
         });
+        events
     }
 
     fn update_ingredients_edit_mode(
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
         ui: &mut egui::Ui,
         ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
-        refresh_self: &mut bool,
+        changed_usages: &mut Vec<UsageChange>,
     ) {
         let available_height = ui.available_height();
         egui_extras::TableBuilder::new(ui)
@@ -459,6 +980,7 @@ impl RecipeWindow {
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
             .column(egui_extras::Column::remainder())
             .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::remainder())
             .column(egui_extras::Column::exact(40.0))
             .column(egui_extras::Column::exact(40.0))
             .column(egui_extras::Column::exact(40.0))
@@ -467,19 +989,22 @@ impl RecipeWindow {
             .max_scroll_height(available_height)
             .header(20.0, |mut header| {
                 header.col(|ui| {
-                    ui.heading("Name");
+                    ui.heading(tr("Name"));
+                });
+                header.col(|ui| {
+                    ui.heading(tr("Note"));
                 });
                 header.col(|ui| {
-                    ui.heading("Category");
+                    ui.heading(tr("Category"));
                 });
                 header.col(|ui| {
-                    ui.heading("Qty");
+                    ui.heading(tr("Qty"));
                 });
                 header.col(|ui| {
                     ui.heading("");
                 });
                 header.col(|ui| {
-                    ui.heading("Cal.");
+                    ui.heading(tr("Cal."));
                 });
                 header.col(|ui| {
                     ui.heading("");
@@ -489,9 +1014,10 @@ impl RecipeWindow {
                 self.update_ingredients_table(
                     conn,
                     toasts,
+                    settings,
                     &mut body,
                     ingredient_calories_windows,
-                    refresh_self,
+                    changed_usages,
                 );
             });
     }
@@ -500,8 +1026,9 @@ impl RecipeWindow {
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
         ui: &mut egui::Ui,
-        refresh_self: &mut bool,
+        changed_usages: &mut Vec<UsageChange>,
     ) {
         let available_height = ui.available_height();
         egui_extras::TableBuilder::new(ui)
@@ -510,6 +1037,7 @@ impl RecipeWindow {
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
             .column(egui_extras::Column::remainder())
             .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::remainder())
             .column(egui_extras::Column::exact(40.0))
             .column(egui_extras::Column::exact(30.0))
             .column(egui_extras::Column::exact(60.0))
@@ -517,28 +1045,32 @@ impl RecipeWindow {
             .max_scroll_height(available_height)
             .header(20.0, |mut header| {
                 header.col(|ui| {
-                    ui.heading("Name");
+                    ui.heading(tr("Name"));
+                });
+                header.col(|ui| {
+                    ui.heading(tr("Note"));
                 });
                 header.col(|ui| {
-                    ui.heading("Category");
+                    ui.heading(tr("Category"));
                 });
                 header.col(|ui| {
-                    ui.heading("Qty");
+                    ui.heading(tr("Qty"));
                 });
                 header.col(|ui| {
                     ui.heading("");
                 });
                 header.col(|ui| {
-                    ui.heading("Cal.");
+                    ui.heading(tr("Cal."));
                 });
             })
             .body(|mut body| {
                 self.update_ingredients_table(
                     conn,
                     toasts,
+                    settings,
                     &mut body,
                     &mut Default::default(),
-                    refresh_self,
+                    changed_usages,
                 );
             });
     }
@@ -568,19 +1100,13 @@ impl RecipeWindow {
                         .size(egui_extras::Size::remainder())
                         .horizontal(|mut strip| {
                             strip.cell(|ui| {
-                                ui.label("Name:");
+                                ui.label(tr("Name:"));
                             });
                             strip.cell(|ui| {
-                                let mut name = self.recipe.name.clone();
                                 ui.add(
-                                    egui::TextEdit::singleline(&mut name)
+                                    egui::TextEdit::singleline(&mut self.draft.name)
                                         .desired_width(f32::INFINITY),
                                 );
-                                if name != self.recipe.name {
-                                    query::edit_recipe_name(conn, self.recipe.id, &name);
-                                    self.recipe.name = name.clone();
-                                    events.push(UpdateEvent::Renamed(self.recipe.clone()));
-                                }
                             });
                         });
                 });
@@ -591,7 +1117,7 @@ impl RecipeWindow {
                         .size(egui_extras::Size::exact(40.0))
                         .horizontal(|mut strip| {
                             strip.cell(|ui| {
-                                ui.label("Category:");
+                                ui.label(tr("Category:"));
                             });
                             let mut saved = false;
                             strip.cell(|ui| {
@@ -607,23 +1133,26 @@ impl RecipeWindow {
                                                     &mut self.cached_category_search,
                                                     query,
                                                 )
+                                                .unwrap_or_report()
                                             },
                                         )
                                         .desired_width(f32::INFINITY)
-                                        .hint_text("search for category"),
+                                        .hint_text(tr("search for category")),
                                     )
                                     .pressed_enter();
                             });
                             let e = !self.new_category_name.is_empty();
                             strip.cell(|ui| {
-                                saved |= ui.add_enabled(e, egui::Button::new("Save")).clicked();
+                                saved |= ui.add_enabled(e, egui::Button::new(tr("Save"))).clicked();
                             });
                             if saved && e {
                                 if let Some(cat) = self.new_category {
-                                    query::edit_recipe_category(conn, self.recipe.id, cat);
+                                    query::edit_recipe_category(conn, self.recipe.id, cat)
+                                        .or_report();
                                     events.push(UpdateEvent::CategoryChanged);
                                 } else {
-                                    toasts.add(new_error_toast("Couldn't find recipe category"));
+                                    toasts
+                                        .add(new_error_toast(tr("Couldn't find recipe category")));
                                 }
                             }
                         });
@@ -634,22 +1163,18 @@ impl RecipeWindow {
                         .size(egui_extras::Size::remainder())
                         .horizontal(|mut strip| {
                             strip.cell(|ui| {
-                                ui.label("Duration:");
+                                ui.label(tr("Duration:"));
                             });
 
                             strip.cell(|ui| {
-                                let mut selected = self.recipe.duration.clone();
+                                let selected = &mut self.draft.duration;
                                 egui::ComboBox::from_id_salt(("recipe duration", self.recipe.id))
-                                    .selected_text(&selected.to_string())
+                                    .selected_text(selected.to_string())
                                     .show_ui(ui, |ui| {
                                         for d in RecipeDuration::iter() {
-                                            ui.selectable_value(&mut selected, d, d.to_string());
+                                            ui.selectable_value(selected, d, d.to_string());
                                         }
                                     });
-                                if selected != self.recipe.duration {
-                                    query::edit_recipe_duration(conn, self.recipe.id, selected);
-                                    self.recipe.duration = selected;
-                                }
                             });
                         });
                 });
@@ -659,35 +1184,234 @@ impl RecipeWindow {
                         .size(egui_extras::Size::remainder())
                         .horizontal(|mut strip| {
                             strip.cell(|ui| {
-                                ui.label("Description:");
+                                ui.label(tr("Description:"));
                             });
                             strip.cell(|ui| {
-                                let mut description = self.recipe.description.clone();
+                                let description = &mut self.draft.description;
                                 egui::ScrollArea::vertical().show(ui, |ui| {
-                                    ui.add(
-                                        egui::TextEdit::multiline(&mut description)
-                                            .desired_width(f32::INFINITY),
-                                    );
+                                    let find = &self.find;
+                                    let mut layouter = |ui: &egui::Ui, text: &str, width: f32| {
+                                        let color = ui.visuals().widgets.inactive.text_color();
+                                        let job = find
+                                            .as_ref()
+                                            .unwrap()
+                                            .layout_job(ui, text, color, width);
+                                        ui.fonts(|f| f.layout_job(job))
+                                    };
+                                    let mut edit = egui::TextEdit::multiline(description)
+                                        .desired_width(f32::INFINITY);
+                                    if find.is_some() {
+                                        edit = edit.layouter(&mut layouter);
+                                    }
+                                    let output = edit.show(ui);
+                                    if let Some(find) = &mut self.find {
+                                        find.scroll_to_current(
+                                            ui,
+                                            description,
+                                            &output.galley,
+                                            output.galley_pos,
+                                        );
+                                    }
                                 });
-                                if description != self.recipe.description {
-                                    query::edit_recipe_description(
-                                        conn,
-                                        self.recipe.id,
-                                        &description,
-                                    );
-                                    self.recipe.description = description;
-                                }
                             });
                         });
                 });
                 strip.cell(|ui| {
-                    ui.label(format!("Total Calories:   {}", self.total_calories()));
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{}   {}",
+                            tr("Total Calories:"),
+                            self.total_calories()
+                        ));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let changed = self.draft.is_changed(&self.recipe);
+                            if ui
+                                .add_enabled(changed, egui::Button::new(tr("Revert")))
+                                .clicked()
+                            {
+                                self.draft = RecipeDraft::new(&self.recipe);
+                            }
+                            if ui
+                                .add_enabled(changed, egui::Button::new(tr("Save")))
+                                .clicked()
+                            {
+                                events.extend(self.save_draft(conn, toasts));
+                            }
+                        });
+                    });
                 });
             });
         events
     }
 
-    fn update_recipe_information(&mut self, ui: &mut egui::Ui) {
+    fn save_draft(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let name = self.draft.name.trim();
+        if name.is_empty() {
+            toasts.add(new_error_toast(tr("Recipe needs a name")));
+            return events;
+        }
+        // Anything which fails to save is left in the draft
+        if name != self.recipe.name
+            && query::edit_recipe_name(conn, self.recipe.id, name)
+                .or_report()
+                .is_some()
+        {
+            self.recipe.name = name.into();
+            events.push(UpdateEvent::Renamed(self.recipe.clone()));
+        }
+        if self.draft.duration != self.recipe.duration
+            && query::edit_recipe_duration(conn, self.recipe.id, self.draft.duration)
+                .or_report()
+                .is_some()
+        {
+            self.recipe.duration = self.draft.duration;
+            events.push(UpdateEvent::DetailsChanged(self.recipe.category));
+        }
+        if self.draft.description != self.recipe.description
+            && query::edit_recipe_description(conn, self.recipe.id, &self.draft.description)
+                .or_report()
+                .is_some()
+        {
+            self.recipe.description = self.draft.description.clone();
+        }
+        if self.draft.name.trim() == self.recipe.name {
+            self.draft.name = self.recipe.name.clone();
+        }
+        events
+    }
+
+    /// Whether there are edits which would be lost if the window were closed.
+    fn has_unsaved_changes(&self) -> bool {
+        let ingredient_changed = self.ingredient_being_edited.as_ref().is_some_and(|e| {
+            self.ingredients
+                .iter()
+                .find(|usage| usage.id == e.usage_id)
+                .is_some_and(|usage| e.is_changed(usage))
+        });
+        self.draft.is_changed(&self.recipe) || ingredient_changed
+    }
+
+    /// Stars which set the rating when clicked, or clear it when the current rating is clicked.
+    fn update_rating(
+        &mut self,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+    ) -> Option<UpdateEvent> {
+        let rating = self.recipe.rating;
+        let mut new_rating = rating;
+        ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            for star in 1..=5 {
+                let text = if rating.is_some_and(|r| r >= star) {
+                    "★"
+                } else {
+                    "☆"
+                };
+                let clicked = ui
+                    .add(egui::Button::new(text).frame(false))
+                    .on_hover_text(tr("{star} out of 5").replace("{star}", &star.to_string()))
+                    .clicked();
+                if clicked {
+                    new_rating = (rating != Some(star)).then_some(star);
+                }
+            }
+        });
+        if new_rating == rating {
+            return None;
+        }
+        query::edit_recipe_rating(conn, self.recipe.id, new_rating).or_report()?;
+        self.recipe.rating = new_rating;
+        Some(UpdateEvent::DetailsChanged(self.recipe.category))
+    }
+
+    /// Marks the recipe as one to try, or as tried.
+    fn update_status(
+        &mut self,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+    ) -> Option<UpdateEvent> {
+        let mut want_to_try = self.recipe.status == RecipeStatus::WantToTry;
+        let changed = ui
+            .add_enabled(
+                is_editable(ui.ctx()),
+                egui::Checkbox::new(&mut want_to_try, tr("Want to try")),
+            )
+            .on_hover_text(tr("Listed in the Try Next window until it's cooked"))
+            .changed();
+        if !changed {
+            return None;
+        }
+        let status = if want_to_try {
+            RecipeStatus::WantToTry
+        } else {
+            RecipeStatus::Tried
+        };
+        query::edit_recipe_status(conn, self.recipe.id, status).or_report()?;
+        self.recipe.status = status;
+        Some(UpdateEvent::DetailsChanged(self.recipe.category))
+    }
+
+    /// How many people the recipe feeds, for scaling the shopping list to how many are eating.
+    fn update_servings(&mut self, conn: &mut database::Connection, ui: &mut egui::Ui) {
+        let label = ui.label(tr("Serves:"));
+        let mut servings = self.recipe.servings.unwrap_or(0);
+        let changed = ui
+            .add_enabled(
+                is_editable(ui.ctx()),
+                egui::DragValue::new(&mut servings)
+                    .range(0..=99)
+                    .custom_formatter(|n, _| if n == 0.0 { "-".into() } else { n.to_string() }),
+            )
+            .labelled_by(label.id)
+            .on_hover_text(tr("How many people it feeds as written"))
+            .changed();
+        if changed {
+            let servings = (servings > 0).then_some(servings);
+            if query::edit_recipe_servings(conn, self.recipe.id, servings)
+                .or_report()
+                .is_some()
+            {
+                self.recipe.servings = servings;
+            }
+        }
+    }
+
+    /// Archives the recipe, or brings it back.
+    fn update_archived(
+        &mut self,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+    ) -> Option<UpdateEvent> {
+        let mut archived = self.recipe.archived;
+        let changed = ui
+            .add_enabled(
+                is_editable(ui.ctx()),
+                egui::Checkbox::new(&mut archived, tr("Archived")),
+            )
+            .on_hover_text(tr(
+                "Left out of the recipe lists and searches, but still on the calendar where it was",
+            ))
+            .changed();
+        if !changed {
+            return None;
+        }
+        query::edit_recipe_archived(conn, self.recipe.id, archived).or_report()?;
+        self.recipe.archived = archived;
+        Some(UpdateEvent::ArchivedChanged(self.recipe.category))
+    }
+
+    fn update_recipe_information(
+        &mut self,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        ui: &mut egui::Ui,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
         let text_height = egui::TextStyle::Body
             .resolve(ui.style())
             .size
@@ -704,10 +1428,18 @@ impl RecipeWindow {
                         .size(egui_extras::Size::remainder())
                         .horizontal(|mut strip| {
                             strip.cell(|ui| {
-                                ui.label("Duration:");
+                                ui.label(tr("Duration:"));
                             });
                             strip.cell(|ui| {
-                                ui.label(self.recipe.duration.to_string());
+                                ui.horizontal(|ui| {
+                                    ui.label(self.recipe.duration.to_string());
+                                    ui.add_space(20.0);
+                                    ui.label(tr("Rating:"));
+                                    events.extend(self.update_rating(conn, ui));
+                                    ui.add_space(20.0);
+                                    events.extend(self.update_status(conn, ui));
+                                    events.extend(self.update_archived(conn, ui));
+                                });
                             });
                         });
                 });
@@ -717,11 +1449,11 @@ impl RecipeWindow {
                         .size(egui_extras::Size::remainder())
                         .horizontal(|mut strip| {
                             strip.cell(|ui| {
-                                ui.label("Description:");
+                                ui.label(tr("Description:"));
                             });
                             strip.cell(|ui| {
                                 egui::ScrollArea::vertical().show(ui, |ui| {
-                                    ui.add(egui::Label::new(&self.recipe.description).wrap());
+                                    self.update_description(settings, ui);
                                 });
                             });
                         });
@@ -732,175 +1464,543 @@ impl RecipeWindow {
                         .size(egui_extras::Size::remainder())
                         .horizontal(|mut strip| {
                             strip.cell(|ui| {
-                                ui.label("Total Calories:");
+                                ui.label(tr("Total Calories:"));
                             });
                             strip.cell(|ui| {
-                                ui.label(format!("{}", self.total_calories()));
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{}", self.total_calories()));
+                                    ui.add_space(20.0);
+                                    self.update_servings(conn, ui);
+                                    ui.add_space(20.0);
+                                    let badges = recipe_manager::diet::recipe_badges(
+                                        self.ingredients.iter().map(|i| i.ingredient.diet),
+                                    );
+                                    diet::show_badges(ui, &badges);
+                                });
                             });
                         });
                 });
             });
+        events
+    }
+
+    fn update_description(&mut self, settings: &Settings, ui: &mut egui::Ui) {
+        let Some(find) = &mut self.find else {
+            let description = unit_conversion::with_temperature_conversions(
+                &self.recipe.description,
+                settings.measurement_system,
+            );
+            markdown::show(ui, &description);
+            return;
+        };
+
+        let color = ui.visuals().text_color();
+        let job = find.layout_job(ui, &self.recipe.description, color, ui.available_width());
+        let galley = ui.fonts(|f| f.layout_job(job));
+        let response = ui.add(egui::Label::new(galley.clone()));
+        find.scroll_to_current(ui, &self.recipe.description, &galley, response.rect.min);
+    }
+
+    fn update_find_bar(&mut self, ui: &mut egui::Ui) {
+        let Some(find) = &mut self.find else {
+            return;
+        };
+        let description = if self.edit_mode {
+            &self.draft.description
+        } else {
+            &self.recipe.description
+        };
+        let num_matches = find_matches(description, &find.query).len();
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut find.query)
+                .hint_text(tr("find in description"))
+                .desired_width(120.0),
+        );
+        if find.focus_query {
+            response.request_focus();
+            find.focus_query = false;
+        }
+        if response.changed() {
+            find.current = 0;
+            find.scroll_to_current = true;
+        }
+        let mut close = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape));
+        if response.pressed_enter() {
+            find.step(num_matches, true);
+        }
+
+        if !find.query.is_empty() {
+            if num_matches == 0 {
+                ui.label(tr("no matches"));
+            } else {
+                ui.label(
+                    tr("{current} of {total}")
+                        .replace("{current}", &(find.current + 1).to_string())
+                        .replace("{total}", &num_matches.to_string()),
+                );
+            }
+        }
+        if ui
+            .button("<")
+            .accessible_name(tr("Previous match"))
+            .clicked()
+        {
+            find.step(num_matches, false);
+        }
+        if ui.button(">").accessible_name(tr("Next match")).clicked() {
+            find.step(num_matches, true);
+        }
+        close |= ui.button("x").accessible_name(tr("Close find")).clicked();
+
+        if close {
+            self.find = None;
+        }
     }
 
     fn total_calories(&self) -> String {
         use thousands::Separable;
 
-        let mut total = self
-            .ingredients
-            .iter()
-            .filter_map(|i| i.calories())
-            .sum::<f32>();
-        if total == -0.0 {
-            total = 0.0;
+        total_calories(&self.ingredients).separate_with_commas()
+    }
+
+    fn update_photos(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ui: &mut egui::Ui,
+    ) {
+        let mut removed = None;
+        egui::ScrollArea::vertical()
+            .max_height(600.0)
+            .show(ui, |ui| {
+                for photo in &self.photos {
+                    let uri = format!(
+                        "bytes://recipe-photo-{}/{}",
+                        photo.id.to_i32(),
+                        photo.file_name
+                    );
+                    ui.add(egui::Image::from_bytes(uri, photo.data.clone()).max_width(400.0));
+                    if self.edit_mode && is_editable(ui.ctx()) && ui.button(tr("Remove")).clicked()
+                    {
+                        removed = Some(photo.id);
+                    }
+                }
+            });
+        if let Some(id) = removed {
+            match query::delete_recipe_photo(conn, id) {
+                Ok(()) => self.photos.retain(|p| p.id != id),
+                Err(error) => {
+                    toasts.add(new_error_toast(
+                        tr("Error removing photo: {error}").replace("{error}", &error.to_string()),
+                    ));
+                }
+            }
         }
-        total.separate_with_commas()
+    }
+
+    /// Saves the recipe as a Cooklang file wherever the user picks.
+    #[cfg(feature = "legacy-import")]
+    fn export_cooklang(&self) -> recipe_manager::Result<()> {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Cooklang", &["cook"])
+            .set_file_name(format!("{}.cook", self.recipe.name))
+            .save_file()
+        else {
+            return Ok(());
+        };
+        let text =
+            cooklang::recipe_to_cooklang(&self.recipe, &self.new_category_name, &self.ingredients);
+        std::fs::write(path, text)?;
+        Ok(())
     }
 
     fn update_recipe_controls(
         &mut self,
         conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
         ui: &mut egui::Ui,
+        recipe_steps_windows: &mut HashMap<RecipeId, RecipeStepsWindow>,
     ) -> Vec<UpdateEvent> {
         let mut events = vec![];
+        self.week.set_start_day(conn, settings.week_start.weekday());
         ui.horizontal(|ui| {
-            ui.toggle_value(&mut self.edit_mode, "Edit");
+            ui.edit_toggle(&mut self.edit_mode);
             if !self.edit_mode {
                 self.ingredient_being_edited = None;
             }
+            self.update_find_bar(ui);
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                ui.menu_button("Schedule", |ui| {
-                    for (day, recipe) in self.week.recipes() {
-                        let recipe = recipe.map(|r| r.name.clone()).unwrap_or("No Recipe".into());
-                        if ui.button(format!("{day}: {recipe}")).clicked() {
-                            self.week.schedule(conn, day, self.recipe.id);
-                            ui.close_menu();
-                            events.push(UpdateEvent::Scheduled(self.week.week()));
+                ui.add_enabled_ui(is_editable(ui.ctx()), |ui| {
+                    ui.menu_button(tr("Schedule"), |ui| {
+                        for (day, recipe) in self.week.recipes() {
+                            let recipe = recipe
+                                .map(|r| r.name.clone())
+                                .unwrap_or(tr("No Recipe").into());
+                            if ui.button(format!("{day}: {recipe}")).clicked() {
+                                self.week.schedule(conn, day, self.recipe.id);
+                                ui.close_menu();
+                                events.push(UpdateEvent::Scheduled(self.week.week()));
+                            }
                         }
-                    }
+                    });
                 });
                 self.week.pick_date(conn, |date| {
-                    ui.add(egui_extras::DatePickerButton::new(date));
+                    ui.add(
+                        egui_extras::DatePickerButton::new(date)
+                            .format(settings.date_format.short()),
+                    );
                 });
+                #[cfg(feature = "print")]
+                if ui.button(tr("Print")).clicked() {
+                    if let Err(error) =
+                        print::print_recipe(&self.recipe, &self.ingredients, settings)
+                    {
+                        toasts.add(new_error_toast(
+                            tr("Error printing recipe: {error}")
+                                .replace("{error}", &error.to_string()),
+                        ));
+                    }
+                }
+                #[cfg(feature = "legacy-import")]
+                if ui.button(tr("Export")).clicked() {
+                    if let Err(error) = self.export_cooklang() {
+                        toasts.add(new_error_toast(
+                            tr("Error exporting recipe: {error}")
+                                .replace("{error}", &error.to_string()),
+                        ));
+                    }
+                }
+                #[cfg(feature = "share")]
+                {
+                    let menu = ui.menu_button(tr("Share"), |ui| {
+                        let share = self.share.get_or_insert_with(|| {
+                            RecipeShare::new(
+                                ui.ctx(),
+                                &self.recipe,
+                                &self.new_category_name,
+                                &self.ingredients,
+                            )
+                        });
+                        share.update(toasts, ui);
+                    });
+                    if menu.inner.is_none() {
+                        self.share = None;
+                    }
+                }
+                if !self.photos.is_empty() {
+                    ui.menu_button(tr("Photos"), |ui| self.update_photos(conn, toasts, ui));
+                }
+                let edit_mode = self.edit_mode;
+                ui.menu_button(tr("Journal"), |ui| {
+                    self.journal.update(conn, settings, edit_mode, ui)
+                });
+                if ui.button(tr("Cook")).clicked() {
+                    events.push(UpdateEvent::Cook(self.recipe.id));
+                }
+                // Tabs are in the main window's background, there's nothing to keep them above.
+                if self.detached || ui.layer_id().order != egui::Order::Background {
+                    ui.toggle_value(&mut self.pinned, tr("Pin"))
+                        .on_hover_text(tr("Keep this recipe above the other windows"));
+                    // Without multiple viewports, they'd only be shown as egui windows anyway.
+                    if !ui.ctx().embed_viewports() {
+                        ui.toggle_value(&mut self.detached, tr("Detach"))
+                            .on_hover_text(tr("Show this recipe in its own window"));
+                    }
+                }
+                let mut steps_shown = recipe_steps_windows.contains_key(&self.recipe.id);
+                ui.toggle_value(&mut steps_shown, tr("Steps"));
+                if steps_shown && !recipe_steps_windows.contains_key(&self.recipe.id) {
+                    recipe_steps_windows.insert(
+                        self.recipe.id,
+                        RecipeStepsWindow::new(conn, self.recipe.to_handle()),
+                    );
+                } else if !steps_shown {
+                    recipe_steps_windows.remove(&self.recipe.id);
+                }
             });
         });
         events
     }
 
-    pub fn update(
-        &mut self,
-        ctx: &egui::Context,
-        conn: &mut database::Connection,
-        toasts: &mut egui_toast::Toasts,
-        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
-    ) -> Vec<UpdateEvent> {
-        let style = ctx.style();
+    fn section_heights(&self, style: &egui::Style) -> SectionHeights {
         let text_height = egui::TextStyle::Body
-            .resolve(&style)
+            .resolve(style)
             .size
             .max(style.spacing.interact_size.y);
-        let button_height = (egui::TextStyle::Button.resolve(&style).size
+        let button_height = (egui::TextStyle::Button.resolve(style).size
             + style.spacing.button_padding.y as f32 * 2.0)
             .max(style.spacing.interact_size.y);
         let spacing = style.spacing.item_spacing.y;
 
         let separator_height = 6.0;
         let table_height = 20.0 + (20.0 + spacing) * self.ingredients.len() as f32 + spacing;
-        let info_height = (text_height + spacing) * 6.0 + separator_height;
-        let controls_height = button_height + spacing + separator_height;
-
         let add_ingredient_height = button_height + spacing;
         let edit_info_height = (text_height + spacing) * 8.0 + separator_height;
+        let controls_height = button_height + spacing + separator_height;
+
+        SectionHeights {
+            info: (text_height + spacing) * 6.0 + separator_height,
+            controls: controls_height,
+            add_ingredient: add_ingredient_height,
+            edit_info: edit_info_height,
+            edit: table_height + add_ingredient_height + edit_info_height + controls_height,
+        }
+    }
 
-        let edit_height = table_height + add_ingredient_height + edit_info_height + controls_height;
+    /// The window title, marked when there are unsaved changes.
+    pub fn title(&self) -> String {
+        let mut title = self.recipe.name.clone();
+        if self.has_unsaved_changes() {
+            title.push('*');
+        }
+        title
+    }
 
+    /// Shows the recipe in the given `Ui`, either inside its own window or in a tab.
+    pub fn update_contents(
+        &mut self,
+        ui: &mut egui::Ui,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+        recipe_steps_windows: &mut HashMap<RecipeId, RecipeStepsWindow>,
+        settings: &Settings,
+    ) -> Vec<UpdateEvent> {
+        let heights = self.section_heights(ui.style());
         let mut events = vec![];
-        let mut open = true;
-        let mut refresh_self = false;
+        let mut changed_usages = vec![];
+
+        if ui.ctx().top_layer_id() == Some(ui.layer_id())
+            && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::F))
+        {
+            self.find
+                .get_or_insert_with(DescriptionFind::new)
+                .focus_query = true;
+        }
+
+        if self.edit_mode {
+            egui_extras::StripBuilder::new(ui)
+                .size(egui_extras::Size::remainder())
+                .size(egui_extras::Size::exact(heights.add_ingredient))
+                .size(egui_extras::Size::exact(heights.edit_info))
+                .size(egui_extras::Size::exact(heights.controls))
+                .vertical(|mut strip| {
+                    strip.cell(|ui| {
+                        self.update_ingredients_edit_mode(
+                            conn,
+                            toasts,
+                            settings,
+                            ui,
+                            ingredient_calories_windows,
+                            &mut changed_usages,
+                        );
+                    });
+                    strip.cell(|ui| {
+                        events.extend(self.update_add_ingredient(
+                            conn,
+                            toasts,
+                            ui,
+                            &mut changed_usages,
+                        ));
+                    });
+                    strip.cell(|ui| {
+                        ui.separator();
+                        events.extend(self.update_recipe_information_edit_mode(conn, toasts, ui));
+                    });
+                    strip.cell(|ui| {
+                        ui.separator();
+                        events.extend(self.update_recipe_controls(
+                            conn,
+                            toasts,
+                            settings,
+                            ui,
+                            recipe_steps_windows,
+                        ));
+                    });
+                });
+        } else {
+            egui_extras::StripBuilder::new(ui)
+                .size(egui_extras::Size::remainder())
+                .size(egui_extras::Size::exact(heights.info))
+                .size(egui_extras::Size::exact(heights.controls))
+                .vertical(|mut strip| {
+                    strip.cell(|ui| {
+                        self.update_ingredients(conn, toasts, settings, ui, &mut changed_usages);
+                    });
+                    strip.cell(|ui| {
+                        ui.separator();
+                        events.extend(self.update_recipe_information(conn, settings, ui));
+                    });
+                    strip.cell(|ui| {
+                        ui.separator();
+                        events.extend(self.update_recipe_controls(
+                            conn,
+                            toasts,
+                            settings,
+                            ui,
+                            recipe_steps_windows,
+                        ));
+                    });
+                });
+        }
 
-        let mut default_height = edit_height + 20.0;
-        if default_height > 500.0 {
-            default_height = 500.0;
+        if !changed_usages.is_empty() {
+            self.apply_usage_changes(conn, changed_usages);
+            events.push(UpdateEvent::IngredientsChanged);
         }
+        events
+    }
 
-        egui::Window::new(self.recipe.name.clone())
-            .id(egui::Id::new(("recipe", self.recipe.id)))
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ingredient_calories_windows: &mut HashMap<IngredientId, IngredientCaloriesWindow>,
+        recipe_steps_windows: &mut HashMap<RecipeId, RecipeStepsWindow>,
+        settings: &Settings,
+    ) -> Vec<UpdateEvent> {
+        let default_height = (self.section_heights(&ctx.style()).edit + 20.0).min(500.0);
+        if self.detached && !ctx.embed_viewports() {
+            let level = if self.pinned {
+                egui::WindowLevel::AlwaysOnTop
+            } else {
+                egui::WindowLevel::Normal
+            };
+            let builder = egui::ViewportBuilder::default()
+                .with_title(self.title())
+                .with_inner_size([500.0, default_height])
+                .with_window_level(level);
+            return ctx.show_viewport_immediate(self.viewport_id(), builder, |ctx, _| {
+                let mut events = egui::CentralPanel::default()
+                    .show(ctx, |ui| {
+                        self.update_contents(
+                            ui,
+                            conn,
+                            toasts,
+                            ingredient_calories_windows,
+                            recipe_steps_windows,
+                            settings,
+                        )
+                    })
+                    .inner;
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    events.push(UpdateEvent::Closed);
+                }
+                events
+            });
+        }
+
+        let mut events = vec![];
+        let mut open = true;
+        let order = if self.pinned {
+            egui::Order::Foreground
+        } else {
+            egui::Order::Middle
+        };
+        egui::Window::new(self.title())
+            .id(self.window_id())
+            .order(order)
             .default_height(default_height)
             .default_width(500.0)
             .open(&mut open)
             .show(ctx, |ui| {
-                if self.edit_mode {
-                    egui_extras::StripBuilder::new(ui)
-                        .size(egui_extras::Size::remainder())
-                        .size(egui_extras::Size::exact(add_ingredient_height))
-                        .size(egui_extras::Size::exact(edit_info_height))
-                        .size(egui_extras::Size::exact(controls_height))
-                        .vertical(|mut strip| {
-                            strip.cell(|ui| {
-                                self.update_ingredients_edit_mode(
-                                    conn,
-                                    toasts,
-                                    ui,
-                                    ingredient_calories_windows,
-                                    &mut refresh_self,
-                                );
-                            });
-                            strip.cell(|ui| {
-                                self.update_add_ingredient(conn, toasts, ui, &mut refresh_self);
-                            });
-                            strip.cell(|ui| {
-                                ui.separator();
-                                events.extend(
-                                    self.update_recipe_information_edit_mode(conn, toasts, ui),
-                                );
-                            });
-                            strip.cell(|ui| {
-                                ui.separator();
-                                events.extend(self.update_recipe_controls(conn, ui));
-                            });
-                        });
-                } else {
-                    egui_extras::StripBuilder::new(ui)
-                        .size(egui_extras::Size::remainder())
-                        .size(egui_extras::Size::exact(info_height))
-                        .size(egui_extras::Size::exact(controls_height))
-                        .vertical(|mut strip| {
-                            strip.cell(|ui| {
-                                self.update_ingredients(conn, toasts, ui, &mut refresh_self);
-                            });
-                            strip.cell(|ui| {
-                                ui.separator();
-                                self.update_recipe_information(ui);
-                            });
-                            strip.cell(|ui| {
-                                ui.separator();
-                                events.extend(self.update_recipe_controls(conn, ui));
-                            });
-                        });
-                }
+                events = self.update_contents(
+                    ui,
+                    conn,
+                    toasts,
+                    ingredient_calories_windows,
+                    recipe_steps_windows,
+                    settings,
+                );
             });
 
-        if refresh_self {
-            *self = Self::new(conn, self.recipe.id, Some(self.week.week()), self.edit_mode);
-        }
-
         if !open {
             events.push(UpdateEvent::Closed);
         }
         events
     }
 
+    pub fn window_id(&self) -> egui::Id {
+        egui::Id::new(("recipe", self.recipe.id))
+    }
+
+    fn viewport_id(&self) -> egui::ViewportId {
+        egui::ViewportId::from_hash_of(("recipe viewport", self.recipe.id))
+    }
+
     pub fn recipe_scheduled(&mut self, conn: &mut database::Connection, week: chrono::NaiveWeek) {
         if self.week.week() == week {
             self.week.refresh(conn);
         }
     }
 
-    pub fn ingredient_edited(&mut self, conn: &mut database::Connection) {
-        *self = Self::new(conn, self.recipe.id, Some(self.week.week()), self.edit_mode);
+    /// Reloads the recipe from the database, keeping any unsaved edits to its information.
+    /// Reads the journal again after entries were added elsewhere.
+    pub fn journal_changed(&mut self, conn: &mut database::Connection) {
+        self.journal.refresh(conn);
+    }
+
+    pub fn reload(&mut self, conn: &mut database::Connection) {
+        let reloaded = Self::new(conn, self.recipe.id, Some(self.week.week()), self.edit_mode);
+        if let Some(reloaded) = reloaded.or_report() {
+            let draft = std::mem::replace(&mut self.draft, RecipeDraft::new(&self.recipe));
+            let (pinned, detached) = (self.pinned, self.detached);
+            *self = reloaded;
+            self.draft = draft;
+            self.pinned = pinned;
+            self.detached = detached;
+        }
+    }
+
+    fn apply_usage_changes(&mut self, conn: &mut database::Connection, changes: Vec<UsageChange>) {
+        for change in changes {
+            match change {
+                UsageChange::Saved(id) => {
+                    let Some(usage) = query::get_recipe_ingredient(conn, id).or_report() else {
+                        continue;
+                    };
+                    match self.ingredients.iter_mut().find(|u| u.id == id) {
+                        Some(existing) => *existing = usage,
+                        None => self.ingredients.push(usage),
+                    }
+                }
+                UsageChange::Removed(id) => self.ingredients.retain(|u| u.id != id),
+            }
+        }
+        self.ingredients
+            .sort_by(|a, b| a.ingredient.name.cmp(&b.ingredient.name));
+
+        // An ingredient may have been given a category which was just created.
+        let new_category = self.ingredients.iter().any(|u| {
+            u.ingredient
+                .category_id
+                .is_some_and(|c| !self.ingredient_categories.contains_key(&c))
+        });
+        if new_category {
+            self.ingredient_categories =
+                query::get_ingredient_category_names(conn).unwrap_or_report();
+        }
     }
 
-    pub fn ingredient_deleted(&mut self, conn: &mut database::Connection) {
-        *self = Self::new(conn, self.recipe.id, Some(self.week.week()), self.edit_mode);
+    /// Updates the rows using the given ingredients, after their name, category, or calories
+    /// were changed.
+    pub fn ingredients_edited(
+        &mut self,
+        conn: &mut database::Connection,
+        ingredient_ids: &[IngredientId],
+    ) {
+        let changes: Vec<_> = self
+            .ingredients
+            .iter()
+            .filter(|u| ingredient_ids.contains(&u.ingredient.id))
+            .map(|u| UsageChange::Saved(u.id))
+            .collect();
+        if !changes.is_empty() {
+            self.apply_usage_changes(conn, changes);
+        }
     }
 
     pub fn recipe_deleted(&mut self, conn: &mut database::Connection) {
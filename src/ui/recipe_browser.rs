@@ -0,0 +1,136 @@
+use super::errors::QueryResultExt as _;
+use super::recipe::RecipeWindow;
+use super::recipe_list::{RecipeListWindow, UpdateEvent};
+use super::settings::Settings;
+use recipe_manager::database;
+use recipe_manager::database::models::{RecipeCategory, RecipeCategoryId, RecipeId};
+use recipe_manager::query;
+use std::collections::HashMap;
+
+/// Every category in one window, with the categories down the left side and the recipes in the
+/// chosen one on the right. It's easier to get around than a window per category once there are a
+/// lot of them.
+pub struct RecipeBrowserWindow {
+    categories: Vec<(RecipeCategory, usize)>,
+    show_categories: bool,
+    list: Option<RecipeListWindow>,
+}
+
+impl RecipeBrowserWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        let mut window = Self {
+            categories: vec![],
+            show_categories: true,
+            list: None,
+        };
+        window.categories_changed(conn);
+        window
+    }
+
+    fn selected(&self) -> Option<RecipeCategoryId> {
+        self.list.as_ref().map(|l| l.category().id)
+    }
+
+    fn update_categories(&mut self, conn: &mut database::Connection, ui: &mut egui::Ui) {
+        let selected = self.selected();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (category, count) in &self.categories {
+                let label = ui
+                    .selectable_label(selected == Some(category.id), &category.name)
+                    .on_hover_text(format!("{count} recipes"));
+                if label.clicked() && selected != Some(category.id) {
+                    self.list = Some(RecipeListWindow::new(conn, category.clone(), false));
+                }
+            }
+        });
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        selected_week: Option<chrono::NaiveWeek>,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let mut open = true;
+        egui::Window::new("All Recipes")
+            .open(&mut open)
+            .default_size([500.0, 400.0])
+            .show(ctx, |ui| {
+                egui::SidePanel::left("recipe browser categories")
+                    .resizable(true)
+                    .default_width(150.0)
+                    .show_animated_inside(ui, self.show_categories, |ui| {
+                        self.update_categories(conn, ui);
+                    });
+                egui::CentralPanel::default().show_inside(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let arrow = if self.show_categories { "⏴" } else { "⏵" };
+                        ui.toggle_value(&mut self.show_categories, arrow)
+                            .on_hover_text("Show the categories");
+                        if let Some(list) = &self.list {
+                            ui.strong(&list.category().name);
+                        }
+                    });
+                    ui.separator();
+                    match &mut self.list {
+                        Some(list) => {
+                            events = list.update_contents(
+                                ui,
+                                conn,
+                                settings,
+                                selected_week,
+                                recipe_windows,
+                            );
+                        }
+                        None => {
+                            ui.weak("Choose a category to see its recipes.");
+                        }
+                    }
+                });
+            });
+
+        if events
+            .iter()
+            .any(|e| matches!(e, UpdateEvent::RecipeDeleted(_)))
+        {
+            self.categories_changed(conn);
+        }
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+
+    /// The list being shown, if it's the given category's.
+    pub fn list_mut(&mut self, category: RecipeCategoryId) -> Option<&mut RecipeListWindow> {
+        self.list.as_mut().filter(|l| l.category().id == category)
+    }
+
+    pub fn recipe_details_changed(&mut self, conn: &mut database::Connection) {
+        if let Some(list) = &mut self.list {
+            list.recipe_details_changed(conn);
+        }
+    }
+
+    pub fn recipe_category_changed(&mut self, conn: &mut database::Connection) {
+        self.categories_changed(conn);
+    }
+
+    /// Reads the categories again, keeping the one shown unless it was deleted.
+    pub fn categories_changed(&mut self, conn: &mut database::Connection) {
+        self.categories = query::get_recipe_categories(conn).unwrap_or_report();
+        let selected = self
+            .selected()
+            .and_then(|id| self.categories.iter().find(|(c, _)| c.id == id));
+        match (selected, &mut self.list) {
+            (Some((category, _)), Some(list)) => {
+                list.category_name_changed(category.name.clone());
+                list.recipe_category_changed(conn);
+            }
+            _ => self.list = None,
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use super::errors::QueryResultExt as _;
+use super::recipe::RecipeWindow;
+use super::recipe_preview::RecipePreviewExt as _;
+use recipe_manager::database;
+use recipe_manager::database::models::{RecipeHandle, RecipeId};
+use recipe_manager::query;
+use recipe_manager::recipe::{recipe_cost, RecipeIngredient};
+use std::collections::HashMap;
+
+struct RecipeCost {
+    recipe: RecipeHandle,
+    servings: Option<i32>,
+    /// The total, or the ingredients without prices and why.
+    cost: Result<f32, Vec<(String, String)>>,
+}
+
+impl RecipeCost {
+    fn per_serving(&self) -> Option<f32> {
+        Some(*self.cost.as_ref().ok()? / self.servings? as f32)
+    }
+
+    fn problem(&self) -> Option<(String, String)> {
+        match &self.cost {
+            Err(missing) => {
+                let names: Vec<_> = missing.iter().map(|(name, _)| name.as_str()).collect();
+                let reasons: Vec<_> = missing
+                    .iter()
+                    .map(|(name, reason)| format!("{name}: {reason}"))
+                    .collect();
+                Some((
+                    format!("missing prices for {}", names.join(", ")),
+                    reasons.join("\n"),
+                ))
+            }
+            Ok(_) if self.servings.is_none() => Some((
+                "servings not set".into(),
+                "Set how many it serves from the recipe's window".into(),
+            )),
+            Ok(_) => None,
+        }
+    }
+}
+
+/// The recipes ranked by what a serving costs, cheapest first, followed by the ones which can't be
+/// worked out.
+pub struct RecipeCostsWindow {
+    costs: Vec<RecipeCost>,
+}
+
+impl RecipeCostsWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        let mut ingredients: HashMap<RecipeId, (RecipeHandle, Vec<RecipeIngredient>)> =
+            HashMap::new();
+        for (recipe, ingredient) in query::get_all_recipe_ingredients(conn).unwrap_or_report() {
+            ingredients
+                .entry(recipe.id)
+                .or_insert_with(|| (recipe, vec![]))
+                .1
+                .push(ingredient);
+        }
+        let servings = query::get_all_recipe_servings(conn).unwrap_or_report();
+
+        let mut costs: Vec<_> = ingredients
+            .into_values()
+            .map(|(recipe, ingredients)| RecipeCost {
+                servings: servings.get(&recipe.id).copied(),
+                cost: recipe_cost(&ingredients),
+                recipe,
+            })
+            .collect();
+        // Per serving first, then the totals of recipes without servings, then the rest.
+        costs.sort_by(|a, b| {
+            let rank = |c: &RecipeCost| match (c.per_serving(), &c.cost) {
+                (Some(per_serving), _) => (0, per_serving),
+                (None, Ok(total)) => (1, *total),
+                (None, Err(_)) => (2, 0.0),
+            };
+            let (a_rank, a_cost) = rank(a);
+            let (b_rank, b_cost) = rank(b);
+            a_rank
+                .cmp(&b_rank)
+                .then(a_cost.total_cmp(&b_cost))
+                .then_with(|| a.recipe.name.cmp(&b.recipe.name))
+        });
+        Self { costs }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        *self = Self::new(conn);
+    }
+
+    fn update_table(
+        &self,
+        conn: &mut database::Connection,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+        selected_week: Option<chrono::NaiveWeek>,
+        ui: &mut egui::Ui,
+    ) {
+        let available_height = ui.available_height();
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("recipe costs table")
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::initial(180.0).clip(true))
+            .column(egui_extras::Column::exact(90.0))
+            .column(egui_extras::Column::exact(70.0))
+            .column(egui_extras::Column::remainder().clip(true))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(available_height)
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.heading("Recipe");
+                });
+                header.col(|ui| {
+                    ui.heading("Per Serving");
+                });
+                header.col(|ui| {
+                    ui.heading("Total");
+                });
+                header.col(|ui| {
+                    ui.heading("Problem");
+                });
+            })
+            .body(|body| {
+                body.rows(20.0, self.costs.len(), |mut row| {
+                    let c = &self.costs[row.index()];
+                    let id = c.recipe.id;
+                    let mut shown = recipe_windows.contains_key(&id);
+                    row.col(|ui| {
+                        ui.toggle_value(&mut shown, &c.recipe.name)
+                            .on_hover_recipe_preview(conn, id);
+                    });
+                    row.col(|ui| {
+                        if let Some(per_serving) = c.per_serving() {
+                            ui.strong(format!("{per_serving:.2}"));
+                        }
+                    });
+                    row.col(|ui| {
+                        if let Ok(total) = c.cost {
+                            ui.label(format!("{total:.2}"));
+                        }
+                    });
+                    row.col(|ui| {
+                        if let Some((problem, details)) = c.problem() {
+                            ui.colored_label(ui.visuals().warn_fg_color, problem)
+                                .on_hover_text(details);
+                        }
+                    });
+
+                    if shown && !recipe_windows.contains_key(&id) {
+                        if let Some(window) =
+                            RecipeWindow::new(conn, id, selected_week, false).or_report()
+                        {
+                            recipe_windows.insert(id, window);
+                        }
+                    } else if !shown {
+                        recipe_windows.remove(&id);
+                    }
+                });
+            });
+    }
+
+    /// Returns true when the window is closed.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+        selected_week: Option<chrono::NaiveWeek>,
+    ) -> bool {
+        let style = ctx.style();
+        let text_height = egui::TextStyle::Body
+            .resolve(&style)
+            .size
+            .max(style.spacing.interact_size.y);
+        let spacing = style.spacing.item_spacing.y;
+        let separator_height = 6.0;
+
+        let summary_height = text_height + spacing + separator_height;
+
+        let mut open = true;
+        egui::Window::new("Recipe Costs")
+            .open(&mut open)
+            .default_width(550.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                egui_extras::StripBuilder::new(ui)
+                    .size(egui_extras::Size::exact(summary_height))
+                    .size(egui_extras::Size::remainder())
+                    .vertical(|mut strip| {
+                        strip.cell(|ui| {
+                            ui.horizontal(|ui| {
+                                let missing = self.costs.iter().filter(|c| c.cost.is_err()).count();
+                                ui.label(format!(
+                                    "{missing} recipes can't be costed, their ingredients are \
+                                    missing prices"
+                                ));
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui.button("Refresh").clicked() {
+                                            self.refresh(conn);
+                                        }
+                                    },
+                                );
+                            });
+                            ui.separator();
+                        });
+                        strip.cell(|ui| {
+                            self.update_table(conn, recipe_windows, selected_week, ui);
+                        });
+                    });
+            });
+        !open
+    }
+}
@@ -1,19 +1,134 @@
-use super::{query, recipe::RecipeWindow, PressedEnterExt as _};
-use crate::database;
-use crate::database::models::{RecipeCategory, RecipeHandle, RecipeId};
-use std::collections::HashMap;
+use super::errors::QueryResultExt as _;
+use super::{
+    nutrition, paged::Paged, recipe::RecipeWindow, recipe_preview::RecipePreviewExt as _,
+    settings::Settings, AccessibleNameExt as _, EditToggleExt as _, PressedEnterExt as _,
+};
+use derive_more::Display;
+use recipe_manager::database;
+use recipe_manager::database::models::{RecipeCategory, RecipeCategoryId, RecipeHandle, RecipeId};
+use recipe_manager::query::{self, RecipeSummary};
+use recipe_manager::recipe::total_calories;
+use std::collections::{HashMap, HashSet};
+use strum::{EnumIter, IntoEnumIterator as _};
 
 pub enum UpdateEvent {
     Closed,
     RecipeDeleted(RecipeId),
 }
 
+/// The columns which can be shown after the recipe's name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display, EnumIter)]
+enum RecipeColumn {
+    #[display("Duration")]
+    Duration,
+    #[display("Rating")]
+    Rating,
+    #[display("Last Cooked")]
+    LastCooked,
+    #[display("Calories")]
+    Calories,
+}
+
+impl RecipeColumn {
+    fn width(&self) -> f32 {
+        match self {
+            Self::Duration => 90.0,
+            Self::Rating => 70.0,
+            Self::LastCooked => 90.0,
+            Self::Calories => 70.0,
+        }
+    }
+
+    fn text(&self, recipe: &RecipeSummary) -> String {
+        match self {
+            Self::Duration => recipe.duration.to_string(),
+            Self::Rating => stars(recipe.rating),
+            Self::LastCooked => recipe
+                .last_cooked
+                .map(|d| d.format("%b %-d, %Y").to_string())
+                .unwrap_or_default(),
+            Self::Calories => nutrition::calories_display(total_calories(&recipe.ingredients)),
+        }
+    }
+
+    /// Ascending order, with recipes missing the value first.
+    fn compare(&self, a: &RecipeSummary, b: &RecipeSummary) -> std::cmp::Ordering {
+        match self {
+            Self::Duration => a.duration.cmp(&b.duration),
+            Self::Rating => a.rating.cmp(&b.rating),
+            Self::LastCooked => a.last_cooked.cmp(&b.last_cooked),
+            Self::Calories => {
+                total_calories(&a.ingredients).total_cmp(&total_calories(&b.ingredients))
+            }
+        }
+    }
+}
+
+/// A rating as stars, like "★★★☆☆".
+pub fn stars(rating: Option<i32>) -> String {
+    match rating {
+        Some(rating) => {
+            let rating = rating.clamp(0, 5) as usize;
+            "★".repeat(rating) + &"☆".repeat(5 - rating)
+        }
+        None => String::new(),
+    }
+}
+
+/// The recipes with the extra columns, all read at once so they can be sorted.
+struct RecipeTable {
+    recipes: Vec<RecipeSummary>,
+    /// The column sorted by and whether it's descending, or by name if there isn't one.
+    sort: Option<(RecipeColumn, bool)>,
+}
+
+impl RecipeTable {
+    fn new(conn: &mut database::Connection, category_id: RecipeCategoryId) -> Self {
+        Self {
+            recipes: query::get_recipe_summaries(conn, category_id).unwrap_or_report(),
+            sort: None,
+        }
+    }
+
+    fn sort(&mut self) {
+        // They are read ordered by name, which the sorts below keep for ties.
+        self.recipes.sort_by_key(|r| r.name.to_lowercase());
+        if let Some((column, descending)) = self.sort {
+            self.recipes.sort_by(|a, b| {
+                let order = column.compare(a, b);
+                if descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+        }
+    }
+
+    /// Sorts by the column, or the other way if it's already sorted by it.
+    fn sort_by(&mut self, column: RecipeColumn) {
+        self.sort = match self.sort {
+            Some((c, descending)) if c == column => Some((column, !descending)),
+            _ => Some((column, false)),
+        };
+        self.sort();
+    }
+}
+
 pub struct RecipeListWindow {
     recipe_category: RecipeCategory,
-    recipes: Vec<RecipeHandle>,
-    recipe_lookup: HashMap<RecipeId, usize>,
+    recipes: Paged<RecipeHandle>,
+    columns: Vec<RecipeColumn>,
+    /// Only read while there are columns shown, since it reads the whole category at once.
+    table: Option<RecipeTable>,
     edit_mode: bool,
+    /// Only the recipes with this in their name are shown.
+    filter: String,
+    cached_filter: Option<query::CachedQuery<RecipeId>>,
     new_recipe_name: String,
+    /// The name of the template the new recipe's description starts from, if any.
+    new_recipe_template: Option<String>,
+    focus_new_recipe_name: bool,
 }
 
 impl RecipeListWindow {
@@ -22,19 +137,185 @@ impl RecipeListWindow {
         recipe_category: RecipeCategory,
         edit_mode: bool,
     ) -> Self {
-        let recipe_vec = query::get_recipes(conn, recipe_category.id);
-        let recipe_lookup = recipe_vec
-            .iter()
-            .enumerate()
-            .map(|(i, h)| (h.id, i))
-            .collect();
+        Self::new_with_columns(conn, recipe_category, edit_mode, vec![], None)
+    }
+
+    fn new_with_columns(
+        conn: &mut database::Connection,
+        recipe_category: RecipeCategory,
+        edit_mode: bool,
+        columns: Vec<RecipeColumn>,
+        sort: Option<(RecipeColumn, bool)>,
+    ) -> Self {
+        let num_recipes = query::count_recipes(conn, recipe_category.id).unwrap_or_report();
+        let table = (!columns.is_empty()).then(|| {
+            let mut table = RecipeTable::new(conn, recipe_category.id);
+            table.sort = sort;
+            table.sort();
+            table
+        });
         Self {
-            recipes: recipe_vec,
-            recipe_lookup,
+            recipes: Paged::new(num_recipes),
             recipe_category,
+            columns,
+            table,
             edit_mode,
+            filter: String::new(),
+            cached_filter: None,
             new_recipe_name: String::new(),
+            new_recipe_template: None,
+            focus_new_recipe_name: false,
+        }
+    }
+
+    /// Reads the window again, keeping the columns and how they're sorted, and the filter.
+    fn refresh(&mut self, conn: &mut database::Connection) {
+        let sort = self.table.as_ref().and_then(|t| t.sort);
+        let filter = std::mem::take(&mut self.filter);
+        let template = self.new_recipe_template.take();
+        *self = Self::new_with_columns(
+            conn,
+            self.recipe_category.clone(),
+            self.edit_mode,
+            std::mem::take(&mut self.columns),
+            sort,
+        );
+        self.filter = filter;
+        self.new_recipe_template = template;
+    }
+
+    fn update_columns_menu(&mut self, conn: &mut database::Connection, ui: &mut egui::Ui) {
+        ui.menu_button("Columns", |ui| {
+            for column in RecipeColumn::iter() {
+                let mut shown = self.columns.contains(&column);
+                if ui.checkbox(&mut shown, column.to_string()).changed() {
+                    if shown {
+                        self.columns.push(column);
+                        self.columns.sort_by_key(|c| *c as u8);
+                    } else {
+                        self.columns.retain(|c| *c != column);
+                    }
+                    self.refresh(conn);
+                }
+            }
+        });
+    }
+
+    /// The column headings, which sort the table when clicked.
+    fn update_header(
+        table: &mut RecipeTable,
+        columns: &[RecipeColumn],
+        mut header: egui_extras::TableRow<'_, '_>,
+    ) {
+        let sort = table.sort;
+        header.col(|ui| {
+            if ui.selectable_label(sort.is_none(), "Name").clicked() {
+                table.sort = None;
+                table.sort();
+            }
+        });
+        for column in columns {
+            header.col(|ui| {
+                let label = match sort {
+                    Some((c, false)) if c == *column => format!("{column} ⏶"),
+                    Some((c, true)) if c == *column => format!("{column} ⏷"),
+                    _ => column.to_string(),
+                };
+                let sorted = sort.is_some_and(|(c, _)| c == *column);
+                if ui.selectable_label(sorted, label).clicked() {
+                    table.sort_by(*column);
+                }
+            });
         }
+        header.col(|_| {});
+    }
+
+    fn update_rows(
+        &mut self,
+        conn: &mut database::Connection,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+        body: egui_extras::TableBody<'_>,
+        selected_week: Option<chrono::NaiveWeek>,
+        refresh_self: &mut bool,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let category_id = self.recipe_category.id;
+        let filtered = (!self.filter.is_empty()).then(|| {
+            query::search_category_recipes(conn, &mut self.cached_filter, category_id, &self.filter)
+                .unwrap_or_report()
+        });
+        // Which of the table's recipes are shown, in order.
+        let table_rows: Vec<usize> = match (&self.table, &filtered) {
+            (Some(table), Some(filtered)) => {
+                let ids: HashSet<_> = filtered.iter().map(|(id, _)| *id).collect();
+                (0..table.recipes.len())
+                    .filter(|&i| ids.contains(&table.recipes[i].id))
+                    .collect()
+            }
+            (Some(table), None) => (0..table.recipes.len()).collect(),
+            (None, _) => vec![],
+        };
+        let num_rows = match (&self.table, &filtered) {
+            (Some(_), _) => table_rows.len(),
+            (None, Some(filtered)) => filtered.len(),
+            (None, None) => self.recipes.len(),
+        };
+        body.rows(20.0, num_rows, |mut row| {
+            let summary = self
+                .table
+                .as_ref()
+                .map(|table| &table.recipes[table_rows[row.index()]]);
+            let (id, name) = match (summary, &filtered) {
+                (Some(recipe), _) => (recipe.id, recipe.name.clone()),
+                (None, Some(filtered)) => filtered[row.index()].clone(),
+                (None, None) => {
+                    let Some(RecipeHandle { name, id }) = self
+                        .recipes
+                        .get(row.index(), |offset, limit| {
+                            query::get_recipes_page(conn, category_id, offset, limit)
+                                .unwrap_or_report()
+                        })
+                        .cloned()
+                    else {
+                        return;
+                    };
+                    (id, name)
+                }
+            };
+            let mut shown = recipe_windows.contains_key(&id);
+            row.col(|ui| {
+                ui.toggle_value(&mut shown, name)
+                    .on_hover_recipe_preview(conn, id);
+            });
+            if let Some(recipe) = summary {
+                for column in &self.columns {
+                    row.col(|ui| {
+                        ui.label(column.text(recipe));
+                    });
+                }
+            }
+
+            row.col(|ui| {
+                if self.edit_mode
+                    && ui.button("Delete").clicked()
+                    && query::delete_recipe(conn, id).or_report().is_some()
+                {
+                    events.push(UpdateEvent::RecipeDeleted(id));
+                    *refresh_self = true;
+                    shown = false;
+                }
+            });
+
+            if shown && !recipe_windows.contains_key(&id) {
+                if let Some(window) = RecipeWindow::new(conn, id, selected_week, false).or_report()
+                {
+                    recipe_windows.insert(id, window);
+                }
+            } else if !shown {
+                recipe_windows.remove(&id);
+            }
+        });
+        events
     }
 
     fn update_table(
@@ -48,123 +329,195 @@ impl RecipeListWindow {
         let mut events = vec![];
 
         let available_height = ui.available_height();
-        egui_extras::TableBuilder::new(ui)
+        let mut table = egui_extras::TableBuilder::new(ui)
             .id_salt(("recipe category list table", self.recipe_category.id))
             .striped(false)
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-            .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::remainder());
+        for column in &self.columns {
+            table = table.column(egui_extras::Column::exact(column.width()));
+        }
+        let table = table
             .column(egui_extras::Column::exact(50.0))
             .min_scrolled_height(0.0)
-            .max_scroll_height(available_height)
-            .body(|mut body| {
-                for RecipeHandle { name, id } in &self.recipes {
-                    body.row(20.0, |mut row| {
-                        let mut shown = recipe_windows.contains_key(&id);
-                        row.col(|ui| {
-                            ui.toggle_value(&mut shown, name.clone());
-                        });
-
-                        row.col(|ui| {
-                            if self.edit_mode {
-                                if ui.button("Delete").clicked() {
-                                    query::delete_recipe(conn, *id);
-                                    events.push(UpdateEvent::RecipeDeleted(*id));
-                                    *refresh_self = true;
-                                    shown = false;
-                                }
-                            }
-                        });
-
-                        if shown && !recipe_windows.contains_key(&id) {
-                            recipe_windows
-                                .insert(*id, RecipeWindow::new(conn, *id, selected_week, false));
-                        } else if !shown {
-                            recipe_windows.remove(id);
-                        }
-                    });
-                }
-            });
+            .max_scroll_height(available_height);
+
+        match &mut self.table {
+            Some(recipe_table) => {
+                let columns = &self.columns;
+                let table = table.header(20.0, |header| {
+                    Self::update_header(recipe_table, columns, header)
+                });
+                table.body(|body| {
+                    events =
+                        self.update_rows(conn, recipe_windows, body, selected_week, refresh_self);
+                });
+            }
+            None => {
+                table.body(|body| {
+                    events =
+                        self.update_rows(conn, recipe_windows, body, selected_week, refresh_self);
+                });
+            }
+        }
         events
     }
 
+    fn update_filter(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let clear = !self.filter.is_empty();
+            let width = ui.available_width() - if clear { 30.0 } else { 0.0 };
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text("filter by name")
+                    .desired_width(width),
+            );
+            if clear
+                && ui
+                    .small_button("x")
+                    .accessible_name("Clear filter")
+                    .on_hover_text("Show every recipe")
+                    .clicked()
+            {
+                self.filter.clear();
+            }
+        });
+    }
+
+    /// Picks which of the templates the new recipe's description starts from.
+    fn update_template_menu(&mut self, settings: &Settings, ui: &mut egui::Ui) {
+        egui::ComboBox::from_id_salt(("new recipe template", self.recipe_category.id))
+            .selected_text(self.new_recipe_template.as_deref().unwrap_or("No Template"))
+            .width(100.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.new_recipe_template, None, "No Template");
+                for template in &settings.description_templates {
+                    ui.selectable_value(
+                        &mut self.new_recipe_template,
+                        Some(template.name.clone()),
+                        &template.name,
+                    );
+                }
+            })
+            .response
+            .on_hover_text("What the new recipe's description starts out as");
+    }
+
     fn update_add_recipe(
         &mut self,
         conn: &mut database::Connection,
+        settings: &Settings,
         ui: &mut egui::Ui,
         refresh_self: &mut bool,
     ) {
         ui.horizontal(|ui| {
-            ui.toggle_value(&mut self.edit_mode, "Edit");
+            ui.edit_toggle(&mut self.edit_mode);
+            if !self.edit_mode {
+                self.update_columns_menu(conn, ui);
+            }
             if self.edit_mode {
                 let mut new_recipe = false;
-                new_recipe |= ui
-                    .add(
-                        egui::TextEdit::singleline(&mut self.new_recipe_name)
-                            .hint_text("recipe name")
-                            .desired_width(ui.available_width() - 100.0),
-                    )
-                    .pressed_enter();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.new_recipe_name)
+                        .hint_text("recipe name")
+                        .desired_width(ui.available_width() - 220.0),
+                );
+                if self.focus_new_recipe_name {
+                    response.request_focus();
+                    self.focus_new_recipe_name = false;
+                }
+                new_recipe |= response.pressed_enter();
                 let e = !self.new_recipe_name.is_empty();
+                self.update_template_menu(settings, ui);
                 new_recipe |= ui.add_enabled(e, egui::Button::new("New Recipe")).clicked();
 
                 if new_recipe && e {
-                    query::add_recipe(conn, &self.new_recipe_name, self.recipe_category.id);
-                    self.new_recipe_name = "".into();
-                    *refresh_self = true;
+                    if let Some(recipe_id) =
+                        query::add_recipe(conn, &self.new_recipe_name, self.recipe_category.id)
+                            .or_report()
+                    {
+                        let template = settings
+                            .description_templates
+                            .iter()
+                            .find(|t| Some(&t.name) == self.new_recipe_template.as_ref());
+                        if let Some(template) = template {
+                            query::edit_recipe_description(conn, recipe_id, &template.text)
+                                .or_report();
+                        }
+                        self.new_recipe_name = "".into();
+                        *refresh_self = true;
+                    }
                 }
             }
         });
     }
 
-    pub fn update(
+    /// The table and the row below it, for showing in a window or in the recipe browser.
+    pub fn update_contents(
         &mut self,
-        ctx: &egui::Context,
+        ui: &mut egui::Ui,
         conn: &mut database::Connection,
+        settings: &Settings,
         selected_week: Option<chrono::NaiveWeek>,
         recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
     ) -> Vec<UpdateEvent> {
-        let style = ctx.style();
+        let style = ui.ctx().style();
         let button_height = (egui::TextStyle::Button.resolve(&style).size
             + style.spacing.button_padding.y as f32 * 2.0)
             .max(style.spacing.interact_size.y);
         let spacing = style.spacing.item_spacing.y;
 
         let separator_height = 6.0;
+        let filter_height = button_height + spacing;
         let add_recipe_height = button_height + spacing + separator_height + 2.0;
 
         let mut events = vec![];
-        let mut open = true;
         let mut refresh_self = false;
-        egui::Window::new(&self.recipe_category.name)
-            .id(egui::Id::new((
-                "recipe category list",
-                self.recipe_category.id,
-            )))
-            .open(&mut open)
-            .show(ctx, |ui| {
-                egui_extras::StripBuilder::new(ui)
-                    .size(egui_extras::Size::remainder())
-                    .size(egui_extras::Size::exact(add_recipe_height))
-                    .vertical(|mut strip| {
-                        strip.cell(|ui| {
-                            events.extend(self.update_table(
-                                conn,
-                                recipe_windows,
-                                ui,
-                                selected_week,
-                                &mut refresh_self,
-                            ));
-                        });
-                        strip.cell(|ui| {
-                            ui.separator();
-                            self.update_add_recipe(conn, ui, &mut refresh_self);
-                        });
-                    });
+        egui_extras::StripBuilder::new(ui)
+            .size(egui_extras::Size::exact(filter_height))
+            .size(egui_extras::Size::remainder())
+            .size(egui_extras::Size::exact(add_recipe_height))
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    self.update_filter(ui);
+                });
+                strip.cell(|ui| {
+                    events.extend(self.update_table(
+                        conn,
+                        recipe_windows,
+                        ui,
+                        selected_week,
+                        &mut refresh_self,
+                    ));
+                });
+                strip.cell(|ui| {
+                    ui.separator();
+                    self.update_add_recipe(conn, settings, ui, &mut refresh_self);
+                });
             });
 
         if refresh_self {
-            *self = Self::new(conn, self.recipe_category.clone(), self.edit_mode);
+            self.refresh(conn);
         }
+        events
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        selected_week: Option<chrono::NaiveWeek>,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let mut open = true;
+        egui::Window::new(&self.recipe_category.name)
+            .id(self.window_id())
+            .open(&mut open)
+            .show(ctx, |ui| {
+                events = self.update_contents(ui, conn, settings, selected_week, recipe_windows);
+            });
 
         if !open {
             events.push(UpdateEvent::Closed);
@@ -173,17 +526,49 @@ impl RecipeListWindow {
         events
     }
 
+    pub fn window_id(&self) -> egui::Id {
+        egui::Id::new(("recipe category list", self.recipe_category.id))
+    }
+
+    /// Switches to edit mode with the name of the new recipe focused.
+    pub fn start_new_recipe(&mut self) {
+        self.edit_mode = true;
+        self.focus_new_recipe_name = true;
+    }
+
+    pub fn category(&self) -> &RecipeCategory {
+        &self.recipe_category
+    }
+
     pub fn category_name_changed(&mut self, new_name: String) {
         self.recipe_category.name = new_name;
     }
 
     pub fn recipe_name_changed(&mut self, recipe_id: RecipeId, new_name: String) {
-        if let Some(i) = self.recipe_lookup.get_mut(&recipe_id) {
-            self.recipes[*i].name = new_name;
+        if let Some(recipe) = self.recipes.loaded_mut().find(|r| r.id == recipe_id) {
+            recipe.name = new_name.clone();
+        }
+        if let Some(table) = &mut self.table {
+            if let Some(recipe) = table.recipes.iter_mut().find(|r| r.id == recipe_id) {
+                recipe.name = new_name;
+            }
+            table.sort();
         }
     }
 
     pub fn recipe_category_changed(&mut self, conn: &mut database::Connection) {
-        *self = Self::new(conn, self.recipe_category.clone(), self.edit_mode);
+        self.refresh(conn);
+    }
+
+    /// Reads the list again after a recipe was archived or brought back.
+    pub fn recipe_archived_changed(&mut self, conn: &mut database::Connection) {
+        self.refresh(conn);
+    }
+
+    /// Reads the columns again after something they show changed, like a recipe's rating.
+    pub fn recipe_details_changed(&mut self, conn: &mut database::Connection) {
+        if self.table.is_some() {
+            self.refresh(conn);
+        }
     }
 }
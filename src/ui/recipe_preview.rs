@@ -0,0 +1,54 @@
+use super::errors::QueryResultExt as _;
+use super::nutrition;
+use recipe_manager::database;
+use recipe_manager::database::models::RecipeId;
+use recipe_manager::query;
+use recipe_manager::recipe::total_calories;
+
+/// How many ingredients the preview lists before leaving the rest off.
+const PREVIEW_INGREDIENTS: usize = 5;
+
+fn update_preview(conn: &mut database::Connection, recipe_id: RecipeId, ui: &mut egui::Ui) {
+    let Some(summary) = query::get_recipe_summary(conn, recipe_id).or_report() else {
+        return;
+    };
+    ui.strong(&summary.name);
+    ui.label(format!("Duration: {}", summary.duration));
+    if summary.ingredients.is_empty() {
+        ui.weak("No ingredients");
+    }
+    for ingredient in summary.ingredients.iter().take(PREVIEW_INGREDIENTS) {
+        ui.label(format!("• {}", ingredient.ingredient.name));
+    }
+    if summary.ingredients.len() > PREVIEW_INGREDIENTS {
+        ui.weak(format!(
+            "and {} more",
+            summary.ingredients.len() - PREVIEW_INGREDIENTS
+        ));
+    }
+    let calories = total_calories(&summary.ingredients);
+    ui.label(format!(
+        "{} calories",
+        nutrition::calories_display(calories)
+    ));
+}
+
+pub trait RecipePreviewExt {
+    /// Shows a card with the recipe's duration, first few ingredients and calories while the
+    /// recipe's name is hovered, so it doesn't need to be opened to see what it is.
+    fn on_hover_recipe_preview(
+        self,
+        conn: &mut database::Connection,
+        recipe_id: RecipeId,
+    ) -> egui::Response;
+}
+
+impl RecipePreviewExt for egui::Response {
+    fn on_hover_recipe_preview(
+        self,
+        conn: &mut database::Connection,
+        recipe_id: RecipeId,
+    ) -> egui::Response {
+        self.on_hover_ui(|ui| update_preview(conn, recipe_id, ui))
+    }
+}
@@ -0,0 +1,213 @@
+use super::errors::QueryResultExt as _;
+use super::{is_editable, new_error_toast, settings::Settings, PressedEnterExt as _};
+use recipe_manager::database;
+use recipe_manager::database::models::{RecipeHandle, RecipeStep};
+use recipe_manager::query;
+use recipe_manager::unit_conversion::with_temperature_conversions;
+
+pub enum UpdateEvent {
+    Closed,
+    StepsChanged,
+}
+
+pub struct RecipeStepsWindow {
+    recipe: RecipeHandle,
+    steps: Vec<RecipeStep>,
+    new_day: String,
+    new_label: String,
+}
+
+impl RecipeStepsWindow {
+    pub fn new(conn: &mut database::Connection, recipe: RecipeHandle) -> Self {
+        let steps = query::get_recipe_steps(conn, recipe.id).unwrap_or_report();
+        Self {
+            new_day: steps
+                .last()
+                .map(|s| (s.day + 1).to_string())
+                .unwrap_or("1".into()),
+            new_label: String::new(),
+            recipe,
+            steps,
+        }
+    }
+
+    fn update_table(
+        &mut self,
+        conn: &mut database::Connection,
+        settings: &Settings,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+    ) {
+        let available_height = ui.available_height();
+        egui_extras::TableBuilder::new(ui)
+            .id_salt(("recipe steps table", self.recipe.id))
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::exact(40.0))
+            .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::exact(50.0))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(available_height)
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.heading("Day");
+                });
+                header.col(|ui| {
+                    ui.heading("Step");
+                });
+                header.col(|ui| {
+                    ui.heading("");
+                });
+            })
+            .body(|mut body| {
+                for step in &self.steps {
+                    body.row(20.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label(step.day.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(with_temperature_conversions(
+                                &step.label,
+                                settings.measurement_system,
+                            ));
+                        });
+                        row.col(|ui| {
+                            if ui
+                                .add_enabled(is_editable(ui.ctx()), egui::Button::new("Delete"))
+                                .clicked()
+                            {
+                                query::delete_recipe_step(conn, step.id).or_report();
+                                *refresh_self = true;
+                            }
+                        });
+                    });
+                }
+            });
+    }
+
+    fn update_add_step(
+        &mut self,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        ui: &mut egui::Ui,
+        refresh_self: &mut bool,
+    ) {
+        egui_extras::StripBuilder::new(ui)
+            .size(egui_extras::Size::exact(40.0))
+            .size(egui_extras::Size::remainder())
+            .size(egui_extras::Size::exact(40.0))
+            .horizontal(|mut strip| {
+                let mut added = false;
+                strip.cell(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.new_day).hint_text("day"));
+                });
+                strip.cell(|ui| {
+                    added |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.new_label)
+                                .hint_text("e.g. start the dough")
+                                .desired_width(f32::INFINITY),
+                        )
+                        .pressed_enter();
+                });
+                strip.cell(|ui| {
+                    added |= ui
+                        .add_enabled(is_editable(ui.ctx()), egui::Button::new("Add"))
+                        .clicked();
+                });
+
+                if added {
+                    match self.new_day.trim().parse::<i32>() {
+                        Ok(day) if day > 0 => {
+                            if self.new_label.trim().is_empty() {
+                                toasts.add(new_error_toast("Step needs a description"));
+                            } else {
+                                query::add_recipe_step(
+                                    conn,
+                                    self.recipe.id,
+                                    day,
+                                    self.new_label.trim(),
+                                )
+                                .or_report();
+                                *refresh_self = true;
+                            }
+                        }
+                        _ => {
+                            toasts.add(new_error_toast("Day must be a number greater than zero"));
+                        }
+                    }
+                }
+            });
+    }
+
+    pub fn window_id(&self) -> egui::Id {
+        egui::Id::new(("recipe steps", self.recipe.id))
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &Settings,
+    ) -> Vec<UpdateEvent> {
+        let style = ctx.style();
+        let text_height = egui::TextStyle::Body
+            .resolve(&style)
+            .size
+            .max(style.spacing.interact_size.y);
+        let button_height = (egui::TextStyle::Button.resolve(&style).size
+            + style.spacing.button_padding.y * 2.0)
+            .max(style.spacing.interact_size.y);
+        let spacing = style.spacing.item_spacing.y;
+        let separator_height = 6.0;
+
+        let help_height = (text_height + spacing) * 2.0 + separator_height;
+        let table_height = (20.0 + spacing) * (self.steps.len() + 1) as f32;
+        let add_height = button_height + spacing + separator_height + 2.0;
+
+        let mut events = vec![];
+        let mut open = true;
+        let mut refresh_self = false;
+        egui::Window::new(format!("{} - Steps", &self.recipe.name))
+            .id(self.window_id())
+            .default_height(help_height + table_height + add_height)
+            .default_width(400.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui_extras::StripBuilder::new(ui)
+                    .size(egui_extras::Size::exact(help_height))
+                    .size(egui_extras::Size::remainder())
+                    .size(egui_extras::Size::exact(add_height))
+                    .vertical(|mut strip| {
+                        strip.cell(|ui| {
+                            ui.label(
+                                "For recipes which take several days. The recipe is scheduled \
+                                on its last day, and earlier steps show up on the calendar in \
+                                the days before it.",
+                            );
+                            ui.separator();
+                        });
+                        strip.cell(|ui| {
+                            egui::ScrollArea::vertical().show(ui, |ui| {
+                                self.update_table(conn, settings, ui, &mut refresh_self);
+                            });
+                        });
+                        strip.cell(|ui| {
+                            ui.separator();
+                            self.update_add_step(conn, toasts, ui, &mut refresh_self);
+                        });
+                    });
+            });
+
+        if refresh_self {
+            *self = Self::new(conn, self.recipe.clone());
+            events.push(UpdateEvent::StepsChanged);
+        }
+
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+}
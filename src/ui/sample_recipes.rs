@@ -0,0 +1,19 @@
+//! A few recipes bundled with the program, for trying it out on an empty recipe book.
+
+use recipe_manager::database;
+use recipe_manager::json;
+
+const SAMPLE_RECIPES: &str = include_str!("../../samples/recipes.json");
+
+#[test]
+fn sample_recipes_test() {
+    let samples = json::parse(SAMPLE_RECIPES).unwrap();
+    assert!(!samples.categories.is_empty());
+    assert!(samples.categories.iter().all(|c| !c.recipes.is_empty()));
+}
+
+/// Adds the sample recipes to the recipe book, returning how many were added. Ingredients which
+/// already exist are reused.
+pub fn load(conn: &mut database::Connection) -> recipe_manager::Result<usize> {
+    json::import(conn, SAMPLE_RECIPES)
+}
@@ -1,9 +1,20 @@
-use super::{new_error_toast, query, recipe::RecipeWindow, PressedEnterExt as _};
-use crate::database::{
-    self,
-    models::{Ingredient, IngredientHandle, IngredientId, RecipeHandle, RecipeId},
+use super::{
+    diet,
+    errors::QueryResultExt as _,
+    i18n::{self, tr},
+    new_error_toast,
+    recipe::RecipeWindow,
+    recipe_preview::RecipePreviewExt as _,
+    unit_choices, PressedEnterExt as _,
 };
 use derive_more::Display;
+use recipe_manager::database::{
+    self,
+    models::{
+        Ingredient, IngredientHandle, IngredientId, IngredientMeasurement, RecipeHandle, RecipeId,
+    },
+};
+use recipe_manager::query;
 use std::collections::HashMap;
 use std::hash::Hash;
 use strum::{EnumIter, IntoEnumIterator as _};
@@ -15,6 +26,7 @@ pub struct SearchWidget<'a, SearchFn, ValueT> {
     pop_up_id: egui::Id,
     hint_text: Option<egui::WidgetText>,
     desired_width: Option<f32>,
+    create: Option<&'a mut bool>,
 }
 
 impl<'a, SearchFn, ValueT> SearchWidget<'a, SearchFn, ValueT>
@@ -34,6 +46,7 @@ where
             pop_up_id: egui::Id::new(id_source),
             hint_text: None,
             desired_width: None,
+            create: None,
         }
     }
 
@@ -46,6 +59,13 @@ where
         self.desired_width = Some(desired_width);
         self
     }
+
+    /// When nothing matches, offer an entry to create what was typed. `create` is set when it is
+    /// clicked.
+    pub fn offer_create(mut self, create: &'a mut bool) -> Self {
+        self.create = Some(create);
+        self
+    }
 }
 
 impl<'a, SearchFn, ValueT> egui::Widget for SearchWidget<'a, SearchFn, ValueT>
@@ -61,9 +81,31 @@ where
             search_fn,
             hint_text,
             desired_width,
+            mut create,
         } = self;
 
-        let mut edit = egui::TextEdit::singleline(buf);
+        // The result picked with the arrow keys, taken before the text edit moves its cursor with
+        // them.
+        let edit_id = pop_up_id.with("edit");
+        let highlighted_id = pop_up_id.with("highlighted");
+        let mut highlighted: Option<usize> = ui.data(|d| d.get_temp(highlighted_id));
+        let mut highlight_moved = false;
+        if ui.memory(|m| m.has_focus(edit_id) && m.is_popup_open(pop_up_id)) {
+            ui.input_mut(|i| {
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+                    highlighted = Some(highlighted.map_or(0, |h| h + 1));
+                    highlight_moved = true;
+                }
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+                    highlighted = highlighted.and_then(|h| h.checked_sub(1));
+                    highlight_moved = true;
+                }
+            });
+        }
+
+        // Screen readers don't read out the hint text, so it names the text edit instead.
+        let name = hint_text.as_ref().map(|h| h.text().to_owned());
+        let mut edit = egui::TextEdit::singleline(buf).id(edit_id);
         if let Some(hint_text) = hint_text {
             edit = edit.hint_text(hint_text);
         }
@@ -73,10 +115,24 @@ where
         let edit_output = edit.show(ui);
 
         let mut r = edit_output.response;
+        if let Some(name) = name {
+            let enabled = r.enabled();
+            r.widget_info(|| {
+                let mut info = egui::WidgetInfo::text_edit(enabled, "", buf.as_str());
+                info.label = Some(name.clone());
+                info
+            });
+        }
         let under_text = r.rect.bottom();
         if r.gained_focus() {
             ui.memory_mut(|m| m.open_popup(pop_up_id));
         }
+        if r.changed() {
+            highlighted = None;
+        }
+        let pick_highlighted = highlighted.is_some()
+            && r.lost_focus()
+            && ui.input(|i| i.key_pressed(egui::Key::Enter));
 
         let mut changed = false;
         egui::popup_below_widget(
@@ -95,8 +151,10 @@ where
                 let height_for_size =
                     |l: usize| button_height * l as f32 + spacing * l.saturating_sub(1) as f32;
 
+                let offer_create = create.is_some() && results.is_empty() && !buf.trim().is_empty();
+
                 let remaining_height = ui.ctx().screen_rect().height() - under_text - 12.0;
-                let contents_height = height_for_size(results.len());
+                let contents_height = height_for_size(results.len().max(offer_create as usize));
                 ui.set_height(
                     contents_height
                         .min(height_for_size(19))
@@ -107,7 +165,10 @@ where
                     .max_height(f32::INFINITY)
                     .show(ui, |ui| {
                         let mut matches_valid = false;
-                        for (text_id, text) in results {
+                        if let Some(h) = &mut highlighted {
+                            *h = (*h).min(results.len().saturating_sub(1));
+                        }
+                        for (i, (text_id, text)) in results.into_iter().enumerate() {
                             if buf == &text {
                                 matches_valid = true;
                                 if value.is_none() {
@@ -115,7 +176,12 @@ where
                                 }
                             }
 
-                            if ui.selectable_label(false, &text).clicked() {
+                            let is_highlighted = highlighted == Some(i);
+                            let label = ui.selectable_label(is_highlighted, &text);
+                            if is_highlighted && highlight_moved {
+                                label.scroll_to_me(None);
+                            }
+                            if label.clicked() || (is_highlighted && pick_highlighted) {
                                 *value = Some(text_id);
                                 *buf = text;
                                 changed = true;
@@ -126,10 +192,28 @@ where
                         if !matches_valid {
                             *value = None;
                         }
+                        if offer_create
+                            && ui
+                                .selectable_label(
+                                    false,
+                                    tr("Create '{name}'").replace("{name}", buf.trim()),
+                                )
+                                .clicked()
+                        {
+                            if let Some(create) = &mut create {
+                                **create = true;
+                            }
+                            ui.memory_mut(|m| m.close_popup());
+                        }
                     });
             },
         );
 
+        if changed {
+            highlighted = None;
+        }
+        ui.data_mut(|d| d.insert_temp(highlighted_id, highlighted));
+
         if changed {
             r.mark_changed();
             if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), r.id) {
@@ -168,7 +252,7 @@ impl SearchResultsWindow {
     ) {
         if self.results.is_empty() {
             ui.add(egui::Label::new(&self.query).wrap());
-            ui.label("Nothing found");
+            ui.label(tr("Nothing found"));
             return;
         }
 
@@ -184,24 +268,26 @@ impl SearchResultsWindow {
                     ui.add(egui::Label::new(&self.query).wrap());
                 });
             })
-            .body(|mut body| {
-                for recipe in &self.results {
-                    body.row(20.0, |mut row| {
-                        let mut shown = recipe_windows.contains_key(&recipe.id);
-                        row.col(|ui| {
-                            ui.toggle_value(&mut shown, recipe.name.clone());
-                        });
+            .body(|body| {
+                body.rows(20.0, self.results.len(), |mut row| {
+                    let recipe = &self.results[row.index()];
+                    let mut shown = recipe_windows.contains_key(&recipe.id);
+                    row.col(|ui| {
+                        ui.toggle_value(&mut shown, recipe.name.clone())
+                            .on_hover_recipe_preview(conn, recipe.id);
+                        diet::show_badges(ui, &diet::recipe_badges(conn, recipe.id));
+                    });
 
-                        if shown && !recipe_windows.contains_key(&recipe.id) {
-                            recipe_windows.insert(
-                                recipe.id,
-                                RecipeWindow::new(conn, recipe.id, selected_week, false),
-                            );
-                        } else if !shown {
-                            recipe_windows.remove(&recipe.id);
+                    if shown && !recipe_windows.contains_key(&recipe.id) {
+                        if let Some(window) =
+                            RecipeWindow::new(conn, recipe.id, selected_week, false).or_report()
+                        {
+                            recipe_windows.insert(recipe.id, window);
                         }
-                    });
-                }
+                    } else if !shown {
+                        recipe_windows.remove(&recipe.id);
+                    }
+                });
             });
     }
 
@@ -213,7 +299,7 @@ impl SearchResultsWindow {
         recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
     ) -> bool {
         let mut open = true;
-        egui::Window::new("Search Results")
+        egui::Window::new(tr("Search Results"))
             .id(egui::Id::new(("search window", self.id)))
             .open(&mut open)
             .show(ctx, |ui| {
@@ -243,8 +329,50 @@ impl IngredientSearchControl {
     }
 }
 
+struct SearchIngredient {
+    ingredient: IngredientHandle,
+    /// Only recipes using at least this much count as including it, when set.
+    minimum: Option<(f32, Option<IngredientMeasurement>)>,
+}
+
+impl SearchIngredient {
+    fn update_minimum(&mut self, ui: &mut egui::Ui) {
+        let mut limited = self.minimum.is_some();
+        ui.checkbox(&mut limited, tr("at least"));
+        match (&mut self.minimum, limited) {
+            (Some((quantity, units)), true) => {
+                ui.add(
+                    egui::DragValue::new(quantity)
+                        .speed(0.1)
+                        .range(0.0..=f32::MAX),
+                );
+                egui::ComboBox::from_id_salt(("search minimum units", self.ingredient.id))
+                    .selected_text(units.map(i18n::units).unwrap_or(""))
+                    .width(40.0)
+                    .show_ui(ui, |ui| {
+                        for m in unit_choices(ui.ctx()).units() {
+                            ui.selectable_value(units, Some(m), i18n::units(m));
+                        }
+                        ui.selectable_value(units, None, "");
+                    });
+            }
+            (None, true) => self.minimum = Some((1.0, None)),
+            (_, false) => self.minimum = None,
+        }
+    }
+
+    fn to_minimum(&self) -> Option<query::MinimumQuantity> {
+        self.minimum
+            .map(|(quantity, units)| query::MinimumQuantity {
+                ingredient: self.ingredient.id,
+                quantity,
+                units,
+            })
+    }
+}
+
 struct RecipeSearchByIngredient {
-    to_search: Vec<IngredientHandle>,
+    to_search: Vec<SearchIngredient>,
 
     new_ingredient_name: String,
     new_ingredient: Option<Ingredient>,
@@ -270,17 +398,21 @@ impl RecipeSearchByIngredient {
             .striped(true)
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
             .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::exact(200.0))
             .column(egui_extras::Column::exact(60.0))
             .min_scrolled_height(0.0)
             .max_scroll_height(available_height)
             .body(|mut body| {
-                for ingredient in std::mem::take(&mut self.to_search) {
+                for mut ingredient in std::mem::take(&mut self.to_search) {
                     body.row(20.0, |mut row| {
                         row.col(|ui| {
-                            ui.label(&ingredient.name);
+                            ui.label(&ingredient.ingredient.name);
+                        });
+                        row.col(|ui| {
+                            ingredient.update_minimum(ui);
                         });
                         row.col(|ui| {
-                            if !ui.button("Remove").clicked() {
+                            if !ui.button(tr("Remove")).clicked() {
                                 self.to_search.push(ingredient);
                             }
                         });
@@ -313,29 +445,37 @@ impl RecipeSearchByIngredient {
                                         &mut self.cached_ingredient_search,
                                         query,
                                     )
+                                    .unwrap_or_report()
                                 },
                             )
-                            .hint_text("search for ingredient")
+                            .hint_text(tr("search for ingredient"))
                             .desired_width(f32::INFINITY),
                         )
                         .pressed_enter();
                 });
                 let e = !self.new_ingredient_name.is_empty();
                 strip.cell(|ui| {
-                    added |= ui.add_enabled(e, egui::Button::new("Add")).clicked();
+                    added |= ui.add_enabled(e, egui::Button::new(tr("Add"))).clicked();
                 });
 
                 if added && e {
                     if let Some(ingredient) = &self.new_ingredient {
-                        if self.to_search.iter().any(|i| i.id == ingredient.id) {
-                            toasts.add(new_error_toast("Ingredient already in search"));
+                        if self
+                            .to_search
+                            .iter()
+                            .any(|i| i.ingredient.id == ingredient.id)
+                        {
+                            toasts.add(new_error_toast(tr("Ingredient already in search")));
                         } else {
-                            self.to_search.push(ingredient.to_handle());
+                            self.to_search.push(SearchIngredient {
+                                ingredient: ingredient.to_handle(),
+                                minimum: None,
+                            });
                             self.new_ingredient_name = "".into();
                             self.new_ingredient = None;
                         }
                     } else {
-                        toasts.add(new_error_toast("Couldn't find ingredient"));
+                        toasts.add(new_error_toast(tr("Couldn't find ingredient")));
                     }
                 }
             });
@@ -344,10 +484,13 @@ impl RecipeSearchByIngredient {
     fn update_do_search(
         &mut self,
         conn: &mut database::Connection,
+        include_archived: bool,
         mut search_for_ingredients: impl FnMut(
             &mut database::Connection,
             IngredientSearchControl,
             Vec<IngredientHandle>,
+            Vec<query::MinimumQuantity>,
+            bool,
         ),
         ui: &mut egui::Ui,
     ) {
@@ -357,7 +500,7 @@ impl RecipeSearchByIngredient {
             .horizontal(|mut strip| {
                 strip.cell(|ui| {
                     ui.horizontal(|ui| {
-                        ui.label("for recipes including");
+                        ui.label(tr("for recipes including"));
                         egui::ComboBox::from_id_salt("recipe search combo-box")
                             .selected_text(self.control.to_string())
                             .show_ui(ui, |ui| {
@@ -369,15 +512,27 @@ impl RecipeSearchByIngredient {
                         if let IngredientSearchControl::AtLeast(v) = &mut self.control {
                             ui.add(egui::DragValue::new(v).speed(1));
                         }
-                        ui.label("of the listed ingredient");
+                        ui.label(tr("of the listed ingredient"));
                     });
                 });
                 strip.cell(|ui| {
                     if ui
-                        .add_enabled(!self.to_search.is_empty(), egui::Button::new("Search"))
+                        .add_enabled(!self.to_search.is_empty(), egui::Button::new(tr("Search")))
                         .clicked()
                     {
-                        search_for_ingredients(conn, self.control, self.to_search.clone());
+                        search_for_ingredients(
+                            conn,
+                            self.control,
+                            self.to_search
+                                .iter()
+                                .map(|i| i.ingredient.clone())
+                                .collect(),
+                            self.to_search
+                                .iter()
+                                .filter_map(|i| i.to_minimum())
+                                .collect(),
+                            include_archived,
+                        );
                     }
                 });
             });
@@ -387,10 +542,13 @@ impl RecipeSearchByIngredient {
         &mut self,
         conn: &mut database::Connection,
         toasts: &mut egui_toast::Toasts,
+        include_archived: bool,
         search_for_ingredients: impl FnMut(
             &mut database::Connection,
             IngredientSearchControl,
             Vec<IngredientHandle>,
+            Vec<query::MinimumQuantity>,
+            bool,
         ),
         ui: &mut egui::Ui,
     ) {
@@ -420,7 +578,7 @@ impl RecipeSearchByIngredient {
                 });
                 strip.cell(|ui| {
                     ui.separator();
-                    self.update_do_search(conn, search_for_ingredients, ui);
+                    self.update_do_search(conn, include_archived, search_for_ingredients, ui);
                 });
             });
     }
@@ -428,7 +586,7 @@ impl RecipeSearchByIngredient {
     fn ingredient_deleted(&mut self, id: IngredientId) {
         self.new_ingredient = None;
         self.cached_ingredient_search = None;
-        self.to_search.retain(|i| i.id != id);
+        self.to_search.retain(|i| i.ingredient.id != id);
     }
 }
 
@@ -450,14 +608,16 @@ impl RecipeSearchByName {
         conn: &mut database::Connection,
         recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
         selected_week: Option<chrono::NaiveWeek>,
+        include_archived: bool,
         ui: &mut egui::Ui,
     ) {
         ui.add(
             egui::TextEdit::singleline(&mut self.name)
-                .hint_text("search by name")
+                .hint_text(tr("search by name"))
                 .desired_width(f32::INFINITY),
         );
-        query::search_recipes(conn, &mut self.recipes, &self.name);
+        query::search_recipes(conn, &mut self.recipes, &self.name, include_archived)
+            .unwrap_or_report();
 
         let available_height = ui.available_height();
         egui_extras::TableBuilder::new(ui)
@@ -467,28 +627,27 @@ impl RecipeSearchByName {
             .column(egui_extras::Column::remainder())
             .min_scrolled_height(0.0)
             .max_scroll_height(available_height)
-            .body(|mut body| {
-                let recipe_iter = self
-                    .recipes
-                    .as_ref()
-                    .map(|c| c.results.iter())
-                    .into_iter()
-                    .flatten();
-                for (id, name) in recipe_iter {
-                    body.row(20.0, |mut row| {
-                        let mut shown = recipe_windows.contains_key(&id);
-                        row.col(|ui| {
-                            ui.toggle_value(&mut shown, name.clone());
-                        });
+            .body(|body| {
+                let results = self.recipes.as_ref().map_or(&[][..], |c| &c.results);
+                body.rows(20.0, results.len(), |mut row| {
+                    let (id, name) = &results[row.index()];
+                    let mut shown = recipe_windows.contains_key(id);
+                    row.col(|ui| {
+                        ui.toggle_value(&mut shown, name.clone())
+                            .on_hover_recipe_preview(conn, *id);
+                        diet::show_badges(ui, &diet::recipe_badges(conn, *id));
+                    });
 
-                        if shown && !recipe_windows.contains_key(&id) {
-                            recipe_windows
-                                .insert(*id, RecipeWindow::new(conn, *id, selected_week, false));
-                        } else if !shown {
-                            recipe_windows.remove(id);
+                    if shown && !recipe_windows.contains_key(id) {
+                        if let Some(window) =
+                            RecipeWindow::new(conn, *id, selected_week, false).or_report()
+                        {
+                            recipe_windows.insert(*id, window);
                         }
-                    });
-                }
+                    } else if !shown {
+                        recipe_windows.remove(id);
+                    }
+                });
             });
     }
 
@@ -512,6 +671,7 @@ pub struct RecipeSearchWindow {
     selected_tab: RecipeSearchTab,
     by_ingredient: RecipeSearchByIngredient,
     by_name: RecipeSearchByName,
+    include_archived: bool,
 }
 
 impl RecipeSearchWindow {
@@ -520,6 +680,7 @@ impl RecipeSearchWindow {
             selected_tab: Default::default(),
             by_ingredient: RecipeSearchByIngredient::new(),
             by_name: RecipeSearchByName::new(),
+            include_archived: false,
         }
     }
 
@@ -534,10 +695,13 @@ impl RecipeSearchWindow {
             &mut database::Connection,
             IngredientSearchControl,
             Vec<IngredientHandle>,
+            Vec<query::MinimumQuantity>,
+            bool,
         ),
     ) -> bool {
         let mut open = true;
-        egui::Window::new("Recipe Search")
+        egui::Window::new(tr("Recipe Search"))
+            .id(egui::Id::new("Recipe Search"))
             .open(&mut open)
             .default_height(200.0)
             .default_width(300.0)
@@ -546,15 +710,34 @@ impl RecipeSearchWindow {
                     for v in RecipeSearchTab::iter() {
                         ui.selectable_value(&mut self.selected_tab, v, v.to_string());
                     }
+                    ui.separator();
+                    if ui
+                        .checkbox(&mut self.include_archived, tr("include archived"))
+                        .changed()
+                    {
+                        // The cached results are only for one or the other.
+                        self.by_name.recipes = None;
+                    }
                 });
                 ui.separator();
                 match self.selected_tab {
                     RecipeSearchTab::ByIngredient => {
-                        self.by_ingredient
-                            .update(conn, toasts, search_for_ingredients, ui);
+                        self.by_ingredient.update(
+                            conn,
+                            toasts,
+                            self.include_archived,
+                            search_for_ingredients,
+                            ui,
+                        );
                     }
                     RecipeSearchTab::ByName => {
-                        self.by_name.update(conn, recipe_windows, selected_week, ui);
+                        self.by_name.update(
+                            conn,
+                            recipe_windows,
+                            selected_week,
+                            self.include_archived,
+                            ui,
+                        );
                     }
                 }
             });
@@ -0,0 +1,1062 @@
+use super::description_template::DescriptionTemplate;
+use super::errors::QueryResultExt as _;
+use super::i18n::{tr, Language};
+use super::{is_editable, new_error_toast, recipe::FractionPrecision, PressedEnterExt as _};
+use derive_more::Display;
+#[cfg(any(
+    feature = "email",
+    feature = "grocery-export",
+    feature = "nutrition-lookup"
+))]
+use recipe_manager::credentials::Credentials;
+use recipe_manager::database;
+use recipe_manager::diet::DietFlag;
+#[cfg(feature = "email")]
+use recipe_manager::email::{self, SmtpSettings};
+#[cfg(feature = "grocery-export")]
+use recipe_manager::grocery::GroceryAccounts;
+#[cfg(feature = "http-api")]
+use recipe_manager::http_api;
+use recipe_manager::query;
+use recipe_manager::unit_conversion::{MeasurementSystem, UnitChoices};
+#[cfg(feature = "sync")]
+use std::path::PathBuf;
+use strum::EnumIter;
+
+const DAILY_CALORIE_TARGET: &str = "daily_calorie_target";
+const FRACTION_PRECISION: &str = "fraction_precision";
+const MEASUREMENT_SYSTEM: &str = "measurement_system";
+const UNIT_CHOICES: &str = "unit_choices";
+const THEME: &str = "theme";
+const RECIPE_LAYOUT: &str = "recipe_layout";
+const DIAGNOSTICS: &str = "diagnostics";
+const HIGH_CONTRAST: &str = "high_contrast";
+const REDUCE_MOTION: &str = "reduce_motion";
+#[cfg(feature = "sync")]
+const SYNC_FOLDER: &str = "sync_folder";
+const DIET_RESTRICTIONS: &str = "diet_restrictions";
+const WEEK_START: &str = query::WEEK_START_SETTING;
+const DATE_FORMAT: &str = "date_format";
+const LANGUAGE: &str = "language";
+const DESCRIPTION_TEMPLATES: &str = "description_templates";
+#[cfg(feature = "nutrition-lookup")]
+const FDC_API_KEY: &str = "fdc_api_key";
+#[cfg(feature = "email")]
+const SMTP_SERVER: &str = "smtp_server";
+#[cfg(feature = "email")]
+const SMTP_PORT: &str = "smtp_port";
+#[cfg(feature = "email")]
+const SMTP_USERNAME: &str = "smtp_username";
+#[cfg(feature = "email")]
+const SMTP_PASSWORD: &str = "smtp_password";
+#[cfg(feature = "email")]
+const EMAIL_FROM: &str = "email_from";
+#[cfg(feature = "email")]
+const EMAIL_RECIPIENTS: &str = "email_recipients";
+#[cfg(feature = "grocery-export")]
+const TODOIST_TOKEN: &str = "todoist_token";
+#[cfg(feature = "grocery-export")]
+const TODOIST_PROJECT: &str = "todoist_project";
+#[cfg(feature = "grocery-export")]
+const BRING_EMAIL: &str = "bring_email";
+#[cfg(feature = "grocery-export")]
+const BRING_PASSWORD: &str = "bring_password";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum Theme {
+    #[default]
+    #[display("same as system")]
+    System,
+    #[display("light")]
+    Light,
+    #[display("dark")]
+    Dark,
+}
+
+impl Theme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|t| t.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+
+    pub fn preference(&self) -> egui::ThemePreference {
+        match self {
+            Self::System => egui::ThemePreference::System,
+            Self::Light => egui::ThemePreference::Light,
+            Self::Dark => egui::ThemePreference::Dark,
+        }
+    }
+}
+
+/// Whether recipes open in their own windows or as tabs in the main window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum RecipeLayout {
+    #[default]
+    #[display("windows")]
+    Windows,
+    #[display("tabs")]
+    Tabs,
+}
+
+impl RecipeLayout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Windows => "windows",
+            Self::Tabs => "tabs",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|l| l.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum WeekStart {
+    #[default]
+    #[display("Sunday")]
+    Sunday,
+    #[display("Monday")]
+    Monday,
+}
+
+impl WeekStart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sunday => "sunday",
+            Self::Monday => "monday",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|w| w.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+
+    pub fn weekday(&self) -> chrono::Weekday {
+        match self {
+            Self::Sunday => chrono::Weekday::Sun,
+            Self::Monday => chrono::Weekday::Mon,
+        }
+    }
+}
+
+/// How dates are written. Each has a long form, a short one with the month abbreviated, and one
+/// without the year. They're `strftime` formats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum DateFormat {
+    #[default]
+    #[display("March 3, 2024")]
+    MonthFirst,
+    #[display("3 March 2024")]
+    DayFirst,
+    #[display("2024-03-03")]
+    Iso,
+}
+
+impl DateFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MonthFirst => "month_first",
+            Self::DayFirst => "day_first",
+            Self::Iso => "iso",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|f| f.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+
+    #[cfg_attr(not(feature = "rtf"), expect(dead_code))]
+    pub fn long(&self) -> &'static str {
+        match self {
+            Self::MonthFirst => "%B %-d, %Y",
+            Self::DayFirst => "%-d %B %Y",
+            Self::Iso => "%Y-%m-%d",
+        }
+    }
+
+    pub fn short(&self) -> &'static str {
+        match self {
+            Self::MonthFirst => "%b %-d, %Y",
+            Self::DayFirst => "%-d %b %Y",
+            Self::Iso => "%Y-%m-%d",
+        }
+    }
+
+    pub fn without_year(&self) -> &'static str {
+        match self {
+            Self::MonthFirst => "%B %-d",
+            Self::DayFirst => "%-d %B",
+            Self::Iso => "%m-%d",
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Settings {
+    pub daily_calorie_target: Option<f32>,
+    pub fraction_precision: FractionPrecision,
+    pub measurement_system: MeasurementSystem,
+    /// Which units the lists of units offer.
+    pub unit_choices: UnitChoices,
+    pub theme: Theme,
+    pub recipe_layout: RecipeLayout,
+    pub diagnostics: bool,
+    /// Stronger text and outlines against plain backgrounds.
+    pub high_contrast: bool,
+    /// No animations, and notifications stay until they're clicked.
+    pub reduce_motion: bool,
+    /// Where the database is synced with other machines, if anywhere.
+    #[cfg(feature = "sync")]
+    pub sync_folder: Option<PathBuf>,
+    /// What the household avoids or sticks to, which the calendar warns about.
+    pub diet_restrictions: Vec<DietFlag>,
+    /// The day weeks start on in the calendar and everything else going by weeks.
+    pub week_start: WeekStart,
+    pub date_format: DateFormat,
+    /// The language of the UI and of what it exports.
+    pub language: Language,
+    /// What new recipes' descriptions can start out as.
+    pub description_templates: Vec<DescriptionTemplate>,
+    #[cfg(feature = "nutrition-lookup")]
+    pub fdc_api_key: Option<String>,
+    /// The port to serve the recipe book on, from the next start.
+    #[cfg(feature = "http-api")]
+    pub http_api_port: Option<u16>,
+    /// Whether other devices on the network can connect, rather than only this computer.
+    #[cfg(feature = "http-api")]
+    pub http_api_lan: bool,
+    /// Where the week's menu and shopping list are emailed from, and who to.
+    #[cfg(feature = "email")]
+    pub email: SmtpSettings,
+    /// The grocery list apps the shopping list can be sent to.
+    #[cfg(feature = "grocery-export")]
+    pub grocery: GroceryAccounts,
+}
+
+impl Settings {
+    pub fn load(conn: &mut database::Connection) -> Self {
+        #[cfg(any(
+            feature = "email",
+            feature = "grocery-export",
+            feature = "nutrition-lookup"
+        ))]
+        let credentials = Credentials::load().unwrap_or_else(|error| {
+            log::error!("failed to load credentials: {error}");
+            Credentials::default()
+        });
+        Self {
+            daily_calorie_target: query::get_setting(conn, DAILY_CALORIE_TARGET)
+                .unwrap_or_report()
+                .and_then(|v| v.parse().ok()),
+            fraction_precision: query::get_setting(conn, FRACTION_PRECISION)
+                .unwrap_or_report()
+                .and_then(|v| FractionPrecision::from_str(&v))
+                .unwrap_or_default(),
+            measurement_system: query::get_setting(conn, MEASUREMENT_SYSTEM)
+                .unwrap_or_report()
+                .and_then(|v| MeasurementSystem::from_str(&v))
+                .unwrap_or_default(),
+            unit_choices: query::get_setting(conn, UNIT_CHOICES)
+                .unwrap_or_report()
+                .and_then(|v| UnitChoices::from_str(&v))
+                .unwrap_or_default(),
+            theme: query::get_setting(conn, THEME)
+                .unwrap_or_report()
+                .and_then(|v| Theme::from_str(&v))
+                .unwrap_or_default(),
+            recipe_layout: query::get_setting(conn, RECIPE_LAYOUT)
+                .unwrap_or_report()
+                .and_then(|v| RecipeLayout::from_str(&v))
+                .unwrap_or_default(),
+            diagnostics: query::get_setting(conn, DIAGNOSTICS)
+                .unwrap_or_report()
+                .is_some_and(|v| v == "true"),
+            high_contrast: query::get_setting(conn, HIGH_CONTRAST)
+                .unwrap_or_report()
+                .is_some_and(|v| v == "true"),
+            reduce_motion: query::get_setting(conn, REDUCE_MOTION)
+                .unwrap_or_report()
+                .is_some_and(|v| v == "true"),
+            #[cfg(feature = "sync")]
+            sync_folder: query::get_setting(conn, SYNC_FOLDER)
+                .unwrap_or_report()
+                .map(PathBuf::from),
+            diet_restrictions: query::get_setting(conn, DIET_RESTRICTIONS)
+                .unwrap_or_report()
+                .map(|v| v.split(',').filter_map(DietFlag::from_str).collect())
+                .unwrap_or_default(),
+            week_start: query::get_setting(conn, WEEK_START)
+                .unwrap_or_report()
+                .and_then(|v| WeekStart::from_str(&v))
+                .unwrap_or_default(),
+            date_format: query::get_setting(conn, DATE_FORMAT)
+                .unwrap_or_report()
+                .and_then(|v| DateFormat::from_str(&v))
+                .unwrap_or_default(),
+            language: query::get_setting(conn, LANGUAGE)
+                .unwrap_or_report()
+                .and_then(|v| Language::from_str(&v))
+                .unwrap_or_default(),
+            description_templates: query::get_setting(conn, DESCRIPTION_TEMPLATES)
+                .unwrap_or_report()
+                .map(|v| DescriptionTemplate::parse_all(&v))
+                .unwrap_or_else(DescriptionTemplate::defaults),
+            #[cfg(feature = "nutrition-lookup")]
+            fdc_api_key: credentials.get(FDC_API_KEY).map(Into::into),
+            #[cfg(feature = "http-api")]
+            http_api_port: query::get_setting(conn, http_api::PORT_SETTING)
+                .unwrap_or_report()
+                .and_then(|v| v.parse().ok()),
+            #[cfg(feature = "http-api")]
+            http_api_lan: query::get_setting(conn, http_api::LAN_SETTING)
+                .unwrap_or_report()
+                .is_some_and(|v| v == "true"),
+            #[cfg(feature = "email")]
+            email: SmtpSettings {
+                server: query::get_setting(conn, SMTP_SERVER)
+                    .unwrap_or_report()
+                    .unwrap_or_default(),
+                port: query::get_setting(conn, SMTP_PORT)
+                    .unwrap_or_report()
+                    .and_then(|v| v.parse().ok()),
+                username: query::get_setting(conn, SMTP_USERNAME)
+                    .unwrap_or_report()
+                    .unwrap_or_default(),
+                password: credentials.get(SMTP_PASSWORD).unwrap_or_default().into(),
+                from: query::get_setting(conn, EMAIL_FROM)
+                    .unwrap_or_report()
+                    .unwrap_or_default(),
+                recipients: query::get_setting(conn, EMAIL_RECIPIENTS)
+                    .unwrap_or_report()
+                    .map(|v| email::parse_recipients(&v))
+                    .unwrap_or_default(),
+            },
+            #[cfg(feature = "grocery-export")]
+            grocery: GroceryAccounts {
+                todoist_token: credentials.get(TODOIST_TOKEN).unwrap_or_default().into(),
+                todoist_project: query::get_setting(conn, TODOIST_PROJECT)
+                    .unwrap_or_report()
+                    .unwrap_or_default(),
+                bring_email: query::get_setting(conn, BRING_EMAIL)
+                    .unwrap_or_report()
+                    .unwrap_or_default(),
+                bring_password: credentials.get(BRING_PASSWORD).unwrap_or_default().into(),
+            },
+        }
+    }
+
+    /// Only the credentials file can fail to be written, the database errors are reported.
+    fn save(&self, conn: &mut database::Connection) -> recipe_manager::Result<()> {
+        query::set_setting(
+            conn,
+            DAILY_CALORIE_TARGET,
+            self.daily_calorie_target.map(|t| t.to_string()).as_deref(),
+        )
+        .or_report();
+        query::set_setting(
+            conn,
+            FRACTION_PRECISION,
+            Some(self.fraction_precision.as_str()),
+        )
+        .or_report();
+        query::set_setting(
+            conn,
+            MEASUREMENT_SYSTEM,
+            Some(self.measurement_system.as_str()),
+        )
+        .or_report();
+        query::set_setting(conn, UNIT_CHOICES, Some(self.unit_choices.as_str())).or_report();
+        query::set_setting(conn, THEME, Some(self.theme.as_str())).or_report();
+        query::set_setting(conn, RECIPE_LAYOUT, Some(self.recipe_layout.as_str())).or_report();
+        query::set_setting(conn, DIAGNOSTICS, Some(&self.diagnostics.to_string())).or_report();
+        query::set_setting(conn, HIGH_CONTRAST, Some(&self.high_contrast.to_string())).or_report();
+        query::set_setting(conn, REDUCE_MOTION, Some(&self.reduce_motion.to_string())).or_report();
+        #[cfg(feature = "sync")]
+        query::set_setting(
+            conn,
+            SYNC_FOLDER,
+            self.sync_folder
+                .as_ref()
+                .map(|f| f.to_string_lossy())
+                .as_deref(),
+        )
+        .or_report();
+        let restrictions: Vec<_> = self.diet_restrictions.iter().map(|r| r.as_str()).collect();
+        query::set_setting(
+            conn,
+            DIET_RESTRICTIONS,
+            Some(restrictions.join(","))
+                .filter(|r| !r.is_empty())
+                .as_deref(),
+        )
+        .or_report();
+        query::set_setting(conn, WEEK_START, Some(self.week_start.as_str())).or_report();
+        query::set_setting(conn, DATE_FORMAT, Some(self.date_format.as_str())).or_report();
+        query::set_setting(conn, LANGUAGE, Some(self.language.as_str())).or_report();
+        query::set_setting(
+            conn,
+            DESCRIPTION_TEMPLATES,
+            Some(&DescriptionTemplate::write_all(&self.description_templates)),
+        )
+        .or_report();
+        #[cfg(feature = "http-api")]
+        query::set_setting(
+            conn,
+            http_api::PORT_SETTING,
+            self.http_api_port.map(|p| p.to_string()).as_deref(),
+        )
+        .or_report();
+        #[cfg(feature = "http-api")]
+        query::set_setting(
+            conn,
+            http_api::LAN_SETTING,
+            Some(&self.http_api_lan.to_string()),
+        )
+        .or_report();
+        #[cfg(feature = "email")]
+        {
+            let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_owned());
+            let email = &self.email;
+            query::set_setting(conn, SMTP_SERVER, non_empty(&email.server).as_deref()).or_report();
+            query::set_setting(
+                conn,
+                SMTP_PORT,
+                email.port.map(|p| p.to_string()).as_deref(),
+            )
+            .or_report();
+            query::set_setting(conn, SMTP_USERNAME, non_empty(&email.username).as_deref())
+                .or_report();
+            query::set_setting(conn, EMAIL_FROM, non_empty(&email.from).as_deref()).or_report();
+            query::set_setting(
+                conn,
+                EMAIL_RECIPIENTS,
+                non_empty(&email.recipients.join(", ")).as_deref(),
+            )
+            .or_report();
+        }
+        #[cfg(feature = "grocery-export")]
+        {
+            let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_owned());
+            let grocery = &self.grocery;
+            let values = [
+                (TODOIST_PROJECT, &grocery.todoist_project),
+                (BRING_EMAIL, &grocery.bring_email),
+            ];
+            for (key, value) in values {
+                query::set_setting(conn, key, non_empty(value).as_deref()).or_report();
+            }
+        }
+        #[cfg(any(
+            feature = "email",
+            feature = "grocery-export",
+            feature = "nutrition-lookup"
+        ))]
+        {
+            let mut credentials = Credentials::load()?;
+            #[cfg(feature = "nutrition-lookup")]
+            credentials.set(FDC_API_KEY, self.fdc_api_key.as_deref());
+            #[cfg(feature = "email")]
+            credentials.set(SMTP_PASSWORD, Some(&self.email.password));
+            #[cfg(feature = "grocery-export")]
+            {
+                credentials.set(TODOIST_TOKEN, Some(&self.grocery.todoist_token));
+                credentials.set(BRING_PASSWORD, Some(&self.grocery.bring_password));
+            }
+            credentials.save()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "nutrition-lookup")]
+    pub fn fdc_api_key(&self) -> &str {
+        self.fdc_api_key
+            .as_deref()
+            .unwrap_or(recipe_manager::fdc::DEMO_API_KEY)
+    }
+}
+
+pub struct SettingsWindow {
+    daily_calorie_target: String,
+    fraction_precision: FractionPrecision,
+    measurement_system: MeasurementSystem,
+    unit_choices: UnitChoices,
+    theme: Theme,
+    recipe_layout: RecipeLayout,
+    diagnostics: bool,
+    high_contrast: bool,
+    reduce_motion: bool,
+    #[cfg(feature = "sync")]
+    sync_folder: String,
+    diet_restrictions: Vec<DietFlag>,
+    week_start: WeekStart,
+    date_format: DateFormat,
+    language: Language,
+    description_templates: Vec<DescriptionTemplate>,
+    #[cfg(feature = "nutrition-lookup")]
+    fdc_api_key: String,
+    #[cfg(feature = "http-api")]
+    http_api_port: String,
+    #[cfg(feature = "http-api")]
+    http_api_lan: bool,
+    #[cfg(feature = "email")]
+    smtp_server: String,
+    #[cfg(feature = "email")]
+    smtp_port: String,
+    #[cfg(feature = "email")]
+    smtp_username: String,
+    #[cfg(feature = "email")]
+    smtp_password: String,
+    #[cfg(feature = "email")]
+    email_from: String,
+    #[cfg(feature = "email")]
+    email_recipients: String,
+    #[cfg(feature = "grocery-export")]
+    grocery: GroceryAccounts,
+}
+
+impl SettingsWindow {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            daily_calorie_target: settings
+                .daily_calorie_target
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+            fraction_precision: settings.fraction_precision,
+            measurement_system: settings.measurement_system,
+            unit_choices: settings.unit_choices,
+            theme: settings.theme,
+            recipe_layout: settings.recipe_layout,
+            diagnostics: settings.diagnostics,
+            high_contrast: settings.high_contrast,
+            reduce_motion: settings.reduce_motion,
+            #[cfg(feature = "sync")]
+            sync_folder: settings
+                .sync_folder
+                .as_ref()
+                .map(|f| f.display().to_string())
+                .unwrap_or_default(),
+            diet_restrictions: settings.diet_restrictions.clone(),
+            week_start: settings.week_start,
+            date_format: settings.date_format,
+            language: settings.language,
+            description_templates: settings.description_templates.clone(),
+            #[cfg(feature = "nutrition-lookup")]
+            fdc_api_key: settings.fdc_api_key.clone().unwrap_or_default(),
+            #[cfg(feature = "http-api")]
+            http_api_port: settings
+                .http_api_port
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+            #[cfg(feature = "http-api")]
+            http_api_lan: settings.http_api_lan,
+            #[cfg(feature = "email")]
+            smtp_server: settings.email.server.clone(),
+            #[cfg(feature = "email")]
+            smtp_port: settings
+                .email
+                .port
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+            #[cfg(feature = "email")]
+            smtp_username: settings.email.username.clone(),
+            #[cfg(feature = "email")]
+            smtp_password: settings.email.password.clone(),
+            #[cfg(feature = "email")]
+            email_from: settings.email.from.clone(),
+            #[cfg(feature = "email")]
+            email_recipients: settings.email.recipients.join(", "),
+            #[cfg(feature = "grocery-export")]
+            grocery: settings.grocery.clone(),
+        }
+    }
+
+    /// The settings for emailing the week's menu, shown apart from the rest since there are a lot
+    /// of them.
+    #[cfg(feature = "email")]
+    fn update_email(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut save = false;
+        egui::CollapsingHeader::new("Email").show(ui, |ui| {
+            egui::Grid::new("settings email grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    let port_hint = email::DEFAULT_PORT.to_string();
+                    let fields = [
+                        ("SMTP server:", &mut self.smtp_server, "none", None),
+                        ("Port:", &mut self.smtp_port, &port_hint, None),
+                        ("Username:", &mut self.smtp_username, "", None),
+                        (
+                            "Password:",
+                            &mut self.smtp_password,
+                            "",
+                            Some(
+                                "Kept unencrypted in the database, and in the copy in the sync \
+                                folder if there is one.",
+                            ),
+                        ),
+                        ("From:", &mut self.email_from, "same as username", None),
+                        (
+                            "Send to:",
+                            &mut self.email_recipients,
+                            "",
+                            Some("The addresses to send the menu and shopping list to"),
+                        ),
+                    ];
+                    for (label, value, hint, hover) in fields {
+                        ui.label(label);
+                        let mut response = ui.add(
+                            egui::TextEdit::singleline(value)
+                                .hint_text(hint)
+                                .password(label == "Password:")
+                                .desired_width(200.0),
+                        );
+                        if let Some(hover) = hover {
+                            response = response.on_hover_text(hover);
+                        }
+                        save |= response.pressed_enter();
+                        ui.end_row();
+                    }
+                });
+        });
+        save
+    }
+
+    /// The templates for new recipes' descriptions, each with a name to pick it by.
+    fn update_description_templates(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Description templates").show(ui, |ui| {
+            let mut delete = None;
+            for (i, template) in self.description_templates.iter_mut().enumerate() {
+                ui.push_id(i, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut template.name)
+                                .hint_text("name")
+                                .desired_width(200.0),
+                        );
+                        if ui.button("Delete").clicked() {
+                            delete = Some(i);
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::multiline(&mut template.text)
+                            .desired_rows(3)
+                            .desired_width(300.0),
+                    );
+                });
+            }
+            if let Some(i) = delete {
+                self.description_templates.remove(i);
+            }
+            if ui.button("Add Template").clicked() {
+                self.description_templates.push(DescriptionTemplate {
+                    name: String::new(),
+                    text: String::new(),
+                });
+            }
+        });
+    }
+
+    /// The accounts for the grocery list apps the shopping list can be sent to.
+    #[cfg(feature = "grocery-export")]
+    fn update_grocery(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut save = false;
+        egui::CollapsingHeader::new("Grocery list apps").show(ui, |ui| {
+            egui::Grid::new("settings grocery grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    let grocery = &mut self.grocery;
+                    let fields = [
+                        (
+                            "Todoist API token:",
+                            &mut grocery.todoist_token,
+                            "none",
+                            Some("Found in Todoist under Settings, Integrations, Developer"),
+                        ),
+                        (
+                            "Todoist project:",
+                            &mut grocery.todoist_project,
+                            "Inbox",
+                            None,
+                        ),
+                        ("Bring! email:", &mut grocery.bring_email, "none", None),
+                        (
+                            "Bring! password:",
+                            &mut grocery.bring_password,
+                            "",
+                            Some(
+                                "Kept unencrypted in the database, and in the copy in the sync \
+                                folder if there is one.",
+                            ),
+                        ),
+                    ];
+                    for (label, value, hint, hover) in fields {
+                        ui.label(label);
+                        let mut response = ui.add(
+                            egui::TextEdit::singleline(value)
+                                .hint_text(hint)
+                                .password(label.ends_with("password:") || label.ends_with("token:"))
+                                .desired_width(200.0),
+                        );
+                        if let Some(hover) = hover {
+                            response = response.on_hover_text(hover);
+                        }
+                        save |= response.pressed_enter();
+                        ui.end_row();
+                    }
+                });
+        });
+        save
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        toasts: &mut egui_toast::Toasts,
+        settings: &mut Settings,
+    ) -> bool {
+        let mut open = true;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .resizable([false, false])
+            .show(ctx, |ui| {
+                let mut save = false;
+                egui::Grid::new("settings grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label(tr("Language:"));
+                        egui::ComboBox::from_id_salt("settings language")
+                            .selected_text(self.language.to_string())
+                            .show_ui(ui, |ui| {
+                                for l in Language::iter() {
+                                    ui.selectable_value(&mut self.language, l, l.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Daily calorie target:"));
+                        save |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut self.daily_calorie_target)
+                                    .hint_text("none")
+                                    .desired_width(80.0),
+                            )
+                            .pressed_enter();
+                        ui.end_row();
+
+                        ui.label(tr("Fractions:"));
+                        egui::ComboBox::from_id_salt("settings fraction precision")
+                            .selected_text(self.fraction_precision.to_string())
+                            .show_ui(ui, |ui| {
+                                for p in FractionPrecision::iter() {
+                                    ui.selectable_value(
+                                        &mut self.fraction_precision,
+                                        p,
+                                        p.to_string(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Measurements:"));
+                        egui::ComboBox::from_id_salt("settings measurement system")
+                            .selected_text(self.measurement_system.to_string())
+                            .show_ui(ui, |ui| {
+                                for s in MeasurementSystem::iter() {
+                                    ui.selectable_value(
+                                        &mut self.measurement_system,
+                                        s,
+                                        s.to_string(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Unit lists:"));
+                        egui::ComboBox::from_id_salt("settings unit choices")
+                            .selected_text(self.unit_choices.to_string())
+                            .show_ui(ui, |ui| {
+                                for c in UnitChoices::iter() {
+                                    ui.selectable_value(&mut self.unit_choices, c, c.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Theme:"));
+                        egui::ComboBox::from_id_salt("settings theme")
+                            .selected_text(self.theme.to_string())
+                            .show_ui(ui, |ui| {
+                                for t in Theme::iter() {
+                                    ui.selectable_value(&mut self.theme, t, t.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Weeks start on:"));
+                        egui::ComboBox::from_id_salt("settings week start")
+                            .selected_text(self.week_start.to_string())
+                            .show_ui(ui, |ui| {
+                                for w in WeekStart::iter() {
+                                    ui.selectable_value(&mut self.week_start, w, w.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Dates:"));
+                        egui::ComboBox::from_id_salt("settings date format")
+                            .selected_text(self.date_format.to_string())
+                            .show_ui(ui, |ui| {
+                                for f in DateFormat::iter() {
+                                    ui.selectable_value(&mut self.date_format, f, f.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Open recipes in:"));
+                        egui::ComboBox::from_id_salt("settings recipe layout")
+                            .selected_text(self.recipe_layout.to_string())
+                            .show_ui(ui, |ui| {
+                                for l in RecipeLayout::iter() {
+                                    ui.selectable_value(&mut self.recipe_layout, l, l.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(tr("Diagnostics:"));
+                        ui.checkbox(
+                            &mut self.diagnostics,
+                            "Log slow queries and show frame times",
+                        )
+                        .on_hover_text(
+                            "Useful for reporting what is slow. Set RUST_LOG=info to log \
+                                every query.",
+                        );
+                        ui.end_row();
+
+                        ui.label(tr("Accessibility:"));
+                        ui.vertical(|ui| {
+                            ui.checkbox(&mut self.high_contrast, "High contrast");
+                            ui.checkbox(&mut self.reduce_motion, "Reduce motion")
+                                .on_hover_text(
+                                    "No animations, and notifications stay until they're clicked",
+                                );
+                        });
+                        ui.end_row();
+
+                        #[cfg(feature = "sync")]
+                        {
+                            ui.label(tr("Sync folder:"));
+                            save |= ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut self.sync_folder)
+                                        .hint_text("none")
+                                        .desired_width(200.0),
+                                )
+                                .on_hover_text(
+                                    "A folder kept in sync between machines, like one in Dropbox. \
+                                    Changes from other machines are merged at startup.",
+                                )
+                                .pressed_enter();
+                            ui.end_row();
+                        }
+
+                        ui.label(tr("Household diet:"));
+                        ui.horizontal_wrapped(|ui| {
+                            for flag in DietFlag::iter() {
+                                let mut restricted = self.diet_restrictions.contains(&flag);
+                                if ui.checkbox(&mut restricted, flag.restriction()).changed() {
+                                    if restricted {
+                                        self.diet_restrictions.push(flag);
+                                    } else {
+                                        self.diet_restrictions.retain(|r| *r != flag);
+                                    }
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("The calendar warns about recipes which don't fit");
+                        ui.end_row();
+
+                        #[cfg(feature = "nutrition-lookup")]
+                        {
+                            ui.label("FoodData Central API key:");
+                            save |= ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut self.fdc_api_key)
+                                        .hint_text(recipe_manager::fdc::DEMO_API_KEY)
+                                        .desired_width(200.0),
+                                )
+                                .pressed_enter();
+                            ui.end_row();
+                        }
+
+                        #[cfg(feature = "http-api")]
+                        {
+                            ui.label("Serve on port:");
+                            save |= ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut self.http_api_port)
+                                        .hint_text("off")
+                                        .desired_width(80.0),
+                                )
+                                .on_hover_text(
+                                    "Lets a browser look at the recipes and the week's menu. \
+                                    Takes effect the next time Recipe Manager starts.",
+                                )
+                                .pressed_enter();
+                            ui.end_row();
+
+                            ui.label("");
+                            ui.checkbox(&mut self.http_api_lan, "Serve to other devices")
+                                .on_hover_text(
+                                    "Lets phones and other devices on the network connect, not \
+                                    just this computer. Anyone on the network can see the recipe \
+                                    book.",
+                                );
+                            ui.end_row();
+                        }
+                    });
+                self.update_description_templates(ui);
+                #[cfg(feature = "email")]
+                {
+                    save |= self.update_email(ui);
+                }
+                #[cfg(feature = "grocery-export")]
+                {
+                    save |= self.update_grocery(ui);
+                }
+                ui.separator();
+                let editable = is_editable(ui.ctx());
+                save |= ui
+                    .add_enabled(editable, egui::Button::new(tr("Save")))
+                    .clicked();
+
+                if save && editable {
+                    let target = self.daily_calorie_target.trim();
+                    let daily_calorie_target = if target.is_empty() {
+                        None
+                    } else if let Ok(target) = target.parse() {
+                        Some(target)
+                    } else {
+                        toasts.add(new_error_toast("Calorie target must be a number"));
+                        return;
+                    };
+                    #[cfg(feature = "http-api")]
+                    let http_api_port = match self.http_api_port.trim() {
+                        "" => None,
+                        port => match port.parse() {
+                            Ok(port) => Some(port),
+                            Err(_) => {
+                                toasts.add(new_error_toast("Port must be a number up to 65535"));
+                                return;
+                            }
+                        },
+                    };
+                    #[cfg(feature = "email")]
+                    let smtp_port = match self.smtp_port.trim() {
+                        "" => None,
+                        port => match port.parse() {
+                            Ok(port) => Some(port),
+                            Err(_) => {
+                                toasts
+                                    .add(new_error_toast("SMTP port must be a number up to 65535"));
+                                return;
+                            }
+                        },
+                    };
+                    #[cfg(feature = "sync")]
+                    let folder = self.sync_folder.trim();
+                    #[cfg(feature = "sync")]
+                    if !folder.is_empty() && !std::path::Path::new(folder).is_dir() {
+                        toasts.add(new_error_toast("Sync folder must be an existing folder"));
+                        return;
+                    }
+
+                    settings.daily_calorie_target = daily_calorie_target;
+                    settings.fraction_precision = self.fraction_precision;
+                    settings.measurement_system = self.measurement_system;
+                    settings.unit_choices = self.unit_choices;
+                    settings.theme = self.theme;
+                    settings.recipe_layout = self.recipe_layout;
+                    settings.diagnostics = self.diagnostics;
+                    settings.high_contrast = self.high_contrast;
+                    settings.reduce_motion = self.reduce_motion;
+                    #[cfg(feature = "sync")]
+                    {
+                        settings.sync_folder = (!folder.is_empty()).then(|| folder.into());
+                    }
+                    settings.diet_restrictions = self.diet_restrictions.clone();
+                    settings.week_start = self.week_start;
+                    settings.date_format = self.date_format;
+                    settings.language = self.language;
+                    settings.description_templates = self
+                        .description_templates
+                        .iter()
+                        .filter(|t| !t.name.trim().is_empty())
+                        .cloned()
+                        .collect();
+                    #[cfg(feature = "nutrition-lookup")]
+                    {
+                        let key = self.fdc_api_key.trim();
+                        settings.fdc_api_key = (!key.is_empty()).then(|| key.into());
+                    }
+                    #[cfg(feature = "http-api")]
+                    {
+                        settings.http_api_port = http_api_port;
+                        settings.http_api_lan = self.http_api_lan;
+                    }
+                    #[cfg(feature = "email")]
+                    {
+                        settings.email = SmtpSettings {
+                            server: self.smtp_server.trim().into(),
+                            port: smtp_port,
+                            username: self.smtp_username.trim().into(),
+                            password: self.smtp_password.clone(),
+                            from: self.email_from.trim().into(),
+                            recipients: email::parse_recipients(&self.email_recipients),
+                        };
+                    }
+                    #[cfg(feature = "grocery-export")]
+                    {
+                        let grocery = &self.grocery;
+                        settings.grocery = GroceryAccounts {
+                            todoist_token: grocery.todoist_token.trim().into(),
+                            todoist_project: grocery.todoist_project.trim().into(),
+                            bring_email: grocery.bring_email.trim().into(),
+                            bring_password: grocery.bring_password.clone(),
+                        };
+                    }
+                    if let Err(error) = settings.save(conn) {
+                        log::error!("failed to save credentials: {error}");
+                        toasts.add(new_error_toast(format!(
+                            "Couldn't save the passwords and tokens: {error}"
+                        )));
+                    }
+                }
+            });
+        !open
+    }
+}
@@ -0,0 +1,65 @@
+use super::new_success_toast;
+use recipe_manager::database::models::Recipe;
+use recipe_manager::recipe::RecipeIngredient;
+use recipe_manager::share;
+
+/// The blank modules around a QR code which scanners need to find it.
+const QUIET_ZONE: usize = 4;
+
+const QR_CODE_SIZE: f32 = 240.0;
+
+fn qr_code_image(code: &str) -> recipe_manager::Result<egui::ColorImage> {
+    let qr = qrcode::QrCode::with_error_correction_level(code, qrcode::EcLevel::L)?;
+    let width = qr.width();
+    let size = width + QUIET_ZONE * 2;
+    let mut image = egui::ColorImage::new([size, size], egui::Color32::WHITE);
+    for (i, color) in qr.to_colors().into_iter().enumerate() {
+        if color == qrcode::Color::Dark {
+            let (x, y) = (i % width + QUIET_ZONE, i / width + QUIET_ZONE);
+            image.pixels[y * size + x] = egui::Color32::BLACK;
+        }
+    }
+    Ok(image)
+}
+
+/// A recipe's share code, and the QR code of it. Long recipes don't fit in a QR code, but the code
+/// can still be copied.
+pub struct RecipeShare {
+    code: String,
+    qr_code: recipe_manager::Result<egui::TextureHandle>,
+}
+
+impl RecipeShare {
+    pub fn new(
+        ctx: &egui::Context,
+        recipe: &Recipe,
+        category: &str,
+        ingredients: &[RecipeIngredient],
+    ) -> Self {
+        let code = share::encode_share_code(recipe, category, ingredients);
+        let qr_code = qr_code_image(&code)
+            .map(|image| ctx.load_texture("share qr code", image, egui::TextureOptions::NEAREST));
+        Self { code, qr_code }
+    }
+
+    pub fn update(&self, toasts: &mut egui_toast::Toasts, ui: &mut egui::Ui) {
+        match &self.qr_code {
+            Ok(texture) => {
+                let size = egui::vec2(QR_CODE_SIZE, QR_CODE_SIZE);
+                ui.add(egui::Image::from_texture(egui::load::SizedTexture::new(
+                    texture.id(),
+                    size,
+                )));
+            }
+            Err(_) => {
+                ui.label("This recipe is too long for a QR code, but the code can still be sent.");
+            }
+        }
+        ui.label("Anyone with Recipe Manager can add it from Import Data.");
+        if ui.button("Copy Share Code").clicked() {
+            ui.ctx().copy_text(self.code.clone());
+            toasts.add(new_success_toast("Copied the share code"));
+            ui.close_menu();
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use super::errors::QueryResultExt as _;
+use super::{
+    calendar::this_week,
+    i18n,
+    recipe::{quantity_display_with_precision, FractionPrecision},
+    settings::Settings,
+};
+use recipe_manager::database;
+use recipe_manager::database::models::{IngredientId, IngredientMeasurement};
+use recipe_manager::query;
+use recipe_manager::unit_conversion::{convert_totals_to_system, MeasurementSystem};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Total quantity of an ingredient needed for a week, by unit.
+type Totals = BTreeMap<Option<IngredientMeasurement>, f32>;
+
+/// How the quantities needed changed going from the first week to the second. Units which didn't
+/// change are left out.
+fn quantity_deltas(first: &Totals, second: &Totals) -> Vec<(Option<IngredientMeasurement>, f32)> {
+    let mut units: Vec<_> = first.keys().chain(second.keys()).copied().collect();
+    units.sort();
+    units.dedup();
+    units
+        .into_iter()
+        .filter_map(|u| {
+            let delta = second.get(&u).unwrap_or(&0.0) - first.get(&u).unwrap_or(&0.0);
+            (delta.abs() > f32::EPSILON).then_some((u, delta))
+        })
+        .collect()
+}
+
+#[test]
+fn quantity_deltas_test() {
+    use maplit::btreemap;
+    use IngredientMeasurement::*;
+
+    assert_eq!(
+        quantity_deltas(
+            &btreemap! { Some(Cups) => 1.0, None => 2.0 },
+            &btreemap! { Some(Cups) => 1.5, None => 2.0, Some(Grams) => 100.0 },
+        ),
+        vec![(Some(Cups), 0.5), (Some(Grams), 100.0)]
+    );
+    assert_eq!(
+        quantity_deltas(&btreemap! { Some(Teaspoons) => 2.0 }, &btreemap! {}),
+        vec![(Some(Teaspoons), -2.0)]
+    );
+    assert_eq!(
+        quantity_deltas(&btreemap! { None => 3.0 }, &btreemap! { None => 3.0 }),
+        vec![]
+    );
+}
+
+fn quantity_text(
+    quantity: f32,
+    units: Option<IngredientMeasurement>,
+    precision: FractionPrecision,
+) -> String {
+    let quantity = quantity_display_with_precision(quantity, &units, precision);
+    match units {
+        Some(units) => format!("{quantity} {}", i18n::units(units)),
+        None => quantity,
+    }
+}
+
+fn totals_text(totals: &Totals, precision: FractionPrecision) -> String {
+    totals
+        .iter()
+        .map(|(u, q)| quantity_text(*q, *u, precision))
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+fn deltas_text(first: &Totals, second: &Totals, precision: FractionPrecision) -> String {
+    let deltas = quantity_deltas(first, second);
+    if deltas.is_empty() {
+        return "same".into();
+    }
+    deltas
+        .into_iter()
+        .map(|(u, q)| {
+            let sign = if q < 0.0 { "-" } else { "+" };
+            format!("{sign}{}", quantity_text(q.abs(), u, precision))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+struct ComparedIngredient {
+    name: String,
+    notes: BTreeSet<String>,
+    first: Totals,
+    second: Totals,
+}
+
+#[derive(Default)]
+struct WeekTotal {
+    name: String,
+    notes: BTreeSet<String>,
+    totals: Totals,
+}
+
+fn get_week_totals(
+    conn: &mut database::Connection,
+    week: chrono::NaiveWeek,
+    include_staples: bool,
+) -> HashMap<IngredientId, WeekTotal> {
+    let mut totals: HashMap<IngredientId, WeekTotal> = HashMap::new();
+    let ingredients = query::get_calendar_week_shopping_ingredients(conn, week).unwrap_or_report();
+    for (usage, ingredient) in ingredients {
+        if ingredient.staple && !include_staples {
+            continue;
+        }
+        let total = totals.entry(ingredient.id).or_insert_with(|| WeekTotal {
+            name: ingredient.name.clone(),
+            ..Default::default()
+        });
+        *total.totals.entry(usage.quantity_units).or_default() += usage.quantity;
+        if !usage.note.is_empty() {
+            total.notes.insert(usage.note);
+        }
+    }
+    totals
+}
+
+pub struct ShoppingComparisonWindow {
+    first: chrono::NaiveWeek,
+    second: chrono::NaiveWeek,
+    include_staples: bool,
+    common: Vec<ComparedIngredient>,
+    only_first: Vec<ComparedIngredient>,
+    only_second: Vec<ComparedIngredient>,
+}
+
+impl ShoppingComparisonWindow {
+    pub fn new(
+        conn: &mut database::Connection,
+        first: Option<chrono::NaiveWeek>,
+        week_start: chrono::Weekday,
+    ) -> Self {
+        let first = first.unwrap_or_else(|| this_week(week_start));
+        let second = first
+            .first_day()
+            .checked_add_days(chrono::Days::new(7))
+            .unwrap()
+            .week(week_start);
+        Self::new_with_args(conn, first, second, false)
+    }
+
+    fn new_with_args(
+        conn: &mut database::Connection,
+        first: chrono::NaiveWeek,
+        second: chrono::NaiveWeek,
+        include_staples: bool,
+    ) -> Self {
+        let mut first_totals = get_week_totals(conn, first, include_staples);
+        let mut second_totals = get_week_totals(conn, second, include_staples);
+
+        let mut common = vec![];
+        let mut only_first = vec![];
+        for (id, first) in first_totals.drain() {
+            match second_totals.remove(&id) {
+                Some(second) => common.push(ComparedIngredient {
+                    name: first.name,
+                    notes: first.notes.union(&second.notes).cloned().collect(),
+                    first: first.totals,
+                    second: second.totals,
+                }),
+                None => only_first.push(ComparedIngredient {
+                    name: first.name,
+                    notes: first.notes,
+                    first: first.totals,
+                    second: Totals::new(),
+                }),
+            }
+        }
+        let mut only_second: Vec<_> = second_totals
+            .into_values()
+            .map(|second| ComparedIngredient {
+                name: second.name,
+                notes: second.notes,
+                first: Totals::new(),
+                second: second.totals,
+            })
+            .collect();
+
+        for list in [&mut common, &mut only_first, &mut only_second] {
+            list.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        Self {
+            first,
+            second,
+            include_staples,
+            common,
+            only_first,
+            only_second,
+        }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        *self = Self::new_with_args(conn, self.first, self.second, self.include_staples);
+    }
+
+    fn week_picker(
+        ui: &mut egui::Ui,
+        id_salt: &str,
+        week: chrono::NaiveWeek,
+        settings: &Settings,
+    ) -> chrono::NaiveWeek {
+        let mut date = week.first_day();
+        ui.add(
+            egui_extras::DatePickerButton::new(&mut date)
+                .id_salt(id_salt)
+                .format(settings.date_format.short()),
+        );
+        date.week(settings.week_start.weekday())
+    }
+
+    fn update_section(
+        ui: &mut egui::Ui,
+        heading: &str,
+        ingredients: &[ComparedIngredient],
+        precision: FractionPrecision,
+        system: MeasurementSystem,
+    ) {
+        ui.heading(format!("{heading} ({})", ingredients.len()));
+        egui::Grid::new(("shopping comparison", heading))
+            .num_columns(4)
+            .striped(true)
+            .min_col_width(80.0)
+            .show(ui, |ui| {
+                ui.strong("Ingredient");
+                ui.strong("First week");
+                ui.strong("Second week");
+                ui.strong("Change");
+                ui.end_row();
+
+                for i in ingredients {
+                    let first = convert_totals_to_system(&i.first, system);
+                    let second = convert_totals_to_system(&i.second, system);
+                    let name = ui.label(&i.name);
+                    if !i.notes.is_empty() {
+                        let notes: Vec<_> = i.notes.iter().map(|n| n.as_str()).collect();
+                        name.on_hover_text(notes.join("\n"));
+                    }
+                    ui.label(totals_text(&first, precision));
+                    ui.label(totals_text(&second, precision));
+                    ui.label(deltas_text(&first, &second, precision));
+                    ui.end_row();
+                }
+            });
+        ui.add_space(10.0);
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        settings: &Settings,
+    ) -> bool {
+        let mut open = true;
+        egui::Window::new("Shopping Comparison")
+            .open(&mut open)
+            .default_width(600.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Week of");
+                    let first =
+                        Self::week_picker(ui, "shopping comparison first", self.first, settings);
+                    ui.label("compared to week of");
+                    let second =
+                        Self::week_picker(ui, "shopping comparison second", self.second, settings);
+                    let mut include_staples = self.include_staples;
+                    ui.checkbox(&mut include_staples, "Include staples");
+                    if first != self.first
+                        || second != self.second
+                        || include_staples != self.include_staples
+                    {
+                        *self = Self::new_with_args(conn, first, second, include_staples);
+                    }
+                });
+                ui.separator();
+
+                let precision = settings.fraction_precision;
+                let system = settings.measurement_system;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (heading, ingredients) in [
+                        ("In both weeks", &self.common),
+                        ("Only in first week", &self.only_first),
+                        ("Only in second week", &self.only_second),
+                    ] {
+                        Self::update_section(ui, heading, ingredients, precision, system);
+                    }
+                });
+            });
+        !open
+    }
+}
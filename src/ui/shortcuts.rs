@@ -0,0 +1,83 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shortcut {
+    NewRecipe,
+    Search,
+    CloseWindow,
+    Delete,
+    Help,
+}
+
+const SHORTCUTS: [(Shortcut, KeyboardShortcut, &str); 5] = [
+    (
+        Shortcut::NewRecipe,
+        KeyboardShortcut::new(Modifiers::COMMAND, Key::N),
+        "New recipe in the focused category",
+    ),
+    (
+        Shortcut::Search,
+        KeyboardShortcut::new(Modifiers::COMMAND, Key::F),
+        "Recipe search (find in description when a recipe is focused)",
+    ),
+    (
+        Shortcut::CloseWindow,
+        KeyboardShortcut::new(Modifiers::COMMAND, Key::W),
+        "Close the focused window",
+    ),
+    (
+        Shortcut::Delete,
+        KeyboardShortcut::new(Modifiers::NONE, Key::Delete),
+        "Delete the selected ingredients",
+    ),
+    (
+        Shortcut::Help,
+        KeyboardShortcut::new(Modifiers::NONE, Key::F1),
+        "Show keyboard shortcuts",
+    ),
+];
+
+/// The shortcuts pressed this frame which no window has handled already.
+pub fn pressed(ctx: &egui::Context) -> Vec<Shortcut> {
+    // Keys without modifiers belong to whatever text field is being typed in
+    let typing = ctx.wants_keyboard_input();
+    ctx.input_mut(|i| {
+        SHORTCUTS
+            .iter()
+            .filter(|(_, keys, _)| {
+                !(typing && keys.modifiers.is_none()) && i.consume_shortcut(keys)
+            })
+            .map(|(shortcut, _, _)| *shortcut)
+            .collect()
+    })
+}
+
+pub struct ShortcutsWindow {}
+
+impl ShortcutsWindow {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+
+        egui::Window::new("Keyboard Shortcuts")
+            .resizable([false, false])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("keyboard shortcuts grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (_, keys, description) in &SHORTCUTS {
+                            ui.strong(ctx.format_shortcut(keys));
+                            ui.label(*description);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        !open
+    }
+}
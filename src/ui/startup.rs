@@ -0,0 +1,271 @@
+//! Opening the database before the rest of the UI starts. Progress is shown while it's upgraded to
+//! the current schema, and if that fails there's a way out other than crashing.
+
+use super::RecipeManager;
+use crate::instance::Access;
+use eframe::App as _;
+use recipe_manager::database;
+use std::path::{Path, PathBuf};
+
+/// Called with the database once it's open, before the UI starts using it.
+pub type OpenedFn = fn(&mut database::Connection, &Path, bool);
+
+enum FailedAction {
+    RestoreBackup,
+    OpenReadOnly,
+    Quit,
+}
+
+enum State {
+    Migrating(database::Migrator),
+    Failed {
+        error: String,
+        /// The newest backup, if it can be restored.
+        backup: Option<PathBuf>,
+    },
+    /// Written by a newer version, so there's nothing to do but quit.
+    Newer(String),
+    Running(Box<RecipeManager>),
+}
+
+pub struct Startup {
+    path: PathBuf,
+    access: Access,
+    opened: OpenedFn,
+    /// Only taken while it's being updated.
+    state: Option<State>,
+}
+
+impl Startup {
+    pub fn new(path: PathBuf, access: Access, opened: OpenedFn) -> Self {
+        let mut startup = Self {
+            path,
+            access,
+            opened,
+            state: None,
+        };
+        startup.state = Some(startup.open());
+        startup
+    }
+
+    fn open(&mut self) -> State {
+        if !self.access.is_read_write() {
+            // Upgrading is left to whichever instance holds the lock. If it hasn't been done yet,
+            // the one open elsewhere is from an older version, so what it can read is shown.
+            let conn = database::establish_read_only_connection(&self.path).or_else(|error| {
+                match self.access {
+                    Access::InUse => database::establish_outdated_connection(&self.path),
+                    _ => Err(error),
+                }
+            });
+            match conn {
+                Ok(conn) => self.run(conn),
+                Err(error) => self.failed(error),
+            }
+        } else {
+            match database::Migrator::new(&self.path) {
+                Ok(migrator) => State::Migrating(migrator),
+                Err(error) => self.failed(error),
+            }
+        }
+    }
+
+    fn run(&mut self, mut conn: database::Connection) -> State {
+        let read_only = !self.access.is_read_write();
+        if let Err(error) = database::set_read_only(&mut conn, read_only) {
+            return self.failed(error.into());
+        }
+        (self.opened)(&mut conn, &self.path, read_only);
+        let access = std::mem::replace(&mut self.access, Access::ReadOnly);
+        State::Running(Box::new(RecipeManager::new(conn, access)))
+    }
+
+    fn failed(&self, error: recipe_manager::Error) -> State {
+        log::error!("failed to open database: {error}");
+        if let Some(newer) = error.downcast_ref::<database::NewerDatabase>() {
+            return State::Newer(newer.to_string());
+        }
+        State::Failed {
+            error: error.to_string(),
+            backup: database::backups(&self.path)
+                .into_iter()
+                .next()
+                .filter(|_| self.access.is_read_write()),
+        }
+    }
+
+    fn next_state(
+        &mut self,
+        state: State,
+        ctx: &egui::Context,
+        frame: &mut eframe::Frame,
+    ) -> State {
+        match state {
+            State::Running(mut manager) => {
+                manager.update(ctx, frame);
+                State::Running(manager)
+            }
+            State::Migrating(mut migrator) => {
+                if let Some(name) = migrator.next_name() {
+                    update_progress_window(ctx, migrator.progress(), &name);
+                }
+                match migrator.run_next() {
+                    Ok(true) => {
+                        ctx.request_repaint();
+                        State::Migrating(migrator)
+                    }
+                    Ok(false) => match migrator.finish() {
+                        Ok(conn) => self.run(conn),
+                        Err(error) => self.failed(error),
+                    },
+                    Err(error) => self.failed(error),
+                }
+            }
+            State::Failed { error, backup } => {
+                match update_failed_window(ctx, &error, backup.as_deref()) {
+                    Some(FailedAction::RestoreBackup) => {
+                        let backup = backup.unwrap();
+                        match database::restore_backup(&backup, &self.path) {
+                            Ok(()) => self.open(),
+                            Err(error) => self.failed(error),
+                        }
+                    }
+                    Some(FailedAction::OpenReadOnly) => {
+                        // Gives up the instance lock too, since nothing will be changed.
+                        self.access = Access::ReadOnly;
+                        match database::establish_outdated_connection(&self.path) {
+                            Ok(conn) => self.run(conn),
+                            Err(error) => self.failed(error),
+                        }
+                    }
+                    Some(FailedAction::Quit) => {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        State::Failed { error, backup }
+                    }
+                    None => State::Failed { error, backup },
+                }
+            }
+            State::Newer(message) => {
+                if update_newer_window(ctx, &message) {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+                State::Newer(message)
+            }
+        }
+    }
+}
+
+fn update_progress_window(ctx: &egui::Context, (done, total): (usize, usize), name: &str) {
+    egui::Window::new("Upgrading the Recipe Database")
+        .collapsible(false)
+        .resizable([false, false])
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label("The database is being upgraded for this version of Recipe Manager.");
+            ui.add(
+                egui::ProgressBar::new(done as f32 / total as f32)
+                    .desired_width(300.0)
+                    .text(format!("{done} of {total}")),
+            );
+            ui.weak(name);
+        });
+}
+
+fn update_failed_window(
+    ctx: &egui::Context,
+    error: &str,
+    backup: Option<&Path>,
+) -> Option<FailedAction> {
+    let mut action = None;
+    egui::Window::new("Couldn't Open the Recipe Database")
+        .collapsible(false)
+        .resizable([false, false])
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label("The recipe database couldn't be opened or upgraded:");
+            ui.add_space(5.0);
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    ui.add(egui::Label::new(egui::RichText::new(error).monospace()).wrap());
+                });
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                if let Some(backup) = backup {
+                    let name = backup.file_name().unwrap_or_default().to_string_lossy();
+                    if ui
+                        .button("Restore Backup")
+                        .on_hover_text(format!(
+                            "Replace the database with {name}, the copy made before it was last \
+                            upgraded, and try again"
+                        ))
+                        .clicked()
+                    {
+                        action = Some(FailedAction::RestoreBackup);
+                    }
+                }
+                if ui
+                    .button("Open Read-Only")
+                    .on_hover_text(
+                        "Browse the database as it is, without upgrading it. Some things may \
+                        not load.",
+                    )
+                    .clicked()
+                {
+                    action = Some(FailedAction::OpenReadOnly);
+                }
+                if ui.button("Quit").clicked() {
+                    action = Some(FailedAction::Quit);
+                }
+            });
+        });
+    action
+}
+
+/// Returns true when quitting.
+fn update_newer_window(ctx: &egui::Context, message: &str) -> bool {
+    let mut quit = false;
+    egui::Window::new("The Recipe Database Is Newer")
+        .collapsible(false)
+        .resizable([false, false])
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(format!("The recipe database can't be opened, {message}."));
+            ui.label(
+                "This version doesn't know about what the newer one changed, and could fail or \
+                damage it. Update Recipe Manager to open it.",
+            );
+            ui.add_space(5.0);
+            quit = ui.button("Quit").clicked();
+        });
+    quit
+}
+
+impl eframe::App for Startup {
+    fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
+        match &self.state {
+            Some(State::Running(manager)) => manager.clear_color(visuals),
+            _ => visuals.panel_fill.to_normalized_gamma_f32(),
+        }
+    }
+
+    fn on_exit(&mut self, gl: Option<&eframe::glow::Context>) {
+        if let Some(State::Running(manager)) = &mut self.state {
+            manager.on_exit(gl);
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let state = self
+            .state
+            .take()
+            .expect("always in a state between updates");
+        let before = std::mem::discriminant(&state);
+        let state = self.next_state(state, ctx, frame);
+        // So the next state is shown without waiting for input.
+        if std::mem::discriminant(&state) != before {
+            ctx.request_repaint();
+        }
+        self.state = Some(state);
+    }
+}
@@ -0,0 +1,385 @@
+use super::calendar::this_week;
+use super::errors::QueryResultExt as _;
+use super::i18n::tr;
+#[cfg(feature = "legacy-import")]
+use super::new_error_toast;
+use super::nutrition;
+use recipe_manager::database;
+use recipe_manager::database::models::RecipeId;
+use recipe_manager::query;
+use recipe_manager::recipe::calories_by_day;
+use std::collections::HashMap;
+
+const COOKING_BADGES: [u32; 5] = [3, 7, 14, 30, 100];
+const PLANNING_BADGES: [u32; 5] = [2, 4, 8, 26, 52];
+
+/// How many of the most used ingredients are listed.
+const MOST_USED_INGREDIENTS: i64 = 10;
+
+/// How wide each bar of the calorie histogram is, in calories.
+const CALORIE_BIN: f32 = 250.0;
+
+const CHART_WIDTH: f32 = 300.0;
+const CHART_HEIGHT: f32 = 150.0;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Streak {
+    current: u32,
+    best: u32,
+}
+
+impl Streak {
+    /// Finds runs in the given dates which are each `step` apart. The current streak is the run
+    /// ending at `latest`, or one step before it since the latest period may not be over yet.
+    fn new(dates: &[chrono::NaiveDate], step: chrono::Days, latest: chrono::NaiveDate) -> Self {
+        let mut streak = Self::default();
+        let mut run = 0;
+        let mut last: Option<chrono::NaiveDate> = None;
+        for &date in dates {
+            if last.and_then(|l| l.checked_add_days(step)) == Some(date) {
+                run += 1;
+            } else {
+                run = 1;
+            }
+            streak.best = streak.best.max(run);
+            last = Some(date);
+        }
+
+        if let Some(last) = last {
+            if last == latest || last.checked_add_days(step) == Some(latest) {
+                streak.current = run;
+            }
+        }
+        streak
+    }
+}
+
+#[test]
+fn streak_new() {
+    let day = chrono::Days::new(1);
+    let date = |d| chrono::NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+
+    assert_eq!(Streak::new(&[], day, date(10)), Streak::default());
+    assert_eq!(
+        Streak::new(
+            &[date(1), date(2), date(3), date(8), date(9)],
+            day,
+            date(10)
+        ),
+        Streak {
+            current: 2,
+            best: 3
+        }
+    );
+    assert_eq!(
+        Streak::new(&[date(8), date(9), date(10)], day, date(10)),
+        Streak {
+            current: 3,
+            best: 3
+        }
+    );
+    assert_eq!(
+        Streak::new(&[date(1), date(2), date(7)], day, date(10)),
+        Streak {
+            current: 0,
+            best: 2
+        }
+    );
+
+    let week = chrono::Days::new(7);
+    assert_eq!(
+        Streak::new(&[date(3), date(10), date(17)], week, date(24)),
+        Streak {
+            current: 3,
+            best: 3
+        }
+    );
+}
+
+/// The first day of every week which had a recipe planned for all seven days.
+fn fully_planned_weeks(
+    planned_days: &[chrono::NaiveDate],
+    week_start: chrono::Weekday,
+) -> Vec<chrono::NaiveDate> {
+    let mut weeks: HashMap<chrono::NaiveDate, u32> = HashMap::new();
+    for day in planned_days {
+        *weeks.entry(day.week(week_start).first_day()).or_default() += 1;
+    }
+    let mut weeks: Vec<_> = weeks
+        .into_iter()
+        .filter_map(|(week, days)| (days == 7).then_some(week))
+        .collect();
+    weeks.sort();
+    weeks
+}
+
+/// How many of the calorie totals fall in each `bin` wide range, starting from zero. Negative
+/// totals are counted in the first range.
+fn calorie_histogram(calories: impl IntoIterator<Item = f32>, bin: f32) -> Vec<usize> {
+    let mut histogram = vec![];
+    for c in calories {
+        let i = (c.max(0.0) / bin) as usize;
+        if histogram.len() <= i {
+            histogram.resize(i + 1, 0);
+        }
+        histogram[i] += 1;
+    }
+    histogram
+}
+
+#[test]
+fn calorie_histogram_test() {
+    assert!(calorie_histogram([], 100.0).is_empty());
+    assert_eq!(
+        calorie_histogram([50.0, 99.9, 100.0, 350.0, -1.0], 100.0),
+        [3, 1, 0, 1]
+    );
+}
+
+/// Plots counts as bars, with the labels under them.
+fn bar_chart(ui: &mut egui::Ui, id: &str, bars: Vec<(String, usize)>) {
+    use egui_plot::{Bar, BarChart, Plot};
+
+    let labels: Vec<_> = bars.iter().map(|(l, _)| l.clone()).collect();
+    let bars = bars
+        .into_iter()
+        .enumerate()
+        .map(|(i, (label, count))| Bar::new(i as f64, count as f64).name(label).width(0.7))
+        .collect();
+    Plot::new(id)
+        .height(CHART_HEIGHT)
+        .width(CHART_WIDTH)
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false)
+        .include_y(0.0)
+        .x_axis_formatter(move |mark, _| {
+            let i = mark.value.round();
+            if (mark.value - i).abs() > f64::EPSILON || i < 0.0 {
+                return String::new();
+            }
+            labels.get(i as usize).cloned().unwrap_or_default()
+        })
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(
+                BarChart::new(bars)
+                    .element_formatter(Box::new(|bar, _| format!("{}: {}", bar.name, bar.value))),
+            );
+        });
+}
+
+pub struct StatsWindow {
+    cooking: Streak,
+    planning: Streak,
+    week_start: chrono::Weekday,
+    totals: query::RecipeBookTotals,
+    most_used: Vec<(String, usize)>,
+    /// For exporting the cook history.
+    #[cfg_attr(not(feature = "legacy-import"), expect(dead_code))]
+    recipe_calories: HashMap<RecipeId, f32>,
+    /// How many recipes have calorie totals in each `CALORIE_BIN` wide range.
+    calorie_histogram: Vec<usize>,
+    recipes_per_category: Vec<(String, usize)>,
+}
+
+impl StatsWindow {
+    pub fn new(conn: &mut database::Connection, week_start: chrono::Weekday) -> Self {
+        let this_week = this_week(week_start);
+        let history = query::get_calendar_history(conn, this_week.last_day()).unwrap_or_report();
+
+        let today = chrono::Local::now().date_naive();
+        let cooked_days: Vec<_> = history
+            .iter()
+            .filter(|e| e.cooked && e.day <= today)
+            .map(|e| e.day)
+            .collect();
+        let planned_days: Vec<_> = history.iter().map(|e| e.day).collect();
+
+        let ingredients: Vec<_> = query::get_all_recipe_ingredients(conn)
+            .unwrap_or_report()
+            .into_iter()
+            .map(|(recipe, ingredient)| (recipe.id, ingredient))
+            .collect();
+        let recipe_calories = calories_by_day(&ingredients);
+
+        Self {
+            cooking: Streak::new(&cooked_days, chrono::Days::new(1), today),
+            planning: Streak::new(
+                &fully_planned_weeks(&planned_days, week_start),
+                chrono::Days::new(7),
+                this_week.first_day(),
+            ),
+            week_start,
+            totals: query::get_recipe_book_totals(conn).unwrap_or_report(),
+            most_used: query::get_most_used_ingredients(conn, MOST_USED_INGREDIENTS)
+                .unwrap_or_report(),
+            calorie_histogram: calorie_histogram(recipe_calories.values().copied(), CALORIE_BIN),
+            recipe_calories,
+            recipes_per_category: query::get_recipe_categories(conn)
+                .unwrap_or_report()
+                .into_iter()
+                .map(|(category, count)| (category.name, count))
+                .collect(),
+        }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        *self = Self::new(conn, self.week_start);
+    }
+
+    /// `badge` is what each one is called, with `{n}` where the length of the streak goes.
+    fn update_badges(ui: &mut egui::Ui, thresholds: &[u32], best: u32, badge: &str) {
+        ui.horizontal_wrapped(|ui| {
+            for &threshold in thresholds {
+                let text = egui::RichText::new(badge.replace("{n}", &threshold.to_string()));
+                if best >= threshold {
+                    ui.label(text.strong().color(ui.visuals().warn_fg_color));
+                } else {
+                    ui.label(text.weak());
+                }
+            }
+        });
+    }
+
+    fn update_recipe_book(&self, ui: &mut egui::Ui) {
+        egui::Grid::new("stats totals grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let totals = [
+                    (tr("Recipes:"), self.totals.recipes),
+                    (tr("Ingredients:"), self.totals.ingredients),
+                    (tr("Categories:"), self.totals.categories),
+                ];
+                for (label, total) in totals {
+                    ui.label(label);
+                    ui.label(total.to_string());
+                    ui.end_row();
+                }
+            });
+
+        egui::CollapsingHeader::new(tr("Most used ingredients")).show(ui, |ui| {
+            egui::Grid::new("stats most used grid")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (name, count) in &self.most_used {
+                        ui.label(name);
+                        ui.label(tr("{count} recipes").replace("{count}", &count.to_string()));
+                        ui.end_row();
+                    }
+                });
+        });
+
+        egui::CollapsingHeader::new(tr("Calories per recipe")).show(ui, |ui| {
+            let bars = self
+                .calorie_histogram
+                .iter()
+                .enumerate()
+                .map(|(i, &count)| {
+                    let from = i as f32 * CALORIE_BIN;
+                    (nutrition::calories_display(from), count)
+                })
+                .collect();
+            bar_chart(ui, "stats calorie histogram", bars);
+        });
+
+        egui::CollapsingHeader::new(tr("Recipes per category")).show(ui, |ui| {
+            bar_chart(
+                ui,
+                "stats recipes per category",
+                self.recipes_per_category.clone(),
+            );
+        });
+    }
+
+    /// Saves each day a recipe was cooked, along with the recipe's calories, as a CSV file wherever
+    /// the user picks.
+    #[cfg(feature = "legacy-import")]
+    fn export_cook_history(&self, conn: &mut database::Connection) -> recipe_manager::Result<()> {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("Cook History.csv")
+            .save_file()
+        else {
+            return Ok(());
+        };
+        let rows = query::get_cook_history(conn)?
+            .into_iter()
+            .map(|(day, recipe)| {
+                let calories = self
+                    .recipe_calories
+                    .get(&recipe.id)
+                    .map(|c| c.round().to_string())
+                    .unwrap_or_default();
+                vec![day.to_string(), recipe.name, calories]
+            });
+        let csv = recipe_manager::csv::write_csv(&["Date Cooked", "Recipe", "Calories"], rows);
+        std::fs::write(path, csv)?;
+        Ok(())
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        #[cfg_attr(not(feature = "legacy-import"), expect(unused_variables))]
+        conn: &mut database::Connection,
+        #[cfg_attr(not(feature = "legacy-import"), expect(unused_variables))]
+        toasts: &mut egui_toast::Toasts,
+    ) -> bool {
+        let mut open = true;
+        egui::Window::new(tr("Stats"))
+            .id(egui::Id::new("Stats"))
+            .open(&mut open)
+            .resizable([false, false])
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("stats streaks grid")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label(tr("Current"));
+                        ui.label(tr("Best"));
+                        ui.end_row();
+
+                        let days = |n: u32| tr("{n} days").replace("{n}", &n.to_string());
+                        ui.label(tr("Cooking streak:"));
+                        ui.label(days(self.cooking.current));
+                        ui.label(days(self.cooking.best));
+                        ui.end_row();
+
+                        let weeks = |n: u32| tr("{n} weeks").replace("{n}", &n.to_string());
+                        ui.label(tr("Planning streak:"));
+                        ui.label(weeks(self.planning.current));
+                        ui.label(weeks(self.planning.best));
+                        ui.end_row();
+                    });
+                ui.separator();
+                ui.heading(tr("Badges"));
+                let cooking = tr("{n}-day cooking streak");
+                Self::update_badges(ui, &COOKING_BADGES, self.cooking.best, cooking);
+                let planning = tr("{n}-week planning streak");
+                Self::update_badges(ui, &PLANNING_BADGES, self.planning.best, planning);
+                ui.separator();
+                ui.heading(tr("Recipe Book"));
+                self.update_recipe_book(ui);
+                #[cfg(feature = "legacy-import")]
+                {
+                    ui.separator();
+                    if ui
+                        .button(tr("Export CSV"))
+                        .on_hover_text(tr("Save the days recipes were cooked, for a spreadsheet"))
+                        .clicked()
+                    {
+                        if let Err(error) = self.export_cook_history(conn) {
+                            toasts.add(new_error_toast(
+                                tr("Couldn't export: {error}")
+                                    .replace("{error}", &error.to_string()),
+                            ));
+                        }
+                    }
+                }
+            });
+        !open
+    }
+}
@@ -0,0 +1,105 @@
+//! Settling the conflicts found when merging changes from other machines through the sync folder.
+
+use super::new_error_toast;
+use recipe_manager::database;
+use recipe_manager::sync::{self, Conflict, Resolution};
+use std::path::Path;
+
+pub enum UpdateEvent {
+    Closed,
+    Resolved,
+}
+
+pub struct SyncConflictsWindow {
+    conflicts: Vec<(Conflict, Resolution)>,
+}
+
+impl SyncConflictsWindow {
+    pub fn new(conflicts: Vec<Conflict>) -> Self {
+        Self {
+            conflicts: conflicts
+                .into_iter()
+                .map(|c| (c, Resolution::default()))
+                .collect(),
+        }
+    }
+
+    fn update_conflicts(&mut self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("sync conflicts")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (conflict, resolution) in &mut self.conflicts {
+                        ui.label(&conflict.description);
+                        ui.radio_value(resolution, Resolution::KeepMine, "Keep mine")
+                            .on_hover_text(if conflict.deleted_here {
+                                "Deleted here"
+                            } else {
+                                "Changed here"
+                            });
+                        ui.radio_value(resolution, Resolution::UseTheirs, "Use theirs")
+                            .on_hover_text(if conflict.deleted_there {
+                                format!("Deleted on {}", conflict.machine)
+                            } else {
+                                format!("Changed on {}", conflict.machine)
+                            });
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        folder: &Path,
+        toasts: &mut egui_toast::Toasts,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let mut open = true;
+        egui::Window::new("Sync Conflicts")
+            .open(&mut open)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    "These were changed both here and on another machine. Choose which version \
+                    to keep.",
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Keep All Mine").clicked() {
+                        for (_, r) in &mut self.conflicts {
+                            *r = Resolution::KeepMine;
+                        }
+                    }
+                    if ui.button("Use All Theirs").clicked() {
+                        for (_, r) in &mut self.conflicts {
+                            *r = Resolution::UseTheirs;
+                        }
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Apply").clicked() {
+                            match sync::resolve(conn, folder, &self.conflicts) {
+                                Ok(()) => {
+                                    events.push(UpdateEvent::Resolved);
+                                    events.push(UpdateEvent::Closed);
+                                }
+                                Err(error) => {
+                                    toasts.add(new_error_toast(format!(
+                                        "Couldn't settle the conflicts: {error}"
+                                    )));
+                                }
+                            }
+                        }
+                    });
+                });
+                ui.separator();
+                self.update_conflicts(ui);
+            });
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+}
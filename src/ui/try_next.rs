@@ -0,0 +1,112 @@
+use super::errors::QueryResultExt as _;
+use super::is_editable;
+use super::recipe::RecipeWindow;
+use super::recipe_preview::RecipePreviewExt as _;
+use recipe_manager::database;
+use recipe_manager::database::models::{RecipeHandle, RecipeId, RecipeStatus};
+use recipe_manager::query;
+use std::collections::HashMap;
+
+/// The recipes waiting to be tried, the ones added longest ago first.
+pub struct TryNextWindow {
+    recipes: Vec<RecipeHandle>,
+}
+
+impl TryNextWindow {
+    pub fn new(conn: &mut database::Connection) -> Self {
+        Self {
+            recipes: query::get_want_to_try_recipes(conn).unwrap_or_report(),
+        }
+    }
+
+    pub fn refresh(&mut self, conn: &mut database::Connection) {
+        self.recipes = query::get_want_to_try_recipes(conn).unwrap_or_report();
+    }
+
+    fn update_table(
+        &mut self,
+        conn: &mut database::Connection,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+        selected_week: Option<chrono::NaiveWeek>,
+        ui: &mut egui::Ui,
+    ) {
+        let editable = is_editable(ui.ctx());
+        let mut tried = None;
+        let available_height = ui.available_height();
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("try next table")
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::remainder().clip(true))
+            .column(egui_extras::Column::exact(50.0))
+            .min_scrolled_height(0.0)
+            .max_scroll_height(available_height)
+            .body(|body| {
+                body.rows(20.0, self.recipes.len(), |mut row| {
+                    let recipe = &self.recipes[row.index()];
+                    let id = recipe.id;
+                    let mut shown = recipe_windows.contains_key(&id);
+                    row.col(|ui| {
+                        ui.toggle_value(&mut shown, &recipe.name)
+                            .on_hover_recipe_preview(conn, id);
+                    });
+                    row.col(|ui| {
+                        if ui
+                            .add_enabled(editable, egui::Button::new("Tried"))
+                            .on_hover_text("Take it off the list")
+                            .clicked()
+                        {
+                            tried = Some(id);
+                        }
+                    });
+
+                    if shown && !recipe_windows.contains_key(&id) {
+                        if let Some(window) =
+                            RecipeWindow::new(conn, id, selected_week, false).or_report()
+                        {
+                            recipe_windows.insert(id, window);
+                        }
+                    } else if !shown {
+                        recipe_windows.remove(&id);
+                    }
+                });
+            });
+
+        if let Some(id) = tried {
+            if query::edit_recipe_status(conn, id, RecipeStatus::Tried)
+                .or_report()
+                .is_some()
+            {
+                self.recipes.retain(|r| r.id != id);
+                if let Some(window) = recipe_windows.get_mut(&id) {
+                    window.reload(conn);
+                }
+            }
+        }
+    }
+
+    /// Returns true when the window is closed.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        recipe_windows: &mut HashMap<RecipeId, RecipeWindow>,
+        selected_week: Option<chrono::NaiveWeek>,
+    ) -> bool {
+        let mut open = true;
+        egui::Window::new("Try Next")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                if self.recipes.is_empty() {
+                    ui.label(
+                        "No recipes to try. Mark recipes you want to try from their windows, \
+                        and they're taken off once they're cooked.",
+                    );
+                } else {
+                    self.update_table(conn, recipe_windows, selected_week, ui);
+                }
+            });
+        !open
+    }
+}
@@ -0,0 +1,101 @@
+//! Undoing quick operations from the toast which reports them.
+
+use diesel::QueryResult;
+use recipe_manager::database;
+use recipe_manager::database::models::{
+    CalendarEntry, IngredientComponentId, IngredientId, IngredientUsage, IngredientUsageId,
+};
+use recipe_manager::query;
+
+/// The `egui_toast::ToastKind::Custom` used for toasts with an undo button.
+pub const UNDO_TOAST_KIND: u32 = 1;
+
+/// How many undo actions are remembered. Older toasts will have expired long before this many
+/// operations are done.
+const MAX_ACTIONS: usize = 20;
+
+/// An operation which can be undone, holding what is needed to put things back.
+#[derive(Clone)]
+pub enum UndoAction {
+    RemovedRecipeIngredient(IngredientUsage),
+    ClearedCalendarEntry(CalendarEntry),
+    ReplacedIngredient {
+        ingredient: IngredientId,
+        usages: Vec<IngredientUsageId>,
+        components: Vec<IngredientComponentId>,
+    },
+}
+
+impl UndoAction {
+    pub fn undo(&self, conn: &mut database::Connection) -> QueryResult<()> {
+        match self {
+            Self::RemovedRecipeIngredient(usage) => query::restore_recipe_ingredient(conn, usage),
+            Self::ClearedCalendarEntry(entry) => query::restore_calendar_entry(conn, entry),
+            Self::ReplacedIngredient {
+                ingredient,
+                usages,
+                components,
+            } => query::restore_ingredient_references(conn, *ingredient, usages, components),
+        }
+    }
+}
+
+fn actions_id() -> egui::Id {
+    egui::Id::new("undo actions")
+}
+
+fn clicked_id() -> egui::Id {
+    egui::Id::new("undo clicked")
+}
+
+/// Reports an operation which was done, with a button to undo it.
+pub fn add_undo_toast(
+    ctx: &egui::Context,
+    toasts: &mut egui_toast::Toasts,
+    msg: impl Into<String>,
+    action: UndoAction,
+) {
+    let msg = msg.into();
+    ctx.data_mut(|d| {
+        let actions = d.get_temp_mut_or_default::<Vec<(String, UndoAction)>>(actions_id());
+        actions.push((msg.clone(), action));
+        if actions.len() > MAX_ACTIONS {
+            actions.remove(0);
+        }
+    });
+    toasts.add(egui_toast::Toast {
+        kind: egui_toast::ToastKind::Custom(UNDO_TOAST_KIND),
+        ..super::new_success_toast(msg)
+    });
+}
+
+/// Draws a toast with an undo button, for `egui_toast::Toasts::custom_contents`.
+pub fn toast_contents(ui: &mut egui::Ui, toast: &mut egui_toast::Toast) -> egui::Response {
+    egui::Frame::window(ui.style())
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(toast.style.success_icon.clone());
+                ui.label(toast.text.clone());
+                if ui.button("Undo").clicked() {
+                    let msg = toast.text.text().to_owned();
+                    ui.ctx().data_mut(|d| d.insert_temp(clicked_id(), msg));
+                    toast.close();
+                }
+                if ui.button(toast.style.close_button_text.clone()).clicked() {
+                    toast.close();
+                }
+            })
+        })
+        .response
+}
+
+/// The action whose undo button was clicked, if any.
+pub fn take_clicked(ctx: &egui::Context) -> Option<UndoAction> {
+    ctx.data_mut(|d| {
+        let msg: String = d.remove_temp(clicked_id())?;
+        let actions = d.get_temp_mut_or_default::<Vec<(String, UndoAction)>>(actions_id());
+        let i = actions.iter().rposition(|(m, _)| *m == msg)?;
+        Some(actions.remove(i).1)
+    })
+}
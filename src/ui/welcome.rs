@@ -0,0 +1,115 @@
+use super::errors::QueryResultExt as _;
+use super::PressedEnterExt as _;
+#[cfg(feature = "sample-recipes")]
+use super::{new_error_toast, new_success_toast, sample_recipes};
+use recipe_manager::database;
+use recipe_manager::query;
+
+pub enum UpdateEvent {
+    Closed,
+    #[cfg(feature = "legacy-import")]
+    Import,
+    RecipesAdded,
+}
+
+/// Shown on the first run, when there are no recipes yet, with a few ways to get started.
+pub struct WelcomeWindow {
+    new_category_name: String,
+}
+
+impl WelcomeWindow {
+    pub fn new() -> Self {
+        Self {
+            new_category_name: String::new(),
+        }
+    }
+
+    fn update_add_category(
+        &mut self,
+        conn: &mut database::Connection,
+        ui: &mut egui::Ui,
+        events: &mut Vec<UpdateEvent>,
+    ) {
+        ui.label("Create a first category to start adding your own recipes to.");
+        ui.horizontal(|ui| {
+            let mut new_category = ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.new_category_name)
+                        .hint_text("category name"),
+                )
+                .pressed_enter();
+            let e = !self.new_category_name.trim().is_empty();
+            new_category |= ui
+                .add_enabled(e, egui::Button::new("Create Category"))
+                .clicked();
+            if new_category
+                && e
+                && query::add_category(conn, self.new_category_name.trim())
+                    .or_report()
+                    .is_some()
+            {
+                events.push(UpdateEvent::RecipesAdded);
+                events.push(UpdateEvent::Closed);
+            }
+        });
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        conn: &mut database::Connection,
+        #[cfg_attr(not(feature = "sample-recipes"), expect(unused_variables))]
+        toasts: &mut egui_toast::Toasts,
+    ) -> Vec<UpdateEvent> {
+        let mut events = vec![];
+        let mut open = true;
+        egui::Window::new("Welcome")
+            .open(&mut open)
+            .resizable([false, false])
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.heading("Welcome to Recipe Manager");
+                ui.label("Your recipe book is empty. Here are a few ways to get started.");
+                ui.separator();
+
+                #[cfg(feature = "legacy-import")]
+                {
+                    ui.label("Bring over recipes and calendar from the older Mac OS version.");
+                    if ui.button("Import Data from Previous Version").clicked() {
+                        events.push(UpdateEvent::Import);
+                        events.push(UpdateEvent::Closed);
+                    }
+                    ui.separator();
+                }
+
+                self.update_add_category(conn, ui, &mut events);
+
+                #[cfg(feature = "sample-recipes")]
+                {
+                    ui.separator();
+                    ui.label("Try things out with a few sample recipes.");
+                    if ui.button("Load Sample Recipes").clicked() {
+                        match sample_recipes::load(conn) {
+                            Ok(num_added) => {
+                                toasts.add(new_success_toast(format!(
+                                    "Added {num_added} sample recipes"
+                                )));
+                            }
+                            Err(error) => {
+                                toasts.add(new_error_toast(format!(
+                                    "Couldn't load sample recipes: {error}"
+                                )));
+                            }
+                        }
+                        events.push(UpdateEvent::RecipesAdded);
+                        events.push(UpdateEvent::Closed);
+                    }
+                }
+            });
+        if !open {
+            events.push(UpdateEvent::Closed);
+        }
+        events
+    }
+}
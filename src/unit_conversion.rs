@@ -0,0 +1,1018 @@
+use crate::database::models::IngredientMeasurement;
+use derive_more::Display;
+use std::collections::BTreeMap;
+use std::fmt;
+use strum::EnumIter;
+
+#[derive(PartialEq, Eq, Debug, Display)]
+pub enum MeasurementKind {
+    #[display("volume")]
+    Volume,
+    #[display("weight")]
+    Weight,
+}
+
+impl From<IngredientMeasurement> for MeasurementKind {
+    fn from(m: IngredientMeasurement) -> Self {
+        match m {
+            IngredientMeasurement::Cups => Self::Volume,
+            IngredientMeasurement::FluidOunces => Self::Volume,
+            IngredientMeasurement::Grams => Self::Weight,
+            IngredientMeasurement::Kilograms => Self::Weight,
+            IngredientMeasurement::Kiloliters => Self::Volume,
+            IngredientMeasurement::Liters => Self::Volume,
+            IngredientMeasurement::Milligrams => Self::Weight,
+            IngredientMeasurement::Milliliters => Self::Volume,
+            IngredientMeasurement::Ounces => Self::Weight,
+            IngredientMeasurement::Pounds => Self::Weight,
+            IngredientMeasurement::Quart => Self::Volume,
+            IngredientMeasurement::Tablespoons => Self::Volume,
+            IngredientMeasurement::Teaspoons => Self::Volume,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum MeasurementClass {
+    Us,
+    Metric,
+}
+
+impl From<IngredientMeasurement> for MeasurementClass {
+    fn from(m: IngredientMeasurement) -> Self {
+        match m {
+            IngredientMeasurement::Cups => Self::Us,
+            IngredientMeasurement::FluidOunces => Self::Us,
+            IngredientMeasurement::Grams => Self::Metric,
+            IngredientMeasurement::Kilograms => Self::Metric,
+            IngredientMeasurement::Kiloliters => Self::Metric,
+            IngredientMeasurement::Liters => Self::Metric,
+            IngredientMeasurement::Milligrams => Self::Metric,
+            IngredientMeasurement::Milliliters => Self::Metric,
+            IngredientMeasurement::Ounces => Self::Us,
+            IngredientMeasurement::Pounds => Self::Us,
+            IngredientMeasurement::Quart => Self::Us,
+            IngredientMeasurement::Tablespoons => Self::Us,
+            IngredientMeasurement::Teaspoons => Self::Us,
+        }
+    }
+}
+
+fn as_teaspoons(a: IngredientMeasurement) -> Option<f32> {
+    match a {
+        IngredientMeasurement::Cups => Some(48.0),
+        IngredientMeasurement::FluidOunces => Some(6.0),
+        IngredientMeasurement::Teaspoons => Some(1.0),
+        IngredientMeasurement::Tablespoons => Some(3.0),
+        IngredientMeasurement::Quart => Some(192.0),
+        _ => None,
+    }
+}
+
+fn as_milliliters(a: IngredientMeasurement) -> Option<f32> {
+    match a {
+        IngredientMeasurement::Cups => Some(236.588236),
+        IngredientMeasurement::FluidOunces => Some(29.573535296),
+        IngredientMeasurement::Kiloliters => Some(1_000_000.0),
+        IngredientMeasurement::Liters => Some(1_000.0),
+        IngredientMeasurement::Milliliters => Some(1.0),
+        IngredientMeasurement::Tablespoons => Some(14.7867648),
+        IngredientMeasurement::Teaspoons => Some(4.92892159),
+        IngredientMeasurement::Quart => Some(946.353),
+        _ => None,
+    }
+}
+
+fn as_ounces(a: IngredientMeasurement) -> Option<f32> {
+    match a {
+        IngredientMeasurement::Ounces => Some(1.0),
+        IngredientMeasurement::Pounds => Some(16.0),
+        _ => None,
+    }
+}
+
+fn as_milligrams(a: IngredientMeasurement) -> Option<f32> {
+    match a {
+        IngredientMeasurement::Grams => Some(1_000.0),
+        IngredientMeasurement::Kilograms => Some(1_000_000.0),
+        IngredientMeasurement::Milligrams => Some(1.0),
+        IngredientMeasurement::Ounces => Some(28349.52),
+        IngredientMeasurement::Pounds => Some(453592.4),
+        _ => None,
+    }
+}
+
+/// Units which can't be converted to the others, because one is a volume and the other a weight
+/// and there is no density to go between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConversionError {
+    pub from: IngredientMeasurement,
+    pub to: IngredientMeasurement,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "can't convert {} to {} ({} vs {}, no density)",
+            self.from.as_str(),
+            self.to.as_str(),
+            MeasurementKind::from(self.from),
+            MeasurementKind::from(self.to)
+        )
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// What a quantity in `a` is multiplied by to give it in `b`. Both have to measure the same kind
+/// of thing, see `conversion_factor_with_density` for going between volume and weight.
+pub fn conversion_factor(
+    a: IngredientMeasurement,
+    b: IngredientMeasurement,
+) -> Result<f32, ConversionError> {
+    let error = ConversionError { from: a, to: b };
+    let kind = MeasurementKind::from(a);
+    if kind != MeasurementKind::from(b) {
+        return Err(error);
+    }
+
+    let ratio = |as_base: fn(IngredientMeasurement) -> Option<f32>| Some(as_base(a)? / as_base(b)?);
+    let us = (MeasurementClass::from(a), MeasurementClass::from(b))
+        == (MeasurementClass::Us, MeasurementClass::Us);
+    let factor = match (kind, us) {
+        (MeasurementKind::Volume, true) => ratio(as_teaspoons),
+        (MeasurementKind::Volume, false) => ratio(as_milliliters),
+        (MeasurementKind::Weight, true) => ratio(as_ounces),
+        (MeasurementKind::Weight, false) => ratio(as_milligrams),
+    };
+    factor.ok_or(error)
+}
+
+/// Like `conversion_factor`, but going between volume and weight using the density of the
+/// ingredient, in grams per milliliter, if it has one.
+pub fn conversion_factor_with_density(
+    a: IngredientMeasurement,
+    b: IngredientMeasurement,
+    density: Option<f32>,
+) -> Result<f32, ConversionError> {
+    use IngredientMeasurement::{Grams, Milliliters};
+
+    let density = density.filter(|d| *d > 0.0);
+    match (MeasurementKind::from(a), MeasurementKind::from(b), density) {
+        (MeasurementKind::Volume, MeasurementKind::Weight, Some(density)) => {
+            Ok(conversion_factor(a, Milliliters)? * density * conversion_factor(Grams, b)?)
+        }
+        (MeasurementKind::Weight, MeasurementKind::Volume, Some(density)) => {
+            Ok(conversion_factor(a, Grams)? / density * conversion_factor(Milliliters, b)?)
+        }
+        _ => conversion_factor(a, b),
+    }
+}
+
+/// `conversion_factor` for units which are known to be the same kind.
+fn same_kind_factor(a: IngredientMeasurement, b: IngredientMeasurement) -> f32 {
+    conversion_factor(a, b).expect("units of the same kind can always be converted")
+}
+
+/// Which units quantities are shown in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum MeasurementSystem {
+    #[default]
+    #[display("as entered")]
+    AsEntered,
+    #[display("US")]
+    Us,
+    #[display("metric")]
+    Metric,
+}
+
+impl MeasurementSystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AsEntered => "as_entered",
+            Self::Us => "us",
+            Self::Metric => "metric",
+        }
+    }
+
+    /// The inverse of `as_str`, like for the other settings.
+    #[expect(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|p| p.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+
+    fn class(&self) -> Option<MeasurementClass> {
+        match self {
+            Self::AsEntered => None,
+            Self::Us => Some(MeasurementClass::Us),
+            Self::Metric => Some(MeasurementClass::Metric),
+        }
+    }
+}
+
+/// Which units are offered when picking one, to keep rarely used ones out of the way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum UnitChoices {
+    #[default]
+    #[display("all units")]
+    All,
+    #[display("US units")]
+    Us,
+    #[display("metric units")]
+    Metric,
+    #[display("US kitchen units")]
+    UsKitchen,
+    #[display("metric kitchen units")]
+    MetricKitchen,
+}
+
+impl UnitChoices {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Us => "us",
+            Self::Metric => "metric",
+            Self::UsKitchen => "us_kitchen",
+            Self::MetricKitchen => "metric_kitchen",
+        }
+    }
+
+    /// The inverse of `as_str`, like for the other settings.
+    #[expect(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::iter().find(|c| c.as_str() == s)
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        <Self as strum::IntoEnumIterator>::iter()
+    }
+
+    pub fn includes(&self, units: IngredientMeasurement) -> bool {
+        let class = MeasurementClass::from(units);
+        let kitchen = || cooking_units(&units.into(), &class).contains(&units);
+        match self {
+            Self::All => true,
+            Self::Us => class == MeasurementClass::Us,
+            Self::Metric => class == MeasurementClass::Metric,
+            Self::UsKitchen => class == MeasurementClass::Us && kitchen(),
+            Self::MetricKitchen => class == MeasurementClass::Metric && kitchen(),
+        }
+    }
+
+    /// The units to offer, in the usual order.
+    pub fn units(&self) -> impl Iterator<Item = IngredientMeasurement> {
+        let choices = *self;
+        <IngredientMeasurement as strum::IntoEnumIterator>::iter()
+            .filter(move |u| choices.includes(*u))
+    }
+}
+
+#[test]
+fn unit_choices() {
+    use IngredientMeasurement::*;
+
+    assert_eq!(UnitChoices::All.units().count(), 13);
+    assert_eq!(
+        UnitChoices::MetricKitchen.units().collect::<Vec<_>>(),
+        [Grams, Kilograms, Liters, Milliliters]
+    );
+    assert_eq!(
+        UnitChoices::UsKitchen.units().collect::<Vec<_>>(),
+        [Cups, Ounces, Pounds, Tablespoons, Teaspoons]
+    );
+    assert!(UnitChoices::Us.includes(Quart));
+    assert!(!UnitChoices::Metric.includes(Cups));
+    assert!(UnitChoices::Metric.includes(Kiloliters));
+}
+
+/// The units of the given kind and class that we'd cook with, from smallest to largest.
+fn cooking_units(
+    kind: &MeasurementKind,
+    class: &MeasurementClass,
+) -> &'static [IngredientMeasurement] {
+    use IngredientMeasurement::*;
+
+    match (kind, class) {
+        (MeasurementKind::Volume, MeasurementClass::Us) => &[Teaspoons, Tablespoons, Cups],
+        (MeasurementKind::Weight, MeasurementClass::Us) => &[Ounces, Pounds],
+        (MeasurementKind::Volume, MeasurementClass::Metric) => &[Milliliters, Liters],
+        (MeasurementKind::Weight, MeasurementClass::Metric) => &[Grams, Kilograms],
+    }
+}
+
+fn smallest_units(kind: &MeasurementKind, class: &MeasurementClass) -> IngredientMeasurement {
+    cooking_units(kind, class)[0]
+}
+
+/// Picks a reasonably sized unit for a quantity given in `smallest_units`.
+fn readable_units(
+    q: f32,
+    kind: &MeasurementKind,
+    class: &MeasurementClass,
+) -> (f32, IngredientMeasurement) {
+    let small = smallest_units(kind, class);
+    for &units in cooking_units(kind, class).iter().rev() {
+        let converted = q * same_kind_factor(small, units);
+        // A quarter cup is easier to measure than four tablespoons
+        let threshold = if units == IngredientMeasurement::Cups {
+            0.25
+        } else {
+            1.0
+        };
+        if converted >= threshold {
+            return (converted, units);
+        }
+    }
+    (q, small)
+}
+
+/// How finely a quantity in some units is worth measuring in a kitchen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KitchenMeasure {
+    /// Whole numbers and fractions with these denominators, like measuring cups and spoons come in.
+    Fractions(&'static [u32]),
+    /// Multiples of this much, like the 5 grams a kitchen scale can tell apart.
+    Step(f32),
+}
+
+impl KitchenMeasure {
+    pub fn for_units(units: Option<IngredientMeasurement>) -> Self {
+        use IngredientMeasurement::*;
+
+        match units {
+            None | Some(Cups) => Self::Fractions(&[2, 3, 4]),
+            Some(Teaspoons) => Self::Fractions(&[2, 4, 8]),
+            Some(Tablespoons | FluidOunces) => Self::Fractions(&[2]),
+            Some(Quart | Ounces | Pounds) => Self::Fractions(&[2, 4]),
+            Some(Grams | Milliliters) => Self::Step(5.0),
+            Some(Milligrams) => Self::Step(10.0),
+            Some(Kilograms | Liters) => Self::Step(0.05),
+            Some(Kiloliters) => Self::Step(0.001),
+        }
+    }
+
+    /// The nearest quantity which can be measured. Small quantities aren't rounded away to
+    /// nothing, they're left as they are.
+    pub fn round(&self, q: f32) -> f32 {
+        let rounded = match self {
+            Self::Fractions(denominators) => {
+                let whole = q.trunc();
+                let fractions = denominators
+                    .iter()
+                    .flat_map(|&d| (1..d).map(move |n| n as f32 / d as f32));
+                [0.0, 1.0]
+                    .into_iter()
+                    .chain(fractions)
+                    .map(|f| whole + f)
+                    .min_by(|a, b| (a - q).abs().total_cmp(&(b - q).abs()))
+                    .unwrap_or(q)
+            }
+            Self::Step(step) => (q / step).round() * step,
+        };
+        if rounded == 0.0 && q > 0.0 {
+            q
+        } else {
+            rounded
+        }
+    }
+}
+
+#[test]
+fn kitchen_measure_test() {
+    use IngredientMeasurement::*;
+
+    let round = |q, units| KitchenMeasure::for_units(units).round(q);
+    assert_eq!(round(2.0 / 3.0, Some(Cups)), 2.0 / 3.0);
+    assert_eq!(round(0.3, Some(Teaspoons)), 0.25);
+    assert_eq!(round(1.4, Some(Tablespoons)), 1.5);
+    assert_eq!(round(2.9, None), 3.0);
+    assert_eq!(round(123.0, Some(Grams)), 125.0);
+    assert_eq!(round(1.23, Some(Liters)), 1.25);
+    assert_eq!(round(0.01, Some(Teaspoons)), 0.01);
+    assert_eq!(round(1.0, Some(Grams)), 1.0);
+}
+
+/// The most natural unit for a quantity, which is the largest one in the same measurement class
+/// that gives at least one of it. For example 0.125 cups is better as 2 tablespoons.
+pub fn best_unit_for(q: f32, units: IngredientMeasurement) -> (f32, IngredientMeasurement) {
+    let kind = MeasurementKind::from(units);
+    let class = MeasurementClass::from(units);
+    let candidates = cooking_units(&kind, &class);
+    for &candidate in candidates.iter().rev() {
+        let converted = q * same_kind_factor(units, candidate);
+        if converted >= 1.0 {
+            return (converted, candidate);
+        }
+    }
+    let small = candidates[0];
+    (q * same_kind_factor(units, small), small)
+}
+
+/// Converts the quantity to the given measurement system, unless it is already in it.
+pub fn convert_to_system(
+    q: f32,
+    units: IngredientMeasurement,
+    system: MeasurementSystem,
+) -> (f32, IngredientMeasurement) {
+    let Some(class) = system.class() else {
+        return (q, units);
+    };
+    if MeasurementClass::from(units) == class {
+        return (q, units);
+    }
+
+    let kind = MeasurementKind::from(units);
+    let small = smallest_units(&kind, &class);
+    readable_units(q * same_kind_factor(units, small), &kind, &class)
+}
+
+/// Like `convert_to_system`, but quantities without units are left alone.
+pub fn convert_quantity_to_system(
+    q: f32,
+    units: Option<IngredientMeasurement>,
+    system: MeasurementSystem,
+) -> (f32, Option<IngredientMeasurement>) {
+    match units {
+        Some(units) => {
+            let (q, units) = convert_to_system(q, units, system);
+            (q, Some(units))
+        }
+        None => (q, None),
+    }
+}
+
+/// Converts totals of a quantity by unit to the given measurement system, combining the ones
+/// which end up in the same unit.
+pub fn convert_totals_to_system(
+    totals: &BTreeMap<Option<IngredientMeasurement>, f32>,
+    system: MeasurementSystem,
+) -> BTreeMap<Option<IngredientMeasurement>, f32> {
+    let mut converted = BTreeMap::new();
+    for (units, q) in totals {
+        let (q, units) = convert_quantity_to_system(*q, *units, system);
+        *converted.entry(units).or_default() += q;
+    }
+    converted
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    Fahrenheit,
+    Celsius,
+}
+
+impl TemperatureUnit {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Fahrenheit => "°F",
+            Self::Celsius => "°C",
+        }
+    }
+}
+
+pub fn convert_temperature(degrees: f32, from: TemperatureUnit, to: TemperatureUnit) -> f32 {
+    match (from, to) {
+        (TemperatureUnit::Fahrenheit, TemperatureUnit::Celsius) => (degrees - 32.0) * 5.0 / 9.0,
+        (TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit) => degrees * 9.0 / 5.0 + 32.0,
+        _ => degrees,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FoundTemperature {
+    pub range: std::ops::Range<usize>,
+    pub degrees: f32,
+    pub units: TemperatureUnit,
+}
+
+/// Matches the units after the number of a temperature, returning how long they are.
+fn match_temperature_units(rest: &str) -> Option<(usize, TemperatureUnit)> {
+    const UNITS: &[(&str, TemperatureUnit)] = &[
+        (" degrees fahrenheit", TemperatureUnit::Fahrenheit),
+        (" degrees celsius", TemperatureUnit::Celsius),
+        (" degrees f", TemperatureUnit::Fahrenheit),
+        (" degrees c", TemperatureUnit::Celsius),
+        (" °f", TemperatureUnit::Fahrenheit),
+        (" °c", TemperatureUnit::Celsius),
+        ("° f", TemperatureUnit::Fahrenheit),
+        ("° c", TemperatureUnit::Celsius),
+        ("°f", TemperatureUnit::Fahrenheit),
+        ("°c", TemperatureUnit::Celsius),
+        // Without a degree sign, only count capital letters right after the number
+        ("F", TemperatureUnit::Fahrenheit),
+        ("C", TemperatureUnit::Celsius),
+    ];
+    UNITS.iter().find_map(|(u, units)| {
+        let candidate = rest.get(..u.len())?;
+        let matches = if u.contains(['°', ' ']) {
+            candidate.to_lowercase() == *u
+        } else {
+            candidate == *u
+        };
+        let ends_word = !rest[u.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric());
+        (matches && ends_word).then_some((u.len(), *units))
+    })
+}
+
+/// Finds temperatures like "350°F" or "180 degrees C" in some text.
+pub fn find_temperatures(text: &str) -> Vec<FoundTemperature> {
+    let mut found = vec![];
+    let mut start = 0;
+    while start < text.len() {
+        let rest = &text[start..];
+        let Some(offset) = rest.find(|c: char| c.is_ascii_digit()) else {
+            break;
+        };
+        let number_start = start + offset;
+        let number_len = text[number_start..]
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(text.len() - number_start);
+        let number_end = number_start + number_len;
+        let preceded_by_word = text[..number_start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric());
+
+        if let (Ok(degrees), false, Some((units_len, units))) = (
+            text[number_start..number_end].trim_end_matches('.').parse(),
+            preceded_by_word,
+            match_temperature_units(&text[number_end..]),
+        ) {
+            found.push(FoundTemperature {
+                range: number_start..(number_end + units_len),
+                degrees,
+                units,
+            });
+            start = number_end + units_len;
+        } else {
+            start = number_end;
+        }
+    }
+    found
+}
+
+/// Adds the temperature in the given measurement system after any temperatures in the text which
+/// aren't already in it, like "350°F (177°C)".
+pub fn with_temperature_conversions(text: &str, system: MeasurementSystem) -> String {
+    let to = match system {
+        MeasurementSystem::AsEntered => return text.into(),
+        MeasurementSystem::Us => TemperatureUnit::Fahrenheit,
+        MeasurementSystem::Metric => TemperatureUnit::Celsius,
+    };
+
+    let found = find_temperatures(text);
+    let mut converted = String::new();
+    let mut last = 0;
+    for (i, t) in found.iter().enumerate() {
+        converted += &text[last..t.range.end];
+        last = t.range.end;
+        if t.units == to {
+            continue;
+        }
+        // Leave it alone if the text already has the conversion right after it
+        let already_converted = found.get(i + 1).is_some_and(|next| {
+            next.units == to
+                && text[t.range.end..next.range.start]
+                    .chars()
+                    .all(|c| c.is_whitespace() || c == '(' || c == '/')
+        });
+        if !already_converted {
+            let degrees = convert_temperature(t.degrees, t.units, to);
+            converted += &format!(" ({}{})", degrees.round(), to.symbol());
+        }
+    }
+    converted += &text[last..];
+    converted
+}
+
+/// The value of a unicode fraction character, like ½.
+fn vulgar_fraction_value(c: char) -> Option<f32> {
+    let (n, d) = match c {
+        '¼' => (1, 4),
+        '½' => (1, 2),
+        '¾' => (3, 4),
+        '⅐' => (1, 7),
+        '⅑' => (1, 9),
+        '⅒' => (1, 10),
+        '⅓' => (1, 3),
+        '⅔' => (2, 3),
+        '⅕' => (1, 5),
+        '⅖' => (2, 5),
+        '⅗' => (3, 5),
+        '⅘' => (4, 5),
+        '⅙' => (1, 6),
+        '⅚' => (5, 6),
+        '⅛' => (1, 8),
+        '⅜' => (3, 8),
+        '⅝' => (5, 8),
+        '⅞' => (7, 8),
+        _ => return None,
+    };
+    Some(n as f32 / d as f32)
+}
+
+/// Parses a quantity like "2", "0.5", "1 1/2" or "1½". Anything else, including numbers which
+/// aren't finite, is `None`.
+pub fn quantity_parse(q: &str) -> Option<f32> {
+    use std::str::FromStr as _;
+
+    let parts: Vec<_> = q.split_whitespace().collect();
+    if parts.len() > 1 {
+        return parts
+            .into_iter()
+            .map(quantity_parse)
+            .sum::<Option<f32>>()
+            .filter(|v| v.is_finite());
+    }
+    let q = parts.first()?;
+
+    let value = if let Some(frac) = q.chars().last().and_then(vulgar_fraction_value) {
+        let whole = &q[..q.len() - q.chars().last()?.len_utf8()];
+        if whole.is_empty() {
+            frac
+        } else {
+            u32::from_str(whole).ok()? as f32 + frac
+        }
+    } else if q.contains("/") {
+        let parts: Vec<_> = q.split("/").collect();
+        if parts.len() != 2 {
+            return None;
+        }
+        let n = f32::from_str(parts[0]).ok()?;
+        let d = f32::from_str(parts[1]).ok()?;
+        n / d
+    } else {
+        q.parse().ok()?
+    };
+    value.is_finite().then_some(value)
+}
+
+/// Parses the units of a quantity, either abbreviated or spelled out.
+pub fn measurement_parse(s: &str) -> Option<IngredientMeasurement> {
+    use IngredientMeasurement::*;
+
+    let s = s.trim().to_lowercase();
+    let s = s.trim_end_matches('.');
+    let units = match s {
+        "c" | "cup" | "cups" => Cups,
+        "fl oz" | "fl. oz" | "floz" | "fluid ounce" | "fluid ounces" => FluidOunces,
+        "g" | "gram" | "grams" => Grams,
+        "kg" | "kilogram" | "kilograms" => Kilograms,
+        "kl" | "kiloliter" | "kiloliters" | "kilolitre" | "kilolitres" => Kiloliters,
+        "l" | "liter" | "liters" | "litre" | "litres" => Liters,
+        "mg" | "milligram" | "milligrams" => Milligrams,
+        "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Milliliters,
+        "oz" | "ounce" | "ounces" => Ounces,
+        "lb" | "lbs" | "pound" | "pounds" => Pounds,
+        "qt" | "quart" | "quarts" => Quart,
+        "tbsp" | "tbs" | "tablespoon" | "tablespoons" => Tablespoons,
+        "tsp" | "teaspoon" | "teaspoons" => Teaspoons,
+        _ => return None,
+    };
+    Some(units)
+}
+
+/// Parses a quantity which may be followed by its units, like "1 1/2 cups" or "500g".
+pub fn quantity_with_unit_parse(q: &str) -> Option<(f32, Option<IngredientMeasurement>)> {
+    let q = q.trim();
+    let number_end = q
+        .find(|c: char| {
+            !(c.is_ascii_digit()
+                || c.is_whitespace()
+                || c == '.'
+                || c == '/'
+                || vulgar_fraction_value(c).is_some())
+        })
+        .unwrap_or(q.len());
+    let (number, units) = q.split_at(number_end);
+    let quantity = quantity_parse(number.trim())?;
+    if units.is_empty() {
+        Some((quantity, None))
+    } else {
+        Some((quantity, Some(measurement_parse(units)?)))
+    }
+}
+
+#[test]
+fn unit_conversion_us() {
+    use IngredientMeasurement::*;
+    assert_eq!(conversion_factor(Cups, FluidOunces), Ok(8.0));
+    assert_eq!(conversion_factor(Cups, Tablespoons), Ok(16.0));
+    assert_eq!(conversion_factor(Cups, Teaspoons), Ok(48.0));
+
+    assert_eq!(conversion_factor(FluidOunces, Cups), Ok(1.0 / 8.0));
+    assert_eq!(conversion_factor(Tablespoons, Cups), Ok(1.0 / 16.0));
+    assert_eq!(conversion_factor(Teaspoons, Cups), Ok(1.0 / 48.0));
+
+    assert_eq!(conversion_factor(Tablespoons, FluidOunces), Ok(1.0 / 2.0));
+    assert_eq!(conversion_factor(Tablespoons, Teaspoons), Ok(3.0));
+
+    assert_eq!(conversion_factor(FluidOunces, Tablespoons), Ok(2.0));
+    assert_eq!(conversion_factor(Teaspoons, Tablespoons), Ok(1.0 / 3.0));
+
+    assert_eq!(conversion_factor(Teaspoons, FluidOunces), Ok(1.0 / 6.0));
+    assert_eq!(conversion_factor(FluidOunces, Teaspoons), Ok(6.0));
+
+    assert_eq!(conversion_factor(Pounds, Ounces), Ok(16.0));
+    assert_eq!(conversion_factor(Ounces, Pounds), Ok(1.0 / 16.0));
+}
+
+#[test]
+fn unit_conversion_metric() {
+    use IngredientMeasurement::*;
+
+    assert_eq!(conversion_factor(Liters, Milliliters), Ok(1_000.0));
+    assert_eq!(conversion_factor(Kiloliters, Milliliters), Ok(1_000_000.0));
+
+    assert_eq!(conversion_factor(Milliliters, Liters), Ok(1.0 / 1_000.0));
+    assert_eq!(
+        conversion_factor(Milliliters, Kiloliters),
+        Ok(1.0 / 1_000_000.0)
+    );
+
+    assert_eq!(conversion_factor(Kiloliters, Liters), Ok(1_000.0));
+    assert_eq!(conversion_factor(Liters, Kiloliters), Ok(1.0 / 1_000.0));
+
+    assert_eq!(conversion_factor(Grams, Milligrams), Ok(1_000.0));
+    assert_eq!(conversion_factor(Kilograms, Milligrams), Ok(1_000_000.0));
+
+    assert_eq!(conversion_factor(Milligrams, Grams), Ok(1.0 / 1_000.0));
+    assert_eq!(
+        conversion_factor(Milligrams, Kilograms),
+        Ok(1.0 / 1_000_000.0)
+    );
+
+    assert_eq!(conversion_factor(Kilograms, Grams), Ok(1_000.0));
+    assert_eq!(conversion_factor(Grams, Kilograms), Ok(1.0 / 1_000.0));
+}
+
+#[test]
+fn unit_conversion_us_metric() {
+    use IngredientMeasurement::*;
+
+    assert_eq!(conversion_factor(Liters, Teaspoons), Ok(202.88412));
+    assert_eq!(conversion_factor(Liters, Cups), Ok(4.2267528));
+    assert_eq!(conversion_factor(Kiloliters, Teaspoons), Ok(202884.13));
+
+    assert_eq!(conversion_factor(Ounces, Grams), Ok(28.34952));
+    assert_eq!(conversion_factor(Pounds, Grams), Ok(453.5924));
+}
+
+#[test]
+fn unit_conversion_across_kinds() {
+    use IngredientMeasurement::*;
+
+    let error = ConversionError {
+        from: Grams,
+        to: Cups,
+    };
+    assert_eq!(conversion_factor(Grams, Cups), Err(error));
+    assert_eq!(
+        error.to_string(),
+        "can't convert g to cups (weight vs volume, no density)"
+    );
+    assert_eq!(
+        conversion_factor_with_density(Grams, Cups, None),
+        Err(error)
+    );
+    assert_eq!(
+        conversion_factor_with_density(Grams, Cups, Some(0.0)),
+        Err(error)
+    );
+
+    // Water weighs a gram per milliliter.
+    assert_eq!(
+        conversion_factor_with_density(Liters, Kilograms, Some(1.0)),
+        Ok(1.0)
+    );
+    assert_eq!(
+        conversion_factor_with_density(Cups, Grams, Some(0.5)),
+        Ok(118.29412)
+    );
+    assert_eq!(
+        conversion_factor_with_density(Grams, Milliliters, Some(0.5)),
+        Ok(2.0)
+    );
+    assert_eq!(
+        conversion_factor_with_density(Cups, Tablespoons, Some(0.5)),
+        Ok(16.0)
+    );
+}
+
+#[test]
+fn convert_to_system_test() {
+    use IngredientMeasurement::*;
+    use MeasurementSystem::*;
+
+    assert_eq!(
+        convert_to_system(2.0, FluidOunces, AsEntered),
+        (2.0, FluidOunces)
+    );
+    assert_eq!(convert_to_system(2.0, FluidOunces, Us), (2.0, FluidOunces));
+    assert_eq!(convert_to_system(500.0, Grams, Metric), (500.0, Grams));
+
+    assert_eq!(convert_to_system(1.0, Liters, Us), (4.2267528, Cups));
+    assert_eq!(
+        convert_to_system(15.0, Milliliters, Us),
+        (1.0144207, Tablespoons)
+    );
+    assert_eq!(
+        convert_to_system(5.0, Milliliters, Us),
+        (1.0144207, Teaspoons)
+    );
+    assert_eq!(convert_to_system(1.0, Kilograms, Us), (2.204623, Pounds));
+    assert_eq!(convert_to_system(100.0, Grams, Us), (3.5273964, Ounces));
+
+    assert_eq!(
+        convert_to_system(2.0, FluidOunces, Metric),
+        (59.14707, Milliliters)
+    );
+    assert_eq!(convert_to_system(8.0, Cups, Metric), (1.892706, Liters));
+    assert_eq!(convert_to_system(2.0, Ounces, Metric), (56.69904, Grams));
+    assert_eq!(
+        convert_to_system(4.0, Pounds, Metric),
+        (1.8143697, Kilograms)
+    );
+}
+
+#[test]
+fn convert_totals_to_system_test() {
+    use maplit::btreemap;
+    use IngredientMeasurement::*;
+
+    assert_eq!(
+        convert_totals_to_system(
+            &btreemap! { Some(Grams) => 100.0, Some(Ounces) => 1.0, None => 2.0 },
+            MeasurementSystem::Metric
+        ),
+        btreemap! { Some(Grams) => 128.34952, None => 2.0 }
+    );
+}
+
+#[test]
+fn convert_temperature_test() {
+    use TemperatureUnit::*;
+
+    assert_eq!(convert_temperature(212.0, Fahrenheit, Celsius), 100.0);
+    assert_eq!(convert_temperature(32.0, Fahrenheit, Celsius), 0.0);
+    assert_eq!(convert_temperature(100.0, Celsius, Fahrenheit), 212.0);
+    assert_eq!(convert_temperature(180.0, Celsius, Fahrenheit), 356.0);
+    assert_eq!(convert_temperature(350.0, Fahrenheit, Fahrenheit), 350.0);
+}
+
+#[test]
+fn find_temperatures_test() {
+    use TemperatureUnit::*;
+
+    let found = |text| {
+        find_temperatures(text)
+            .into_iter()
+            .map(|t| (&text[t.range], t.degrees, t.units))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(found("Bake at 350°F."), vec![("350°F", 350.0, Fahrenheit)]);
+    assert_eq!(
+        found("Preheat to 200 °C, then 425 degrees F"),
+        vec![
+            ("200 °C", 200.0, Celsius),
+            ("425 degrees F", 425.0, Fahrenheit)
+        ]
+    );
+    assert_eq!(found("heat to 375F"), vec![("375F", 375.0, Fahrenheit)]);
+    assert_eq!(found("add 2 c. flour and 3 cups water"), vec![]);
+    assert_eq!(found("use 2 Fuji apples"), vec![]);
+    assert_eq!(found("a 9x13F pan"), vec![]);
+}
+
+#[test]
+fn with_temperature_conversions_test() {
+    use MeasurementSystem::*;
+
+    let text = "Bake at 350°F for 20 minutes.";
+    assert_eq!(with_temperature_conversions(text, AsEntered), text);
+    assert_eq!(with_temperature_conversions(text, Us), text);
+    assert_eq!(
+        with_temperature_conversions(text, Metric),
+        "Bake at 350°F (177°C) for 20 minutes."
+    );
+    assert_eq!(
+        with_temperature_conversions("Roast at 220 degrees C", Us),
+        "Roast at 220 degrees C (428°F)"
+    );
+    assert_eq!(
+        with_temperature_conversions("Bake at 350°F (180°C)", Metric),
+        "Bake at 350°F (180°C)"
+    );
+}
+
+#[test]
+fn best_unit_for_test() {
+    use IngredientMeasurement::*;
+
+    assert_eq!(best_unit_for(0.125, Cups), (2.0, Tablespoons));
+    assert_eq!(best_unit_for(1.5, Cups), (1.5, Cups));
+    assert_eq!(best_unit_for(24.0, Teaspoons), (8.0, Tablespoons));
+    assert_eq!(best_unit_for(0.25, Tablespoons), (0.75, Teaspoons));
+    assert_eq!(best_unit_for(32.0, Ounces), (2.0, Pounds));
+    assert_eq!(best_unit_for(1500.0, Grams), (1.5000001, Kilograms));
+    assert_eq!(best_unit_for(250.0, Milliliters), (250.0, Milliliters));
+    assert_eq!(best_unit_for(2.0, Quart), (8.0, Cups));
+}
+
+#[test]
+fn quantity_with_unit_parse_test() {
+    use IngredientMeasurement::*;
+
+    assert_eq!(quantity_with_unit_parse("2"), Some((2.0, None)));
+    assert_eq!(
+        quantity_with_unit_parse("1 1/2 cups"),
+        Some((1.5, Some(Cups)))
+    );
+    assert_eq!(quantity_with_unit_parse("500g"), Some((500.0, Some(Grams))));
+    assert_eq!(
+        quantity_with_unit_parse("2 Tablespoons"),
+        Some((2.0, Some(Tablespoons)))
+    );
+    assert_eq!(
+        quantity_with_unit_parse("1/4 tsp."),
+        Some((0.25, Some(Teaspoons)))
+    );
+    assert_eq!(
+        quantity_with_unit_parse("8 fl. oz."),
+        Some((8.0, Some(FluidOunces)))
+    );
+    assert_eq!(quantity_with_unit_parse("3 lbs"), Some((3.0, Some(Pounds))));
+    assert_eq!(quantity_with_unit_parse("0.5 L"), Some((0.5, Some(Liters))));
+    assert_eq!(quantity_with_unit_parse("2 pinches"), None);
+    assert_eq!(quantity_with_unit_parse("cups"), None);
+}
+
+#[test]
+fn quantity_parse_test() {
+    assert_eq!(quantity_parse("1/2").unwrap(), 0.5);
+    assert_eq!(quantity_parse("1 1/2").unwrap(), 1.5);
+    assert!((quantity_parse("1/3").unwrap() - 0.333).abs() < 0.001);
+    assert_eq!(quantity_parse("3").unwrap(), 3.0);
+    assert_eq!(quantity_parse("0.123").unwrap(), 0.123);
+    assert_eq!(quantity_parse(" 2  1/4 ").unwrap(), 2.25);
+    assert_eq!(quantity_parse("½").unwrap(), 0.5);
+    assert_eq!(quantity_parse("1½").unwrap(), 1.5);
+    assert_eq!(quantity_parse("2 ¾").unwrap(), 2.75);
+    assert!((quantity_parse("⅓").unwrap() - 0.333).abs() < 0.001);
+    assert_eq!(quantity_parse(""), None);
+    assert_eq!(quantity_parse("1 cup"), None);
+    assert_eq!(quantity_parse("1/0"), None);
+    assert_eq!(quantity_parse("NaN"), None);
+    assert_eq!(quantity_parse("½½"), None);
+    assert_eq!(quantity_parse("1.5½"), None);
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn quantity_parse_vulgar_fractions(
+        whole in proptest::option::of(0u32..100),
+        fraction in proptest::sample::select(vec!['¼', '½', '¾', '⅓', '⅔', '⅛', '⅜', '⅝', '⅞']),
+        space in proptest::bool::ANY,
+    ) {
+        let frac = vulgar_fraction_value(fraction).unwrap();
+        let (text, expected) = match whole {
+            Some(w) if space => (format!("{w} {fraction}"), w as f32 + frac),
+            Some(w) => (format!("{w}{fraction}"), w as f32 + frac),
+            None => (fraction.to_string(), frac),
+        };
+        proptest::prop_assert_eq!(quantity_parse(&text), Some(expected));
+        proptest::prop_assert_eq!(
+            quantity_with_unit_parse(&format!("{text} cups")),
+            Some((expected, Some(IngredientMeasurement::Cups)))
+        );
+    }
+
+    #[test]
+    fn quantity_parse_malformed(s in "\\PC*") {
+        // Never panics, and never comes up with something which isn't a number.
+        if let Some(v) = quantity_parse(&s) {
+            proptest::prop_assert!(v.is_finite(), "{s:?}");
+        }
+        if let Some((v, _)) = quantity_with_unit_parse(&s) {
+            proptest::prop_assert!(v.is_finite(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn quantity_parse_rejects_trailing_garbage(
+        v in 0u32..1000,
+        garbage in "[a-zA-Z%*#]{1,5}",
+    ) {
+        proptest::prop_assert_eq!(quantity_parse(&format!("{v} {garbage}")), None);
+        proptest::prop_assert_eq!(quantity_parse(&format!("{v}{garbage}")), None);
+    }
+}
@@ -0,0 +1,200 @@
+//! Exporting a recipe book as JSON and importing it into an empty database should give back the
+//! same recipe book, so neither side can drift from the other without this failing.
+
+use recipe_manager::database::{self, models::IngredientMeasurement::*};
+use recipe_manager::{json, query, recipe::total_calories};
+
+type RecipeRow = (
+    String,
+    String,
+    String,
+    String,
+    Option<i32>,
+    bool,
+    String,
+    Option<i32>,
+    f32,
+    Vec<(String, f32, Option<String>, String)>,
+);
+type IngredientRow = (
+    String,
+    bool,
+    i32,
+    i32,
+    Option<f32>,
+    Option<f32>,
+    Option<String>,
+    Vec<(f32, f32, Option<String>)>,
+);
+type CalendarRow = (chrono::NaiveDate, String, bool, Option<i32>, bool);
+
+/// Everything which is exported, without the ids which are allowed to change.
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    categories: Vec<(String, Option<String>)>,
+    recipes: Vec<RecipeRow>,
+    ingredients: Vec<IngredientRow>,
+    calendar: Vec<CalendarRow>,
+}
+
+fn snapshot(conn: &mut database::Connection) -> Snapshot {
+    let categories = query::get_recipe_categories(conn)
+        .unwrap()
+        .into_iter()
+        .map(|(c, _)| (c.name, c.default_duration.map(|d| d.to_string())))
+        .collect();
+
+    let mut recipes = vec![];
+    for recipe in query::get_all_recipes(conn).unwrap() {
+        let (_, category, ingredients) = query::get_recipe(conn, recipe.id).unwrap();
+        let usages = query::get_ingredients_for_recipe(conn, recipe.id)
+            .unwrap()
+            .into_iter()
+            .map(|(u, i)| {
+                let units = u.quantity_units.map(|u| u.as_str().to_owned());
+                (i.name, u.quantity, units, u.note)
+            })
+            .collect();
+        recipes.push((
+            category,
+            recipe.name,
+            recipe.description,
+            recipe.duration.to_string(),
+            recipe.rating,
+            recipe.archived,
+            recipe.status.to_string(),
+            recipe.servings,
+            total_calories(&ingredients),
+            usages,
+        ));
+    }
+    recipes.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+    let mut ingredients = vec![];
+    for ingredient in query::get_all_ingredients(conn).unwrap() {
+        let calories = query::get_ingredient_calories(conn, ingredient.id)
+            .unwrap()
+            .into_iter()
+            .map(|c| {
+                let units = c.quantity_units.map(|u| u.as_str().to_owned());
+                (c.calories, c.quantity, units)
+            })
+            .collect();
+        ingredients.push((
+            ingredient.name,
+            ingredient.staple,
+            ingredient.season,
+            ingredient.diet,
+            ingredient.density,
+            ingredient.price,
+            ingredient.price_units.map(|u| u.as_str().to_owned()),
+            calories,
+        ));
+    }
+    ingredients.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let calendar = query::get_all_calendar_entries(conn)
+        .unwrap()
+        .into_iter()
+        .map(|e| {
+            let (recipe, _, _) = query::get_recipe(conn, e.recipe_id).unwrap();
+            (e.day, recipe.name, e.cooked, e.servings, e.from_freezer)
+        })
+        .collect();
+
+    Snapshot {
+        categories,
+        recipes,
+        ingredients,
+        calendar,
+    }
+}
+
+fn day(d: u32) -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+}
+
+/// A recipe book using everything the export knows about. The recipes are added in a different
+/// order than the import adds them, so their ids don't line up.
+fn seed(conn: &mut database::Connection) {
+    use recipe_manager::database::models::{CalendarEntry, RecipeDuration, RecipeStatus};
+
+    let dinner = query::add_category(conn, "Dinner").unwrap();
+    let dessert = query::add_category(conn, "Dessert").unwrap();
+    query::edit_category_default_duration(conn, dessert, Some(RecipeDuration::Long)).unwrap();
+
+    let carrot = query::add_ingredient(conn, "carrot").unwrap().id;
+    query::add_ingredient_calories_entry(conn, carrot, 25.0, 1.0, None).unwrap();
+    query::add_ingredient_calories_entry(conn, carrot, 41.0, 100.0, Some(Grams)).unwrap();
+    query::set_ingredient_season(conn, carrot, 0b111000000111).unwrap();
+    let sugar = query::add_ingredient(conn, "sugar").unwrap().id;
+    query::add_ingredient_calories_entry(conn, sugar, 774.0, 1.0, Some(Cups)).unwrap();
+    query::set_ingredient_staple(conn, sugar, true).unwrap();
+    query::set_ingredient_density(conn, sugar, Some(0.85)).unwrap();
+    query::set_ingredient_price(conn, sugar, Some(2.5), Some(Kilograms)).unwrap();
+    query::set_ingredient_diet(conn, sugar, 3).unwrap();
+    let saffron = query::add_ingredient(conn, "saffron").unwrap().id;
+
+    let cake = query::add_recipe(conn, "Carrot Cake", dessert).unwrap();
+    query::edit_recipe_description(conn, cake, "Mix it all.\n\nBake for *45* minutes.").unwrap();
+    query::add_recipe_ingredient_with_units(conn, cake, carrot, 2.0, None, "grated").unwrap();
+    query::add_recipe_ingredient_with_units(conn, cake, sugar, 0.75, Some(Cups), "").unwrap();
+    query::edit_recipe_rating(conn, cake, Some(4)).unwrap();
+    query::edit_recipe_servings(conn, cake, Some(8)).unwrap();
+
+    let soup = query::add_recipe(conn, "Carrot Soup", dinner).unwrap();
+    query::add_recipe_ingredient_with_units(conn, soup, carrot, 400.0, Some(Grams), "").unwrap();
+    query::add_recipe_ingredient_with_units(conn, soup, saffron, 1.0, None, "a pinch").unwrap();
+
+    let stew = query::add_recipe(conn, "Old Stew", dinner).unwrap();
+    query::edit_recipe_duration(conn, stew, RecipeDuration::ReallyLong).unwrap();
+    query::edit_recipe_archived(conn, stew, true).unwrap();
+
+    let tart = query::add_recipe(conn, "Sugar Tart", dessert).unwrap();
+    query::edit_recipe_status(conn, tart, RecipeStatus::WantToTry).unwrap();
+
+    query::insert_or_update_calendar_entry(conn, day(1), soup).unwrap();
+    query::set_calendar_entry_cooked(conn, day(1), true).unwrap();
+    query::insert_or_update_calendar_entry(conn, day(2), stew).unwrap();
+    query::set_calendar_entry_servings(conn, day(2), Some(6)).unwrap();
+    query::insert_or_update_calendar_entry(conn, day(4), cake).unwrap();
+    let from_freezer = CalendarEntry {
+        day: day(5),
+        recipe_id: soup,
+        cooked: false,
+        servings: None,
+        from_freezer: true,
+    };
+    query::restore_calendar_entry(conn, &from_freezer).unwrap();
+}
+
+#[test]
+fn round_trip_test() {
+    let mut original = database::establish_connection(":memory:").unwrap();
+    seed(&mut original);
+    let exported = json::export(&mut original).unwrap();
+
+    let mut imported = database::establish_connection(":memory:").unwrap();
+    assert_eq!(json::import(&mut imported, &exported).unwrap(), 4);
+
+    let expected = snapshot(&mut original);
+    assert_eq!(expected.recipes.len(), 4);
+    assert_eq!(expected.ingredients.len(), 3);
+    assert_eq!(expected.calendar.len(), 4);
+    assert_eq!(snapshot(&mut imported), expected);
+
+    // And the second trip gives back exactly what the first one exported.
+    let mut again = database::establish_connection(":memory:").unwrap();
+    json::import(&mut again, &json::export(&mut imported).unwrap()).unwrap();
+    assert_eq!(snapshot(&mut again), expected);
+}
+
+#[test]
+fn round_trip_empty_test() {
+    let mut original = database::establish_connection(":memory:").unwrap();
+    let exported = json::export(&mut original).unwrap();
+
+    let mut imported = database::establish_connection(":memory:").unwrap();
+    assert_eq!(json::import(&mut imported, &exported).unwrap(), 0);
+    assert!(query::is_recipe_book_empty(&mut imported).unwrap());
+}